@@ -0,0 +1,50 @@
+//! 不依赖 Tauri 使用搜索引擎
+//!
+//! cargo run --example search --no-default-features -- 88 30
+//!
+//! 参数：靓号模式（默认 88）、最长运行时间（秒，默认 30）。找到第一个匹配后停止；
+//! 按 Enter 可以提前取消。
+
+use fancy_wallet_lib::{CancellationToken, SearchConfig, SearchEvent};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let pattern = args.next().unwrap_or_else(|| "88".to_string());
+    let max_duration_secs = args.next().and_then(|arg| arg.parse().ok()).unwrap_or(30);
+
+    let mut config = SearchConfig::new(pattern);
+    config.limits.max_matches = Some(1);
+    config.limits.max_duration_secs = Some(max_duration_secs);
+    if let Err(e) = config.validate() {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    }
+    println!("pattern {:?}, ~{:.0} attempts expected", config.pattern, config.expected_attempts());
+
+    // 取消令牌可以克隆到其它线程
+    let cancel = CancellationToken::new();
+    let stdin_cancel = cancel.clone();
+    std::thread::spawn(move || {
+        // 没有可读的终端时（输入已关闭）不取消
+        if let Ok(1..) = std::io::stdin().read_line(&mut String::new()) {
+            stdin_cancel.cancel();
+        }
+    });
+
+    let report = fancy_wallet_lib::run(&config, &cancel, |event| match event {
+        SearchEvent::Progress(progress) if !progress.matched && progress.attempts.is_multiple_of(100_000) => {
+            println!("{} attempts, {} ms", progress.attempts, progress.duration);
+        }
+        SearchEvent::Found(found) => println!("found {} after {} attempts", found.address, found.attempts),
+        SearchEvent::RngWarning(progress) => eprintln!("warning: {} rejected keys", progress.rejected_keys),
+        SearchEvent::WorkerFailed(failure) => eprintln!("worker {} panicked: {}", failure.worker, failure.message),
+        _ => {}
+    })
+    .expect("pattern was validated above");
+
+    match &report.stop {
+        Some(stop) => println!("stopped: {:?} ({})", stop.reason, stop.detail.as_deref().unwrap_or("")),
+        None => println!("cancelled"),
+    }
+    println!("{}", serde_json::to_string_pretty(&report).expect("report serializes"));
+}
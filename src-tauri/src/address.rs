@@ -0,0 +1,190 @@
+use secp256k1::PublicKey;
+#[cfg(feature = "gui")]
+use crate::hexutil::{Address, HexError, HexProblem};
+use crate::keccak::keccak256;
+
+/// 以太坊地址的十六进制字符数（不含 0x 前缀）
+#[cfg(feature = "gui")]
+const ADDRESS_HEX_LENGTH: usize = 40;
+
+/**
+ * 按文本自身的 Keccak256 哈希套用 EIP-55 大小写（不校验输入）
+ * 严格区分大小写
+ * 
+ * @param text - 小写的十六进制文本
+ * @returns 套用大小写后的文本
+ */
+fn apply_checksum_case(text: &str) -> String {
+    // 对文本进行Keccak256哈希
+    let hash = keccak256(text.as_bytes());
+    
+    // 构建checksum地址
+    let mut checksum = String::with_capacity(text.len());
+    for (i, char) in text.chars().enumerate() {
+        // 超过 64 个字符的输入没有对应的哈希位，保持原样
+        let Some(&byte) = hash.get(i / 2) else {
+            checksum.push(char);
+            continue;
+        };
+        let nibble = if i % 2 == 0 {
+            (byte >> 4) & 0xf
+        } else {
+            byte & 0xf
+        };
+        
+        // 如果nibble >= 8，则转换为大写
+        if nibble >= 8 {
+            checksum.push(char.to_ascii_uppercase());
+        } else {
+            checksum.push(char);
+        }
+    }
+    
+    checksum
+}
+
+/**
+ * 将以太坊地址转换为Checksum格式（EIP-55）
+ * 
+ * 输入必须恰好是 40 个十六进制字符（不含0x前缀和空白），大小写不限，先统一为小写再计算哈希；
+ * 长度或字符不对时返回错误，而不是对任意文本计算出没有意义的大小写。
+ * 
+ * @param address - 以太坊地址（不含0x前缀）
+ * @returns Checksum格式的地址（不含0x前缀）
+ */
+#[cfg(feature = "gui")]
+pub fn to_checksum_address(address: &str) -> Result<String, HexError> {
+    let error = |problem| HexError { name: "地址", problem };
+    if address.is_empty() {
+        return Err(error(HexProblem::Empty));
+    }
+    if let Some((i, character)) = address.chars().enumerate().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        return Err(error(HexProblem::InvalidCharacter { position: i + 1, character }));
+    }
+    if address.len() != ADDRESS_HEX_LENGTH {
+        return Err(error(HexProblem::WrongLength { expected: &[ADDRESS_HEX_LENGTH], actual: address.len() }));
+    }
+    Ok(apply_checksum_case(&address.to_ascii_lowercase()))
+}
+
+/**
+ * 将用户输入的地址转换为Checksum格式：允许首尾空白和 0x/0X 前缀，大小写不限
+ * 
+ * @param input - 用户输入的地址
+ * @returns 带0x前缀的Checksum格式地址
+ */
+#[cfg(feature = "gui")]
+pub fn to_checksum_address_checked(input: &str) -> Result<String, HexError> {
+    Ok(Address::parse(input)?.to_checksum())
+}
+
+/**
+ * 检查用户输入的地址的大小写是否与 EIP-55 checksum 一致：允许首尾空白和 0x/0X 前缀
+ * 
+ * 全小写或全大写的地址只有在 checksum 恰好也是全小写或全大写时才算一致（旧的小写记录需要先转换）。
+ * 
+ * @param input - 用户输入的地址
+ * @returns 大小写是否一致；不是 40 个十六进制字符时返回错误
+ */
+#[cfg(feature = "gui")]
+pub fn has_valid_checksum(input: &str) -> Result<bool, HexError> {
+    let checksum = Address::parse(input)?.to_checksum();
+    let trimmed = input.trim();
+    let digits = trimmed.strip_prefix("0x").or_else(|| trimmed.strip_prefix("0X")).unwrap_or(trimmed);
+    Ok(digits == &checksum[2..])
+}
+
+/**
+ * 由地址的 20 个字节计算Checksum格式（不含0x前缀）
+ */
+pub fn checksum_from_bytes(bytes: &[u8; 20]) -> String {
+    // 编码结果必然是 40 个小写十六进制字符，不需要再校验
+    apply_checksum_case(&hex::encode(bytes))
+}
+
+/**
+ * 从公钥计算以太坊地址的 20 个字节（生成循环先按字节比较，不分配字符串）
+ * 
+ * @param public_key - secp256k1 公钥
+ * @returns 未压缩公钥 Keccak-256 的后 20 字节
+ */
+pub fn public_key_to_bytes(public_key: &PublicKey) -> [u8; 20] {
+    let public_key_bytes = public_key.serialize_uncompressed();
+    let hash = keccak256(&public_key_bytes[1..]); // 去掉0x04前缀
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    address
+}
+
+/**
+ * 从公钥计算以太坊地址
+ * 
+ * @param public_key - secp256k1 公钥
+ * @returns 小写的以太坊地址（不含0x前缀）
+ */
+pub fn public_key_to_address(public_key: &PublicKey) -> String {
+    hex::encode(public_key_to_bytes(public_key))
+}
+
+/**
+ * 从公钥计算Checksum格式的以太坊地址（生成循环中使用）
+ * 
+ * @param public_key - secp256k1 公钥
+ * @returns Checksum格式的地址（不含0x前缀）
+ */
+pub fn public_key_to_checksum_address(public_key: &PublicKey) -> String {
+    checksum_from_bytes(&public_key_to_bytes(public_key))
+}
+
+#[cfg(all(test, feature = "gui"))]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+    use rand::Rng;
+    use sha2::Digest;
+
+    /// checksum 转换的随机用例数
+    const CHECKSUM_PROPERTY_CASES: usize = 2000;
+
+    /// 构造非法地址时替换进去的字符
+    const NON_HEX_CHARACTERS: &[u8] = b"gGxXzZ -_/*.";
+
+    /**
+     * checksum 转换的性质：随机大小写的 40 位十六进制输入得到符合 EIP-55 的结果（每一位的大小写按独立的 Keccak256 复算），
+     * 截断、含非十六进制字符或带 0x 前缀的输入一律返回错误，带 0x 前缀和空白的输入只被 to_checksum_address_checked 接受
+     */
+    #[test]
+    fn checksum_properties() -> Result<(), String> {
+        const HEX: &[u8] = b"0123456789abcdefABCDEF";
+        let mut rng = OsRng;
+        for _ in 0..CHECKSUM_PROPERTY_CASES {
+            let input: String = (0..40).map(|_| HEX[rng.gen_range(0..HEX.len())] as char).collect();
+            let lower = input.to_ascii_lowercase();
+            let checksum = to_checksum_address(&input)?;
+            let hash = sha3::Keccak256::digest(lower.as_bytes());
+            let conforms = checksum.len() == 40
+                && checksum.chars().zip(lower.chars()).enumerate().all(|(i, (actual, original))| {
+                    let nibble = if i % 2 == 0 { hash[i / 2] >> 4 } else { hash[i / 2] & 0xf };
+                    actual == if nibble >= 8 { original.to_ascii_uppercase() } else { original }
+                });
+            if !conforms {
+                return Err(format!("{} 的 checksum 结果 {} 不符合 EIP-55", input, checksum));
+            }
+            if Address::parse_canonical(&format!("0x{}", checksum)).is_err() {
+                return Err(format!("checksum 结果 {} 不是规范格式", checksum));
+            }
+            if to_checksum_address_checked(&format!(" 0x{} ", input))? != format!("0x{}", checksum) {
+                return Err(format!("带 0x 前缀的 {} 转换结果不一致", input));
+            }
+            
+            let mut invalid = vec![input[..rng.gen_range(0..40)].to_string(), format!("{}{}", input, HEX[rng.gen_range(0..HEX.len())] as char), format!("0x{}", &input[2..])];
+            let mut replaced = input.clone().into_bytes();
+            replaced[rng.gen_range(0..40)] = NON_HEX_CHARACTERS[rng.gen_range(0..NON_HEX_CHARACTERS.len())];
+            invalid.push(String::from_utf8(replaced).map_err(|e| e.to_string())?);
+            if let Some(accepted) = invalid.iter().find(|text| to_checksum_address(text).is_ok()) {
+                return Err(format!("非法输入 {:?} 没有被拒绝", accepted));
+            }
+        }
+        Ok(())
+    }
+}
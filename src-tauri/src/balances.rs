@@ -0,0 +1,585 @@
+use crate::address::has_valid_checksum;
+use crate::hexutil::Address;
+use crate::retry::RetryPolicy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 一次最多查询的地址数
+pub const MAX_ADDRESSES: usize = 10_000;
+
+/// 每个 JSON-RPC 批量请求中的 eth_getBalance 调用数
+pub const BATCH_SIZE: usize = 20;
+
+/// 两次请求之间的最短间隔（每秒最多 4 个批量请求，避免被免费的公共节点限流或封禁）
+pub const MIN_REQUEST_INTERVAL: Duration = Duration::from_millis(250);
+
+/// 单个请求的超时时间
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 请求失败（网络错误、HTTP 429 或 5xx）后的重试策略：最多 4 次，第一次重试前等待 1 秒，之后每次翻倍
+pub const RPC_RETRY: RetryPolicy = RetryPolicy { attempts: 4, base_delay: Duration::from_secs(1) };
+
+/// 节点通过 Retry-After 要求的等待时间上限
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// 检查新地址的链上记录（verify_unused）时单个请求的超时时间；超时记为未知，不重试
+pub const ACTIVITY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 余额查询结果（只读：请求中只有地址，从不发送私钥、签名或交易）
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct BalanceReport {
+    /// 地址（与输入相同，去掉首尾空白）→ 最新区块的余额（wei，十进制字符串）
+    pub balances: BTreeMap<String, String>,
+    /// 无法查询的地址及原因（不影响其它地址）
+    pub errors: Vec<BalanceError>,
+    /// 余额不为零的地址数
+    pub funded: usize,
+    /// 发送的 HTTP 请求数（含重试）
+    pub requests: u32,
+}
+
+/// 无法查询余额的一个地址（错误信息中只有地址，不含私钥）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct BalanceError {
+    /// 输入的地址
+    pub address: String,
+    /// 原因
+    pub message: String,
+}
+
+/// 新生成的地址在链上的记录（verify_unused 的查询结果）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ChainActivity {
+    /// 地址已发送的交易数（nonce，十进制字符串）
+    pub transaction_count: String,
+    /// 最新区块的余额（wei，十进制字符串）
+    pub balance: String,
+}
+
+impl ChainActivity {
+    /**
+     * 地址是否已有链上记录（交易数或余额不为零）
+     */
+    pub fn is_active(&self) -> bool {
+        self.transaction_count != "0" || self.balance != "0"
+    }
+}
+
+/// 按最短间隔发送请求
+#[derive(Debug, Clone)]
+pub struct RequestPacer {
+    interval: Duration,
+    /// 上一次请求的时刻
+    last: Option<Instant>,
+}
+
+impl RequestPacer {
+    /**
+     * 创建请求间隔的限制
+     *
+     * @param interval - 两次请求之间的最短间隔
+     */
+    pub fn new(interval: Duration) -> Self {
+        RequestPacer { interval, last: None }
+    }
+
+    /**
+     * 下一次请求前还需要等待的时间；记为在等待之后发送
+     *
+     * @param now - 当前时刻（测试中由模拟时钟提供）
+     */
+    pub fn next_delay(&mut self, now: Instant) -> Duration {
+        let send_at = self.last.map_or(now, |last| (last + self.interval).max(now));
+        self.last = Some(send_at);
+        send_at - now
+    }
+}
+
+/**
+ * 校验 RPC 地址：只接受 http 和 https
+ */
+pub fn validate_rpc_url(rpc_url: &str) -> Result<reqwest::Url, String> {
+    let url = reqwest::Url::parse(rpc_url.trim()).map_err(|e| format!("RPC 地址无效: {}", e))?;
+    match url.scheme() {
+        "http" | "https" => Ok(url),
+        other => Err(format!("RPC 地址只能使用 http 或 https，不支持 {}", other)),
+    }
+}
+
+/**
+ * 解析要查询的地址：无效的地址单独报告，重复的地址只查询一次
+ *
+ * @param addresses - 输入的地址
+ * @returns (每个地址的输入文本和解析结果, 无效的地址)
+ */
+pub fn parse_addresses(addresses: &[String]) -> (Vec<(String, Address)>, Vec<BalanceError>) {
+    let mut valid: Vec<(String, Address)> = Vec::new();
+    let mut errors = Vec::new();
+    for input in addresses {
+        let text = input.trim();
+        if valid.iter().any(|(seen, _)| seen == text) {
+            continue;
+        }
+        let error = |message: String| BalanceError { address: text.to_string(), message };
+        let address = match Address::parse(text) {
+            Ok(address) => address,
+            Err(e) => {
+                errors.push(error(format!("不是以太坊地址（eth_getBalance 只能查询以太坊地址）: {}", e)));
+                continue;
+            }
+        };
+        // 大小写混合的地址按 EIP-55 校验，抄错的地址不会查询到另一个地址的余额
+        let digits = text.trim_start_matches("0x").trim_start_matches("0X");
+        let mixed_case = digits.chars().any(|c| c.is_ascii_uppercase()) && digits.chars().any(|c| c.is_ascii_lowercase());
+        if mixed_case && !has_valid_checksum(text).unwrap_or(false) {
+            errors.push(error(format!("checksum 不正确（可能抄错），应为 {}", address.to_checksum())));
+            continue;
+        }
+        valid.push((text.to_string(), address));
+    }
+    (valid, errors)
+}
+
+/**
+ * 一个批量请求的内容：每个地址一个 eth_getBalance 调用，id 为地址在本批中的位置
+ */
+pub fn batch_request(chunk: &[(String, Address)]) -> Value {
+    let calls: Vec<Value> = chunk
+        .iter()
+        .enumerate()
+        .map(|(id, (_, address))| json!({ "jsonrpc": "2.0", "id": id, "method": "eth_getBalance", "params": [address.to_checksum(), "latest"] }))
+        .collect();
+    Value::Array(calls)
+}
+
+/**
+ * 检查一个地址的链上记录的批量请求：id 0 为 eth_getTransactionCount，id 1 为 eth_getBalance
+ */
+pub fn activity_request(address: &Address) -> Value {
+    let address = address.to_checksum();
+    json!([
+        { "jsonrpc": "2.0", "id": 0, "method": "eth_getTransactionCount", "params": [address, "latest"] },
+        { "jsonrpc": "2.0", "id": 1, "method": "eth_getBalance", "params": [address, "latest"] },
+    ])
+}
+
+/**
+ * 解析 activity_request 的响应；任何一个调用失败时整个结果未知
+ */
+pub fn parse_activity_response(response: &Value) -> Result<ChainActivity, String> {
+    let mut results = batch_results(2, response).into_iter();
+    let transaction_count = results.next().ok_or("响应中没有交易数")??;
+    let balance = results.next().ok_or("响应中没有余额")??;
+    Ok(ChainActivity { transaction_count, balance })
+}
+
+/**
+ * 把 JSON-RPC 的十六进制数量（0x 开头，最多 256 位）转换为十进制字符串
+ */
+pub fn quantity_to_decimal(quantity: &str) -> Result<String, String> {
+    let digits = quantity.strip_prefix("0x").ok_or_else(|| format!("余额不是 0x 开头的十六进制数: {}", quantity))?;
+    if digits.is_empty() || digits.len() > 64 {
+        return Err(format!("余额的长度不正确: {}", quantity));
+    }
+    // 以 10^9 为基数的小端序大整数
+    const BASE: u64 = 1_000_000_000;
+    let mut limbs: Vec<u64> = vec![0];
+    for c in digits.chars() {
+        let mut carry = c.to_digit(16).ok_or_else(|| format!("余额不是十六进制数: {}", quantity))? as u64;
+        for limb in limbs.iter_mut() {
+            let value = *limb * 16 + carry;
+            *limb = value % BASE;
+            carry = value / BASE;
+        }
+        if carry > 0 {
+            limbs.push(carry);
+        }
+    }
+    let mut text = limbs.last().copied().unwrap_or_default().to_string();
+    for limb in limbs.iter().rev().skip(1) {
+        text.push_str(&format!("{:09}", limb));
+    }
+    Ok(text)
+}
+
+/**
+ * 解析批量请求的响应，按 id 对应到地址；缺少结果或返回错误的地址单独报告
+ *
+ * @param chunk - 本批查询的地址
+ * @param response - 响应的 JSON
+ * @returns 每个地址的余额（wei，十进制）或错误信息，顺序与 chunk 相同
+ */
+pub fn parse_batch_response(chunk: &[(String, Address)], response: &Value) -> Vec<Result<String, String>> {
+    batch_results(chunk.len(), response)
+}
+
+/**
+ * 按 id（0 到 count - 1）取出批量请求中每个调用的结果，并转换为十进制
+ */
+fn batch_results(count: usize, response: &Value) -> Vec<Result<String, String>> {
+    let items = match response {
+        Value::Array(items) => items,
+        // 不支持批量请求的节点通常只返回一个错误
+        other => {
+            let message = match other.get("error") {
+                Some(error) => rpc_error(error),
+                None => "响应不是批量请求的结果".to_string(),
+            };
+            return (0..count).map(|_| Err(message.clone())).collect();
+        }
+    };
+    let by_id: HashMap<u64, &Value> = items.iter().filter_map(|item| Some((item.get("id")?.as_u64()?, item))).collect();
+    (0..count as u64)
+        .map(|id| {
+            let item = by_id.get(&id).ok_or("响应中没有这个地址的结果")?;
+            if let Some(error) = item.get("error") {
+                return Err(rpc_error(error));
+            }
+            let quantity = item.get("result").and_then(Value::as_str).ok_or("响应中没有余额")?;
+            quantity_to_decimal(quantity)
+        })
+        .collect()
+}
+
+/**
+ * JSON-RPC 错误对象的说明
+ */
+fn rpc_error(error: &Value) -> String {
+    match (error.get("code").and_then(Value::as_i64), error.get("message").and_then(Value::as_str)) {
+        (Some(code), Some(message)) => format!("节点返回错误 {}: {}", code, message),
+        (None, Some(message)) => format!("节点返回错误: {}", message),
+        _ => format!("节点返回错误: {}", error),
+    }
+}
+
+/// 一次请求失败的原因
+enum RequestFailure {
+    /// 稍后重试可能成功（网络错误、超时、HTTP 429 或 5xx）；节点给出 Retry-After 时按它等待
+    Transient(String, Option<Duration>),
+    /// 重试也不会成功
+    Permanent(String),
+}
+
+/**
+ * 发送一个批量请求
+ */
+async fn send_batch(client: &reqwest::Client, url: &reqwest::Url, body: &Value) -> Result<Value, RequestFailure> {
+    let response = client
+        .post(url.clone())
+        .json(body)
+        .send()
+        .await
+        .map_err(|e| RequestFailure::Transient(format!("无法连接 RPC 节点: {}", e), None))?;
+    let status = response.status();
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok()?.trim().parse::<u64>().ok())
+            .map(|secs| Duration::from_secs(secs).min(MAX_RETRY_AFTER));
+        return Err(RequestFailure::Transient(format!("RPC 节点返回 HTTP {}", status), retry_after));
+    }
+    if !status.is_success() {
+        return Err(RequestFailure::Permanent(format!("RPC 节点返回 HTTP {}", status)));
+    }
+    response.json().await.map_err(|e| RequestFailure::Permanent(format!("RPC 节点的响应不是 JSON: {}", e)))
+}
+
+/**
+ * 查询地址的余额：每批 BATCH_SIZE 个地址，请求之间至少间隔 MIN_REQUEST_INTERVAL，失败的请求按 RPC_RETRY 重试
+ *
+ * 只调用 eth_getBalance，请求中只有地址；一批重试用尽后这一批的地址单独报告错误，其它批照常查询。
+ *
+ * @param addresses - 以太坊地址
+ * @param rpc_url - JSON-RPC 节点地址（http 或 https，由用户提供）
+ */
+pub async fn check(addresses: &[String], rpc_url: &str) -> Result<BalanceReport, String> {
+    let url = validate_rpc_url(rpc_url)?;
+    if addresses.is_empty() {
+        return Err("没有要查询的地址".to_string());
+    }
+    if addresses.len() > MAX_ADDRESSES {
+        return Err(format!("一次最多查询 {} 个地址", MAX_ADDRESSES));
+    }
+    let client = reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build().map_err(|e| format!("无法创建 HTTP 客户端: {}", e))?;
+    let (valid, errors) = parse_addresses(addresses);
+    let mut report = BalanceReport { errors, ..BalanceReport::default() };
+    let mut pacer = RequestPacer::new(MIN_REQUEST_INTERVAL);
+    for chunk in valid.chunks(BATCH_SIZE) {
+        let body = batch_request(chunk);
+        let mut retries = 0;
+        let response = loop {
+            tokio::time::sleep(pacer.next_delay(Instant::now())).await;
+            report.requests += 1;
+            match send_batch(&client, &url, &body).await {
+                Ok(response) => break Ok(response),
+                Err(RequestFailure::Transient(message, retry_after)) if retries + 1 < RPC_RETRY.attempts => {
+                    retries += 1;
+                    eprintln!("{}，{} 秒后重试", message, RPC_RETRY.delay(retries).as_secs());
+                    tokio::time::sleep(retry_after.unwrap_or(RPC_RETRY.delay(retries))).await;
+                }
+                Err(RequestFailure::Transient(message, _)) => break Err(format!("{}（已重试 {} 次）", message, retries)),
+                Err(RequestFailure::Permanent(message)) => break Err(message),
+            }
+        };
+        let results = match response {
+            Ok(response) => parse_batch_response(chunk, &response),
+            Err(message) => chunk.iter().map(|_| Err(message.clone())).collect(),
+        };
+        for ((input, _), result) in chunk.iter().zip(results) {
+            match result {
+                Ok(balance) => {
+                    report.funded += usize::from(balance != "0");
+                    report.balances.insert(input.clone(), balance);
+                }
+                Err(message) => report.errors.push(BalanceError { address: input.clone(), message }),
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// 找到匹配后在后台检查新地址的链上记录（verify_unused），不阻塞生成；结果按完成的顺序取回
+///
+/// 只发送 eth_getTransactionCount 和 eth_getBalance，请求中只有地址。每个地址只请求一次（不重试），
+/// 网络错误、超时或节点返回错误时结果为错误（链上记录未知）。
+pub struct UnusedCheck<T> {
+    /// 待检查的地址（后台任务按顺序处理，两次请求之间至少间隔 MIN_REQUEST_INTERVAL）
+    requests: Option<tokio::sync::mpsc::UnboundedSender<(Address, T)>>,
+    /// 后台任务无法接收时直接返回错误
+    failed: std::sync::mpsc::Sender<(T, Result<ChainActivity, String>)>,
+    results: std::sync::mpsc::Receiver<(T, Result<ChainActivity, String>)>,
+    /// 结束时不再发送请求，剩余的地址直接记为未知
+    abandoned: Arc<AtomicBool>,
+    /// 已提交、尚未取回结果的地址数
+    pending: usize,
+}
+
+impl<T: Send + 'static> UnusedCheck<T> {
+    /**
+     * 校验 RPC 地址并启动后台检查任务
+     *
+     * @param rpc_url - JSON-RPC 节点地址（http 或 https）
+     */
+    pub fn start(rpc_url: &str) -> Result<Self, String> {
+        let url = validate_rpc_url(rpc_url)?;
+        let client = reqwest::Client::builder().timeout(ACTIVITY_TIMEOUT).build().map_err(|e| format!("无法创建 HTTP 客户端: {}", e))?;
+        let (requests, mut queue) = tokio::sync::mpsc::unbounded_channel::<(Address, T)>();
+        let (sender, results) = std::sync::mpsc::channel();
+        let abandoned = Arc::new(AtomicBool::new(false));
+        let failed = sender.clone();
+        let stop = abandoned.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut pacer = RequestPacer::new(MIN_REQUEST_INTERVAL);
+            while let Some((address, payload)) = queue.recv().await {
+                let result = if stop.load(Ordering::SeqCst) {
+                    Err("会话已结束，没有完成检查".to_string())
+                } else {
+                    tokio::time::sleep(pacer.next_delay(Instant::now())).await;
+                    check_activity(&client, &url, &address).await
+                };
+                if sender.send((payload, result)).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(UnusedCheck { requests: Some(requests), failed, results, abandoned, pending: 0 })
+    }
+
+    /**
+     * 提交一个新地址
+     *
+     * @param address - 找到的地址
+     * @param payload - 取回结果时一起返回的数据
+     */
+    pub fn submit(&mut self, address: Address, payload: T) {
+        self.pending += 1;
+        let sent = self.requests.as_ref().map(|requests| requests.send((address, payload)));
+        if let Some(Err(tokio::sync::mpsc::error::SendError((_, payload)))) = sent {
+            let _ = self.failed.send((payload, Err("检查任务已停止".to_string())));
+        }
+    }
+
+    /**
+     * 取回已经完成的检查（不等待）
+     */
+    pub fn completed(&mut self) -> Vec<(T, Result<ChainActivity, String>)> {
+        let done: Vec<_> = self.results.try_iter().collect();
+        self.pending -= done.len();
+        done
+    }
+
+    /**
+     * 会话结束时取回全部剩余的检查：最多等待 wait，之后剩余的地址不再请求，直接记为未知
+     *
+     * @param wait - 等待进行中的检查完成的时间
+     */
+    pub fn finish(mut self, wait: Duration) -> Vec<(T, Result<ChainActivity, String>)> {
+        // 关闭队列，后台任务处理完已提交的地址后退出
+        self.requests = None;
+        let deadline = Instant::now() + wait;
+        let mut done = Vec::new();
+        while self.pending > 0 {
+            let timeout = match deadline.checked_duration_since(Instant::now()) {
+                Some(left) if !self.abandoned.load(Ordering::SeqCst) => left,
+                _ => {
+                    // 正在进行的请求最多再等一个超时时间
+                    self.abandoned.store(true, Ordering::SeqCst);
+                    ACTIVITY_TIMEOUT
+                }
+            };
+            match self.results.recv_timeout(timeout) {
+                Ok(result) => {
+                    self.pending -= 1;
+                    done.push(result);
+                }
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) if !self.abandoned.load(Ordering::SeqCst) => self.abandoned.store(true, Ordering::SeqCst),
+                Err(_) => break,
+            }
+        }
+        done
+    }
+}
+
+/**
+ * 查询一个地址的交易数和余额（一次批量请求，不重试）
+ */
+async fn check_activity(client: &reqwest::Client, url: &reqwest::Url, address: &Address) -> Result<ChainActivity, String> {
+    match send_batch(client, url, &activity_request(address)).await {
+        Ok(response) => parse_activity_response(&response),
+        Err(RequestFailure::Transient(message, _) | RequestFailure::Permanent(message)) => Err(message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::templates::GenerationParams;
+    use crate::test_support::TRON_ADDRESS;
+    use crate::self_test::KNOWN_KEYS;
+
+    /**
+     * 余额查询（不连接网络）：批量请求只有地址，响应按 id 对应，无效的地址和失败的调用单独报告，256 位余额转换为十进制
+     */
+    #[test]
+    fn balance_lookup() -> Result<(), String> {
+        let (key, checksum) = KNOWN_KEYS[0];
+        let (_, other_checksum) = KNOWN_KEYS[1];
+        let address = format!("0x{}", checksum);
+        let other = format!("0x{}", other_checksum);
+        for (quantity, expected) in [
+            ("0x0", "0"),
+            ("0xde0b6b3a7640000", "1000000000000000000"),
+            ("0x3b9aca00", "1000000000"),
+            ("0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", "115792089237316195423570985008687907853269984665640564039457584007913129639935"),
+        ] {
+            let decimal = quantity_to_decimal(quantity)?;
+            if decimal != expected {
+                return Err(format!("{} 转换为 {}，应为 {}", quantity, decimal, expected));
+            }
+        }
+        for quantity in ["", "0x", "123", "0xzz", &format!("0x1{}", "0".repeat(64))] {
+            if quantity_to_decimal(quantity).is_ok() {
+                return Err(format!("无效的余额 {:?} 没有被拒绝", quantity));
+            }
+        }
+        
+        // 无效的地址单独报告，重复的地址只查询一次；大小写混合的地址校验 checksum
+        let flipped = address.char_indices().skip(2).find(|(_, c)| c.is_ascii_alphabetic()).map(|(i, _)| i).ok_or("地址中没有字母")?;
+        let wrong_case: String = address
+            .char_indices()
+            .map(|(i, c)| if i != flipped { c } else if c.is_ascii_uppercase() { c.to_ascii_lowercase() } else { c.to_ascii_uppercase() })
+            .collect();
+        let inputs: Vec<String> = [address.as_str(), &format!(" {} ", address.to_lowercase()), &other, TRON_ADDRESS, "0x1234", &wrong_case, &address].iter().map(|s| s.to_string()).collect();
+        let (valid, errors) = parse_addresses(&inputs);
+        let invalid: Vec<&str> = errors.iter().map(|e| e.address.as_str()).collect();
+        if valid.len() != 3 || invalid != [TRON_ADDRESS, "0x1234", wrong_case.as_str()] {
+            return Err(format!("有效的地址 {:?}，无效的地址 {:?}", valid.iter().map(|(input, _)| input).collect::<Vec<_>>(), invalid));
+        }
+        
+        // 请求中只有地址和 latest
+        let request = batch_request(&valid);
+        let text = request.to_string();
+        if request.as_array().map(Vec::len) != Some(3) || text.contains(key) || !text.contains(&address) || !text.contains("\"eth_getBalance\"") || !text.contains("\"latest\"") {
+            return Err(format!("批量请求不正确: {}", text));
+        }
+        
+        // 响应顺序被打乱、一个调用返回错误、一个调用缺少结果
+        let response = serde_json::json!([
+            { "jsonrpc": "2.0", "id": 2, "error": { "code": -32000, "message": "header not found" } },
+            { "jsonrpc": "2.0", "id": 0, "result": "0xde0b6b3a7640000" },
+        ]);
+        let results = parse_batch_response(&valid, &response);
+        let ok = |i: usize| results.get(i).and_then(|r| r.as_ref().ok()).cloned();
+        if results.len() != 3 || ok(0).as_deref() != Some("1000000000000000000") || ok(1).is_some() || !results[2].as_ref().is_err_and(|e| e.contains("header not found")) {
+            return Err(format!("批量响应的解析结果为 {:?}", results));
+        }
+        let rejected = parse_batch_response(&valid, &serde_json::json!({ "jsonrpc": "2.0", "id": null, "error": { "code": -32600, "message": "batch not supported" } }));
+        if rejected.len() != 3 || rejected.iter().any(|r| !r.as_ref().is_err_and(|e| e.contains("batch not supported"))) {
+            return Err(format!("不支持批量请求时的结果为 {:?}", rejected));
+        }
+        
+        for url in ["ftp://example.com", "file:///etc/passwd", "localhost:8545", ""] {
+            if validate_rpc_url(url).is_ok() {
+                return Err(format!("RPC 地址 {:?} 没有被拒绝", url));
+            }
+        }
+        validate_rpc_url("http://127.0.0.1:8545")?;
+        
+        // 请求之间至少间隔 MIN_REQUEST_INTERVAL
+        let interval = MIN_REQUEST_INTERVAL;
+        let start = Instant::now();
+        let mut pacer = RequestPacer::new(interval);
+        let delays = [pacer.next_delay(start), pacer.next_delay(start), pacer.next_delay(start + interval / 2), pacer.next_delay(start + interval * 10)];
+        if delays != [Duration::ZERO, interval, interval * 3 / 2, Duration::ZERO] {
+            return Err(format!("请求间隔为 {:?}", delays));
+        }
+        if RPC_RETRY.delay(1) != RPC_RETRY.base_delay || RPC_RETRY.delay(3) != RPC_RETRY.base_delay * 4 {
+            return Err("重试的退避时间不正确".to_string());
+        }
+        Ok(())
+    }
+
+    /**
+     * verify_unused（不连接网络）：请求只有地址，交易数或余额不为零时算作已有记录，任何一个调用失败时记录未知
+     */
+    #[test]
+    fn unused_verification() -> Result<(), String> {
+        let (key, checksum) = KNOWN_KEYS[0];
+        let address = Address::parse(checksum)?;
+        let request = activity_request(&address).to_string();
+        if request.contains(key) || !request.contains(&address.to_checksum()) || !request.contains("eth_getTransactionCount") || !request.contains("eth_getBalance") {
+            return Err(format!("链上记录的请求不正确: {}", request));
+        }
+        let response = |nonce: &str, balance: &str| serde_json::json!([
+            { "jsonrpc": "2.0", "id": 1, "result": balance },
+            { "jsonrpc": "2.0", "id": 0, "result": nonce },
+        ]);
+        for (nonce, balance, active) in [("0x0", "0x0", false), ("0x1", "0x0", true), ("0x0", "0x2386f26fc10000", true)] {
+            let activity = parse_activity_response(&response(nonce, balance))?;
+            if activity.is_active() != active {
+                return Err(format!("交易数 {} 余额 {} 的链上记录判断为 {}", nonce, balance, activity.is_active()));
+            }
+        }
+        let failed = serde_json::json!([
+            { "jsonrpc": "2.0", "id": 0, "result": "0x0" },
+            { "jsonrpc": "2.0", "id": 1, "error": { "code": -32005, "message": "rate limited" } },
+        ]);
+        if parse_activity_response(&failed).is_ok() || parse_activity_response(&serde_json::json!("502 Bad Gateway")).is_ok() {
+            return Err("查询失败时没有记为未知".to_string());
+        }
+        
+        for (chain, rpc_url) in [(None, "not a url"), (Some("tron"), "https://rpc.example")] {
+            let params = GenerationParams { pattern: "8".to_string(), chain: chain.map(str::to_string), verify_unused: Some(rpc_url.to_string()), ..GenerationParams::default() };
+            if params.validate().is_ok() {
+                return Err(format!("链 {:?} 与 verify_unused {} 没有被拒绝", chain, rpc_url));
+            }
+        }
+        GenerationParams { pattern: "8".to_string(), verify_unused: Some("https://rpc.example".to_string()), ..GenerationParams::default() }.validate()?;
+        Ok(())
+    }
+}
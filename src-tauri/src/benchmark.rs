@@ -0,0 +1,159 @@
+use crate::chain::Chain;
+use crate::engine::{next_source_key, CancellationToken, IncrementalKeys, KeyCandidateStats, INCREMENTAL_KEYS_PER_BASE};
+use crate::entropy::{KeySource, RngMode};
+use crate::solana;
+use crate::worker;
+use secp256k1::Secp256k1;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
+
+/// 最长测速时间（毫秒）
+pub const MAX_BENCHMARK_DURATION_MS: u64 = 10 * 60 * 1000;
+
+/// 单个工作线程的测速结果
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ThreadBenchmark {
+    /// 工作线程编号（从 0 开始）
+    pub thread: u32,
+    /// 推导的地址数
+    pub attempts: u64,
+    /// 每秒推导的地址数
+    pub keys_per_second: f64,
+}
+
+/// 测速报告
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BenchmarkReport {
+    /// 测速的链
+    pub chain: Chain,
+    /// 工作线程数
+    pub threads: u32,
+    /// 实际运行时间（毫秒）
+    pub duration_ms: u64,
+    /// 推导的地址总数
+    pub attempts: u64,
+    /// 全部工作线程合计的每秒推导地址数
+    pub keys_per_second: f64,
+    /// 各工作线程的结果
+    pub per_thread: Vec<ThreadBenchmark>,
+    /// 是否被取消（取消前的结果仍然有效）
+    pub cancelled: bool,
+}
+
+/**
+ * 每秒次数（耗时为 0 时为 0）
+ */
+fn per_second(attempts: u64, elapsed: Duration) -> f64 {
+    let secs = elapsed.as_secs_f64();
+    if secs > 0.0 {
+        attempts as f64 / secs
+    } else {
+        0.0
+    }
+}
+
+/**
+ * 单个工作线程：按该链生成时的默认流程推导地址（候选私钥 → 公钥 → 地址的哈希和编码），不做模式匹配
+ *
+ * 以太坊为 Keccak-256，波场和比特币还要做 Base58 或 Bech32 编码，公钥都由基础私钥增量推导；
+ * Solana 每个候选都是随机私钥，计算 ed25519 公钥后做 Base58 编码。
+ */
+fn benchmark_thread(thread: u32, chain: Chain, deadline: Instant, cancel: &CancellationToken) -> ThreadBenchmark {
+    let secp = Secp256k1::new();
+    let mut source = KeySource::new(RngMode::default(), None);
+    let mut key_stats = KeyCandidateStats::default();
+    let mut keys = IncrementalKeys::new(&secp, INCREMENTAL_KEYS_PER_BASE);
+    let start = Instant::now();
+    let mut attempts = 0;
+    while !cancel.is_cancelled() && Instant::now() < deadline {
+        match chain.encoder() {
+            Some(encoder) => {
+                let Some((_, public_key)) = keys.next(&secp, &mut source, &mut key_stats) else {
+                    continue;
+                };
+                std::hint::black_box(encoder.match_text(&public_key, false));
+            }
+            None => {
+                let Some(secret_key) = next_source_key(&mut source, &mut key_stats) else {
+                    continue;
+                };
+                let public_key = solana::public_key(&Zeroizing::new(secret_key.secret_bytes()));
+                std::hint::black_box(solana::encode(&public_key));
+            }
+        }
+        attempts += 1;
+    }
+    ThreadBenchmark { thread, attempts, keys_per_second: per_second(attempts, start.elapsed()) }
+}
+
+/**
+ * 测速：在固定时间内用指定数量的工作线程推导地址（不做模式匹配，也不写入任何文件）
+ *
+ * @param duration - 运行时间
+ * @param threads - 工作线程数（为空时每个 CPU 核心一个）
+ * @param chain - 测速的链（各链推导地址的速度不同）
+ * @param cancel - 取消令牌（取消时提前结束并返回已有的结果）
+ * @returns 测速报告
+ */
+pub fn run(duration: Duration, threads: Option<u32>, chain: Chain, cancel: &CancellationToken) -> Result<BenchmarkReport, String> {
+    if duration.is_zero() || duration > Duration::from_millis(MAX_BENCHMARK_DURATION_MS) {
+        return Err(format!("测速时间必须在 1 到 {} 毫秒之间", MAX_BENCHMARK_DURATION_MS));
+    }
+    worker::validate(threads)?;
+    let threads = threads.unwrap_or_else(worker::default_workers);
+
+    let start = Instant::now();
+    let deadline = start + duration;
+    let per_thread: Vec<ThreadBenchmark> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads).map(|thread| scope.spawn(move || benchmark_thread(thread, chain, deadline, cancel))).collect();
+        handles.into_iter().filter_map(|handle| handle.join().ok()).collect()
+    });
+    let elapsed = start.elapsed();
+    if per_thread.len() != threads as usize {
+        return Err("测速的工作线程异常退出".to_string());
+    }
+
+    let attempts = per_thread.iter().map(|thread| thread.attempts).sum();
+    Ok(BenchmarkReport {
+        chain,
+        threads,
+        duration_ms: elapsed.as_millis() as u64,
+        attempts,
+        keys_per_second: per_second(attempts, elapsed),
+        per_thread,
+        cancelled: cancel.is_cancelled(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * 测速：各工作线程都推导了地址，合计次数与各线程之和一致；取消后立即结束，无效的时间和线程数被拒绝
+     */
+    #[test]
+    fn benchmark() -> Result<(), String> {
+        let report = run(Duration::from_millis(200), Some(2), Chain::Ethereum, &CancellationToken::new())?;
+        let threads: Vec<u32> = report.per_thread.iter().map(|thread| thread.thread).collect();
+        if threads != [0, 1] || report.cancelled || report.per_thread.iter().any(|thread| thread.attempts == 0 || thread.keys_per_second <= 0.0) {
+            return Err(format!("测速结果无效: {:?}", report));
+        }
+        if report.attempts != report.per_thread.iter().map(|thread| thread.attempts).sum::<u64>() || report.keys_per_second <= 0.0 {
+            return Err(format!("合计的测速结果与各线程不一致: {:?}", report));
+        }
+        
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let cancelled = run(Duration::from_millis(MAX_BENCHMARK_DURATION_MS), Some(1), Chain::Ethereum, &cancel)?;
+        if !cancelled.cancelled || cancelled.attempts != 0 || cancelled.duration_ms > 1000 {
+            return Err(format!("取消的测速没有立即结束: {:?}", cancelled));
+        }
+        let invalid = [(Duration::ZERO, Some(1)), (Duration::from_millis(MAX_BENCHMARK_DURATION_MS + 1), Some(1)), (Duration::from_millis(10), Some(0))];
+        if invalid.iter().any(|(duration, threads)| run(*duration, *threads, Chain::Ethereum, &CancellationToken::new()).is_ok()) {
+            return Err("无效的测速时间或线程数没有被拒绝".to_string());
+        }
+        Ok(())
+    }
+}
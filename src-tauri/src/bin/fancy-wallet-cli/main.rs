@@ -0,0 +1,206 @@
+//! 命令行搜索（不依赖 Tauri）：结束时打印按模式汇总的表格，并写入机器可读的汇总文件
+//!
+//! cargo run --bin fancy-wallet-cli --no-default-features -- [选项] <模式>...
+//!
+//! 选项：
+//!   --max-matches N       找到 N 个匹配后停止
+//!   --max-attempts N      尝试 N 次后停止
+//!   --max-duration SECS   最长运行时间（秒）
+//!   --workers N           工作线程数（默认使用全部 CPU 核心）
+//!   --output DIR          匹配写入 DIR/<序号>-<模式>.csv，汇总写入 DIR/summary.json（默认当前目录）
+//!   --color WHEN          auto（默认：输出到终端且没有设置 NO_COLOR 时着色）、always 或 never
+//!
+//! 按 Enter 可以提前停止。
+
+mod summary_table;
+
+use fancy_wallet_lib::{CancellationToken, FoundKey, SearchConfig, SearchEvent, SearchReport};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use summary_table::PatternSummary;
+
+/// 汇总文件名（写入输出目录）
+const SUMMARY_FILE: &str = "summary.json";
+
+/// 输出文件名中模式部分的最大长度
+const MAX_FILE_LABEL_LENGTH: usize = 40;
+
+/// 输出文件的表头
+const CSV_HEADER: &str = "address,private_key,attempts,duration_ms,mnemonic";
+
+/// 命令行参数
+struct Options {
+    config: SearchConfig,
+    output: PathBuf,
+    color: bool,
+}
+
+/// 机器可读的汇总（与表格的内容相同）
+#[derive(Serialize)]
+struct RunSummary<'a> {
+    /// 各模式的汇总
+    patterns: &'a [PatternSummary],
+    /// 搜索报告（不含 last_found，私钥只写入各模式的输出文件）
+    report: &'a SearchReport,
+}
+
+fn main() {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    if let Err(e) = run(&options) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+/**
+ * 解析命令行参数
+ *
+ * @param args - 不含程序名的参数
+ */
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut patterns = Vec::new();
+    let mut config = SearchConfig::new(String::new());
+    let mut output = PathBuf::from(".");
+    let mut color = None;
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("{} 缺少取值", name));
+        let number = |name: &str, text: String| text.parse::<u64>().map_err(|_| format!("{} 的取值必须是非负整数: {}", name, text));
+        match arg.as_str() {
+            "--max-matches" => config.limits.max_matches = Some(number(&arg, value(&arg)?)?),
+            "--max-attempts" => config.limits.max_attempts = Some(number(&arg, value(&arg)?)?),
+            "--max-duration" => config.limits.max_duration_secs = Some(number(&arg, value(&arg)?)?),
+            "--workers" => {
+                let workers = number(&arg, value(&arg)?)?;
+                config.workers = Some(u32::try_from(workers).map_err(|_| format!("工作线程数过大: {}", workers))?);
+            }
+            "--output" => output = PathBuf::from(value(&arg)?),
+            "--color" => {
+                color = match value(&arg)?.as_str() {
+                    "auto" => None,
+                    "always" => Some(true),
+                    "never" => Some(false),
+                    other => return Err(format!("--color 的取值必须是 auto、always 或 never: {}", other)),
+                }
+            }
+            _ if arg.starts_with("--") => return Err(format!("未知的选项: {}", arg)),
+            _ => patterns.push(arg),
+        }
+    }
+    let mut patterns = patterns.into_iter();
+    config.pattern = patterns.next().ok_or("至少需要一个靓号模式")?;
+    config.additional_patterns = patterns.collect();
+    config.validate()?;
+    let color = color.unwrap_or_else(|| std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none());
+    Ok(Options { config, output, color })
+}
+
+/**
+ * 运行搜索：匹配写入各模式的输出文件，结束后打印汇总表格并写入汇总文件
+ */
+fn run(options: &Options) -> Result<(), String> {
+    let config = &options.config;
+    fs::create_dir_all(&options.output).map_err(|e| format!("无法创建输出目录: {}", e))?;
+    let cancel = CancellationToken::new();
+    let stdin_cancel = cancel.clone();
+    std::thread::spawn(move || {
+        // 没有可读的终端时（输入已关闭）不取消
+        if let Ok(1..) = std::io::stdin().read_line(&mut String::new()) {
+            stdin_cancel.cancel();
+        }
+    });
+
+    let mut files: BTreeMap<String, (PathBuf, File)> = BTreeMap::new();
+    let mut write_error = None;
+    let mut report = fancy_wallet_lib::run(config, &cancel, |event| match event {
+        SearchEvent::Found(found) => {
+            println!("found {} ({}) after {} attempts", display_address(config, &found), found.pattern, found.attempts);
+            if let Err(e) = append_match(config, &options.output, &mut files, &found) {
+                // 无法保存的匹配不能丢掉：停止搜索并报告
+                write_error = Some(e);
+                cancel.cancel();
+            }
+        }
+        SearchEvent::RngWarning(progress) => eprintln!("warning: {} rejected keys, the random number generator may be broken", progress.rejected_keys),
+        SearchEvent::WorkerFailed(failure) => eprintln!("worker {} panicked: {}", failure.worker, failure.message),
+        _ => {}
+    })?;
+    if let Some(e) = write_error {
+        return Err(e);
+    }
+
+    report.last_found = None;
+    let outputs = files.into_iter().map(|(pattern, (path, _))| (pattern, path)).collect();
+    let rows = summary_table::pattern_summaries(config, &report, &outputs);
+    let summary_path = options.output.join(SUMMARY_FILE);
+    let json = serde_json::to_string_pretty(&RunSummary { patterns: &rows, report: &report }).map_err(|e| format!("无法序列化汇总: {}", e))?;
+    fs::write(&summary_path, json).map_err(|e| format!("无法写入汇总文件 {}: {}", summary_path.display(), e))?;
+    print!("{}", summary_table::render(&rows, &report, options.color));
+    println!("summary written to {}", summary_path.display());
+    Ok(())
+}
+
+/**
+ * 匹配的地址（该链的文本格式）
+ */
+fn display_address(config: &SearchConfig, found: &FoundKey) -> String {
+    match (&found.chain_address, config.encoder()) {
+        (Some(address), _) => address.clone(),
+        (None, Some(encoder)) => encoder.encode(found.address),
+        (None, None) => found.address.to_string(),
+    }
+}
+
+/**
+ * 把匹配追加到它所满足的模式的输出文件（第一次写入时创建文件和表头；文件只有当前用户可以读写）
+ *
+ * @param config - 搜索配置
+ * @param dir - 输出目录
+ * @param files - 已打开的各模式的输出文件
+ * @param found - 找到的匹配
+ */
+fn append_match(config: &SearchConfig, dir: &Path, files: &mut BTreeMap<String, (PathBuf, File)>, found: &FoundKey) -> Result<(), String> {
+    if !files.contains_key(&found.pattern) {
+        let index = config.patterns().iter().position(|pattern| *pattern == found.pattern).unwrap_or(0);
+        let path = dir.join(format!("{}-{}.csv", index + 1, file_label(&found.pattern)));
+        let mut options = OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&path).map_err(|e| format!("无法打开输出文件 {}: {}", path.display(), e))?;
+        let empty = file.metadata().map(|metadata| metadata.len() == 0).unwrap_or(false);
+        if empty {
+            writeln!(file, "{}", CSV_HEADER).map_err(|e| format!("无法写入输出文件 {}: {}", path.display(), e))?;
+        }
+        files.insert(found.pattern.clone(), (path, file));
+    }
+    let (path, file) = files.get_mut(&found.pattern).expect("上面已经打开");
+    writeln!(
+        file,
+        "{},{},{},{},{}",
+        display_address(config, found),
+        found.private_key.expose(),
+        found.attempts,
+        found.duration,
+        found.mnemonic.as_deref().unwrap_or("")
+    )
+    .map_err(|e| format!("无法写入输出文件 {}: {}", path.display(), e))
+}
+
+/**
+ * 模式在文件名中的形式（只保留字母、数字和连字符，其它字符换成下划线，最长 MAX_FILE_LABEL_LENGTH 个字符）
+ */
+fn file_label(pattern: &str) -> String {
+    pattern.chars().take(MAX_FILE_LABEL_LENGTH).map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' }).collect()
+}
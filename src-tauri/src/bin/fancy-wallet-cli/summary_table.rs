@@ -0,0 +1,269 @@
+//! 搜索结束时按模式汇总的终端表格
+
+use fancy_wallet_lib::{SearchConfig, SearchReport};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+/// 模式列的最大宽度（字符数），更长的模式截断后以省略号结尾
+pub const MAX_PATTERN_WIDTH: usize = 24;
+
+/// 截断的模式末尾的省略号
+const ELLIPSIS: char = '…';
+
+/// 表头
+const HEADERS: [&str; 6] = ["PATTERN", "MATCHES", "EXPECTED", "ACTUAL", "LUCK", "OUTPUT"];
+
+/// 一个模式的汇总（表格的一行，也写入机器可读的汇总文件）
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct PatternSummary {
+    /// 模式
+    pub pattern: String,
+    /// 本次运行的匹配数
+    pub matches: u64,
+    /// 只看这个模式时找到一个匹配的期望尝试次数
+    pub expected_attempts: f64,
+    /// 实际平均每个匹配的尝试次数（没有匹配时为空）
+    pub attempts_per_match: Option<f64>,
+    /// 运气系数：期望尝试次数除以实际平均每个匹配的尝试次数，大于 1 表示比期望更快（没有匹配时为空）
+    pub luck: Option<f64>,
+    /// 写入匹配的文件（没有匹配时为空）
+    pub output: Option<PathBuf>,
+}
+
+/**
+ * 按配置中模式的顺序汇总每个模式（每个候选地址都与全部模式比较，各模式的尝试次数都是总尝试次数）
+ *
+ * @param config - 搜索配置
+ * @param report - 搜索报告
+ * @param outputs - 各模式写入匹配的文件
+ */
+pub fn pattern_summaries(config: &SearchConfig, report: &SearchReport, outputs: &BTreeMap<String, PathBuf>) -> Vec<PatternSummary> {
+    config
+        .patterns()
+        .into_iter()
+        .map(|pattern| {
+            let matches = report.pattern_matches.get(pattern).copied().unwrap_or(0);
+            let expected_attempts = config.pattern_expected_attempts(pattern);
+            let attempts_per_match = (matches > 0).then(|| report.attempts as f64 / matches as f64);
+            PatternSummary {
+                pattern: pattern.to_string(),
+                matches,
+                expected_attempts,
+                attempts_per_match,
+                luck: attempts_per_match.map(|actual| expected_attempts / actual),
+                output: outputs.get(pattern).cloned(),
+            }
+        })
+        .collect()
+}
+
+/**
+ * 渲染汇总表格：每个模式一行，最后一行为总计和停止原因
+ *
+ * @param rows - 各模式的汇总
+ * @param report - 搜索报告
+ * @param color - 是否使用 ANSI 颜色（表头加粗，运气系数不低于 1 为绿色、低于 1 为黄色，没有匹配的行变暗）
+ */
+pub fn render(rows: &[PatternSummary], report: &SearchReport, color: bool) -> String {
+    let cells: Vec<[String; 6]> = rows
+        .iter()
+        .map(|row| {
+            [
+                truncate_pattern(&row.pattern),
+                row.matches.to_string(),
+                compact(row.expected_attempts),
+                row.attempts_per_match.map_or("-".to_string(), compact),
+                row.luck.map_or("-".to_string(), |luck| format!("{:.2}x", luck)),
+                row.output.as_ref().map_or("-".to_string(), |path| path.display().to_string()),
+            ]
+        })
+        .collect();
+    let mut widths = HEADERS.map(|header| header.chars().count());
+    for row in &cells {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut out = String::new();
+    let header = pad_line(&HEADERS.map(String::from), &widths);
+    out.push_str(&paint(&header, "1", color));
+    out.push('\n');
+    for (row, cells) in rows.iter().zip(&cells) {
+        let line = pad_line(cells, &widths);
+        let line = match row.luck {
+            None => paint(&line, "2", color),
+            Some(luck) => {
+                // 只给运气系数一列着色：按列宽找到它在这一行中的位置
+                let start: usize = widths[..4].iter().map(|width| width + 2).sum();
+                let end = start + widths[4];
+                let (head, rest) = split_at_char(&line, start);
+                let (cell, tail) = split_at_char(rest, end - start);
+                format!("{}{}{}", head, paint(cell, if luck >= 1.0 { "32" } else { "33" }, color), tail)
+            }
+        };
+        out.push_str(&line);
+        out.push('\n');
+    }
+    let stop = match &report.stop {
+        Some(stop) => serde_json::to_value(stop.reason).ok().and_then(|value| value.as_str().map(String::from)).unwrap_or_default(),
+        None => "cancelled".to_string(),
+    };
+    let _ = writeln!(
+        out,
+        "{} attempts, {} matches in {:.1} s, stopped: {}",
+        report.attempts,
+        report.matches,
+        report.duration as f64 / 1000.0,
+        stop
+    );
+    if report.entropy.test_watermark {
+        out.push_str(&paint("test wallets (deterministic-test), never use them for real funds", "33", color));
+        out.push('\n');
+    }
+    out
+}
+
+/**
+ * 截断过长的模式（按字符计数，不会截断在多字节字符中间）
+ */
+fn truncate_pattern(pattern: &str) -> String {
+    if pattern.chars().count() <= MAX_PATTERN_WIDTH {
+        return pattern.to_string();
+    }
+    pattern.chars().take(MAX_PATTERN_WIDTH - 1).chain(std::iter::once(ELLIPSIS)).collect()
+}
+
+/**
+ * 次数的简短形式：一万以下显示整数，更大的用 k/M/G/T/P/E 后缀保留一位小数，再大的用科学计数法
+ */
+fn compact(value: f64) -> String {
+    const SUFFIXES: [&str; 6] = ["k", "M", "G", "T", "P", "E"];
+    if value < 10_000.0 {
+        return format!("{:.0}", value);
+    }
+    let mut scaled = value;
+    for suffix in SUFFIXES {
+        scaled /= 1000.0;
+        if scaled < 999.95 {
+            return format!("{:.1}{}", scaled, suffix);
+        }
+    }
+    format!("{:.2e}", value)
+}
+
+/**
+ * 一行单元格：第一列和最后一列左对齐，数字列右对齐，列之间两个空格，去掉行尾空白
+ */
+fn pad_line(cells: &[String; 6], widths: &[usize; 6]) -> String {
+    let padded: Vec<String> = cells
+        .iter()
+        .zip(widths)
+        .enumerate()
+        .map(|(column, (cell, width))| {
+            if column == 0 || column == cells.len() - 1 {
+                format!("{:<width$}", cell, width = width)
+            } else {
+                format!("{:>width$}", cell, width = width)
+            }
+        })
+        .collect();
+    padded.join("  ").trim_end().to_string()
+}
+
+/**
+ * 在第 n 个字符处拆分（n 超过长度时第二部分为空）
+ */
+fn split_at_char(text: &str, n: usize) -> (&str, &str) {
+    let index = text.char_indices().nth(n).map_or(text.len(), |(index, _)| index);
+    text.split_at(index)
+}
+
+/**
+ * 按需加上 ANSI 颜色
+ *
+ * @param text - 文本
+ * @param code - SGR 参数
+ * @param color - 是否着色
+ */
+fn paint(text: &str, code: &str, color: bool) -> String {
+    if color {
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fancy_wallet_lib::{CancellationToken, GenerationLimits, RngMode, StopReason, StopRecord};
+
+    /**
+     * 构造固定计数的报告（计数、运行时间和停止原因不依赖实际的搜索速度）
+     */
+    fn fixed_report(config: &SearchConfig, attempts: u64, pattern_matches: &[(&str, u64)], stop: Option<StopReason>) -> Result<SearchReport, String> {
+        let mut report = fancy_wallet_lib::run(config, &CancellationToken::new(), |_| {})?;
+        report.attempts = attempts;
+        report.pattern_matches = pattern_matches.iter().map(|(pattern, matches)| (pattern.to_string(), *matches)).collect();
+        report.matches = report.pattern_matches.values().sum();
+        report.duration = 12_345;
+        report.stop = stop.map(|reason| StopRecord { reason, detail: None });
+        Ok(report)
+    }
+
+    fn test_config(pattern: &str, additional: &[&str]) -> SearchConfig {
+        SearchConfig {
+            limits: GenerationLimits { max_attempts: Some(1), ..GenerationLimits::default() },
+            rng_mode: RngMode::DeterministicTest,
+            test_seed: Some(1),
+            additional_patterns: additional.iter().map(|pattern| pattern.to_string()).collect(),
+            ..SearchConfig::new(pattern)
+        }
+    }
+
+    /**
+     * 快照：不着色时各列对齐，长模式被截断，没有匹配的模式显示为 -，最后一行为总计和停止原因
+     */
+    #[test]
+    fn summary_table_snapshot() -> Result<(), String> {
+        let long_pattern = "dead/beefbeefbeefbeefbeefbeef";
+        let config = test_config("88", &["*cafe*", long_pattern]);
+        let report = fixed_report(&config, 300_000, &[("88", 5), ("*cafe*", 100)], Some(StopReason::MaxAttempts))?;
+        let outputs = BTreeMap::from([("88".to_string(), PathBuf::from("out/1-88.csv")), ("*cafe*".to_string(), PathBuf::from("out/2-cafe.csv"))]);
+        let table = render(&pattern_summaries(&config, &report, &outputs), &report, false);
+        let expected = "\
+PATTERN                   MATCHES  EXPECTED  ACTUAL   LUCK  OUTPUT
+88                              5     65.5k   60.0k  1.09x  out/1-88.csv
+*cafe*                        100      1772    3000  0.59x  out/2-cafe.csv
+dead/beefbeefbeefbeefbe…        0   5.19e33       -      -  -
+300000 attempts, 105 matches in 12.3 s, stopped: max_attempts
+test wallets (deterministic-test), never use them for real funds
+";
+        if table != expected {
+            return Err(format!("汇总表格与快照不一致:\n{}\n期望:\n{}", table, expected));
+        }
+        Ok(())
+    }
+
+    /**
+     * 着色只加在表头、运气系数和没有匹配的行上，去掉颜色后与不着色的表格相同
+     */
+    #[test]
+    fn summary_table_color() -> Result<(), String> {
+        let config = test_config("8", &["a"]);
+        let report = fixed_report(&config, 100, &[("8", 10)], None)?;
+        let rows = pattern_summaries(&config, &report, &BTreeMap::new());
+        let (plain, colored) = (render(&rows, &report, false), render(&rows, &report, true));
+        if !colored.contains("\x1b[32m") || !colored.contains("\x1b[2m") || plain.contains('\x1b') {
+            return Err(format!("着色的表格缺少颜色，或不着色的表格带有颜色:\n{:?}", colored));
+        }
+        let stripped = regex::Regex::new("\x1b\\[[0-9]+m").map_err(|e| e.to_string())?.replace_all(&colored, "");
+        if stripped != plain {
+            return Err(format!("去掉颜色后与不着色的表格不同:\n{}\n{}", stripped, plain));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,326 @@
+use crate::chain::{base58_encode, AddressEncoder};
+#[cfg(feature = "gui")]
+use crate::chain::base58_decode;
+use crate::hexutil::Address;
+use crate::pattern::PatternAlphabet;
+use ripemd::Ripemd160;
+use secp256k1::PublicKey;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+/// Bech32 字母表（按数值 0 到 31 排列，不含 1、b、i、o）
+pub(crate) const BECH32_CHARSET: &str = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// 主网 Bech32 地址的人类可读部分
+const BECH32_HRP: &str = "bc";
+
+/// 主网 P2WPKH 地址固定的开头（人类可读部分、分隔符 1 和见证版本 0）
+const P2WPKH_PREFIX: &str = "bc1q";
+
+/// 主网 P2PKH 地址的版本字节（编码后总是以 1 开头）
+const P2PKH_VERSION: u8 = 0x00;
+
+/// 主网 WIF 私钥的版本字节
+#[cfg(feature = "gui")]
+const WIF_VERSION: u8 = 0x80;
+
+/// WIF 私钥末尾的标记字节：对应的地址使用压缩公钥
+#[cfg(feature = "gui")]
+const WIF_COMPRESSED: u8 = 0x01;
+
+/// Bech32 校验和的生成多项式（BIP-173）
+const BECH32_GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+
+/// 比特币地址的格式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BitcoinAddressType {
+    /// 1 开头的 Base58Check 地址（默认）
+    #[default]
+    P2pkh,
+    /// bc1q 开头的 Bech32 隔离见证地址（只有小写字母）
+    P2wpkh,
+}
+
+impl BitcoinAddressType {
+    /**
+     * 参数和错误信息中的名称
+     */
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            BitcoinAddressType::P2pkh => "p2pkh",
+            BitcoinAddressType::P2wpkh => "p2wpkh",
+        }
+    }
+
+    /**
+     * 该格式的地址编码
+     */
+    pub fn encoder(self) -> &'static dyn AddressEncoder {
+        match self {
+            BitcoinAddressType::P2pkh => &P2pkhEncoder,
+            BitcoinAddressType::P2wpkh => &P2wpkhEncoder,
+        }
+    }
+}
+
+/**
+ * 公钥的 HASH160：压缩公钥（33 字节）的 SHA-256 再做 RIPEMD-160
+ *
+ * P2PKH 和 P2WPKH 地址编码的都是这 20 字节。
+ */
+pub fn hash160(public_key: &PublicKey) -> Address {
+    let digest = Ripemd160::digest(Sha256::digest(public_key.serialize()));
+    let mut bytes = [0u8; 20];
+    bytes.copy_from_slice(&digest);
+    Address::from(bytes)
+}
+
+/// P2PKH 地址：版本字节 0x00 + HASH160 的 Base58Check 编码（26 到 34 个字符，总是以 1 开头）
+pub struct P2pkhEncoder;
+
+impl AddressEncoder for P2pkhEncoder {
+    fn alphabet(&self) -> PatternAlphabet {
+        PatternAlphabet::Base58
+    }
+
+    fn address(&self, public_key: &PublicKey) -> Address {
+        hash160(public_key)
+    }
+
+    fn encode(&self, address: Address) -> String {
+        let mut payload = Vec::with_capacity(21);
+        payload.push(P2PKH_VERSION);
+        payload.extend_from_slice(address.as_bytes());
+        base58check_encode(&payload)
+    }
+
+    fn fixed_prefix(&self) -> &'static str {
+        "1"
+    }
+
+    fn match_text(&self, public_key: &PublicKey, _case_sensitive: bool) -> String {
+        self.encode(hash160(public_key)).split_off(self.fixed_prefix().len())
+    }
+}
+
+/// P2WPKH 地址：见证版本 0 + HASH160 的 Bech32 编码（42 个字符，总是以 bc1q 开头）
+pub struct P2wpkhEncoder;
+
+impl AddressEncoder for P2wpkhEncoder {
+    fn alphabet(&self) -> PatternAlphabet {
+        PatternAlphabet::Bech32
+    }
+
+    fn address(&self, public_key: &PublicKey) -> Address {
+        hash160(public_key)
+    }
+
+    fn encode(&self, address: Address) -> String {
+        let mut data = vec![0u8];
+        data.extend(to_five_bits(address.as_bytes()));
+        bech32_encode(BECH32_HRP, &data)
+    }
+
+    fn fixed_prefix(&self) -> &'static str {
+        P2WPKH_PREFIX
+    }
+
+    fn match_text(&self, public_key: &PublicKey, _case_sensitive: bool) -> String {
+        self.encode(hash160(public_key)).split_off(self.fixed_prefix().len())
+    }
+}
+
+/**
+ * 私钥的 WIF 格式（主网、压缩公钥，以 K 或 L 开头；离开作用域时清零）
+ *
+ * @param secret - 32 字节私钥
+ */
+#[cfg(feature = "gui")]
+pub fn wif(secret: &[u8; 32]) -> Zeroizing<String> {
+    let mut payload = Zeroizing::new(Vec::with_capacity(34));
+    payload.push(WIF_VERSION);
+    payload.extend_from_slice(secret);
+    payload.push(WIF_COMPRESSED);
+    Zeroizing::new(base58check_encode(&payload))
+}
+
+/**
+ * 解析 WIF 格式的私钥（只接受主网、压缩公钥的格式）
+ *
+ * @param text - WIF 私钥
+ * @returns 32 字节私钥
+ */
+#[cfg(all(test, feature = "gui"))]
+pub fn parse_wif(text: &str) -> Result<Zeroizing<[u8; 32]>, String> {
+    let payload = Zeroizing::new(base58check_decode(text.trim()).ok_or("WIF 私钥格式或校验和错误")?);
+    if payload.len() != 34 || payload[0] != WIF_VERSION || payload[33] != WIF_COMPRESSED {
+        return Err("不是主网压缩公钥的 WIF 私钥".to_string());
+    }
+    let mut secret = Zeroizing::new([0u8; 32]);
+    secret.copy_from_slice(&payload[1..33]);
+    Ok(secret)
+}
+
+/**
+ * 解析主网比特币地址（P2PKH 校验 Base58Check 和版本字节，P2WPKH 校验 Bech32 校验和与见证版本）
+ *
+ * @returns 地址编码的 HASH160
+ */
+#[cfg(feature = "gui")]
+pub fn parse_address(text: &str) -> Result<Address, String> {
+    let text = text.trim();
+    let bytes = if text.to_ascii_lowercase().starts_with(P2WPKH_PREFIX) {
+        let data = bech32_decode(BECH32_HRP, text).ok_or_else(|| format!("Bech32 地址格式或校验和错误: {}", text))?;
+        match data.split_first() {
+            Some((0, program)) => from_five_bits(program).ok_or_else(|| format!("不是有效的 P2WPKH 地址: {}", text))?,
+            _ => return Err(format!("不是见证版本 0 的地址: {}", text)),
+        }
+    } else {
+        let payload = base58check_decode(text).ok_or_else(|| format!("比特币地址格式或校验和错误: {}", text))?;
+        match payload.split_first() {
+            Some((&P2PKH_VERSION, hash)) => hash.to_vec(),
+            _ => return Err(format!("不是主网 P2PKH 地址: {}", text)),
+        }
+    };
+    let bytes: [u8; 20] = bytes.try_into().map_err(|_| format!("地址的长度不正确: {}", text))?;
+    Ok(Address::from(bytes))
+}
+
+/**
+ * 比特币地址的格式（由开头判断，不校验地址本身）
+ */
+#[cfg(feature = "gui")]
+pub fn address_type(text: &str) -> BitcoinAddressType {
+    if text.trim().to_ascii_lowercase().starts_with(P2WPKH_PREFIX) {
+        BitcoinAddressType::P2wpkh
+    } else {
+        BitcoinAddressType::P2pkh
+    }
+}
+
+/**
+ * 追加 4 字节校验和（两次 SHA-256 的前 4 字节）后做 Base58 编码（内容可能是私钥，中间结果离开作用域时清零）
+ */
+fn base58check_encode(payload: &[u8]) -> String {
+    let mut data = Zeroizing::new(payload.to_vec());
+    data.extend_from_slice(&Sha256::digest(Sha256::digest(payload))[..4]);
+    base58_encode(&data)
+}
+
+/**
+ * Base58 解码并检查末尾的 4 字节校验和
+ *
+ * @returns 去掉校验和的内容；字符或校验和无效时为空
+ */
+#[cfg(feature = "gui")]
+fn base58check_decode(text: &str) -> Option<Vec<u8>> {
+    let mut payload = base58_decode(text)?;
+    if payload.len() < 4 {
+        return None;
+    }
+    let checksum = payload.split_off(payload.len() - 4);
+    (Sha256::digest(Sha256::digest(&payload))[..4] == checksum[..]).then_some(payload)
+}
+
+/**
+ * 字节转为 5 位分组（末尾不足 5 位的部分补零）
+ */
+fn to_five_bits(data: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(data.len() * 8 / 5 + 1);
+    let (mut acc, mut bits) = (0u32, 0u32);
+    for &value in data {
+        acc = ((acc << 8) | value as u32) & 0xfff;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            result.push(((acc >> bits) & 31) as u8);
+        }
+    }
+    if bits > 0 {
+        result.push(((acc << (5 - bits)) & 31) as u8);
+    }
+    result
+}
+
+/**
+ * 5 位分组转回字节：剩余的位不能超过 4 位且必须为零（BIP-173）
+ */
+#[cfg(feature = "gui")]
+fn from_five_bits(data: &[u8]) -> Option<Vec<u8>> {
+    let mut result = Vec::with_capacity(data.len() * 5 / 8);
+    let (mut acc, mut bits) = (0u32, 0u32);
+    for &value in data {
+        acc = ((acc << 5) | value as u32) & 0xfff;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            result.push(((acc >> bits) & 0xff) as u8);
+        }
+    }
+    (bits < 5 && acc & ((1 << bits) - 1) == 0).then_some(result)
+}
+
+/**
+ * Bech32 校验和计算中的多项式取模
+ */
+fn bech32_polymod(values: impl IntoIterator<Item = u8>) -> u32 {
+    let mut checksum = 1u32;
+    for value in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x1ffffff) << 5) ^ value as u32;
+        for (i, generator) in BECH32_GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+/**
+ * 参与校验和计算的人类可读部分：各字符的高 3 位、0、各字符的低 5 位
+ */
+fn bech32_hrp_expand(hrp: &str) -> Vec<u8> {
+    let bytes = hrp.as_bytes();
+    bytes.iter().map(|b| b >> 5).chain(std::iter::once(0)).chain(bytes.iter().map(|b| b & 31)).collect()
+}
+
+/**
+ * Bech32 编码（小写）
+ *
+ * @param hrp - 人类可读部分
+ * @param data - 5 位分组的数据
+ */
+fn bech32_encode(hrp: &str, data: &[u8]) -> String {
+    let values = bech32_hrp_expand(hrp).into_iter().chain(data.iter().copied()).chain([0; 6]);
+    let polymod = bech32_polymod(values) ^ 1;
+    let charset = BECH32_CHARSET.as_bytes();
+    let checksum = (0..6).map(|i| ((polymod >> (5 * (5 - i))) & 31) as u8);
+    let mut text = format!("{}1", hrp);
+    text.extend(data.iter().copied().chain(checksum).map(|value| charset[value as usize] as char));
+    text
+}
+
+/**
+ * Bech32 解码：人类可读部分必须一致，不能混用大小写，校验和必须正确
+ *
+ * @returns 5 位分组的数据（不含校验和）
+ */
+#[cfg(feature = "gui")]
+fn bech32_decode(hrp: &str, text: &str) -> Option<Vec<u8>> {
+    if text.chars().any(|c| c.is_ascii_lowercase()) && text.chars().any(|c| c.is_ascii_uppercase()) {
+        return None;
+    }
+    let text = text.to_ascii_lowercase();
+    let (prefix, data) = text.rsplit_once('1')?;
+    if prefix != hrp || data.len() < 6 {
+        return None;
+    }
+    let values: Vec<u8> = data.chars().map(|c| BECH32_CHARSET.find(c).map(|i| i as u8)).collect::<Option<_>>()?;
+    if bech32_polymod(bech32_hrp_expand(hrp).into_iter().chain(values.iter().copied())) != 1 {
+        return None;
+    }
+    Some(values[..values.len() - 6].to_vec())
+}
@@ -0,0 +1,199 @@
+use crate::address::checksum_from_bytes;
+use crate::engine::{next_secret_key, KeyCandidateStats};
+use rand::rngs::OsRng;
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// 每隔多少个候选私钥做一次独立复核
+pub const SAMPLE_EVERY: u64 = 64;
+
+/// 最长压力测试时间（秒）
+pub const MAX_DURATION_SECS: u64 = 24 * 60 * 60;
+
+/// 报告中保留的不一致样本上限
+const MAX_REPORTED_MISMATCHES: usize = 20;
+
+/// 复核结果不一致的样本（不包含私钥）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BurnInMismatch {
+    /// 第几次尝试
+    pub attempt: u64,
+    /// 生成流程得到的地址
+    pub pipeline_address: String,
+    /// 独立复核得到的地址
+    pub verified_address: String,
+}
+
+/// 压力测试进度
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BurnInProgress {
+    /// 尝试次数
+    pub attempts: u64,
+    /// 复核样本数
+    pub verified_samples: u64,
+    /// 不一致次数
+    pub mismatches: u64,
+    /// 耗时（毫秒）
+    pub duration: u64,
+}
+
+/// 压力测试过程中的事件
+pub enum BurnInEvent {
+    /// 进度更新（每 1000 次尝试）
+    Progress(BurnInProgress),
+    /// 发现不一致
+    Mismatch(BurnInMismatch),
+}
+
+/// 压力测试报告
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BurnInReport {
+    /// 是否通过（没有任何不一致）
+    pub passed: bool,
+    /// 是否被取消
+    pub cancelled: bool,
+    /// 尝试次数
+    pub attempts: u64,
+    /// 复核样本数
+    pub verified_samples: u64,
+    /// 复核比例（复核样本数 / 尝试次数）
+    pub verified_rate: f64,
+    /// 不一致次数
+    pub mismatches: u64,
+    /// 不一致样本（最多保留 20 个）
+    pub mismatch_samples: Vec<BurnInMismatch>,
+    /// 耗时（毫秒）
+    pub duration: u64,
+}
+
+/**
+ * 独立推导 checksum 地址：使用单独构造的上下文，直接用 sha3 库对公钥字节做 Keccak256，
+ * 不经过生成流程中的地址计算代码（也不随 keccak 模块选择的实现变化）
+ */
+fn verify_address(secp: &Secp256k1<secp256k1::SignOnly>, key: &SecretKey) -> String {
+    let public_key = PublicKey::from_secret_key(secp, key);
+    let serialized = public_key.serialize_uncompressed();
+    let hash = Keccak256::digest(&serialized[1..]);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    checksum_from_bytes(&address)
+}
+
+/**
+ * 运行压力测试：以满负荷执行生成流程，并每隔 SAMPLE_EVERY 个候选私钥独立复核一次地址
+ *
+ * 不保存任何结果文件；私钥不会出现在事件和报告中。
+ *
+ * @param limit - 运行时间
+ * @param cancel - 取消标志
+ * @param pipeline - 生成流程（私钥 -> checksum 地址），复核结果与之比较
+ * @param on_event - 进度和不一致事件回调
+ * @returns 压力测试报告
+ */
+pub fn run(
+    limit: Duration,
+    cancel: &AtomicBool,
+    mut pipeline: impl FnMut(&SecretKey) -> String,
+    mut on_event: impl FnMut(BurnInEvent),
+) -> BurnInReport {
+    let start = Instant::now();
+    let verify_secp = Secp256k1::signing_only();
+    let mut rng = OsRng;
+    let mut key_stats = KeyCandidateStats::default();
+    let mut attempts = 0u64;
+    let mut verified_samples = 0u64;
+    let mut mismatches = 0u64;
+    let mut mismatch_samples = Vec::new();
+    let mut cancelled = false;
+
+    while start.elapsed() < limit {
+        if cancel.load(Ordering::SeqCst) {
+            cancelled = true;
+            break;
+        }
+        attempts += 1;
+        let Some(key) = next_secret_key(&mut rng, &mut key_stats) else {
+            continue;
+        };
+        let pipeline_address = pipeline(&key);
+
+        if attempts.is_multiple_of(SAMPLE_EVERY) {
+            verified_samples += 1;
+            let verified_address = verify_address(&verify_secp, &key);
+            if verified_address != pipeline_address {
+                mismatches += 1;
+                let mismatch = BurnInMismatch { attempt: attempts, pipeline_address, verified_address };
+                if mismatch_samples.len() < MAX_REPORTED_MISMATCHES {
+                    mismatch_samples.push(mismatch.clone());
+                }
+                on_event(BurnInEvent::Mismatch(mismatch));
+            }
+        }
+
+        if attempts.is_multiple_of(1000) {
+            on_event(BurnInEvent::Progress(BurnInProgress {
+                attempts,
+                verified_samples,
+                mismatches,
+                duration: start.elapsed().as_millis() as u64,
+            }));
+        }
+    }
+
+    BurnInReport {
+        passed: mismatches == 0 && verified_samples > 0,
+        cancelled,
+        attempts,
+        verified_samples,
+        verified_rate: if attempts == 0 { 0.0 } else { verified_samples as f64 / attempts as f64 },
+        mismatches,
+        mismatch_samples,
+        duration: start.elapsed().as_millis() as u64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::public_key_to_checksum_address;
+
+    /// 压力测试故障注入检查的运行时间
+    const BURN_IN_CHECK_DURATION: Duration = Duration::from_millis(100);
+
+    /**
+     * 压力测试复核：正常流程必须通过，注入错误后必须被发现
+     */
+    #[test]
+    fn burn_in_verification() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        let cancel = AtomicBool::new(false);
+        let clean = run(BURN_IN_CHECK_DURATION, &cancel, |key| public_key_to_checksum_address(&PublicKey::from_secret_key(&secp, key)), |_| {});
+        if !clean.passed {
+            return Err(format!("正常流程复核出现 {} 次不一致", clean.mismatches));
+        }
+        
+        // 每隔一段时间返回一个错误的地址，模拟硬件故障
+        let mut calls = 0u64;
+        let faulty = run(
+            BURN_IN_CHECK_DURATION,
+            &cancel,
+            |key| {
+                calls += 1;
+                let address = public_key_to_checksum_address(&PublicKey::from_secret_key(&secp, key));
+                if calls.is_multiple_of(SAMPLE_EVERY * 2) {
+                    address.chars().rev().collect()
+                } else {
+                    address
+                }
+            },
+            |_| {},
+        );
+        if faulty.passed || faulty.mismatches == 0 {
+            return Err("注入的错误没有被复核发现".to_string());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,275 @@
+use crate::chain::Chain;
+use crate::engine::{self, CancellationToken, SearchConfig};
+use crate::pattern::ParsedPattern;
+use crate::templates::MAX_WORKERS;
+use crate::timestamps;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// 校准记录文件（位于应用数据目录；校准结果与机器相关，不按用户配置隔离）
+const CALIBRATION_FILE: &str = "calibration.json";
+
+/// 校准记录格式版本
+pub const CALIBRATION_VERSION: u32 = 1;
+
+/// 保留的校准结果数
+const MAX_CALIBRATION_HISTORY: usize = 3;
+
+/// 首次启动时的校准时长
+pub const CALIBRATION_DURATION: Duration = Duration::from_secs(2);
+
+/// 校准时使用的模式（只统计速度，找到的匹配直接丢弃）
+const CALIBRATION_PATTERN: &str = "8888";
+
+/// 写入延迟探测文件名（写入后立即删除，不会被当作结果文件）
+const PROBE_FILE: &str = ".calibration_probe";
+
+/// 写入延迟探测的次数（取中位数）
+const PROBE_WRITES: usize = 5;
+
+/// 期望的进度事件间隔
+const TARGET_PROGRESS_PERIOD: Duration = Duration::from_millis(250);
+
+/// “一小时内”提示的时间预算
+const PATTERN_TIME_BUDGET: Duration = Duration::from_secs(60 * 60);
+
+/// 快速测速的尝试次数（全部工作线程合计）
+const QUICK_BENCHMARK_ATTEMPTS: u64 = 20_000;
+
+/// 写入延迟不超过该值时建议每行都同步到磁盘
+const FSYNC_EVERY_ROW_LATENCY_MS: f64 = 10.0;
+
+/// 写入结果时的同步策略
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FsyncPolicy {
+    /// 每写入一行都同步到磁盘
+    EveryRow,
+    /// 只在会话结束时同步
+    SessionEnd,
+}
+
+/// 根据本机校准结果推荐的默认设置
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecommendedDefaults {
+    /// 工作线程数（不超过本版本支持的数量）
+    pub workers: u32,
+    /// 进度事件间隔（尝试次数）
+    pub progress_interval: u64,
+    /// 写入结果时的同步策略
+    pub fsync_policy: FsyncPolicy,
+    /// 期望一小时内能找到的最长前后缀模式（纯数字字符；含字母的模式更难）
+    pub max_pattern_length_for_hour: usize,
+}
+
+/// 一次校准的结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Calibration {
+    /// 校准时间
+    pub measured_at: String,
+    /// 单线程生成速度（次/秒）
+    pub hashrate: f64,
+    /// 逻辑核心数
+    pub cores: usize,
+    /// 默认输出目录的写入并同步延迟（毫秒，无法探测时为空）
+    pub disk_write_latency_ms: Option<f64>,
+    /// 推荐的默认设置
+    pub recommended: RecommendedDefaults,
+}
+
+/// 校准记录（最近的在最后）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CalibrationStore {
+    /// 格式版本
+    pub version: u32,
+    /// 最近几次的校准结果
+    pub history: Vec<Calibration>,
+}
+
+impl Default for CalibrationStore {
+    fn default() -> Self {
+        CalibrationStore { version: CALIBRATION_VERSION, history: Vec::new() }
+    }
+}
+
+impl CalibrationStore {
+    /**
+     * 最近一次校准推荐的默认设置
+     */
+    pub fn recommended(&self) -> Option<&RecommendedDefaults> {
+        self.history.last().map(|calibration| &calibration.recommended)
+    }
+}
+
+/**
+ * 探测目录的写入并同步延迟：写入一个小文件并 fsync，取多次的中位数，完成后删除探测文件
+ *
+ * @param dir - 输出目录（不存在时不创建，返回空）
+ */
+fn probe_write_latency(dir: &Path) -> Option<f64> {
+    if !dir.is_dir() {
+        return None;
+    }
+    let path = dir.join(PROBE_FILE);
+    let mut samples = Vec::new();
+    for _ in 0..PROBE_WRITES {
+        let start = Instant::now();
+        let written = File::create(&path).and_then(|mut file| {
+            file.write_all(b"address,private_key,pattern\n")?;
+            file.sync_all()
+        });
+        if written.is_err() {
+            break;
+        }
+        samples.push(start.elapsed().as_secs_f64() * 1000.0);
+    }
+    let _ = fs::remove_file(&path);
+    samples.sort_by(f64::total_cmp);
+    samples.get(samples.len() / 2).copied()
+}
+
+/**
+ * 由校准数据推导推荐设置
+ */
+fn recommend(hashrate: f64, cores: usize, disk_write_latency_ms: Option<f64>) -> RecommendedDefaults {
+    let workers = (cores as u32).clamp(1, MAX_WORKERS);
+    let per_period = hashrate * workers as f64 * TARGET_PROGRESS_PERIOD.as_secs_f64();
+    let progress_interval = ((per_period / 1000.0).round() as u64).max(1) * 1000;
+    let fsync_policy = match disk_write_latency_ms {
+        Some(latency) if latency <= FSYNC_EVERY_ROW_LATENCY_MS => FsyncPolicy::EveryRow,
+        _ => FsyncPolicy::SessionEnd,
+    };
+    let budget = hashrate * workers as f64 * PATTERN_TIME_BUDGET.as_secs_f64();
+    let max_pattern_length_for_hour = (1..=20)
+        .take_while(|&length| ParsedPattern::new(&"8".repeat(length)).expected_attempts() <= budget)
+        .last()
+        .unwrap_or(0);
+    RecommendedDefaults { workers, progress_interval, fsync_policy, max_pattern_length_for_hour }
+}
+
+/**
+ * 运行校准：以试运行方式执行生成流程测速（找到的匹配只计数，不写入任何结果文件），
+ * 并探测默认输出目录的写入延迟
+ *
+ * @param duration - 测速时长
+ * @param output_dir - 默认输出目录（用于探测写入延迟）
+ * @param cancel - 取消令牌
+ * @returns 校准结果
+ */
+pub fn calibrate(duration: Duration, output_dir: &Path, cancel: &CancellationToken) -> Result<Calibration, String> {
+    let mut config = SearchConfig::new(CALIBRATION_PATTERN);
+    config.limits.max_duration_secs = Some(duration.as_secs().max(1));
+    // 测量单个工作线程的速度，推荐设置再按核心数换算
+    config.workers = Some(1);
+    let report = engine::run(&config, cancel, |_| {})?;
+    if report.stop.is_none() {
+        return Err("校准已取消".to_string());
+    }
+    if report.duration == 0 {
+        return Err("校准运行时间过短".to_string());
+    }
+
+    let hashrate = report.attempts as f64 / (report.duration as f64 / 1000.0);
+    let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let disk_write_latency_ms = probe_write_latency(output_dir);
+    Ok(Calibration {
+        measured_at: timestamps::now_rfc3339(),
+        hashrate,
+        cores,
+        disk_write_latency_ms,
+        recommended: recommend(hashrate, cores, disk_write_latency_ms),
+    })
+}
+
+/**
+ * 快速测速：按指定的链运行固定次数的尝试（不写入任何文件，也不记录为校准结果；校准只测以太坊）
+ *
+ * @param chain - 测速的链（各链推导地址的速度不同）
+ * @param workers - 工作线程数（为空时使用全部 CPU 核心）
+ * @returns 全部工作线程合计的每秒尝试次数
+ */
+pub fn quick_benchmark(chain: Chain, workers: Option<u32>) -> Result<f64, String> {
+    let mut config = SearchConfig { chain, workers, ..SearchConfig::new(CALIBRATION_PATTERN) };
+    config.limits.max_attempts = Some(QUICK_BENCHMARK_ATTEMPTS);
+    let report = engine::run(&config, &CancellationToken::new(), |_| {})?;
+    if report.duration == 0 {
+        return Err("测速运行时间过短".to_string());
+    }
+    Ok(report.attempts as f64 / (report.duration as f64 / 1000.0))
+}
+
+/**
+ * 读取校准记录（文件不存在时返回空记录）
+ *
+ * @param data_dir - 应用数据目录
+ */
+pub fn load(data_dir: &Path) -> Result<CalibrationStore, String> {
+    let path = data_dir.join(CALIBRATION_FILE);
+    if !path.exists() {
+        return Ok(CalibrationStore::default());
+    }
+    let data = fs::read_to_string(&path).map_err(|e| format!("无法读取校准记录: {}", e))?;
+    serde_json::from_str(&data).map_err(|e| format!("校准记录格式错误: {}", e))
+}
+
+/**
+ * 追加一次校准结果，只保留最近三次
+ *
+ * @param data_dir - 应用数据目录
+ * @param calibration - 校准结果
+ * @returns 更新后的校准记录
+ */
+pub fn record(data_dir: &Path, calibration: Calibration) -> Result<CalibrationStore, String> {
+    let mut store = load(data_dir)?;
+    store.history.push(calibration);
+    let excess = store.history.len().saturating_sub(MAX_CALIBRATION_HISTORY);
+    store.history.drain(..excess);
+
+    fs::create_dir_all(data_dir).map_err(|e| format!("无法创建应用数据目录: {}", e))?;
+    let json = serde_json::to_string_pretty(&store).map_err(|e| format!("无法序列化校准记录: {}", e))?;
+    let path = data_dir.join(CALIBRATION_FILE);
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, json).map_err(|e| format!("无法写入校准记录: {}", e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("无法写入校准记录: {}", e))?;
+    Ok(store)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::with_data_dir;
+
+    /**
+     * 校准：试运行得到非零的速度，输出目录中不留下任何文件；校准记录只保留最近三次
+     */
+    #[test]
+    fn calibration() -> Result<(), String> {
+        with_data_dir("calibration", |dir| {
+            let output_dir = dir.join("FancyWallets");
+            std::fs::create_dir_all(&output_dir).map_err(|e| e.to_string())?;
+            let result = calibrate(Duration::from_secs(1), &output_dir, &CancellationToken::new())?;
+            if result.hashrate <= 0.0 || result.recommended.workers == 0 {
+                return Err(format!("校准结果无效: {:?}", result));
+            }
+            let leftovers = std::fs::read_dir(&output_dir).map_err(|e| e.to_string())?.count();
+            if leftovers > 0 {
+                return Err(format!("校准后输出目录中留下了 {} 个文件", leftovers));
+            }
+            
+            for i in 0..4 {
+                let mut entry = result.clone();
+                entry.measured_at = format!("run-{}", i);
+                record(dir, entry)?;
+            }
+            let store = load(dir)?;
+            let kept: Vec<&str> = store.history.iter().map(|c| c.measured_at.as_str()).collect();
+            if kept != ["run-1", "run-2", "run-3"] {
+                return Err(format!("校准记录保留的结果不正确: {:?}", kept));
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,367 @@
+use crate::address::{public_key_to_address, public_key_to_checksum_address};
+#[cfg(feature = "gui")]
+use crate::bitcoin;
+use crate::bitcoin::BitcoinAddressType;
+use crate::hexutil::Address;
+use crate::mnemonic::KeySourceKind;
+use crate::pattern::PatternAlphabet;
+#[cfg(feature = "gui")]
+use crate::solana;
+use secp256k1::PublicKey;
+#[cfg(feature = "gui")]
+use secp256k1::{Secp256k1, SecretKey, Signing};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+#[cfg(feature = "gui")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "gui")]
+use zeroize::Zeroizing;
+
+/// Base58 字母表（不含 0、O、I、l）
+pub(crate) const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// 波场地址的版本字节（主网）
+const TRON_ADDRESS_PREFIX: u8 = 0x41;
+
+/// 地址所属的链
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Chain {
+    /// 以太坊（0x 开头的十六进制地址，默认）
+    #[default]
+    Ethereum,
+    /// 波场（T 开头的 Base58 地址）
+    Tron,
+    /// 比特币（1 开头的 P2PKH 或 bc1q 开头的 P2WPKH 地址，由压缩公钥的 HASH160 编码）
+    Bitcoin,
+    /// Solana（ed25519 公钥的 Base58 编码，私钥作为 ed25519 的种子，与 secp256k1 无关）
+    Solana,
+}
+
+/// 所有已知的链；读取结果时会查找 FancyWallets 下对应的子目录
+#[cfg(feature = "gui")]
+pub const KNOWN_CHAINS: &[Chain] = &[Chain::Ethereum, Chain::Tron, Chain::Bitcoin, Chain::Solana];
+
+/// 把 secp256k1 公钥对应的 20 字节地址编码为某条链的文本格式
+///
+/// 私钥和公钥的生成对所有 secp256k1 链都相同，地址的哈希、编码和模式使用的字符因链而异；
+/// 增加新的 secp256k1 链时实现这个 trait，并在 Chain::address_encoder 中返回。Solana 使用 ed25519 公钥，没有对应的编码。
+pub trait AddressEncoder: Sync {
+    /// 模式可以使用的字符
+    fn alphabet(&self) -> PatternAlphabet;
+
+    /// 公钥对应的 20 字节地址（默认为公钥 Keccak-256 的后 20 字节，比特币为压缩公钥的 HASH160）
+    fn address(&self, public_key: &PublicKey) -> Address {
+        Address::from_public_key(public_key)
+    }
+
+    /// 地址的文本格式（写入结果文件和界面显示）
+    fn encode(&self, address: Address) -> String;
+
+    /// 所有地址都相同的开头部分（match_text 中不包含）
+    fn fixed_prefix(&self) -> &'static str;
+
+    /// 生成循环中与模式比较的文本：不含所有地址都相同的开头部分（以太坊的 0x、波场的 T、比特币的 1 或 bc1q）
+    ///
+    /// case_sensitive 为 false 时可以返回任意大小写（模式按小写比较）。
+    fn match_text(&self, public_key: &PublicKey, case_sensitive: bool) -> String;
+}
+
+/// 以太坊地址：0x + EIP-55 checksum 格式的十六进制
+pub struct EthereumEncoder;
+
+impl AddressEncoder for EthereumEncoder {
+    fn alphabet(&self) -> PatternAlphabet {
+        PatternAlphabet::Hex
+    }
+
+    fn encode(&self, address: Address) -> String {
+        address.to_checksum()
+    }
+
+    fn fixed_prefix(&self) -> &'static str {
+        "0x"
+    }
+
+    fn match_text(&self, public_key: &PublicKey, case_sensitive: bool) -> String {
+        // 不区分大小写时直接使用小写地址，不计算 checksum
+        if case_sensitive {
+            public_key_to_checksum_address(public_key)
+        } else {
+            public_key_to_address(public_key)
+        }
+    }
+}
+
+/// 波场地址：版本字节 0x41 + 20 字节地址的 Base58Check 编码（34 个字符，总是以 T 开头）
+pub struct TronEncoder;
+
+impl AddressEncoder for TronEncoder {
+    fn alphabet(&self) -> PatternAlphabet {
+        PatternAlphabet::Base58
+    }
+
+    fn encode(&self, address: Address) -> String {
+        let mut payload = Vec::with_capacity(25);
+        payload.push(TRON_ADDRESS_PREFIX);
+        payload.extend_from_slice(address.as_bytes());
+        let checksum = Sha256::digest(Sha256::digest(&payload));
+        payload.extend_from_slice(&checksum[..4]);
+        base58_encode(&payload)
+    }
+
+    fn fixed_prefix(&self) -> &'static str {
+        "T"
+    }
+
+    fn match_text(&self, public_key: &PublicKey, _case_sensitive: bool) -> String {
+        self.encode(Address::from_public_key(public_key)).split_off(self.fixed_prefix().len())
+    }
+}
+
+impl Chain {
+    /**
+     * 链名称（与 CSV 列、子目录名和模板中的 chain 字段一致）
+     */
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Chain::Ethereum => "ethereum",
+            Chain::Tron => "tron",
+            Chain::Bitcoin => "bitcoin",
+            Chain::Solana => "solana",
+        }
+    }
+
+    /**
+     * 该链的地址编码（比特币为默认的 P2PKH；Solana 不使用 secp256k1，没有编码）
+     */
+    pub fn encoder(self) -> Option<&'static dyn AddressEncoder> {
+        self.address_encoder(BitcoinAddressType::default())
+    }
+
+    /**
+     * 该链指定格式的地址编码（Solana 没有编码）
+     *
+     * @param bitcoin_address - 比特币地址的格式（其它链忽略）
+     */
+    pub fn address_encoder(self, bitcoin_address: BitcoinAddressType) -> Option<&'static dyn AddressEncoder> {
+        match self {
+            Chain::Ethereum => Some(&EthereumEncoder),
+            Chain::Tron => Some(&TronEncoder),
+            Chain::Bitcoin => Some(bitcoin_address.encoder()),
+            Chain::Solana => None,
+        }
+    }
+
+    /**
+     * 模式可以使用的字符
+     *
+     * @param bitcoin_address - 比特币地址的格式（其它链忽略）
+     */
+    pub fn alphabet(self, bitcoin_address: BitcoinAddressType) -> PatternAlphabet {
+        self.address_encoder(bitcoin_address).map_or(PatternAlphabet::Base58PublicKey, |encoder| encoder.alphabet())
+    }
+
+    /**
+     * 模式是否区分大小写：Solana 的地址没有其它写法，总是按字符本身比较，其它链按用户的设置
+     *
+     * @param requested - 用户的 case_sensitive 设置
+     */
+    pub fn case_sensitive(self, requested: bool) -> bool {
+        requested || self == Chain::Solana
+    }
+
+    /**
+     * 私钥在该链上对应的 20 字节地址和文本格式，与生成时的流程相同
+     *
+     * 比特币按 text 的开头（1 或 bc1q）选择 P2PKH 或 P2WPKH 编码；Solana 由私钥计算 ed25519 公钥，20 字节地址为 solana::address_id。
+     *
+     * @param secp - secp256k1 上下文
+     * @param secret_key - 私钥
+     * @param text - 记录的该链地址（用于选择比特币地址的格式）
+     */
+    #[cfg(feature = "gui")]
+    pub fn derive_address<C: Signing>(self, secp: &Secp256k1<C>, secret_key: &SecretKey, text: &str) -> (Address, String) {
+        let encoder: &dyn AddressEncoder = match self {
+            Chain::Ethereum => &EthereumEncoder,
+            Chain::Tron => &TronEncoder,
+            Chain::Bitcoin => bitcoin::address_type(text).encoder(),
+            Chain::Solana => {
+                let public_key = solana::public_key(&Zeroizing::new(secret_key.secret_bytes()));
+                return (solana::address_id(&public_key), solana::encode(&public_key));
+            }
+        };
+        let address = encoder.address(&PublicKey::from_secret_key(secp, secret_key));
+        (address, encoder.encode(address))
+    }
+
+    /**
+     * 检查该链能否使用指定的私钥来源：助记词按以太坊的路径 m/44'/60'/0'/0/0 派生，只能用于以太坊
+     *
+     * @param key_source - 候选私钥的来源
+     */
+    pub fn validate_key_source(self, key_source: KeySourceKind) -> Result<(), String> {
+        if self != Chain::Ethereum && key_source == KeySourceKind::Mnemonic {
+            return Err(format!("助记词按以太坊的路径 m/44'/60'/0'/0/0 派生，不能用于 {}", self.as_str()));
+        }
+        Ok(())
+    }
+
+    /**
+     * 解析链名称（不区分大小写）
+     */
+    #[cfg(feature = "gui")]
+    pub fn parse(name: &str) -> Result<Chain, String> {
+        KNOWN_CHAINS
+            .iter()
+            .copied()
+            .find(|chain| chain.as_str().eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| format!("未知的链: {}", name))
+    }
+
+    /**
+     * 根据地址格式推断所属的链（用于没有记录链的旧结果文件）
+     */
+    #[cfg(feature = "gui")]
+    pub fn from_address(address: &str) -> Option<Chain> {
+        let address = address.trim();
+        if address.starts_with("0x") && Address::parse(address).is_ok() {
+            return Some(Chain::Ethereum);
+        }
+        if address.len() == 34 && address.starts_with('T') && address.chars().all(|c| BASE58_ALPHABET.contains(c)) {
+            return Some(Chain::Tron);
+        }
+        if bitcoin::parse_address(address).is_ok() {
+            return Some(Chain::Bitcoin);
+        }
+        if solana::parse_address(address).is_ok() {
+            return Some(Chain::Solana);
+        }
+        None
+    }
+
+    /**
+     * 把 20 字节地址转换为该链的文本格式（以太坊为规范的 0x + EIP-55，波场为 Base58Check，比特币为 P2PKH）
+     *
+     * 写入结果文件时按会话的链调用，不使用以太坊格式的链必须通过这里显式转换；比特币的 P2WPKH 地址使用 bitcoin_address 对应的编码。
+     * Solana 的地址无法由 20 字节标识还原（结果中保存在 chain_address），这里返回标识本身的以太坊格式。
+     */
+    #[cfg(feature = "gui")]
+    pub fn format_address(self, address: Address) -> String {
+        self.encoder().map_or_else(|| address.to_checksum(), |encoder| encoder.encode(address))
+    }
+
+    /**
+     * 解析该链文本格式的地址（以太坊接受带或不带 0x 的十六进制，波场校验 Base58Check 和版本字节，
+     * 比特币接受 P2PKH 和 P2WPKH，返回 HASH160；Solana 返回公钥的 20 字节标识）
+     */
+    #[cfg(feature = "gui")]
+    pub fn parse_address(self, text: &str) -> Result<Address, String> {
+        let text = text.trim();
+        match self {
+            Chain::Ethereum => Address::parse(text).map_err(|e| e.to_string()),
+            Chain::Tron => {
+                let payload = base58_decode(text).ok_or_else(|| format!("波场地址包含无效字符: {}", text))?;
+                if payload.len() != 25 || payload[0] != TRON_ADDRESS_PREFIX {
+                    return Err(format!("不是有效的波场地址: {}", text));
+                }
+                let checksum = Sha256::digest(Sha256::digest(&payload[..21]));
+                if checksum[..4] != payload[21..] {
+                    return Err(format!("波场地址校验和错误: {}", text));
+                }
+                let mut bytes = [0u8; 20];
+                bytes.copy_from_slice(&payload[1..21]);
+                Ok(Address::from(bytes))
+            }
+            Chain::Bitcoin => bitcoin::parse_address(text),
+            Chain::Solana => solana::parse_address(text).map(|public_key| solana::address_id(&public_key)),
+        }
+    }
+
+    /**
+     * 该链文本格式的地址在生成循环中与模式比较的文本（与 AddressEncoder::match_text 相同，区分大小写的以太坊地址为 checksum 格式）
+     *
+     * @param text - 地址（以太坊地址可以是任意大小写，比特币按开头选择 P2PKH 或 P2WPKH）
+     * @returns 规范格式的地址、不参与比较的固定开头和比较的文本
+     */
+    #[cfg(feature = "gui")]
+    pub fn match_text_of(self, text: &str) -> Result<(String, &'static str, String), String> {
+        let address = self.parse_address(text)?;
+        let encoder: &dyn AddressEncoder = match self {
+            Chain::Bitcoin => bitcoin::address_type(text).encoder(),
+            Chain::Solana => {
+                let canonical = solana::encode(&solana::parse_address(text)?);
+                return Ok((canonical.clone(), "", canonical));
+            }
+            _ => self.encoder().ok_or_else(|| format!("{} 没有地址编码", self.as_str()))?,
+        };
+        let canonical = encoder.encode(address);
+        let match_text = canonical[encoder.fixed_prefix().len()..].to_string();
+        Ok((canonical, encoder.fixed_prefix(), match_text))
+    }
+
+    /**
+     * 该链的输出目录（FancyWallets/<链名称>）
+     *
+     * @param wallets_dir - FancyWallets 目录
+     */
+    #[cfg(feature = "gui")]
+    pub fn dir(self, wallets_dir: &Path) -> PathBuf {
+        wallets_dir.join(self.as_str())
+    }
+
+    /**
+     * 结果文件所在目录对应的链（旧版本直接写在 FancyWallets 下的文件返回空）
+     */
+    #[cfg(feature = "gui")]
+    pub fn from_dir(file: &Path) -> Option<Chain> {
+        let dir = file.parent()?.file_name()?.to_str()?;
+        KNOWN_CHAINS.iter().copied().find(|chain| chain.as_str() == dir)
+    }
+}
+
+/**
+ * Base58 解码（前导字符 1 解码为零字节），包含字母表以外的字符时返回空
+ */
+#[cfg(feature = "gui")]
+pub(crate) fn base58_decode(text: &str) -> Option<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::new();
+    for c in text.chars() {
+        let mut carry = BASE58_ALPHABET.find(c)? as u32;
+        for byte in bytes.iter_mut() {
+            carry += (*byte as u32) * 58;
+            *byte = (carry & 0xff) as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push((carry & 0xff) as u8);
+            carry >>= 8;
+        }
+    }
+    let zeros = text.chars().take_while(|&c| c == '1').count();
+    Some(std::iter::repeat_n(0, zeros).chain(bytes.into_iter().rev()).collect())
+}
+
+/**
+ * Base58 编码（前导零字节编码为字符 1）
+ */
+pub(crate) fn base58_encode(bytes: &[u8]) -> String {
+    let alphabet = BASE58_ALPHABET.as_bytes();
+    let mut digits: Vec<u8> = Vec::new();
+    for &byte in bytes {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let zeros = bytes.iter().take_while(|&&b| b == 0).count();
+    std::iter::repeat_n('1', zeros)
+        .chain(digits.iter().rev().map(|&d| alphabet[d as usize] as char))
+        .collect()
+}
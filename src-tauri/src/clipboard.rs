@@ -0,0 +1,223 @@
+use crate::timestamps;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use zeroize::Zeroizing;
+
+/// 复制的私钥等在剪贴板中保留的最长时间（秒）
+pub const MAX_CLEAR_AFTER_SECS: u64 = 600;
+
+/// 最近一次复制、尚未清除的值（只保存一份：再次复制时之前的值已被剪贴板覆盖）
+static PENDING: Mutex<Option<PendingCopy>> = Mutex::new(None);
+
+/// 下一次复制的编号
+static NEXT_COPY_ID: AtomicU64 = AtomicU64::new(1);
+
+/// 系统剪贴板（Tauri 剪贴板插件的文本读写；测试中由内存中的剪贴板代替）
+pub trait ClipboardAccess {
+    /**
+     * 读取剪贴板中的文本（剪贴板中不是文本时返回错误）
+     */
+    fn read_text(&self) -> Result<String, String>;
+
+    /**
+     * 把文本写入剪贴板
+     */
+    fn write_text(&self, text: &str) -> Result<(), String>;
+
+    /**
+     * 清空剪贴板
+     */
+    fn clear(&self) -> Result<(), String>;
+}
+
+/// 等待清除的一次复制
+struct PendingCopy {
+    /// 复制的编号
+    id: u64,
+    /// 复制的值（离开作用域时清零）
+    value: Zeroizing<String>,
+}
+
+/// 一次复制的结果（copy_secret_to_clipboard 的返回值，界面据此显示倒计时）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ClipboardCopy {
+    /// 复制的编号（与 clipboard-cleared 事件对应）
+    pub copy_id: u64,
+    /// 多少秒后清除
+    pub clear_after_secs: u64,
+    /// 预计清除的时间（UTC，RFC 3339）
+    pub clear_at: String,
+}
+
+/// 到期时的处理结果
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClearOutcome {
+    /// 剪贴板中仍是复制的值，已清除
+    Cleared,
+    /// 用户之后复制了其它内容，没有清除
+    Replaced,
+    /// 之后又复制了一个值，清除由那一次复制负责
+    Superseded,
+    /// 剪贴板中仍是复制的值，但清除失败
+    Failed,
+}
+
+/// 剪贴板到期的处理结果（clipboard-cleared 事件）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ClipboardCleared {
+    /// 复制的编号
+    pub copy_id: u64,
+    /// 处理结果
+    pub outcome: ClearOutcome,
+    /// 清除失败的原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/**
+ * 校验清除前等待的时间
+ *
+ * @param clear_after_secs - 多少秒后清除（1 到 MAX_CLEAR_AFTER_SECS）
+ */
+pub fn validate(clear_after_secs: u64) -> Result<(), String> {
+    if !(1..=MAX_CLEAR_AFTER_SECS).contains(&clear_after_secs) {
+        return Err(format!("clear_after_secs 必须在 1 到 {} 秒之间", MAX_CLEAR_AFTER_SECS));
+    }
+    Ok(())
+}
+
+/**
+ * 把值写入剪贴板，并记下它以便到期时比较
+ *
+ * @param clipboard - 系统剪贴板
+ * @param value - 要复制的值
+ * @param clear_after_secs - 多少秒后清除
+ */
+pub fn copy(clipboard: &impl ClipboardAccess, value: &str, clear_after_secs: u64) -> Result<ClipboardCopy, String> {
+    validate(clear_after_secs)?;
+    if value.is_empty() {
+        return Err("要复制的值不能为空".to_string());
+    }
+    let mut pending = PENDING.lock().map_err(|_| "剪贴板状态不可用".to_string())?;
+    clipboard.write_text(value)?;
+    let id = NEXT_COPY_ID.fetch_add(1, Ordering::Relaxed);
+    *pending = Some(PendingCopy { id, value: Zeroizing::new(value.to_string()) });
+    let clear_at = chrono::Utc::now() + chrono::Duration::seconds(clear_after_secs as i64);
+    Ok(ClipboardCopy { copy_id: id, clear_after_secs, clear_at: timestamps::to_rfc3339(clear_at) })
+}
+
+/**
+ * 到期时清除剪贴板：只有剪贴板中仍是这次复制的值时才清除，不清除用户之后复制的其它内容
+ *
+ * @param clipboard - 系统剪贴板
+ * @param copy_id - 复制的编号
+ */
+pub fn clear_if_unchanged(clipboard: &impl ClipboardAccess, copy_id: u64) -> ClipboardCleared {
+    let result = |outcome, error| ClipboardCleared { copy_id, outcome, error };
+    let Ok(mut pending) = PENDING.lock() else {
+        return result(ClearOutcome::Failed, Some("剪贴板状态不可用".to_string()));
+    };
+    let Some(copied) = pending.take_if(|copied| copied.id == copy_id) else {
+        return result(ClearOutcome::Superseded, None);
+    };
+    // 剪贴板中不是文本（例如图片）时同样是用户之后复制的内容
+    let current = clipboard.read_text().map(Zeroizing::new);
+    if !current.is_ok_and(|current| *current == *copied.value) {
+        return result(ClearOutcome::Replaced, None);
+    }
+    match clipboard.clear() {
+        Ok(()) => result(ClearOutcome::Cleared, None),
+        Err(e) => result(ClearOutcome::Failed, Some(e)),
+    }
+}
+
+/**
+ * 立即按 clear_if_unchanged 处理还没有到期的复制（退出应用时调用）
+ *
+ * @param clipboard - 系统剪贴板
+ * @returns 处理结果；没有等待清除的复制时为空
+ */
+pub fn clear_pending(clipboard: &impl ClipboardAccess) -> Option<ClipboardCleared> {
+    let copy_id = PENDING.lock().ok()?.as_ref()?.id;
+    Some(clear_if_unchanged(clipboard, copy_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use crate::self_test::KNOWN_KEYS;
+
+    /// 测试中代替系统剪贴板的内存剪贴板（None 表示剪贴板中不是文本）
+    struct MemoryClipboard(std::sync::Mutex<Option<String>>);
+
+    impl ClipboardAccess for MemoryClipboard {
+        fn read_text(&self) -> Result<String, String> {
+            self.0.lock().map_err(|e| e.to_string())?.clone().ok_or_else(|| "剪贴板中不是文本".to_string())
+        }
+
+        fn write_text(&self, text: &str) -> Result<(), String> {
+            *self.0.lock().map_err(|e| e.to_string())? = Some(text.to_string());
+            Ok(())
+        }
+
+        fn clear(&self) -> Result<(), String> {
+            *self.0.lock().map_err(|e| e.to_string())? = Some(String::new());
+            Ok(())
+        }
+    }
+
+    /**
+     * 剪贴板自动清除：到期时剪贴板中仍是复制的值才清除，用户之后复制的内容和新的一次复制不受影响
+     */
+    #[test]
+    fn clipboard_clear() -> Result<(), String> {
+        let (key, _) = KNOWN_KEYS[0];
+        let memory = MemoryClipboard(std::sync::Mutex::new(None));
+        let contents = || memory.read_text().unwrap_or_default();
+        for secs in [0, MAX_CLEAR_AFTER_SECS + 1] {
+            if copy(&memory, key, secs).is_ok() {
+                return Err(format!("clear_after_secs {} 没有被拒绝", secs));
+            }
+        }
+        
+        // 没有变化：清除
+        let copied = copy(&memory, key, 30)?;
+        let clear_at = timestamps::parse_rfc3339(&copied.clear_at).ok_or("预计清除的时间不是 RFC 3339")?;
+        if contents() != key || clear_at <= Utc::now() {
+            return Err(format!("复制后剪贴板为 {:?}，预计清除时间 {}", contents(), copied.clear_at));
+        }
+        let cleared = clear_if_unchanged(&memory, copied.copy_id);
+        if cleared.outcome != ClearOutcome::Cleared || !contents().is_empty() {
+            return Err(format!("到期时没有清除剪贴板: {:?}", cleared));
+        }
+        
+        // 用户之后复制了其它文本或图片：保留
+        for other in [Some("用户复制的其它内容".to_string()), None] {
+            let copied = copy(&memory, key, 30)?;
+            *memory.0.lock().map_err(|e| e.to_string())? = other.clone();
+            let cleared = clear_if_unchanged(&memory, copied.copy_id);
+            if cleared.outcome != ClearOutcome::Replaced || memory.read_text().ok() != other {
+                return Err(format!("清除了用户之后复制的内容: {:?}", cleared));
+            }
+        }
+        
+        // 同一个值复制两次：第一次到期时不清除，由第二次负责
+        let first = copy(&memory, key, 30)?;
+        let second = copy(&memory, key, 30)?;
+        let outcomes = [clear_if_unchanged(&memory, first.copy_id).outcome, clear_if_unchanged(&memory, second.copy_id).outcome];
+        if first.copy_id == second.copy_id || outcomes != [ClearOutcome::Superseded, ClearOutcome::Cleared] {
+            return Err(format!("两次复制到期的结果为 {:?}", outcomes));
+        }
+        
+        // 退出应用时立即清除，没有等待清除的复制时不做任何事
+        copy(&memory, key, 30)?;
+        let on_exit = clear_pending(&memory).map(|cleared| cleared.outcome);
+        if on_exit != Some(ClearOutcome::Cleared) || !contents().is_empty() || clear_pending(&memory).is_some() {
+            return Err(format!("退出时的清除结果为 {:?}", on_exit));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,248 @@
+use crate::chain::Chain;
+use crate::hexutil::Address;
+use crate::output_root;
+use crate::saved_wallets::{self, SavedWallet, CSV_FORMAT_VERSION, CSV_HEADER};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// FancyWallets 下保存集合的目录
+pub const COLLECTIONS_DIR: &str = "collections";
+
+/// 集合名称的最大长度
+const MAX_COLLECTION_NAME_LENGTH: usize = 64;
+
+/// 追加前读取的集合状态
+#[derive(Debug, Clone, Default)]
+pub struct CollectionState {
+    /// 集合中已有的记录数
+    pub rows: usize,
+    /// 集合中已有的最大序号（新记录的序号从其后开始）
+    pub max_index: u64,
+    /// 集合中已有的地址（用于去重）
+    pub seen: HashSet<Address>,
+}
+
+/// 集合格式迁移结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CollectionMigration {
+    /// 集合名称
+    pub name: String,
+    /// 迁移前的格式版本
+    pub from_version: u32,
+    /// 迁移后的格式版本
+    pub to_version: u32,
+    /// 迁移的记录数
+    pub rows: usize,
+    /// 原文件的备份（格式已是最新时为空）
+    pub backup: Option<PathBuf>,
+}
+
+/**
+ * 校验集合名称（只能包含字母、数字、下划线和连字符，用于拼接文件名）
+ */
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if name.chars().count() > MAX_COLLECTION_NAME_LENGTH {
+        return Err(format!("集合名称不能超过 {} 个字符", MAX_COLLECTION_NAME_LENGTH));
+    }
+    saved_wallets::validate_session_id(name).map_err(|_| format!("无效的集合名称: {}（只能包含字母、数字、下划线和连字符）", name))
+}
+
+/**
+ * 集合文件路径（FancyWallets/collections/<名称>.csv）
+ *
+ * @param wallets_dir - FancyWallets 目录
+ * @param name - 集合名称
+ */
+pub fn collection_path(wallets_dir: &Path, name: &str) -> Result<PathBuf, String> {
+    validate_name(name)?;
+    output_root::expand(&wallets_dir.join(COLLECTIONS_DIR), &format!("{}.csv", name))
+}
+
+/**
+ * 读取集合文件的格式版本（文件不存在或为空时为当前版本）
+ */
+fn format_version(path: &Path) -> Result<u32, String> {
+    let Ok(file) = fs::File::open(path) else {
+        return Ok(CSV_FORMAT_VERSION);
+    };
+    let mut header = String::new();
+    BufReader::new(file).read_line(&mut header).map_err(|e| format!("无法读取集合文件: {}", e))?;
+    if header.trim().is_empty() {
+        return Ok(CSV_FORMAT_VERSION);
+    }
+    saved_wallets::csv_format_version(&header)
+        .ok_or_else(|| format!("无法识别集合文件的 CSV 标题，可能由更新的版本写入: {}", header.trim()))
+}
+
+/**
+ * 追加前检查集合：格式版本必须是当前版本（旧格式需要先迁移），并读取已有的地址用于去重
+ *
+ * @param path - 集合文件路径（不存在时视为空集合）
+ * @param name - 集合名称（用于错误信息）
+ */
+pub fn open(path: &Path, name: &str) -> Result<CollectionState, String> {
+    let version = format_version(path)?;
+    if version != CSV_FORMAT_VERSION {
+        return Err(format!(
+            "集合 {} 使用旧的 CSV 格式（v{}），为避免混入不同格式的行不能直接追加；请先调用 migrate_collection 迁移为当前格式（v{}）",
+            name, version, CSV_FORMAT_VERSION
+        ));
+    }
+    let mut state = CollectionState::default();
+    if !path.exists() {
+        return Ok(state);
+    }
+    for wallet in saved_wallets::read_all(path)? {
+        state.rows += 1;
+        state.max_index = state.max_index.max(wallet.index.unwrap_or(0));
+        if let Some(address) = wallet_address(&wallet) {
+            state.seen.insert(address);
+        }
+    }
+    Ok(state)
+}
+
+fn wallet_address(wallet: &SavedWallet) -> Option<Address> {
+    wallet.chain.unwrap_or(Chain::Ethereum).parse_address(&wallet.address).ok()
+}
+
+/**
+ * 把旧格式的集合改写为当前格式：原文件保留为 <名称>.csv.v<版本>.bak，缺少的链列按地址格式推断，
+ * 旧记录的随机数来源未知，entropy 列留空；旧记录都由原始私钥生成，mnemonic 列留空；
+ * 比特币记录的 wif 列由私钥重新计算；v7 之前的记录没有生成时间、尝试次数和耗时，这三列留空
+ *
+ * @param path - 集合文件路径
+ * @param name - 集合名称
+ * @returns 迁移结果；格式已是最新时不做修改
+ */
+pub fn migrate(path: &Path, name: &str) -> Result<CollectionMigration, String> {
+    if saved_wallets::is_active(path) {
+        return Err(format!("集合 {} 正在被会话写入，不能迁移", name));
+    }
+    let from_version = format_version(path)?;
+    let wallets = if path.exists() { saved_wallets::read_all(path)? } else { Vec::new() };
+    let mut result =
+        CollectionMigration { name: name.to_string(), from_version, to_version: CSV_FORMAT_VERSION, rows: wallets.len(), backup: None };
+    if from_version == CSV_FORMAT_VERSION {
+        return Ok(result);
+    }
+
+    let mut content = format!("{}\n", CSV_HEADER);
+    for wallet in &wallets {
+        content.push_str(&saved_wallets::current_format_row(wallet));
+        content.push('\n');
+    }
+    let tmp_path = path.with_extension("csv.tmp");
+    let backup = path.with_extension(format!("csv.v{}.bak", from_version));
+    fs::write(&tmp_path, content).map_err(|e| format!("无法写入集合文件: {}", e))?;
+    fs::copy(path, &backup).map_err(|e| format!("无法备份集合文件: {}", e))?;
+    fs::rename(&tmp_path, path).map_err(|e| format!("无法写入集合文件: {}", e))?;
+    result.backup = Some(backup);
+    Ok(result)
+}
+
+/**
+ * 集合文件的集合名称（不在 collections 目录中时为空）
+ */
+pub fn name_of(path: &Path) -> Option<&str> {
+    let parent = path.parent()?.file_name()?;
+    if parent != COLLECTIONS_DIR {
+        return None;
+    }
+    path.file_stem()?.to_str()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::public_key_to_checksum_address;
+    use crate::entropy::RngMode;
+    use crate::gui::{write_wallet_row, Wallet};
+    use crate::hexutil::SecretHex;
+    use crate::saved_wallets::CsvPrivateKeys;
+    use crate::timestamps;
+    use secp256k1::{PublicKey, Secp256k1, SecretKey};
+    use crate::test_support::with_data_dir;
+    use crate::self_test::KNOWN_KEYS;
+
+    /**
+     * 集合：旧格式的集合需要先迁移，同一集合只能由一个会话追加，已有地址用于去重，列表按集合分组
+     */
+    #[test]
+    fn collections() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        with_data_dir("collections", |dir| {
+            for name in ["", "../escape", "a/b", "my collection", &"x".repeat(65)] {
+                if collection_path(dir, name).is_ok() {
+                    return Err(format!("无效的集合名称 {:?} 没有被拒绝", name));
+                }
+            }
+            let name = "my-8888-collection";
+            let path = collection_path(dir, name)?;
+            std::fs::create_dir_all(path.parent().unwrap_or(dir)).map_err(|e| e.to_string())?;
+            
+            // v1 格式（只有 address,private_key,pattern 三列）的旧集合：拒绝追加并提示迁移
+            let (key1, address1) = KNOWN_KEYS[0];
+            std::fs::write(&path, format!("address,private_key,pattern\n0x{},{},*\n", address1, key1)).map_err(|e| e.to_string())?;
+            match open(&path, name) {
+                Err(e) if e.contains("v1") && e.contains("migrate_collection") => {}
+                other => return Err(format!("追加 v1 格式的集合时结果为 {:?}，应提示迁移", other.map(|s| s.rows))),
+            }
+            let migration = migrate(&path, name)?;
+            if migration.from_version != 1 || migration.rows != 1 || !migration.backup.as_ref().is_some_and(|b| b.exists()) {
+                return Err(format!("迁移结果不符: {:?}", migration));
+            }
+            let state = open(&path, name)?;
+            let address = Address::parse(address1).map_err(|e| e.to_string())?;
+            if state.rows != 1 || !state.seen.contains(&address) {
+                return Err(format!("迁移后读取到 {} 条记录，去重集合{}包含已有地址", state.rows, if state.seen.contains(&address) { "" } else { "不" }));
+            }
+            
+            // 第二个会话追加同一集合时被拒绝，第一个会话结束后可以继续
+            let first = saved_wallets::ActiveFile::register_exclusive(&path)?;
+            if saved_wallets::ActiveFile::register_exclusive(&path).is_ok() {
+                return Err("同一集合被两个会话同时登记".to_string());
+            }
+            if migrate(&path, name).is_ok() {
+                return Err("正在追加的集合被迁移".to_string());
+            }
+            let (key2, _) = KNOWN_KEYS[1];
+            let secret = SecretKey::from_slice(&hex::decode(key2).map_err(|e| e.to_string())?).map_err(|e| e.to_string())?;
+            let wallet = Wallet {
+                index: state.max_index + 1,
+                address: Address::parse(&public_key_to_checksum_address(&PublicKey::from_secret_key(&secp, &secret))).map_err(|e| e.to_string())?,
+                private_key: SecretHex::from(key2.to_string()),
+                attempts: 1,
+                duration: 0,
+                matched_spans: Vec::new(),
+                rng_mode: RngMode::ReseededCsprng,
+                test_wallet: false,
+                mnemonic: None,
+                chain_address: None,
+                matched_pattern: String::new(),
+                created_at: timestamps::now_rfc3339(),
+            };
+            write_wallet_row(&path, &wallet, "*", Chain::Ethereum, CsvPrivateKeys::Full)?;
+            drop(first);
+            let second = saved_wallets::ActiveFile::register_exclusive(&path)?;
+            let state = open(&path, name)?;
+            drop(second);
+            if state.rows != 2 || !state.seen.contains(&wallet.address) {
+                return Err(format!("追加后集合中有 {} 条记录", state.rows));
+            }
+            
+            // 会话文件在前，集合文件按名称分组在后
+            let session = Chain::Ethereum.dir(dir).join("wallet_888_20240101_120000Z.csv");
+            std::fs::create_dir_all(session.parent().unwrap_or(dir)).map_err(|e| e.to_string())?;
+            std::fs::write(&session, format!("{}\n", saved_wallets::CSV_HEADER)).map_err(|e| e.to_string())?;
+            let listed: Vec<Option<String>> = saved_wallets::list_files(dir, None)?.into_iter().map(|f| f.collection).collect();
+            if listed != [None, Some(name.to_string())] {
+                return Err(format!("结果文件列表为 {:?}", listed));
+            }
+            Ok(())
+        })
+    }
+}
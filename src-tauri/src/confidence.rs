@@ -0,0 +1,365 @@
+use crate::engine::GenerationLimits;
+use crate::timer::{self, RateEstimator, SlidingRate};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// 会话摘要中给出的置信度里程碑
+pub const SUMMARY_MILESTONES: [f64; 3] = [0.5, 0.9, 0.99];
+
+/// 进度中 probability_next_hour 的时间窗口
+pub const NEXT_HOUR: Duration = Duration::from_secs(3600);
+
+/// 达到目标置信度的估计
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConfidenceEstimate {
+    /// 目标置信度（至少找到一个匹配的概率）
+    pub confidence: f64,
+    /// 从会话开始算起达到目标置信度所需的尝试次数
+    pub total_attempts: f64,
+    /// 还需要的尝试次数（已达到时为 0）
+    pub remaining_attempts: f64,
+    /// 按当前速度还需要的时间（毫秒，速度未知或时间超出 f64 范围时为空）
+    pub remaining_ms: Option<f64>,
+    /// 当前已达到的置信度（用于显示进度条）
+    pub current_confidence: f64,
+    /// 已尝试次数
+    pub attempts: u64,
+    /// 当前速度（每秒尝试次数，未测得时为空）
+    pub rate: Option<f64>,
+}
+
+/**
+ * n 次尝试后至少找到一个匹配的概率：1 - (1 - p)^n
+ *
+ * @param probability - 单次尝试匹配的概率
+ * @param attempts - 尝试次数
+ */
+pub fn confidence_after(probability: f64, attempts: f64) -> f64 {
+    // 概率或次数无效（含 NaN）时视为没有进展；p = 1 时 ln(1 - p) 为负无穷，单独处理
+    if probability.is_nan() || attempts.is_nan() || probability <= 0.0 || attempts <= 0.0 {
+        return 0.0;
+    }
+    if probability >= 1.0 {
+        return 1.0;
+    }
+    // p 很小时直接计算 (1 - p)^n 会丢失精度，改用 ln_1p / exp_m1
+    -(attempts * (-probability).ln_1p()).exp_m1()
+}
+
+/**
+ * 已尝试 attempts 次时至少找到一个匹配的概率（按几何分布，与 confidence_after 相同）
+ *
+ * @param probability - 单次尝试匹配的概率（score 模式和正则表达式无法估计，为空）
+ * @param attempts - 尝试次数
+ * @returns 概率；匹配概率未知或无效时为空
+ */
+pub fn cumulative_success(probability: Option<f64>, attempts: u64) -> Option<f64> {
+    let probability = probability.filter(|p| *p > 0.0 && *p <= 1.0)?;
+    Some(confidence_after(probability, attempts as f64))
+}
+
+/**
+ * 按当前速度在接下来一段时间内至少找到一个匹配的概率
+ *
+ * 每次尝试相互独立，结果与已经尝试的次数无关：已经尝试了很久仍然没有找到，并不会让接下来更容易找到。
+ *
+ * @param probability - 单次尝试匹配的概率（无法估计时为空）
+ * @param rate - 每秒尝试次数（未测得时为空）
+ * @param window - 时间窗口
+ * @returns 概率；匹配概率或速度未知时为空
+ */
+pub fn success_within(probability: Option<f64>, rate: Option<f64>, window: Duration) -> Option<f64> {
+    let probability = probability.filter(|p| *p > 0.0 && *p <= 1.0)?;
+    let rate = rate.filter(|r| r.is_finite() && *r > 0.0)?;
+    Some(confidence_after(probability, rate * window.as_secs_f64()))
+}
+
+/**
+ * 达到目标置信度所需的尝试次数：解 1 - (1 - p)^n = c，得 n = ln(1 - c) / ln(1 - p)
+ *
+ * @param probability - 单次尝试匹配的概率
+ * @param confidence - 目标置信度（0 到 1 之间，不含两端）
+ */
+pub fn attempts_for_confidence(probability: f64, confidence: f64) -> Result<f64, String> {
+    if !(confidence > 0.0 && confidence < 1.0) {
+        return Err(format!("置信度必须在 0 到 1 之间（不含两端）: {}", confidence));
+    }
+    if !(probability > 0.0 && probability <= 1.0) {
+        return Err(format!("无效的匹配概率: {}", probability));
+    }
+    if probability == 1.0 {
+        return Ok(1.0);
+    }
+    // p 接近下溢时商可能超出 f64 范围，取 f64::MAX 而不是无穷大
+    Ok(((-confidence).ln_1p() / (-probability).ln_1p()).min(f64::MAX))
+}
+
+/**
+ * 根据匹配概率、已尝试次数和速度估计达到目标置信度的剩余尝试次数和时间
+ *
+ * @param probability - 单次尝试匹配的概率
+ * @param attempts - 已尝试次数
+ * @param rate - 每秒尝试次数（未知时为空）
+ * @param confidence - 目标置信度
+ */
+pub fn estimate(probability: f64, attempts: u64, rate: Option<f64>, confidence: f64) -> Result<ConfidenceEstimate, String> {
+    let total_attempts = attempts_for_confidence(probability, confidence)?;
+    let remaining_attempts = (total_attempts - attempts as f64).max(0.0);
+    let rate = rate.filter(|r| r.is_finite() && *r > 0.0);
+    Ok(ConfidenceEstimate {
+        confidence,
+        total_attempts,
+        remaining_attempts,
+        remaining_ms: rate.map(|r| remaining_attempts / r * 1000.0).filter(|ms| ms.is_finite()),
+        current_confidence: confidence_after(probability, attempts as f64),
+        attempts,
+        rate,
+    })
+}
+
+/**
+ * 按停止条件估计会话还需要的时间：取达到最大匹配数的期望时间、用完最大尝试次数的时间和剩余的最长运行时间中最小的一个；
+ * 没有停止条件时为找到下一个匹配的期望时间
+ *
+ * @param limits - 停止条件
+ * @param probability - 单次尝试匹配的概率（无法估计时为空）
+ * @param attempts - 已尝试次数
+ * @param matches - 已找到的匹配数
+ * @param active - 运行时间（不含暂停时间）
+ * @param rate - 当前速度（每秒尝试次数，未测得时为空）
+ * @returns 剩余时间（毫秒）；匹配概率或速度未知、时间超出 f64 范围时为空
+ */
+pub fn remaining_ms(
+    limits: &GenerationLimits,
+    probability: Option<f64>,
+    attempts: u64,
+    matches: u64,
+    active: Duration,
+    rate: Option<f64>,
+) -> Option<f64> {
+    let probability = probability.filter(|p| *p > 0.0 && *p <= 1.0)?;
+    let rate = rate.filter(|r| r.is_finite() && *r > 0.0)?;
+    let attempts_left = [
+        limits.max_matches.map(|max| max.saturating_sub(matches) as f64 / probability),
+        limits.max_attempts.map(|max| max.saturating_sub(attempts) as f64),
+    ]
+    .into_iter()
+    .flatten()
+    .reduce(f64::min);
+    let time_left = limits.max_duration().map(|max| max.saturating_sub(active).as_secs_f64() * 1000.0);
+    let attempts_left = match (attempts_left, time_left) {
+        (None, None) => Some(1.0 / probability),
+        (attempts_left, _) => attempts_left,
+    };
+    [attempts_left.map(|left| left / rate * 1000.0), time_left].into_iter().flatten().reduce(f64::min).filter(|ms| ms.is_finite())
+}
+
+/**
+ * 计算会话摘要中的 50% / 90% / 99% 里程碑
+ */
+pub fn milestones(probability: f64, attempts: u64, rate: Option<f64>) -> Vec<ConfidenceEstimate> {
+    SUMMARY_MILESTONES
+        .iter()
+        .filter_map(|&c| estimate(probability, attempts, rate, c).ok())
+        .collect()
+}
+
+/// 正在运行的会话的实时统计
+struct LiveStats {
+    probability: f64,
+    attempts: u64,
+    rate: RateEstimator,
+    recent: SlidingRate,
+}
+
+/// 正在运行的会话（以会话标识为键）
+static LIVE_SESSIONS: OnceLock<Mutex<HashMap<String, LiveStats>>> = OnceLock::new();
+
+fn live_sessions() -> &'static Mutex<HashMap<String, LiveStats>> {
+    LIVE_SESSIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 会话的实时速度跟踪，离开作用域时注销
+pub struct LiveSession {
+    session_id: String,
+}
+
+impl LiveSession {
+    /**
+     * 登记会话，以便按会话标识查询置信度估计
+     *
+     * @param session_id - 会话标识
+     * @param probability - 单次尝试匹配的概率
+     */
+    pub fn new(session_id: &str, probability: f64) -> Self {
+        if let Ok(mut map) = live_sessions().lock() {
+            map.insert(
+                session_id.to_string(),
+                LiveStats {
+                    probability,
+                    attempts: 0,
+                    rate: RateEstimator::new(timer::tuning()),
+                    recent: SlidingRate::new(timer::tuning()),
+                },
+            );
+        }
+        LiveSession { session_id: session_id.to_string() }
+    }
+
+    /**
+     * 记录当前尝试次数，并更新平滑后的速度和滑动窗口内的速度
+     *
+     * 速度按运行时间计算，暂停期间运行时间不增加，不会拉低速度。测量窗口和平滑系数按启动时检测到的时钟分辨率确定，
+     * 计时器很粗（例如部分虚拟机中的 Windows）时窗口更长、平滑更强。
+     *
+     * @param attempts - 已尝试次数
+     * @param active - 会话的运行时间（不含暂停时间）
+     */
+    pub fn update(&self, attempts: u64, active: Duration) {
+        let Ok(mut map) = live_sessions().lock() else {
+            return;
+        };
+        let Some(stats) = map.get_mut(&self.session_id) else {
+            return;
+        };
+        stats.rate.update(attempts, active);
+        stats.recent.update(attempts, active);
+        stats.attempts = attempts;
+    }
+
+    /**
+     * 当前平滑后的速度（每秒尝试次数，还没有完整的测量窗口时为空）
+     */
+    pub fn rate(&self) -> Option<f64> {
+        live_sessions().lock().ok().and_then(|map| map.get(&self.session_id)?.rate.rate())
+    }
+
+    /**
+     * 最近 SLIDING_RATE_WINDOW 内的速度（每秒尝试次数，样本还不够时为空）
+     */
+    pub fn attempts_per_second(&self) -> Option<f64> {
+        live_sessions().lock().ok().and_then(|map| map.get(&self.session_id)?.recent.rate())
+    }
+
+    /**
+     * 计时精度是否不足（速度和剩余时间只是粗略估计）
+     */
+    pub fn low_precision(&self) -> bool {
+        timer::tuning().low_precision
+    }
+
+    /**
+     * 当前会话的 50% / 90% / 99% 里程碑
+     */
+    pub fn milestones(&self) -> Vec<ConfidenceEstimate> {
+        live_sessions()
+            .lock()
+            .ok()
+            .and_then(|map| {
+                let stats = map.get(&self.session_id)?;
+                Some(milestones(stats.probability, stats.attempts, stats.rate.rate()))
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for LiveSession {
+    fn drop(&mut self) {
+        if let Ok(mut map) = live_sessions().lock() {
+            map.remove(&self.session_id);
+        }
+    }
+}
+
+/**
+ * 按会话标识估计达到目标置信度还需要多久
+ *
+ * @param session_id - 正在运行的会话标识
+ * @param confidence - 目标置信度
+ */
+pub fn estimate_for_session(session_id: &str, confidence: f64) -> Result<ConfidenceEstimate, String> {
+    let map = live_sessions().lock().map_err(|_| "会话状态不可用".to_string())?;
+    let stats = map.get(session_id).ok_or_else(|| format!("会话不存在或已结束: {}", session_id))?;
+    estimate(stats.probability, stats.attempts, stats.rate.rate(), confidence)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::ParsedPattern;
+    use crate::gui::{pattern_difficulty, RateSource};
+    use crate::self_test::MATCHER_CASES;
+
+    /**
+     * 置信度计算：所需尝试次数随置信度单调递增，与期望尝试次数一致，并能往返换算
+     */
+    #[test]
+    fn confidence_math() -> Result<(), String> {
+        let levels = [0.01, 0.1, 0.5, 0.632, 0.9, 0.99, 0.999999];
+        for (pattern, _, _) in MATCHER_CASES {
+            let parsed = ParsedPattern::new(pattern);
+            let p = parsed.probability();
+            let mut previous = 0.0;
+            for &c in &levels {
+                let n = attempts_for_confidence(p, c)?;
+                if n <= previous {
+                    return Err(format!("模式 {} 在置信度 {} 时所需尝试次数没有增加", pattern, c));
+                }
+                let back = confidence_after(p, n);
+                if (back - c).abs() > 1e-9 {
+                    return Err(format!("模式 {} 置信度 {} 往返换算得到 {}", pattern, c, back));
+                }
+                previous = n;
+            }
+            // 期望尝试次数 1/p 对应的置信度为 1 - (1 - p)^(1/p)，反解应得到 1/p
+            let expected = parsed.expected_attempts();
+            let n = attempts_for_confidence(p, confidence_after(p, expected))?;
+            if ((n - expected) / expected).abs() > 1e-9 {
+                return Err(format!("模式 {} 的期望尝试次数为 {}，反解得到 {}", pattern, expected, n));
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * 进度中的成功概率：累计概率与难度估计在相同尝试次数下的成功概率一致，接下来一小时的概率按速度换算成尝试次数，
+     * 匹配概率或速度未知、无效时为空，极端的难度和速度下仍在 0 到 1 之间
+     */
+    #[test]
+    fn success_outlook() -> Result<(), String> {
+        let parsed = ParsedPattern::new("888");
+        let p = parsed.probability();
+        let budget = 4096;
+        let estimated = pattern_difficulty(&parsed, Some(budget), (1000.0, RateSource::Supplied))?.success_probability;
+        if estimated != cumulative_success(Some(p), budget) {
+            return Err(format!("难度估计的成功概率 {:?} 与进度中的累计概率不同", estimated));
+        }
+        let next_hour = success_within(Some(p), Some(2.0), NEXT_HOUR).ok_or("速度已知时没有接下来一小时的概率")?;
+        if (next_hour - confidence_after(p, 7200.0)).abs() > 1e-12 {
+            return Err(format!("每秒 2 次时接下来一小时的概率为 {}，应为 {} 次尝试的概率", next_hour, 7200));
+        }
+        let unknown = [
+            cumulative_success(None, budget),
+            cumulative_success(Some(0.0), budget),
+            cumulative_success(Some(f64::NAN), budget),
+            success_within(None, Some(1000.0), NEXT_HOUR),
+            success_within(Some(p), None, NEXT_HOUR),
+            success_within(Some(p), Some(f64::NAN), NEXT_HOUR),
+            success_within(Some(p), Some(0.0), NEXT_HOUR),
+        ];
+        if let Some(value) = unknown.iter().flatten().next() {
+            return Err(format!("匹配概率或速度未知时仍给出概率 {}", value));
+        }
+        for probability in [1.0, 0.5, (-160.0f64).exp2(), f64::MIN_POSITIVE] {
+            for rate in [1e-300, 1.0, 1e12, f64::MAX] {
+                let values = [cumulative_success(Some(probability), u64::MAX), success_within(Some(probability), Some(rate), NEXT_HOUR)];
+                if values.iter().any(|value| !value.is_some_and(|value| (0.0..=1.0).contains(&value))) {
+                    return Err(format!("匹配概率 {}、速度 {} 时的概率为 {:?}", probability, rate, values));
+                }
+            }
+        }
+        Ok(())
+    }
+}
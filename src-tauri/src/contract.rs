@@ -0,0 +1,324 @@
+use crate::address::checksum_from_bytes;
+use crate::chain::Chain;
+use crate::csv_field;
+use crate::engine::{self, GenerationLimits, KeyCandidateStats};
+use crate::entropy::{KeySource, RngMode};
+use crate::hexutil::{Address, SecretHex};
+use crate::keccak::keccak256;
+use crate::pattern::{MatchedSpan, ParsedPattern};
+use crate::stop::StopRecord;
+use secp256k1::Secp256k1;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+use zeroize::Zeroizing;
+
+/// 合约地址结果文件的 CSV 标题（结果浏览器不读取这种文件）
+pub const CSV_HEADER: &str = "contract_address,nonce,deployer_address,private_key,pattern,chain";
+
+/// 每个部署者私钥最多检查的 nonce 个数（部署前需要先用掉更小的 nonce，太大的范围没有实际意义）
+pub const MAX_NONCE_COUNT: u64 = 1 << 16;
+
+/// RLP 中 20 字节字符串的前缀（0x80 + 20）
+const RLP_ADDRESS_PREFIX: u8 = 0x94;
+
+/// 检查的 nonce 范围（包含两端）
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct NonceRange {
+    /// 第一个 nonce（默认 0，即部署者发出的第一笔交易）
+    #[serde(default)]
+    pub start: u64,
+    /// 最后一个 nonce（包含）
+    pub end: u64,
+}
+
+impl NonceRange {
+    /**
+     * 范围内的 nonce 个数
+     */
+    pub fn count(&self) -> u64 {
+        self.end.saturating_sub(self.start).saturating_add(1)
+    }
+
+    /**
+     * 检查范围：起点不大于终点，个数不超过 MAX_NONCE_COUNT
+     */
+    pub fn validate(&self) -> Result<(), String> {
+        if self.start > self.end {
+            return Err(format!("nonce_range 的 start（{}）不能大于 end（{}）", self.start, self.end));
+        }
+        if self.count() > MAX_NONCE_COUNT {
+            return Err(format!("nonce_range 最多包含 {} 个 nonce（当前为 {} 个）", MAX_NONCE_COUNT, self.count()));
+        }
+        Ok(())
+    }
+}
+
+/// 一个满足模式的合约地址
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ContractMatch {
+    /// 合约地址
+    pub contract_address: Address,
+    /// 部署合约的交易使用的 nonce（部署者必须恰好用这个 nonce 发出部署交易）
+    pub nonce: u64,
+    /// 部署者地址
+    pub deployer_address: Address,
+    /// 部署者私钥（只在保存时写入文件，不返回给界面；离开作用域时清零）
+    #[serde(skip)]
+    pub private_key: SecretHex,
+    /// 找到时已检查的合约地址数
+    pub attempts: u64,
+    /// 合约地址中满足模式的字符区间（用于界面高亮）
+    pub matched_spans: Vec<MatchedSpan>,
+}
+
+/// 搜索结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ContractSearchReport {
+    /// 已检查的合约地址数（每个部署者私钥检查 nonce 范围内的全部地址）
+    pub attempts: u64,
+    /// 已尝试的部署者私钥数
+    pub deployers: u64,
+    /// 满足模式的合约地址（按找到的顺序）
+    pub matches: Vec<ContractMatch>,
+    /// 运行时间（毫秒）
+    pub duration_ms: u64,
+    /// 达到的停止条件（被取消时为空）
+    pub stop: Option<StopRecord>,
+}
+
+/**
+ * nonce 的 RLP 编码：0 编码为空字符串（0x80），1 到 0x7f 为该字节本身，更大的值为 0x80 + 字节数，后接去掉前导零的大端字节
+ *
+ * @param nonce - 部署者的 nonce
+ */
+pub fn rlp_encode_nonce(nonce: u64) -> Vec<u8> {
+    match nonce {
+        0 => vec![0x80],
+        1..=0x7f => vec![nonce as u8],
+        _ => {
+            let bytes = nonce.to_be_bytes();
+            let significant = &bytes[nonce.leading_zeros() as usize / 8..];
+            let mut encoded = Vec::with_capacity(1 + significant.len());
+            encoded.push(0x80 + significant.len() as u8);
+            encoded.extend_from_slice(significant);
+            encoded
+        }
+    }
+}
+
+/**
+ * 部署者用 CREATE 部署的合约地址：keccak256(rlp([部署者地址, nonce])) 的后 20 字节
+ *
+ * @param deployer - 部署者地址
+ * @param nonce - 部署交易的 nonce
+ */
+pub fn create_address(deployer: &Address, nonce: u64) -> Address {
+    let nonce = rlp_encode_nonce(nonce);
+    // 列表内容最多 21 + 9 字节，总是使用短列表前缀 0xc0 + 长度
+    let mut encoded = Vec::with_capacity(2 + 20 + nonce.len());
+    encoded.push(0xc0 + (21 + nonce.len()) as u8);
+    encoded.push(RLP_ADDRESS_PREFIX);
+    encoded.extend_from_slice(deployer.as_bytes());
+    encoded.extend_from_slice(&nonce);
+    let hash = keccak256(&encoded);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    Address::from(address)
+}
+
+/**
+ * 结果文件中的一行（含换行；含有私钥，离开作用域时清零）
+ *
+ * @param found - 满足模式的合约地址
+ * @param pattern - 靓号模式
+ */
+pub fn format_row(found: &ContractMatch, pattern: &str) -> Zeroizing<String> {
+    Zeroizing::new(format!(
+        "{},{},{},{},{},{}\n",
+        found.contract_address,
+        found.nonce,
+        found.deployer_address,
+        found.private_key.expose(),
+        csv_field::escape(pattern),
+        Chain::Ethereum.as_str()
+    ))
+}
+
+/**
+ * 随机生成部署者私钥（与生成钱包使用相同的随机数来源），依次计算 nonce 范围内的合约地址并与模式比较
+ *
+ * 只在内存中计算，不写入任何文件；停止条件中的尝试次数按检查的合约地址数计算。
+ *
+ * @param parsed - 解析后的模式（与 generate_fancy_wallet 使用同一个匹配器，比较合约地址而不是部署者地址）
+ * @param nonces - 检查的 nonce 范围
+ * @param limits - 停止条件（不限制时只能通过取消标志停止）
+ * @param cancel - 取消标志（取消时提前结束并返回已有的结果）
+ * @param on_address - 每个合约地址检查后调用（已检查的地址数，满足模式时为该地址）；返回错误时停止搜索并返回该错误
+ * @returns 搜索结果
+ */
+pub fn search(
+    parsed: &ParsedPattern,
+    nonces: NonceRange,
+    limits: &GenerationLimits,
+    cancel: &AtomicBool,
+    mut on_address: impl FnMut(u64, Option<&ContractMatch>) -> Result<(), String>,
+) -> Result<ContractSearchReport, String> {
+    nonces.validate()?;
+    let start = Instant::now();
+    let secp = Secp256k1::new();
+    let mut source = KeySource::new(RngMode::default(), None);
+    let mut key_stats = KeyCandidateStats::default();
+    let mut matches = Vec::new();
+    let (mut attempts, mut deployers) = (0u64, 0u64);
+    let stop = 'search: loop {
+        let Some(secret_key) = engine::next_source_key(&mut source, &mut key_stats) else {
+            continue;
+        };
+        deployers += 1;
+        let deployer_address = Address::from_secret_key(&secp, &secret_key);
+        for nonce in nonces.start..=nonces.end {
+            if cancel.load(Ordering::SeqCst) {
+                break 'search None;
+            }
+            if let Some((reason, detail)) = limits.reached(attempts, matches.len() as u64, start.elapsed()) {
+                break 'search Some(StopRecord { reason, detail: Some(detail) });
+            }
+            attempts += 1;
+            let contract_address = create_address(&deployer_address, nonce);
+            let address_checksum = checksum_from_bytes(contract_address.as_bytes());
+            if !parsed.matches(&address_checksum) {
+                on_address(attempts, None)?;
+                continue;
+            }
+            let found = ContractMatch {
+                contract_address,
+                nonce,
+                deployer_address,
+                private_key: SecretHex::encode(&*Zeroizing::new(secret_key.secret_bytes())),
+                attempts,
+                matched_spans: parsed.matched_spans(&address_checksum).unwrap_or_default(),
+            };
+            on_address(attempts, Some(&found))?;
+            matches.push(found);
+        }
+    };
+    Ok(ContractSearchReport { attempts, deployers, matches, duration_ms: start.elapsed().as_millis() as u64, stop })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern;
+    use crate::gui::write_contract_row;
+    use crate::hexutil::PrivKeyHex;
+
+    /// nonce 的 RLP 编码：(nonce, 编码)；覆盖空字符串、单字节和多字节的边界
+    const RLP_NONCE_VECTORS: &[(u64, &[u8])] = &[
+        (0, &[0x80]),
+        (1, &[0x01]),
+        (0x7f, &[0x7f]),
+        (0x80, &[0x81, 0x80]),
+        (0xff, &[0x81, 0xff]),
+        (0x100, &[0x82, 0x01, 0x00]),
+        (446, &[0x82, 0x01, 0xbe]),
+        (u64::MAX, &[0x88, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]),
+    ];
+
+    /// 已知的 CREATE 合约地址：(部署者地址, nonce, 合约地址)
+    const CREATE_ADDRESS_VECTORS: &[(&str, u64, &str)] = &[
+        // Uniswap V2 Factory（nonce 0 编码为空字符串）
+        ("0x9C33eaCc2F50E39940D3AfaF2c7B8246B681A374", 0, "0x5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f"),
+        // Uniswap V3 Factory
+        ("0x6C9FC64A53c1b71FB3f9Af64d1ae3A4931A5f4E9", 0, "0x1F98431c8aD98523631AE4a59f267346ea31F984"),
+        // USDT
+        ("0x36928500Bc1dCd7af6a2B4008875CC336b927D57", 6, "0xdAC17F958D2ee523a2206206994597C13D831ec7"),
+        // WETH9（nonce 446 需要两个字节）
+        ("0x4F26FfBe5F04ED43630fdC30A87638d53D0b0876", 446, "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
+        // 常见的 CREATE 地址计算示例，nonce 0–3
+        ("0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0", 0, "0xcd234a471b72ba2f1ccf0a70fcaba648a5eecd8d"),
+        ("0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0", 1, "0x343c43a37d37dff08ae8c4a11544c718abb4fcf8"),
+        ("0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0", 2, "0xf778b86fa74e846c4f0a1fbd1335fe81c00a0c91"),
+        ("0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0", 3, "0xfffd933a0bc612844eaf0c6fe3e5b8e9b6c1d19c"),
+    ];
+
+    /**
+     * 合约地址：nonce 的 RLP 编码、主网合约的 CREATE 地址，以及搜索结果写入文件后可以由部署者私钥重新推导
+     */
+    #[test]
+    fn contract_addresses() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        for (nonce, expected) in RLP_NONCE_VECTORS {
+            let encoded = rlp_encode_nonce(*nonce);
+            if encoded != *expected {
+                return Err(format!("nonce {} 的 RLP 编码为 {}，期望 {}", nonce, hex::encode(&encoded), hex::encode(expected)));
+            }
+        }
+        for (deployer, nonce, expected) in CREATE_ADDRESS_VECTORS {
+            let address = create_address(&Address::parse(deployer)?, *nonce);
+            if address != Address::parse(expected)? {
+                return Err(format!("{} 以 nonce {} 部署的合约地址为 {}，期望 {}", deployer, nonce, address, expected));
+            }
+        }
+        for range in [NonceRange { start: 5, end: 4 }, NonceRange { start: 0, end: MAX_NONCE_COUNT }] {
+            if range.validate().is_ok() {
+                return Err(format!("无效的 nonce 范围 {}–{} 没有被拒绝", range.start, range.end));
+            }
+        }
+        
+        let dir = std::env::temp_dir().join(format!("fancy-wallet-test-contract-{}", std::process::id()));
+        let result = (|| {
+            let file = dir.join("contract_selftest.csv");
+            let parsed = ParsedPattern::new(&pattern::prefix_suffix_pattern("0", ""));
+            let limits = GenerationLimits { max_matches: Some(2), ..GenerationLimits::default() };
+            let mut write_error = None;
+            let report = search(&parsed, NonceRange { start: 0, end: 3 }, &limits, &AtomicBool::new(false), |_, found| {
+                if let Some(found) = found {
+                    write_error = write_contract_row(&file, found, "0").err();
+                }
+                write_error.clone().map_or(Ok(()), Err)
+            })?;
+            if report.matches.len() != 2 || report.attempts > report.deployers * 4 {
+                return Err(format!("搜索结果为 {} 个地址（检查了 {} 个部署者的 {} 个地址），期望 2 个", report.matches.len(), report.deployers, report.attempts));
+            }
+            let content = std::fs::read_to_string(&file).map_err(|e| format!("无法读取合约地址结果文件: {}", e))?;
+            let mut lines = content.lines();
+            if lines.next() != Some(CSV_HEADER) {
+                return Err("合约地址结果文件的标题不正确".to_string());
+            }
+            let rows: Vec<&str> = lines.collect();
+            if rows.len() != 2 {
+                return Err(format!("合约地址结果文件有 {} 行，期望 2 行", rows.len()));
+            }
+            for row in rows {
+                let fields: Vec<&str> = row.split(',').collect();
+                let [contract_address, nonce, deployer, private_key, "0", "ethereum"] = fields[..] else {
+                    return Err(format!("合约地址结果文件的行格式不正确: {}", fields.len()));
+                };
+                let nonce: u64 = nonce.parse().map_err(|_| "nonce 不是整数".to_string())?;
+                let derived = Address::from_secret_key(&secp, &PrivKeyHex::parse(private_key)?.secret_key());
+                if derived != Address::parse(deployer)? {
+                    return Err(format!("保存的私钥推导出 {}，而部署者地址为 {}", derived, deployer));
+                }
+                let created = create_address(&derived, nonce);
+                if created != Address::parse(contract_address)? || !parsed.matches(&created.to_checksum()) {
+                    return Err(format!("部署者 {} 以 nonce {} 部署的合约地址为 {}，文件中为 {}", deployer, nonce, created, contract_address));
+                }
+            }
+            // 私钥只用于保存，不返回给界面
+            let json = serde_json::to_string(&report).map_err(|e| e.to_string())?;
+            if json.contains(report.matches[0].private_key.expose()) {
+                return Err("搜索结果的 JSON 含有私钥".to_string());
+            }
+            Ok(())
+        })();
+        let _ = std::fs::remove_dir_all(&dir);
+        result?;
+        
+        let cancelled = search(&ParsedPattern::new("0"), NonceRange { start: 0, end: 0 }, &GenerationLimits::default(), &AtomicBool::new(true), |_, _| Ok(()))?;
+        if cancelled.attempts != 0 || cancelled.stop.is_some() {
+            return Err(format!("取消后仍检查了 {} 个合约地址", cancelled.attempts));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use std::borrow::Cow;
+
+/**
+ * CSV 字段（RFC 4180）：包含逗号、引号或换行时加引号，字段中的引号写成两个
+ */
+pub fn escape(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/**
+ * 以逗号连接的一行（不含换行），每个字段按需加引号
+ */
+pub fn join<'a>(fields: impl IntoIterator<Item = &'a str>) -> String {
+    fields.into_iter().map(escape).collect::<Vec<_>>().join(",")
+}
+
+/**
+ * 拆分一行 CSV（不含换行）：带引号的字段可以包含逗号，两个引号表示一个引号；没有引号的字段原样保留
+ *
+ * @returns 各字段；引号没有闭合或闭合的引号后面不是逗号时为空
+ */
+pub fn split(line: &str) -> Option<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        let mut field = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            loop {
+                match chars.next()? {
+                    '"' if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    }
+                    '"' => break,
+                    c => field.push(c),
+                }
+            }
+            match chars.next() {
+                None => {
+                    fields.push(field);
+                    return Some(fields);
+                }
+                Some(',') => fields.push(field),
+                Some(_) => return None,
+            }
+        } else {
+            loop {
+                match chars.next() {
+                    None => {
+                        fields.push(field);
+                        return Some(fields);
+                    }
+                    Some(',') => break,
+                    Some(c) => field.push(c),
+                }
+            }
+            fields.push(field);
+        }
+    }
+}
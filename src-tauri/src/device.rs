@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+
+/// 搜索使用的计算设备
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Device {
+    /// CPU 工作线程（默认）
+    #[default]
+    Cpu,
+    /// GPU 批量计算公钥和 Keccak-256，候选匹配交给 CPU 重新推导后才保存
+    Gpu,
+}
+
+/// 请求的设备不可用、改用其它设备时的警告（device-fallback 事件）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct DeviceFallback {
+    /// 会话标识
+    pub session_id: String,
+    /// 请求的设备
+    pub requested: Device,
+    /// 实际使用的设备
+    pub device: Device,
+    /// 原因
+    pub reason: String,
+}
+
+impl Device {
+    /**
+     * 设备名称（模板和事件中使用）
+     */
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Device::Cpu => "cpu",
+            Device::Gpu => "gpu",
+        }
+    }
+
+    /**
+     * 解析设备名称（不区分大小写）
+     */
+    pub fn parse(name: &str) -> Result<Device, String> {
+        [Device::Cpu, Device::Gpu]
+            .into_iter()
+            .find(|device| device.as_str().eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| format!("未知的计算设备: {}", name))
+    }
+}
+
+/**
+ * 查找可用的 GPU 后端
+ *
+ * 本版本没有编译 GPU 后端（wgpu 或 OpenCL 计算着色器），总是返回原因。GPU 后端只能报告候选私钥，
+ * 保存前必须由 CPU 按 engine 的推导流程重新计算地址并与模式比较，着色器的错误不会写入结果文件。
+ */
+fn detect_gpu() -> Result<Device, String> {
+    Err("此版本没有编译 GPU 后端".to_string())
+}
+
+/**
+ * 确定实际使用的计算设备：请求 GPU 但没有可用的 GPU 时回退到 CPU
+ *
+ * @param requested - 请求的设备
+ * @returns (实际使用的设备, 回退的原因)
+ */
+pub fn resolve(requested: Device) -> (Device, Option<String>) {
+    match requested {
+        Device::Cpu => (Device::Cpu, None),
+        Device::Gpu => match detect_gpu() {
+            Ok(device) => (device, None),
+            Err(reason) => (Device::Cpu, Some(format!("{}，改用 CPU 搜索", reason))),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::templates::GenerationParams;
+
+    /**
+     * OpenPGP 导出往返：加密给临时密钥并签名、读回解密比对，并确认仅签名的密钥被拒绝
+     */
+    /**
+     * 计算设备：cpu 和 gpu 都是支持的参数，其它名称被拒绝；没有 GPU 后端时请求 gpu 回退到 CPU 并给出原因
+     */
+    #[test]
+    fn device_fallback() -> Result<(), String> {
+        if resolve(Device::Cpu) != (Device::Cpu, None) {
+            return Err("请求 CPU 时不应回退".to_string());
+        }
+        let (resolved, reason) = resolve(Device::Gpu);
+        let _reason = match (resolved, reason) {
+            (Device::Gpu, None) => return Ok(()),
+            (Device::Cpu, Some(reason)) if !reason.is_empty() => reason,
+            other => return Err(format!("请求 GPU 的结果 {:?} 既不是 GPU 也不是带原因的 CPU 回退", other)),
+        };
+        for name in ["cpu", "GPU"] {
+            let params = GenerationParams { pattern: "8".to_string(), device: Some(name.to_string()), ..GenerationParams::default() };
+            params.check_capabilities()?;
+            if params.device()?.as_str() != name.to_ascii_lowercase() {
+                return Err(format!("计算设备 {} 解析的结果不正确", name));
+            }
+        }
+        let unsupported = GenerationParams { pattern: "8".to_string(), device: Some("tpu".to_string()), ..GenerationParams::default() };
+        if unsupported.check_capabilities().is_ok() || unsupported.device().is_ok() {
+            return Err("不支持的计算设备 tpu 没有被拒绝".to_string());
+        }
+        if serde_json::to_string(&Device::Gpu).map_err(|e| e.to_string())? != "\"gpu\"" {
+            return Err("计算设备序列化的名称不是 gpu".to_string());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,231 @@
+use crate::engine::GenerationLimits;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// 预计占用超过目标卷可用空间的这个比例时发出警告
+pub const DISK_WARNING_FRACTION: f64 = 0.5;
+
+/// 一次会话各种输出的大小（由各自的写入方提供，写入格式变化时自动跟随）
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct OutputSizes {
+    /// CSV 标题行（字节，新文件写入一次）
+    pub csv_header: u64,
+    /// 每个匹配的 CSV 行（字节，上限；不写入 CSV 时为 0）
+    pub csv_row: u64,
+    /// 每个匹配的 JSON Lines 行（字节，上限；不写入 JSON Lines 时为 0）
+    #[serde(default)]
+    pub jsonl_row: u64,
+    /// 每个匹配的回放事件（字节，上限）
+    pub replay_per_match: u64,
+    /// 与匹配数无关的部分：回放文件头、进度快照和清单（字节，上限）
+    pub session_overhead: u64,
+}
+
+impl OutputSizes {
+    /**
+     * 每个匹配的输出大小（字节）
+     */
+    pub fn per_match(&self) -> u64 {
+        self.csv_row + self.jsonl_row + self.replay_per_match
+    }
+}
+
+/// 开始前对磁盘占用的估计
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DiskUsageEstimate {
+    /// 按停止条件估计的匹配数（没有能限制匹配数的停止条件时为空）
+    pub expected_matches: Option<f64>,
+    /// 每个匹配的输出大小（字节）
+    pub bytes_per_match: u64,
+    /// 各种输出的大小
+    pub sizes: OutputSizes,
+    /// 预计总占用（字节，匹配数无法估计时为空）
+    pub projected_bytes: Option<u64>,
+    /// 目标卷的可用空间（字节，无法读取时为空）
+    pub free_bytes: Option<u64>,
+    /// 预计占用超过可用空间的 DISK_WARNING_FRACTION 时的警告
+    pub warning: Option<String>,
+}
+
+/**
+ * 按停止条件估计的匹配数：取最大匹配数、最大尝试次数 × 匹配概率、最长运行时间 × 速度 × 匹配概率中最小的一个
+ *
+ * @param limits - 停止条件
+ * @param probability - 单次尝试匹配的概率
+ * @param rate - 速度（每秒尝试次数，未知时不使用运行时间限制）
+ * @returns 估计的匹配数；没有能限制匹配数的停止条件时为空
+ */
+pub fn expected_matches(limits: &GenerationLimits, probability: f64, rate: Option<f64>) -> Option<f64> {
+    let bounds = [
+        limits.max_matches.map(|max| max as f64),
+        limits.max_attempts.map(|max| max as f64 * probability),
+        limits.max_duration().zip(rate).map(|(max, rate)| max.as_secs_f64() * rate * probability),
+    ];
+    bounds.into_iter().flatten().reduce(f64::min)
+}
+
+/**
+ * 估计会话的磁盘占用并与目标卷的可用空间比较
+ *
+ * @param limits - 停止条件
+ * @param probability - 单次尝试匹配的概率
+ * @param rate - 速度（每秒尝试次数，未知时为空）
+ * @param sizes - 各种输出的大小
+ * @param free_bytes - 目标卷的可用空间（无法读取时为空）
+ */
+pub fn estimate(
+    limits: &GenerationLimits,
+    probability: f64,
+    rate: Option<f64>,
+    sizes: OutputSizes,
+    free_bytes: Option<u64>,
+) -> DiskUsageEstimate {
+    let expected_matches = expected_matches(limits, probability, rate);
+    let bytes_per_match = sizes.per_match();
+    let projected_bytes = expected_matches.map(|matches| {
+        let rows = (matches.ceil() as u64).saturating_mul(bytes_per_match);
+        rows.saturating_add(sizes.csv_header).saturating_add(sizes.session_overhead)
+    });
+    let warning = match (projected_bytes, free_bytes) {
+        (Some(projected), Some(free)) if projected as f64 > free as f64 * DISK_WARNING_FRACTION => Some(format!(
+            "预计占用 {:.1} MB，超过目标卷可用空间 {:.1} MB 的 {:.0}%",
+            projected as f64 / 1e6,
+            free as f64 / 1e6,
+            DISK_WARNING_FRACTION * 100.0
+        )),
+        _ => None,
+    };
+    DiskUsageEstimate { expected_matches, bytes_per_match, sizes, projected_bytes, free_bytes, warning }
+}
+
+/**
+ * 路径本身或离它最近的已存在的上级目录（输出目录可能还没有创建）
+ */
+fn existing_ancestor(path: &Path) -> Option<&Path> {
+    path.ancestors().find(|ancestor| ancestor.exists())
+}
+
+/**
+ * Windows：GetDiskFreeSpaceExW 返回调用者可用的字节数
+ */
+#[cfg(windows)]
+pub fn free_space(path: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let dir = existing_ancestor(path)?;
+    let wide: Vec<u16> = dir.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut available = 0u64;
+    // SAFETY: wide 以 0 结尾，输出参数指向有效的 u64，其余输出参数可以为空
+    if unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut available, std::ptr::null_mut(), std::ptr::null_mut()) } == 0 {
+        return None;
+    }
+    Some(available)
+}
+
+/**
+ * 其它系统：读取 df -Pk 的 Available 列（KB）
+ */
+#[cfg(not(windows))]
+pub fn free_space(path: &Path) -> Option<u64> {
+    let dir = existing_ancestor(path)?;
+    let output = std::process::Command::new("df").arg("-Pk").arg(dir).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // 输出形如 "Filesystem 1024-blocks Used Available Capacity Mounted on"，第二行为数据
+    let text = String::from_utf8_lossy(&output.stdout);
+    let kilobytes: u64 = text.lines().nth(1)?.split_whitespace().nth(3)?.parse().ok()?;
+    Some(kilobytes.saturating_mul(1024))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::Chain;
+    use crate::entropy::RngMode;
+    use crate::gui::{csv_row_bytes, write_wallet_row, Wallet};
+    use crate::hexutil::{Address, SecretHex};
+    use crate::replay::ReplayRecorder;
+    use crate::saved_wallets::{self, CsvPrivateKeys};
+    use crate::timestamps;
+
+    /**
+     * 磁盘占用估计：几种停止条件组合下的匹配数和总占用，可用空间警告的阈值，以及大小表与实际写入的文件一致
+     */
+    #[test]
+    fn disk_estimate() -> Result<(), String> {
+        let sizes = OutputSizes { csv_header: 50, csv_row: 150, jsonl_row: 0, replay_per_match: 250, session_overhead: 10_000 };
+        let limits = |max_matches: Option<u64>, max_attempts: Option<u64>, max_duration_secs: Option<u64>| crate::engine::GenerationLimits {
+            max_matches,
+            max_attempts,
+            max_duration_secs,
+            ..Default::default()
+        };
+        // (停止条件, 匹配概率, 速度, 期望匹配数, 期望总占用)
+        let cases = [
+            (limits(Some(10), None, None), 1.0 / 16.0, None, Some(10.0), Some(10_000 + 50 + 10 * 400)),
+            (limits(None, Some(1_000_000), None), 1.0 / 16.0, None, Some(62_500.0), Some(10_050 + 62_500 * 400)),
+            (limits(Some(100), Some(1_000_000), None), 1.0 / 16.0, None, Some(100.0), Some(10_050 + 100 * 400)),
+            (limits(None, None, Some(60)), 1.0 / 256.0, Some(1000.0), Some(234.375), Some(10_050 + 235 * 400)),
+            (limits(None, None, Some(60)), 1.0 / 256.0, None, None, None),
+            (limits(None, None, None), 1.0 / 16.0, Some(1000.0), None, None),
+        ];
+        for (i, (limits, probability, rate, matches, bytes)) in cases.iter().enumerate() {
+            let estimate = estimate(limits, *probability, *rate, sizes, None);
+            if estimate.expected_matches != *matches || estimate.projected_bytes != *bytes || estimate.bytes_per_match != 400 {
+                return Err(format!("用例 {} 估计为 {:?} 个匹配、{:?} 字节，应为 {:?} 和 {:?}", i, estimate.expected_matches, estimate.projected_bytes, matches, bytes));
+            }
+        }
+        
+        // 预计占用超过可用空间的一半时警告，正好一半时不警告
+        let projected = 10_050 + 10 * 400;
+        let warned = |free: u64| estimate(&limits(Some(10), None, None), 1.0, None, sizes, Some(free)).warning.is_some();
+        if !warned(projected * 2 - 2) || warned(projected * 2) {
+            return Err("可用空间警告的阈值不正确".to_string());
+        }
+        
+        // 大小表来自写入方：实际写入的结果行和回放事件不超过估计的大小
+        let dir = std::env::temp_dir().join(format!("fancy-wallet-test-disk-{}", std::process::id()));
+        let result = (|| -> Result<(), String> {
+            std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+            let csv = dir.join("wallet_8888_selftest.csv");
+            let wallet = Wallet {
+                index: 1,
+                address: Address::from([0x88; 20]),
+                private_key: SecretHex::from("1".repeat(64)),
+                attempts: 1,
+                duration: 0,
+                matched_spans: Vec::new(),
+                rng_mode: RngMode::ReseededCsprng,
+                test_wallet: false,
+                mnemonic: None,
+                chain_address: None,
+                matched_pattern: String::new(),
+                created_at: timestamps::now_rfc3339(),
+            };
+            write_wallet_row(&csv, &wallet, "8888", Chain::Ethereum, CsvPrivateKeys::Full)?;
+            let written = std::fs::metadata(&csv).map_err(|e| e.to_string())?.len();
+            let csv_estimate = saved_wallets::CSV_HEADER.len() as u64 + 1 + csv_row_bytes("8888", Chain::Ethereum, RngMode::ReseededCsprng, None, CsvPrivateKeys::Full);
+            if written > csv_estimate {
+                return Err(format!("结果文件写入 {} 字节，超过估计的 {} 字节", written, csv_estimate));
+            }
+            
+            let mut replay = ReplayRecorder::create(&dir, "8888", Chain::Ethereum)?;
+            let before = std::fs::metadata(replay.path()).map_err(|e| e.to_string())?.len();
+            replay.record_found(wallet.address, u64::MAX, u64::MAX);
+            replay.record_progress(u64::MAX, u64::MAX, u64::MAX, true);
+            let grown = std::fs::metadata(replay.path()).map_err(|e| e.to_string())?.len() - before;
+            if grown > crate::replay::bytes_per_match() {
+                return Err(format!("一个匹配写入回放 {} 字节，超过估计的 {} 字节", grown, crate::replay::bytes_per_match()));
+            }
+            if before > crate::replay::session_overhead_bytes("8888", Chain::Ethereum) {
+                return Err("回放文件头超过估计的固定部分".to_string());
+            }
+            Ok(())
+        })();
+        let _ = std::fs::remove_dir_all(&dir);
+        result?;
+        Ok(())
+    }
+}
@@ -0,0 +1,269 @@
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// 加密结果文件的文件名后缀（wallet_<模式>_<时间>.csv.enc，结果浏览器不读取）
+pub const ENCRYPTED_SUFFIX: &str = ".enc";
+
+/// 文件第一行的格式标识
+const MAGIC: &str = "fancy-wallet-encrypted-csv";
+
+/// 加密格式版本
+const FORMAT_VERSION: u32 = 1;
+
+/// 解密时允许的 Argon2 最大内存（KiB），防止来历不明的文件耗尽内存
+const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+
+/// 解密时允许的 Argon2 最大迭代次数
+const MAX_TIME_COST: u32 = 64;
+
+/// 盐的字节数
+const SALT_LEN: usize = 16;
+
+/// AES-GCM 随机数的字节数
+const NONCE_LEN: usize = 12;
+
+/// 加密 CSV 的密钥：由密码和文件自己的盐经 Argon2id 派生，AES-256-GCM 逐行加密
+///
+/// 文件第一行是明文的格式标识和派生参数，之后每行是一条独立认证的记录（随机数:密文），
+/// 第一条记录是 CSV 标题。追加写入中断时只有最后半行无法解密，之前保存的记录不受影响。
+#[derive(Clone)]
+pub struct OutputCipher {
+    /// AES-256-GCM 密钥
+    cipher: Aes256Gcm,
+    /// 文件第一行（同时作为每条记录的附加认证数据，修改派生参数后所有记录都无法解密）
+    header: String,
+}
+
+impl std::fmt::Debug for OutputCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // 不输出密钥
+        f.debug_struct("OutputCipher").field("header", &self.header).finish_non_exhaustive()
+    }
+}
+
+impl OutputCipher {
+    /**
+     * 为一个新文件派生密钥（随机的盐，Argon2id 的默认参数）
+     *
+     * @param password - 密码（不能为空）
+     */
+    pub fn new(password: &str) -> Result<Self, String> {
+        if password.is_empty() {
+            return Err("加密结果文件的密码不能为空".to_string());
+        }
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let header = format!(
+            "{},{},argon2id,{},{},{},{}",
+            MAGIC,
+            FORMAT_VERSION,
+            Params::DEFAULT_M_COST,
+            Params::DEFAULT_T_COST,
+            Params::DEFAULT_P_COST,
+            hex::encode(salt)
+        );
+        Self::from_header(&header, password)
+    }
+
+    /**
+     * 按文件第一行中的参数派生密钥
+     *
+     * @param header - 文件第一行（不含换行）
+     * @param password - 密码
+     */
+    fn from_header(header: &str, password: &str) -> Result<Self, String> {
+        let invalid = || "不是加密的结果文件，或文件头已损坏".to_string();
+        let fields: Vec<&str> = header.split(',').collect();
+        let [magic, version, kdf, m_cost, t_cost, p_cost, salt] = fields[..] else {
+            return Err(invalid());
+        };
+        if magic != MAGIC || kdf != "argon2id" {
+            return Err(invalid());
+        }
+        if version.parse::<u32>().ok() != Some(FORMAT_VERSION) {
+            return Err(format!("不支持的加密格式版本: {}", version));
+        }
+        let parse = |value: &str| value.parse::<u32>().map_err(|_| invalid());
+        let (m_cost, t_cost, p_cost) = (parse(m_cost)?, parse(t_cost)?, parse(p_cost)?);
+        if m_cost > MAX_MEMORY_KIB || t_cost > MAX_TIME_COST {
+            return Err(format!("文件的 Argon2 参数过大: m={} KiB t={}", m_cost, t_cost));
+        }
+        let salt = hex::decode(salt).map_err(|_| invalid())?;
+        let params = Params::new(m_cost, t_cost, p_cost, Some(32)).map_err(|e| format!("无效的 Argon2 参数: {}", e))?;
+        let mut key = [0u8; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(password.as_bytes(), &salt, &mut key)
+            .map_err(|e| format!("无法派生密钥: {}", e))?;
+        let cipher = Aes256Gcm::new_from_slice(&key).map_err(|e| e.to_string())?;
+        Ok(OutputCipher { cipher, header: header.to_string() })
+    }
+
+    /**
+     * 新文件开头写入的内容：明文的文件头和加密的 CSV 标题（含换行）
+     *
+     * @param csv_header - CSV 标题行
+     */
+    pub fn file_prefix(&self, csv_header: &str) -> Result<String, String> {
+        Ok(format!("{}\n{}", self.header, self.seal(csv_header)?))
+    }
+
+    /**
+     * 加密一行 CSV（不含换行）
+     *
+     * @returns 加密后的一行（随机数:密文，含换行）
+     */
+    pub fn seal(&self, line: &str) -> Result<String, String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = self
+            .cipher
+            .encrypt(&Nonce::from(nonce), Payload { msg: line.as_bytes(), aad: self.header.as_bytes() })
+            .map_err(|_| "无法加密钱包信息".to_string())?;
+        Ok(format!("{}:{}\n", hex::encode(nonce), hex::encode(ciphertext)))
+    }
+
+    /**
+     * 解密一条记录
+     *
+     * @returns 明文；密码错误或记录被修改、不完整时为空
+     */
+    fn open(&self, record: &str) -> Option<String> {
+        let (nonce, ciphertext) = record.split_once(':')?;
+        let nonce: [u8; NONCE_LEN] = hex::decode(nonce).ok()?.try_into().ok()?;
+        let ciphertext = hex::decode(ciphertext).ok()?;
+        let plaintext = self
+            .cipher
+            .decrypt(&Nonce::from(nonce), Payload { msg: &ciphertext, aad: self.header.as_bytes() })
+            .ok()?;
+        String::from_utf8(plaintext).ok()
+    }
+}
+
+/// 解密后的结果文件
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DecryptedCsv {
+    /// CSV 标题
+    pub header: String,
+    /// 钱包记录的各行（不含标题）
+    pub rows: Vec<String>,
+    /// 最后一行不完整（写入中断），已忽略
+    pub truncated: bool,
+}
+
+/**
+ * 解密加密的结果文件
+ *
+ * 只有末尾没有换行的最后半行被视为写入中断并忽略；其它任何一行无法解密时返回错误（文件被修改）。
+ *
+ * @param path - 文件路径
+ * @param password - 密码
+ */
+pub fn decrypt_file(path: &Path, password: &str) -> Result<DecryptedCsv, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("无法读取加密的结果文件: {}", e))?;
+    let complete = content.ends_with('\n');
+    let lines: Vec<&str> = content.lines().collect();
+    let header = lines.first().ok_or("加密的结果文件为空")?;
+    let cipher = OutputCipher::from_header(header, password)?;
+    let csv_header = lines.get(1).and_then(|record| cipher.open(record)).ok_or("密码错误，或文件已损坏")?;
+    let mut truncated = false;
+    let mut rows = Vec::with_capacity(lines.len().saturating_sub(2));
+    let last = lines.len() - 1;
+    for (i, record) in lines.iter().enumerate().skip(2) {
+        match cipher.open(record) {
+            Some(row) => rows.push(row),
+            None if i == last && !complete => truncated = true,
+            None => return Err(format!("第 {} 行无法解密，文件可能被修改", i + 1)),
+        }
+    }
+    Ok(DecryptedCsv { header: csv_header, rows, truncated })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::Chain;
+    use crate::entropy::RngMode;
+    use crate::engine::{next_secret_key, KeyCandidateStats};
+    use crate::gui::{write_encrypted_wallet_row, Wallet};
+    use crate::hexutil::{Address, SecretHex};
+    use crate::saved_wallets::{self, CsvPrivateKeys};
+    use crate::timestamps;
+    use secp256k1::{PublicKey, Secp256k1};
+    use std::io::Write;
+    use crate::test_support::with_data_dir;
+
+    /**
+     * 加密的结果文件：逐行加密的记录能用密码全部解密，文件中没有明文私钥；
+     * 写入中断的最后半行被忽略，错误的密码和被修改的记录被拒绝
+     */
+    #[test]
+    fn encrypted_output() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        with_data_dir("encrypted_output", |dir| {
+            let path = dir.join(format!("wallet_8_selftest.csv{}", ENCRYPTED_SUFFIX));
+            let cipher = OutputCipher::new("correct horse")?;
+            let mut wallets = Vec::new();
+            for index in 1..=3 {
+                let key = next_secret_key(&mut OsRng, &mut KeyCandidateStats::default()).ok_or("无法生成随机私钥")?;
+                let wallet = Wallet {
+                    index,
+                    address: Address::from_public_key(&PublicKey::from_secret_key(&secp, &key)),
+                    private_key: SecretHex::encode(&key.secret_bytes()),
+                    attempts: index,
+                    duration: 0,
+                    matched_spans: Vec::new(),
+                    rng_mode: RngMode::default(),
+                    test_wallet: false,
+                    mnemonic: None,
+                    chain_address: None,
+                    matched_pattern: String::new(),
+                    created_at: timestamps::now_rfc3339(),
+                };
+                write_encrypted_wallet_row(&path, &wallet, "8", Chain::Ethereum, CsvPrivateKeys::Full, &cipher)?;
+                wallets.push(wallet);
+            }
+            let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            if let Some(wallet) = wallets.iter().find(|wallet| content.contains(wallet.private_key.expose())) {
+                return Err(format!("加密的文件中有 {} 的明文私钥", wallet.address));
+            }
+            
+            let decrypted = decrypt_file(&path, "correct horse")?;
+            let rows = saved_wallets::parse_rows(&decrypted.rows, &path, CsvPrivateKeys::Full);
+            let restored: Vec<(String, Option<u64>)> = rows.iter().map(|row| (row.private_key.clone(), row.index)).collect();
+            let expected: Vec<(String, Option<u64>)> = wallets.iter().map(|wallet| (wallet.private_key.expose().to_string(), Some(wallet.index))).collect();
+            if decrypted.header != saved_wallets::CSV_HEADER || restored != expected || decrypted.truncated {
+                return Err(format!("解密出的记录不正确: {:?}", decrypted));
+            }
+            if decrypt_file(&path, "wrong horse").is_ok() {
+                return Err("错误的密码没有被拒绝".to_string());
+            }
+            
+            // 写入中断：最后半行被忽略，之前的记录仍然可以解密
+            let partial = cipher.seal("0xdead,beef")?;
+            let mut file = std::fs::OpenOptions::new().append(true).open(&path).map_err(|e| e.to_string())?;
+            file.write_all(&partial.as_bytes()[..partial.len() / 2]).map_err(|e| e.to_string())?;
+            drop(file);
+            let interrupted = decrypt_file(&path, "correct horse")?;
+            if !interrupted.truncated || interrupted.rows != decrypted.rows {
+                return Err(format!("写入中断后解密的结果不正确: {:?}", interrupted));
+            }
+            
+            // 修改中间的一条记录
+            let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+            let record = lines[3].clone();
+            let last = record.chars().last().unwrap_or('0');
+            lines[3] = format!("{}{}", &record[..record.len() - 1], if last == '0' { '1' } else { '0' });
+            std::fs::write(&path, lines.join("\n") + "\n").map_err(|e| e.to_string())?;
+            if decrypt_file(&path, "correct horse").is_ok() {
+                return Err("被修改的记录没有被拒绝".to_string());
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,1655 @@
+use crate::bitcoin::BitcoinAddressType;
+use crate::chain::{AddressEncoder, Chain};
+use crate::entropy::{self, EntropyProvenance, KeySource, RngMode};
+use crate::hexutil::{Address, SecretHex};
+use crate::mnemonic::{self, KeySourceKind};
+use crate::pattern::{self, MatchMode, MatchedSpan, NibbleAffixes, ParsedPattern, PatternAlphabet, PatternType};
+use crate::solana;
+use crate::stop::{StopReason, StopRecord};
+use crate::worker::{self, WorkerCapacity, WorkerFailure};
+#[cfg(any(feature = "gui", test))]
+use rand::Rng;
+use secp256k1::{All, PublicKey, Scalar, Secp256k1, SecretKey, Signing};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, SyncSender};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
+
+/// 默认每隔多少次尝试发送一次进度
+pub const DEFAULT_PROGRESS_INTERVAL: u64 = 1000;
+
+/// 暂停时检查恢复的间隔
+pub(crate) const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 汇总线程没有收到消息时检查暂停、取消和运行时间限制的间隔
+const COORDINATOR_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// CPU 占用上限的最大值（百分比，不限制）
+pub const MAX_CPU_LIMIT_PERCENT: u8 = 100;
+
+/// 限制 CPU 占用时每段连续计算的时长，之后按占空比休眠
+const THROTTLE_BURST: Duration = Duration::from_millis(20);
+
+/// 限制 CPU 占用时每隔多少次尝试检查一次这一段计算的时长
+const THROTTLE_CHECK_INTERVAL: u32 = 64;
+
+/// 汇总线程发送吞吐量采样（RateSample 事件）的间隔
+pub const RATE_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 工作线程发往汇总线程的消息队列长度（回调处理不过来时工作线程等待，内存不会无限增长）
+const WORKER_QUEUE_CAPACITY: usize = 1024;
+
+/// 增量推导时每个基础私钥最多推导的候选数，用完后重新随机抽取基础私钥
+pub(crate) const INCREMENTAL_KEYS_PER_BASE: u64 = 1 << 22;
+
+/// 候选私钥被拒绝比例的警告阈值
+///
+/// 随机的 32 字节落在曲线阶之外（或为零）的概率约为 2^-128，
+/// 正常情况下永远不会发生，任何可观测到的拒绝都说明随机数来源有问题。
+const KEY_REJECTION_WARNING_RATE: f64 = 1e-12;
+
+/// 单个工作线程的候选私钥统计
+#[derive(Debug, Default, Clone)]
+pub(crate) struct KeyCandidateStats {
+    /// 被拒绝的候选数量
+    pub(crate) rejected: u64,
+    /// 是否已经发出过安全警告
+    warned: bool,
+}
+
+impl KeyCandidateStats {
+    /**
+     * 计算拒绝比例
+     *
+     * @param attempts - 总尝试次数
+     */
+    pub(crate) fn rejection_rate(&self, attempts: u64) -> f64 {
+        if attempts == 0 {
+            0.0
+        } else {
+            self.rejected as f64 / attempts as f64
+        }
+    }
+
+    /**
+     * 判断是否需要发出安全警告（每个会话只警告一次）
+     *
+     * @param attempts - 总尝试次数
+     */
+    pub(crate) fn should_warn(&mut self, attempts: u64) -> bool {
+        if !self.warned && self.rejection_rate(attempts) > KEY_REJECTION_WARNING_RATE {
+            self.warned = true;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/**
+ * 从随机数生成器中抽取一个候选私钥
+ *
+ * 落在曲线阶之外或为零的候选会被 secp256k1 拒绝，此时返回 None 并计数。
+ *
+ * @param rng - 随机数生成器
+ * @param stats - 候选私钥统计
+ * @returns 有效的私钥
+ */
+#[cfg(any(feature = "gui", test))]
+pub(crate) fn next_secret_key<R: Rng + ?Sized>(rng: &mut R, stats: &mut KeyCandidateStats) -> Option<SecretKey> {
+    let mut random_bytes = Zeroizing::new([0u8; 32]);
+    rng.fill(&mut *random_bytes);
+    candidate_key(&random_bytes, stats)
+}
+
+/**
+ * 从会话的候选私钥来源中抽取一个候选私钥（按配置的随机数来源）
+ *
+ * @param source - 候选私钥来源
+ * @param stats - 候选私钥统计
+ * @returns 有效的私钥
+ */
+pub(crate) fn next_source_key(source: &mut KeySource, stats: &mut KeyCandidateStats) -> Option<SecretKey> {
+    let mut random_bytes = Zeroizing::new([0u8; 32]);
+    source.fill_candidate(&mut random_bytes);
+    candidate_key(&random_bytes, stats)
+}
+
+/**
+ * 按配置的私钥来源抽取一个候选：直接使用随机的 32 字节，或由随机的熵生成助记词后按 m/44'/60'/0'/0/0 派生
+ *
+ * 确定性测试模式下助记词的熵带有 TESTONLY 水印，派生出的私钥没有（测试钱包按随机数来源识别）。
+ *
+ * @param config - 搜索配置
+ * @param secp - secp256k1 上下文
+ * @param source - 候选私钥来源
+ * @param stats - 候选私钥统计
+ * @returns (有效的私钥, 助记词)
+ */
+fn next_candidate(
+    config: &SearchConfig,
+    secp: &Secp256k1<All>,
+    source: &mut KeySource,
+    stats: &mut KeyCandidateStats,
+) -> Option<(SecretKey, Option<String>)> {
+    match config.key_source {
+        KeySourceKind::PrivateKey => next_source_key(source, stats).map(|key| (key, None)),
+        KeySourceKind::Mnemonic => {
+            let mut random_bytes = Zeroizing::new([0u8; 32]);
+            source.fill_candidate(&mut random_bytes);
+            let words = config.mnemonic_words.unwrap_or(mnemonic::DEFAULT_MNEMONIC_WORDS);
+            let phrase = mnemonic::from_entropy(&random_bytes[..mnemonic::entropy_len(words)]);
+            match mnemonic::derive_account(secp, &mnemonic::to_seed(&phrase, ""), 0) {
+                Some(key) => Some((key, Some(phrase))),
+                None => {
+                    stats.rejected += 1;
+                    None
+                }
+            }
+        }
+    }
+}
+
+fn candidate_key(random_bytes: &[u8; 32], stats: &mut KeyCandidateStats) -> Option<SecretKey> {
+    match SecretKey::from_slice(random_bytes) {
+        Ok(key) => Some(key),
+        Err(_) => {
+            stats.rejected += 1;
+            None
+        }
+    }
+}
+
+/// 增量推导的 secp256k1 候选：从随机的基础私钥开始，每个候选的私钥加一、公钥加上生成元 G
+///
+/// 一次点加法比完整的标量乘法（PublicKey::from_secret_key）便宜得多。基础私钥在推导 keys_per_base 个候选后，
+/// 以及每次找到匹配后重新随机抽取，因此找到的两个私钥不会只相差一个很小的偏移。
+pub(crate) struct IncrementalKeys {
+    /// 生成元 G（私钥 1 的公钥）
+    generator: PublicKey,
+    /// 每个基础私钥最多推导的候选数
+    keys_per_base: u64,
+    /// 上一个候选的私钥、公钥和当前基础私钥已推导的候选数（为空时需要抽取新的基础私钥）
+    current: Option<(SecretKey, PublicKey, u64)>,
+}
+
+impl IncrementalKeys {
+    /**
+     * @param secp - secp256k1 上下文
+     * @param keys_per_base - 每个基础私钥最多推导的候选数
+     */
+    pub(crate) fn new<C: Signing>(secp: &Secp256k1<C>, keys_per_base: u64) -> Self {
+        let one = SecretKey::from_slice(&Scalar::ONE.to_be_bytes()).expect("1 是有效的私钥");
+        IncrementalKeys { generator: PublicKey::from_secret_key(secp, &one), keys_per_base, current: None }
+    }
+
+    /**
+     * 下一个候选：上一个候选的私钥加一、公钥加 G；基础私钥用完（或结果为无穷远点）时从来源抽取新的基础私钥
+     *
+     * @param secp - secp256k1 上下文
+     * @param source - 基础私钥的来源
+     * @param stats - 候选私钥统计（只统计基础私钥）
+     * @returns (私钥, 公钥)；抽取的基础私钥被拒绝时为空
+     */
+    pub(crate) fn next<C: Signing>(
+        &mut self,
+        secp: &Secp256k1<C>,
+        source: &mut KeySource,
+        stats: &mut KeyCandidateStats,
+    ) -> Option<(SecretKey, PublicKey)> {
+        if let Some((secret_key, public_key, derived)) = self.current.take() {
+            if derived < self.keys_per_base {
+                if let (Ok(secret_key), Ok(public_key)) = (secret_key.add_tweak(&Scalar::ONE), public_key.combine(&self.generator)) {
+                    self.current = Some((secret_key, public_key, derived + 1));
+                    return Some((secret_key, public_key));
+                }
+            }
+        }
+        let secret_key = next_source_key(source, stats)?;
+        let public_key = PublicKey::from_secret_key(secp, &secret_key);
+        self.current = Some((secret_key, public_key, 1));
+        Some((secret_key, public_key))
+    }
+
+    /**
+     * 丢弃当前的基础私钥，下一个候选从新的随机基础私钥开始（找到匹配后调用）
+     */
+    pub(crate) fn rebase(&mut self) {
+        self.current = None;
+    }
+}
+
+/// 会话的停止条件
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct GenerationLimits {
+    /// 最大尝试次数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_attempts: Option<u64>,
+    /// 最大匹配数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_matches: Option<u64>,
+    /// 最长运行时间（秒，不含暂停时间）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_duration_secs: Option<u64>,
+    /// 最长运行时间（毫秒，不含暂停时间；与 max_duration_secs 同时设置时以较短的为准）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_duration_ms: Option<u64>,
+    /// 本版本不认识的字段（原样保留）
+    #[serde(flatten)]
+    pub unknown: BTreeMap<String, Value>,
+}
+
+impl GenerationLimits {
+    /**
+     * 检查是否达到任一停止条件
+     *
+     * @param attempts - 已尝试次数
+     * @param matches - 已找到的匹配数
+     * @param elapsed - 已运行时间（不含暂停时间）
+     * @returns 达到的停止条件及触发的限制值
+     */
+    pub fn reached(&self, attempts: u64, matches: u64, elapsed: Duration) -> Option<(StopReason, String)> {
+        if let Some(max) = self.max_matches.filter(|&max| matches >= max) {
+            return Some((StopReason::MaxMatches, max.to_string()));
+        }
+        if let Some(max) = self.max_attempts.filter(|&max| attempts >= max) {
+            return Some((StopReason::MaxAttempts, max.to_string()));
+        }
+        if let Some(max) = self.max_duration().filter(|&max| elapsed >= max) {
+            return Some((StopReason::MaxDuration, format_duration_limit(max)));
+        }
+        None
+    }
+
+    /**
+     * 最长运行时间（max_duration_secs 和 max_duration_ms 中较短的一个，都没有设置时为空）
+     */
+    pub fn max_duration(&self) -> Option<Duration> {
+        let secs = self.max_duration_secs.map(Duration::from_secs);
+        let ms = self.max_duration_ms.map(Duration::from_millis);
+        match (secs, ms) {
+            (Some(secs), Some(ms)) => Some(secs.min(ms)),
+            (secs, ms) => secs.or(ms),
+        }
+    }
+}
+
+/**
+ * 停止记录中的时间限制：整秒时写作 "<秒>s"，否则写作 "<毫秒>ms"
+ */
+fn format_duration_limit(max: Duration) -> String {
+    if max.subsec_millis() == 0 {
+        format!("{}s", max.as_secs())
+    } else {
+        format!("{}ms", max.as_millis())
+    }
+}
+
+/// 会话计时：分别累计运行时间和暂停时间
+///
+/// 所有方法都接收调用时的时刻，生成循环传入 Instant::now()，测试传入构造的时刻。
+#[derive(Debug, Clone)]
+pub(crate) struct SessionClock {
+    /// 会话开始时刻
+    start: Instant,
+    /// 已结束的暂停区间的总时长
+    paused: Duration,
+    /// 当前暂停区间的开始时刻（未暂停时为空）
+    paused_since: Option<Instant>,
+    /// 恢复的会话之前已运行的时间
+    carried: Duration,
+}
+
+impl SessionClock {
+    /**
+     * 开始计时
+     *
+     * @param start - 会话开始时刻
+     */
+    #[cfg(test)]
+    pub(crate) fn new(start: Instant) -> Self {
+        SessionClock::resumed(start, Duration::ZERO)
+    }
+
+    /**
+     * 恢复中断的会话时开始计时，运行时间从之前的运行时间继续累计
+     *
+     * @param start - 本次开始的时刻
+     * @param carried - 之前已运行的时间
+     */
+    pub(crate) fn resumed(start: Instant, carried: Duration) -> Self {
+        SessionClock { start, paused: Duration::ZERO, paused_since: None, carried }
+    }
+
+    /**
+     * 记录暂停状态；只有状态变化时才开始或结束一个暂停区间
+     *
+     * @param paused - 当前是否暂停
+     * @param now - 当前时刻
+     */
+    pub(crate) fn set_paused(&mut self, paused: bool, now: Instant) {
+        match (paused, self.paused_since) {
+            (true, None) => self.paused_since = Some(now),
+            (false, Some(since)) => {
+                self.paused += now.saturating_duration_since(since);
+                self.paused_since = None;
+            }
+            _ => {}
+        }
+    }
+
+    /**
+     * 到 now 为止的暂停时间
+     */
+    pub(crate) fn paused(&self, now: Instant) -> Duration {
+        self.paused + self.paused_since.map_or(Duration::ZERO, |since| now.saturating_duration_since(since))
+    }
+
+    /**
+     * 到 now 为止的运行时间（总时长减去暂停时间，加上恢复前已运行的时间）
+     */
+    pub(crate) fn active(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.start).saturating_sub(self.paused(now)) + self.carried
+    }
+}
+
+fn default_progress_interval() -> u64 {
+    DEFAULT_PROGRESS_INTERVAL
+}
+
+/// 搜索配置
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchConfig {
+    /// 靓号模式（语法与界面中的输入框相同）
+    pub pattern: String,
+    /// 停止条件（默认不限制，只能通过取消令牌停止）
+    #[serde(default)]
+    pub limits: GenerationLimits,
+    /// 每隔多少次尝试发送一次进度（0 表示只在找到匹配时发送）
+    #[serde(default = "default_progress_interval")]
+    pub progress_interval: u64,
+    /// 私钥的随机数来源（默认 reseeded-csprng）
+    #[serde(default)]
+    pub rng_mode: RngMode,
+    /// 确定性测试模式的种子（只能与 deterministic-test 一起使用，默认 0）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_seed: Option<u64>,
+    /// 工作线程数（默认使用全部 CPU 核心；确定性测试模式固定为 1 个，保证结果可复现）
+    #[serde(default, alias = "thread_count", skip_serializing_if = "Option::is_none")]
+    pub workers: Option<u32>,
+    /// 是否按模式给出的大小写与 checksum 地址比较（默认 false，只比较十六进制字符）
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// 模式类型（默认 standard；regex 时 pattern 为正则表达式，leading_zeros 时 pattern 为 zeros>=N）
+    #[serde(default)]
+    pub pattern_type: PatternType,
+    /// 比较方式（默认按模式语法决定：普通模式为 both，*...* 形式为 contains）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub match_mode: Option<MatchMode>,
+    /// 与 pattern 同时搜索的其它模式（每个候选地址依次与全部模式比较，记录第一个满足的模式）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub additional_patterns: Vec<String>,
+    /// 候选私钥的来源（默认 private_key；mnemonic 时每个候选来自新的 BIP-39 助记词，慢得多）
+    #[serde(default)]
+    pub key_source: KeySourceKind,
+    /// 助记词词数（只能与 key_source: mnemonic 一起使用，12 或 24，默认 12）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mnemonic_words: Option<u8>,
+    /// 地址所属的链（默认 ethereum；tron 时模式与 T 之后的 Base58 地址比较，只能使用 Base58 字符；
+    /// bitcoin 时与 bitcoin_address 格式的地址比较；solana 时私钥作为 ed25519 的种子，模式与整个 Base58 公钥区分大小写比较）
+    #[serde(default)]
+    pub chain: Chain,
+    /// 比特币地址的格式（默认 p2pkh，与 1 之后的 Base58 地址比较；p2wpkh 与 bc1q 之后的 Bech32 地址比较；其它链忽略）
+    #[serde(default)]
+    pub bitcoin_address: BitcoinAddressType,
+    /// 恢复中断的会话时已有的计数（尝试次数、匹配数和运行时间从这里继续累计，停止条件按累计值判断；
+    /// 私钥是随机的，随机数状态不恢复）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resume_from: Option<SearchTotals>,
+    /// 工作线程的 CPU 占用上限（默认不限制；克隆的 CpuLimit 共享同一个值，搜索运行时可以调整；不序列化）
+    #[serde(skip)]
+    pub cpu_limit: CpuLimit,
+}
+
+/// 会话到目前为止的计数
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchTotals {
+    /// 尝试次数
+    pub attempts: u64,
+    /// 匹配次数
+    pub matches: u64,
+    /// 运行时间（毫秒，不含暂停时间）
+    pub duration: u64,
+}
+
+impl SearchConfig {
+    /**
+     * 创建不带停止条件的搜索配置
+     *
+     * @param pattern - 靓号模式
+     */
+    pub fn new(pattern: impl Into<String>) -> Self {
+        SearchConfig {
+            pattern: pattern.into(),
+            limits: GenerationLimits::default(),
+            progress_interval: DEFAULT_PROGRESS_INTERVAL,
+            rng_mode: RngMode::default(),
+            test_seed: None,
+            workers: None,
+            case_sensitive: false,
+            pattern_type: PatternType::Standard,
+            match_mode: None,
+            additional_patterns: Vec::new(),
+            key_source: KeySourceKind::default(),
+            mnemonic_words: None,
+            chain: Chain::default(),
+            bitcoin_address: BitcoinAddressType::default(),
+            resume_from: None,
+            cpu_limit: CpuLimit::default(),
+        }
+    }
+
+    /**
+     * 本配置的地址编码（由链和比特币地址的格式决定；Solana 没有编码）
+     */
+    pub fn encoder(&self) -> Option<&'static dyn AddressEncoder> {
+        self.chain.address_encoder(self.bitcoin_address)
+    }
+
+    /**
+     * 按本配置的模式类型、大小写设置和比较方式解析靓号模式
+     */
+    pub fn parsed_pattern(&self) -> ParsedPattern {
+        self.parse(&self.pattern)
+    }
+
+    /**
+     * 按本配置的设置解析一个模式（可以使用的字符和是否区分大小写由链决定）
+     */
+    fn parse(&self, pattern: &str) -> ParsedPattern {
+        ParsedPattern::with_options(pattern, self.pattern_type, self.chain.case_sensitive(self.case_sensitive))
+            .with_match_mode(self.match_mode)
+            .with_alphabet(self.chain.alphabet(self.bitcoin_address))
+    }
+
+    /**
+     * 匹配函数是否需要 checksum 地址（区分大小写的模式和只看大小写的 caps_prefix 模式；否则传入小写地址，不计算 checksum）
+     */
+    fn checksum_text(&self) -> bool {
+        self.case_sensitive || self.pattern_type == PatternType::CapsPrefix
+    }
+
+    /**
+     * 本配置搜索的全部模式（pattern 在前，之后是 additional_patterns）
+     */
+    pub fn patterns(&self) -> Vec<&str> {
+        std::iter::once(self.pattern.as_str()).chain(self.additional_patterns.iter().map(String::as_str)).collect()
+    }
+
+    /**
+     * 解析本配置搜索的全部模式（顺序与 patterns 相同）
+     */
+    pub fn parsed_patterns(&self) -> Vec<ParsedPattern> {
+        self.patterns().into_iter().map(|pattern| self.parse(pattern)).collect()
+    }
+
+    /**
+     * 检查靓号模式、随机数设置、私钥来源和工作线程数是否有效
+     */
+    pub fn validate(&self) -> Result<(), String> {
+        for parsed in self.parsed_patterns() {
+            parsed.validate()?;
+        }
+        worker::validate(self.workers)?;
+        mnemonic::validate(self.key_source, self.mnemonic_words)?;
+        self.chain.validate_key_source(self.key_source)?;
+        entropy::validate(self.rng_mode, self.test_seed)
+    }
+
+    /**
+     * 实际使用的工作线程数
+     */
+    pub fn worker_count(&self) -> u32 {
+        if self.rng_mode.is_test() {
+            return 1;
+        }
+        self.workers.unwrap_or_else(worker::default_workers).clamp(1, worker::MAX_SEARCH_WORKERS)
+    }
+
+    /**
+     * 生成循环先按地址字节比较的前后缀（每个模式一个）
+     *
+     * 只有地址为 20 字节十六进制（以太坊）且全部模式都是十六进制的前后缀模式时才有，否则为空，每个候选都计算匹配文本。
+     */
+    pub fn nibble_affixes(&self) -> Option<Vec<NibbleAffixes>> {
+        if self.encoder()?.alphabet() != PatternAlphabet::Hex {
+            return None;
+        }
+        self.parsed_patterns().iter().map(ParsedPattern::nibble_affixes).collect()
+    }
+
+    /**
+     * 是否由随机的基础私钥增量推导候选（私钥来源为 private_key 的 secp256k1 链）
+     *
+     * os-direct 承诺每个候选私钥都直接读取操作系统随机数，Solana 的 ed25519 公钥不能增量推导，助记词按路径派生，都不使用增量推导。
+     */
+    pub fn incremental(&self) -> bool {
+        self.key_source == KeySourceKind::PrivateKey && self.encoder().is_some() && self.rng_mode != RngMode::OsDirect
+    }
+
+    /**
+     * 本配置的随机数来源说明
+     */
+    pub fn entropy(&self) -> EntropyProvenance {
+        EntropyProvenance::new(self.rng_mode, self.test_seed)
+    }
+
+    /**
+     * 找到一个匹配的期望尝试次数
+     */
+    pub fn expected_attempts(&self) -> f64 {
+        pattern::combined_difficulty_bits(&self.parsed_patterns()).exp2().min(f64::MAX)
+    }
+
+    /**
+     * 只看其中一个模式时，找到一个匹配的期望尝试次数
+     *
+     * @param pattern - patterns 中的一个模式
+     */
+    pub fn pattern_expected_attempts(&self, pattern: &str) -> f64 {
+        self.parse(pattern).expected_attempts()
+    }
+}
+
+/// 取消令牌：可以克隆后交给其它线程，调用 cancel 后搜索在下一次尝试前停止
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /**
+     * 创建未取消的令牌
+     */
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * 使用已有的取消标志创建令牌（与应用的全局取消标志共享状态）
+     */
+    #[cfg(feature = "gui")]
+    pub(crate) fn from_flag(flag: Arc<AtomicBool>) -> Self {
+        CancellationToken(flag)
+    }
+
+    /**
+     * 请求停止搜索
+     */
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /**
+     * 是否已经请求停止
+     */
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// 工作线程的 CPU 占用上限（百分比）：可以克隆后交给其它线程，set 之后各工作线程在当前这一段计算结束时生效
+#[derive(Debug, Clone)]
+pub struct CpuLimit(Arc<AtomicU8>);
+
+impl Default for CpuLimit {
+    fn default() -> Self {
+        CpuLimit(Arc::new(AtomicU8::new(MAX_CPU_LIMIT_PERCENT)))
+    }
+}
+
+impl CpuLimit {
+    /**
+     * 创建 CPU 占用上限
+     *
+     * @param percent - 上限（1–100，100 为不限制）
+     */
+    pub fn new(percent: u8) -> Result<Self, String> {
+        let limit = CpuLimit::default();
+        limit.set(percent)?;
+        Ok(limit)
+    }
+
+    /**
+     * 调整上限（与这个上限共享同一个值的搜索随之调整）
+     *
+     * @param percent - 上限（1–100，100 为不限制）
+     */
+    pub fn set(&self, percent: u8) -> Result<(), String> {
+        validate_cpu_limit(percent)?;
+        self.0.store(percent, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /**
+     * 当前的上限
+     */
+    pub fn percent(&self) -> u8 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/**
+ * 检查 CPU 占用上限的取值
+ *
+ * @param percent - 上限（百分比）
+ */
+pub fn validate_cpu_limit(percent: u8) -> Result<(), String> {
+    if !(1..=MAX_CPU_LIMIT_PERCENT).contains(&percent) {
+        return Err(format!("CPU 占用上限必须在 1 到 {} 之间（当前为 {}）", MAX_CPU_LIMIT_PERCENT, percent));
+    }
+    Ok(())
+}
+
+/// 工作线程的占空比：连续计算约 THROTTLE_BURST 后休眠，使计算时间约占 CPU 占用上限的比例
+///
+/// 休眠时间计入运行时间（暂停才不计入），进度和吞吐量采样中的速度就是限制后的实际速度。
+struct DutyCycle {
+    /// 这一段计算的开始时刻
+    burst_start: Instant,
+    /// 距上次检查的尝试次数
+    attempts: u32,
+}
+
+impl DutyCycle {
+    fn new() -> Self {
+        DutyCycle { burst_start: Instant::now(), attempts: 0 }
+    }
+
+    /**
+     * 每次尝试前调用：这一段计算已经足够长时按上限休眠（休眠期间仍响应取消、停止和暂停，上限调整后立即按新的上限继续）
+     *
+     * @param limit - CPU 占用上限
+     * @param interrupted - 是否应当立即结束休眠
+     */
+    fn pace(&mut self, limit: &CpuLimit, interrupted: impl Fn() -> bool) {
+        self.attempts += 1;
+        if self.attempts < THROTTLE_CHECK_INTERVAL {
+            return;
+        }
+        self.attempts = 0;
+        let percent = limit.percent();
+        if percent >= MAX_CPU_LIMIT_PERCENT {
+            // 不限制时也不断开始新的一段，之后调低上限时不会按很长的一段计算休眠
+            self.burst_start = Instant::now();
+            return;
+        }
+        let busy = self.burst_start.elapsed();
+        if busy < THROTTLE_BURST {
+            return;
+        }
+        let idle = busy.mul_f64(f64::from(MAX_CPU_LIMIT_PERCENT - percent) / f64::from(percent));
+        let wake = Instant::now() + idle;
+        while !interrupted() && limit.percent() == percent {
+            let remaining = wake.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            std::thread::sleep(remaining.min(PAUSE_POLL_INTERVAL));
+        }
+        self.burst_start = Instant::now();
+    }
+}
+
+/// 搜索进度
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchProgress {
+    /// 尝试次数
+    pub attempts: u64,
+    /// 匹配次数
+    pub matches: u64,
+    /// 运行时间（毫秒，不含暂停时间）
+    pub duration: u64,
+    /// 暂停时间（毫秒）
+    pub paused_duration: u64,
+    /// 被 secp256k1 拒绝的候选私钥数量
+    pub rejected_keys: u64,
+    /// 候选私钥被拒绝的比例
+    pub rejection_rate: f64,
+    /// 本次进度是否因找到匹配而发送
+    pub matched: bool,
+    /// leading_zeros 模式下目前见过的地址开头最多的零的个数（包括没有达到要求的候选地址；其它模式为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub best_zero_nibbles: Option<u32>,
+}
+
+/// 找到的匹配
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FoundKey {
+    /// 地址（序列化为带 0x 前缀的 checksum 格式；比特币为压缩公钥的 HASH160，由链的编码转换为地址；
+    /// Solana 为 solana::address_id，地址本身在 chain_address 中）
+    pub address: Address,
+    /// 十六进制私钥（离开作用域时清零）
+    pub private_key: SecretHex,
+    /// 找到时的尝试次数
+    pub attempts: u64,
+    /// 找到时的运行时间（毫秒，不含暂停时间）
+    pub duration: u64,
+    /// 满足的模式（同时搜索多个模式时为第一个满足的模式）
+    pub pattern: String,
+    /// 地址中满足模式的字符区间
+    pub matched_spans: Vec<MatchedSpan>,
+    /// 私钥的随机数来源
+    pub rng_mode: RngMode,
+    /// 派生出私钥的 BIP-39 助记词（只有 key_source 为 mnemonic 时才有）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mnemonic: Option<String>,
+    /// 该链文本格式的地址（只有 Solana 才有：20 字节的 address 无法还原公钥，其它链由编码转换）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_address: Option<String>,
+}
+
+/// 候选私钥对应的公钥
+enum CandidateKey {
+    /// secp256k1 公钥及计算地址的编码
+    Secp256k1(&'static dyn AddressEncoder, PublicKey),
+    /// ed25519 公钥（Solana，私钥作为种子）
+    Ed25519([u8; 32]),
+}
+
+/// 搜索过程中的事件
+pub enum SearchEvent {
+    /// 进度更新（每 progress_interval 次尝试，以及每次找到匹配后）
+    Progress(SearchProgress),
+    /// 找到匹配
+    Found(FoundKey),
+    /// 随机数生成器产生了无效的候选私钥（每次搜索最多一次）
+    RngWarning(SearchProgress),
+    /// 工作线程异常退出（panic），其余工作线程继续搜索
+    WorkerFailed(WorkerFailure),
+    /// 吞吐量采样（每 RATE_SAMPLE_INTERVAL 一次，由汇总线程读取计数，不经过工作线程）
+    RateSample(RateSample),
+}
+
+/// 一次吞吐量采样：与上一次采样之间的速度（暂停期间为 0）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RateSample {
+    /// 采样时间（Unix 毫秒）
+    pub timestamp_ms: i64,
+    /// 与上一次采样之间每秒的尝试次数
+    pub attempts_per_second: f64,
+    /// 累计的尝试次数
+    pub attempts: u64,
+    /// 各工作线程的速度（按线程序号）
+    pub threads: Vec<ThreadRate>,
+}
+
+/// 一个工作线程在一次采样中的速度
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ThreadRate {
+    /// 工作线程序号
+    pub worker: u32,
+    /// 这次搜索中该线程累计的尝试次数
+    pub attempts: u64,
+    /// 与上一次采样之间每秒的尝试次数
+    pub attempts_per_second: f64,
+    /// 这次搜索中该线程找到的匹配数
+    pub matches: u64,
+}
+
+/// 搜索报告
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchReport {
+    /// 尝试次数
+    pub attempts: u64,
+    /// 匹配次数
+    pub matches: u64,
+    /// 运行时间（毫秒，不含暂停时间）
+    pub duration: u64,
+    /// 暂停时间（毫秒）
+    pub paused_duration: u64,
+    /// 被 secp256k1 拒绝的候选私钥数量
+    pub rejected_keys: u64,
+    /// 达到的停止条件（通过取消令牌停止时为空）
+    pub stop: Option<StopRecord>,
+    /// 最后找到的匹配
+    pub last_found: Option<FoundKey>,
+    /// 本次运行中各模式的匹配数（按 FoundKey 的 pattern 统计，没有匹配的模式不出现；恢复前的匹配不按模式统计）
+    #[serde(default)]
+    pub pattern_matches: BTreeMap<String, u64>,
+    /// 私钥的随机数来源
+    pub entropy: EntropyProvenance,
+    /// 搜索结束时的工作线程容量
+    pub workers: WorkerCapacity,
+    /// 异常退出的工作线程
+    pub worker_failures: Vec<WorkerFailure>,
+}
+
+/// 工作线程之间共享的计数和控制标志（各工作线程的尝试次数和匹配数汇总为一个总数）
+#[derive(Default)]
+struct SharedSearch {
+    attempts: AtomicU64,
+    matches: AtomicU64,
+    paused: AtomicBool,
+    halted: AtomicBool,
+    /// leading_zeros 模式下见过的开头最多的零的个数
+    best_zero_nibbles: AtomicU32,
+    /// 各工作线程的尝试次数和匹配数（只用于吞吐量采样）
+    threads: Vec<ThreadCounter>,
+}
+
+/// 一个工作线程的计数，独占一条缓存行，各线程更新自己的计数时互不干扰
+#[derive(Default)]
+#[repr(align(64))]
+struct ThreadCounter {
+    attempts: AtomicU64,
+    matches: AtomicU64,
+}
+
+/// 汇总线程的吞吐量采样：记录上一次采样时的计数，按间隔生成 RateSample
+struct RateSampler {
+    last: Instant,
+    attempts: u64,
+    threads: Vec<u64>,
+}
+
+impl RateSampler {
+    fn new(now: Instant, shared: &SharedSearch) -> Self {
+        RateSampler { last: now, attempts: shared.attempts.load(Ordering::Relaxed), threads: vec![0; shared.threads.len()] }
+    }
+    
+    /**
+     * 距离上一次采样达到 RATE_SAMPLE_INTERVAL 时读取计数并生成采样
+     * 
+     * @param now - 当前时间
+     * @param shared - 共享的计数
+     */
+    fn sample(&mut self, now: Instant, shared: &SharedSearch) -> Option<RateSample> {
+        let elapsed = now.duration_since(self.last);
+        if elapsed < RATE_SAMPLE_INTERVAL {
+            return None;
+        }
+        let seconds = elapsed.as_secs_f64();
+        let attempts = shared.attempts.load(Ordering::Relaxed);
+        let threads = shared
+            .threads
+            .iter()
+            .zip(self.threads.iter_mut())
+            .zip(0..)
+            .map(|((counter, last), worker)| {
+                let current = counter.attempts.load(Ordering::Relaxed);
+                let rate = ThreadRate {
+                    worker,
+                    attempts: current,
+                    attempts_per_second: current.saturating_sub(*last) as f64 / seconds,
+                    matches: counter.matches.load(Ordering::Relaxed),
+                };
+                *last = current;
+                rate
+            })
+            .collect();
+        let sample = RateSample {
+            timestamp_ms: chrono::Utc::now().timestamp_millis(),
+            attempts_per_second: attempts.saturating_sub(self.attempts) as f64 / seconds,
+            attempts,
+            threads,
+        };
+        self.last = now;
+        self.attempts = attempts;
+        Some(sample)
+    }
+}
+
+/// 工作线程发往汇总线程的消息
+enum WorkerMessage {
+    /// 总尝试次数达到 progress_interval 的倍数
+    Progress(u64),
+    /// 找到匹配（运行时间由汇总线程填写）
+    Found(FoundKey),
+    /// 候选私钥被 secp256k1 拒绝
+    Rejected,
+    /// 工作线程异常退出
+    Failed(WorkerFailure),
+}
+
+/**
+ * 在上限内把共享计数加一（多个工作线程同时达到上限时只有一个成功，计数不会超过上限）
+ *
+ * @returns 加一后的值；已达到上限时为空
+ */
+fn claim(counter: &AtomicU64, max: Option<u64>) -> Option<u64> {
+    counter
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| max.is_none_or(|max| n < max).then_some(n + 1))
+        .ok()
+        .map(|n| n + 1)
+}
+
+fn progress(
+    attempts: u64,
+    matches: u64,
+    clock: &SessionClock,
+    key_stats: &KeyCandidateStats,
+    matched: bool,
+    best_zero_nibbles: Option<u32>,
+) -> SearchProgress {
+    let now = Instant::now();
+    SearchProgress {
+        attempts,
+        matches,
+        duration: clock.active(now).as_millis() as u64,
+        paused_duration: clock.paused(now).as_millis() as u64,
+        rejected_keys: key_stats.rejected,
+        rejection_rate: key_stats.rejection_rate(attempts),
+        matched,
+        best_zero_nibbles,
+    }
+}
+
+/**
+ * 运行一次搜索，直到达到停止条件或令牌被取消
+ *
+ * 阻塞调用线程直到搜索结束（工作线程在内部启动，事件回调都在调用线程上执行）；需要在后台运行时
+ * 由调用方放到单独的线程中，并从其它线程调用 cancel。找到的私钥只通过事件和报告返回，不写入任何文件。
+ *
+ * @param config - 搜索配置
+ * @param cancel - 取消令牌
+ * @param on_event - 进度、匹配和安全警告事件回调
+ * @returns 搜索报告；靓号模式或工作线程数无效时返回错误
+ */
+pub fn run(config: &SearchConfig, cancel: &CancellationToken, on_event: impl FnMut(SearchEvent)) -> Result<SearchReport, String> {
+    config.validate()?;
+    Ok(search(config, cancel, || false, on_event))
+}
+
+/**
+ * 搜索循环（不检查靓号模式）
+ *
+ * @param config - 搜索配置
+ * @param cancel - 取消令牌
+ * @param is_paused - 返回 true 时不消耗 CPU，等待恢复或取消
+ * @param on_event - 事件回调
+ */
+pub(crate) fn search(
+    config: &SearchConfig,
+    cancel: &CancellationToken,
+    is_paused: impl FnMut() -> bool,
+    on_event: impl FnMut(SearchEvent),
+) -> SearchReport {
+    let parsed_patterns = config.parsed_patterns();
+    let nibble_affixes = config.nibble_affixes();
+    let is_match = |address: &str| parsed_patterns.iter().any(|parsed| parsed.matches(address));
+    search_filtered(config, cancel, is_paused, is_match, nibble_affixes.as_deref(), on_event)
+}
+
+/**
+ * 单个工作线程的搜索循环：使用自己的 secp256k1 上下文和候选私钥来源，尝试次数和匹配数记入共享计数
+ *
+ * @param index - 工作线程序号（自己的尝试次数和匹配数记入 shared.threads 中的这一项）
+ * @param config - 搜索配置
+ * @param cancel - 取消令牌
+ * @param shared - 共享的计数和控制标志
+ * @param is_match - 判断地址是否匹配（传入链的匹配文本：以太坊不区分大小写时为小写地址，否则为 checksum 地址）
+ * @param nibble_affixes - 先按地址字节比较的前后缀（地址不满足任何一个时不计算匹配文本，也不调用 is_match）
+ * @param sender - 发往汇总线程的消息
+ */
+fn search_worker(
+    index: u32,
+    config: &SearchConfig,
+    cancel: &CancellationToken,
+    shared: &SharedSearch,
+    is_match: &(impl Fn(&str) -> bool + Sync),
+    nibble_affixes: Option<&[NibbleAffixes]>,
+    sender: &SyncSender<WorkerMessage>,
+) {
+    let patterns = config.patterns();
+    let parsed_patterns = config.parsed_patterns();
+    let track_zeros = config.pattern_type == PatternType::LeadingZeros;
+    let encoder = config.encoder();
+    let secp = Secp256k1::new();
+    let mut source = KeySource::new(config.rng_mode, config.test_seed);
+    let mut key_stats = KeyCandidateStats::default();
+    let mut incremental = config.incremental().then(|| IncrementalKeys::new(&secp, INCREMENTAL_KEYS_PER_BASE));
+    let mut duty = DutyCycle::new();
+    let send_progress = |attempts: u64| {
+        if config.progress_interval > 0 && attempts.is_multiple_of(config.progress_interval) {
+            let _ = sender.send(WorkerMessage::Progress(attempts));
+        }
+    };
+    loop {
+        if cancel.is_cancelled() || shared.halted.load(Ordering::Relaxed) {
+            break;
+        }
+        if shared.paused.load(Ordering::Relaxed) {
+            std::thread::sleep(PAUSE_POLL_INTERVAL);
+            // 恢复后重新开始计算一段，暂停的时间不算作计算时间
+            duty = DutyCycle::new();
+            continue;
+        }
+        duty.pace(&config.cpu_limit, || cancel.is_cancelled() || shared.halted.load(Ordering::Relaxed) || shared.paused.load(Ordering::Relaxed));
+
+        // 达到最大尝试次数后其余工作线程也停止
+        let Some(attempts) = claim(&shared.attempts, config.limits.max_attempts) else {
+            shared.halted.store(true, Ordering::Relaxed);
+            break;
+        };
+        let counter = &shared.threads[index as usize];
+        counter.attempts.fetch_add(1, Ordering::Relaxed);
+        // 由基础私钥增量推导下一个候选，或生成随机私钥（或由新的助记词派生）
+        let candidate = match incremental.as_mut() {
+            Some(keys) => keys.next(&secp, &mut source, &mut key_stats).map(|(secret_key, public_key)| (secret_key, None, Some(public_key))),
+            None => next_candidate(config, &secp, &mut source, &mut key_stats).map(|(secret_key, mnemonic)| (secret_key, mnemonic, None)),
+        };
+        let Some((secret_key, mnemonic, incremental_key)) = candidate else {
+            let _ = sender.send(WorkerMessage::Rejected);
+            continue;
+        };
+
+        // 从私钥生成公钥和地址（Solana 的地址就是整个 ed25519 公钥的 Base58 编码）
+        let (public_key, address_checksum) = match encoder {
+            Some(encoder) => {
+                let public_key = incremental_key.unwrap_or_else(|| PublicKey::from_secret_key(&secp, &secret_key));
+                // 前后缀模式先按字节比较，绝大多数地址在这里被排除，不分配十六进制文本，也不计算 checksum
+                if let Some(affixes) = nibble_affixes {
+                    let address = encoder.address(&public_key);
+                    if !affixes.iter().any(|affixes| affixes.matches(address.as_bytes())) {
+                        send_progress(attempts);
+                        continue;
+                    }
+                }
+                let text = encoder.match_text(&public_key, config.checksum_text());
+                (CandidateKey::Secp256k1(encoder, public_key), text)
+            }
+            None => {
+                let public_key = solana::public_key(&Zeroizing::new(secret_key.secret_bytes()));
+                (CandidateKey::Ed25519(public_key), solana::encode(&public_key))
+            }
+        };
+        if track_zeros {
+            shared.best_zero_nibbles.fetch_max(pattern::leading_zero_nibbles(&address_checksum) as u32, Ordering::Relaxed);
+        }
+
+        if is_match(&address_checksum) {
+            // 其它工作线程已经找满最大匹配数时丢弃这个匹配
+            let Some(matches) = claim(&shared.matches, config.limits.max_matches) else {
+                shared.halted.store(true, Ordering::Relaxed);
+                break;
+            };
+            counter.matches.fetch_add(1, Ordering::Relaxed);
+            if config.limits.max_matches == Some(matches) {
+                shared.halted.store(true, Ordering::Relaxed);
+            }
+            if let Some(keys) = incremental.as_mut() {
+                keys.rebase();
+            }
+            // 记录第一个满足的模式（注入的匹配函数与全部模式都不满足时记为第一个模式）
+            let hit = parsed_patterns.iter().position(|parsed| parsed.matches(&address_checksum)).unwrap_or(0);
+            let (address, chain_address) = match public_key {
+                CandidateKey::Secp256k1(encoder, public_key) => (encoder.address(&public_key), None),
+                CandidateKey::Ed25519(public_key) => (solana::address_id(&public_key), Some(address_checksum.clone())),
+            };
+            let _ = sender.send(WorkerMessage::Found(FoundKey {
+                address,
+                private_key: SecretHex::encode(&*Zeroizing::new(secret_key.secret_bytes())),
+                attempts,
+                duration: 0,
+                pattern: patterns[hit].to_string(),
+                matched_spans: parsed_patterns[hit].matched_spans(&address_checksum).unwrap_or_default(),
+                rng_mode: config.rng_mode,
+                mnemonic,
+                chain_address,
+            }));
+        } else {
+            send_progress(attempts);
+        }
+    }
+}
+
+/**
+ * 使用指定匹配函数的搜索循环（测试通过它注入会 panic 的匹配函数）
+ *
+ * 启动 config.worker_count() 个工作线程，调用线程负责汇总：检查暂停、取消和停止条件，
+ * 并按顺序调用 on_event，因此回调（例如写入结果文件）不会被多个线程同时调用。
+ * 每个工作线程在隔离边界内运行：panic 时发送 WorkerFailed 事件，其余工作线程继续搜索；
+ * 全部工作线程都异常退出时以 worker_failure 停止，不会无限期空转。
+ *
+ * @param config - 搜索配置
+ * @param cancel - 取消令牌
+ * @param is_paused - 返回 true 时工作线程不消耗 CPU，等待恢复或取消
+ * @param is_match - 判断 checksum 地址是否匹配（各工作线程同时调用）
+ * @param on_event - 事件回调
+ */
+#[cfg(feature = "gui")]
+pub(crate) fn search_with(
+    config: &SearchConfig,
+    cancel: &CancellationToken,
+    is_paused: impl FnMut() -> bool,
+    is_match: impl Fn(&str) -> bool + Sync,
+    on_event: impl FnMut(SearchEvent),
+) -> SearchReport {
+    search_filtered(config, cancel, is_paused, is_match, None, on_event)
+}
+
+/**
+ * 搜索循环：匹配函数之前先按地址字节比较前后缀（为空时每个候选都调用匹配函数）
+ *
+ * nibble_affixes 必须是匹配函数的必要条件（由同一组模式得到），否则会漏掉匹配。
+ */
+fn search_filtered(
+    config: &SearchConfig,
+    cancel: &CancellationToken,
+    mut is_paused: impl FnMut() -> bool,
+    is_match: impl Fn(&str) -> bool + Sync,
+    nibble_affixes: Option<&[NibbleAffixes]>,
+    mut on_event: impl FnMut(SearchEvent),
+) -> SearchReport {
+    // 恢复的会话从之前的计数继续
+    let totals = config.resume_from.unwrap_or_default();
+    let mut workers = WorkerCapacity::full(config.worker_count());
+    let shared = SharedSearch {
+        attempts: AtomicU64::new(totals.attempts),
+        matches: AtomicU64::new(totals.matches),
+        threads: (0..workers.total).map(|_| ThreadCounter::default()).collect(),
+        ..SharedSearch::default()
+    };
+    let best_zero_nibbles = || (config.pattern_type == PatternType::LeadingZeros).then(|| shared.best_zero_nibbles.load(Ordering::Relaxed));
+    let mut clock = SessionClock::resumed(Instant::now(), Duration::from_millis(totals.duration));
+    let mut key_stats = KeyCandidateStats::default();
+    let mut worker_failures = Vec::new();
+    let mut matches = totals.matches;
+    let mut last_found: Option<FoundKey> = None;
+    let mut pattern_matches = BTreeMap::new();
+    let mut reported = totals.attempts;
+    let mut stop = None;
+    let mut sampler = RateSampler::new(Instant::now(), &shared);
+
+    let (sender, receiver) = mpsc::sync_channel(WORKER_QUEUE_CAPACITY);
+    std::thread::scope(|scope| {
+        for index in 0..workers.total {
+            let sender = sender.clone();
+            let (shared, is_match) = (&shared, &is_match);
+            scope.spawn(move || {
+                if let Err(mut failure) = worker::run_isolated(index, || search_worker(index, config, cancel, shared, is_match, nibble_affixes, &sender)) {
+                    failure.attempts = shared.attempts.load(Ordering::Relaxed);
+                    let _ = sender.send(WorkerMessage::Failed(failure));
+                }
+            });
+        }
+        // 全部工作线程退出后通道关闭，汇总循环结束
+        drop(sender);
+
+        loop {
+            let now = Instant::now();
+            let paused = is_paused();
+            clock.set_paused(paused, now);
+            shared.paused.store(paused, Ordering::Relaxed);
+            if cancel.is_cancelled() {
+                shared.halted.store(true, Ordering::Relaxed);
+            } else if stop.is_none() {
+                let attempts = shared.attempts.load(Ordering::Relaxed);
+                if let Some((reason, detail)) = config.limits.reached(attempts, shared.matches.load(Ordering::Relaxed), clock.active(now)) {
+                    stop = Some(StopRecord { reason, detail: Some(detail) });
+                    shared.halted.store(true, Ordering::Relaxed);
+                }
+            }
+            if let Some(sample) = sampler.sample(now, &shared) {
+                on_event(SearchEvent::RateSample(sample));
+            }
+
+            let message = match receiver.recv_timeout(COORDINATOR_POLL_INTERVAL) {
+                Ok(message) => message,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+            match message {
+                // 各工作线程的进度消息可能乱序到达，只发送递增的尝试次数
+                WorkerMessage::Progress(attempts) if attempts > reported => {
+                    reported = attempts;
+                    on_event(SearchEvent::Progress(progress(attempts, matches, &clock, &key_stats, false, best_zero_nibbles())));
+                }
+                WorkerMessage::Progress(_) => {}
+                WorkerMessage::Found(mut found) => {
+                    matches += 1;
+                    found.duration = clock.active(Instant::now()).as_millis() as u64;
+                    reported = reported.max(found.attempts);
+                    *pattern_matches.entry(found.pattern.clone()).or_insert(0) += 1;
+                    last_found = Some(found.clone());
+                    on_event(SearchEvent::Found(found));
+                    on_event(SearchEvent::Progress(progress(reported, matches, &clock, &key_stats, true, best_zero_nibbles())));
+                }
+                WorkerMessage::Rejected => {
+                    key_stats.rejected += 1;
+                    let attempts = shared.attempts.load(Ordering::Relaxed);
+                    if key_stats.should_warn(attempts) {
+                        on_event(SearchEvent::RngWarning(progress(attempts, matches, &clock, &key_stats, false, best_zero_nibbles())));
+                    }
+                }
+                WorkerMessage::Failed(failure) => {
+                    workers.record_failure();
+                    on_event(SearchEvent::WorkerFailed(failure.clone()));
+                    worker_failures.push(failure);
+                }
+            }
+        }
+    });
+
+    let now = Instant::now();
+    let attempts = shared.attempts.load(Ordering::Relaxed);
+    let stop = if workers.alive == 0 {
+        // 没有剩余的工作线程可以继续搜索
+        Some(StopRecord { reason: StopReason::WorkerFailure, detail: Some(format!("全部 {} 个工作线程异常退出", workers.total)) })
+    } else {
+        // 工作线程在汇总线程检查之前就可能因达到最大尝试次数或匹配数而停止
+        stop.or_else(|| {
+            let (reason, detail) = config.limits.reached(attempts, matches, clock.active(now))?;
+            Some(StopRecord { reason, detail: Some(detail) })
+        })
+    };
+    // 最后一次进度事件带上最终的尝试次数（它通常不是进度间隔的整数倍）；
+    // 因时间限制停止时总是发送，让最后的进度带上实际的运行时间
+    let timed_out = stop.as_ref().is_some_and(|stop| stop.reason == StopReason::MaxDuration);
+    if attempts > reported || timed_out {
+        on_event(SearchEvent::Progress(progress(attempts, matches, &clock, &key_stats, false, best_zero_nibbles())));
+    }
+    SearchReport {
+        attempts,
+        matches,
+        duration: clock.active(now).as_millis() as u64,
+        paused_duration: clock.paused(now).as_millis() as u64,
+        rejected_keys: key_stats.rejected,
+        stop,
+        last_found,
+        pattern_matches,
+        entropy: config.entropy(),
+        workers,
+        worker_failures,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "gui")]
+    use crate::address::public_key_to_checksum_address;
+    #[cfg(feature = "gui")]
+    use crate::hexutil::PrivKeyHex;
+    use rand::RngCore;
+
+    /// 按脚本依次返回整块 32 字节的随机数生成器（用完后从头开始）
+    struct ScriptedRng {
+        blocks: Vec<[u8; 32]>,
+        next: usize,
+    }
+
+    impl ScriptedRng {
+        fn new(blocks: &[[u8; 32]]) -> Self {
+            ScriptedRng { blocks: blocks.to_vec(), next: 0 }
+        }
+    }
+
+    impl RngCore for ScriptedRng {
+        fn next_u32(&mut self) -> u32 {
+            let mut bytes = [0; 4];
+            self.fill_bytes(&mut bytes);
+            u32::from_le_bytes(bytes)
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            let mut bytes = [0; 8];
+            self.fill_bytes(&mut bytes);
+            u64::from_le_bytes(bytes)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            let block = self.blocks[self.next % self.blocks.len()];
+            self.next += 1;
+            dest.copy_from_slice(&block[..dest.len()]);
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    /// 大于曲线阶的候选（secp256k1 拒绝）
+    const ABOVE_ORDER: [u8; 32] = [0xff; 32];
+    /// 零（secp256k1 拒绝）
+    const ZERO: [u8; 32] = [0; 32];
+    /// 有效的私钥
+    const VALID: [u8; 32] = [0x11; 32];
+
+    /**
+     * 超出曲线阶和为零的候选被拒绝并计数，有效的候选原样成为私钥
+     */
+    #[test]
+    fn out_of_range_candidates_are_counted() -> Result<(), String> {
+        let mut rng = ScriptedRng::new(&[ABOVE_ORDER, VALID, ZERO, VALID]);
+        let mut stats = KeyCandidateStats::default();
+        let keys: Vec<Option<SecretKey>> = (0..4).map(|_| next_secret_key(&mut rng, &mut stats)).collect();
+        let rejected = keys.iter().filter(|key| key.is_none()).count();
+        if rejected != 2 || keys[1].map(|key| key.secret_bytes()) != Some(VALID) {
+            return Err(format!("4 个候选中有 {} 个被拒绝，应为 2 个，且第二个候选应原样成为私钥", rejected));
+        }
+        if stats.rejected != 2 || stats.rejection_rate(4) != 0.5 {
+            return Err(format!("统计到 {} 个被拒绝的候选、拒绝比例 {}，应为 2 和 0.5", stats.rejected, stats.rejection_rate(4)));
+        }
+        Ok(())
+    }
+
+    /**
+     * 拒绝比例超过阈值时发出警告，每个会话只警告一次
+     */
+    #[test]
+    fn warning_fires_once_above_threshold() -> Result<(), String> {
+        let mut rng = ScriptedRng::new(&[ABOVE_ORDER]);
+        let mut stats = KeyCandidateStats::default();
+        if next_secret_key(&mut rng, &mut stats).is_some() {
+            return Err("超出曲线阶的候选没有被拒绝".to_string());
+        }
+        if !stats.should_warn(1) {
+            return Err("拒绝比例超过阈值时没有警告".to_string());
+        }
+        if stats.should_warn(2) {
+            return Err("同一个会话警告了两次".to_string());
+        }
+        Ok(())
+    }
+
+    /**
+     * 没有被拒绝的候选时不警告（包括尝试次数为 0 时）
+     */
+    #[test]
+    fn no_warning_without_rejections() -> Result<(), String> {
+        let mut rng = ScriptedRng::new(&[VALID]);
+        let mut stats = KeyCandidateStats::default();
+        for attempt in 0..100 {
+            if next_secret_key(&mut rng, &mut stats).is_none() {
+                return Err(format!("第 {} 个有效候选被拒绝", attempt + 1));
+            }
+        }
+        if stats.rejected != 0 || stats.rejection_rate(0) != 0.0 {
+            return Err(format!("统计到 {} 个被拒绝的候选、拒绝比例 {}，应都为 0", stats.rejected, stats.rejection_rate(0)));
+        }
+        if stats.should_warn(100) {
+            return Err("没有被拒绝的候选时发出了警告".to_string());
+        }
+        Ok(())
+    }
+
+    /**
+     * 拒绝比例低于阈值时不警告，超过阈值后才警告
+     */
+    #[test]
+    fn rejection_rate_below_threshold_does_not_warn() -> Result<(), String> {
+        let mut stats = KeyCandidateStats { rejected: 1, warned: false };
+        // 1e-13 低于阈值 1e-12
+        if stats.should_warn(10_000_000_000_000) {
+            return Err("拒绝比例 1e-13 低于阈值，不应警告".to_string());
+        }
+        if !stats.should_warn(100_000_000_000) {
+            return Err("拒绝比例 1e-11 超过阈值，应当警告".to_string());
+        }
+        Ok(())
+    }
+
+    /// 确定性搜索找到的匹配：(尝试次数, 校验和地址, 私钥)
+    #[cfg(feature = "gui")]
+    type DeterministicMatch = (u64, &'static str, &'static str);
+
+    /// 种子 42 的确定性搜索向量：(模式, 是否区分大小写, 前两个匹配)；
+    /// 匹配、校验和或尝试次数的计算发生变化时这里的结果会改变
+    #[cfg(feature = "gui")]
+    const DETERMINISTIC_RUN_VECTORS: &[(&str, bool, [DeterministicMatch; 2])] = &[
+        ("0", false, [
+            (1, "0x01fdE8D0209a3e86072DD5b1eA721484cD919BA0", "544553544f4e4c597d51ad3f130af08ad13451de7160efa2b23076fd782de967"),
+            (907, "0x0Bc006618226915567d4ff2DE1E388e1dd74EaD0", "544553544f4e4c59a8810f9ea39c3a6afb780859e8d8c7bc37b78e2f9b8d6c62"),
+        ]),
+        ("A", true, [
+            (41, "0xA50C68A252994753141582276e031F863ed9B56A", "544553544f4e4c597d51ad3f130af08ad13451de7160efa2b23076fd782de98f"),
+            (515, "0xAc737777241500eD6f626281c0310463b4B7878A", "544553544f4e4c59a8810f9ea39c3a6afb780859e8d8c7bc37b78e2f9b8d6ab2"),
+        ]),
+        ("*beef*", false, [
+            (836, "0x0f189cdea0D5dA930BbEEf55C3a87beA63B84226", "544553544f4e4c597d51ad3f130af08ad13451de7160efa2b23076fd782decaa"),
+            (1625, "0xe89972ee00B0a25085834C85e7EFc2BEeFEC6ffF", "544553544f4e4c59a8810f9ea39c3a6afb780859e8d8c7bc37b78e2f9b8d6bed"),
+        ]),
+    ];
+
+    /**
+     * 会话计时：用构造的时刻模拟多次暂停和恢复，运行时间和暂停时间必须精确拆分
+     */
+    #[test]
+    fn session_clock() -> Result<(), String> {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut clock = SessionClock::new(start);
+        // (时刻, 是否暂停, 该时刻的期望运行时间, 期望暂停时间)；重复上报同一状态不应开始新的区间
+        let steps: [(u64, bool, u64, u64); 9] = [
+            (1_000, false, 1_000, 0),
+            (1_500, true, 1_500, 0),
+            (2_000, true, 1_500, 500),
+            (4_500, false, 1_500, 3_000),
+            (7_000, false, 4_000, 3_000),
+            (7_000, true, 4_000, 3_000),
+            (7_250, false, 4_000, 3_250),
+            (9_000, true, 5_750, 3_250),
+            (10_000, true, 5_750, 4_250),
+        ];
+        for (ms, paused, active, paused_total) in steps {
+            clock.set_paused(paused, at(ms));
+            let (got_active, got_paused) = (clock.active(at(ms)), clock.paused(at(ms)));
+            if got_active != Duration::from_millis(active) || got_paused != Duration::from_millis(paused_total) {
+                return Err(format!(
+                    "{} ms 时运行 {:?}、暂停 {:?}，应为 {} ms 和 {} ms",
+                    ms, got_active, got_paused, active, paused_total
+                ));
+            }
+            if got_active + got_paused != Duration::from_millis(ms) {
+                return Err(format!("{} ms 时运行时间与暂停时间之和不等于总时长", ms));
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * 多线程搜索：各工作线程的尝试次数和匹配数汇总为一个总数，停止条件不会被多个线程同时越过，
+     * 找到的匹配按顺序交给回调，取消后全部工作线程停止
+     */
+    #[test]
+    #[cfg(feature = "gui")]
+    fn parallel_search() -> Result<(), String> {
+        const WORKERS: u32 = 4;
+        const MAX_ATTEMPTS: u64 = 20_000;
+        const MAX_MATCHES: u64 = 50;
+        
+        let attempts_config = SearchConfig {
+            limits: GenerationLimits { max_attempts: Some(MAX_ATTEMPTS), ..Default::default() },
+            workers: Some(WORKERS),
+            ..SearchConfig::new("8")
+        };
+        let mut progress = Vec::new();
+        let report = search_with(&attempts_config, &CancellationToken::new(), || false, |_| false, |event| {
+            if let SearchEvent::Progress(p) = event {
+                progress.push(p.attempts);
+            }
+        });
+        if report.attempts != MAX_ATTEMPTS || report.workers.total != WORKERS || report.stop.as_ref().map(|stop| stop.reason) != Some(crate::stop::StopReason::MaxAttempts) {
+            return Err(format!("{} 个工作线程尝试 {} 次（应为 {}），停止记录 {:?}", report.workers.total, report.attempts, MAX_ATTEMPTS, report.stop));
+        }
+        if !progress.is_sorted() || progress.last() != Some(&MAX_ATTEMPTS) {
+            return Err("汇总的进度不是单调递增到最大尝试次数".to_string());
+        }
+        
+        // 最大尝试次数不是进度间隔的整数倍时，结束前仍然发送带最终尝试次数的进度事件
+        let uneven_config = SearchConfig { progress_interval: 7, ..attempts_config.clone() };
+        let mut last_progress = None;
+        let report = search_with(&uneven_config, &CancellationToken::new(), || false, |_| false, |event| {
+            if let SearchEvent::Progress(p) = event {
+                last_progress = Some(p.attempts);
+            }
+        });
+        if last_progress != Some(report.attempts) || report.attempts != MAX_ATTEMPTS {
+            return Err(format!("最后一次进度事件为 {:?}，最终尝试次数为 {}", last_progress, report.attempts));
+        }
+        
+        // 每次尝试都匹配：匹配数恰好为上限，每个匹配的尝试次数各不相同
+        let matches_config = SearchConfig {
+            limits: GenerationLimits { max_matches: Some(MAX_MATCHES), ..Default::default() },
+            ..attempts_config.clone()
+        };
+        let mut found = Vec::new();
+        let mut final_matches = None;
+        let report = search_with(&matches_config, &CancellationToken::new(), || false, |_| true, |event| match event {
+            SearchEvent::Found(key) => found.push(key.attempts),
+            SearchEvent::Progress(p) => final_matches = Some(p.matches),
+            _ => {}
+        });
+        found.sort_unstable();
+        found.dedup();
+        if report.matches != MAX_MATCHES || found.len() as u64 != MAX_MATCHES || report.stop.as_ref().map(|stop| stop.reason) != Some(crate::stop::StopReason::MaxMatches) {
+            return Err(format!("找到 {} 个匹配（{} 个不同），应为 {}", report.matches, found.len(), MAX_MATCHES));
+        }
+        if final_matches != Some(MAX_MATCHES) {
+            return Err(format!("最后一次进度事件的匹配数为 {:?}，应为 {}", final_matches, MAX_MATCHES));
+        }
+        
+        // 取消令牌让全部工作线程停止
+        let cancel = CancellationToken::new();
+        let unlimited = SearchConfig { workers: Some(WORKERS), ..SearchConfig::new("8") };
+        let report = search_with(&unlimited, &cancel, || false, |_| false, |event| {
+            if matches!(event, SearchEvent::Progress(ref p) if p.attempts >= MAX_ATTEMPTS) {
+                cancel.cancel();
+            }
+        });
+        if report.stop.is_some() || report.attempts < MAX_ATTEMPTS {
+            return Err(format!("取消后停止记录为 {:?}，尝试 {} 次", report.stop, report.attempts));
+        }
+        
+        // 确定性测试模式固定使用一个工作线程
+        let test_config = SearchConfig { rng_mode: RngMode::DeterministicTest, ..unlimited };
+        if test_config.worker_count() != 1 {
+            return Err("确定性测试模式使用了多个工作线程".to_string());
+        }
+        Ok(())
+    }
+
+    /**
+     * 增量推导：每个候选的公钥和地址与由私钥重新计算的结果一致，私钥依次加一，基础私钥按间隔和找到匹配后重新抽取；
+     * 增量推导的搜索找到的私钥可以推导出记录的地址
+     */
+    #[test]
+    #[cfg(feature = "gui")]
+    fn incremental_keys() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        const CANDIDATES: u64 = 2500;
+        const KEYS_PER_BASE: u64 = 1000;
+        const REBASE_AT: u64 = 1700;
+        let mut keys = IncrementalKeys::new(&secp, KEYS_PER_BASE);
+        let mut source = KeySource::new(RngMode::DeterministicTest, Some(31));
+        let mut stats = KeyCandidateStats::default();
+        let (mut previous, mut bases) = (None::<SecretKey>, 0);
+        for index in 0..CANDIDATES {
+            if index == REBASE_AT {
+                keys.rebase();
+            }
+            let (secret_key, public_key) = keys.next(&secp, &mut source, &mut stats).ok_or("确定性测试模式的基础私钥被拒绝")?;
+            if public_key != PublicKey::from_secret_key(&secp, &secret_key) || Address::from_public_key(&public_key) != Address::from_secret_key(&secp, &secret_key) {
+                return Err(format!("第 {} 个候选增量推导的公钥与由私钥重新计算的不一致", index));
+            }
+            if previous.and_then(|previous| previous.add_tweak(&Scalar::ONE).ok()) != Some(secret_key) {
+                bases += 1;
+            }
+            previous = Some(secret_key);
+        }
+        // 第 0、1000 和 1700（rebase）个候选使用新的基础私钥
+        if bases != 3 {
+            return Err(format!("{} 个候选使用了 {} 个基础私钥，期望 3 个", CANDIDATES, bases));
+        }
+
+        let config = SearchConfig {
+            limits: GenerationLimits { max_matches: Some(3), ..GenerationLimits::default() },
+            rng_mode: RngMode::DeterministicTest,
+            test_seed: Some(37),
+            match_mode: Some(MatchMode::Prefix),
+            ..SearchConfig::new("a")
+        };
+        let excluded = [
+            SearchConfig { rng_mode: RngMode::OsDirect, ..config.clone() },
+            SearchConfig { key_source: KeySourceKind::Mnemonic, ..config.clone() },
+            SearchConfig { chain: Chain::Solana, ..config.clone() },
+        ];
+        if !config.incremental() || excluded.iter().any(SearchConfig::incremental) {
+            return Err("只有 private_key 来源的 secp256k1 链（os-direct 以外）应使用增量推导".to_string());
+        }
+        let mut found = Vec::new();
+        run(&config, &CancellationToken::new(), |event| {
+            if let SearchEvent::Found(key) = event {
+                found.push(key);
+            }
+        })?;
+        for key in &found {
+            let secret_key = PrivKeyHex::parse(key.private_key.expose())?.secret_key();
+            if Address::from_secret_key(&secp, &secret_key) != key.address {
+                return Err(format!("私钥 {} 推导出的地址与记录的 {} 不一致", key.private_key.expose(), key.address));
+            }
+        }
+        if found.len() != 3 {
+            return Err(format!("搜索找到 {} 个地址，期望 3 个", found.len()));
+        }
+        Ok(())
+    }
+
+    /**
+     * 固定种子的搜索结果与记录的向量一致：匹配的地址、校验和大小写、私钥和尝试次数；
+     * 尝试次数上限在第二个匹配之前停止搜索
+     */
+    #[test]
+    #[cfg(feature = "gui")]
+    fn deterministic_run() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        let run = |pattern: &str, case_sensitive: bool, limits: GenerationLimits| -> Result<(Vec<FoundKey>, u64), String> {
+            let config = SearchConfig {
+                limits,
+                rng_mode: RngMode::DeterministicTest,
+                test_seed: Some(42),
+                case_sensitive,
+                ..SearchConfig::new(pattern)
+            };
+            let mut found = Vec::new();
+            let report = run(&config, &CancellationToken::new(), |event| {
+                if let SearchEvent::Found(key) = event {
+                    found.push(key);
+                }
+            })?;
+            Ok((found, report.attempts))
+        };
+        for (pattern, case_sensitive, expected) in DETERMINISTIC_RUN_VECTORS {
+            let (found, attempts) = run(pattern, *case_sensitive, GenerationLimits { max_matches: Some(2), ..Default::default() })?;
+            let actual: Vec<(u64, String, String)> = found
+                .iter()
+                .map(|key| (key.attempts, key.address.to_checksum(), key.private_key.expose().to_string()))
+                .collect();
+            let wanted: Vec<(u64, String, String)> = expected.iter().map(|(n, address, key)| (*n, address.to_string(), key.to_string())).collect();
+            if actual != wanted {
+                return Err(format!("模式 {} 的确定性搜索结果 {:?} 与向量 {:?} 不一致", pattern, actual, wanted));
+            }
+            if attempts != expected[1].0 {
+                return Err(format!("模式 {} 的尝试次数为 {}，应为 {}", pattern, attempts, expected[1].0));
+            }
+            for (_, address, key) in expected {
+                let secret = SecretKey::from_slice(&hex::decode(key).map_err(|e| e.to_string())?).map_err(|e| e.to_string())?;
+                if Some(public_key_to_checksum_address(&PublicKey::from_secret_key(&secp, &secret)).as_str()) != address.strip_prefix("0x") {
+                    return Err(format!("私钥 {} 推导的地址不是 {}", key, address));
+                }
+            }
+            
+            let cap = expected[1].0 - 1;
+            let (capped, attempts) = run(pattern, *case_sensitive, GenerationLimits { max_matches: Some(2), max_attempts: Some(cap), ..Default::default() })?;
+            if capped.len() != 1 || attempts != cap {
+                return Err(format!("模式 {} 在 {} 次尝试后停止时找到 {} 个匹配、尝试 {} 次", pattern, cap, capped.len(), attempts));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+use rand::rngs::{OsRng, StdRng};
+use rand::{RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// reseeded-csprng 模式下每抽取多少个随机私钥重新从操作系统取一次种子（增量推导时为基础私钥）
+pub const RESEED_INTERVAL_KEYS: u64 = 65_536;
+
+/// 确定性测试模式的私钥前 8 个字节（ASCII "TESTONLY"），任何导出的私钥都能据此识别为测试钱包
+pub const TEST_KEY_MARKER: [u8; 8] = *b"TESTONLY";
+
+/// 确定性测试模式写入文件名和提示中的水印
+pub const TEST_WATERMARK: &str = "TEST-ONLY";
+
+/// 私钥的随机数来源
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum RngMode {
+    /// 每个候选私钥都直接从操作系统随机数（getrandom）读取，较慢
+    OsDirect,
+    /// 以操作系统随机数为种子的 ChaCha12（rand 的 StdRng），定期重新播种（默认）
+    #[default]
+    ReseededCsprng,
+    /// 固定种子的 ChaCha12，结果可复现，只能用于测试（私钥和所有输出都带有测试水印）
+    DeterministicTest,
+}
+
+impl RngMode {
+    /**
+     * 写入 CSV 和清单中的名称
+     */
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RngMode::OsDirect => "os-direct",
+            RngMode::ReseededCsprng => "reseeded-csprng",
+            RngMode::DeterministicTest => "deterministic-test",
+        }
+    }
+
+    /**
+     * 解析 CSV 中的名称
+     */
+    pub fn parse(text: &str) -> Option<Self> {
+        [RngMode::OsDirect, RngMode::ReseededCsprng, RngMode::DeterministicTest]
+            .into_iter()
+            .find(|mode| mode.as_str() == text.trim())
+    }
+
+    /**
+     * 是否为确定性测试模式
+     */
+    pub fn is_test(self) -> bool {
+        self == RngMode::DeterministicTest
+    }
+}
+
+/// 会话的随机数来源说明（记录在会话信息、搜索报告和清单中）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct EntropyProvenance {
+    /// 随机数来源
+    pub rng_mode: RngMode,
+    /// 具体说明
+    pub source: String,
+    /// 重新播种的间隔（抽取的随机私钥数，增量推导时为基础私钥数；只有 reseeded-csprng 有）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reseed_interval_keys: Option<u64>,
+    /// 确定性测试模式的种子
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_seed: Option<u64>,
+    /// 结果是测试钱包，不能用于真实资金
+    pub test_watermark: bool,
+}
+
+impl EntropyProvenance {
+    /**
+     * 按随机数来源生成说明
+     *
+     * @param mode - 随机数来源
+     * @param test_seed - 确定性测试模式的种子（默认 0）
+     */
+    pub fn new(mode: RngMode, test_seed: Option<u64>) -> Self {
+        let seed = test_seed.unwrap_or(0);
+        let source = match mode {
+            RngMode::OsDirect => "操作系统随机数（getrandom），每个候选私钥直接读取".to_string(),
+            RngMode::ReseededCsprng => format!(
+                "ChaCha12（rand StdRng），以操作系统随机数（getrandom）为种子，每抽取 {} 个随机私钥重新播种",
+                RESEED_INTERVAL_KEYS
+            ),
+            RngMode::DeterministicTest => format!(
+                "{}：固定种子 {} 的 ChaCha12（rand StdRng），私钥以 \"TESTONLY\" 开头，结果可复现，不得用于真实资金",
+                TEST_WATERMARK, seed
+            ),
+        };
+        EntropyProvenance {
+            rng_mode: mode,
+            source,
+            reseed_interval_keys: (mode == RngMode::ReseededCsprng).then_some(RESEED_INTERVAL_KEYS),
+            test_seed: mode.is_test().then_some(seed),
+            test_watermark: mode.is_test(),
+        }
+    }
+}
+
+/**
+ * 检查随机数设置：种子只能与确定性测试模式一起使用
+ *
+ * @param mode - 随机数来源
+ * @param test_seed - 确定性测试模式的种子
+ */
+pub fn validate(mode: RngMode, test_seed: Option<u64>) -> Result<(), String> {
+    if test_seed.is_some() && !mode.is_test() {
+        return Err(format!("test_seed 只能与 deterministic-test 模式一起使用（当前为 {}）", mode.as_str()));
+    }
+    Ok(())
+}
+
+/**
+ * 私钥是否带有确定性测试模式的水印（前 8 个字节为 "TESTONLY"；真实私钥以此开头的概率约为 2^-64）
+ *
+ * @param private_key - 十六进制私钥（可带 0x 前缀）
+ */
+pub fn is_test_key(private_key: &str) -> bool {
+    let key = private_key.trim();
+    let key = key.strip_prefix("0x").unwrap_or(key);
+    key.get(..TEST_KEY_MARKER.len() * 2).is_some_and(|prefix| prefix.eq_ignore_ascii_case(&hex::encode(TEST_KEY_MARKER)))
+}
+
+fn os_seed() -> [u8; 32] {
+    let mut seed = [0u8; 32];
+    OsRng.fill_bytes(&mut seed);
+    seed
+}
+
+/// 搜索循环使用的候选私钥来源
+pub(crate) struct KeySource {
+    mode: RngMode,
+    rng: StdRng,
+    drawn: u64,
+}
+
+impl KeySource {
+    /**
+     * 按随机数来源创建
+     *
+     * @param mode - 随机数来源
+     * @param test_seed - 确定性测试模式的种子（默认 0）
+     */
+    pub(crate) fn new(mode: RngMode, test_seed: Option<u64>) -> Self {
+        let rng = match mode {
+            RngMode::DeterministicTest => StdRng::seed_from_u64(test_seed.unwrap_or(0)),
+            // os-direct 不使用这个生成器
+            RngMode::OsDirect | RngMode::ReseededCsprng => StdRng::from_seed(os_seed()),
+        };
+        KeySource { mode, rng, drawn: 0 }
+    }
+
+    /**
+     * 填充一个 32 字节的候选私钥
+     */
+    pub(crate) fn fill_candidate(&mut self, bytes: &mut [u8; 32]) {
+        match self.mode {
+            RngMode::OsDirect => OsRng.fill_bytes(bytes),
+            RngMode::ReseededCsprng => {
+                if self.drawn > 0 && self.drawn.is_multiple_of(RESEED_INTERVAL_KEYS) {
+                    self.rng = StdRng::from_seed(os_seed());
+                }
+                self.rng.fill_bytes(bytes);
+            }
+            RngMode::DeterministicTest => {
+                self.rng.fill_bytes(bytes);
+                // "TESTONLY" 小于曲线阶的前 8 个字节，加上水印后仍是有效的私钥
+                bytes[..TEST_KEY_MARKER.len()].copy_from_slice(&TEST_KEY_MARKER);
+            }
+        }
+        self.drawn += 1;
+    }
+}
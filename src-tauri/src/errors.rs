@@ -0,0 +1,245 @@
+use crate::hexutil::HexError;
+use crate::pattern::ParsedPattern;
+use crate::stop::StopReason;
+use crate::worker::WorkerFailure;
+use serde::Serialize;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+/// 命令返回的错误（序列化为带 kind 的对象，界面按 kind 区分错误；message 为中文说明，可以直接显示）
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WalletError {
+    /// 模式无效
+    InvalidPattern {
+        /// 说明
+        message: String,
+        /// 无效的原因（与 validate_pattern 的 error 相同）
+        reason: String,
+        /// 第一个无效字符在模式中的序号（从 1 开始；不是字符导致的错误时为空）
+        position: Option<usize>,
+    },
+    /// 无法写入结果文件或输出目录
+    SaveFailed {
+        /// 说明
+        message: String,
+        /// 无法写入的路径
+        path: String,
+        /// 底层 I/O 错误的类别（例如 permission_denied、storage_full；不是 I/O 错误时为空）
+        io_kind: Option<String>,
+    },
+    /// 生成已取消，没有找到匹配
+    Cancelled {
+        /// 说明
+        message: String,
+        /// 取消时的尝试次数
+        attempts: u64,
+        /// 取消时的匹配数
+        matches: u64,
+    },
+    /// 达到停止条件（最大尝试次数或最长运行时间）仍没有找到匹配
+    NoMatchWithinBudget {
+        /// 说明
+        message: String,
+        /// 达到的停止条件
+        reason: StopReason,
+        /// 尝试次数
+        attempts: u64,
+        /// 运行时间（毫秒，不含暂停时间）
+        duration: u64,
+    },
+    /// 全部工作线程异常退出，会话已中止
+    WorkerFailure {
+        /// 说明
+        message: String,
+        /// 异常退出的工作线程
+        failures: Vec<WorkerFailure>,
+    },
+    /// 其它 I/O 错误
+    Io {
+        /// 说明
+        message: String,
+        /// I/O 错误的类别
+        io_kind: String,
+    },
+    /// 其它错误（参数无效、状态不允许等，只有说明文本）
+    Other {
+        /// 说明
+        message: String,
+    },
+}
+
+impl WalletError {
+    /**
+     * 模式无效（位置取模式中的第一个无效字符）
+     *
+     * @param parsed - 解析后的模式
+     * @param reason - validate 返回的原因
+     */
+    pub fn invalid_pattern(parsed: &ParsedPattern, reason: String) -> Self {
+        let position = parsed.invalid_characters().first().map(|invalid| invalid.position);
+        WalletError::InvalidPattern { message: reason.clone(), reason, position }
+    }
+
+    /**
+     * 无法写入路径
+     *
+     * @param path - 无法写入的文件或目录
+     * @param error - 底层的 I/O 错误
+     */
+    pub fn save_failed(path: &Path, error: &io::Error) -> Self {
+        WalletError::SaveFailed {
+            message: format!("{} 无法写入: {}", path.display(), error),
+            path: path.display().to_string(),
+            io_kind: Some(io_kind_name(error.kind())),
+        }
+    }
+
+    /**
+     * 生成已取消，没有找到匹配
+     */
+    pub fn cancelled(attempts: u64, matches: u64) -> Self {
+        WalletError::Cancelled { message: "生成已取消，未找到匹配的钱包".to_string(), attempts, matches }
+    }
+
+    /**
+     * 达到停止条件仍没有找到匹配
+     *
+     * @param reason - 达到的停止条件
+     * @param attempts - 尝试次数
+     * @param duration - 运行时间（毫秒）
+     */
+    pub fn no_match_within_budget(reason: StopReason, attempts: u64, duration: u64) -> Self {
+        let message = match reason {
+            StopReason::MaxDuration => format!("已达到最长运行时间（运行了 {} 毫秒），尝试 {} 次，0 个匹配，未找到匹配的钱包", duration, attempts),
+            _ => format!("已尝试 {} 次（达到最大尝试次数），未找到匹配的钱包", attempts),
+        };
+        WalletError::NoMatchWithinBudget { message, reason, attempts, duration }
+    }
+
+    /**
+     * 全部工作线程异常退出
+     */
+    pub fn worker_failure(failures: Vec<WorkerFailure>) -> Self {
+        let messages: Vec<&str> = failures.iter().map(|failure| failure.message.as_str()).collect();
+        WalletError::WorkerFailure { message: format!("全部工作线程异常退出，会话已中止: {}", messages.join("; ")), failures }
+    }
+
+    /**
+     * 用于显示的说明
+     */
+    pub fn message(&self) -> &str {
+        match self {
+            WalletError::InvalidPattern { message, .. }
+            | WalletError::SaveFailed { message, .. }
+            | WalletError::Cancelled { message, .. }
+            | WalletError::NoMatchWithinBudget { message, .. }
+            | WalletError::WorkerFailure { message, .. }
+            | WalletError::Io { message, .. }
+            | WalletError::Other { message } => message,
+        }
+    }
+}
+
+impl fmt::Display for WalletError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl From<WalletError> for String {
+    fn from(error: WalletError) -> Self {
+        error.message().to_string()
+    }
+}
+
+impl From<String> for WalletError {
+    fn from(message: String) -> Self {
+        WalletError::Other { message }
+    }
+}
+
+impl From<&str> for WalletError {
+    fn from(message: &str) -> Self {
+        WalletError::Other { message: message.to_string() }
+    }
+}
+
+impl From<HexError> for WalletError {
+    fn from(error: HexError) -> Self {
+        WalletError::Other { message: error.into() }
+    }
+}
+
+impl From<io::Error> for WalletError {
+    fn from(error: io::Error) -> Self {
+        WalletError::Io { message: error.to_string(), io_kind: io_kind_name(error.kind()) }
+    }
+}
+
+/**
+ * I/O 错误类别的名称（ErrorKind 的变体名转换为 snake_case，例如 StorageFull 为 storage_full）
+ */
+pub fn io_kind_name(kind: io::ErrorKind) -> String {
+    let mut name = String::new();
+    for (i, c) in format!("{:?}", kind).chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            name.push('_');
+        }
+        name.push(c.to_ascii_lowercase());
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output_root;
+    use crate::templates::GenerationParams;
+    use crate::test_support::with_data_dir;
+
+    /**
+     * 命令的错误：序列化为带 kind 和 message 的对象，无效的模式带有第一个无效字符的位置，无法写入的目录和 I/O 错误带有错误类别，
+     * 没有匹配时按停止原因区分取消和达到停止条件
+     */
+    #[test]
+    fn wallet_errors() -> Result<(), String> {
+        let invalid = GenerationParams { pattern: "8zz".to_string(), ..GenerationParams::default() }.check_patterns();
+        let Err(WalletError::InvalidPattern { position: Some(2), .. }) = &invalid else {
+            return Err(format!("无效的模式的错误为 {:?}", invalid));
+        };
+        let value = serde_json::to_value(invalid.unwrap_err()).map_err(|e| e.to_string())?;
+        if value["kind"] != "invalid_pattern" || !value["message"].as_str().is_some_and(|message| message.contains('z')) {
+            return Err(format!("错误的序列化结果不正确: {}", value));
+        }
+        let valid = GenerationParams { pattern: "8888".to_string(), ..GenerationParams::default() };
+        if valid.check_patterns().is_err() {
+            return Err("有效的模式被拒绝".to_string());
+        }
+        
+        with_data_dir("errors", |dir| {
+            let file = dir.join("not-a-directory");
+            std::fs::write(&file, "x").map_err(|e| e.to_string())?;
+            match output_root::check_writable(&file.join("ethereum")) {
+                Err(WalletError::SaveFailed { path, io_kind: Some(_), .. }) if path.ends_with("ethereum") => {}
+                other => return Err(format!("无法写入的目录的错误为 {:?}", other)),
+            }
+            let io = WalletError::from(std::io::Error::from(std::io::ErrorKind::PermissionDenied));
+            if !matches!(&io, WalletError::Io { io_kind, .. } if io_kind == "permission_denied") {
+                return Err(format!("I/O 错误的类别为 {:?}", io));
+            }
+            
+            let cancelled = serde_json::to_value(WalletError::cancelled(100, 0)).map_err(|e| e.to_string())?;
+            let budget = serde_json::to_value(WalletError::no_match_within_budget(StopReason::MaxAttempts, 100, 5)).map_err(|e| e.to_string())?;
+            if cancelled["kind"] != "cancelled" || cancelled["attempts"] != 100 || budget["kind"] != "no_match_within_budget" || budget["reason"] != "max_attempts" {
+                return Err(format!("没有匹配时的错误不正确: {} / {}", cancelled, budget));
+            }
+            let other = WalletError::from("其它错误");
+            if String::from(other.clone()) != "其它错误" || serde_json::to_value(&other).map_err(|e| e.to_string())?["kind"] != "other" {
+                return Err(format!("其它错误的转换不正确: {:?}", other));
+            }
+            Ok(())
+        })
+    }
+}
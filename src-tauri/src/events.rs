@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// 连续发送失败多少次后认为前端已没有监听者
+const MAX_CONSECUTIVE_EMIT_FAILURES: u32 = 5;
+
+/// 正在运行的会话的重新订阅标志（以会话标识为键）
+static SUBSCRIPTIONS: OnceLock<Mutex<HashMap<String, Arc<AtomicBool>>>> = OnceLock::new();
+
+fn subscriptions() -> &'static Mutex<HashMap<String, Arc<AtomicBool>>> {
+    SUBSCRIPTIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 会话事件发送闸门
+///
+/// 连续发送失败（例如窗口已关闭、只在托盘中运行）时切换到“无监听者”模式，
+/// 此时不再构造和序列化事件，直到前端通过 resubscribe 重新注册。
+pub struct EventGate {
+    session_id: String,
+    consecutive_failures: u32,
+    no_listener: bool,
+    resumed: bool,
+    resubscribed: Arc<AtomicBool>,
+}
+
+impl EventGate {
+    /**
+     * 为会话创建发送闸门并登记，以便前端按会话标识重新订阅
+     *
+     * @param session_id - 会话标识
+     */
+    pub fn new(session_id: &str) -> Self {
+        let resubscribed = Arc::new(AtomicBool::new(false));
+        if let Ok(mut map) = subscriptions().lock() {
+            map.insert(session_id.to_string(), resubscribed.clone());
+        }
+        EventGate {
+            session_id: session_id.to_string(),
+            consecutive_failures: 0,
+            no_listener: false,
+            resumed: false,
+            resubscribed,
+        }
+    }
+
+    /**
+     * 当前是否有监听者；前端重新订阅后恢复发送，并标记需要发送追赶快照
+     *
+     * @returns 是否应当构造并发送事件
+     */
+    pub fn is_listening(&mut self) -> bool {
+        if self.resubscribed.swap(false, Ordering::SeqCst) {
+            self.no_listener = false;
+            self.resumed = true;
+            self.consecutive_failures = 0;
+        }
+        !self.no_listener
+    }
+
+    /**
+     * 是否刚刚从无监听者模式恢复（读取后清除），此时应先发送一次追赶快照
+     */
+    pub fn take_resumed(&mut self) -> bool {
+        std::mem::take(&mut self.resumed)
+    }
+
+    /**
+     * 发送事件并统计失败次数
+     *
+     * @param emit - 实际发送事件的函数
+     * @returns 是否发送成功（无监听者模式下不调用 emit，直接返回 false）
+     */
+    pub fn send<E: Display>(&mut self, emit: impl FnOnce() -> Result<(), E>) -> bool {
+        if !self.is_listening() {
+            return false;
+        }
+        match emit() {
+            Ok(()) => {
+                self.consecutive_failures = 0;
+                true
+            }
+            Err(e) => {
+                self.consecutive_failures += 1;
+                if self.consecutive_failures >= MAX_CONSECUTIVE_EMIT_FAILURES {
+                    eprintln!(
+                        "会话 {} 的事件连续发送失败 {} 次，暂停发送直到重新订阅: {}",
+                        self.session_id, self.consecutive_failures, e
+                    );
+                    self.no_listener = true;
+                }
+                false
+            }
+        }
+    }
+}
+
+impl Drop for EventGate {
+    fn drop(&mut self) {
+        if let Ok(mut map) = subscriptions().lock() {
+            map.remove(&self.session_id);
+        }
+    }
+}
+
+/**
+ * 重新订阅正在运行的会话，恢复完整的事件发送
+ *
+ * @param session_id - 会话标识
+ */
+pub fn resubscribe(session_id: &str) -> Result<(), String> {
+    let map = subscriptions().lock().map_err(|_| "会话状态不可用".to_string())?;
+    let flag = map.get(session_id).ok_or_else(|| format!("会话不存在或已结束: {}", session_id))?;
+    flag.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /**
+     * 事件闸门：发送函数连续失败 MAX_CONSECUTIVE_EMIT_FAILURES 次后不再调用它，中途成功会重新计数；
+     * 重新订阅后恢复发送并只报告一次追赶快照，闸门释放后不能再重新订阅
+     */
+    #[test]
+    fn event_gate() -> Result<(), String> {
+        let calls = Cell::new(0u32);
+        let failing = || {
+            calls.set(calls.get() + 1);
+            Err::<(), _>("窗口已关闭")
+        };
+        let mut gate = EventGate::new("test-event-gate");
+        for _ in 1..MAX_CONSECUTIVE_EMIT_FAILURES {
+            gate.send(failing);
+        }
+        if !gate.send(|| Ok::<(), String>(())) || !gate.is_listening() {
+            return Err("发送成功后仍然认为没有监听者".to_string());
+        }
+        calls.set(0);
+        for _ in 0..MAX_CONSECUTIVE_EMIT_FAILURES + 3 {
+            if gate.send(failing) {
+                return Err("失败的发送函数返回了成功".to_string());
+            }
+        }
+        if calls.get() != MAX_CONSECUTIVE_EMIT_FAILURES || gate.is_listening() {
+            return Err(format!("连续失败后发送函数被调用了 {} 次，应为 {} 次", calls.get(), MAX_CONSECUTIVE_EMIT_FAILURES));
+        }
+        if gate.take_resumed() {
+            return Err("还没有重新订阅就报告了追赶快照".to_string());
+        }
+
+        resubscribe("test-event-gate")?;
+        if !gate.is_listening() || !gate.take_resumed() || gate.take_resumed() {
+            return Err("重新订阅后没有恢复发送，或追赶快照没有只报告一次".to_string());
+        }
+        if !gate.send(|| Ok::<(), String>(())) {
+            return Err("重新订阅后发送失败".to_string());
+        }
+        drop(gate);
+        if resubscribe("test-event-gate").is_ok() || resubscribe("test-event-missing").is_ok() {
+            return Err("已经结束或不存在的会话可以重新订阅".to_string());
+        }
+        Ok(())
+    }
+}
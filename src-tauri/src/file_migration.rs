@@ -0,0 +1,246 @@
+use crate::hexutil::PrivKeyHex;
+use crate::saved_wallets::{self, CsvPrivateKeys, SavedWallet, CSV_FORMAT_VERSION, CSV_HEADER};
+use secp256k1::Secp256k1;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 改写的一个旧格式结果文件
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MigratedFile {
+    /// 结果文件路径（改写为当前格式）
+    pub file: PathBuf,
+    /// 原文件改名后的备份（<文件名>.v<版本>.bak）
+    pub backup: PathBuf,
+    /// 原文件的格式版本
+    pub from_version: u32,
+    /// 写入新文件的记录数
+    pub rows: usize,
+}
+
+/// 记录的地址与私钥推导出的地址不一致的行（文件可能已损坏）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AddressMismatch {
+    /// 原文件路径
+    pub file: PathBuf,
+    /// 行号（从 1 开始，包含标题行）
+    pub line: usize,
+    /// 记录的地址
+    pub stored_address: String,
+    /// 私钥推导出的地址
+    pub derived_address: String,
+}
+
+/// 旧结果文件的迁移结果（migrate_wallet_files 的返回值）
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct WalletFilesMigration {
+    /// 改写的文件
+    pub files: Vec<MigratedFile>,
+    /// 改写为当前格式的记录数（地址换成私钥推导出的规范格式）
+    pub migrated_rows: usize,
+    /// 无法解析、无法判断链或私钥无效而没有写入新文件的行数（原文件的备份中仍然保留）
+    pub skipped_rows: usize,
+    /// 记录的地址与私钥推导出的地址不一致的行数
+    pub mismatched_rows: usize,
+    /// 地址不一致的行（按原文写入新文件，不做修正）
+    pub mismatches: Vec<AddressMismatch>,
+    /// 没有改写的文件及原因（例如正被会话写入、没有保存完整的私钥、备份文件已存在）
+    pub warnings: Vec<String>,
+}
+
+impl WalletFilesMigration {
+    /**
+     * 计入一个改写完成的文件的结果
+     */
+    fn merge(&mut self, file: WalletFilesMigration) {
+        self.files.extend(file.files);
+        self.migrated_rows += file.migrated_rows;
+        self.skipped_rows += file.skipped_rows;
+        self.mismatched_rows += file.mismatched_rows;
+        self.mismatches.extend(file.mismatches);
+    }
+}
+
+/// 一行的迁移结果
+enum RowOutcome {
+    /// 地址与私钥一致，地址改为规范格式
+    Migrated(SavedWallet),
+    /// 地址与私钥不一致，按原文保留
+    Mismatched(SavedWallet, String),
+    /// 无法迁移
+    Skipped,
+}
+
+/**
+ * 由私钥重新推导一行记录的地址
+ */
+fn migrate_row(secp: &Secp256k1<secp256k1::SignOnly>, line: &str, file: &Path) -> RowOutcome {
+    let Some(mut wallet) = saved_wallets::parse_rows(&[line.to_string()], file, CsvPrivateKeys::Full).pop() else {
+        return RowOutcome::Skipped;
+    };
+    let (Some(chain), Ok(key)) = (wallet.chain, PrivKeyHex::parse(&wallet.private_key)) else {
+        return RowOutcome::Skipped;
+    };
+    let (derived, derived_text) = chain.derive_address(secp, &key.secret_key(), &wallet.address);
+    if chain.parse_address(&wallet.address).ok() != Some(derived) {
+        return RowOutcome::Mismatched(wallet, derived_text);
+    }
+    wallet.address = derived_text;
+    RowOutcome::Migrated(wallet)
+}
+
+/**
+ * 把一个旧格式的结果文件改写为当前格式：先写临时文件，原文件改名为备份后再换入新文件
+ *
+ * @returns 这个文件的迁移结果；格式已是最新或文件为空时为空
+ */
+fn migrate_file(secp: &Secp256k1<secp256k1::SignOnly>, file: &Path) -> Result<Option<WalletFilesMigration>, String> {
+    let content = fs::read_to_string(file).map_err(|e| format!("无法读取结果文件: {}", e))?;
+    let mut lines = content.lines();
+    let header = lines.next().unwrap_or_default();
+    let Some(from_version) = saved_wallets::csv_format_version(header) else {
+        return match CsvPrivateKeys::from_header(header) {
+            Some(layout) => Err(format!("文件没有保存完整的私钥（{}），无法重新推导地址", layout.as_str())),
+            None if header.trim().is_empty() => Ok(None),
+            None => Err(format!("无法识别 CSV 标题，可能由更新的版本写入: {}", header.trim())),
+        };
+    };
+    if from_version == CSV_FORMAT_VERSION {
+        return Ok(None);
+    }
+    let name = file.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    let backup = file.with_file_name(format!("{}.v{}.bak", name, from_version));
+    if backup.exists() {
+        return Err(format!("备份文件 {} 已存在", backup.display()));
+    }
+
+    let mut result = WalletFilesMigration::default();
+    let mut migrated = format!("{}\n", CSV_HEADER);
+    let mut rows = 0;
+    // 标题是第 1 行，记录从第 2 行开始
+    for (line_number, line) in lines.enumerate().map(|(i, line)| (i + 2, line.trim_end())) {
+        if line.is_empty() {
+            continue;
+        }
+        let wallet = match migrate_row(secp, line, file) {
+            RowOutcome::Migrated(wallet) => {
+                result.migrated_rows += 1;
+                wallet
+            }
+            RowOutcome::Mismatched(wallet, derived_address) => {
+                result.mismatched_rows += 1;
+                result.mismatches.push(AddressMismatch {
+                    file: file.to_path_buf(),
+                    line: line_number,
+                    stored_address: wallet.address.clone(),
+                    derived_address,
+                });
+                wallet
+            }
+            RowOutcome::Skipped => {
+                result.skipped_rows += 1;
+                continue;
+            }
+        };
+        migrated.push_str(&saved_wallets::current_format_row(&wallet));
+        migrated.push('\n');
+        rows += 1;
+    }
+
+    let tmp_path = file.with_file_name(format!("{}.tmp", name));
+    fs::write(&tmp_path, migrated).map_err(|e| format!("无法写入结果文件: {}", e))?;
+    if let Err(e) = fs::rename(file, &backup) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(format!("无法备份结果文件: {}", e));
+    }
+    fs::rename(&tmp_path, file).map_err(|e| format!("无法写入结果文件（原文件在 {}）: {}", backup.display(), e))?;
+    result.files.push(MigratedFile { file: file.to_path_buf(), backup, from_version, rows });
+    Ok(Some(result))
+}
+
+/**
+ * 把目录中旧格式的 CSV 结果文件（包括各链子目录和集合）改写为当前格式
+ *
+ * 每条记录的地址由私钥重新推导（以太坊地址改为 EIP-55 格式）；记录的地址与推导出的不一致时
+ * 说明文件可能已损坏，这一行按原文保留并在结果中列出。原文件改名为 <文件名>.v<版本>.bak，从不删除。
+ * 正被会话写入的文件和没有保存完整私钥的文件不改写，在 warnings 中说明。
+ *
+ * @param dir - FancyWallets 目录
+ */
+pub fn migrate_dir(dir: &Path) -> Result<WalletFilesMigration, String> {
+    let secp = Secp256k1::signing_only();
+    let mut result = WalletFilesMigration::default();
+    for file in saved_wallets::list_wallet_files(dir)? {
+        if saved_wallets::is_active(&file) {
+            result.warnings.push(format!("{}: 正被会话写入，没有改写", file.display()));
+            continue;
+        }
+        match migrate_file(&secp, &file) {
+            Ok(Some(migrated)) => result.merge(migrated),
+            Ok(None) => {}
+            Err(e) => result.warnings.push(format!("{}: {}", file.display(), e)),
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::with_data_dir;
+    use crate::self_test::KNOWN_KEYS;
+
+    /**
+     * 旧结果文件迁移：v1 文件改写为当前格式，地址由私钥重新推导为 EIP-55 格式，与私钥不一致的行按原文保留并列出，
+     * 无法解析的行不写入新文件；原文件改名为 .v1.bak，已是当前格式的文件不被修改，再次迁移时没有需要改写的文件
+     */
+    #[test]
+    fn wallet_file_migration() -> Result<(), String> {
+        with_data_dir("file-migration", |dir| {
+            let (key1, address1) = KNOWN_KEYS[0];
+            let (_, address2) = KNOWN_KEYS[1];
+            let legacy = dir.join("wallet_888_20230101_120000.csv");
+            let original = format!(
+                "address,private_key,pattern\n0x{},{},*\n0x{},{},*\nnot-an-address,zz,*\n",
+                address1.to_lowercase(),
+                key1,
+                address2.to_lowercase(),
+                key1
+            );
+            std::fs::write(&legacy, &original).map_err(|e| e.to_string())?;
+            let current = dir.join("wallet_999_20240101_120000Z.csv");
+            let current_content = format!("{}\n", saved_wallets::CSV_HEADER);
+            std::fs::write(&current, &current_content).map_err(|e| e.to_string())?;
+
+            let result = migrate_dir(dir)?;
+            if result.files.len() != 1 || result.migrated_rows != 1 || result.mismatched_rows != 1 || result.skipped_rows != 1 || !result.warnings.is_empty() {
+                return Err(format!("迁移结果为 {:?}", result));
+            }
+            let mismatch = &result.mismatches[0];
+            if mismatch.line != 3 || mismatch.derived_address != format!("0x{}", address1) || mismatch.stored_address != format!("0x{}", address2.to_lowercase()) {
+                return Err(format!("地址不一致的行为 {:?}", mismatch));
+            }
+            let backup = std::fs::read_to_string(&result.files[0].backup).map_err(|e| e.to_string())?;
+            let migrated = std::fs::read_to_string(&legacy).map_err(|e| e.to_string())?;
+            let lines: Vec<&str> = migrated.lines().collect();
+            if backup != original || !result.files[0].backup.to_string_lossy().ends_with(".v1.bak") || lines.len() != 3 || lines[0] != saved_wallets::CSV_HEADER {
+                return Err(format!("备份{}与原文件一致，新文件为 {:?}", if backup == original { "" } else { "不" }, lines));
+            }
+            if !lines[1].starts_with(&format!("0x{},{},*,,ethereum,", address1, key1)) {
+                return Err(format!("迁移后的记录为 {}，地址应为 EIP-55 格式", lines[1]));
+            }
+            if std::fs::read_to_string(&current).map_err(|e| e.to_string())? != current_content {
+                return Err("已是当前格式的文件被修改".to_string());
+            }
+            let report = saved_wallets::verify_file(&legacy)?;
+            if report.valid_rows != 1 || report.corrupt_rows.len() != 1 {
+                return Err(format!("迁移后的文件校验通过 {} 行，损坏 {} 行", report.valid_rows, report.corrupt_rows.len()));
+            }
+            let again = migrate_dir(dir)?;
+            if !again.files.is_empty() {
+                return Err(format!("再次迁移时改写了 {:?}", again.files));
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,183 @@
+use crate::chain::Chain;
+
+/// 默认的文件名模板（结果文件为 wallet_<模式>_<日期>_<时间>.csv）
+pub const DEFAULT_TEMPLATE: &str = "{pattern}_{date}_{time}";
+
+/// 模板的最大字符数
+const MAX_TEMPLATE_LENGTH: usize = 64;
+
+/// 支持的占位符
+const PLACEHOLDERS: &[(&str, Placeholder)] = &[
+    ("pattern", Placeholder::Pattern),
+    ("date", Placeholder::Date),
+    ("time", Placeholder::Time),
+    ("chain", Placeholder::Chain),
+];
+
+/// 模板中的占位符
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Placeholder {
+    /// 模式（同时搜索多个模式时模板必须包含，否则各模式的结果文件同名）
+    Pattern,
+    /// 会话开始的日期（UTC）
+    Date,
+    /// 会话开始的时间（UTC，以 Z 结尾）
+    Time,
+    /// 链名称
+    Chain,
+}
+
+/// 模板的一段：原样保留的文字或占位符
+enum Segment<'a> {
+    Literal(&'a str),
+    Placeholder(Placeholder),
+}
+
+/**
+ * 文件名中允许的字符（与会话标识相同：字母、数字、下划线和连字符）
+ */
+fn is_file_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+/**
+ * 把模板拆成文字和占位符；文字只能包含字母、数字、下划线和连字符，占位符必须是支持的名称
+ */
+fn parse(template: &str) -> Result<Vec<Segment<'_>>, String> {
+    let mut segments = Vec::new();
+    let mut rest = template;
+    while !rest.is_empty() {
+        let (literal, tail) = rest.split_at(rest.find('{').unwrap_or(rest.len()));
+        if let Some(c) = literal.chars().find(|c| !is_file_char(*c)) {
+            return Err(format!("文件名模板 {} 包含不能用于文件名的字符 {:?}（只能使用字母、数字、下划线、连字符和占位符）", template, c));
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        let Some(tail) = tail.strip_prefix('{') else {
+            break;
+        };
+        let end = tail.find('}').ok_or_else(|| format!("文件名模板 {} 中的 {{ 没有对应的 }}", template))?;
+        let name = &tail[..end];
+        let (_, placeholder) = PLACEHOLDERS
+            .iter()
+            .find(|(known, _)| *known == name)
+            .ok_or_else(|| format!("文件名模板中有未知的占位符 {{{}}}（支持: {}）", name, placeholder_list()))?;
+        segments.push(Segment::Placeholder(*placeholder));
+        rest = &tail[end + 1..];
+    }
+    Ok(segments)
+}
+
+/**
+ * 支持的占位符列表（错误信息中使用）
+ */
+fn placeholder_list() -> String {
+    PLACEHOLDERS.iter().map(|(name, _)| format!("{{{}}}", name)).collect::<Vec<_>>().join(", ")
+}
+
+/**
+ * 校验文件名模板
+ *
+ * @param template - 文件名模板（结果文件为 wallet_<展开的模板>.csv）
+ * @param patterns - 会话搜索的模式数（多于一个时模板必须包含 {pattern}）
+ */
+pub fn validate(template: &str, patterns: usize) -> Result<(), String> {
+    if template.trim().is_empty() {
+        return Err("文件名模板不能为空".to_string());
+    }
+    if template.chars().count() > MAX_TEMPLATE_LENGTH {
+        return Err(format!("文件名模板不能超过 {} 个字符", MAX_TEMPLATE_LENGTH));
+    }
+    let segments = parse(template)?;
+    if patterns > 1 && !segments.iter().any(|segment| matches!(segment, Segment::Placeholder(Placeholder::Pattern))) {
+        return Err("同时搜索多个模式时每个模式写入各自的文件，文件名模板必须包含 {pattern}".to_string());
+    }
+    Ok(())
+}
+
+/**
+ * 展开文件名模板
+ *
+ * 占位符的值都只含文件名中允许的字符：模式已由 pattern::file_label 去掉通配符和其它符号，
+ * 时间为会话开始时的 UTC 时间（每个会话各自的，例如 20240101 和 120000Z）。
+ *
+ * @param template - 文件名模板
+ * @param label - 文件名中的模式部分（由 session_file_labels 生成）
+ * @param stamp - 会话开始时间（timestamps::filename_stamp 的结果，日期和时间以下划线分隔）
+ * @param chain - 生成的链
+ * @returns 结果文件名中 wallet_ 之后、扩展名之前的部分
+ */
+pub fn render(template: &str, label: &str, stamp: &str, chain: Chain) -> Result<String, String> {
+    validate(template, 1)?;
+    let (date, time) = stamp.split_once('_').unwrap_or((stamp, ""));
+    let name: String = parse(template)?
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Literal(text) => text,
+            Segment::Placeholder(Placeholder::Pattern) => label,
+            Segment::Placeholder(Placeholder::Date) => date,
+            Segment::Placeholder(Placeholder::Time) => time,
+            Segment::Placeholder(Placeholder::Chain) => chain.as_str(),
+        })
+        .collect();
+    if name.is_empty() || !name.chars().all(is_file_char) {
+        return Err(format!("文件名模板 {} 展开后不是有效的文件名: {}", template, name));
+    }
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern;
+    use crate::templates::GenerationParams;
+    use crate::timestamps;
+    use chrono::{TimeZone, Utc};
+
+    /// 应当被拒绝的文件名模板：空、路径分隔符、上级目录、文件名中不能使用的字符、未知或没有闭合的占位符、太长
+    const INVALID_FILENAME_TEMPLATES: &[&str] = &["", " ", "a/{pattern}", "..", "{pattern}*{date}", "x?{time}", "{pattern} {date}", "{seed}", "{pattern", "{{pattern}}", "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"];
+
+    /**
+     * 文件名模板：默认模板与之前的文件名相同，占位符展开为本次会话的时间和链，模式中的符号不会出现在文件名中
+     */
+    #[test]
+    fn filename_template() -> Result<(), String> {
+        let started = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).single().ok_or("无效的时间")?;
+        let stamp = timestamps::filename_stamp(started);
+        let default = render(DEFAULT_TEMPLATE, "abc", &stamp, Chain::Ethereum)?;
+        if default != format!("abc_{}", stamp) {
+            return Err(format!("默认模板展开为 {}，应为 abc_{}", default, stamp));
+        }
+        let label = pattern::file_label("*a?b/c*");
+        let custom = render("{chain}-{pattern}_{date}", &label, &stamp, Chain::Tron)?;
+        if custom != "tron-axb-c_20240102" {
+            return Err(format!("模板 {{chain}}-{{pattern}}_{{date}} 展开为 {}", custom));
+        }
+        let later = timestamps::filename_stamp(started + chrono::Duration::hours(3));
+        if render("{time}", "abc", &later, Chain::Ethereum)? != "060405Z" {
+            return Err("{time} 没有使用本次会话开始的时间".to_string());
+        }
+        
+        for template in INVALID_FILENAME_TEMPLATES {
+            if validate(template, 1).is_ok() || render(template, "abc", &stamp, Chain::Ethereum).is_ok() {
+                return Err(format!("文件名模板 {:?} 没有被拒绝", template));
+            }
+        }
+        let params = GenerationParams {
+            pattern: "dead".to_string(),
+            additional_patterns: vec!["beef".to_string()],
+            filename_template: Some("{date}_{time}".to_string()),
+            ..GenerationParams::default()
+        };
+        if params.validate().is_ok() {
+            return Err("同时搜索多个模式时不含 {pattern} 的文件名模板没有被拒绝".to_string());
+        }
+        let collection = GenerationParams { additional_patterns: Vec::new(), collection: Some("gallery".to_string()), ..params.clone() };
+        if collection.validate().is_ok() {
+            return Err("文件名模板与集合同时使用没有被拒绝".to_string());
+        }
+        GenerationParams { additional_patterns: Vec::new(), ..params }.validate()?;
+        Ok(())
+    }
+}
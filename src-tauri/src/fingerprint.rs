@@ -0,0 +1,78 @@
+use crate::hexutil::Address;
+use crate::keccak::keccak256;
+use crate::mnemonic::wordlist;
+
+/// 指纹的词数
+pub const FINGERPRINT_WORDS: usize = 6;
+
+/**
+ * 计算地址的指纹短语（便于电话核对或与打印的恢复单比对，不能代替完整地址）
+ *
+ * 算法（固定不变，其它实现可以独立计算出相同的结果）：
+ * 1. 对 20 字节地址（以太坊地址和波场地址的同一账户相同）计算 Keccak-256；
+ * 2. 取哈希的前 24 字节，按顺序分为 6 段，每段 4 字节按大端读为 32 位整数；
+ * 3. 每个整数对 2048 取余（即低 11 位），作为 BIP-39 英文词表中的序号（从 0 开始）；
+ * 4. 6 个词以空格连接。
+ *
+ * 指纹来自哈希而不是地址本身，只有前后缀相同的仿冒地址会得到完全不同的短语。
+ *
+ * @param address - 地址
+ * @returns 指纹短语，例如 "word word word word word word"
+ */
+pub fn phrase(address: &Address) -> String {
+    let hash = keccak256(address.as_bytes());
+    let words = wordlist();
+    hash.chunks_exact(4)
+        .take(FINGERPRINT_WORDS)
+        .map(|chunk| {
+            let value = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+            words[value as usize % words.len()]
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::Chain;
+    use crate::mnemonic;
+    use sha2::{Digest, Sha256};
+    use crate::test_support::TRON_ZERO_ADDRESS;
+
+    /// 地址指纹的固定用例：(地址, 指纹短语)；算法或词表变化会导致这些用例失败
+    const FINGERPRINT_VECTORS: &[(&str, &str)] = &[
+        ("0x0000000000000000000000000000000000000000", "want range monitor wife sauce fit"),
+        ("0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf", "grunt parrot buzz enhance error echo"),
+        ("0x2B5AD5c4795c026514f8317c7a215E218DcCD6cF", "lunch stool image glory capital drink"),
+        ("0x6813Eb9362372EEF6200f3b1dbC3f819671cBA69", "major march creek protect artwork book"),
+    ];
+
+    /**
+     * 地址指纹：词表与 BIP-39 英文词表一致，固定用例不变，同一账户的以太坊和波场地址得到相同的短语
+     */
+    #[test]
+    fn fingerprint_vectors() -> Result<(), String> {
+        let mut text = mnemonic::wordlist().join("\n");
+        text.push('\n');
+        let digest = hex::encode(Sha256::digest(text.as_bytes()));
+        if mnemonic::wordlist().len() != 2048 || digest != mnemonic::WORDLIST_SHA256 {
+            return Err(format!("词表有 {} 个词，SHA-256 为 {}", mnemonic::wordlist().len(), digest));
+        }
+        for (address, expected) in FINGERPRINT_VECTORS {
+            let parsed = Address::parse(address).map_err(|e| e.to_string())?;
+            let phrase = phrase(&parsed);
+            if phrase != *expected || phrase.split(' ').count() != FINGERPRINT_WORDS {
+                return Err(format!("{} 的指纹为 \"{}\"，应为 \"{}\"", address, phrase, expected));
+            }
+            let tron = Chain::Tron.format_address(parsed);
+            if Chain::Tron.parse_address(&tron)? != parsed {
+                return Err(format!("波场地址 {} 没有解析回 {}", tron, address));
+            }
+        }
+        if Chain::Tron.parse_address(&TRON_ZERO_ADDRESS.replace('b', "c")).is_ok() {
+            return Err("校验和错误的波场地址没有被拒绝".to_string());
+        }
+        Ok(())
+    }
+}
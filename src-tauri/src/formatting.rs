@@ -0,0 +1,393 @@
+use crate::timestamps;
+use chrono::TimeZone;
+use serde::{Deserialize, Serialize};
+
+/// 默认语言（与前端 i18n 的 fallbackLng 一致）
+pub const DEFAULT_LOCALE: &str = "zh-CN";
+
+/// 超过该值的数量改用科学计数法（最大的单位“万亿”/T 已不够用）
+const SCIENTIFIC_THRESHOLD: f64 = 1e15;
+
+/// 上标数字（科学计数法的指数）
+const SUPERSCRIPT_DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+
+/// 数字分组方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberLocale {
+    /// 简体中文：万 / 亿
+    SimplifiedChinese,
+    /// 繁体中文：萬 / 億
+    TraditionalChinese,
+    /// 其它语言：k / M / B
+    Western,
+}
+
+impl NumberLocale {
+    /**
+     * 根据语言标签（如 zh-CN、zh-TW、en）选择分组方式
+     */
+    pub fn from_tag(tag: &str) -> Self {
+        let tag = tag.to_ascii_lowercase().replace('_', "-");
+        if tag == "zh-tw" || tag == "zh-hk" || tag.starts_with("zh-hant") {
+            NumberLocale::TraditionalChinese
+        } else if tag == "zh" || tag.starts_with("zh-") {
+            NumberLocale::SimplifiedChinese
+        } else {
+            NumberLocale::Western
+        }
+    }
+
+    fn is_chinese(self) -> bool {
+        self != NumberLocale::Western
+    }
+
+    /// 中文大数单位：万、亿、万亿
+    fn chinese_units(self) -> [&'static str; 3] {
+        match self {
+            NumberLocale::TraditionalChinese => ["萬", "億", "萬億"],
+            _ => ["万", "亿", "万亿"],
+        }
+    }
+
+    /// 时长单位：年、天、小时、分、秒、毫秒
+    fn duration_units(self) -> [&'static str; 6] {
+        match self {
+            NumberLocale::SimplifiedChinese => ["年", "天", "小时", "分", "秒", "毫秒"],
+            NumberLocale::TraditionalChinese => ["年", "天", "小時", "分", "秒", "毫秒"],
+            NumberLocale::Western => ["y", "d", "h", "m", "s", "ms"],
+        }
+    }
+}
+
+/// 可格式化的数值类型
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuantityKind {
+    /// 普通数量（尝试次数等）
+    Count,
+    /// 时长（毫秒）
+    Duration,
+    /// 速度（每秒尝试次数）
+    HashRate,
+    /// 概率，以期望尝试次数表示（例如 1 in 16.7M）
+    Odds,
+}
+
+/**
+ * 按单位缩放并保留有效位数：小于 100 时保留一位小数，否则取整，并去掉末尾的 .0
+ */
+fn scaled(value: f64, unit: f64) -> String {
+    let v = value / unit;
+    let text = if v < 100.0 { format!("{:.1}", v) } else { format!("{:.0}", v) };
+    text.strip_suffix(".0").map(str::to_string).unwrap_or(text)
+}
+
+/**
+ * 将数值缩放到合适的单位；四舍五入后达到下一级单位时进位（例如 999.96k 显示为 1M）
+ *
+ * @param units - (单位大小, 单位名) 从小到大排列
+ * @returns (数字, 单位名)，不足第一级单位时单位名为空
+ */
+fn compact(value: f64, units: &[(f64, &'static str)]) -> (String, &'static str) {
+    let index = match units.iter().rposition(|&(size, _)| value >= size) {
+        Some(index) => index,
+        None if value.round() < units[0].0 => return (format!("{:.0}", value), ""),
+        None => 0,
+    };
+    let (size, name) = units[index];
+    let text = scaled(value, size);
+    if let Some(&(next, next_name)) = units.get(index + 1) {
+        if text.parse::<f64>().unwrap_or(0.0) * size >= next {
+            return (scaled(value, next), next_name);
+        }
+    }
+    (text, name)
+}
+
+/**
+ * 科学计数法，保留一位小数，例如 2.6×10¹²⁰（用于远超日常单位的难度和尝试次数）
+ */
+fn scientific(value: f64) -> String {
+    let mut exponent = value.log10().floor() as i32;
+    let mut mantissa = value / 10f64.powi(exponent);
+    // 10 的幂在浮点下可能得到 9.99… 或 10.0，四舍五入后进位
+    if (mantissa * 10.0).round() >= 100.0 {
+        mantissa /= 10.0;
+        exponent += 1;
+    }
+    let text = format!("{:.1}", mantissa);
+    let text = text.strip_suffix(".0").unwrap_or(&text);
+    let power: String = exponent
+        .to_string()
+        .chars()
+        .map(|c| c.to_digit(10).map_or(c, |d| SUPERSCRIPT_DIGITS[d as usize]))
+        .collect();
+    format!("{}×10{}", text, power)
+}
+
+/**
+ * 格式化大数：中文使用万/亿分组，其它语言使用 k/M/B/T，超过 10¹⁵ 时使用科学计数法
+ *
+ * @param value - 数值
+ * @param locale - 分组方式
+ */
+pub fn format_count(value: f64, locale: NumberLocale) -> String {
+    if value.is_nan() {
+        return "-".to_string();
+    }
+    if value.is_infinite() {
+        return "∞".to_string();
+    }
+    if value < 0.0 {
+        return format!("-{}", format_count(-value, locale));
+    }
+    if value >= SCIENTIFIC_THRESHOLD {
+        return scientific(value);
+    }
+    let (number, unit) = if locale.is_chinese() {
+        let [wan, yi, wan_yi] = locale.chinese_units();
+        compact(value, &[(1e4, wan), (1e8, yi), (1e12, wan_yi)])
+    } else {
+        compact(value, &[(1e3, "k"), (1e6, "M"), (1e9, "B"), (1e12, "T")])
+    };
+    format!("{}{}", number, unit)
+}
+
+/**
+ * 格式化速度（每秒尝试次数），例如 2.4 MH/s 或 240万 H/s
+ */
+pub fn format_hash_rate(per_second: f64, locale: NumberLocale) -> String {
+    if locale.is_chinese() || !per_second.is_finite() || !(0.0..SCIENTIFIC_THRESHOLD).contains(&per_second) {
+        return format!("{} H/s", format_count(per_second, locale));
+    }
+    let (number, prefix) = compact(per_second, &[(1e3, "k"), (1e6, "M"), (1e9, "G"), (1e12, "T")]);
+    format!("{} {}H/s", number, prefix)
+}
+
+/**
+ * 格式化概率（以期望尝试次数表示），例如 1 in 16.7M 或 1/1670万
+ */
+pub fn format_odds(expected_attempts: f64, locale: NumberLocale) -> String {
+    if locale.is_chinese() {
+        format!("1/{}", format_count(expected_attempts, locale))
+    } else {
+        format!("1 in {}", format_count(expected_attempts, locale))
+    }
+}
+
+/**
+ * 格式化时长，只显示最大的两级单位，例如 3h 12m 或 3小时12分
+ *
+ * @param millis - 时长（毫秒）
+ */
+pub fn format_duration(millis: f64, locale: NumberLocale) -> String {
+    if millis.is_nan() {
+        return "-".to_string();
+    }
+    if millis.is_infinite() || millis < 0.0 {
+        return "∞".to_string();
+    }
+    let [year, day, hour, minute, second, milli] = locale.duration_units();
+    let sep = if locale.is_chinese() { "" } else { " " };
+
+    if millis < 999.5 {
+        return format!("{:.0}{}", millis, milli);
+    }
+    let seconds = millis / 1000.0;
+    if seconds < 60.0 {
+        let text = format!("{:.1}", seconds);
+        let text = text.strip_suffix(".0").unwrap_or(&text);
+        return if text == "60" {
+            format!("1{}", minute)
+        } else {
+            format!("{}{}", text, second)
+        };
+    }
+
+    let total = seconds.round();
+    let years = (total / 31_536_000.0).floor();
+    // 年数过大时只用大数表示
+    if years >= 1e4 {
+        return format!("{}{}{}", format_count(years, locale), sep, year);
+    }
+    let total = total as u64;
+    let levels = [
+        (total / 31_536_000, year),
+        (total % 31_536_000 / 86_400, day),
+        (total % 86_400 / 3_600, hour),
+        (total % 3_600 / 60, minute),
+        (total % 60, second),
+    ];
+    let first = levels.iter().position(|&(n, _)| n > 0).unwrap_or(levels.len() - 1);
+    let mut text = format!("{}{}", levels[first].0, levels[first].1);
+    if let Some(&(n, unit)) = levels.get(first + 1) {
+        if n > 0 {
+            text.push_str(&format!("{}{}{}", sep, n, unit));
+        }
+    }
+    text
+}
+
+/**
+ * 按类型格式化数值（前端通过 IPC 调用，保证两端显示一致）
+ *
+ * @param kind - 数值类型
+ * @param value - 数值
+ * @param locale - 语言标签
+ */
+pub fn format_quantity(kind: QuantityKind, value: f64, locale: &str) -> String {
+    let locale = NumberLocale::from_tag(locale);
+    match kind {
+        QuantityKind::Count => format_count(value, locale),
+        QuantityKind::Duration => format_duration(value, locale),
+        QuantityKind::HashRate => format_hash_rate(value, locale),
+        QuantityKind::Odds => format_odds(value, locale),
+    }
+}
+
+/**
+ * 把保存的时间（RFC 3339，UTC 或旧版本带时区偏移的本地时间）转换为本机时区的本地时间显示
+ *
+ * @param value - 保存的时间
+ * @param locale - 显示语言
+ * @returns 本地时间；无法解析的值原样返回
+ */
+pub fn format_timestamp(value: &str, locale: NumberLocale) -> String {
+    format_timestamp_in(value, locale, &chrono::Local)
+}
+
+/**
+ * 把保存的时间转换为指定时区的时间显示（format_timestamp 使用本机时区）
+ */
+pub fn format_timestamp_in<Tz: TimeZone>(value: &str, locale: NumberLocale, zone: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let Some(time) = timestamps::parse_rfc3339(value) else {
+        return value.to_string();
+    };
+    let format = if locale.is_chinese() { "%Y年%-m月%-d日 %H:%M" } else { "%Y-%m-%d %H:%M" };
+    time.with_timezone(zone).format(format).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::confidence;
+    use crate::pattern::ParsedPattern;
+
+    /**
+     * 极端难度下的统计：长度 1 到 40 的各类模式（以及超长的无效输入），概率、置信度、估计时间和显示文本中都没有 NaN 或无穷大
+     */
+    #[test]
+    fn stats_extremes() -> Result<(), String> {
+        let mut patterns: Vec<String> = ["*aaaa*", "*aabb*", "*abab*", &"f".repeat(1000)].map(str::to_string).to_vec();
+        for length in 1..=40 {
+            patterns.push("8".repeat(length));
+            patterns.push("a".repeat(length));
+            patterns.push("0123456789abcdef".chars().cycle().take(length).collect());
+        }
+        let attempts = [0.0, 1.0, 1e6, u64::MAX as f64, f64::MAX];
+        let rates = [None, Some(1e-300), Some(1e9)];
+        for pattern in &patterns {
+            let parsed = ParsedPattern::new(pattern);
+            let (bits, p, expected) = (parsed.difficulty_bits(), parsed.probability(), parsed.expected_attempts());
+            if !bits.is_finite() || bits <= 0.0 || !(0.0..=1.0).contains(&p) || !expected.is_finite() || expected < 1.0 {
+                return Err(format!("模式 {} 的难度 {}、概率 {}、期望尝试次数 {} 无效", pattern, bits, p, expected));
+            }
+            for &n in &attempts {
+                let c = confidence::confidence_after(p, n);
+                if !(0.0..=1.0).contains(&c) {
+                    return Err(format!("模式 {} 在 {} 次尝试后的置信度为 {}", pattern, n, c));
+                }
+            }
+            
+            let mut shown = vec![(QuantityKind::Odds, expected), (QuantityKind::Count, expected)];
+            if p > 0.0 {
+                for rate in rates {
+                    for estimate in confidence::milestones(p, u64::MAX, rate) {
+                        let fields = [estimate.total_attempts, estimate.remaining_attempts, estimate.current_confidence];
+                        if fields.iter().any(|v| !v.is_finite()) || estimate.remaining_ms.is_some_and(|ms| !ms.is_finite()) {
+                            return Err(format!("模式 {} 的置信度估计无效: {:?}", pattern, estimate));
+                        }
+                        shown.push((QuantityKind::Count, estimate.total_attempts));
+                        shown.extend(estimate.remaining_ms.map(|ms| (QuantityKind::Duration, ms)));
+                        shown.extend(rate.map(|r| (QuantityKind::HashRate, r)));
+                    }
+                }
+            }
+            for (kind, value) in shown {
+                for locale in ["en", "zh-CN"] {
+                    let text = format_quantity(kind, value, locale);
+                    if text.contains("NaN") || text.contains("inf") || text.contains('∞') || text.chars().count() > 24 {
+                        return Err(format!("{:?} {} 显示为 {:?}", kind, value, text));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 各语言在单位边界附近的显示：(类型, 数值, 语言, 显示文本)
+    const PINNED_QUANTITIES: &[(QuantityKind, f64, &str, &str)] = &[
+        (QuantityKind::Count, 999.0, "en", "999"),
+        (QuantityKind::Count, 999.6, "en", "1k"),
+        (QuantityKind::Count, 1500.0, "en", "1.5k"),
+        (QuantityKind::Count, 999_960.0, "en", "1M"),
+        (QuantityKind::Count, 16_777_216.0, "en", "16.8M"),
+        (QuantityKind::Count, 1e9, "en", "1B"),
+        (QuantityKind::Count, 1e12, "en", "1T"),
+        (QuantityKind::Count, 1e15, "en", "1×10¹⁵"),
+        (QuantityKind::Count, 2.6e120, "en", "2.6×10¹²⁰"),
+        (QuantityKind::Count, -1500.0, "en", "-1.5k"),
+        (QuantityKind::Count, 9999.0, "zh-CN", "9999"),
+        (QuantityKind::Count, 1e4, "zh-CN", "1万"),
+        (QuantityKind::Count, 16_777_216.0, "zh-CN", "1678万"),
+        (QuantityKind::Count, 1e8, "zh-CN", "1亿"),
+        (QuantityKind::Count, 123_456_789_012.0, "zh-CN", "1235亿"),
+        (QuantityKind::Count, 1e12, "zh-CN", "1万亿"),
+        (QuantityKind::Count, 1e15, "zh-CN", "1×10¹⁵"),
+        (QuantityKind::Count, 1e4, "zh-TW", "1萬"),
+        (QuantityKind::Count, 1e8, "zh_Hant", "1億"),
+        (QuantityKind::Odds, 16_777_216.0, "en", "1 in 16.8M"),
+        (QuantityKind::Odds, 16_777_216.0, "zh-CN", "1/1678万"),
+        (QuantityKind::HashRate, 999.0, "en", "999 H/s"),
+        (QuantityKind::HashRate, 2_400_000.0, "en", "2.4 MH/s"),
+        (QuantityKind::HashRate, 2_400_000.0, "zh-CN", "240万 H/s"),
+        (QuantityKind::Duration, 999.0, "en", "999ms"),
+        (QuantityKind::Duration, 999.5, "en", "1s"),
+        (QuantityKind::Duration, 59_960.0, "en", "1m"),
+        (QuantityKind::Duration, 11_520_000.0, "en", "3h 12m"),
+        (QuantityKind::Duration, 11_520_000.0, "zh-CN", "3小时12分"),
+        (QuantityKind::Duration, 11_520_000.0, "zh-TW", "3小時12分"),
+        (QuantityKind::Duration, 86_400_000.0, "en", "1d"),
+        (QuantityKind::Duration, 63_331_200_000.0, "en", "2y 3d"),
+        (QuantityKind::Duration, 63_331_200_000.0, "zh-CN", "2年3天"),
+    ];
+
+    /**
+     * 数量、概率、速度、时长和时间的显示文本在各语言下固定，不随运行环境的语言或时区变化
+     */
+    #[test]
+    fn locale_pinned_output() -> Result<(), String> {
+        for &(kind, value, locale, expected) in PINNED_QUANTITIES {
+            let text = format_quantity(kind, value, locale);
+            if text != expected {
+                return Err(format!("{:?} {} 在 {} 下显示为 {:?}，应为 {:?}", kind, value, locale, text, expected));
+            }
+        }
+        let beijing = chrono::FixedOffset::east_opt(8 * 3600).ok_or("无效的时区")?;
+        let timestamps = [
+            (format_timestamp_in("2026-01-02T03:04:05Z", NumberLocale::SimplifiedChinese, &chrono::Utc), "2026年1月2日 03:04"),
+            (format_timestamp_in("2026-01-02T03:04:05Z", NumberLocale::Western, &chrono::Utc), "2026-01-02 03:04"),
+            (format_timestamp_in("2026-01-02T03:04:05Z", NumberLocale::Western, &beijing), "2026-01-02 11:04"),
+            (format_timestamp_in("not-a-time", NumberLocale::Western, &beijing), "not-a-time"),
+        ];
+        for (text, expected) in timestamps {
+            if text != expected {
+                return Err(format!("时间显示为 {:?}，应为 {:?}", text, expected));
+            }
+        }
+        Ok(())
+    }
+}
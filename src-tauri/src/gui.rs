@@ -0,0 +1,6986 @@
+use crate::{balances, benchmark, bitcoin, burn_in, calibration, chain::Chain, clipboard, collections, confidence, contract, device, csv_field, disk_usage, encrypted_csv, engine, events, file_migration, filename_template, fingerprint, formatting, idle, keccak, keystore, leaderboard, manifest, match_preview, memory, migrations, mnemonic_scan, notes, notifications, output_cap, output_check, output_folder, output_root, pair_check, pattern, pause, pgp_export, presets, profiles, queue, rate_history, recovery, replay, results_db, retry, saved_wallets, scoring, secure_delete, self_test, session_archive, session_state, sessions, settings, solana, split_key, start_limit, taskbar, templates, timer, timestamps, wallet_qr, worker, worker_stats, xlsx_export};
+use serde::{Deserialize, Serialize};
+use secp256k1::{Secp256k1, SecretKey, PublicKey};
+use rand::rngs::OsRng;
+use tauri::{AppHandle, Emitter, ipc::Channel};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_notification::{NotificationExt, PermissionState};
+use tauri_plugin_opener::OpenerExt;
+use std::fs::{OpenOptions};
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{OnceLock, Mutex, atomic::{AtomicBool, AtomicUsize, Ordering}};
+use zeroize::Zeroizing;
+use crate::bitcoin::BitcoinAddressType;
+use crate::balances::{ChainActivity, UnusedCheck};
+use crate::clipboard::{ClipboardAccess, ClipboardCopy};
+use crate::address::{has_valid_checksum, public_key_to_checksum_address, to_checksum_address, to_checksum_address_checked};
+use crate::confidence::{ConfidenceEstimate, LiveSession};
+use crate::contract::{ContractMatch, ContractSearchReport, NonceRange};
+use crate::disk_usage::{DiskUsageEstimate, OutputSizes};
+use crate::encrypted_csv::OutputCipher;
+use crate::errors::WalletError;
+use crate::engine::{next_secret_key, CancellationToken, GenerationLimits, KeyCandidateStats, SearchConfig, SearchEvent, SearchProgress, SearchTotals};
+use crate::entropy::{self, EntropyProvenance, RngMode};
+use crate::events::EventGate;
+use crate::hexutil::{Address, PrivKeyHex, SecretHex};
+use crate::keccak::{KeccakBackend, KeccakSelection};
+use crate::leaderboard::{Leaderboard, LeaderboardEntry, LeaderboardWriter};
+use crate::match_batch::MatchBatch;
+use crate::memory::{ReturnedResults, SessionMemory};
+use crate::mnemonic::{self, KeySourceKind};
+use crate::mnemonic_scan::ScanReport;
+use crate::notifications::{MatchNotifier, NotificationUnavailable};
+use crate::output_cap::{OutputBudget, OutputCaps, OutputUsage};
+use crate::output_folder::OutputFolder;
+use crate::output_root::{OutputRoot, OutputSource};
+use crate::pause::PauseRequest;
+use crate::pattern::{CaseMode, CharCount, MatchMode, MatchRule, MatchedSpan, ParsedPattern, PatternType};
+use crate::profiles::{Profile, ProfileStore};
+use crate::recovery::RecoverySheet;
+use crate::replay::{ReplayEvent, ReplayRecorder};
+use crate::retry::RetryPolicy;
+use crate::saved_wallets::{CsvPrivateKeys, OutputFormat, OutputLayout};
+use crate::scoring::{RecordTracker, ScoreFunction, ScoreWeights};
+use crate::session_state::{Resume, ResumedSession, SessionState, StateWriter};
+use crate::sessions::SessionKind;
+use crate::split_key::SplitKeyResult;
+use crate::stop::{StopReason, StopRecord};
+use crate::taskbar::SessionTaskbar;
+use crate::templates::GenerationParams;
+use crate::wallet_qr::{QrErrorCorrection, QrKind, QrOptions};
+use crate::timer::{ClockJump, ClockJumpDetector, ProgressThrottle};
+use crate::timestamps::TimestampZone;
+use crate::worker::{WorkerCapacity, WorkerFailure};
+
+/// dry_run 会话返回的钱包中代替私钥的标记（不是有效的私钥）
+pub const DRY_RUN_PRIVATE_KEY: &str = "DRY-RUN-NOT-SAVED";
+
+/// 正在运行的任务数（生成、拆分密钥搜索、压力测试、校准和测速）
+static RUNNING_TASKS: AtomicUsize = AtomicUsize::new(0);
+
+/// 空闲检测自动启动的会话（空闲检测只自动暂停和恢复这个会话）
+static AUTO_STARTED_SESSION: Mutex<Option<String>> = Mutex::new(None);
+
+/// 空闲检测的轮询间隔
+const IDLE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// 队列线程等待其它会话结束时的检查间隔
+const QUEUE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// 队列线程是否正在运行
+static QUEUE_RUNNER: AtomicBool = AtomicBool::new(false);
+
+/// 应用退出时等待正在运行的会话写完结果和会话状态的最长时间（超时后直接退出）
+const EXIT_GRACE_PERIOD: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// 是否已在等待会话结束后退出（窗口关闭后可能再次收到退出请求）
+static EXIT_PENDING: AtomicBool = AtomicBool::new(false);
+
+/// 会话已经结束（或等待超时），可以退出
+static EXIT_READY: AtomicBool = AtomicBool::new(false);
+
+/// 用户配置列表（首次访问时加载）
+static PROFILES: OnceLock<Mutex<ProfileStore>> = OnceLock::new();
+
+/// 启动迁移报告（迁移失败时应用处于只读模式）
+static MIGRATION_REPORT: OnceLock<migrations::MigrationReport> = OnceLock::new();
+
+/// 拆分密钥会话结果（按会话标识保存）
+static SPLIT_KEY_RESULTS: OnceLock<Mutex<HashMap<String, SplitKeyResult>>> = OnceLock::new();
+
+/// 正在运行的生成会话的实时状态（以会话标识为键；界面重新加载后由 get_generation_status 读取）
+static ACTIVE_GENERATIONS: OnceLock<Mutex<HashMap<String, ActiveGeneration>>> = OnceLock::new();
+
+/// 每个生成会话最后找到的钱包（带私钥；还没有找到时为空）。保留到下一个会话开始或应用退出，界面错过返回值时由 get_last_wallet 读取
+static LAST_WALLETS: OnceLock<Mutex<HashMap<String, Option<WalletFound>>>> = OnceLock::new();
+
+/// 钱包信息
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Wallet {
+    /// 会话内的序号（写入文件时按顺序分配，从 1 开始）
+    pub index: u64,
+    /// 钱包地址（比特币为压缩公钥的 HASH160，Solana 为公钥的 20 字节标识，地址文本见 chain_address）
+    pub address: Address,
+    /// 私钥（离开作用域时清零）
+    pub private_key: SecretHex,
+    /// 尝试次数
+    pub attempts: u64,
+    /// 找到时的运行时间（毫秒，不含暂停时间）
+    pub duration: u64,
+    /// 地址中满足模式的字符区间（用于界面高亮）
+    pub matched_spans: Vec<MatchedSpan>,
+    /// 私钥的随机数来源
+    pub rng_mode: RngMode,
+    /// 确定性测试模式生成的测试钱包（不能用于真实资金）
+    pub test_wallet: bool,
+    /// 派生出私钥的 BIP-39 助记词（只有 key_source 为 mnemonic 时才有，与私钥一起保存）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mnemonic: Option<String>,
+    /// 该链格式的地址（只有不使用以太坊格式的链才有，例如波场 T 开头的地址、比特币 1 或 bc1q 开头的地址、Solana 的 Base58 公钥；
+    /// 结果文件的地址列写入它）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_address: Option<String>,
+    /// 满足的模式（同时搜索多个模式时为这个钱包满足的那一个）
+    #[serde(default)]
+    pub matched_pattern: String,
+    /// 找到时间（RFC 3339，按 timestamp_zone 为 UTC 或本机时区；每个匹配各自的时间，不是会话开始时间）
+    #[serde(default)]
+    pub created_at: String,
+}
+
+/// 会话内的钱包序号：每个会话各自从 1 开始连续分配，不同会话的序号互不影响；
+/// 会话的钱包都由汇总线程依次分配序号并写入，结果文件中的行顺序与序号顺序一致
+#[derive(Debug, Default)]
+pub(crate) struct WalletIndex {
+    /// 最近分配的序号（还没有分配时为 0）
+    last: u64,
+}
+
+impl WalletIndex {
+    /**
+     * 从 last 之后继续分配（追加到集合时从集合中最大的序号之后继续，集合文件中的序号不重复）
+     * 
+     * @param last - 已经使用的最大序号
+     */
+    pub(crate) fn after(last: u64) -> Self {
+        WalletIndex { last }
+    }
+
+    /**
+     * 为钱包分配下一个序号
+     * 
+     * @param wallet - 钱包信息
+     */
+    pub(crate) fn assign(&mut self, wallet: &mut Wallet) {
+        self.last += 1;
+        wallet.index = self.last;
+    }
+}
+
+/**
+ * 记录没有会话事件或回放记录可以承载的错误（例如队列线程和退出时的错误），写到标准错误输出
+ * 
+ * @param message - 错误说明
+ */
+fn log_error(message: &str) {
+    eprintln!("{}", message);
+}
+
+/**
+ * 是否有任务正在运行
+ */
+fn generation_running() -> bool {
+    RUNNING_TASKS.load(Ordering::SeqCst) > 0
+}
+
+/// 任务运行标记，离开作用域时自动清除（暂停状态属于各个会话，随会话注销）
+struct RunningGuard;
+
+impl RunningGuard {
+    fn new() -> Self {
+        RUNNING_TASKS.fetch_add(1, Ordering::SeqCst);
+        RunningGuard
+    }
+}
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        RUNNING_TASKS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/**
+ * 空闲检测自动启动、仍在运行的会话
+ */
+fn auto_started_session() -> Option<String> {
+    let session_id = AUTO_STARTED_SESSION.lock().ok()?.clone()?;
+    sessions::list().iter().any(|session| session.session_id == session_id).then_some(session_id)
+}
+
+/**
+ * 运行一次校准并记录结果（不写入任何结果文件；只读模式下只返回结果，不保存）
+ * 
+ * @param cancel - 取消令牌
+ * @returns 校准结果
+ */
+fn calibrate_and_record(cancel: &CancellationToken) -> Result<calibration::Calibration, String> {
+    // 写入延迟在默认输出目录中探测（目录不存在时先创建，与生成时相同）
+    let wallets_dir = resolve_wallets_dir(None)?;
+    let _ = std::fs::create_dir_all(&wallets_dir);
+    let result = calibration::calibrate(calibration::CALIBRATION_DURATION, &wallets_dir, cancel)?;
+    if !migration_report().read_only() {
+        calibration::record(&profiles::app_data_dir()?, result.clone())?;
+    }
+    Ok(result)
+}
+
+/**
+ * 首次启动时在后台运行校准（已有校准记录、生成任务正在运行或处于只读模式时跳过）
+ */
+fn spawn_first_run_calibration(app: AppHandle) {
+    let Ok(data_dir) = profiles::app_data_dir() else {
+        return;
+    };
+    let has_history = calibration::load(&data_dir).map(|store| !store.history.is_empty()).unwrap_or(true);
+    if has_history || migration_report().read_only() {
+        return;
+    }
+    std::thread::spawn(move || {
+        if generation_running() {
+            return;
+        }
+        // 使用独立的取消令牌，不影响之后启动的生成任务
+        let _ = match calibrate_and_record(&CancellationToken::new()) {
+            Ok(result) => app.emit("calibration-complete", result),
+            Err(e) => app.emit("calibration-failed", e),
+        };
+    });
+}
+
+/**
+ * 获取用户配置列表（首次访问时从应用数据目录加载）
+ */
+fn profile_store() -> Result<&'static Mutex<ProfileStore>, String> {
+    if let Some(store) = PROFILES.get() {
+        return Ok(store);
+    }
+    let store = profiles::load(&profiles::app_data_dir()?)?;
+    Ok(PROFILES.get_or_init(|| Mutex::new(store)))
+}
+
+/**
+ * 执行启动迁移（只执行一次，之后返回同一份报告）
+ */
+fn migration_report() -> &'static migrations::MigrationReport {
+    MIGRATION_REPORT.get_or_init(|| match profiles::app_data_dir() {
+        Ok(dir) => migrations::run(&dir, migrations::MIGRATIONS),
+        Err(e) => migrations::MigrationReport {
+            status: migrations::MigrationStatus::Failed,
+            from_version: 0,
+            to_version: 0,
+            applied: Vec::new(),
+            error: Some(e),
+        },
+    })
+}
+
+/**
+ * 修改应用数据前检查是否处于只读模式（启动迁移失败或数据来自更新的版本）
+ * 
+ * 只读模式只限制应用数据目录中的设置、配置和模板；生成结果仍然写入 FancyWallets 目录。
+ */
+fn ensure_data_writable() -> Result<(), String> {
+    let report = migration_report();
+    if report.read_only() {
+        return Err(format!(
+            "应用数据处于只读模式：{}",
+            report.error.as_deref().unwrap_or("数据迁移失败")
+        ));
+    }
+    Ok(())
+}
+
+/**
+ * 获取当前使用的用户配置
+ */
+fn active_profile() -> Result<Profile, String> {
+    profile_store()?
+        .lock()
+        .map_err(|_| "用户配置不可用".to_string())?
+        .active_profile()
+        .cloned()
+        .ok_or_else(|| "当前用户配置不存在".to_string())
+}
+
+/**
+ * 停止正在运行的会话；只停止指定的会话，其它会话继续运行
+ * 
+ * @param session_id - 目标会话（为空时停止全部正在运行的会话）
+ * @param reason - 停止原因（默认为界面停止按钮）
+ * @param detail - 补充说明（可选）
+ */
+#[tauri::command]
+fn cancel_generation(session_id: Option<String>, reason: Option<StopReason>, detail: Option<String>) -> Result<(), WalletError> {
+    sessions::request_stop(session_id.as_deref(), reason.unwrap_or(StopReason::UserUi), detail).map(|_| ()).map_err(WalletError::from)
+}
+
+/**
+ * 运行中调整生成会话的 CPU 占用上限，不需要取消重来；工作线程按占空比交替计算和休眠，进度中的速度是限制后的实际速度
+ * 
+ * @param percent - 上限（1–100，100 为不限制）
+ * @param session_id - 目标会话（为空时调整全部正在运行的生成会话）
+ * @returns 调整的会话数
+ */
+#[tauri::command]
+fn set_cpu_limit(percent: u8, session_id: Option<String>) -> Result<usize, WalletError> {
+    sessions::set_cpu_limit(session_id.as_deref(), percent).map_err(WalletError::from)
+}
+
+/**
+ * 列出正在运行的会话（生成、拆分密钥搜索、压力测试、校准和测速）
+ */
+#[tauri::command]
+fn list_sessions() -> Vec<sessions::SessionSummary> {
+    sessions::list()
+}
+
+// Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
+#[tauri::command]
+fn greet(name: &str) -> String {
+    format!("Hello, {}! You've been greeted from Rust!", name)
+}
+
+/// 进度统计信息
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProgressStats {
+    /// 会话标识
+    pub session_id: String,
+    /// 尝试次数
+    pub attempts: u64,
+    /// 匹配次数
+    pub matches: u64,
+    /// 运行时间（毫秒，不含暂停时间）
+    pub duration: u64,
+    /// 暂停时间（毫秒）
+    pub paused_duration: u64,
+    /// 被 secp256k1 拒绝的候选私钥数量
+    pub rejected_keys: u64,
+    /// 候选私钥被拒绝的比例
+    pub rejection_rate: f64,
+    /// 到目前为止至少找到一个匹配的概率
+    pub confidence: f64,
+    /// 按模式难度（与 estimate_pattern_difficulty 相同的匹配概率）到目前为止应当至少找到一个匹配的概率
+    /// （score 模式和正则表达式无法估计，为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cumulative_success_probability: Option<f64>,
+    /// 按当前速度在接下来一小时内至少找到一个匹配的概率（与已经尝试的次数无关；匹配概率或速度未知时为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub probability_next_hour: Option<f64>,
+    /// 平滑后的速度（每秒尝试次数，还没有完整的测量窗口时为空）
+    pub rate: Option<f64>,
+    /// 最近几秒内的速度（每秒尝试次数，反映当前吞吐量；样本还不够时为空）
+    pub attempts_per_second: Option<f64>,
+    /// 按模式难度、停止条件和当前速度估计的剩余时间（毫秒；score 模式、正则表达式或速度未知时为空）
+    pub estimated_remaining_ms: Option<f64>,
+    /// 计时器精度不足，速度和剩余时间只是粗略估计
+    pub rate_low_precision: bool,
+    /// 各模式的匹配数（只搜索一个模式时也列出；拆分密钥搜索为空）
+    pub pattern_matches: BTreeMap<String, u64>,
+    /// leading_zeros 模式下目前见过的地址开头最多的零的个数（其它模式为空）
+    pub best_zero_nibbles: Option<u32>,
+    /// score 模式目前的最高分（其它模式为空）
+    pub best_score: Option<f64>,
+}
+
+/// 重新订阅后发送的追赶快照
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CatchUpSnapshot {
+    /// 会话标识
+    pub session_id: String,
+    /// 当前进度
+    pub progress: ProgressStats,
+    /// 本次会话已找到的地址（不含私钥）
+    pub found: Vec<Address>,
+}
+
+/// 生成任务暂停或恢复时发送的信息（generation-paused / generation-resumed）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PauseChanged {
+    /// 会话标识
+    pub session_id: String,
+    /// 新的暂停状态（暂停时区分手动暂停、自动暂停和因输出上限暂停）
+    pub state: pause::PauseState,
+}
+
+/// 会话停止时发送的信息
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GenerationStopped {
+    /// 会话标识
+    pub session_id: String,
+    /// 停止原因
+    #[serde(flatten)]
+    pub stop: StopRecord,
+    /// 会话实际执行的匹配条件
+    pub matcher: String,
+    /// 停止时的尝试次数
+    pub attempts: u64,
+    /// 停止时的匹配数
+    pub matches: u64,
+    /// 运行时间（毫秒，不含暂停时间）
+    pub duration: u64,
+    /// 暂停时间（毫秒）
+    pub paused_duration: u64,
+    /// 按会话的匹配概率和速度计算的 50% / 90% / 99% 里程碑
+    pub milestones: Vec<ConfidenceEstimate>,
+    /// 会话所属的队列任务（不是由队列开始的会话为空）
+    pub job_id: Option<String>,
+    /// 会话期间检测到的系统时间跳变（时长不受影响，记录中的时间可能不是单调递增的）
+    pub clock_jumps: Vec<ClockJump>,
+    /// 停止时的工作线程容量
+    pub workers: WorkerCapacity,
+    /// 异常退出的工作线程
+    pub worker_failures: Vec<WorkerFailure>,
+    /// 会话的输出统计、上限和触发状态（不写入结果文件的会话为空）
+    pub output: Option<OutputUsage>,
+}
+
+/// 生成会话开始时发送的信息
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GenerationSessionInfo {
+    /// 会话标识（用于 resubscribe）
+    pub session_id: String,
+    /// 靓号模式
+    pub pattern: String,
+    /// 解析后实际执行的匹配条件（例如 prefix=888 AND suffix=888, case-sensitive）
+    pub matcher: String,
+    /// 来源模板（直接开始时为空）
+    pub template: Option<String>,
+    /// 本次会话的输出目录（绝对路径）
+    pub output_dir: PathBuf,
+    /// 输出目录的来源
+    pub output_source: OutputSource,
+    /// 本次会话使用的评分权重（归一化后）
+    pub score_weights: ScoreWeights,
+    /// 本次会话追加的集合名称（写入新的会话文件时为空）
+    pub collection: Option<String>,
+    /// 本次会话开始时使用的 Keccak-256 实现
+    pub keccak_backend: KeccakBackend,
+    /// 会话所属的队列任务（不是由队列开始的会话为空）
+    pub job_id: Option<String>,
+    /// 私钥的随机数来源
+    pub entropy: EntropyProvenance,
+    /// 实际使用的计算设备（请求的设备不可用时为回退后的设备）
+    pub device: device::Device,
+    /// 会话状态文件（中断后传给 resume_session 恢复；dry_run 时不写入状态文件，为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_file: Option<PathBuf>,
+    /// score 模式的排行榜文件（不是 score 模式或 dry_run 时为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leaderboard_file: Option<PathBuf>,
+}
+
+/// 会话开始时解析一次的输出位置，所有写入方都使用这里的路径
+#[derive(Debug, Clone)]
+struct SessionOutput {
+    /// 输出根目录（FancyWallets）
+    root: OutputRoot,
+    /// 会话输出目录（FancyWallets/<链名称>，结果、回放和清单都写在这里）
+    session_dir: PathBuf,
+    /// 生成的链（结果文件的地址列和 chain 列按它写入）
+    chain: Chain,
+    /// 本次会话追加的 CSV 文件
+    csv_path: PathBuf,
+    /// 追加的集合名称（写入会话文件时为空）
+    collection: Option<String>,
+    /// 逐行加密结果文件的密钥（不加密时为空）
+    cipher: Option<OutputCipher>,
+    /// CSV 中私钥列的写法
+    private_keys: CsvPrivateKeys,
+    /// 写入的结果文件格式
+    format: OutputFormat,
+    /// 保存钱包失败后整次写入的重试策略
+    save_retry: RetryPolicy,
+    /// 另外写入的结果数据库（不写入 wallets.db 时为空）
+    database: Option<results_db::SessionDb>,
+}
+
+/// 结果的存储后端（CSV、JSON Lines 和 wallets.db 可以同时写入，每个后端分别重试）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResultBackend {
+    Csv,
+    Jsonl,
+    Database,
+}
+
+impl SessionOutput {
+    /**
+     * 按 output_format 设置写入的结果文件格式
+     */
+    fn with_format(self, format: OutputFormat) -> Self {
+        SessionOutput { format, ..self }
+    }
+
+    /**
+     * 按 save_retries 设置保存失败后的重试策略
+     */
+    fn with_save_retry(self, save_retry: RetryPolicy) -> Self {
+        SessionOutput { save_retry, ..self }
+    }
+
+    /**
+     * 按 results_db 另外写入结果数据库
+     */
+    fn with_database(self, database: Option<results_db::SessionDb>) -> Self {
+        SessionOutput { database, ..self }
+    }
+
+    /**
+     * 本次会话写入的存储后端
+     */
+    fn backends(&self) -> Vec<ResultBackend> {
+        [
+            (ResultBackend::Csv, self.format.writes_csv()),
+            (ResultBackend::Jsonl, self.format.writes_jsonl()),
+            (ResultBackend::Database, self.database.is_some()),
+        ]
+        .into_iter()
+        .filter(|(_, writes)| *writes)
+        .map(|(backend, _)| backend)
+        .collect()
+    }
+
+    /**
+     * 结果文件所在的目录（会话输出目录或集合目录）
+     */
+    fn csv_dir(&self) -> &std::path::Path {
+        self.csv_path.parent().unwrap_or(&self.session_dir)
+    }
+
+    /**
+     * JSON Lines 结果文件（与 CSV 文件同名，扩展名为 .jsonl）
+     */
+    fn jsonl_path(&self) -> PathBuf {
+        self.csv_path.with_extension(saved_wallets::JSONL_EXTENSION)
+    }
+
+    /**
+     * 是否另外写入 Solana 密钥对文件（只在结果文件保存明文私钥时）
+     */
+    fn writes_keypair(&self) -> bool {
+        self.chain == Chain::Solana && self.private_keys == CsvPrivateKeys::Full && self.cipher.is_none()
+    }
+
+    /**
+     * 本次会话写入的结果文件
+     */
+    fn result_files(&self) -> Vec<PathBuf> {
+        let csv = self.format.writes_csv().then(|| self.csv_path.clone());
+        csv.into_iter().chain(self.format.writes_jsonl().then(|| self.jsonl_path())).collect()
+    }
+}
+
+/// 每个钱包单独的 JSON 文件所在的目录（FancyWallets/json，只在 emit_json_files 时写入）
+const WALLET_JSON_DIR: &str = "json";
+
+/// JSON Lines 结果文件中的一行：序列化的钱包信息（字段随 Wallet 变化），加上模式、链和找到时间
+#[derive(Serialize)]
+struct WalletRecord<'a> {
+    /// 钱包信息
+    #[serde(flatten)]
+    wallet: &'a Wallet,
+    /// 靓号模式
+    pattern: &'a str,
+    /// 地址所属的链
+    chain: Chain,
+    /// 找到时间（与钱包的 created_at 相同，保留这个字段供旧版本读取）
+    timestamp: &'a str,
+}
+
+/// 会话的 keystore 输出设置（密码只保存在内存中，不写入模板、队列、回放或清单）
+struct KeystoreOutput {
+    /// 加密 keystore 的密码
+    password: String,
+    /// 只写入 keystore，不把私钥写入 CSV
+    only: bool,
+}
+
+impl KeystoreOutput {
+    /**
+     * 检查 generate_fancy_wallet 的 keystore 参数
+     * 
+     * @param password - keystore 密码（为空时不写入 keystore）
+     * @param only - 是否只写入 keystore
+     * @param params - 生成参数
+     */
+    fn new(password: Option<String>, only: Option<bool>, params: &GenerationParams) -> Result<Option<Self>, String> {
+        let only = only.unwrap_or_default();
+        let Some(password) = password else {
+            return if only { Err("keystore_only 需要同时提供 keystore_password".to_string()) } else { Ok(None) };
+        };
+        if password.is_empty() {
+            return Err("keystore 密码不能为空".to_string());
+        }
+        if params.rng_mode.is_some_and(RngMode::is_test) {
+            return Err("确定性测试模式的钱包不能导出为 keystore，以免与真实钱包混在一起".to_string());
+        }
+        if params.chain()? == Chain::Solana {
+            return Err("keystore 保存的是 secp256k1 私钥，不能用于 Solana（Solana 的密钥对另外保存在 keypairs 目录中）".to_string());
+        }
+        if only && params.collection.is_some() {
+            return Err("keystore_only 不写入 CSV，不能与 collection 同时使用".to_string());
+        }
+        if only && params.csv_private_keys.is_some() {
+            return Err("keystore_only 不写入 CSV，不能与 csv_private_keys 同时使用".to_string());
+        }
+        Ok(Some(KeystoreOutput { password, only }))
+    }
+}
+
+/// 只保存在内存中的会话输出密码（只有 generate_fancy_wallet 提供）
+#[derive(Default)]
+struct OutputSecrets {
+    /// keystore 输出设置
+    keystore: Option<KeystoreOutput>,
+    /// 加密结果文件的密码
+    encrypt_output: Option<String>,
+}
+
+impl OutputSecrets {
+    /**
+     * 检查 generate_fancy_wallet 的 keystore 和加密输出参数
+     * 
+     * @param keystore_password - keystore 密码
+     * @param keystore_only - 是否只写入 keystore
+     * @param encrypt_output - 加密结果文件的密码
+     * @param params - 生成参数
+     */
+    fn new(keystore_password: Option<String>, keystore_only: Option<bool>, encrypt_output: Option<String>, params: &GenerationParams) -> Result<Self, String> {
+        let keystore = KeystoreOutput::new(keystore_password, keystore_only, params)?;
+        if let Some(password) = &encrypt_output {
+            if password.is_empty() {
+                return Err("加密结果文件的密码不能为空".to_string());
+            }
+            if params.collection.is_some() {
+                return Err("集合会被之后的会话继续追加，不能加密；encrypt_output 不能与 collection 同时使用".to_string());
+            }
+            if keystore.as_ref().is_some_and(|keystore| keystore.only) {
+                return Err("keystore_only 不写入 CSV，不能与 encrypt_output 同时使用".to_string());
+            }
+        }
+        Ok(OutputSecrets { keystore, encrypt_output })
+    }
+}
+
+/// 每找到一个匹配时发送的信息（wallet-found，默认不包含私钥）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WalletFound {
+    /// 会话标识
+    pub session_id: String,
+    /// 会话内的序号
+    pub index: u64,
+    /// 钱包地址
+    pub address: Address,
+    /// 该链格式的地址（只有不使用以太坊格式的链才有，例如波场 T 开头的地址）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_address: Option<String>,
+    /// 私钥（只有开启 emit_private_key 时才有）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<SecretHex>,
+    /// 助记词（只有开启 emit_private_key 且由助记词派生时才有）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mnemonic: Option<String>,
+    /// 找到时的尝试次数
+    pub attempts: u64,
+    /// 找到时的运行时间（毫秒，不含暂停时间）
+    pub duration: u64,
+    /// 私钥的随机数来源
+    pub rng_mode: RngMode,
+    /// 满足的模式（结果写入该模式的文件）
+    pub pattern: String,
+    /// 钱包满足的模式（与 Wallet 的 matched_pattern 相同）
+    pub matched_pattern: String,
+    /// 找到时间（与 Wallet 的 created_at 相同）
+    pub created_at: String,
+    /// leading_zeros 模式下地址开头实际的零的个数（其它模式为空）
+    pub zero_nibbles: Option<usize>,
+    /// char_count 模式下实际计数的字符和个数（其它模式为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub char_count: Option<CharCount>,
+    /// 地址的指纹短语
+    pub fingerprint: String,
+    /// 按会话评分权重计算的评分（0–100）
+    pub score: f64,
+    /// 地址中满足模式的字符区间
+    pub matched_spans: Vec<MatchedSpan>,
+    /// 确定性测试模式生成的测试钱包（不能用于真实资金）
+    pub test_wallet: bool,
+    /// 地址是否已有链上记录（交易数或余额不为零；只有设置 verify_unused 时才有，查询失败时为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_chain_activity: Option<bool>,
+    /// 无法查询链上记录的原因（此时 on_chain_activity 为空，记录未知）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_chain_error: Option<String>,
+}
+
+/// 批量写入时每批发送一次的信息（wallets-found，代替逐个的 wallet-found）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WalletsFound {
+    /// 会话标识
+    pub session_id: String,
+    /// 这一批的匹配（按序号排列，与 wallet-found 的内容相同）
+    pub wallets: Vec<WalletFound>,
+}
+
+impl WalletFound {
+    /**
+     * 由保存的钱包构造事件
+     * 
+     * @param session_id - 会话标识
+     * @param wallet - 钱包信息
+     * @param pattern - 满足的模式
+     * @param zero_nibbles - leading_zeros 模式下实际的零的个数
+     * @param char_count - char_count 模式下实际的字符和个数
+     * @param weights - 会话的评分权重
+     * @param emit_private_key - 是否带有私钥（和助记词）
+     */
+    pub(crate) fn new(
+        session_id: &str,
+        wallet: &Wallet,
+        pattern: &str,
+        zero_nibbles: Option<usize>,
+        char_count: Option<CharCount>,
+        weights: &ScoreWeights,
+        emit_private_key: bool,
+    ) -> Self {
+        WalletFound {
+            session_id: session_id.to_string(),
+            index: wallet.index,
+            address: wallet.address,
+            chain_address: wallet.chain_address.clone(),
+            private_key: emit_private_key.then(|| wallet.private_key.clone()),
+            mnemonic: wallet.mnemonic.clone().filter(|_| emit_private_key),
+            attempts: wallet.attempts,
+            duration: wallet.duration,
+            rng_mode: wallet.rng_mode,
+            pattern: pattern.to_string(),
+            matched_pattern: wallet.matched_pattern.clone(),
+            created_at: wallet.created_at.clone(),
+            zero_nibbles,
+            char_count,
+            fingerprint: fingerprint::phrase(&wallet.address),
+            score: scoring::score(&wallet.address, weights).score,
+            matched_spans: wallet.matched_spans.clone(),
+            test_wallet: wallet.test_wallet,
+            on_chain_activity: None,
+            on_chain_error: None,
+        }
+    }
+}
+
+/// 会话最后找到的钱包（get_last_wallet 的返回值）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LastWallet {
+    /// 会话标识
+    pub session_id: String,
+    /// 会话是否仍在运行
+    pub running: bool,
+    /// 最后找到的钱包（还没有找到时为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wallet: Option<WalletFound>,
+    /// 私钥和助记词是否已隐去
+    pub redacted: bool,
+    /// 说明（还没有找到钱包时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+fn last_wallets() -> &'static Mutex<HashMap<String, Option<WalletFound>>> {
+    LAST_WALLETS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/**
+ * 新的生成会话开始：清除已经结束的会话最后找到的钱包（同时运行的其它会话保留），登记这个会话
+ * 
+ * @param session_id - 新会话的标识
+ */
+pub(crate) fn reset_last_wallets(session_id: &str) {
+    let running: Vec<String> = sessions::list().into_iter().map(|session| session.session_id).collect();
+    if let Ok(mut map) = last_wallets().lock() {
+        map.retain(|id, _| running.contains(id));
+        map.insert(session_id.to_string(), None);
+    }
+}
+
+/**
+ * 记录会话最后找到的钱包
+ * 
+ * @param found - 找到的钱包（带私钥）
+ */
+pub(crate) fn record_last_wallet(found: WalletFound) {
+    if let Ok(mut map) = last_wallets().lock() {
+        map.insert(found.session_id.clone(), Some(found));
+    }
+}
+
+/**
+ * 读取会话最后找到的钱包；会话还没有找到钱包时返回说明而不是错误
+ * 
+ * @param session_id - 会话标识
+ * @param redact - 是否隐去私钥和助记词
+ */
+pub(crate) fn last_wallet(session_id: &str, redact: bool) -> Result<LastWallet, String> {
+    let map = last_wallets().lock().map_err(|_| "会话状态不可用".to_string())?;
+    let found = map.get(session_id).ok_or_else(|| format!("会话 {} 不存在，或它的结果已在新的会话开始时清除", session_id))?;
+    let wallet = found.clone().map(|wallet| match redact {
+        true => WalletFound { private_key: None, mnemonic: None, ..wallet },
+        false => wallet,
+    });
+    Ok(LastWallet {
+        session_id: session_id.to_string(),
+        running: sessions::list().iter().any(|session| session.session_id == session_id),
+        message: wallet.is_none().then(|| "还没有找到钱包".to_string()),
+        wallet,
+        redacted: redact,
+    })
+}
+
+/// score 模式刷新最高分时发送的事件（new-best）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NewBest {
+    /// 会话标识
+    pub session_id: String,
+    /// 会话内的序号（与 wallet-found 相同）
+    pub index: u64,
+    /// 钱包地址
+    pub address: Address,
+    /// 评分函数
+    pub score_function: ScoreFunction,
+    /// 新的最高分
+    pub best_score: f64,
+    /// 之前的最高分（第一条记录为空）
+    pub previous_score: Option<f64>,
+}
+
+/// 生成方式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GenerationMode {
+    /// 保存与模式匹配的钱包（默认）
+    #[default]
+    Match,
+    /// 为每个地址评分，保存每次刷新的最高分
+    Score,
+}
+
+/// generate_fancy_wallet 的参数：生成参数加上只在开始时使用的字段（各字段的说明见 generate_fancy_wallet；含有密码，不实现 Debug）
+#[derive(Deserialize, Default)]
+pub struct GenerateWalletRequest {
+    /// 生成参数（pattern 可以省略，由预设、prefix/suffix、patterns 或其它模式类型的字段代替）
+    #[serde(flatten)]
+    pub params: GenerationParams,
+    /// 内置模式预设的标识
+    #[serde(default)]
+    pub preset_id: Option<String>,
+    /// 最大尝试次数（0 表示不限制）
+    #[serde(default)]
+    pub max_attempts: Option<u64>,
+    /// 地址前缀
+    #[serde(default)]
+    pub prefix: Option<String>,
+    /// 地址后缀
+    #[serde(default)]
+    pub suffix: Option<String>,
+    /// 最大匹配数（0 表示不限制）
+    #[serde(default)]
+    pub max_matches: Option<u64>,
+    /// 最长运行时间（毫秒，0 表示不限制）
+    #[serde(default)]
+    pub max_duration_ms: Option<u64>,
+    /// 同时搜索的多个模式
+    #[serde(default)]
+    pub patterns: Option<Vec<String>>,
+    /// leading_zeros 模式的地址开头最少的零的个数
+    #[serde(default)]
+    pub min_zero_nibbles: Option<u8>,
+    /// caps_prefix 模式只看大小写的开头字符数
+    #[serde(default)]
+    pub caps_prefix: Option<u8>,
+    /// caps_prefix 模式改为要求开头的字母都是小写
+    #[serde(default)]
+    pub caps_lowercase: Option<bool>,
+    /// char_count 模式计数的字符
+    #[serde(default)]
+    pub character: Option<char>,
+    /// char_count 模式字符最少出现的次数
+    #[serde(default)]
+    pub min_count: Option<u8>,
+    /// 生成方式（默认 match）
+    #[serde(default)]
+    pub mode: Option<GenerationMode>,
+    /// 会话标识（默认由模式和开始时间生成）
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// keystore 密码（只保存在内存中）
+    #[serde(default)]
+    pub keystore_password: Option<String>,
+    /// 是否只保存 keystore
+    #[serde(default)]
+    pub keystore_only: Option<bool>,
+    /// 加密结果文件的密码（只保存在内存中）
+    #[serde(default)]
+    pub encrypt_output: Option<String>,
+}
+
+/// 保存钱包失败（save-error 事件）：重试后仍未能写入，钱包保留在内存中，随命令结果的 unsaved_wallets 返回
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SaveError {
+    /// 会话标识
+    pub session_id: String,
+    /// 错误信息
+    pub message: String,
+    /// 未能保存的钱包（与 wallet-found 事件相同，只有开启 emit_private_key 时才带有私钥）
+    pub wallet: WalletFound,
+    /// 本次会话目前未能保存的钱包数
+    pub unsaved: usize,
+}
+
+/// 生成命令的结果：最后找到的钱包（字段与 Wallet 相同）、本次找到的钱包，以及重试后仍未能保存的钱包
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GenerationResult {
+    /// 最后找到的钱包
+    #[serde(flatten)]
+    pub wallet: Wallet,
+    /// 本次会话找到的钱包（按找到的顺序，最多 max_returned_wallets 个；恢复的会话不含恢复前找到的钱包）
+    pub wallets: Vec<Wallet>,
+    /// 是否有钱包因为达到 max_returned_wallets 而没有返回（它们仍在结果文件中）
+    pub wallets_truncated: bool,
+    /// 会话的计数（尝试次数、匹配数和运行时间，恢复的会话包含恢复前的计数）
+    pub totals: SearchTotals,
+    /// 未能写入结果文件的钱包（含完整的私钥，需要用户手动保存；全部保存成功时为空）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unsaved_wallets: Vec<Wallet>,
+    /// 恢复的会话（由 resume_session 开始时才有；恢复的是参数和统计，不是随机数状态）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resumed: Option<ResumedSession>,
+    /// 是否为 dry_run 会话（钱包的私钥为 DRY_RUN_PRIVATE_KEY，没有保存任何文件）
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// 安全警告（例如随机数生成器疑似异常）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SecurityWarning {
+    /// 警告信息
+    pub message: String,
+    /// 尝试次数
+    pub attempts: u64,
+    /// 被拒绝的候选私钥数量
+    pub rejected_keys: u64,
+    /// 候选私钥被拒绝的比例
+    pub rejection_rate: f64,
+}
+
+/**
+ * 解析并校验输出根目录（save_path 优先，否则使用当前用户配置的默认 FancyWallets 目录）
+ * 
+ * @param save_path - 保存路径（可选）
+ */
+fn resolve_output_root(save_path: Option<String>) -> Result<OutputRoot, String> {
+    output_root::resolve(
+        save_path.as_deref(),
+        || {
+            // 默认使用 Documents 目录，并按当前用户配置隔离
+            let documents_dir = directories::UserDirs::new()
+                .ok_or("无法获取用户目录")?
+                .document_dir()
+                .ok_or("无法获取 Documents 目录")?
+                .to_path_buf();
+            Ok(profiles::default_wallets_dir(&active_profile()?, &documents_dir))
+        },
+        output_root::app_bundle_dir().as_deref(),
+    )
+}
+
+/**
+ * 解析输出根目录并在开始前检查可以写入（保存路径存在并且是目录，FancyWallets 目录可以创建和写入）
+ * 
+ * @param save_path - 保存路径（可选）
+ */
+fn preflight_output_root(save_path: Option<String>) -> Result<OutputRoot, WalletError> {
+    let root = resolve_output_root(save_path)?;
+    output_root::preflight(&root)?;
+    Ok(root)
+}
+
+/**
+ * 解析钱包保存目录（save_path 或当前用户配置的默认 FancyWallets 目录）
+ * 
+ * @param save_path - 保存路径（可选）
+ * @returns FancyWallets 目录路径（不保证已存在）
+ */
+fn resolve_wallets_dir(save_path: Option<String>) -> Result<PathBuf, String> {
+    Ok(resolve_output_root(save_path)?.wallets_dir)
+}
+
+/**
+ * 会话开始时解析输出位置：校验根目录，并确认展开后的文件名仍在会话目录中
+ * 
+ * @param save_path - 保存路径（可选）
+ * @param label - 文件名中的模式部分（由 session_file_labels 生成；为空时全部模式写入合并的 wallets_<时间>.csv）
+ * @param chain - 生成的链
+ * @param collection - 追加的集合名称（可选，结果写入 FancyWallets/collections/<名称>.csv）
+ * @param rng_mode - 随机数来源（确定性测试模式的文件名带有 TEST-ONLY 水印）
+ * @param stamp - 会话开始时间（文件名中的时间部分，每个会话各自的）
+ * @param filename_template - 结果文件名的模板（wallet_ 之后的部分，由 filename_template::render 展开）
+ * @param encrypt_output - 加密结果文件的密码（可选，文件名为 wallet_<模式>_<时间>.csv.enc，每个文件各自派生密钥）
+ * @param private_keys - CSV 中私钥列的写法（集合只能保存完整的私钥）
+ */
+#[allow(clippy::too_many_arguments)]
+fn resolve_session_output(
+    save_path: Option<String>,
+    label: Option<&str>,
+    chain: Chain,
+    collection: Option<String>,
+    rng_mode: RngMode,
+    stamp: &str,
+    filename_template: &str,
+    encrypt_output: Option<&str>,
+    private_keys: CsvPrivateKeys,
+) -> Result<SessionOutput, String> {
+    let root = resolve_output_root(save_path)?;
+    let session_dir = chain.dir(&root.wallets_dir);
+    let csv_path = match &collection {
+        Some(_) if rng_mode.is_test() => return Err("确定性测试模式的钱包不能追加到集合中，以免与真实钱包混在一起".to_string()),
+        Some(_) if private_keys != CsvPrivateKeys::Full => return Err("集合必须保存完整的私钥，csv_private_keys 不能与 collection 同时使用".to_string()),
+        Some(name) => collections::collection_path(&root.wallets_dir, name)?,
+        None => {
+            let watermark = if rng_mode.is_test() { format!("{}_", entropy::TEST_WATERMARK) } else { String::new() };
+            let stem = match label {
+                Some(label) => format!("wallet_{}{}", watermark, filename_template::render(filename_template, label, stamp, chain)?),
+                None => format!("wallets_{}{}", watermark, stamp),
+            };
+            // 加密文件的每个文件各自派生密钥，已有的同名文件总是无法追加
+            let header = encrypt_output.is_none().then(|| private_keys.header());
+            let suffix = if encrypt_output.is_some() { encrypted_csv::ENCRYPTED_SUFFIX } else { "" };
+            session_csv_path(&session_dir, &stem, suffix, header)?
+        }
+    };
+    let cipher = match encrypt_output {
+        Some(_) if collection.is_some() => return Err("集合不能加密".to_string()),
+        Some(password) => Some(OutputCipher::new(password)?),
+        None => None,
+    };
+    Ok(SessionOutput {
+        root,
+        session_dir,
+        chain,
+        csv_path,
+        collection,
+        cipher,
+        private_keys,
+        format: OutputFormat::default(),
+        save_retry: retry::save_policy(None)?,
+        database: None,
+    })
+}
+
+/**
+ * 会话结果文件的路径：同名文件已存在且标题与本次写入的不同时（例如另一种私钥列写法）改用
+ * wallet_<模式>_<时间>-2.csv 等带序号的文件名，不在旧文件中混入不同列的行
+ * 
+ * @param session_dir - 会话输出目录
+ * @param stem - 文件名中 .csv 之前的部分
+ * @param suffix - .csv 之后的后缀（加密文件为 .enc）
+ * @param header - 本次写入的 CSV 标题（为空时已有的非空文件都不能追加）
+ */
+pub(crate) fn session_csv_path(session_dir: &std::path::Path, stem: &str, suffix: &str, header: Option<&str>) -> Result<PathBuf, String> {
+    let mut attempt = 1;
+    loop {
+        let filename = match attempt {
+            1 => format!("{}.csv{}", stem, suffix),
+            n => format!("{}-{}.csv{}", stem, n, suffix),
+        };
+        let path = output_root::expand(session_dir, &filename)?;
+        let mut existing = String::new();
+        let compatible = match std::fs::File::open(&path).map(|file| std::io::BufReader::new(file).read_line(&mut existing)) {
+            Ok(Ok(_)) if !existing.trim().is_empty() => header.is_some_and(|header| existing.trim_end() == header),
+            _ => true,
+        };
+        if compatible {
+            return Ok(path);
+        }
+        attempt += 1;
+    }
+}
+
+/**
+ * 每个模式在结果文件名中的形式；不同模式在文件名中的形式相同时（例如 *abc* 和 abc）加上序号区分
+ * 
+ * @param patterns - 会话搜索的全部模式
+ */
+pub(crate) fn session_file_labels(patterns: &[String]) -> Vec<String> {
+    let mut labels: Vec<String> = Vec::with_capacity(patterns.len());
+    for (i, pattern) in patterns.iter().enumerate() {
+        let label = pattern::file_label(pattern);
+        let label = if labels.contains(&label) { format!("{}-{}", label, i + 1) } else { label };
+        labels.push(label);
+    }
+    labels
+}
+
+/**
+ * 将钱包信息保存到文件
+ * 
+ * @param wallet - 钱包信息
+ * @param pattern - 靓号模式
+ * @param output - 会话的输出位置
+ * @param index - 会话的钱包序号
+ * @returns 写入的字节数和重试次数（含写入内部对短暂错误的重试和整次写入的重试）
+ */
+fn save_wallet_to_file(wallet: &mut Wallet, pattern: &str, output: &SessionOutput, index: &mut WalletIndex) -> Result<WrittenRow, String> {
+    // 先分配序号再写入，使行顺序与序号一致（写入失败时序号仍然保留给该钱包）
+    index.assign(wallet);
+    save_wallets_to_file(&[(&*wallet, pattern)], output)
+}
+
+/**
+ * 将一批已经分配序号的钱包保存到会话的全部存储后端：每个结果文件只打开一次，全部行一起写入
+ * 
+ * @param rows - 钱包信息和结果文件模式列中的模式（按序号排列）
+ * @param output - 会话的输出位置
+ * @returns 写入的字节数和重试次数（含写入内部对短暂错误的重试和整次写入的重试）
+ */
+fn save_wallets_to_file(rows: &[(&Wallet, &str)], output: &SessionOutput) -> Result<WrittenRow, String> {
+    save_wallets_to(&output.backends(), rows, output)
+}
+
+/**
+ * 将一批已经分配序号的钱包保存到指定的存储后端
+ * 
+ * @param backends - 写入的存储后端（按顺序写入）
+ * @param rows - 钱包信息和结果文件模式列中的模式（按序号排列）
+ * @param output - 会话的输出位置
+ * @returns 写入的字节数和重试次数（含写入内部对短暂错误的重试和整次写入的重试）
+ */
+fn save_wallets_to(backends: &[ResultBackend], rows: &[(&Wallet, &str)], output: &SessionOutput) -> Result<WrittenRow, String> {
+    // 每个后端分别按 save_retries 重试整次写入，已经写入的后端不会因另一个后端失败而重复写入
+    let save = |write: &dyn Fn() -> Result<WrittenRow, String>| -> Result<WrittenRow, String> {
+        let (row, save_retries) = retry::retry_all(&output.save_retry, write);
+        let row = row.map_err(|e| if save_retries > 0 { format!("{}（整次写入又重试了 {} 次）", e, save_retries) } else { e })?;
+        Ok(WrittenRow { bytes: row.bytes, retries: row.retries + save_retries })
+    };
+    
+    // 创建 wallets 目录（按链分开保存；集合保存在 collections 目录中）
+    let dir = output.csv_dir();
+    let mut written = save(&|| {
+        let (created, dir_retries) = retry::retry_io(&RetryPolicy::default(), || std::fs::create_dir_all(dir));
+        created.map_err(|e| retry_error("无法创建钱包目录", e, dir_retries))?;
+        Ok(WrittenRow { bytes: 0, retries: dir_retries })
+    })?;
+    for backend in backends {
+        let row = save(&|| match (backend, &output.cipher, &output.database) {
+            (ResultBackend::Csv, Some(cipher), _) => write_encrypted_wallet_rows(&output.csv_path, rows, output.chain, output.private_keys, cipher),
+            (ResultBackend::Csv, None, _) => write_wallet_rows(&output.csv_path, rows, output.chain, output.private_keys),
+            (ResultBackend::Jsonl, _, _) => write_jsonl_rows(&output.jsonl_path(), rows, output.chain),
+            (ResultBackend::Database, _, Some(database)) => results_db::insert_wallets(database, rows, output.chain),
+            (ResultBackend::Database, _, None) => Ok(WrittenRow { bytes: 0, retries: 0 }),
+        })?;
+        written = WrittenRow { bytes: written.bytes + row.bytes, retries: written.retries + row.retries };
+    }
+    Ok(written)
+}
+
+/**
+ * dry_run 会话中代替找到的钱包的保存：分配序号，把私钥换成 DRY_RUN_PRIVATE_KEY 并去掉助记词，不写入任何文件
+ * 
+ * @param wallet - 钱包信息
+ * @param index - 会话的钱包序号
+ * @returns 写入的字节数（总是 0）
+ */
+pub(crate) fn dry_run_wallet(wallet: &mut Wallet, index: &mut WalletIndex) -> Result<WrittenRow, String> {
+    index.assign(wallet);
+    wallet.private_key = SecretHex::from(DRY_RUN_PRIVATE_KEY.to_string());
+    wallet.mnemonic = None;
+    Ok(WrittenRow { bytes: 0, retries: 0 })
+}
+
+/**
+ * 向合约地址结果文件追加一行（目录不存在时先创建，文件为空时先写入标题）
+ * 
+ * @param file_path - 结果文件路径（contract_<模式>_<时间>.csv）
+ * @param found - 满足模式的合约地址及其部署者私钥
+ * @param pattern - 靓号模式
+ * @returns 写入的字节数和重试次数
+ */
+pub(crate) fn write_contract_row(file_path: &std::path::Path, found: &ContractMatch, pattern: &str) -> Result<WrittenRow, String> {
+    let dir = file_path.parent().ok_or("结果文件路径无效")?;
+    let (created, dir_retries) = retry::retry_io(&RetryPolicy::default(), || std::fs::create_dir_all(dir));
+    created.map_err(|e| retry_error("无法创建钱包目录", e, dir_retries))?;
+    let row = append_with_retry(file_path, &contract::format_row(found, pattern), &format!("{}\n", contract::CSV_HEADER))?;
+    Ok(WrittenRow { bytes: row.bytes, retries: dir_retries + row.retries })
+}
+
+/**
+ * 将钱包加密保存为 keystore V3 文件（FancyWallets/<链名称>/keystore/UTC--<时间>--<地址>.json）
+ * 
+ * 只写入 keystore、不写入 CSV 时在这里分配序号。按 geth 的默认参数派生密钥，每个钱包约需 1 秒和 256 MiB 内存。
+ * 
+ * @param wallet - 钱包信息
+ * @param keystore - keystore 输出设置
+ * @param output - 会话的输出位置
+ * @param index - 会话的钱包序号
+ * @returns keystore 文件路径和写入的字节数
+ */
+fn save_wallet_keystore(wallet: &mut Wallet, keystore: &KeystoreOutput, output: &SessionOutput, index: &mut WalletIndex) -> Result<(PathBuf, u64), String> {
+    if keystore.only {
+        index.assign(wallet);
+    }
+    let private_key = wallet.private_key.decode().ok_or("私钥不是 32 字节的十六进制")?;
+    let json = keystore::encrypt(&private_key, &wallet.address, &keystore.password, keystore::ScryptParams::STANDARD)?;
+    keystore::write_file(&output.session_dir.join("keystore"), &wallet.address, &json)
+}
+
+/**
+ * 将 Solana 钱包保存为 solana-keygen 格式的密钥对文件（FancyWallets/solana/keypairs/<地址>.json）
+ * 
+ * @param wallet - 钱包信息
+ * @param output - 会话的输出位置
+ * @returns 密钥对文件路径和写入的字节数
+ */
+fn save_solana_keypair(wallet: &Wallet, output: &SessionOutput) -> Result<(PathBuf, u64), String> {
+    let seed = wallet.private_key.decode().ok_or("私钥不是 32 字节的十六进制")?;
+    let address = wallet.chain_address.as_deref().ok_or("缺少 Solana 地址")?;
+    solana::write_keypair_file(&output.session_dir.join(solana::KEYPAIR_DIR), address, &solana::keypair_json(&seed))
+}
+
+/**
+ * 按 emit_json_files 将钱包单独保存为 JSON 文件（FancyWallets/json/wallet_<地址>.json）
+ * 
+ * @param wallet - 钱包信息
+ * @param pattern - 结果文件模式列中的模式
+ * @param output - 会话的输出位置
+ * @returns JSON 文件路径和写入的字节数
+ */
+fn save_wallet_json(wallet: &Wallet, pattern: &str, output: &SessionOutput) -> Result<(PathBuf, u64), String> {
+    write_wallet_json_file(&output.root.wallets_dir.join(WALLET_JSON_DIR), wallet, pattern, output.chain)
+}
+
+/**
+ * 将一个钱包写入单独的 JSON 文件：内容是序列化的钱包（字段随 Wallet 变化）加上模式、链和找到时间，与 JSON Lines 的一行相同
+ * 
+ * 文件名为 wallet_<地址>.json（确定性测试模式的钱包带有 TEST-ONLY 前缀）。同名文件已存在时，内容完全相同视为已经保存，
+ * 内容不同时返回错误，不覆盖原文件。
+ * 
+ * @param dir - JSON 文件目录（不存在时创建）
+ * @param wallet - 钱包信息
+ * @param pattern - 靓号模式
+ * @param chain - 地址所属的链
+ * @returns 文件路径和写入的字节数（已有相同内容的文件时为 0）
+ */
+pub(crate) fn write_wallet_json_file(dir: &std::path::Path, wallet: &Wallet, pattern: &str, chain: Chain) -> Result<(PathBuf, u64), String> {
+    let record = WalletRecord { wallet, pattern, chain, timestamp: &wallet.created_at };
+    let mut json = Zeroizing::new(serde_json::to_string_pretty(&record).map_err(|e| format!("无法序列化钱包信息: {}", e))?);
+    json.push('\n');
+    let address = wallet.chain_address.clone().unwrap_or_else(|| chain.format_address(wallet.address));
+    let prefix = if wallet.test_wallet { format!("{}_", entropy::TEST_WATERMARK) } else { String::new() };
+    std::fs::create_dir_all(dir).map_err(|e| format!("无法创建 JSON 目录: {}", e))?;
+    let path = dir.join(format!("{}wallet_{}.json", prefix, address));
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = match options.open(&path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+            let existing = Zeroizing::new(std::fs::read(&path).map_err(|e| format!("无法读取已有的 JSON 文件: {}", e))?);
+            return if *existing == json.as_bytes() {
+                Ok((path, 0))
+            } else {
+                Err(format!("{} 已存在且内容不同，未覆盖", path.display()))
+            };
+        }
+        Err(e) => return Err(format!("无法创建 JSON 文件: {}", e)),
+    };
+    file.write_all(json.as_bytes()).map_err(|e| format!("无法写入 JSON 文件: {}", e))?;
+    file.sync_all().map_err(|e| format!("无法写入 JSON 文件: {}", e))?;
+    Ok((path, json.len() as u64))
+}
+
+/**
+ * 重试用尽后的错误信息（附带重试次数）
+ */
+fn retry_error(context: &str, e: std::io::Error, retries: u32) -> String {
+    if retries > 0 {
+        format!("{}: {}（已重试 {} 次）", context, e, retries)
+    } else {
+        format!("{}: {}", context, e)
+    }
+}
+
+/**
+ * 会话结束时统一产物的修改时间并写入清单（manifest_<模式>_<时间>.json）
+ * 
+ * @param recorder - 会话事件回放记录器（写入清单前关闭）
+ * @param pattern - 靓号模式
+ * @param saved_files - 产生过匹配的结果文件（集合除外）
+ * @param output - 会话的输出位置
+ * @param stop - 会话停止原因
+ * @param details - 写入清单的会话信息（模板、评分权重、集合等）
+ * @param stamp - 会话开始时间（没有回放文件时用于清单的文件名）
+ */
+fn finalize_session_artifacts(
+    recorder: Option<ReplayRecorder>,
+    pattern: &str,
+    saved_files: &[PathBuf],
+    output: &SessionOutput,
+    stop: &StopRecord,
+    details: &manifest::SessionDetails,
+    stamp: &str,
+) -> Result<(), String> {
+    // 只有产生过匹配时 CSV 才属于本次会话的产物；集合会被之后的会话继续追加，不作为产物记录
+    let mut artifacts = saved_files.to_vec();
+    let replay_path = recorder.map(|recorder| recorder.path().to_path_buf());
+    if let Some(path) = &replay_path {
+        artifacts.push(path.clone());
+    }
+    if artifacts.is_empty() {
+        return Ok(());
+    }
+    
+    // 清单与回放文件使用同一个会话名
+    let session_name = replay_path
+        .as_ref()
+        .and_then(|path| path.file_stem())
+        .and_then(|stem| stem.to_str())
+        .and_then(|stem| stem.strip_prefix("replay_"))
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| format!("{}_{}", pattern::file_label(pattern), stamp));
+    let manifest_path = output_root::expand(&output.session_dir, &format!("manifest_{}.json", session_name))?;
+    manifest::write_session_manifest(&manifest_path, pattern, &artifacts, stop, details)?;
+    Ok(())
+}
+
+/// 一次追加钱包信息的结果
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct WrittenRow {
+    /// 写入的字节数（新文件包含标题行）
+    pub bytes: u64,
+    /// 遇到短暂错误（杀毒软件锁定文件、外接硬盘短暂无响应等）后的重试次数
+    pub retries: u32,
+}
+
+/**
+ * 向 CSV 文件追加一行钱包信息（文件不存在时先写入标题）
+ * 
+ * @param file_path - CSV 文件路径
+ * @param wallet - 钱包信息
+ * @param pattern - 靓号模式
+ * @param chain - 地址所属的链
+ * @param private_keys - 私钥列的写法（标题随之不同）
+ * @returns 写入的字节数和重试次数
+ */
+pub(crate) fn write_wallet_row(
+    file_path: &std::path::Path,
+    wallet: &Wallet,
+    pattern: &str,
+    chain: Chain,
+    private_keys: CsvPrivateKeys,
+) -> Result<WrittenRow, String> {
+    write_wallet_rows(file_path, &[(wallet, pattern)], chain, private_keys)
+}
+
+/**
+ * 向 CSV 文件一次追加多行钱包信息（只打开一次文件，文件不存在时先写入标题）
+ * 
+ * @param file_path - CSV 文件路径
+ * @param rows - 钱包信息和模式列中的模式
+ * @param chain - 地址所属的链
+ * @param private_keys - 私钥列的写法（标题随之不同）
+ * @returns 写入的字节数和重试次数
+ */
+pub(crate) fn write_wallet_rows(
+    file_path: &std::path::Path,
+    rows: &[(&Wallet, &str)],
+    chain: Chain,
+    private_keys: CsvPrivateKeys,
+) -> Result<WrittenRow, String> {
+    let mut lines = Zeroizing::new(String::new());
+    for (wallet, pattern) in rows {
+        lines.push_str(&format_wallet_row(wallet, pattern, chain, private_keys));
+    }
+    append_with_retry(file_path, &lines, &format!("{}\n", private_keys.header()))
+}
+
+/**
+ * 向加密的结果文件追加一条加密的钱包记录（文件不存在时先写入文件头和加密的标题）
+ * 
+ * @param file_path - 加密的结果文件路径
+ * @param wallet - 钱包信息
+ * @param pattern - 靓号模式
+ * @param chain - 地址所属的链
+ * @param private_keys - 私钥列的写法
+ * @param cipher - 文件的密钥
+ * @returns 写入的字节数和重试次数
+ */
+#[cfg(test)]
+pub(crate) fn write_encrypted_wallet_row(
+    file_path: &std::path::Path,
+    wallet: &Wallet,
+    pattern: &str,
+    chain: Chain,
+    private_keys: CsvPrivateKeys,
+    cipher: &OutputCipher,
+) -> Result<WrittenRow, String> {
+    write_encrypted_wallet_rows(file_path, &[(wallet, pattern)], chain, private_keys, cipher)
+}
+
+/**
+ * 向加密的结果文件一次追加多条加密的钱包记录（每条记录分别加密，只打开一次文件）
+ */
+fn write_encrypted_wallet_rows(
+    file_path: &std::path::Path,
+    rows: &[(&Wallet, &str)],
+    chain: Chain,
+    private_keys: CsvPrivateKeys,
+    cipher: &OutputCipher,
+) -> Result<WrittenRow, String> {
+    let mut lines = String::new();
+    for (wallet, pattern) in rows {
+        lines.push_str(&cipher.seal(format_wallet_row(wallet, pattern, chain, private_keys).trim_end_matches('\n'))?);
+    }
+    append_with_retry(file_path, &lines, &cipher.file_prefix(private_keys.header())?)
+}
+
+/**
+ * 向 JSON Lines 文件追加一个序列化的钱包（与 CSV 相同的追加方式，没有标题行）
+ * 
+ * @param file_path - JSON Lines 文件路径
+ * @param wallet - 钱包信息
+ * @param pattern - 靓号模式
+ * @param chain - 地址所属的链
+ * @returns 写入的字节数和重试次数
+ */
+#[cfg(test)]
+pub(crate) fn write_jsonl_row(file_path: &std::path::Path, wallet: &Wallet, pattern: &str, chain: Chain) -> Result<WrittenRow, String> {
+    write_jsonl_rows(file_path, &[(wallet, pattern)], chain)
+}
+
+/**
+ * 向 JSON Lines 文件一次追加多个序列化的钱包（只打开一次文件）
+ */
+fn write_jsonl_rows(file_path: &std::path::Path, rows: &[(&Wallet, &str)], chain: Chain) -> Result<WrittenRow, String> {
+    let mut lines = Zeroizing::new(String::new());
+    for (wallet, pattern) in rows {
+        lines.push_str(&format_jsonl_row(wallet, pattern, chain)?);
+    }
+    append_with_retry(file_path, &lines, "")
+}
+
+/**
+ * JSON Lines 中的一行钱包信息（含换行；含有私钥，离开作用域时清零）
+ */
+fn format_jsonl_row(wallet: &Wallet, pattern: &str, chain: Chain) -> Result<Zeroizing<String>, String> {
+    let record = WalletRecord { wallet, pattern, chain, timestamp: &wallet.created_at };
+    let mut line = Zeroizing::new(serde_json::to_string(&record).map_err(|e| format!("无法序列化钱包信息: {}", e))?);
+    line.push('\n');
+    Ok(line)
+}
+
+/**
+ * 追加一行并在短暂错误后重试
+ * 
+ * @param file_path - 结果文件路径
+ * @param line - 要追加的一行（含换行）
+ * @param header - 文件为空时先写入的内容（含换行）
+ */
+fn append_with_retry(file_path: &std::path::Path, line: &str, header: &str) -> Result<WrittenRow, String> {
+    // 重试期间钱包一直保存在内存中，重试用尽时由调用方按保存失败处理
+    let (written, retries) = retry::retry_io(&RetryPolicy::default(), || append_wallet_line(file_path, line, header));
+    let (len, bytes) = written.map_err(|e| retry_error("无法写入钱包信息", e, retries))?;
+    
+    // 整行写完后提交新的长度，正在读取该文件的结果浏览器只会读到完整的行
+    saved_wallets::commit(file_path, len);
+    Ok(WrittenRow { bytes, retries })
+}
+
+/**
+ * CSV 中的一行钱包信息（含换行；含有私钥，离开作用域时清零）
+ * 
+ * 字段按 RFC 4180 加引号，模式中的逗号和引号不会打乱列。
+ * 
+ * 时间列为钱包的找到时间（created_at）。
+ * 
+ * @param private_keys - 私钥列的写法（omit 时没有私钥列）
+ */
+fn format_wallet_row(wallet: &Wallet, pattern: &str, chain: Chain, private_keys: CsvPrivateKeys) -> Zeroizing<String> {
+    let address = wallet.chain_address.clone().unwrap_or_else(|| chain.format_address(wallet.address));
+    let private_key = Zeroizing::new(match private_keys {
+        CsvPrivateKeys::Full => Some(wallet.private_key.expose().to_string()),
+        CsvPrivateKeys::Masked => Some(saved_wallets::mask_private_key(wallet.private_key.expose())),
+        CsvPrivateKeys::Omit => None,
+    });
+    // 比特币另外写入 WIF 格式的私钥（只有保存完整私钥时才有这一列）
+    let wif = match private_keys {
+        CsvPrivateKeys::Full if chain == Chain::Bitcoin => Some(wallet.private_key.decode().map(|secret| bitcoin::wif(&secret)).unwrap_or_default()),
+        CsvPrivateKeys::Full => Some(Zeroizing::new(String::new())),
+        _ => None,
+    };
+    let (index, attempts, duration) = (wallet.index.to_string(), wallet.attempts.to_string(), wallet.duration.to_string());
+    let fields = [
+        Some(address.as_str()),
+        private_key.as_deref(),
+        Some(pattern),
+        Some(index.as_str()),
+        Some(chain.as_str()),
+        Some(wallet.rng_mode.as_str()),
+        Some(wallet.mnemonic.as_deref().unwrap_or_default()),
+        wif.as_ref().map(|wif| wif.as_str()),
+        Some(wallet.created_at.as_str()),
+        Some(attempts.as_str()),
+        Some(duration.as_str()),
+    ];
+    let mut line = Zeroizing::new(csv_field::join(fields.into_iter().flatten()));
+    line.push('\n');
+    line
+}
+
+/**
+ * 一行 CSV 的字节数上限（按最长的地址和序号格式化），用于开始前估计磁盘占用
+ * 
+ * @param pattern - 靓号模式
+ * @param chain - 地址所属的链
+ * @param rng_mode - 随机数来源
+ * @param mnemonic_words - 助记词词数（由原始私钥生成时为空）
+ * @param private_keys - 私钥列的写法
+ */
+pub(crate) fn csv_row_bytes(pattern: &str, chain: Chain, rng_mode: RngMode, mnemonic_words: Option<u8>, private_keys: CsvPrivateKeys) -> u64 {
+    format_wallet_row(&widest_wallet(pattern, chain, rng_mode, mnemonic_words), pattern, chain, private_keys).len() as u64
+}
+
+/**
+ * 一行 JSON Lines 的字节数（按最长的地址和序号、没有高亮区间的钱包估计），用于开始前估计磁盘占用
+ * 
+ * @param pattern - 靓号模式
+ * @param chain - 地址所属的链
+ * @param rng_mode - 随机数来源
+ * @param mnemonic_words - 助记词词数（由原始私钥生成时为空）
+ */
+pub(crate) fn jsonl_row_bytes(pattern: &str, chain: Chain, rng_mode: RngMode, mnemonic_words: Option<u8>) -> u64 {
+    let wallet = widest_wallet(pattern, chain, rng_mode, mnemonic_words);
+    format_jsonl_row(&wallet, pattern, chain).map_or(0, |line| line.len() as u64)
+}
+
+/**
+ * 由搜索找到的私钥构造钱包（序号在写入文件时分配），找到时间为调用时的当前时间
+ * 
+ * @param found - 找到的私钥
+ * @param matched_pattern - 满足的模式
+ * @param chain_address - 由地址编码的该链格式的地址（以太坊为空；Solana 使用 found 中的地址）
+ * @param zone - 找到时间的时区
+ */
+pub(crate) fn found_wallet(found: engine::FoundKey, matched_pattern: &str, chain_address: Option<String>, zone: TimestampZone) -> Wallet {
+    Wallet {
+        index: 0,
+        address: found.address,
+        private_key: found.private_key,
+        attempts: found.attempts,
+        duration: found.duration,
+        matched_spans: found.matched_spans,
+        rng_mode: found.rng_mode,
+        test_wallet: found.rng_mode.is_test(),
+        mnemonic: found.mnemonic,
+        chain_address: found.chain_address.or(chain_address),
+        matched_pattern: matched_pattern.to_string(),
+        created_at: zone.now(),
+    }
+}
+
+/**
+ * 各列都取最长值的钱包（估计每行的字节数上限时使用；比特币按较长的 P2WPKH 地址估计，Solana 按 44 个字符的地址估计，
+ * 找到时间按带时区偏移的本地时间估计）
+ */
+fn widest_wallet(pattern: &str, chain: Chain, rng_mode: RngMode, mnemonic_words: Option<u8>) -> Wallet {
+    let address = Address::from([0xff; 20]);
+    let chain_address = match chain.address_encoder(BitcoinAddressType::P2wpkh) {
+        _ if chain == Chain::Ethereum => None,
+        Some(encoder) => Some(encoder.encode(address)),
+        None => Some(solana::encode(&[0xff; 32])),
+    };
+    Wallet {
+        index: u64::MAX,
+        address,
+        private_key: SecretHex::from("f".repeat(64)),
+        attempts: u64::MAX,
+        duration: u64::MAX,
+        matched_spans: Vec::new(),
+        rng_mode,
+        test_wallet: rng_mode.is_test(),
+        mnemonic: mnemonic_words.map(|words| "z".repeat(mnemonic::max_phrase_len(words))),
+        chain_address,
+        matched_pattern: pattern.to_string(),
+        created_at: TimestampZone::Local.now(),
+    }
+}
+
+/**
+ * 追加一行（文件为空时先写入标题）；写入失败时截断已写入的部分，保证可以安全重试
+ * 
+ * @param header - 文件为空时先写入的标题（含换行）
+ * @returns 写入后的文件长度和本次写入的字节数
+ */
+fn append_wallet_line(file_path: &std::path::Path, line: &str, header: &str) -> std::io::Result<(u64, u64)> {
+    // 打开文件（追加模式）
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file_path)?;
+    let start = file.metadata()?.len();
+    
+    // 标题和钱包信息拼成一次写入，尽量避免读取方看到半行
+    let mut row = Zeroizing::new(String::new());
+    if start == 0 {
+        row.push_str(header);
+    }
+    row.push_str(line);
+    if let Err(e) = file.write_all(row.as_bytes()) {
+        let _ = file.set_len(start);
+        return Err(e);
+    }
+    Ok((start + row.len() as u64, row.len() as u64))
+}
+
+/**
+ * 生成靓号钱包
+ * 
+ * 只传 pattern 时前后缀同时与模式匹配（与之前的行为相同）；传入 prefix 和/或 suffix 时分别匹配前缀和后缀，
+ * 只传一侧表示只限制这一侧，两侧都为空时返回错误。结果文件的模式列记录为 <前缀>/<后缀>（例如 dead/beef）。
+ * 
+ * 两次开始之间至少间隔 set_start_cooldown 设置的冷却时间（默认 500 毫秒，全应用共用），
+ * 过于频繁时返回以“开始生成过于频繁”开头的错误，并发送带剩余等待时间的 generation-start-throttled 事件。
+ * 
+ * @param app - 应用句柄
+ * @param params - 生成参数：GenerationParams 的全部字段（thread_count 为 workers 的别名），加上只在开始时使用的
+ *                 模式写法、停止条件、会话标识和密码；各字段为：
+ * - pattern - 靓号模式字符串（使用 prefix/suffix 时省略）
+ * - preset_id - 内置模式预设的标识（可选，见 list_pattern_presets）；代替 pattern 使用预设的模式、比较方式和模式类型，
+ *               不能与 pattern、prefix/suffix、patterns、pattern_type、match_mode、min_zero_nibbles 或 mode: score 同时使用
+ * - max_attempts - 最大尝试次数（0 表示不限制）；达到时停止，返回最后一个匹配，没有匹配时返回带尝试次数的错误
+ * - save_path - 保存路径（可选，默认使用应用设置的 default_save_path，都没有时为 Documents 目录）；必须是已存在的目录的绝对路径，
+ *               搜索开始前创建其中的 FancyWallets 目录并确认可以写入，否则直接返回 save_failed（validate_save_path 可以提前检查）
+ * - score_weights - 评分权重（可选，默认使用内置权重）
+ * - collection - 追加的集合名称（可选，默认每次会话写入新的带时间戳的文件）
+ * - rng_mode - 随机数来源（可选，默认 reseeded-csprng；deterministic-test 只能用于测试）
+ * - thread_count - 工作线程数（可选，默认使用应用设置的 workers，都没有时使用全部 CPU 核心）
+ * - prefix - 地址前缀（可选）
+ * - suffix - 地址后缀（可选）
+ * - max_matches - 最大匹配数（可选，为空或 0 表示不限制）；找到并保存这么多钱包后停止，返回最后一个
+ * - max_duration_ms - 最长运行时间（毫秒，可选，为空或 0 表示不限制，不含暂停时间）；到时后停止并返回最后找到的钱包
+ *                     （score 模式下为得分最高的钱包），一个都没有找到时返回错误；最后一次进度事件带上实际的运行时间
+ * - case_sensitive - 是否按模式给出的大小写与 checksum 地址比较（可选，默认 false：小写的模式直接与小写地址比较）；
+ *                    结果文件总是保存 checksum 地址，进度中的概率和预计时间按对应的难度计算
+ * - case_mode - 大小写方式（可选：insensitive 或 exact，与 case_sensitive 等价，同时给出时必须一致）；exact 时模式按原样
+ *               （十六进制字符，大小写均可）逐字符与 checksum 地址比较，不做任何转换，每个指定大小写的字母使难度约增加一倍
+ * - pattern_type - 模式类型（可选，默认 standard）；regex 时 pattern 为正则表达式（Rust regex 语法，
+ *                  支持 ^ $ 锚点，不支持反向引用和环视），在开始前编译一次，与 40 个字符的小写地址比较；
+ *                  不能与 prefix/suffix 或 case_sensitive 同时使用，无效或编译后过大时在开始前返回错误
+ * - match_mode - 比较方式（可选：prefix、suffix、both 或 contains）；默认普通模式为 both，
+ *                *...* 形式为 contains（地址的任意位置包含该字符串），指定的方式与模式语法矛盾时返回错误
+ * - patterns - 同时搜索的多个模式（可选，不能与 pattern、prefix/suffix 同时使用）；重复的模式只搜索一次，
+ *              每个候选地址依次与全部模式比较，匹配写入第一个满足的模式的结果文件，进度中列出各模式的匹配数
+ * - min_zero_nibbles - pattern_type 为 leading_zeros 时地址开头最少的零的个数（1–40，不能与 pattern、prefix/suffix 或 patterns
+ *                      同时使用）；结果文件的模式列记录实际达到的个数（例如 zeros=10），进度中报告目前见过的最多的个数
+ * - caps_prefix - pattern_type 为 caps_prefix 时只看大小写的开头字符数（1–40，只用于以太坊地址，不能与 pattern、prefix/suffix、
+ *                 patterns 或 min_zero_nibbles 同时使用）；checksum 地址开头这么多个字符中的字母都是大写时匹配，数字不限
+ * - caps_lowercase - caps_prefix 模式改为要求开头的字母都是小写（可选，默认 false）
+ * - character - pattern_type 为 char_count 时计数的十六进制字符（可选，不区分大小写；省略时任意一个字符出现 min_count 次即可）
+ * - min_count - pattern_type 为 char_count 时字符最少出现的次数（1–40，不限定字符时必须大于 3，不能与 pattern、prefix/suffix、
+ *               patterns 或 min_zero_nibbles 同时使用）；结果文件的模式列和 wallet-found 事件的 char_count 记录实际的字符和个数
+ * - mode - 生成方式（可选，默认 match）；score 时不使用模式，按 score_function 为每个地址评分，分数超过目前的最高分时
+ *         保存该钱包（模式列记录分数，例如 score-longest-run=7）并发送 new-best 事件；取消或达到停止条件时返回最高分的钱包；
+ *         前 10 名保存在排行榜中（匹配数为进入排行榜的地址数），自动保存到 FancyWallets/session_state/<会话标识>.leaderboard.json
+ *         （挤掉钱包时立即保存，只是增加时最多每 30 秒一次；只有结果文件保存明文私钥时带有私钥），resume_session 恢复时排行榜继续
+ * - score_function - score 模式的评分函数（可选，默认 longest_run；leading_zeros 或按 score_weights 计算的 weighted）
+ * - session_id - 会话标识（可选，默认由模式和开始时间生成）；事件都带有会话标识，cancel_generation 按它只停止这个会话，
+ *                多个会话可以同时运行，各自写入带开始时间的结果文件；同一标识的会话已在运行时返回错误
+ * - emit_private_key - 每个匹配的 wallet-found 事件是否带有私钥（可选，默认 false，csv_private_keys 不为 full 时默认 true；
+ *                      事件可以被任何网页代码观察到）
+ * - progress_interval_ms - generation-progress 事件的最短间隔（可选，默认使用应用设置，都没有时为 250 毫秒，0 表示不限制）；
+ *                          找到匹配时立即发送，结束前总是发送一次最终的统计
+ * - key_source - 候选私钥的来源（可选，默认 private_key）；mnemonic 时每个候选来自新的 BIP-39 助记词，
+ *                按 m/44'/60'/0'/0/0 派生私钥，助记词与私钥一起保存（每次尝试慢得多）
+ * - mnemonic_words - 助记词词数（可选，12 或 24，默认 12，只能与 key_source: mnemonic 一起使用）
+ * - keystore_password - keystore 密码（可选）；设置时每个匹配另外加密保存为 keystore V3 文件
+ *                       （scrypt + AES-128-CTR，geth 和 MetaMask 可以直接导入），密码不写入任何文件
+ * - keystore_only - 是否只保存 keystore、不把私钥写入 CSV（可选，默认 false，需要 keystore_password，不能与 collection 同时使用）
+ * - encrypt_output - 加密结果文件的密码（可选，不能与 collection 或 keystore_only 同时使用）；设置时结果写入
+ *                    wallet_<模式>_<时间>.csv.enc，每行是一条单独用 AES-256-GCM 加密的记录（密钥由 Argon2id 派生），
+ *                    写入中断只影响最后半行；用 decrypt_wallet_file 读取，结果浏览器不读取加密的文件
+ * - csv_private_keys - CSV 中私钥列的写法（可选，默认 full；masked 只写入首尾各 4 个十六进制字符，omit 不写入私钥列，
+ *                      标题随之不同）；不能与 collection 或 keystore_only 同时使用，私钥仍在返回值和 wallet-found 事件中
+ * - output_format - 结果文件格式（可选，默认使用应用设置，都没有时为 csv；应用设置只用于可以写入 JSON Lines 的会话）；jsonl 时每行写入一个序列化的钱包（含模式和保存时间）到
+ *                   同目录的 wallet_<模式>_<时间>.jsonl，both 时两种都写入；jsonl 和 both 不能与 collection、
+ *                   encrypt_output、keystore_only 或不为 full 的 csv_private_keys 同时使用，结果浏览器只读取 CSV
+ * - output_layout - 同时搜索多个模式时结果文件的组织方式（可选，默认 per_pattern）；per_pattern 时每个模式写入各自的
+ *                   wallet_<模式>_<时间>.csv，combined 时全部模式写入同一个 wallets_<时间>.csv，每行的 pattern 列记录
+ *                   满足的模式；combined 不能与 collection 或 filename_template 同时使用
+ * - chain - 生成的链（可选，ethereum、tron、bitcoin 或 solana，默认 ethereum）；tron 时模式与 T 之后的 33 个 Base58 字符比较
+ *           （只能使用 Base58 字符，0、O、I、l 无效，不支持 regex、leading_zeros、score 和 key_source: mnemonic），
+ *           结果写入 FancyWallets/<链名称>，地址列为该链格式的地址，返回值和 wallet-found 事件的 chain_address 为该地址；
+ *           solana 时私钥作为 ed25519 的种子，模式与整个 Base58 公钥区分大小写比较（最多 44 个字符），结果文件保存明文私钥时
+ *           每个钱包另外写入 keypairs/<地址>.json（solana-keygen 格式的 64 字节密钥对），不能与 keystore_password 同时使用
+ * - bitcoin_address - 比特币地址的格式（可选，只能与 chain: bitcoin 一起使用，默认 p2pkh）；p2pkh 时模式与 1 之后的
+ *                     Base58 字符比较，p2wpkh 时与 bc1q 之后的 38 个 Bech32 字符比较（不区分大小写，1、b、i、o 无效）；
+ *                     CSV 的 wif 列为压缩公钥格式的 WIF 私钥
+ * - device - 计算设备（可选，cpu 或 gpu，默认 cpu）；没有可用的 GPU 时发送 device-fallback 事件并使用 CPU 搜索，
+ *            GPU 报告的匹配总是由 CPU 重新推导地址并与模式比较后才保存
+ * - filename_template - 结果文件名的模板（可选，默认 {pattern}_{date}_{time}）；可以使用 {pattern}、{date}、{time} 和 {chain}，
+ *                       其余部分只能包含字母、数字、下划线和连字符，结果文件为 wallet_<展开的模板>.csv（确定性测试模式为
+ *                       wallet_TEST-ONLY_<展开的模板>.csv）；模式中的通配符、? 和 / 等符号不会出现在文件名中，日期和时间为
+ *                       本次会话开始时的 UTC 时间；同时搜索多个模式时必须包含 {pattern}，不能与 collection 同时使用
+ * - save_retries - 保存钱包失败后的重试次数（可选，默认 2，最多 10，不含写入内部对短暂错误的重试）；开始前先检查结果目录可以写入，
+ *                  无法写入时直接返回错误；仍然失败时发送 save-error 事件并继续搜索，未能保存的钱包（含私钥）在返回值的
+ *                  unsaved_wallets 中
+ * - address_qr - 地址二维码的设置（可选，默认不保存）；设置时每个保存的匹配另外在结果文件旁写入 <地址>_addr.png
+ *                （size 为最小边长，默认 256 像素；error_correction 为 low、medium、quartile 或 high，默认 medium），
+ *                只包含地址，私钥的二维码只能用 generate_wallet_qr 生成，从不自动保存；写入失败时只记录警告
+ * - emit_json_files - 是否把每个匹配另外写入单独的 JSON 文件（可选，默认 false）；true 时写入 FancyWallets/json/wallet_<地址>.json，
+ *                     内容为序列化的钱包加上模式、链和找到时间（保存完整的私钥），同名文件内容相同时不重复写入、内容不同时不覆盖；
+ *                     写入失败与结果文件一样发送 save-error 事件；不能与 encrypt_output、keystore_only 或不为 full 的 csv_private_keys 同时使用
+ * - dry_run - 只搜索不保存（可选，默认 false）；true 时照常生成、匹配、计数和发送事件，但不创建 FancyWallets 目录、不写入结果文件、
+ *             会话状态、回放记录和清单等任何文件，wallet-found 事件不带私钥，返回值中钱包的私钥为 DRY-RUN-NOT-SAVED；用于演示和测速，
+ *             不能与 collection、emit_json_files、results_db、address_qr、verify_unused、emit_private_key: true、keystore_password 或 encrypt_output 同时使用
+ * - batch_size - 批量写入时每批最多的匹配数（可选，1–10000）；与 batch_interval_ms 任一给出时匹配先保存在内存中，攒够 batch_size 个
+ *                （默认 100）或最早的一个等待超过 batch_interval_ms（默认 250 毫秒）时一起写入结果文件（每个文件只打开一次），
+ *                并发送一个带有这一批全部匹配的 wallets-found 事件代替逐个的 wallet-found；等待时间在每个引擎事件时检查
+ *                （进度事件至少每秒一次），停止、取消或出错返回前写入最后一批。用于一两个字符等匹配很多的模式，不能与 verify_unused 同时使用
+ * - batch_interval_ms - 批量写入时一批最长的等待时间（毫秒，可选，1–60000）
+ * - results_db - 是否把每个匹配另外写入 FancyWallets/wallets.db（可选，默认 false）；SQLite 数据库记录地址、私钥（结果文件打码、省略或加密私钥，
+ *                或只写入 keystore 时不保存，in_keystore 标记私钥在 keystore 中）、模式、会话标识、尝试次数、运行时间和找到时间，
+ *                与 CSV 和 JSON Lines 同时写入，用 query_wallets 和 count_wallets 查询全部会话的历史；不能与 dry_run 同时使用
+ * - notify_on_match - 找到匹配时是否发送系统通知（可选，默认使用应用设置，都没有时为 false）；通知只有地址和模式，从不包含私钥，窗口关闭时也会发送，
+ *                     两次通知至少间隔 15 秒，其间的匹配合并到下一次通知中；没有通知权限或发送失败时发送
+ *                     notification-unavailable 事件，本次会话不再通知，搜索照常进行
+ * - notify_every - 每几个匹配发送一次通知（可选，默认使用应用设置，都没有时为 1，只能与 notify_on_match: true 一起使用）
+ * - verify_unused - 检查新地址是否已有链上记录的 JSON-RPC 节点地址（可选，只能与 chain: ethereum 一起使用）；设置时每个保存的匹配
+ *                   在后台查询 eth_getTransactionCount 和 eth_getBalance（只发送地址），查询完成后才发送 wallet-found 事件，
+ *                   事件的 on_chain_activity 为是否有记录；有记录时写入结果文件旁的 ON_CHAIN_ACTIVITY_<时间>.csv 并记录警告，
+ *                   网络错误或超时只记为未知（on_chain_error），不影响生成
+ * - max_returned_wallets - 返回值中最多带有的钱包数（可选，默认 10000，最多 1000000）；更多的匹配照常写入结果文件，
+ *                          返回值只保留最早找到的这些个，并把 wallets_truncated 设为 true
+ * - timestamp_zone - 钱包找到时间（created_at，以及结果文件的时间列）使用的时区（可选，utc 或 local，默认 utc）；
+ *                    local 时为带时区偏移的本机时间，例如 2024-01-01T20:00:00.000+08:00
+ * - cpu_limit_percent - 工作线程的 CPU 占用上限（可选，1–100，默认 100 即不限制）；工作线程交替计算和休眠，
+ *                       运行中可以通过 set_cpu_limit 调整，进度和吞吐量采样中的速度是限制后的实际速度
+ * @returns 最后找到的钱包（attempts 为全部工作线程的尝试次数之和）、本次找到的全部钱包（取消和达到停止条件时都返回）、
+ *          会话的计数和未能保存的钱包；失败时返回带 kind 的错误（invalid_pattern、save_failed、cancelled、no_match_within_budget 等），
+ *          message 为可以直接显示的说明
+ */
+#[tauri::command]
+async fn generate_fancy_wallet(app: AppHandle, params: GenerateWalletRequest) -> Result<GenerationResult, WalletError> {
+    let GenerateWalletRequest {
+        params: requested,
+        preset_id,
+        max_attempts,
+        prefix,
+        suffix,
+        max_matches,
+        max_duration_ms,
+        patterns,
+        min_zero_nibbles,
+        caps_prefix,
+        caps_lowercase,
+        character,
+        min_count,
+        mode,
+        session_id,
+        keystore_password,
+        keystore_only,
+        encrypt_output,
+    } = params;
+    let pattern = Some(requested.pattern.clone()).filter(|pattern| !pattern.is_empty());
+    let (pattern_type, match_mode, score_function) = (requested.pattern_type, requested.match_mode, requested.score_function);
+    let score_mode = mode == Some(GenerationMode::Score);
+    let (pattern, pattern_type, match_mode, min_zero_nibbles) = match preset_id {
+        Some(id) => {
+            if pattern.is_some() || prefix.is_some() || suffix.is_some() || patterns.is_some() || pattern_type.is_some() || match_mode.is_some() || min_zero_nibbles.is_some() || score_mode {
+                return Err("preset_id 已经指定了模式，不能与 pattern、prefix/suffix、patterns、pattern_type、match_mode、min_zero_nibbles 或 mode: score 同时使用".into());
+            }
+            let preset = presets::find(&id)?;
+            (preset.pattern.map(str::to_string), Some(preset.pattern_type()), preset.match_mode, preset.min_zero_nibbles)
+        }
+        None => (pattern, pattern_type, match_mode, min_zero_nibbles),
+    };
+    let pattern_type = pattern_type.unwrap_or_default();
+    if pattern_type != PatternType::CapsPrefix && (caps_prefix.is_some() || caps_lowercase.is_some()) {
+        return Err("caps_prefix 和 caps_lowercase 只能与 pattern_type: caps_prefix 同时使用".into());
+    }
+    if pattern_type != PatternType::CharCount && (character.is_some() || min_count.is_some()) {
+        return Err("character 和 min_count 只能与 pattern_type: char_count 同时使用".into());
+    }
+    if pattern_type == PatternType::Regex && (prefix.is_some() || suffix.is_some()) {
+        return Err("正则表达式模式不能与 prefix/suffix 同时使用，请把完整的正则表达式放在 pattern 中".into());
+    }
+    if !score_mode && score_function.is_some() {
+        return Err("score_function 只能与 mode: score 同时使用".into());
+    }
+    let mut patterns = if score_mode {
+        if pattern.is_some() || prefix.is_some() || suffix.is_some() || patterns.is_some() || min_zero_nibbles.is_some() {
+            return Err("score 模式为每个地址评分，不能与 pattern、prefix/suffix、patterns 或 min_zero_nibbles 同时使用".into());
+        }
+        vec![scoring::score_pattern(score_function.unwrap_or_default())]
+    } else if pattern_type == PatternType::LeadingZeros {
+        if pattern.is_some() || prefix.is_some() || suffix.is_some() || patterns.is_some() {
+            return Err("leading_zeros 模式由 min_zero_nibbles 指定，不能与 pattern、prefix/suffix 或 patterns 同时使用".into());
+        }
+        let min_zero_nibbles = min_zero_nibbles.ok_or("leading_zeros 模式需要 min_zero_nibbles（地址开头最少的零的个数）")?;
+        vec![pattern::leading_zeros_pattern(min_zero_nibbles)]
+    } else if pattern_type == PatternType::CapsPrefix {
+        if pattern.is_some() || prefix.is_some() || suffix.is_some() || patterns.is_some() || min_zero_nibbles.is_some() {
+            return Err("caps_prefix 模式由 caps_prefix 指定，不能与 pattern、prefix/suffix、patterns 或 min_zero_nibbles 同时使用".into());
+        }
+        let length = caps_prefix.ok_or("caps_prefix 模式需要 caps_prefix（只看大小写的开头字符数）")?;
+        vec![pattern::caps_prefix_pattern(length, caps_lowercase.unwrap_or_default())]
+    } else if pattern_type == PatternType::CharCount {
+        if pattern.is_some() || prefix.is_some() || suffix.is_some() || patterns.is_some() || min_zero_nibbles.is_some() {
+            return Err("char_count 模式由 character 和 min_count 指定，不能与 pattern、prefix/suffix、patterns 或 min_zero_nibbles 同时使用".into());
+        }
+        let min_count = min_count.ok_or("char_count 模式需要 min_count（字符最少出现的次数）")?;
+        vec![pattern::char_count_pattern(character, min_count)]
+    } else {
+        if min_zero_nibbles.is_some() {
+            return Err("min_zero_nibbles 只能与 pattern_type: leading_zeros 同时使用".into());
+        }
+        match patterns {
+            Some(_) if pattern.is_some() || prefix.is_some() || suffix.is_some() => {
+                return Err("patterns 不能与 pattern 或 prefix/suffix 同时使用".into());
+            }
+            Some(patterns) => {
+                dedup_patterns(patterns, pattern_type == PatternType::Standard && !CaseMode::resolve(requested.case_mode, requested.case_sensitive)?)?
+            }
+            None => vec![resolve_pattern(pattern, prefix, suffix)?],
+        }
+    };
+    let pattern = patterns.remove(0);
+    // 没有传入的参数使用应用设置；设置中的输出格式只用于可以写入 JSON Lines 的会话
+    let settings = app_settings()?;
+    let jsonl_allowed = requested.collection.is_none() && encrypt_output.is_none() && requested.csv_private_keys.is_none() && keystore_only != Some(true);
+    let output_format = requested.output_format.clone().or(settings.output_format.filter(|_| jsonl_allowed).map(|format| format.as_str().to_string()));
+    let (notify_on_match, notify_every) = match requested.notify_on_match {
+        Some(notify) => (Some(notify), requested.notify_every),
+        None => (settings.notify_on_match, requested.notify_every.or(settings.notify_every)),
+    };
+    let limits = GenerationLimits {
+        max_attempts: max_attempts.filter(|&max| max > 0).or(requested.limits.max_attempts),
+        max_matches: max_matches.filter(|&max| max > 0).or(requested.limits.max_matches),
+        max_duration_ms: max_duration_ms.filter(|&max| max > 0).or(requested.limits.max_duration_ms),
+        ..requested.limits.clone()
+    };
+    let params = GenerationParams {
+        pattern,
+        additional_patterns: patterns,
+        save_path: requested.save_path.clone().or(settings.default_save_path),
+        workers: requested.workers.or(settings.workers),
+        progress_interval_ms: requested.progress_interval_ms.or(settings.progress_interval_ms),
+        output_format,
+        notify_on_match,
+        notify_every,
+        pattern_type: Some(pattern_type),
+        match_mode,
+        score_function: score_mode.then(|| score_function.unwrap_or_default()),
+        limits,
+        ..requested
+    };
+    // 链、计算设备和输出格式在开始前检查，不支持的取值不占用开始的频率限制
+    params.check_capabilities()?;
+    // 正则表达式在开始前编译一次，无效时不占用开始的频率限制
+    params.check_patterns()?;
+    let secrets = OutputSecrets::new(keystore_password, keystore_only, encrypt_output, &params)?;
+    accept_start(&app)?;
+    spawn_generation(app, params, None, session_id, secrets, None).await
+}
+
+/**
+ * 会话的难度（位）和匹配条件
+ * 
+ * score 模式没有固定的匹配概率，难度为无穷大（概率为 0，不估计置信度和剩余时间）。
+ * 
+ * @param params - 生成参数
+ * @param parsed_patterns - 解析后的全部模式
+ */
+fn session_matcher(params: &GenerationParams, parsed_patterns: &[ParsedPattern]) -> (f64, String) {
+    match params.score_function {
+        Some(function) => (f64::INFINITY, format!("score={}", function.as_str())),
+        None => (pattern::combined_difficulty_bits(parsed_patterns), combined_matcher(parsed_patterns)),
+    }
+}
+
+/**
+ * 会话的匹配条件：同时搜索多个模式时用 OR 连接各模式的匹配条件
+ * 
+ * @param parsed_patterns - 解析后的全部模式
+ */
+fn combined_matcher(parsed_patterns: &[ParsedPattern]) -> String {
+    parsed_patterns.iter().map(|parsed| parsed.matcher().to_string()).collect::<Vec<_>>().join(" OR ")
+}
+
+/**
+ * 整理 generate_fancy_wallet 的 patterns 参数：去掉首尾空白、空白的模式和重复的模式（保留第一次出现的顺序）
+ * 
+ * @param patterns - 模式列表
+ * @param ignore_case - 是否把只有大小写不同的模式视为重复（不区分大小写的普通模式）
+ */
+pub(crate) fn dedup_patterns(patterns: Vec<String>, ignore_case: bool) -> Result<Vec<String>, String> {
+    let mut unique: Vec<String> = Vec::with_capacity(patterns.len());
+    for pattern in patterns {
+        let pattern = pattern.trim().to_string();
+        if !pattern.is_empty() && !unique.iter().any(|seen| *seen == pattern || (ignore_case && seen.eq_ignore_ascii_case(&pattern))) {
+            unique.push(pattern);
+        }
+    }
+    if unique.is_empty() {
+        return Err("patterns 不能为空，至少需要一个模式".to_string());
+    }
+    Ok(unique)
+}
+
+/**
+ * 确定 generate_fancy_wallet 使用的模式：prefix/suffix 组成 <前缀>/<后缀>，否则使用 pattern
+ * 
+ * @param pattern - 前后缀相同的靓号模式（旧参数）
+ * @param prefix - 地址前缀
+ * @param suffix - 地址后缀
+ */
+fn resolve_pattern(pattern: Option<String>, prefix: Option<String>, suffix: Option<String>) -> Result<String, String> {
+    if prefix.is_none() && suffix.is_none() {
+        return pattern.ok_or_else(|| "需要指定 pattern，或者 prefix 和/或 suffix".to_string());
+    }
+    if pattern.as_deref().is_some_and(|pattern| !pattern.trim().is_empty()) {
+        return Err("pattern 不能与 prefix/suffix 同时使用".to_string());
+    }
+    let combined = pattern::prefix_suffix_pattern(prefix.as_deref().unwrap_or(""), suffix.as_deref().unwrap_or(""));
+    ParsedPattern::new(&combined).validate()?;
+    Ok(combined)
+}
+
+/**
+ * 按全应用的频率限制接受前端发起的开始生成；过于频繁时发送 generation-start-throttled 事件
+ * 
+ * 恢复暂停的任务（resume_generation）不是新的开始，不受限制。
+ * 
+ * @param app - 应用句柄
+ */
+fn accept_start(app: &AppHandle) -> Result<(), String> {
+    start_limit::accept_start().map_err(|throttled| {
+        let _ = app.emit("generation-start-throttled", throttled);
+        throttled.to_string()
+    })
+}
+
+/**
+ * 更新会话的内存统计；超过软上限时削减内存中的数据，并发送 memory-warning 事件
+ * 
+ * @param app - 应用句柄
+ * @param gate - 会话事件发送闸门
+ * @param session_id - 会话标识
+ * @param session_memory - 会话的内存统计
+ * @param recorder - 会话事件回放记录器（可选）
+ */
+fn check_session_memory(
+    app: &AppHandle,
+    gate: &mut EventGate,
+    session_id: &str,
+    session_memory: &mut SessionMemory,
+    mut recorder: Option<&mut ReplayRecorder>,
+) {
+    let warning = session_memory.check(recorder.as_deref_mut());
+    let usage = session_memory.usage(recorder.as_deref());
+    sessions::update_live(session_id, |live| live.memory = Some(usage));
+    if let Some(warning) = warning {
+        if let Some(recorder) = recorder {
+            recorder.record_warning(&format!(
+                "会话内存超过软上限 {} 字节，已削减内存中的数据",
+                warning.usage.soft_limit_bytes
+            ));
+        }
+        gate.send(|| app.emit("memory-warning", warning));
+    }
+}
+
+/**
+ * 检查系统时间是否发生跳变；跳变时记录警告并发送 clock-jump-warning 事件
+ * 
+ * 会话的时长和速度只使用单调时钟，跳变不影响它们，只影响写入记录中的系统时间。
+ * 
+ * @param app - 应用句柄
+ * @param gate - 会话事件发送闸门
+ * @param detector - 会话的系统时间跳变检测
+ * @param session_start - 会话开始时刻（单调时钟）
+ * @param recorder - 会话事件回放记录器（可选）
+ */
+fn check_clock_jump(
+    app: &AppHandle,
+    gate: &mut EventGate,
+    detector: &mut ClockJumpDetector,
+    session_start: std::time::Instant,
+    recorder: Option<&mut ReplayRecorder>,
+) {
+    let Some(jump) = detector.observe(session_start.elapsed(), chrono::Utc::now()) else {
+        return;
+    };
+    let message = format!(
+        "系统时间跳变 {} ms（跳变后为 {}），会话时长和速度不受影响，记录中的时间可能不是单调递增的",
+        jump.offset_ms, jump.wall_time
+    );
+    if let Some(recorder) = recorder {
+        recorder.record_warning(&message);
+    }
+    gate.send(|| app.emit("clock-jump-warning", jump));
+}
+
+/**
+ * 确认可以发送系统通知（权限尚未决定时向系统请求）
+ * 
+ * @param app - 应用句柄
+ * @returns 不能发送时的原因
+ */
+fn notification_permission(app: &AppHandle) -> Result<(), String> {
+    let notification = app.notification();
+    let state = match notification.permission_state().map_err(|e| format!("无法查询系统通知权限: {}", e))? {
+        PermissionState::Prompt | PermissionState::PromptWithRationale => {
+            notification.request_permission().map_err(|e| format!("无法请求系统通知权限: {}", e))?
+        }
+        state => state,
+    };
+    match state {
+        PermissionState::Granted => Ok(()),
+        _ => Err("系统通知权限被拒绝".to_string()),
+    }
+}
+
+/**
+ * 无法发送系统通知：记录警告并发送 notification-unavailable 事件（调用方随后不再发送通知）
+ * 
+ * @param app - 应用句柄
+ * @param gate - 会话事件发送闸门
+ * @param session_id - 会话标识
+ * @param reason - 原因
+ * @param recorder - 会话事件回放记录器（可选）
+ */
+fn disable_notifications(app: &AppHandle, gate: &mut EventGate, session_id: &str, reason: String, recorder: Option<&mut ReplayRecorder>) {
+    let reason = format!("{}，本次会话不发送匹配通知", reason);
+    if let Some(recorder) = recorder {
+        recorder.record_warning(&reason);
+    }
+    gate.send(|| app.emit("notification-unavailable", NotificationUnavailable { session_id: session_id.to_string(), reason: reason.clone() }));
+}
+
+/// 等待批量写入和发送的匹配（batch_size / batch_interval_ms）
+struct PendingMatch {
+    /// 已经分配序号的钱包
+    wallet: Wallet,
+    /// 结果文件模式列中的模式
+    row_pattern: String,
+    /// 写入的结果文件（会话输出位置的序号）
+    output: usize,
+    /// 是否写入结果文件（集合中已有的地址、dry_run 和只保存 keystore 时不写入）
+    write: bool,
+    /// 写入后发送的事件
+    event: WalletFound,
+}
+
+/**
+ * 写入一批匹配并发送一个 wallets-found 事件
+ * 
+ * 同一个结果文件的行一次写入；写入失败时这一批中该文件的钱包都按保存失败处理（逐个发送 save-error，随命令结果返回）。
+ * 
+ * @param batch - 这一批的匹配（按找到的顺序）
+ * @param outputs - 会话的输出位置（按模式）
+ * @param unsaved_wallets - 未能保存的钱包
+ * @param output_budget - 会话输出上限
+ */
+#[allow(clippy::too_many_arguments)]
+fn flush_found_batch(
+    app: &AppHandle,
+    gate: &mut EventGate,
+    session_id: &str,
+    batch: Vec<PendingMatch>,
+    outputs: &[SessionOutput],
+    unsaved_wallets: &mut Vec<Wallet>,
+    output_budget: &mut OutputBudget,
+    mut recorder: Option<&mut ReplayRecorder>,
+) {
+    for (i, output) in outputs.iter().enumerate() {
+        let pending: Vec<&PendingMatch> = batch.iter().filter(|pending| pending.write && pending.output == i).collect();
+        if pending.is_empty() {
+            continue;
+        }
+        let rows: Vec<(&Wallet, &str)> = pending.iter().map(|pending| (&pending.wallet, pending.row_pattern.as_str())).collect();
+        match save_wallets_to_file(&rows, output) {
+            Ok(written) => {
+                if let (Some(recorder), true) = (recorder.as_deref_mut(), written.retries > 0) {
+                    recorder.record_warning(&format!("写入钱包信息时遇到短暂错误，重试 {} 次后成功", written.retries));
+                }
+                record_output_bytes(app, gate, session_id, output_budget, written.bytes, recorder.as_deref_mut());
+            }
+            Err(message) => {
+                for pending in pending {
+                    unsaved_wallets.push(pending.wallet.clone());
+                    gate.send(|| app.emit("save-error", SaveError {
+                        session_id: session_id.to_string(),
+                        message: message.clone(),
+                        wallet: pending.event.clone(),
+                        unsaved: unsaved_wallets.len(),
+                    }));
+                }
+                if let Some(recorder) = recorder.as_deref_mut() {
+                    recorder.record_warning(&message);
+                }
+            }
+        }
+    }
+    let wallets = batch.into_iter().map(|pending| pending.event).collect();
+    gate.send(|| app.emit("wallets-found", WalletsFound { session_id: session_id.to_string(), wallets }));
+}
+
+/**
+ * 把写入的字节数计入会话输出上限；达到上限时只暂停这个会话（已经暂停时不重复发送警告），提高上限后由用户恢复
+ */
+fn record_output_bytes(
+    app: &AppHandle,
+    gate: &mut EventGate,
+    session_id: &str,
+    output_budget: &mut OutputBudget,
+    bytes: u64,
+    recorder: Option<&mut ReplayRecorder>,
+) {
+    let warning = output_budget.record(bytes, output_cap::caps());
+    let usage = output_budget.usage();
+    sessions::update_live(session_id, |live| live.output = Some(usage));
+    let paused = |_: &_| sessions::update_pause(Some(session_id), PauseRequest::PauseForOutputCap).is_ok_and(|changed| changed > 0);
+    if let Some(warning) = warning.filter(paused) {
+        if let Some(recorder) = recorder {
+            recorder.record_warning(&warning.message);
+        }
+        gate.send(|| app.emit("output-cap-reached", warning));
+    }
+}
+
+/// 等待链上记录查询的匹配（verify_unused）
+struct UncheckedFound {
+    /// 查询完成后发送的 wallet-found 事件
+    event: WalletFound,
+    /// 地址（结果文件中的格式）
+    address: String,
+    /// 结果文件所在的目录
+    dir: PathBuf,
+}
+
+/**
+ * 发送查询完的匹配的 wallet-found 事件；已有链上记录的地址写入结果文件旁的 ON_CHAIN_ACTIVITY_<时间>.csv 并记录警告
+ * 
+ * @param app - 应用句柄
+ * @param gate - 会话事件发送闸门
+ * @param checked - 查询完的匹配和结果（查询失败时记录未知）
+ * @param session_stamp - 会话开始时间（文件名格式）
+ * @param activity_files - 本次会话写入的链上记录文件（记录在清单中）
+ * @param recorder - 会话事件回放记录器（可选）
+ */
+fn report_on_chain_activity(
+    app: &AppHandle,
+    gate: &mut EventGate,
+    checked: Vec<(UncheckedFound, Result<ChainActivity, String>)>,
+    session_stamp: &str,
+    activity_files: &mut Vec<PathBuf>,
+    mut recorder: Option<&mut ReplayRecorder>,
+) {
+    for (found, result) in checked {
+        let UncheckedFound { mut event, address, dir } = found;
+        match result {
+            Ok(activity) => {
+                event.on_chain_activity = Some(activity.is_active());
+                if activity.is_active() {
+                    let warning = format!(
+                        "新生成的地址 {} 已有链上记录（交易数 {}，余额 {} wei），请不要使用这个钱包",
+                        address, activity.transaction_count, activity.balance
+                    );
+                    if let Some(recorder) = recorder.as_deref_mut() {
+                        recorder.record_warning(&warning);
+                    }
+                    let path = dir.join(format!("ON_CHAIN_ACTIVITY_{}.csv", session_stamp));
+                    let checked_at = timestamps::to_rfc3339(chrono::Utc::now());
+                    let row = format!("{}\n", csv_field::join([address.as_str(), &activity.transaction_count, &activity.balance, &checked_at]));
+                    match append_with_retry(&path, &row, "address,transaction_count,balance_wei,checked_at\n") {
+                        Ok(_) if !activity_files.contains(&path) => activity_files.push(path),
+                        Ok(_) => {}
+                        Err(e) => {
+                            if let Some(recorder) = recorder.as_deref_mut() {
+                                recorder.record_warning(&format!("无法写入 {} 的链上记录: {}", address, e));
+                            }
+                        }
+                    }
+                }
+            }
+            Err(e) => event.on_chain_error = Some(e),
+        }
+        gate.send(|| app.emit("wallet-found", event));
+    }
+}
+
+/**
+ * 在阻塞线程池中运行一次生成会话（参数与 run_generation 相同）
+ * 
+ * 搜索在会话结束前一直占用所在的线程；放在异步工作线程上时，同时运行的会话多了会使取消、暂停和统计等其它命令无法及时处理。
+ */
+async fn spawn_generation(
+    app: AppHandle,
+    params: GenerationParams,
+    template: Option<String>,
+    session_id: Option<String>,
+    secrets: OutputSecrets,
+    resume: Option<session_state::Resume>,
+) -> Result<GenerationResult, WalletError> {
+    tauri::async_runtime::spawn_blocking(move || run_generation(app, params, template, session_id, secrets, resume))
+        .await
+        .map_err(|e| WalletError::from(format!("生成会话的线程异常退出: {}", e)))?
+}
+
+/**
+ * 运行一次生成会话，直到被取消或达到停止条件
+ * 
+ * @param app - 应用句柄
+ * @param params - 生成参数：模式、保存路径、停止条件、评分权重（归一化后记录在会话信息和清单中）、集合和随机数来源
+ * @param template - 会话来源的模板名称（可选，记录在清单中）
+ * @param session_id - 会话标识（可选，默认由模式和开始时间生成）
+ * @param secrets - keystore 和加密输出的密码（只有 generate_fancy_wallet 和 resume_session 提供）
+ * @param resume - 恢复的会话（可选，只有 resume_session 提供）；尝试次数、匹配数和运行时间从它的状态继续累计
+ * @returns 最后找到的钱包和未能保存的钱包
+ */
+fn run_generation(
+    app: AppHandle,
+    params: GenerationParams,
+    template: Option<String>,
+    session_id: Option<String>,
+    secrets: OutputSecrets,
+    resume: Option<session_state::Resume>,
+) -> Result<GenerationResult, WalletError> {
+    let _running = RunningGuard::new();
+    let job_id = queue::claim_job();
+    // 解析靓号模式（正则表达式在这里编译一次），无效时在创建任何输出之前返回错误
+    params.check_patterns()?;
+    // 登记会话：取消标志、停止原因和文件名中的时间都属于这个会话，不影响同时运行的其它会话
+    let started = chrono::Utc::now();
+    let session_id = match session_id {
+        Some(session_id) => {
+            sessions::validate_id(&session_id)?;
+            session_id
+        }
+        None => format!("{}_{}", pattern::file_label(&params.pattern), started.format("%Y%m%d_%H%M%S%3fZ")),
+    };
+    let session = sessions::register(&session_id, SessionKind::Generation)?;
+    reset_last_wallets(&session_id);
+    rate_history::start(&session_id);
+    worker_stats::start(&session_id);
+    let session_stamp = timestamps::filename_stamp(started);
+    let patterns = params.patterns();
+    let parsed_patterns = params.parsed_patterns();
+    let (difficulty_bits, matcher) = session_matcher(&params, &parsed_patterns);
+    let probability = (-difficulty_bits).exp2();
+    let chain = params.chain()?;
+    let encoder = params.encoder();
+    // 请求的设备不可用时回退到 CPU（会话开始后发送 device-fallback 事件）
+    let requested_device = params.device()?;
+    let (device, device_fallback) = device::resolve(requested_device);
+    // score 模式没有匹配概率，正则表达式的难度只是粗略估计，都不估计剩余时间
+    let eta_probability = (params.score_function.is_none() && parsed_patterns.iter().all(|parsed| parsed.rule() != MatchRule::Regex))
+        .then_some(probability);
+    params.check_dry_run()?;
+    // 写入会话状态文件的参数（不含密码）
+    let persisted_params = params.clone();
+    let case_sensitive = params.case_sensitive();
+    let GenerationParams {
+        pattern,
+        save_path,
+        workers,
+        limits,
+        score_weights,
+        collection,
+        rng_mode,
+        test_seed,
+        pattern_type,
+        match_mode,
+        additional_patterns,
+        score_function,
+        emit_private_key,
+        progress_interval_ms,
+        key_source,
+        mnemonic_words,
+        csv_private_keys,
+        output_format,
+        output_layout,
+        bitcoin_address,
+        filename_template,
+        save_retries,
+        address_qr,
+        emit_json_files,
+        dry_run,
+        batch_size,
+        batch_interval_ms,
+        results_db,
+        notify_on_match,
+        notify_every,
+        verify_unused,
+        max_returned_wallets,
+        timestamp_zone,
+        cpu_limit_percent,
+        ..
+    } = params;
+    let OutputSecrets { keystore, encrypt_output } = secrets;
+    let csv_private_keys = csv_private_keys.unwrap_or_default();
+    let output_format = OutputFormat::parse(output_format.as_deref())?;
+    if output_format.writes_jsonl() {
+        if encrypt_output.is_some() {
+            return Err("加密输出只支持 CSV，encrypt_output 不能与 output_format: jsonl 或 both 同时使用".into());
+        }
+        if csv_private_keys != CsvPrivateKeys::Full {
+            return Err("JSON Lines 文件保存完整的私钥，csv_private_keys 不能与 output_format: jsonl 或 both 同时使用".into());
+        }
+        if keystore.as_ref().is_some_and(|keystore| keystore.only) {
+            return Err("keystore_only 不写入结果文件，不能与 output_format: jsonl 或 both 同时使用".into());
+        }
+    }
+    let emit_json_files = emit_json_files.unwrap_or_default();
+    if emit_json_files {
+        if encrypt_output.is_some() {
+            return Err("JSON 文件保存明文私钥，emit_json_files 不能与 encrypt_output 同时使用".into());
+        }
+        if keystore.as_ref().is_some_and(|keystore| keystore.only) {
+            return Err("keystore_only 不保存明文私钥，不能与 emit_json_files 同时使用".into());
+        }
+    }
+    let dry_run = dry_run.unwrap_or_default();
+    if dry_run && (keystore.is_some() || encrypt_output.is_some()) {
+        return Err("dry_run 不写入任何文件，不能与 keystore_password 或 encrypt_output 同时使用".into());
+    }
+    // CSV 不保存完整的私钥时默认在事件中带有私钥，用户可以立即复制；dry_run 的事件从不带有私钥
+    let emit_private_key = !dry_run && emit_private_key.unwrap_or(csv_private_keys != CsvPrivateKeys::Full);
+    let save_retry = retry::save_policy(save_retries)?;
+    let returned_limit = memory::returned_wallets_limit(max_returned_wallets)?;
+    let timestamp_zone = timestamp_zone.unwrap_or_default();
+    if let Some(percent) = cpu_limit_percent {
+        session.cpu_limit().set(percent)?;
+    }
+    if let Some(address_qr) = &address_qr {
+        address_qr.validate()?;
+    }
+    notifications::validate(notify_on_match, notify_every)?;
+    let mut notifier = notify_on_match.filter(|&notify| notify).map(|_| MatchNotifier::new(notify_every)).transpose()?;
+    if verify_unused.is_some() && chain != Chain::Ethereum {
+        return Err(format!("verify_unused 通过 eth_getBalance 查询，只能与 chain: ethereum 一起使用（当前为 {}）", chain.as_str()).into());
+    }
+    let mut unused_check = verify_unused.as_deref().map(UnusedCheck::<UncheckedFound>::start).transpose()?;
+    let mut progress_throttle = ProgressThrottle::new(progress_interval_ms)?;
+    let score_weights = scoring::resolve(score_weights.as_ref())?;
+    let scorer = score_function.map(|function| RecordTracker::new(function, score_weights.clone()));
+    let mut best_score: Option<f64> = None;
+    let rng_mode = rng_mode.unwrap_or_default();
+    entropy::validate(rng_mode, test_seed)?;
+    worker::validate(workers)?;
+    let key_source = key_source.unwrap_or_default();
+    mnemonic::validate(key_source, mnemonic_words)?;
+    chain.validate_key_source(key_source)?;
+    let entropy = EntropyProvenance::new(rng_mode, test_seed);
+    
+    // 进入搜索之前确认保存路径存在并且可以写入，而不是等到第一个匹配才发现（dry_run 不创建 FancyWallets 目录）
+    let root = if dry_run { None } else { Some(preflight_output_root(save_path.clone())?) };
+    // results_db：每个匹配另外写入 FancyWallets/wallets.db，开始前先打开并迁移，无法打开时直接返回错误
+    let database = match root.filter(|_| results_db.unwrap_or_default()) {
+        Some(root) => {
+            let path = root.wallets_dir.join(results_db::DB_FILE);
+            results_db::open(&path)?;
+            let keystore_only = keystore.as_ref().is_some_and(|keystore| keystore.only);
+            Some(results_db::SessionDb {
+                path,
+                session_id: session_id.clone(),
+                private_keys: csv_private_keys == CsvPrivateKeys::Full && encrypt_output.is_none() && !keystore_only,
+                in_keystore: keystore.is_some(),
+            })
+        }
+        None => None,
+    };
+    // 输出位置在会话开始时解析并校验一次，之后所有写入都使用同一个目录；每个模式写入各自的文件，
+    // 或者全部模式共用一个合并的文件（每行的 pattern 列记录满足的模式）
+    let resolve_output = |label: Option<&str>| {
+        resolve_session_output(
+            save_path.clone(),
+            label,
+            chain,
+            collection.clone(),
+            rng_mode,
+            &session_stamp,
+            filename_template.as_deref().unwrap_or(filename_template::DEFAULT_TEMPLATE),
+            encrypt_output.as_deref(),
+            csv_private_keys,
+        )
+        .map(|output| output.with_format(output_format).with_save_retry(save_retry).with_database(database.clone()))
+    };
+    let outputs = match output_layout.unwrap_or_default() {
+        OutputLayout::PerPattern => session_file_labels(&patterns).iter().map(|label| resolve_output(Some(label))).collect::<Result<Vec<_>, _>>()?,
+        OutputLayout::Combined => vec![resolve_output(None)?; patterns.len()],
+    };
+    // 开始搜索前确认结果目录可以写入：只读的目录或已满的磁盘直接返回错误，而不是找到匹配后才无法保存
+    for output in outputs.iter().filter(|_| !dry_run) {
+        output_root::check_writable(output.csv_dir())?;
+    }
+    let output = &outputs[0];
+    output_folder::register(&session_id, output.csv_dir(), output.result_files().into_iter().next());
+    // 同一秒内开始的相同模式的会话会得到相同的文件名，拒绝而不是交错写入
+    let busy = |path: &std::path::Path| format!("结果文件 {} 正在被另一个会话写入，请稍后再开始", path.display());
+    let _other_active_files = outputs[1..]
+        .iter()
+        .filter(|other| other.csv_path != output.csv_path)
+        .map(|other| saved_wallets::ActiveFile::register_exclusive(&other.csv_path).map_err(|_| busy(&other.csv_path)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut pattern_matches: BTreeMap<String, u64> = patterns.iter().map(|pattern| (pattern.clone(), 0)).collect();
+    let resume_totals = resume.as_ref().map(|resume| resume.state.totals);
+    if let Some(resume) = &resume {
+        for (pattern, count) in &resume.state.pattern_matches {
+            if let Some(matches) = pattern_matches.get_mut(pattern) {
+                *matches = *count;
+            }
+        }
+    }
+    
+    // 登记本次会话追加的结果文件，读取方据此只读取已完整写入的行；
+    // 集合只允许一个会话追加，并在开始前核对格式版本、读取已有地址用于去重；
+    // 钱包序号属于这个会话，从 1 开始（追加到集合时从集合中最大的序号之后继续）
+    let (_active_file, mut collection_seen, mut wallet_index) = match &output.collection {
+        Some(name) => {
+            let active = saved_wallets::ActiveFile::register_exclusive(&output.csv_path)
+                .map_err(|_| format!("集合 {} 正在被另一个会话追加，请等待该会话结束", name))?;
+            let state = collections::open(&output.csv_path, name)?;
+            (active, Some(state.seen), WalletIndex::after(state.max_index))
+        }
+        None => (saved_wallets::ActiveFile::register_exclusive(&output.csv_path).map_err(|_| busy(&output.csv_path))?, None, WalletIndex::default()),
+    };
+    
+    // 事件发送闸门：窗口关闭后停止构造进度事件，重新订阅后恢复
+    let mut gate = EventGate::new(&session_id);
+    let live = LiveSession::new(&session_id, probability);
+    let session_start = std::time::Instant::now();
+    let mut clock_jumps = ClockJumpDetector::new();
+    let keccak_backend = keccak::backend();
+    // 实时状态：界面重新加载后丢失了事件监听，由 get_generation_status 读取
+    let published = PublishedGeneration::new(ActiveGeneration {
+        session_id: session_id.clone(),
+        params: persisted_params.clone(),
+        template: template.clone(),
+        started_at: timestamps::to_rfc3339(started),
+        attempts: resume_totals.map_or(0, |totals| totals.attempts),
+        matches: resume_totals.map_or(0, |totals| totals.matches),
+        duration: resume_totals.map_or(0, |totals| totals.duration),
+        attempts_per_second: None,
+        paused: false,
+        cancelled: false,
+        live: sessions::LiveStatus::default(),
+    });
+    // 会话状态文件：应用崩溃或重启后可以用 resume_session 按相同的参数恢复，计数从最后一次更新继续（dry_run 不写入）
+    let mut state_writer = (!dry_run).then(|| StateWriter::new(session_state::state_path(&output.root.wallets_dir, &session_id), SessionState {
+        version: session_state::STATE_VERSION,
+        session_id: session_id.clone(),
+        params: persisted_params,
+        template: template.clone(),
+        keystore: keystore.is_some(),
+        keystore_only: keystore.as_ref().is_some_and(|keystore| keystore.only),
+        encrypted_output: encrypt_output.is_some(),
+        totals: resume_totals.unwrap_or_default(),
+        pattern_matches: pattern_matches.clone(),
+        started_at: timestamps::to_rfc3339(started),
+        updated_at: timestamps::to_rfc3339(started),
+        stop: None,
+        resumed_from: resume.as_ref().map(|resume| resume.state.session_id.clone()),
+        resumed_as: None,
+    }));
+    let mut state_warning = state_writer.as_mut().and_then(|writer| writer.update(resume_totals.unwrap_or_default(), &pattern_matches, session_start, true));
+    // score 模式的排行榜（前 LEADERBOARD_SIZE 名）：恢复的会话从之前的会话的排行榜继续；挤掉钱包时立即保存到会话状态文件旁，
+    // 只是增加钱包时最多每 30 秒保存一次（dry_run 只保存在内存中）；只有结果文件保存明文私钥时排行榜文件才带有私钥
+    let keys_included = csv_private_keys == CsvPrivateKeys::Full && encrypt_output.is_none() && keystore.as_ref().is_none_or(|keystore| !keystore.only);
+    let mut leaderboard = match score_function {
+        Some(function) => {
+            let previous = resume.as_ref().map(|resume| leaderboard::leaderboard_path(&output.root.wallets_dir, &resume.state.session_id)).filter(|path| path.exists());
+            Some(match previous.map(|path| leaderboard::load(&path)) {
+                Some(Ok(standings)) if standings.score_function == function => Leaderboard::resume(&session_id, standings, keys_included),
+                Some(Err(e)) => {
+                    state_warning = state_warning.or(Some(e));
+                    Leaderboard::new(&session_id, function, keys_included)
+                }
+                _ => Leaderboard::new(&session_id, function, keys_included),
+            })
+        }
+        None => None,
+    };
+    let mut leaderboard_writer = leaderboard.as_ref().filter(|_| !dry_run).map(|_| LeaderboardWriter::new(leaderboard::leaderboard_path(&output.root.wallets_dir, &session_id)));
+    if let (Some(board), Some(scorer)) = (leaderboard.as_mut(), scorer.as_ref()) {
+        scorer.raise_threshold(board.threshold());
+        best_score = board.best().map(|best| best.score);
+        if best_score.is_some() {
+            if let Some(e) = leaderboard_writer.as_mut().and_then(|writer| writer.save_now(board)) {
+                state_warning = state_warning.or(Some(e));
+            }
+        }
+    }
+    // 恢复的会话标记为已恢复，不能再次恢复
+    let resumed = match resume {
+        Some(Resume { state_file, mut state }) => {
+            let totals = state.totals;
+            state.resumed_as = Some(session_id.clone());
+            if let Err(e) = session_state::save(&state_file, &state) {
+                state_warning = state_warning.or(Some(e));
+            }
+            Some(ResumedSession {
+                session_id: session_id.clone(),
+                resumed_from: state.session_id,
+                state_file,
+                totals,
+                rng_state_restored: false,
+                note: "私钥是随机生成的，恢复的是参数和统计：尝试次数、匹配数和运行时间从状态文件最后一次更新继续累计，之后的候选私钥与之前的尝试无关".to_string(),
+            })
+        }
+        None => None,
+    };
+    gate.send(|| app.emit("generation-session-started", GenerationSessionInfo {
+        session_id: session_id.clone(),
+        pattern: pattern.clone(),
+        matcher: matcher.clone(),
+        template: template.clone(),
+        output_dir: output.session_dir.clone(),
+        output_source: output.root.source,
+        score_weights: score_weights.clone(),
+        collection: output.collection.clone(),
+        keccak_backend,
+        job_id: job_id.clone(),
+        entropy: entropy.clone(),
+        device,
+        state_file: state_writer.as_ref().map(|writer| writer.path().to_path_buf()),
+        leaderboard_file: leaderboard_writer.as_ref().map(|writer| writer.path().to_path_buf()),
+    }));
+    if let Some(resumed) = &resumed {
+        gate.send(|| app.emit("session-resumed", resumed.clone()));
+    }
+    if let Some(reason) = &device_fallback {
+        gate.send(|| app.emit("device-fallback", device::DeviceFallback {
+            session_id: session_id.clone(),
+            requested: requested_device,
+            device,
+            reason: reason.clone(),
+        }));
+    }
+    
+    // 写入前简要检查已有的输出目录，发现问题时只提示不阻止
+    let report = output_check::check_directory(&output.root.wallets_dir);
+    if !report.findings.is_empty() {
+        gate.send(|| app.emit("output-directory-warning", report));
+    }
+    
+    // 创建会话事件回放记录器（失败时不影响生成；dry_run 不记录）
+    let mut recorder = (!dry_run).then(|| ReplayRecorder::create(&output.session_dir.join("replays"), &pattern, chain).ok()).flatten();
+    if let Some(recorder) = recorder.as_mut() {
+        recorder.record_state("started");
+        if entropy.test_watermark {
+            recorder.record_warning(&format!("{}：{}", entropy::TEST_WATERMARK, entropy.source));
+        }
+        if let Some(reason) = &device_fallback {
+            recorder.record_warning(reason);
+        }
+    }
+    if let Some(warning) = &state_warning {
+        if let Some(recorder) = recorder.as_mut() {
+            recorder.record_warning(warning);
+        }
+    }
+    // 没有系统通知权限时只发送警告，会话照常进行
+    if notifier.is_some() {
+        if let Err(reason) = notification_permission(&app) {
+            notifier = None;
+            disable_notifications(&app, &mut gate, &session_id, reason, recorder.as_mut());
+        }
+    }
+    
+    let config = SearchConfig {
+        pattern: pattern.clone(),
+        limits,
+        progress_interval: engine::DEFAULT_PROGRESS_INTERVAL,
+        rng_mode,
+        test_seed,
+        workers,
+        case_sensitive,
+        pattern_type: pattern_type.unwrap_or_default(),
+        match_mode,
+        additional_patterns,
+        key_source,
+        mnemonic_words,
+        chain,
+        bitcoin_address: bitcoin_address.unwrap_or_default(),
+        resume_from: resume_totals,
+        cpu_limit: session.cpu_limit(),
+    };
+    let mut output_budget = OutputBudget::new(&session_id, output_cap::caps());
+    let initial_usage = output_budget.usage();
+    sessions::update_live(&session_id, |live| {
+        live.workers = Some(WorkerCapacity::full(config.worker_count()));
+        live.matcher = Some(matcher.clone());
+        live.output = Some(initial_usage);
+    });
+    let mut last_match: Option<Wallet> = None;
+    let mut returned = ReturnedResults::new(returned_limit);
+    let mut session_memory = SessionMemory::new(&session_id, memory::soft_limit());
+    let mut taskbar = SessionTaskbar::new(taskbar::for_session(&app), probability);
+    let progress_stats = |progress: &SearchProgress, pattern_matches: &BTreeMap<String, u64>, best_score: Option<f64>| ProgressStats {
+        session_id: session_id.clone(),
+        attempts: progress.attempts,
+        matches: progress.matches,
+        duration: progress.duration,
+        paused_duration: progress.paused_duration,
+        rejected_keys: progress.rejected_keys,
+        rejection_rate: progress.rejection_rate,
+        confidence: confidence::confidence_after(probability, progress.attempts as f64),
+        cumulative_success_probability: confidence::cumulative_success(eta_probability, progress.attempts),
+        probability_next_hour: confidence::success_within(eta_probability, live.attempts_per_second(), confidence::NEXT_HOUR),
+        rate: live.rate(),
+        attempts_per_second: live.attempts_per_second(),
+        estimated_remaining_ms: confidence::remaining_ms(
+            &config.limits,
+            eta_probability,
+            progress.attempts,
+            progress.matches,
+            std::time::Duration::from_millis(progress.duration),
+            live.attempts_per_second(),
+        ),
+        rate_low_precision: live.low_precision(),
+        pattern_matches: pattern_matches.clone(),
+        best_zero_nibbles: progress.best_zero_nibbles,
+        best_score,
+    };
+    // 因频率限制没有发送的最近一次进度，搜索结束后补发，界面停在准确的数字上
+    let mut pending_progress: Option<SearchProgress> = None;
+    // 本次会话写入的 keystore 文件、Solana 密钥对文件和地址二维码（记录在清单中）
+    let mut keystore_files: Vec<PathBuf> = Vec::new();
+    let mut keypair_files: Vec<PathBuf> = Vec::new();
+    let mut qr_files: Vec<PathBuf> = Vec::new();
+    let mut json_files: Vec<PathBuf> = Vec::new();
+    // verify_unused 发现已有链上记录时写入的文件
+    let mut activity_files: Vec<PathBuf> = Vec::new();
+    // 重试后仍未能保存的钱包（私钥只在这里，随命令结果返回）
+    let mut unsaved_wallets: Vec<Wallet> = Vec::new();
+    // batch_size / batch_interval_ms：匹配先攒成一批，一起写入结果文件并发送 wallets-found
+    let mut batch: Option<MatchBatch<PendingMatch>> = MatchBatch::new(batch_size, batch_interval_ms);
+    
+    // 事件回调只在当前线程上依次执行，各工作线程找到的钱包逐个写入，不会交错
+    let on_event = |event: SearchEvent| match event {
+        SearchEvent::Found(found) => {
+            // score 模式：多个工作线程同时刷新记录时到达顺序可能颠倒，不再是最高分的结果直接丢弃
+            let lowercase = hex::encode(found.address.as_bytes());
+            let record = scorer.as_ref().map(|scorer| scorer.evaluate(&lowercase));
+            // 够格的地址先进入排行榜并提高门槛，只有刷新最高分的钱包才写入结果文件
+            if let (Some(board), Some(scorer), Some(score)) = (leaderboard.as_mut(), scorer.as_ref(), record) {
+                let placement = board.offer(LeaderboardEntry {
+                    address: chain.format_address(found.address),
+                    score,
+                    attempts: found.attempts,
+                    duration: found.duration,
+                    found_at: timestamp_zone.now(),
+                    private_key: Some(found.private_key.clone()),
+                });
+                scorer.raise_threshold(board.threshold());
+                if let Some(warning) = leaderboard_writer.as_mut().and_then(|writer| writer.record(board, &placement, std::time::Instant::now())) {
+                    if let Some(recorder) = recorder.as_mut() {
+                        recorder.record_warning(&warning);
+                    }
+                }
+            }
+            if record.is_some_and(|score| best_score.is_some_and(|best| score <= best)) {
+                return;
+            }
+            let previous_score = best_score;
+            best_score = record.or(best_score);
+            
+            // 写入满足的模式的结果文件
+            let hit = patterns.iter().position(|pattern| *pattern == found.pattern).unwrap_or(0);
+            *pattern_matches.entry(patterns[hit].clone()).or_default() += 1;
+            
+            // 保存钱包信息到文件（按 output_format 保存 CSV 和/或 JSON Lines）
+            let encoded = encoder.filter(|_| chain != Chain::Ethereum).map(|encoder| encoder.encode(found.address));
+            let mut wallet = found_wallet(found, &patterns[hit], encoded, timestamp_zone);
+            // leading_zeros 模式的模式列记录实际达到的零的个数，char_count 模式记录实际的字符和个数，score 模式记录分数
+            let zero_nibbles = parsed_patterns[hit].achieved_zero_nibbles(&lowercase);
+            let char_count = parsed_patterns[hit].achieved_char_count(&lowercase);
+            let row_pattern = match (score_function.zip(record), zero_nibbles, char_count) {
+                (Some((function, score)), _, _) => scoring::score_label(function, score),
+                (None, Some(zero_nibbles), _) => pattern::achieved_zeros_label(zero_nibbles),
+                (None, None, Some(char_count)) => pattern::achieved_count_label(char_count),
+                (None, None, None) => patterns[hit].clone(),
+            };
+            
+            check_clock_jump(&app, &mut gate, &mut clock_jumps, session_start, recorder.as_mut());
+            
+            // 不返回，继续生成更多匹配的钱包；集合中已有的地址不重复写入
+            let duplicate = collection_seen.as_mut().is_some_and(|seen| !seen.insert(wallet.address));
+            let saved = match &keystore {
+                _ if duplicate => Err(format!("集合中已有地址 {}，未重复写入", wallet.address)),
+                _ if dry_run => dry_run_wallet(&mut wallet, &mut wallet_index),
+                Some(keystore) if keystore.only => Ok(WrittenRow { bytes: 0, retries: 0 }),
+                // 批量写入时先分配序号，结果文件在这一批写入时保存
+                _ if batch.is_some() => {
+                    wallet_index.assign(&mut wallet);
+                    Ok(WrittenRow { bytes: 0, retries: 0 })
+                }
+                _ => save_wallet_to_file(&mut wallet, &row_pattern, &outputs[hit], &mut wallet_index),
+            };
+            let write_row = !duplicate && !dry_run && keystore.as_ref().is_none_or(|keystore| !keystore.only);
+            // keystore 与 CSV 分别写入，一侧失败不影响另一侧；写入的字节数一起计入输出上限
+            let saved = match keystore.as_ref().filter(|_| !duplicate) {
+                Some(keystore) => match save_wallet_keystore(&mut wallet, keystore, &outputs[hit], &mut wallet_index) {
+                    Ok((path, bytes)) => {
+                        keystore_files.push(path);
+                        saved.map(|written| WrittenRow { bytes: written.bytes + bytes, ..written })
+                    }
+                    Err(e) => Err(format!("无法保存 keystore: {}", e)),
+                },
+                None => saved,
+            };
+            // 只写入 keystore 时不写入结果文件，wallets.db 仍然记录这个钱包（私钥只在 keystore 中）
+            let saved = match saved {
+                Ok(written) if !duplicate && keystore.as_ref().is_some_and(|keystore| keystore.only) && outputs[hit].database.is_some() => {
+                    save_wallets_to(&[ResultBackend::Database], &[(&wallet, &row_pattern)], &outputs[hit])
+                        .map(|row| WrittenRow { bytes: written.bytes + row.bytes, retries: written.retries + row.retries })
+                }
+                saved => saved,
+            };
+            // Solana 另外按 solana-keygen 的格式保存密钥对文件，可以直接用于 solana 命令行
+            let saved = match saved {
+                Ok(written) if outputs[hit].writes_keypair() && !dry_run => match save_solana_keypair(&wallet, &outputs[hit]) {
+                    Ok((path, bytes)) => {
+                        keypair_files.push(path);
+                        Ok(WrittenRow { bytes: written.bytes + bytes, ..written })
+                    }
+                    Err(e) => Err(format!("无法保存密钥对: {}", e)),
+                },
+                saved => saved,
+            };
+            // emit_json_files：每个钱包另外写入单独的 JSON 文件，写入失败与结果文件一样按保存失败处理
+            let saved = match saved {
+                Ok(written) if emit_json_files && !duplicate => match save_wallet_json(&wallet, &row_pattern, &outputs[hit]) {
+                    Ok((path, bytes)) => {
+                        json_files.push(path);
+                        Ok(WrittenRow { bytes: written.bytes + bytes, ..written })
+                    }
+                    Err(e) => Err(format!("无法保存 JSON 文件: {}", e)),
+                },
+                saved => saved,
+            };
+            let address = wallet.chain_address.clone().unwrap_or_else(|| chain.format_address(wallet.address));
+            // 按 address_qr 在结果文件旁保存地址的二维码；钱包已经保存，二维码写入失败只记录警告
+            let saved = match (saved, &address_qr) {
+                (Ok(written), Some(address_qr)) => {
+                    let prefix = if wallet.test_wallet { format!("{}_", entropy::TEST_WATERMARK) } else { String::new() };
+                    match wallet_qr::save_address_png(outputs[hit].csv_dir(), &prefix, &address, address_qr) {
+                        Ok((path, bytes)) => {
+                            qr_files.push(path);
+                            Ok(WrittenRow { bytes: written.bytes + bytes, ..written })
+                        }
+                        Err(e) => {
+                            if let Some(recorder) = recorder.as_mut() {
+                                recorder.record_warning(&format!("{}: {}", address, e));
+                            }
+                            Ok(written)
+                        }
+                    }
+                }
+                (saved, _) => saved,
+            };
+            // verify_unused：在后台查询链上记录，查询完成后才发送 wallet-found（集合中已有的地址不查询）
+            let found_event = || WalletFound::new(&session_id, &wallet, &patterns[hit], zero_nibbles, char_count, &score_weights, emit_private_key);
+            match unused_check.as_mut().filter(|_| !duplicate) {
+                Some(check) => check.submit(wallet.address, UncheckedFound { event: found_event(), address: address.clone(), dir: outputs[hit].csv_dir().to_path_buf() }),
+                None => match batch.as_mut() {
+                    Some(batch) => batch.push(
+                        PendingMatch { wallet: wallet.clone(), row_pattern: row_pattern.clone(), output: hit, write: write_row, event: found_event() },
+                        std::time::Instant::now(),
+                    ),
+                    None => {
+                        gate.send(|| app.emit("wallet-found", found_event()));
+                    }
+                },
+            }
+            if let Some(check) = unused_check.as_mut() {
+                report_on_chain_activity(&app, &mut gate, check.completed(), &session_stamp, &mut activity_files, recorder.as_mut());
+            }
+            // 集合中已有的地址不算保存失败；其它失败发送 save-error 事件，钱包留在内存中
+            if let (false, Err(message)) = (duplicate, &saved) {
+                unsaved_wallets.push(wallet.clone());
+                gate.send(|| app.emit("save-error", SaveError {
+                    session_id: session_id.clone(),
+                    message: message.clone(),
+                    wallet: WalletFound::new(&session_id, &wallet, &patterns[hit], zero_nibbles, char_count, &score_weights, emit_private_key),
+                    unsaved: unsaved_wallets.len(),
+                }));
+            }
+            // 系统通知不经过 webview，窗口关闭时也会发送；只有地址和模式，间隔太短的匹配合并到下一次通知
+            if let Some(active) = notifier.as_mut().filter(|_| !duplicate) {
+                if let Some(matches) = active.on_match(session_start.elapsed()) {
+                    let message = active.message(&address, &patterns[hit], matches);
+                    if let Err(e) = app.notification().builder().title(message.title).body(message.body).show() {
+                        notifier = None;
+                        disable_notifications(&app, &mut gate, &session_id, format!("无法发送系统通知: {}", e), recorder.as_mut());
+                    }
+                }
+            }
+            if let Some((score_function, best_score)) = score_function.zip(record) {
+                gate.send(|| app.emit("new-best", NewBest {
+                    session_id: session_id.clone(),
+                    index: wallet.index,
+                    address: wallet.address,
+                    score_function,
+                    best_score,
+                    previous_score,
+                }));
+            }
+            if let Some(recorder) = recorder.as_mut() {
+                recorder.record_found(wallet.address, wallet.attempts, wallet.index);
+                match &saved {
+                    Ok(WrittenRow { retries: 0, .. }) => {}
+                    Ok(written) => recorder.record_warning(&format!("写入钱包信息时遇到短暂错误，重试 {} 次后成功", written.retries)),
+                    Err(e) => recorder.record_warning(e),
+                }
+            }
+            
+            // 达到会话输出上限时暂停
+            if let Ok(written) = &saved {
+                record_output_bytes(&app, &mut gate, &session_id, &mut output_budget, written.bytes, recorder.as_mut());
+            }
+            
+            // 保存最后一次匹配的钱包
+            record_last_wallet(WalletFound::new(&session_id, &wallet, &patterns[hit], zero_nibbles, char_count, &score_weights, true));
+            session_memory.push_found(wallet.address);
+            check_session_memory(&app, &mut gate, &session_id, &mut session_memory, recorder.as_mut());
+            returned.push(wallet.clone());
+            last_match = Some(wallet);
+            if let Some(batch) = batch.as_mut().filter(|batch| batch.due(std::time::Instant::now())) {
+                flush_found_batch(&app, &mut gate, &session_id, batch.take(), &outputs, &mut unsaved_wallets, &mut output_budget, recorder.as_mut());
+            }
+        }
+        SearchEvent::Progress(progress) => {
+            // 匹配变少时，等待超过 batch_interval_ms 的一批在进度事件时写入
+            if let Some(batch) = batch.as_mut().filter(|batch| batch.due(std::time::Instant::now())) {
+                flush_found_batch(&app, &mut gate, &session_id, batch.take(), &outputs, &mut unsaved_wallets, &mut output_budget, recorder.as_mut());
+            }
+            if let Some(check) = unused_check.as_mut() {
+                report_on_chain_activity(&app, &mut gate, check.completed(), &session_stamp, &mut activity_files, recorder.as_mut());
+            }
+            live.update(progress.attempts, std::time::Duration::from_millis(progress.duration));
+            published.update(&progress, live.attempts_per_second());
+            taskbar.update(progress.attempts);
+            if let Some(recorder) = recorder.as_mut() {
+                recorder.record_progress(progress.attempts, progress.matches, progress.duration, progress.matched);
+            }
+            // 每隔 SAVE_INTERVAL 更新会话状态文件，找到匹配时立即更新；写入失败只警告一次
+            let totals = SearchTotals { attempts: progress.attempts, matches: progress.matches, duration: progress.duration };
+            if let Some(warning) = state_writer.as_mut().and_then(|writer| writer.update(totals, &pattern_matches, std::time::Instant::now(), progress.matched)) {
+                if let Some(recorder) = recorder.as_mut() {
+                    recorder.record_warning(&warning);
+                }
+            }
+            if let Some((writer, board)) = leaderboard_writer.as_mut().zip(leaderboard.as_mut()) {
+                if let Some(warning) = writer.tick(board, std::time::Instant::now()) {
+                    if let Some(recorder) = recorder.as_mut() {
+                        recorder.record_warning(&warning);
+                    }
+                }
+            }
+            check_session_memory(&app, &mut gate, &session_id, &mut session_memory, recorder.as_mut());
+            check_clock_jump(&app, &mut gate, &mut clock_jumps, session_start, recorder.as_mut());
+            // 没有监听者时只保留计数，不构造事件；两次进度事件之间至少间隔 progress_interval_ms，找到匹配时立即发送
+            if gate.is_listening() && progress_throttle.ready(session_start.elapsed(), progress.matched) {
+                pending_progress = None;
+                let progress = progress_stats(&progress, &pattern_matches, best_score);
+                if gate.take_resumed() {
+                    gate.send(|| app.emit("generation-catch-up", CatchUpSnapshot {
+                        session_id: session_id.clone(),
+                        progress,
+                        found: session_memory.found(),
+                    }));
+                } else {
+                    gate.send(|| app.emit("generation-progress", progress));
+                }
+            } else {
+                pending_progress = Some(progress);
+            }
+        }
+        SearchEvent::RngWarning(progress) => {
+            let warning = SecurityWarning {
+                message: "随机数生成器产生了无效的候选私钥，随机数来源可能存在问题".to_string(),
+                attempts: progress.attempts,
+                rejected_keys: progress.rejected_keys,
+                rejection_rate: progress.rejection_rate,
+            };
+            if let Some(recorder) = recorder.as_mut() {
+                recorder.record_warning(&warning.message);
+            }
+            gate.send(|| app.emit("security-warning", warning));
+        }
+        SearchEvent::WorkerFailed(failure) => {
+            let location = failure.location.as_ref().map(|location| format!("（{}）", location)).unwrap_or_default();
+            let message = format!("工作线程 {} 异常退出: {}{}", failure.worker, failure.message, location);
+            sessions::update_live(&session_id, |live| {
+                live.workers.get_or_insert(WorkerCapacity::full(config.worker_count())).record_failure();
+            });
+            if let Some(recorder) = recorder.as_mut() {
+                recorder.record_warning(&message);
+            }
+            gate.send(|| app.emit("worker-failure", failure));
+        }
+        SearchEvent::RateSample(sample) => {
+            let stats = worker_stats::record(&session_id, &sample);
+            rate_history::record(&session_id, sample.clone());
+            if gate.is_listening() {
+                if let Some(stats) = stats {
+                    gate.send(|| app.emit("worker-stats", stats));
+                }
+                gate.send(|| app.emit("rate-sample", rate_history::RateSampleEvent { session_id: session_id.clone(), sample }));
+            }
+        }
+    };
+    // 这个会话的暂停状态变化时（无论由命令、空闲检测还是输出上限引起）发送 generation-paused / generation-resumed
+    let mut pause_watcher = pause::PauseWatcher::new();
+    let is_paused = || {
+        let state = sessions::pause_state(&session_id);
+        if let Some(state) = pause_watcher.observe(state) {
+            let event = if state == pause::PauseState::Running { "generation-resumed" } else { "generation-paused" };
+            let _ = app.emit(event, PauseChanged { session_id: session_id.clone(), state });
+        }
+        state != pause::PauseState::Running
+    };
+    let cancel = session.cancel_token();
+    // score 模式按是否刷新最高分判断匹配；暂停时不消耗 CPU，等待恢复或取消
+    let report = match &scorer {
+        Some(scorer) => engine::search_with(&config, &cancel, is_paused, |address| scorer.try_record(address), on_event),
+        None => engine::search(&config, &cancel, is_paused, on_event),
+    };
+    // 停止、取消或工作线程出错时写入最后一批，wallets-found 都在 generation-stopped 之前发送
+    if let Some(mut batch) = batch.filter(|batch| !batch.is_empty()) {
+        flush_found_batch(&app, &mut gate, &session_id, batch.take(), &outputs, &mut unsaved_wallets, &mut output_budget, recorder.as_mut());
+    }
+    if let Some(progress) = pending_progress {
+        gate.send(|| app.emit("generation-progress", progress_stats(&progress, &pattern_matches, best_score)));
+    }
+    // 等待还没有完成的链上记录查询，之后剩余的匹配记为未知，wallet-found 事件都在 generation-stopped 之前发送
+    if let Some(check) = unused_check {
+        report_on_chain_activity(&app, &mut gate, check.finish(balances::ACTIVITY_TIMEOUT), &session_stamp, &mut activity_files, recorder.as_mut());
+    }
+    
+    taskbar.finish();
+    
+    // 达到模板中的停止条件时按对应原因停止，否则使用取消时登记的原因
+    let stop = report.stop.unwrap_or_else(|| session.take_stop());
+    let totals = SearchTotals { attempts: report.attempts, matches: report.matches, duration: report.duration };
+    let state_warning = state_writer.and_then(|writer| writer.finish(totals, &pattern_matches, &stop));
+    let state_warning = match (leaderboard_writer, leaderboard.as_mut()) {
+        (Some(writer), Some(board)) => state_warning.or(writer.finish(board)),
+        _ => state_warning,
+    };
+    if let Some(recorder) = recorder.as_mut() {
+        if let Some(warning) = &state_warning {
+            recorder.record_warning(warning);
+        }
+        recorder.record_state("cancelled");
+        recorder.record_stop(&stop);
+    }
+    gate.send(|| app.emit("generation-stopped", GenerationStopped {
+        session_id: session_id.clone(),
+        stop: stop.clone(),
+        matcher: matcher.clone(),
+        attempts: report.attempts,
+        matches: report.matches,
+        duration: report.duration,
+        paused_duration: report.paused_duration,
+        milestones: live.milestones(),
+        job_id: job_id.clone(),
+        clock_jumps: clock_jumps.jumps().to_vec(),
+        workers: report.workers,
+        worker_failures: report.worker_failures.clone(),
+        output: Some(output_budget.usage()),
+    }));
+    if let Some(job_id) = &job_id {
+        queue::record_run(job_id, &session_id, &stop);
+    }
+    let details = manifest::SessionDetails {
+        template: template.as_deref(),
+        chain,
+        matcher: Some(&matcher),
+        score_weights: Some(&score_weights),
+        collection: output.collection.as_deref(),
+        keccak_backend: Some(keccak_backend),
+        entropy: Some(&entropy),
+        clock_jumps: clock_jumps.jumps(),
+        worker_failures: &report.worker_failures,
+    };
+    // 合并的结果文件由全部模式共用，只记录一次
+    let mut recorded = std::collections::HashSet::new();
+    let saved_files: Vec<PathBuf> = outputs
+        .iter()
+        .zip(&patterns)
+        .filter(|_| keystore.as_ref().is_none_or(|keystore| !keystore.only))
+        .filter(|(output, pattern)| output.collection.is_none() && pattern_matches.get(*pattern).is_some_and(|&count| count > 0))
+        .flat_map(|(output, _)| output.result_files())
+        .filter(|path| recorded.insert(path.clone()))
+        .chain(keystore_files)
+        .chain(keypair_files)
+        .chain(qr_files)
+        .chain(json_files)
+        .chain(activity_files)
+        .collect();
+    if !dry_run {
+        let _ = finalize_session_artifacts(recorder, &pattern, &saved_files, output, &stop, &details, &session_stamp);
+    }
+    // 全部工作线程异常退出时以错误结束（已找到的钱包已经保存；有未能保存的钱包时仍然返回它们，私钥不能只留在错误信息中）
+    if stop.reason == StopReason::WorkerFailure && unsaved_wallets.is_empty() {
+        return Err(WalletError::worker_failure(report.worker_failures));
+    }
+    let wallet = last_match.ok_or_else(|| match stop.reason {
+        StopReason::MaxAttempts | StopReason::MaxDuration => WalletError::no_match_within_budget(stop.reason, report.attempts, report.duration),
+        _ => WalletError::cancelled(report.attempts, report.matches),
+    })?;
+    let (wallets, wallets_truncated) = returned.into_parts();
+    Ok(GenerationResult { wallet, wallets, wallets_truncated, totals, unsaved_wallets, resumed, dry_run })
+}
+
+/// 回放结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReplaySummary {
+    /// 靓号模式
+    pub pattern: String,
+    /// 会话开始时间
+    pub started_at: String,
+    /// 已重放的事件数量
+    pub events: usize,
+    /// 回放文件末尾是否被截断
+    pub truncated: bool,
+}
+
+/**
+ * 重放会话事件（用于调试和支持），按原始时间间隔通过通道重新发送
+ * 
+ * @param path - 回放文件路径
+ * @param speed - 回放速度倍数（可选，默认 1.0）
+ * @param on_event - 接收事件的通道
+ * @returns 回放结果
+ */
+#[tauri::command]
+async fn replay_session(
+    path: String,
+    speed: Option<f64>,
+    on_event: Channel<ReplayEvent>,
+) -> Result<ReplaySummary, WalletError> {
+    let speed = speed.unwrap_or(1.0);
+    if !speed.is_finite() || speed <= 0.0 {
+        return Err("回放速度必须大于 0".into());
+    }
+    
+    let replay = replay::read_replay(&PathBuf::from(path))?;
+    let mut last_t = 0u64;
+    for event in &replay.events {
+        // 按原始时间间隔（除以倍速）等待
+        let wait_ms = event.t.saturating_sub(last_t) as f64 / speed;
+        if wait_ms >= 1.0 {
+            tokio::time::sleep(std::time::Duration::from_millis(wait_ms as u64)).await;
+        }
+        last_t = event.t;
+        on_event.send(event.clone()).map_err(|e| format!("无法发送回放事件: {}", e))?;
+    }
+    
+    Ok(ReplaySummary {
+        pattern: replay.header.pattern,
+        started_at: replay.header.started_at,
+        events: replay.events.len(),
+        truncated: replay.truncated,
+    })
+}
+
+/// 拆分密钥会话开始时发送的信息
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SplitKeySessionInfo {
+    /// 会话标识（由模式和开始时间生成；cancel_generation、pause_generation 和 export_split_key_result 使用它）
+    pub session_id: String,
+    /// 请求文件中的请求编号（只作为记录，同一个请求可以导入多次）
+    pub request_id: String,
+    /// 靓号模式
+    pub pattern: String,
+    /// 请求过期时间
+    pub expires_at: String,
+    /// 期望尝试次数
+    pub expected_attempts: f64,
+}
+
+/**
+ * 导入他人的拆分密钥搜索请求并开始搜索
+ * 
+ * 搜索偏移量 k，使 address(A + k·G) 符合请求方的靓号模式；找到第一个匹配或被取消时结束。
+ * 会话标识由模式和开始时间生成（请求编号来自文件，不能作为会话标识），随 split-key-session-started 事件发送；
+ * 与生成会话一样可以暂停和恢复，候选私钥被拒绝的比例异常时发送 security-warning。
+ * 
+ * @param app - 应用句柄
+ * @param path - 请求文件路径
+ * @returns 搜索结果
+ */
+#[tauri::command]
+async fn load_split_key_request(app: AppHandle, path: String) -> Result<SplitKeyResult, WalletError> {
+    let validated = split_key::load_request(&PathBuf::from(path))?;
+    // 搜索在找到匹配前一直占用所在的线程，放在阻塞线程池中，不占用处理其它命令的异步工作线程
+    tauri::async_runtime::spawn_blocking(move || run_split_key_search(&app, validated))
+        .await
+        .map_err(|e| WalletError::from(format!("拆分密钥搜索的线程异常退出: {}", e)))?
+}
+
+/**
+ * 运行拆分密钥搜索，直到找到第一个匹配或被取消
+ * 
+ * @param app - 应用句柄
+ * @param validated - 校验通过的请求
+ * @returns 搜索结果
+ */
+fn run_split_key_search(app: &AppHandle, validated: split_key::ValidatedRequest) -> Result<SplitKeyResult, WalletError> {
+    let started = chrono::Utc::now();
+    let session_id = format!("split_{}_{}", pattern::file_label(&validated.request.pattern), started.format("%Y%m%d_%H%M%S%3fZ"));
+    let _running = RunningGuard::new();
+    let session = sessions::register(&session_id, SessionKind::SplitKey)?;
+    let mut gate = EventGate::new(&session_id);
+    let probability = validated.pattern.probability();
+    let live = LiveSession::new(&session_id, probability);
+    gate.send(|| app.emit("split-key-session-started", SplitKeySessionInfo {
+        session_id: session_id.clone(),
+        request_id: validated.request.request_id.clone(),
+        pattern: validated.request.pattern.clone(),
+        expires_at: validated.request.expires_at.clone(),
+        expected_attempts: validated.expected_attempts,
+    }));
+    
+    let mut clock = engine::SessionClock::resumed(std::time::Instant::now(), std::time::Duration::ZERO);
+    let mut pause_watcher = pause::PauseWatcher::new();
+    let secp = Secp256k1::new();
+    let mut rng = OsRng;
+    let mut attempt = 0u64;
+    let mut key_stats = KeyCandidateStats::default();
+    
+    loop {
+        // 与生成会话一样每隔 1000 次尝试检查暂停状态，暂停时不消耗 CPU，等待恢复或取消
+        if attempt.is_multiple_of(1000) {
+            loop {
+                let state = sessions::pause_state(&session_id);
+                if let Some(state) = pause_watcher.observe(state) {
+                    let event = if state == pause::PauseState::Running { "generation-resumed" } else { "generation-paused" };
+                    let _ = app.emit(event, PauseChanged { session_id: session_id.clone(), state });
+                }
+                clock.set_paused(state != pause::PauseState::Running, std::time::Instant::now());
+                if state == pause::PauseState::Running || session.cancel_flag().load(Ordering::SeqCst) {
+                    break;
+                }
+                std::thread::sleep(engine::PAUSE_POLL_INTERVAL);
+            }
+        }
+        if session.cancel_flag().load(Ordering::SeqCst) {
+            let stop = session.take_stop();
+            let now = std::time::Instant::now();
+            gate.send(|| app.emit("generation-stopped", GenerationStopped {
+                session_id: session_id.clone(),
+                stop,
+                matcher: validated.pattern.matcher().to_string(),
+                attempts: attempt,
+                matches: 0,
+                duration: clock.active(now).as_millis() as u64,
+                paused_duration: clock.paused(now).as_millis() as u64,
+                milestones: live.milestones(),
+                job_id: None,
+                clock_jumps: Vec::new(),
+                workers: WorkerCapacity::full(1),
+                worker_failures: Vec::new(),
+                output: None,
+            }));
+            return Err("拆分密钥搜索已取消，未找到匹配的地址".into());
+        }
+        
+        attempt += 1;
+        // 随机偏移量 k 与私钥的取值范围相同；被拒绝的候选与生成会话一样计数，比例异常时发送安全警告
+        let tweak = match next_secret_key(&mut rng, &mut key_stats) {
+            Some(key) => key,
+            None => {
+                if key_stats.should_warn(attempt) {
+                    gate.send(|| app.emit("security-warning", SecurityWarning {
+                        message: "随机数生成器产生了无效的候选私钥，随机数来源可能存在问题".to_string(),
+                        attempts: attempt,
+                        rejected_keys: key_stats.rejected,
+                        rejection_rate: key_stats.rejection_rate(attempt),
+                    }));
+                }
+                continue;
+            }
+        };
+        
+        let address_lower = split_key::tweaked_address(&secp, &validated.public_key, &tweak)?;
+        let address_checksum = to_checksum_address(&address_lower)?;
+        let matches = validated.pattern.matches(&address_checksum);
+        
+        let now = std::time::Instant::now();
+        if attempt.is_multiple_of(1000) || matches {
+            live.update(attempt, clock.active(now));
+        }
+        if (attempt.is_multiple_of(1000) || matches) && gate.is_listening() {
+            let progress = ProgressStats {
+                session_id: session_id.clone(),
+                attempts: attempt,
+                matches: matches as u64,
+                duration: clock.active(now).as_millis() as u64,
+                paused_duration: clock.paused(now).as_millis() as u64,
+                rejected_keys: key_stats.rejected,
+                rejection_rate: key_stats.rejection_rate(attempt),
+                confidence: confidence::confidence_after(probability, attempt as f64),
+                cumulative_success_probability: confidence::cumulative_success(Some(probability), attempt),
+                probability_next_hour: confidence::success_within(Some(probability), live.attempts_per_second(), confidence::NEXT_HOUR),
+                rate: live.rate(),
+                attempts_per_second: live.attempts_per_second(),
+                // 拆分密钥搜索在第一个匹配后结束
+                estimated_remaining_ms: confidence::remaining_ms(
+                    &GenerationLimits::default(),
+                    Some(probability),
+                    attempt,
+                    0,
+                    clock.active(now),
+                    live.attempts_per_second(),
+                ),
+                rate_low_precision: live.low_precision(),
+                pattern_matches: BTreeMap::new(),
+                best_zero_nibbles: None,
+                best_score: None,
+            };
+            if gate.take_resumed() {
+                gate.send(|| app.emit("generation-catch-up", CatchUpSnapshot {
+                    session_id: session_id.clone(),
+                    progress,
+                    found: Vec::new(),
+                }));
+            } else {
+                gate.send(|| app.emit("generation-progress", progress));
+            }
+        }
+        
+        if matches {
+            let result = SplitKeyResult {
+                request_id: validated.request.request_id.clone(),
+                public_key: validated.request.public_key.clone(),
+                pattern: validated.request.pattern.clone(),
+                tweak: hex::encode(tweak.secret_bytes()),
+                address: Address::parse(&address_lower)?,
+                attempts: attempt,
+                duration: clock.active(now).as_millis() as u64,
+            };
+            SPLIT_KEY_RESULTS
+                .get_or_init(|| Mutex::new(HashMap::new()))
+                .lock()
+                .map_err(|_| "拆分密钥会话状态不可用".to_string())?
+                .insert(session_id, result.clone());
+            return Ok(result);
+        }
+    }
+}
+
+/**
+ * 导出拆分密钥搜索结果（只包含偏移量和地址，不含本机的任何私钥）
+ * 
+ * @param session_id - 会话标识（split-key-session-started 事件的 session_id）
+ * @param path - 输出文件路径
+ */
+#[tauri::command]
+fn export_split_key_result(session_id: String, path: String) -> Result<(), WalletError> {
+    let result = SPLIT_KEY_RESULTS
+        .get()
+        .and_then(|results| results.lock().ok()?.get(&session_id).cloned())
+        .ok_or_else(|| format!("找不到拆分密钥会话: {}", session_id))?;
+    split_key::write_result(&result, &PathBuf::from(path)).map_err(WalletError::from)
+}
+
+/**
+ * 在一个已有助记词的地址序号中寻找靓号：依次派生 m/44'/60'/0'/0/i，与 generate_fancy_wallet 使用同一个匹配器比较
+ * 
+ * 无效的助记词（词数、词表或校验位）在开始前返回错误。扫描时发送与生成相同的 generation-progress、
+ * wallet-found（不含私钥和助记词）和 generation-stopped 事件，cancel_generation 按会话标识取消；
+ * 只有 save 为 true 时才把匹配的私钥写入结果文件（不写入助记词），否则不写入任何文件。
+ * 
+ * @param app - 应用句柄
+ * @param mnemonic - BIP-39 助记词（大小写和多余的空白不影响结果）
+ * @param pattern - 靓号模式
+ * @param start_index - 第一个地址序号（可选，默认 0）
+ * @param max_index - 最后一个地址序号（包含，不超过 2^31 - 1）
+ * @param passphrase - BIP-39 口令（可选，默认没有）
+ * @param case_sensitive - 是否按模式给出的大小写与 checksum 地址比较（可选，默认 false）
+ * @param case_mode - 大小写方式（可选，含义与 generate_fancy_wallet 相同）
+ * @param pattern_type - 模式类型（可选，默认 standard）
+ * @param match_mode - 比较方式（可选，含义与 generate_fancy_wallet 相同）
+ * @param save - 是否把匹配的私钥写入结果文件（可选，默认 false）
+ * @param save_path - 保存路径（可选，默认 Documents 目录，只在 save 为 true 时使用）
+ * @param session_id - 会话标识（可选，默认由模式和开始时间生成）
+ * @returns 扫描结果（取消时为取消前找到的匹配）
+ */
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn scan_mnemonic_for_pattern(
+    app: AppHandle,
+    mnemonic: String,
+    pattern: String,
+    start_index: Option<u32>,
+    max_index: u32,
+    passphrase: Option<String>,
+    case_sensitive: Option<bool>,
+    case_mode: Option<CaseMode>,
+    pattern_type: Option<PatternType>,
+    match_mode: Option<MatchMode>,
+    save: Option<bool>,
+    save_path: Option<String>,
+    session_id: Option<String>,
+) -> Result<ScanReport, WalletError> {
+    let phrase = mnemonic::parse(&mnemonic)?;
+    let start_index = start_index.unwrap_or_default();
+    mnemonic_scan::validate_range(start_index, max_index)?;
+    let params = GenerationParams { pattern, case_sensitive, case_mode, pattern_type, match_mode, ..GenerationParams::default() };
+    if params.pattern_type == Some(PatternType::LeadingZeros) {
+        return Err("扫描助记词不支持 leading_zeros 模式，请使用由零组成的普通模式".into());
+    }
+    params.check_patterns()?;
+    let parsed = params.parsed_patterns().remove(0);
+    let pattern = params.pattern;
+    
+    let started = chrono::Utc::now();
+    let session_id = match session_id {
+        Some(session_id) => {
+            sessions::validate_id(&session_id)?;
+            session_id
+        }
+        None => format!("{}_{}", pattern::file_label(&pattern), started.format("%Y%m%d_%H%M%S%3fZ")),
+    };
+    let _running = RunningGuard::new();
+    let session = sessions::register(&session_id, SessionKind::MnemonicScan)?;
+    // 只有显式保存时才解析输出位置并登记结果文件
+    let output = if save.unwrap_or_default() {
+        let stamp = timestamps::filename_stamp(started);
+        preflight_output_root(save_path.clone())?;
+        let output = resolve_session_output(
+            save_path,
+            Some(&pattern::file_label(&pattern)),
+            Chain::Ethereum,
+            None,
+            RngMode::default(),
+            &stamp,
+            filename_template::DEFAULT_TEMPLATE,
+            None,
+            CsvPrivateKeys::Full,
+        )?;
+        output_root::check_writable(output.csv_dir())?;
+        output_folder::register(&session_id, output.csv_dir(), Some(output.csv_path.clone()));
+        let active = saved_wallets::ActiveFile::register_exclusive(&output.csv_path)
+            .map_err(|_| format!("结果文件 {} 正在被另一个会话写入，请稍后再开始", output.csv_path.display()))?;
+        Some((output, active))
+    } else {
+        None
+    };
+    
+    let mut gate = EventGate::new(&session_id);
+    let probability = parsed.probability();
+    let live = LiveSession::new(&session_id, probability);
+    let weights = scoring::resolve(None)?;
+    let mut progress_throttle = ProgressThrottle::new(None)?;
+    let total = (max_index - start_index) as u64 + 1;
+    let limits = GenerationLimits { max_attempts: Some(total), ..GenerationLimits::default() };
+    let start_time = std::time::Instant::now();
+    let mut matches = 0u64;
+    let progress_stats = |attempts: u64, matches: u64| ProgressStats {
+        session_id: session_id.clone(),
+        attempts,
+        matches,
+        duration: start_time.elapsed().as_millis() as u64,
+        paused_duration: 0,
+        rejected_keys: 0,
+        rejection_rate: 0.0,
+        confidence: confidence::confidence_after(probability, attempts as f64),
+        cumulative_success_probability: confidence::cumulative_success(Some(probability), attempts),
+        probability_next_hour: confidence::success_within(Some(probability), live.attempts_per_second(), confidence::NEXT_HOUR),
+        rate: live.rate(),
+        attempts_per_second: live.attempts_per_second(),
+        estimated_remaining_ms: confidence::remaining_ms(&limits, Some(probability), attempts, matches, start_time.elapsed(), live.attempts_per_second()),
+        rate_low_precision: live.low_precision(),
+        pattern_matches: BTreeMap::from([(pattern.clone(), matches)]),
+        best_zero_nibbles: None,
+        best_score: None,
+    };
+    let mut pending_progress: Option<(u64, u64)> = None;
+    let mut wallet_index = WalletIndex::default();
+    
+    let report = mnemonic_scan::scan(&phrase, passphrase.as_deref().unwrap_or_default(), &parsed, start_index, max_index, session.cancel_flag(), |scanned, found| {
+        if let Some(found) = found {
+            matches += 1;
+            let mut wallet = Wallet {
+                index: 0,
+                address: found.address,
+                private_key: found.private_key.clone(),
+                attempts: scanned,
+                duration: start_time.elapsed().as_millis() as u64,
+                matched_spans: found.matched_spans.clone(),
+                rng_mode: RngMode::default(),
+                test_wallet: false,
+                mnemonic: None,
+                chain_address: None,
+                matched_pattern: pattern.clone(),
+                created_at: timestamps::now_rfc3339(),
+            };
+            let char_count = parsed.achieved_char_count(&hex::encode(found.address.as_bytes()));
+            if let Some((output, _)) = &output {
+                save_wallet_to_file(&mut wallet, &char_count.map_or_else(|| pattern.clone(), pattern::achieved_count_label), output, &mut wallet_index)?;
+            }
+            gate.send(|| app.emit("wallet-found", WalletFound::new(&session_id, &wallet, &pattern, None, char_count, &weights, false)));
+        }
+        if scanned.is_multiple_of(1000) || found.is_some() {
+            live.update(scanned, start_time.elapsed());
+        }
+        if gate.is_listening() && progress_throttle.ready(start_time.elapsed(), found.is_some()) {
+            pending_progress = None;
+            gate.send(|| app.emit("generation-progress", progress_stats(scanned, matches)));
+        } else {
+            pending_progress = Some((scanned, matches));
+        }
+        Ok(())
+    });
+    if let Some((scanned, matches)) = pending_progress {
+        gate.send(|| app.emit("generation-progress", progress_stats(scanned, matches)));
+    }
+    
+    let (attempts, stop) = match &report {
+        Ok(report) if report.cancelled => (report.scanned, session.take_stop()),
+        Ok(report) => (report.scanned, StopRecord { reason: StopReason::MaxAttempts, detail: Some(format!("已扫描到序号 {}", max_index)) }),
+        Err(e) => (0, StopRecord { reason: StopReason::SaveErrorPolicy, detail: Some(e.clone()) }),
+    };
+    gate.send(|| app.emit("generation-stopped", GenerationStopped {
+        session_id: session_id.clone(),
+        stop,
+        matcher: parsed.matcher().to_string(),
+        attempts,
+        matches,
+        duration: start_time.elapsed().as_millis() as u64,
+        paused_duration: 0,
+        milestones: live.milestones(),
+        job_id: None,
+        clock_jumps: Vec::new(),
+        workers: WorkerCapacity::full(1),
+        worker_failures: Vec::new(),
+        output: None,
+    }));
+    report.map_err(WalletError::from)
+}
+
+/**
+ * 寻找靓号合约地址：随机生成部署者私钥，计算它用 CREATE 在 nonce 范围内部署的合约地址
+ * （keccak256(rlp([部署者地址, nonce])) 的后 20 字节），用与 generate_fancy_wallet 相同的匹配器比较合约地址
+ * 
+ * 每个匹配写入 FancyWallets/ethereum/contract_<模式>_<时间>.csv（合约地址、nonce、部署者地址和部署者私钥），
+ * 结果浏览器不读取该文件。搜索时发送 generation-progress、contract-found（不含私钥）和 generation-stopped 事件，
+ * cancel_generation 按会话标识取消。部署者必须恰好以记录的 nonce 发出部署交易，之前的 nonce 需要先用其它交易用掉。
+ * 
+ * @param app - 应用句柄
+ * @param pattern - 靓号模式（与合约地址比较）
+ * @param nonce_range - 每个部署者检查的 nonce 范围（包含两端，start 默认 0，最多 65536 个）
+ * @param max_attempts - 最大尝试次数（检查的合约地址数，0 表示不限制）
+ * @param max_matches - 最大匹配数（可选，为空或 0 表示不限制）
+ * @param case_sensitive - 是否按模式给出的大小写与 checksum 地址比较（可选，默认 false）
+ * @param case_mode - 大小写方式（可选，含义与 generate_fancy_wallet 相同）
+ * @param pattern_type - 模式类型（可选，默认 standard）
+ * @param match_mode - 比较方式（可选，含义与 generate_fancy_wallet 相同）
+ * @param save_path - 保存路径（可选，默认 Documents 目录）
+ * @param session_id - 会话标识（可选，默认由模式和开始时间生成）
+ * @returns 搜索结果（取消时为取消前找到的匹配）
+ */
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn generate_fancy_contract_wallet(
+    app: AppHandle,
+    pattern: String,
+    nonce_range: NonceRange,
+    max_attempts: u64,
+    max_matches: Option<u64>,
+    case_sensitive: Option<bool>,
+    case_mode: Option<CaseMode>,
+    pattern_type: Option<PatternType>,
+    match_mode: Option<MatchMode>,
+    save_path: Option<String>,
+    session_id: Option<String>,
+) -> Result<ContractSearchReport, WalletError> {
+    nonce_range.validate()?;
+    let params = GenerationParams { pattern, case_sensitive, case_mode, pattern_type, match_mode, ..GenerationParams::default() };
+    params.check_patterns()?;
+    let parsed = params.parsed_patterns().remove(0);
+    let pattern = params.pattern;
+    
+    let started = chrono::Utc::now();
+    let session_id = match session_id {
+        Some(session_id) => {
+            sessions::validate_id(&session_id)?;
+            session_id
+        }
+        None => format!("contract_{}_{}", pattern::file_label(&pattern), started.format("%Y%m%d_%H%M%S%3fZ")),
+    };
+    accept_start(&app)?;
+    let _running = RunningGuard::new();
+    let session = sessions::register(&session_id, SessionKind::ContractSearch)?;
+    let root = preflight_output_root(save_path)?;
+    let contract_dir = Chain::Ethereum.dir(&root.wallets_dir);
+    output_root::check_writable(&contract_dir)?;
+    let stem = format!("contract_{}_{}", pattern::file_label(&pattern), timestamps::filename_stamp(started));
+    let csv_path = session_csv_path(&contract_dir, &stem, "", Some(contract::CSV_HEADER))?;
+    let _active_file = saved_wallets::ActiveFile::register_exclusive(&csv_path)
+        .map_err(|_| format!("结果文件 {} 正在被另一个会话写入，请稍后再开始", csv_path.display()))?;
+    
+    let mut gate = EventGate::new(&session_id);
+    let probability = parsed.probability();
+    let live = LiveSession::new(&session_id, probability);
+    let mut progress_throttle = ProgressThrottle::new(None)?;
+    let limits = GenerationLimits {
+        max_attempts: (max_attempts > 0).then_some(max_attempts),
+        max_matches: max_matches.filter(|&max| max > 0),
+        ..GenerationLimits::default()
+    };
+    let start_time = std::time::Instant::now();
+    let mut matches = 0u64;
+    let progress_stats = |attempts: u64, matches: u64| ProgressStats {
+        session_id: session_id.clone(),
+        attempts,
+        matches,
+        duration: start_time.elapsed().as_millis() as u64,
+        paused_duration: 0,
+        rejected_keys: 0,
+        rejection_rate: 0.0,
+        confidence: confidence::confidence_after(probability, attempts as f64),
+        cumulative_success_probability: confidence::cumulative_success(Some(probability), attempts),
+        probability_next_hour: confidence::success_within(Some(probability), live.attempts_per_second(), confidence::NEXT_HOUR),
+        rate: live.rate(),
+        attempts_per_second: live.attempts_per_second(),
+        estimated_remaining_ms: confidence::remaining_ms(&limits, Some(probability), attempts, matches, start_time.elapsed(), live.attempts_per_second()),
+        rate_low_precision: live.low_precision(),
+        pattern_matches: BTreeMap::from([(pattern.clone(), matches)]),
+        best_zero_nibbles: None,
+        best_score: None,
+    };
+    let mut pending_progress: Option<(u64, u64)> = None;
+    
+    let report = contract::search(&parsed, nonce_range, &limits, session.cancel_flag(), |attempts, found| {
+        if let Some(found) = found {
+            matches += 1;
+            write_contract_row(&csv_path, found, &pattern)?;
+            gate.send(|| app.emit("contract-found", found.clone()));
+        }
+        if attempts.is_multiple_of(1000) || found.is_some() {
+            live.update(attempts, start_time.elapsed());
+        }
+        if gate.is_listening() && progress_throttle.ready(start_time.elapsed(), found.is_some()) {
+            pending_progress = None;
+            gate.send(|| app.emit("generation-progress", progress_stats(attempts, matches)));
+        } else {
+            pending_progress = Some((attempts, matches));
+        }
+        Ok(())
+    });
+    if let Some((attempts, matches)) = pending_progress {
+        gate.send(|| app.emit("generation-progress", progress_stats(attempts, matches)));
+    }
+    
+    let (attempts, stop) = match &report {
+        Ok(report) => (report.attempts, report.stop.clone().unwrap_or_else(|| session.take_stop())),
+        Err(e) => (0, StopRecord { reason: StopReason::SaveErrorPolicy, detail: Some(e.clone()) }),
+    };
+    gate.send(|| app.emit("generation-stopped", GenerationStopped {
+        session_id: session_id.clone(),
+        stop,
+        matcher: parsed.matcher().to_string(),
+        attempts,
+        matches,
+        duration: start_time.elapsed().as_millis() as u64,
+        paused_duration: 0,
+        milestones: live.milestones(),
+        job_id: None,
+        clock_jumps: Vec::new(),
+        workers: WorkerCapacity::full(1),
+        worker_failures: Vec::new(),
+        output: None,
+    }));
+    report.map_err(WalletError::from)
+}
+
+/**
+ * 生成可打印的冷存储恢复单（完全在 Rust 中生成，敏感信息不经过 webview）
+ * 
+ * @param address - 钱包地址
+ * @param format - 输出格式（"html" 或 "pdf"）
+ * @param secret - 要包含的敏感信息（"private_key" 或 "mnemonic"，可选）
+ * @param confirm_sensitive - 是否确认在恢复单中包含敏感信息
+ * @param save_path - 保存路径（可选，默认 Documents 目录）
+ * @returns 恢复单文件路径
+ */
+#[tauri::command]
+fn export_recovery_sheet(
+    address: String,
+    format: String,
+    secret: Option<String>,
+    confirm_sensitive: bool,
+    save_path: Option<String>,
+) -> Result<String, WalletError> {
+    match format.as_str() {
+        "html" => {}
+        "pdf" => return Err("暂不支持 PDF 格式，请导出 HTML 后通过浏览器打印".into()),
+        _ => return Err(format!("不支持的恢复单格式: {}", format).into()),
+    }
+    write_recovery_sheet(&address, secret.as_deref(), confirm_sensitive, save_path, "html", recovery::render_html).map_err(WalletError::from)
+}
+
+/**
+ * 生成纯文本恢复单（.txt），供无法扫描二维码或打开 HTML 的用户手抄
+ * 
+ * 每个字段单独带标签并折行，私钥按 4 个字符一组，并附转录校验码（私钥 SHA-256 的最后 4 个十六进制字符），
+ * 重新输入后可以用 check_transcription 核对。包含私钥同样需要确认。
+ * 
+ * @param address - 钱包地址
+ * @param secret - 要包含的敏感信息（"private_key" 或 "mnemonic"，可选）
+ * @param confirm_sensitive - 是否确认在恢复单中包含敏感信息
+ * @param save_path - 保存路径（可选，默认 Documents 目录）
+ * @returns 恢复单文件路径
+ */
+#[tauri::command]
+fn export_plaintext_sheet(
+    address: String,
+    secret: Option<String>,
+    confirm_sensitive: bool,
+    save_path: Option<String>,
+) -> Result<String, WalletError> {
+    write_recovery_sheet(&address, secret.as_deref(), confirm_sensitive, save_path, "txt", recovery::render_text).map_err(WalletError::from)
+}
+
+/**
+ * 核对手抄后重新输入的私钥与恢复单上的转录校验码是否一致
+ * 
+ * @param key - 重新输入的私钥（可包含分组用的空格）
+ * @param checksum - 恢复单上的转录校验码
+ * @returns 一致时为 true
+ */
+#[tauri::command]
+fn check_transcription(key: String, checksum: String) -> Result<bool, WalletError> {
+    recovery::check_transcription(&key, &checksum).map_err(WalletError::from)
+}
+
+/// 通过 Tauri 剪贴板插件读写系统剪贴板
+struct TauriClipboard<'a>(&'a AppHandle);
+
+impl ClipboardAccess for TauriClipboard<'_> {
+    fn read_text(&self) -> Result<String, String> {
+        self.0.clipboard().read_text().map_err(|e| format!("无法读取剪贴板: {}", e))
+    }
+
+    fn write_text(&self, text: &str) -> Result<(), String> {
+        self.0.clipboard().write_text(text).map_err(|e| format!("无法写入剪贴板: {}", e))
+    }
+
+    fn clear(&self) -> Result<(), String> {
+        self.0.clipboard().clear().map_err(|e| format!("无法清除剪贴板: {}", e))
+    }
+}
+
+/**
+ * 把私钥等敏感值复制到系统剪贴板，到期后自动清除
+ * 
+ * 到期时剪贴板中仍是这个值才清除，用户之后复制的其它内容不受影响；之后又调用本命令时由新的一次负责清除。
+ * 处理后发送 clipboard-cleared 事件（outcome 为 cleared、replaced、superseded 或 failed），退出应用时立即清除。
+ * 
+ * @param value - 要复制的值
+ * @param clear_after_secs - 多少秒后清除（1–600）
+ * @returns 复制的编号和预计清除的时间（用于显示倒计时）
+ */
+#[tauri::command]
+fn copy_secret_to_clipboard(app: AppHandle, value: String, clear_after_secs: u64) -> Result<ClipboardCopy, WalletError> {
+    let value = Zeroizing::new(value);
+    let copy = clipboard::copy(&TauriClipboard(&app), &value, clear_after_secs)?;
+    let copy_id = copy.copy_id;
+    std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_secs(clear_after_secs));
+        let cleared = clipboard::clear_if_unchanged(&TauriClipboard(&app), copy_id);
+        let _ = app.emit("clipboard-cleared", cleared);
+    });
+    Ok(copy)
+}
+
+/**
+ * 生成地址或私钥的二维码（PNG，不写入磁盘），用于扫描到手机
+ * 
+ * 私钥的二维码需要确认，并且只随返回值交给界面，不保存到任何文件；自动保存的二维码见 generate_fancy_wallet 的 address_qr。
+ * 
+ * @param address_or_key - 钱包地址（任何支持的链）或十六进制私钥
+ * @param kind - 二维码的内容（address 或 private_key）
+ * @param confirm_sensitive - 是否确认生成私钥的二维码（kind 为 private_key 时必须为 true）
+ * @param size - 图片的最小边长（可选，默认 256 像素，64–2048）
+ * @param error_correction - 纠错等级（可选，low、medium、quartile 或 high，默认 medium）
+ * @returns base64 编码的 PNG（用 data:image/png;base64, 显示）
+ */
+#[tauri::command]
+fn generate_wallet_qr(
+    address_or_key: String,
+    kind: QrKind,
+    confirm_sensitive: Option<bool>,
+    size: Option<u32>,
+    error_correction: Option<QrErrorCorrection>,
+) -> Result<String, WalletError> {
+    let content = wallet_qr::content(kind, &address_or_key, confirm_sensitive.unwrap_or_default())?;
+    wallet_qr::png_base64(&content, &QrOptions { size, error_correction }).map_err(WalletError::from)
+}
+
+/**
+ * 查询以太坊地址在最新区块的余额（只读，通过用户提供的 JSON-RPC 节点）
+ * 
+ * 只发送 eth_getBalance 请求，请求中只有地址，从不发送私钥、签名或交易；每批 20 个地址，请求之间至少间隔 250 毫秒，
+ * 网络错误、HTTP 429 和 5xx 按退避重试。无效的地址或查询失败的地址在 errors 中单独报告，不影响其它地址。
+ * 
+ * @param addresses - 以太坊地址（最多 10000 个，重复的只查询一次）
+ * @param rpc_url - JSON-RPC 节点地址（http 或 https）
+ * @returns 地址到余额（wei，十进制字符串）的映射和查询失败的地址
+ */
+#[tauri::command]
+async fn check_balances(addresses: Vec<String>, rpc_url: String) -> Result<balances::BalanceReport, WalletError> {
+    balances::check(&addresses, &rpc_url).await.map_err(WalletError::from)
+}
+
+/**
+ * 查找钱包并写入恢复单（recovery 目录中的 recovery_<地址>.<扩展名>，仅所有者可读写）
+ * 
+ * @param address - 钱包地址
+ * @param secret - 要包含的敏感信息（可选）
+ * @param confirm_sensitive - 是否确认在恢复单中包含敏感信息
+ * @param save_path - 保存路径（可选）
+ * @param extension - 文件扩展名
+ * @param render - 渲染恢复单内容
+ * @returns 恢复单文件路径
+ */
+fn write_recovery_sheet(
+    address: &str,
+    secret: Option<&str>,
+    confirm_sensitive: bool,
+    save_path: Option<String>,
+    extension: &str,
+    render: fn(&RecoverySheet) -> Result<String, String>,
+) -> Result<String, String> {
+    let address = Address::parse(address)?;
+    let wallets_dir = resolve_wallets_dir(save_path)?;
+    let saved = saved_wallets::find_saved_wallet(&wallets_dir, &address)?
+        .ok_or_else(|| format!("在钱包目录中找不到地址: {}", address))?;
+    
+    // 只有在明确确认后才写入敏感信息
+    if secret == Some("private_key") && saved.private_keys != CsvPrivateKeys::Full {
+        return Err(format!("结果文件没有保存完整的私钥（{}），恢复单中不能包含私钥", saved.private_keys.as_str()));
+    }
+    let secret = recovery::resolve_secret(secret, confirm_sensitive, &saved.private_key, saved.mnemonic.as_deref())?;
+    
+    // 创建日期取自所在 CSV 文件的修改时间
+    let created_at = std::fs::metadata(&saved.file)
+        .and_then(|meta| meta.modified())
+        .map(|time| chrono::DateTime::<chrono::Local>::from(time).format("%Y-%m-%d").to_string())
+        .unwrap_or_default();
+    
+    let sheet = RecoverySheet {
+        address,
+        pattern: saved.pattern.clone(),
+        secret,
+        derivation_path: saved.mnemonic.as_ref().map(|_| mnemonic::ETHEREUM_DERIVATION_PATH.to_string()),
+        checksum_variant: "EIP-55".to_string(),
+        created_at,
+        test_wallet: saved.test_wallet,
+    };
+    let contents = render(&sheet)?;
+    
+    let chain = saved.chain.unwrap_or(Chain::Ethereum);
+    let recovery_dir = chain.dir(&wallets_dir).join("recovery");
+    std::fs::create_dir_all(&recovery_dir)
+        .map_err(|e| format!("无法创建恢复单目录: {}", e))?;
+    let watermark = if saved.test_wallet { format!("{}_", entropy::TEST_WATERMARK) } else { String::new() };
+    let file_path = recovery_dir.join(format!("recovery_{}{}.{}", watermark, address, extension));
+    recovery::write_private_file(&file_path, &contents)?;
+    
+    Ok(file_path.to_string_lossy().to_string())
+}
+
+/**
+ * 运行离线自检（已知私钥、checksum、匹配器、迷你生成和保存往返）
+ * 
+ * @returns 每项检查的通过情况
+ */
+#[tauri::command]
+async fn run_self_test() -> self_test::SelfTestReport {
+    self_test::run()
+}
+
+/**
+ * 按会话清单校验产物，检测丢失或被修改的文件
+ * 
+ * @param path - 清单文件路径
+ * @returns 每个产物的校验状态
+ */
+#[tauri::command]
+fn verify_session_manifest(path: String) -> Result<manifest::ManifestVerification, WalletError> {
+    manifest::verify_session_manifest(&PathBuf::from(path)).map_err(WalletError::from)
+}
+
+/**
+ * 批量校验外部提供的私钥与期望地址（CSV：private_key,expected_address）
+ * 
+ * 按期望地址的格式选择链，用生成时相同的流程推导地址；报告写在输入文件旁边，只包含行号和地址。
+ * 校验过程中发送 verify-pairs-progress 事件。
+ * 
+ * @param app - 应用句柄
+ * @param path - CSV 文件路径
+ * @returns 一致、不一致和格式错误的行数及报告路径
+ */
+#[tauri::command]
+async fn verify_pairs(app: AppHandle, path: String) -> Result<pair_check::PairSummary, WalletError> {
+    pair_check::verify_pairs(&PathBuf::from(path), |progress| {
+        let _ = app.emit("verify-pairs-progress", progress.clone());
+    }).map_err(WalletError::from)
+}
+
+/**
+ * 由私钥推导地址（与生成循环使用同一个推导函数），用于核对外部来源或手工抄写的私钥
+ * 
+ * 十六进制无效、长度不对和超出 secp256k1 私钥范围时分别返回不同的错误。
+ * 
+ * @param private_key_hex - 私钥（64 个十六进制字符，0x 前缀可选）
+ * @returns 0x 开头的 EIP-55 checksum 地址
+ */
+#[tauri::command]
+pub(crate) fn verify_private_key(private_key_hex: String) -> Result<String, WalletError> {
+    let private_key_hex = Zeroizing::new(private_key_hex);
+    let key = PrivKeyHex::parse(&private_key_hex)?.secret_key();
+    Ok(Address::from_secret_key(&Secp256k1::signing_only(), &key).to_checksum())
+}
+
+/**
+ * 由 BIP-39 助记词和派生路径推导地址，用于核对保存的助记词确实对应保存的地址（与生成和扫描使用同一个派生函数）
+ * 
+ * 不在词表中的词、校验位错误和派生路径中无效的段分别返回指出位置的错误。
+ * 
+ * @param mnemonic - 助记词（大小写和多余的空白不影响结果）
+ * @param path - 派生路径（例如 m/44'/60'/0'/0/0；强化派生用 '、h 或 H 标记）
+ * @param passphrase - BIP-39 口令（可选，默认为空）
+ * @param include_private_key - 是否同时返回派生出的私钥（可选，默认 false）
+ * @returns checksum 地址、规范写法的派生路径和（要求时）十六进制私钥
+ */
+#[tauri::command]
+pub(crate) fn derive_address_from_mnemonic(
+    mnemonic: String,
+    path: String,
+    passphrase: Option<String>,
+    include_private_key: Option<bool>,
+) -> Result<mnemonic::MnemonicDerivation, WalletError> {
+    let mnemonic = Zeroizing::new(mnemonic);
+    let passphrase = Zeroizing::new(passphrase.unwrap_or_default());
+    mnemonic::derive_address(&Secp256k1::signing_only(), &mnemonic, &path, &passphrase, include_private_key.unwrap_or_default()).map_err(WalletError::from)
+}
+
+/**
+ * 将地址转换为 EIP-55 checksum 格式（用于校验粘贴的地址和修正旧的小写记录）
+ * 
+ * @param address - 以太坊地址（40 个十六进制字符，0x 前缀可选，大小写不限）
+ * @returns 0x 开头的 checksum 地址
+ */
+#[tauri::command]
+pub(crate) fn checksum_address(address: String) -> Result<String, WalletError> {
+    Ok(to_checksum_address_checked(&address)?)
+}
+
+/**
+ * 检查地址的大小写是否与 EIP-55 checksum 一致
+ * 
+ * @param address - 以太坊地址（40 个十六进制字符，0x 前缀可选）
+ * @returns 大小写是否一致；地址格式错误时返回错误
+ */
+#[tauri::command]
+pub(crate) fn validate_checksum(address: String) -> Result<bool, WalletError> {
+    Ok(has_valid_checksum(&address)?)
+}
+
+/**
+ * 计算地址的指纹短语（接收方可以用同样的算法独立计算并核对）
+ * 
+ * @param address - 以太坊（0x 十六进制）或波场（T 开头的 Base58）地址
+ * @returns 指纹短语
+ */
+#[tauri::command]
+fn fingerprint_address(address: String) -> Result<String, WalletError> {
+    let chain = Chain::from_address(&address).unwrap_or(Chain::Ethereum);
+    Ok(fingerprint::phrase(&chain.parse_address(&address)?))
+}
+
+/**
+ * 预览地址在给定评分权重下的评分（与生成时使用同一个评分函数）
+ * 
+ * @param address - 以太坊（0x 十六进制）或波场（T 开头的 Base58）地址
+ * @param weights - 评分权重（可选，默认使用内置权重）
+ * @returns 总分、各项明细和归一化后的权重
+ */
+#[tauri::command]
+fn score_address(address: String, weights: Option<ScoreWeights>) -> Result<scoring::AddressScore, WalletError> {
+    let chain = Chain::from_address(&address).unwrap_or(Chain::Ethereum);
+    let address = chain.parse_address(&address)?;
+    Ok(scoring::score(&address, &scoring::resolve(weights.as_ref())?))
+}
+
+/**
+ * 只读检查 keystore 文件或目录：不需要密码，只返回文件中记录的地址和加密参数
+ * 
+ * @param path - keystore 文件或目录
+ * @returns 每个文件的检查结果（没有记录地址的文件需要解密后才能知道地址）
+ */
+#[tauri::command]
+fn inspect_keystore(path: String) -> Result<Vec<keystore::KeystoreInspection>, WalletError> {
+    keystore::inspect(&PathBuf::from(path)).map_err(WalletError::from)
+}
+
+/// 压力测试中发现地址复核不一致时发送的警告
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HardwareWarning {
+    /// 警告信息
+    pub message: String,
+    /// 不一致的样本（不含私钥）
+    pub mismatch: burn_in::BurnInMismatch,
+}
+
+/**
+ * 硬件稳定性压力测试：满负荷运行生成流程，并定期用独立的上下文复核地址
+ * 
+ * 不保存任何文件，可以通过 cancel_generation 提前结束。
+ * 
+ * @param app - 应用句柄
+ * @param duration_secs - 运行时间（秒）
+ * @returns 压力测试报告
+ */
+#[tauri::command]
+async fn burn_in(app: AppHandle, duration_secs: u64) -> Result<burn_in::BurnInReport, WalletError> {
+    if duration_secs == 0 || duration_secs > burn_in::MAX_DURATION_SECS {
+        return Err(format!("运行时间必须在 1 到 {} 秒之间", burn_in::MAX_DURATION_SECS).into());
+    }
+    let _running = RunningGuard::new();
+    let session_id = format!("burn_in_{}", chrono::Utc::now().format("%Y%m%d_%H%M%S%3fZ"));
+    let session = sessions::register(&session_id, SessionKind::BurnIn)?;
+    let mut gate = EventGate::new(&session_id);
+    let secp = Secp256k1::new();
+    // 与生成时相同的流程：推导地址、转换 checksum 并做模式匹配（结果丢弃）
+    let parsed_pattern = ParsedPattern::new("0000");
+    let pipeline = |key: &SecretKey| {
+        let public_key = PublicKey::from_secret_key(&secp, key);
+        let address_checksum = public_key_to_checksum_address(&public_key);
+        std::hint::black_box(parsed_pattern.matches(&address_checksum));
+        address_checksum
+    };
+    
+    let report = burn_in::run(
+        std::time::Duration::from_secs(duration_secs),
+        session.cancel_flag(),
+        pipeline,
+        |event| match event {
+            burn_in::BurnInEvent::Progress(progress) => {
+                if gate.is_listening() {
+                    gate.send(|| app.emit("burn-in-progress", progress));
+                }
+            }
+            burn_in::BurnInEvent::Mismatch(mismatch) => {
+                let warning = HardwareWarning {
+                    message: format!("第 {} 次尝试的地址复核不一致，硬件可能不稳定", mismatch.attempt),
+                    mismatch,
+                };
+                gate.send(|| app.emit("hardware-warning", warning));
+            }
+        },
+    );
+    Ok(report)
+}
+
+/// 生成任务状态
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GenerationStatus {
+    /// 是否有任务正在运行
+    pub running: bool,
+    /// 由空闲检测自动启动、仍在运行的会话
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_started: Option<String>,
+    /// 正在运行的生成会话（按开始时间排序；暂停状态、内存、工作线程和输出统计都按会话分别列出）
+    pub generations: Vec<ActiveGeneration>,
+}
+
+/// 正在运行的生成会话的实时状态（生成循环每次收到进度时更新）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ActiveGeneration {
+    /// 会话标识
+    pub session_id: String,
+    /// 生成参数（模式和选项）
+    pub params: GenerationParams,
+    /// 来源模板（直接开始时为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// 开始时间（UTC，RFC 3339）
+    pub started_at: String,
+    /// 尝试次数
+    pub attempts: u64,
+    /// 匹配次数
+    pub matches: u64,
+    /// 运行时间（毫秒，不含暂停时间）
+    pub duration: u64,
+    /// 最近几秒内的速度（每秒尝试次数；样本还不够时为空）
+    pub attempts_per_second: Option<f64>,
+    /// 是否暂停
+    pub paused: bool,
+    /// 是否已收到停止请求（正在结束）
+    pub cancelled: bool,
+    /// 这个会话的暂停状态、内存统计、工作线程容量、匹配条件和输出统计
+    #[serde(flatten)]
+    pub live: sessions::LiveStatus,
+}
+
+/// 生成会话的实时状态登记，离开作用域时注销
+pub(crate) struct PublishedGeneration {
+    session_id: String,
+}
+
+impl PublishedGeneration {
+    /**
+     * 登记会话的实时状态
+     * 
+     * @param status - 会话开始时的状态（恢复的会话从之前的计数开始）
+     */
+    pub(crate) fn new(status: ActiveGeneration) -> Self {
+        let session_id = status.session_id.clone();
+        if let Ok(mut map) = active_generations().lock() {
+            map.insert(session_id.clone(), status);
+        }
+        PublishedGeneration { session_id }
+    }
+
+    /**
+     * 更新计数和速度
+     * 
+     * @param progress - 最新的进度
+     * @param attempts_per_second - 最近几秒内的速度
+     */
+    pub(crate) fn update(&self, progress: &SearchProgress, attempts_per_second: Option<f64>) {
+        let Ok(mut map) = active_generations().lock() else {
+            return;
+        };
+        if let Some(status) = map.get_mut(&self.session_id) {
+            status.attempts = progress.attempts;
+            status.matches = progress.matches;
+            status.duration = progress.duration;
+            status.attempts_per_second = attempts_per_second;
+        }
+    }
+}
+
+impl Drop for PublishedGeneration {
+    fn drop(&mut self) {
+        if let Ok(mut map) = active_generations().lock() {
+            map.remove(&self.session_id);
+        }
+    }
+}
+
+fn active_generations() -> &'static Mutex<HashMap<String, ActiveGeneration>> {
+    ACTIVE_GENERATIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/**
+ * 正在运行的生成会话的实时状态（按开始时间排序）；各会话的暂停状态、统计和停止请求在读取时确定，不必等到下一次进度
+ */
+pub(crate) fn active_generation_list() -> Vec<ActiveGeneration> {
+    let stopping: HashMap<String, bool> = sessions::list().into_iter().map(|session| (session.session_id, session.stopping)).collect();
+    let mut list: Vec<ActiveGeneration> = active_generations()
+        .lock()
+        .map(|map| map.values().cloned().collect())
+        .unwrap_or_default();
+    for status in list.iter_mut() {
+        status.live = sessions::live_status(&status.session_id).unwrap_or_default();
+        status.paused = status.live.pause != pause::PauseState::Running;
+        status.cancelled = stopping.get(&status.session_id).copied().unwrap_or(false);
+    }
+    list.sort_by(|a, b| (&a.started_at, &a.session_id).cmp(&(&b.started_at, &b.session_id)));
+    list
+}
+
+/// 空闲检测自动开始、暂停或恢复时发送的信息
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IdleTransition {
+    /// 常驻搜索的靓号模式
+    pub pattern: String,
+    /// 当时的系统空闲时间（秒）
+    pub idle_secs: u64,
+}
+
+/**
+ * 暂停正在运行的生成任务或拆分密钥搜索：工作线程休眠等待，尝试次数和运行时间不再增加，并发送 generation-paused 事件；
+ * 暂停期间仍然可以通过 cancel_generation 结束，返回最后找到的钱包
+ * 
+ * @param session_id - 目标会话（为空时暂停全部正在运行的生成会话和拆分密钥搜索）
+ * @returns 新暂停的会话数
+ */
+#[tauri::command]
+fn pause_generation(session_id: Option<String>) -> Result<usize, WalletError> {
+    sessions::update_pause(session_id.as_deref(), PauseRequest::PauseByUser).map_err(WalletError::from)
+}
+
+/**
+ * 恢复暂停的生成任务或拆分密钥搜索（包括自动暂停和因输出上限暂停的任务），并发送 generation-resumed 事件
+ * 
+ * @param session_id - 目标会话（为空时恢复全部暂停的生成会话和拆分密钥搜索）
+ * @returns 恢复的会话数
+ */
+#[tauri::command]
+fn resume_generation(session_id: Option<String>) -> Result<usize, WalletError> {
+    sessions::update_pause(session_id.as_deref(), PauseRequest::ResumeByUser).map_err(WalletError::from)
+}
+
+/**
+ * 获取生成任务状态（同步返回，界面重新加载、事件监听丢失后据此判断是否仍有会话在运行）
+ * 
+ * 每个正在运行的生成会话列出参数、尝试次数、匹配数、运行时间、当前速度以及暂停和停止请求。
+ */
+#[tauri::command]
+fn get_generation_status() -> GenerationStatus {
+    GenerationStatus {
+        running: generation_running(),
+        auto_started: auto_started_session(),
+        generations: active_generation_list(),
+    }
+}
+
+/**
+ * 获取会话最后找到的钱包（界面重新加载或错过返回值时使用，不必打开结果文件）
+ * 
+ * 结果保留到下一个会话开始或应用退出；会话还没有找到钱包时返回说明，wallet 为空。
+ * 
+ * @param session_id - 会话标识
+ * @param redact - 是否隐去私钥和助记词
+ */
+#[tauri::command]
+fn get_last_wallet(session_id: String, redact: bool) -> Result<LastWallet, WalletError> {
+    last_wallet(&session_id, redact).map_err(WalletError::from)
+}
+
+/**
+ * 获取会话最近的吞吐量采样（界面重新加载后用于补齐 rate-sample 曲线）
+ * 
+ * 生成过程中每秒发送一次 rate-sample 事件（时间、与上一次采样之间每秒的尝试次数、累计尝试次数和各工作线程的速度），
+ * 每个会话在内存中保留最近 600 次，保留到下一个会话开始或应用退出。
+ * 
+ * @param session_id - 会话标识（可选，默认为最近开始的生成会话；还没有开始过时返回空的采样）
+ */
+#[tauri::command]
+fn get_rate_history(session_id: Option<String>) -> Result<rate_history::RateHistory, WalletError> {
+    rate_history::history(session_id.as_deref()).map_err(WalletError::from)
+}
+
+/**
+ * 获取会话最近的工作线程统计（界面重新加载后用于显示各线程的速度）
+ * 
+ * 生成过程中每 5 秒发送一次 worker-stats 事件：各工作线程的累计尝试次数、这 5 秒内每秒的尝试次数和找到的匹配数，
+ * 用于发现速度明显偏低的线程（例如被调度到能效核心）。计数由汇总线程读取各线程的原子计数，不影响搜索循环。
+ * 
+ * @param session_id - 会话标识（可选，默认为最近开始的生成会话）
+ */
+#[tauri::command]
+fn get_worker_stats(session_id: Option<String>) -> Result<worker_stats::WorkerStats, WalletError> {
+    worker_stats::current(session_id.as_deref()).map_err(WalletError::from)
+}
+
+/**
+ * 设置会话内存软上限（对之后开始的会话生效）
+ * 
+ * 超过软上限时先抽稀回放历史中的进度快照，仍然超过时丢弃追赶快照中较早的地址；
+ * 结果文件不受影响，并发送 memory-warning 事件说明采取的措施。
+ * 
+ * @param bytes - 软上限（字节）
+ */
+#[tauri::command]
+fn set_memory_soft_limit(bytes: u64) -> Result<(), WalletError> {
+    memory::set_soft_limit(bytes).map_err(WalletError::from)
+}
+
+/**
+ * 设置会话输出上限（写入结果文件的字节数和保存的钱包数，默认 4 GiB 和 1,000,000 个），对正在运行的会话立即生效
+ * 
+ * 达到任一上限时会话暂停并发送 output-cap-reached 事件；提高上限（或设置 disabled）后调用 resume_generation 继续。
+ * 
+ * @param caps - 输出上限
+ */
+#[tauri::command]
+fn set_output_caps(caps: OutputCaps) -> Result<(), WalletError> {
+    output_cap::set_caps(caps).map_err(WalletError::from)
+}
+
+/**
+ * 设置两次开始生成之间的冷却时间（全应用共用，默认 500 毫秒）
+ * 
+ * 用于防止前端反复开始和取消生成时频繁创建线程和打开文件。
+ * 
+ * @param cooldown_ms - 冷却时间（毫秒，0 表示不限制）
+ */
+#[tauri::command]
+fn set_start_cooldown(cooldown_ms: u64) -> Result<(), WalletError> {
+    start_limit::set_cooldown_ms(cooldown_ms).map_err(WalletError::from)
+}
+
+/**
+ * 设置是否在任务栏图标上显示会话进度（对之后开始的会话生效）
+ * 
+ * @param enabled - 是否显示
+ */
+#[tauri::command]
+fn set_taskbar_progress(enabled: bool) {
+    taskbar::set_enabled(enabled);
+}
+
+/**
+ * 设置是否强制使用纯 Rust 的 Keccak-256 实现（用于排查优化实现在某些 CPU 上的问题，对之后开始的会话生效）
+ * 
+ * @param force - 是否强制使用纯 Rust 实现
+ * @returns 重新选择的结果
+ */
+#[tauri::command]
+fn set_keccak_portable(force: bool) -> KeccakSelection {
+    keccak::select(force)
+}
+
+/**
+ * 读取当前用户配置的应用设置（设置文件损坏时恢复为默认设置）
+ */
+fn app_settings() -> Result<settings::AppSettings, String> {
+    let profile_dir = profiles::profile_data_dir(&active_profile()?.name)?;
+    Ok(settings::load(&profile_dir, !migration_report().read_only())?.settings)
+}
+
+/**
+ * 获取当前用户配置的应用设置
+ * 
+ * 设置文件损坏时把它改名保留，用默认设置重新生成，warning 说明原因（成功保存设置后不再返回）。
+ */
+#[tauri::command]
+fn get_settings() -> Result<settings::SettingsInfo, WalletError> {
+    settings::load(&profiles::profile_data_dir(&active_profile()?.name)?, !migration_report().read_only()).map_err(WalletError::from)
+}
+
+/**
+ * 修改当前用户配置的应用设置（generate_fancy_wallet 没有传入的参数使用这些值）
+ * 
+ * @param partial - 部分更新：只修改出现的字段（default_save_path、workers、progress_interval_ms、output_format、
+ *                  notify_on_match、notify_every），字段为 null 时清除该项；未知字段和无效的取值返回错误
+ * @returns 修改后的设置
+ */
+#[tauri::command]
+fn update_settings(partial: serde_json::Value) -> Result<settings::SettingsInfo, WalletError> {
+    ensure_data_writable()?;
+    let profile_dir = profiles::profile_data_dir(&active_profile()?.name)?;
+    let current = settings::load(&profile_dir, true)?;
+    let updated = settings::apply_update(&current.settings, &partial)?;
+    // 默认保存路径与 save_path 使用同样的规则，在保存时就拒绝无效的路径
+    if let Some(path) = &updated.default_save_path {
+        output_root::validate_root(std::path::Path::new(path), output_root::app_bundle_dir().as_deref())?;
+    }
+    settings::save(&profile_dir, &updated)?;
+    settings::clear_reset_warning();
+    Ok(settings::SettingsInfo { settings: updated, path: current.path, warning: None })
+}
+
+/**
+ * 获取当前用户配置的常驻搜索设置
+ */
+#[tauri::command]
+fn get_standing_search() -> Result<idle::StandingSearch, WalletError> {
+    idle::load_standing_search(&profiles::profile_data_dir(&active_profile()?.name)?).map_err(WalletError::from)
+}
+
+/**
+ * 保存当前用户配置的常驻搜索设置（空闲时自动开始，检测到用户活动时自动暂停）
+ * 
+ * @param search - 常驻搜索设置
+ */
+#[tauri::command]
+fn set_standing_search(search: idle::StandingSearch) -> Result<(), WalletError> {
+    ensure_data_writable()?;
+    if search.enabled {
+        ParsedPattern::new(&search.pattern).validate()?;
+        if search.idle_minutes == 0 {
+            return Err("空闲时间必须至少为 1 分钟".into());
+        }
+    }
+    idle::save_standing_search(&profiles::profile_data_dir(&active_profile()?.name)?, &search).map_err(WalletError::from)
+}
+
+/**
+ * 保存命名的生成模板（同名模板会被覆盖）
+ * 
+ * @param name - 模板名称
+ * @param params - 生成参数（模式、链、线程数、输出格式、加密和停止条件）
+ * @returns 保存的模板及提示（本版本不认识的字段会原样保留并给出提示）
+ */
+#[tauri::command]
+fn save_template(name: String, params: GenerationParams) -> Result<templates::TemplateInfo, WalletError> {
+    ensure_data_writable()?;
+    templates::save_template(&profiles::profile_data_dir(&active_profile()?.name)?, &name, params).map_err(WalletError::from)
+}
+
+/**
+ * 列出当前用户配置的生成模板
+ */
+#[tauri::command]
+fn list_templates() -> Result<Vec<templates::TemplateInfo>, WalletError> {
+    templates::list_templates(&profiles::profile_data_dir(&active_profile()?.name)?).map_err(WalletError::from)
+}
+
+/**
+ * 按模板开始生成
+ * 
+ * @param app - 应用句柄
+ * @param name - 模板名称
+ * @param overrides - 覆盖项（与模板参数相同的 JSON 结构，只需包含要修改的字段）
+ * @param session_id - 会话标识（可选，与 generate_fancy_wallet 相同）
+ * @returns 最后找到的钱包和未能保存的钱包
+ */
+#[tauri::command]
+async fn start_from_template(
+    app: AppHandle,
+    name: String,
+    overrides: Option<serde_json::Value>,
+    session_id: Option<String>,
+) -> Result<GenerationResult, WalletError> {
+    accept_start(&app)?;
+    let params = templates::resolve_template(&profiles::profile_data_dir(&active_profile()?.name)?, &name, overrides)?;
+    spawn_generation(app, params, Some(name), session_id, OutputSecrets::default(), None).await
+}
+
+/**
+ * 恢复中断的生成会话（应用崩溃、重启或停止后）：按会话状态文件中的参数重新开始生成，尝试次数、匹配数和运行时间继续累计
+ * 
+ * 私钥是随机生成的，恢复的是参数和统计而不是随机数状态，返回值和 session-resumed 事件中的 rng_state_restored 总是 false。
+ * 新会话有新的会话标识和结果文件，原来的状态文件标记为已恢复，不能再次恢复；无法解析、由更新的版本写入、正在运行、
+ * 已经恢复过、已经达到停止条件或参数在此版本中无效的状态文件返回错误。
+ * 
+ * @param app - 应用句柄
+ * @param session_file - 会话状态文件（generation-session-started 事件的 state_file，位于 FancyWallets/session_state）
+ * @param keystore_password - keystore 密码（原来的会话保存 keystore 时必须提供，密码不写入状态文件）
+ * @param encrypt_output - 加密结果文件的密码（原来的会话加密结果文件时必须提供）
+ * @returns 最后找到的钱包、未能保存的钱包和恢复的会话
+ */
+#[tauri::command]
+async fn resume_session(
+    app: AppHandle,
+    session_file: String,
+    keystore_password: Option<String>,
+    encrypt_output: Option<String>,
+) -> Result<GenerationResult, WalletError> {
+    let state_file = PathBuf::from(session_file);
+    let state = session_state::load(&state_file)?;
+    let running = sessions::list().iter().any(|session| session.session_id == state.session_id);
+    session_state::check_resumable(&state, running)?;
+    if state.keystore && keystore_password.is_none() {
+        return Err("原来的会话另外保存 keystore 文件，恢复时需要 keystore_password".into());
+    }
+    if state.encrypted_output && encrypt_output.is_none() {
+        return Err("原来的会话加密结果文件，恢复时需要 encrypt_output".into());
+    }
+    let keystore_password = keystore_password.filter(|_| state.keystore);
+    let encrypt_output = encrypt_output.filter(|_| state.encrypted_output);
+    let secrets = OutputSecrets::new(keystore_password, state.keystore_only.then_some(true), encrypt_output, &state.params)?;
+    accept_start(&app)?;
+    let (params, template) = (state.params.clone(), state.template.clone());
+    spawn_generation(app, params, template, None, secrets, Some(Resume { state_file, state })).await
+}
+
+/**
+ * 把生成任务加入当前用户配置的队列，并在队列线程没有运行时启动它
+ * 
+ * @param app - 应用句柄
+ * @param params - 生成参数（与模板参数相同）
+ * @returns 加入的任务
+ */
+#[tauri::command]
+fn enqueue_generation(app: AppHandle, params: GenerationParams) -> Result<queue::QueueJob, WalletError> {
+    ensure_data_writable()?;
+    let job = queue::enqueue(&profiles::profile_data_dir(&active_profile()?.name)?, params)?;
+    spawn_queue_runner(app);
+    Ok(job)
+}
+
+/**
+ * 列出当前用户配置的队列（包括尚未汇总的已结束任务）
+ */
+#[tauri::command]
+fn list_queue() -> Result<queue::QueueState, WalletError> {
+    queue::list(&profiles::profile_data_dir(&active_profile()?.name)?).map_err(WalletError::from)
+}
+
+/**
+ * 调整等待中任务的顺序
+ * 
+ * @param job_ids - 排在最前的任务标识（按给出的顺序，其余等待中的任务保持原来的相对顺序）
+ * @returns 调整后的队列
+ */
+#[tauri::command]
+fn reorder_queue(job_ids: Vec<String>) -> Result<queue::QueueState, WalletError> {
+    ensure_data_writable()?;
+    queue::reorder(&profiles::profile_data_dir(&active_profile()?.name)?, &job_ids).map_err(WalletError::from)
+}
+
+/**
+ * 从队列中移除任务（正在运行的任务需要先通过 cancel_generation 停止）
+ * 
+ * @param job_id - 任务标识
+ * @returns 移除的任务
+ */
+#[tauri::command]
+fn remove_from_queue(job_id: String) -> Result<queue::QueueJob, WalletError> {
+    ensure_data_writable()?;
+    queue::remove(&profiles::profile_data_dir(&active_profile()?.name)?, &job_id).map_err(WalletError::from)
+}
+
+/**
+ * 修改队列选项（任务出错时是否停止队列、同时运行的任务数）；队列因出错停止时同时继续运行
+ * 
+ * @param app - 应用句柄
+ * @param options - 队列选项
+ * @returns 修改后的队列
+ */
+#[tauri::command]
+fn set_queue_options(app: AppHandle, options: queue::QueueOptions) -> Result<queue::QueueState, WalletError> {
+    ensure_data_writable()?;
+    let state = queue::set_options(&profiles::profile_data_dir(&active_profile()?.name)?, options)?;
+    spawn_queue_runner(app);
+    Ok(state)
+}
+
+/**
+ * 启动队列线程（已在运行时不做任何事）：依次运行等待中的任务，全部结束后发送 generation-queue-complete
+ * 
+ * @param app - 应用句柄
+ */
+fn spawn_queue_runner(app: AppHandle) {
+    if QUEUE_RUNNER.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(move || loop {
+        if let Err(e) = run_queue(&app) {
+            log_error(&format!("队列运行失败: {}", e));
+        }
+        QUEUE_RUNNER.store(false, Ordering::SeqCst);
+        // 线程退出前加入的任务：重新取得运行权后继续
+        let runnable = active_profile()
+            .and_then(|profile| profiles::profile_data_dir(&profile.name))
+            .and_then(|dir| queue::list(&dir))
+            .is_ok_and(|state| state.has_runnable());
+        if !runnable || queue::is_shutting_down() || QUEUE_RUNNER.swap(true, Ordering::SeqCst) {
+            break;
+        }
+    });
+}
+
+/**
+ * 依次运行队列中的任务；其它会话正在运行时等待其结束
+ * 
+ * @param app - 应用句柄
+ */
+fn run_queue(app: &AppHandle) -> Result<(), String> {
+    loop {
+        if queue::is_shutting_down() {
+            return Ok(());
+        }
+        if generation_running() {
+            std::thread::sleep(QUEUE_POLL_INTERVAL);
+            continue;
+        }
+        let profile_dir = profiles::profile_data_dir(&active_profile()?.name)?;
+        let Some(job) = queue::start_next(&profile_dir)? else {
+            if let Some(summary) = queue::complete(&profile_dir)? {
+                let _ = app.emit("generation-queue-complete", summary);
+            }
+            return Ok(());
+        };
+        let _ = app.emit("queue-job-started", job.clone());
+        
+        queue::begin_job(&job.id);
+        let result = run_generation(app.clone(), job.params, None, None, OutputSecrets::default(), None).map(|result| result.wallet.address).map_err(|e| e.to_string());
+        let run = queue::take_run(&job.id);
+        let finished = queue::finish(&profile_dir, &job.id, result, run)?;
+        let _ = app.emit("queue-job-finished", finished);
+    }
+}
+
+/**
+ * 启动空闲检测线程：系统空闲达到阈值时开始或恢复常驻搜索，检测到用户活动时自动暂停
+ * 
+ * @param app - 应用句柄
+ * @param source - 系统空闲时间来源
+ */
+fn spawn_idle_watcher(app: AppHandle, source: impl idle::IdleSource + 'static) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(IDLE_POLL_INTERVAL);
+        
+        let Ok(search) = active_profile()
+            .and_then(|profile| profiles::profile_data_dir(&profile.name))
+            .and_then(|dir| idle::load_standing_search(&dir))
+        else {
+            continue;
+        };
+        if !search.enabled {
+            continue;
+        }
+        let Some(idle_time) = source.idle_time() else {
+            continue;
+        };
+        
+        // 只自动暂停和恢复空闲检测启动的会话，用户开始的其它会话不受影响
+        let auto_session = auto_started_session();
+        let action = idle::decide(
+            idle_time,
+            std::time::Duration::from_secs(search.idle_minutes * 60),
+            generation_running(),
+            auto_session.is_some(),
+            auto_session.as_deref().map(sessions::pause_state).unwrap_or_default(),
+        );
+        let transition = IdleTransition { pattern: search.pattern.clone(), idle_secs: idle_time.as_secs() };
+        let update_auto_session = |request| {
+            auto_session.as_deref().is_some_and(|session_id| sessions::update_pause(Some(session_id), request).is_ok_and(|changed| changed > 0))
+        };
+        let event = match action {
+            Some(idle::IdleAction::Start) => {
+                let session_id = format!("{}_{}", pattern::file_label(&search.pattern), chrono::Utc::now().format("%Y%m%d_%H%M%S%3fZ"));
+                if let Ok(mut auto_started) = AUTO_STARTED_SESSION.lock() {
+                    *auto_started = Some(session_id.clone());
+                }
+                // 空闲自动开始由后端发起，不受开始生成的频率限制
+                let params = GenerationParams { pattern: search.pattern, save_path: search.save_path, ..GenerationParams::default() };
+                let app = app.clone();
+                tauri::async_runtime::spawn(spawn_generation(app, params, None, Some(session_id), OutputSecrets::default(), None));
+                Some("idle-auto-start")
+            }
+            Some(idle::IdleAction::Resume) => update_auto_session(PauseRequest::ResumeAutomatically).then_some("idle-auto-resume"),
+            Some(idle::IdleAction::Pause) => update_auto_session(PauseRequest::PauseAutomatically).then_some("idle-auto-pause"),
+            None => None,
+        };
+        if let Some(event) = event {
+            let _ = app.emit(event, transition);
+        }
+    });
+}
+
+/**
+ * 检查输出目录中可能引起问题的文件（只读）
+ * 
+ * @param path - 保存路径（可选，与生成时的 save_path 含义相同）
+ */
+#[tauri::command]
+fn check_output_directory(path: Option<String>) -> Result<output_check::DirectoryReport, WalletError> {
+    Ok(output_check::check_directory(&resolve_wallets_dir(path)?))
+}
+
+/**
+ * 检查保存路径（设置界面调用）：必须是绝对路径、不能包含 ..、必须已经存在并且是目录；
+ * 通过时创建其中的 FancyWallets 目录，并写入、删除一个临时文件确认可以写入
+ * 
+ * @param path - 保存路径（可选，与生成时的 save_path 含义相同；为空时检查当前用户配置的默认目录）
+ * @returns 解析后的 FancyWallets 目录和来源；失败时返回 save_failed（带路径和系统错误的类别）或说明路径规则的错误
+ */
+#[tauri::command]
+fn validate_save_path(path: Option<String>) -> Result<OutputRoot, WalletError> {
+    preflight_output_root(path)
+}
+
+/**
+ * 在系统文件管理器中打开会话的输出文件夹，并选中会话的结果文件（文件已经写入时）
+ * 
+ * 文件夹是会话开始时解析的输出位置（按会话的 save_path、链和集合，与写入结果文件的目录相同）；
+ * 还没有开始过会话时打开按应用设置的默认保存路径解析的 FancyWallets 目录。文件夹不存在时先创建。
+ * 
+ * @param session_id - 会话标识（可选，默认最近开始的会话）
+ * @returns 打开的文件夹和选中的结果文件；无法解析或创建文件夹时返回错误（创建失败为 save_failed，带路径和系统错误的类别）
+ */
+#[tauri::command]
+fn open_output_folder(app: AppHandle, session_id: Option<String>) -> Result<OutputFolder, WalletError> {
+    let folder = match output_folder::find(session_id.as_deref())? {
+        Some(folder) => folder,
+        None => OutputFolder { session_id: None, folder: resolve_wallets_dir(app_settings()?.default_save_path)?, selected: None },
+    };
+    std::fs::create_dir_all(&folder.folder).map_err(|e| WalletError::save_failed(&folder.folder, &e))?;
+    let folder = OutputFolder { selected: folder.selected.filter(|file| file.is_file()), ..folder };
+    let opened = match &folder.selected {
+        Some(file) => app.opener().reveal_item_in_dir(file),
+        None => app.opener().open_path(folder.folder.to_string_lossy(), None::<&str>),
+    };
+    opened.map_err(|e| format!("无法打开文件夹 {}: {}", folder.folder.display(), e))?;
+    Ok(folder)
+}
+
+/**
+ * 清理输出目录中遗留的临时文件（需要用户显式确认后调用）
+ * 
+ * @param path - 保存路径（可选，与生成时的 save_path 含义相同）
+ * @returns 删除的文件列表
+ */
+#[tauri::command]
+fn clean_output_directory_temp_files(path: Option<String>) -> Result<Vec<String>, WalletError> {
+    if generation_running() {
+        return Err("生成任务正在运行，无法清理临时文件".into());
+    }
+    output_check::remove_orphaned_temp_files(&resolve_wallets_dir(path)?).map_err(WalletError::from)
+}
+
+/**
+ * 列出保存目录中有结果的会话
+ * 
+ * @param save_path - 保存路径（可选）
+ */
+#[tauri::command]
+fn list_result_sessions(save_path: Option<String>) -> Result<Vec<String>, WalletError> {
+    let wallets_dir = resolve_wallets_dir(save_path)?;
+    if !wallets_dir.exists() {
+        return Ok(Vec::new());
+    }
+    saved_wallets::list_sessions(&wallets_dir).map_err(WalletError::from)
+}
+
+/**
+ * 列出保存目录中的结果文件及其状态（正在运行的会话的文件标记为 active_session）
+ * 
+ * @param save_path - 保存路径（可选）
+ * @param chain - 只列出该链的文件（可选，例如 ethereum）
+ */
+#[tauri::command]
+fn list_saved_wallets(
+    save_path: Option<String>,
+    chain: Option<String>,
+) -> Result<Vec<saved_wallets::WalletFileSummary>, WalletError> {
+    let chain = chain.as_deref().map(Chain::parse).transpose()?;
+    let wallets_dir = resolve_wallets_dir(save_path)?;
+    if !wallets_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut files = saved_wallets::list_files(&wallets_dir, chain)?;
+    let notes = notes::load(&profiles::profile_data_dir(&active_profile()?.name)?)?;
+    for file in &mut files {
+        file.note = notes.get(&file.session_id).map(|note| note.text.clone());
+    }
+    Ok(files)
+}
+
+/**
+ * 列出以前生成的全部钱包（读取保存目录中的每个 CSV 和 JSON Lines 结果文件），用于历史记录
+ * 
+ * 无法解析的行被跳过并在 skipped_rows 中计数，不会因为手动编辑过的旧文件而返回错误。
+ * 
+ * @param save_path - 保存路径（可选）
+ * @param include_keys - 是否带有私钥（可选，默认 false）
+ */
+#[tauri::command]
+fn list_generated_wallets(save_path: Option<String>, include_keys: Option<bool>) -> Result<saved_wallets::GeneratedWallets, WalletError> {
+    let wallets_dir = resolve_wallets_dir(save_path)?;
+    if !wallets_dir.exists() {
+        return Ok(saved_wallets::GeneratedWallets::default());
+    }
+    saved_wallets::list_generated(&wallets_dir, include_keys.unwrap_or_default()).map_err(WalletError::from)
+}
+
+/**
+ * 分页查询结果数据库 wallets.db 中的钱包（只有 results_db 会话写入的钱包，最新的在前），用于历史记录
+ * 
+ * @param pattern_filter - 只返回模式列包含这段文字的钱包（可选）
+ * @param limit - 每页条数（不超过 1000）
+ * @param offset - 跳过的条数
+ * @param include_keys - 是否带有私钥（可选，默认 false）
+ * @param save_path - 保存路径（可选）
+ */
+#[tauri::command]
+fn query_wallets(
+    pattern_filter: Option<String>,
+    limit: usize,
+    offset: u64,
+    include_keys: Option<bool>,
+    save_path: Option<String>,
+) -> Result<Vec<results_db::StoredWallet>, WalletError> {
+    let path = resolve_wallets_dir(save_path)?.join(results_db::DB_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    results_db::query_wallets(&path, pattern_filter.as_deref(), limit, offset, include_keys.unwrap_or_default()).map_err(WalletError::from)
+}
+
+/**
+ * 统计结果数据库 wallets.db 中的钱包数（与 query_wallets 一起分页）
+ * 
+ * @param pattern_filter - 只统计模式列包含这段文字的钱包（可选）
+ * @param save_path - 保存路径（可选）
+ */
+#[tauri::command]
+fn count_wallets(pattern_filter: Option<String>, save_path: Option<String>) -> Result<u64, WalletError> {
+    let path = resolve_wallets_dir(save_path)?.join(results_db::DB_FILE);
+    if !path.exists() {
+        return Ok(0);
+    }
+    results_db::count_wallets(&path, pattern_filter.as_deref()).map_err(WalletError::from)
+}
+
+/**
+ * 把旧 CSV 格式的集合迁移为当前格式（原文件保留备份），之后才能继续追加
+ * 
+ * @param name - 集合名称
+ * @param save_path - 保存路径（可选）
+ */
+#[tauri::command]
+fn migrate_collection(name: String, save_path: Option<String>) -> Result<collections::CollectionMigration, WalletError> {
+    let path = collections::collection_path(&resolve_wallets_dir(save_path)?, &name)?;
+    if !path.exists() {
+        return Err(format!("集合不存在: {}", name).into());
+    }
+    collections::migrate(&path, &name).map_err(WalletError::from)
+}
+
+/**
+ * 把旧版本写入的 CSV 结果文件（例如只有 address,private_key,pattern 三列、地址为小写的文件）改写为当前格式
+ * 
+ * 每条记录的地址由私钥重新推导为规范格式；记录的地址与推导出的不一致说明文件可能已损坏，
+ * 这些行按原文保留并在结果中列出。原文件改名为 <文件名>.v<版本>.bak，从不删除。
+ * 
+ * @param dir - 保存路径（可选，与生成时的 save_path 相同）
+ */
+#[tauri::command]
+fn migrate_wallet_files(dir: Option<String>) -> Result<file_migration::WalletFilesMigration, WalletError> {
+    let wallets_dir = resolve_wallets_dir(dir)?;
+    if !wallets_dir.exists() {
+        return Ok(file_migration::WalletFilesMigration::default());
+    }
+    file_migration::migrate_dir(&wallets_dir).map_err(WalletError::from)
+}
+
+/**
+ * 设置会话备注（内容为空白时删除）
+ * 
+ * 备注保存在当前用户配置的数据目录中，结果文件被移走后仍然可以编辑；
+ * 会话清单还在时同时原子地更新清单中的备注。
+ * 
+ * @param session_id - 会话标识
+ * @param text - 备注内容（最多 8 KB）
+ * @param save_path - 保存路径（可选，用于查找会话清单）
+ */
+#[tauri::command]
+fn set_session_note(session_id: String, text: String, save_path: Option<String>) -> Result<notes::NoteUpdate, WalletError> {
+    ensure_data_writable()?;
+    let note = notes::set(&profiles::profile_data_dir(&active_profile()?.name)?, &session_id, &text)?;
+    
+    // 清单只是备注的副本：找不到或无法更新时不影响已保存的备注
+    let manifests = resolve_wallets_dir(save_path)
+        .map(|dir| manifest::find_session_manifests(&dir, &session_id))
+        .unwrap_or_default();
+    let text = note.as_ref().map(|n| n.text.as_str());
+    let failed = manifests.iter().filter(|path| manifest::set_note(path, text).is_err()).count();
+    let summary_updated = !manifests.is_empty() && failed == 0;
+    Ok(notes::NoteUpdate { session_id, note, summary_updated })
+}
+
+/**
+ * 校验结果文件：逐行由私钥重新推导地址并比对
+ * 
+ * 正在写入的文件只校验已完整写入的行，末尾的半行不会被报告为损坏。
+ * 
+ * @param path - 结果文件路径
+ */
+#[tauri::command]
+fn verify_wallet_file(path: String) -> Result<saved_wallets::WalletFileReport, WalletError> {
+    saved_wallets::verify_file(&PathBuf::from(path)).map_err(WalletError::from)
+}
+
+/**
+ * 用密码解密加密的结果文件（wallet_*.csv.enc），返回其中的钱包记录
+ * 
+ * 写入中断留下的最后半行被忽略（truncated 为 true），其它任何一行无法解密时返回错误。
+ * 
+ * @param path - 加密的结果文件路径
+ * @param password - 加密时使用的密码
+ */
+#[tauri::command]
+fn decrypt_wallet_file(path: String, password: String) -> Result<saved_wallets::DecryptedWalletFile, WalletError> {
+    let path = PathBuf::from(path);
+    let decrypted = encrypted_csv::decrypt_file(&path, &password)?;
+    let layout = CsvPrivateKeys::from_header(&decrypted.header).ok_or("解密后的标题不是钱包结果文件的标题")?;
+    Ok(saved_wallets::DecryptedWalletFile { wallets: saved_wallets::parse_rows(&decrypted.rows, &path, layout), truncated: decrypted.truncated })
+}
+
+/**
+ * 分页获取会话结果（流式读取结果文件，适合数万条以上的结果）
+ * 
+ * @param session_id - 会话标识（见 list_result_sessions）
+ * @param cursor - 上一页返回的游标（为空时从第一页开始）
+ * @param limit - 每页条数
+ * @param sort - 排序方式（默认按序号）
+ * @param save_path - 保存路径（可选）
+ */
+#[tauri::command]
+fn get_results_page(
+    session_id: String,
+    cursor: Option<String>,
+    limit: usize,
+    sort: Option<saved_wallets::ResultsSort>,
+    save_path: Option<String>,
+) -> Result<saved_wallets::ResultsPage, WalletError> {
+    let file = saved_wallets::session_file(&resolve_wallets_dir(save_path)?, &session_id)?;
+    saved_wallets::read_page(
+        &file,
+        cursor.as_deref(),
+        limit,
+        sort.unwrap_or(saved_wallets::ResultsSort::Index),
+    ).map_err(WalletError::from)
+}
+
+/**
+ * 把结果导出为 Excel 文件（.xlsx）：每个模式一个工作表，地址和私钥是文本单元格（Excel 不会把它们转换为科学计数法或去掉前导零），
+ * 标题行冻结；从已保存的结果文件读取，不需要正在运行的会话
+ * 
+ * @param files - 要导出的 CSV 或 JSON Lines 结果文件（与 session_id 二选一）
+ * @param session_id - 要导出的会话（见 list_result_sessions；与 files 二选一）
+ * @param dest_path - 输出文件的绝对路径（扩展名为 .xlsx，已存在时覆盖）
+ * @param save_path - 保存路径（可选，按会话标识查找结果文件时使用）
+ * @returns 导出和跳过的行数，以及各工作表
+ */
+#[tauri::command]
+fn export_wallets_xlsx(
+    files: Option<Vec<PathBuf>>,
+    session_id: Option<String>,
+    dest_path: String,
+    save_path: Option<String>,
+) -> Result<xlsx_export::XlsxExport, WalletError> {
+    let sources = match (files, session_id) {
+        (Some(files), None) if !files.is_empty() => files,
+        (None, Some(session_id)) => xlsx_export::session_sources(&resolve_wallets_dir(save_path)?, &session_id)?,
+        _ => return Err("files 和 session_id 必须且只能提供一个".into()),
+    };
+    xlsx_export::export(&sources, &PathBuf::from(dest_path)).map_err(WalletError::from)
+}
+
+/**
+ * 把一个会话的全部输出文件（结果文件、keystore、Solana 密钥对、地址二维码、清单和回放文件）写入 AES-256 加密的 ZIP 归档，
+ * 写完后用同一个密码读回并逐个比对；可选在校验通过后安全删除原文件（任何一步失败都不删除）
+ * 
+ * @param session_id - 会话标识（见 list_result_sessions）
+ * @param password - 归档密码
+ * @param dest_path - 归档文件的绝对路径（扩展名为 .zip，不能位于钱包目录中，已存在时覆盖）
+ * @param delete_originals - 校验通过后是否用随机数据覆盖并删除原文件（可选，默认 false；会话仍在写入时拒绝）
+ * @param save_path - 保存路径（可选）
+ * @returns 归档路径和归档中的文件清单（路径、大小和 SHA-256）
+ */
+#[tauri::command]
+fn export_session_archive(
+    session_id: String,
+    password: String,
+    dest_path: String,
+    delete_originals: Option<bool>,
+    save_path: Option<String>,
+) -> Result<session_archive::SessionArchive, WalletError> {
+    let password = Zeroizing::new(password);
+    let wallets_dir = resolve_wallets_dir(save_path)?;
+    let files = session_archive::session_files(&wallets_dir, &session_id)?;
+    session_archive::create(&wallets_dir, &files, &password, &PathBuf::from(dest_path), delete_originals.unwrap_or_default()).map_err(WalletError::from)
+}
+
+/**
+ * 安全删除钱包目录中的一个输出文件：用随机数据覆盖内容并写入磁盘后删除
+ * 
+ * 固态硬盘和写时复制的文件系统不能保证覆盖到原来的数据块，结果中的 overwrite_guaranteed 和 caveat 说明这一点，界面应如实显示。
+ * 
+ * @param path - 文件路径（必须位于 FancyWallets 目录中，正被会话写入的结果文件拒绝删除）
+ * @param save_path - 保存路径（可选）
+ * @returns 删除的文件、覆盖的字节数以及能否确认覆盖
+ */
+#[tauri::command]
+fn secure_delete_wallet_file(path: String, save_path: Option<String>) -> Result<secure_delete::SecureDeleteResult, WalletError> {
+    let wallets_dir = resolve_wallets_dir(save_path)?;
+    secure_delete::delete_file(&PathBuf::from(path), &wallets_dir).map_err(WalletError::from)
+}
+
+/**
+ * 安全删除一个会话的全部输出文件（与 export_session_archive 归档的文件相同：结果文件、keystore、Solana 密钥对、
+ * 地址二维码、清单及清单中记录的其它产物）；某个文件删除失败时继续删除其余文件
+ * 
+ * @param session_id - 会话标识（见 list_result_sessions）
+ * @param save_path - 保存路径（可选）
+ * @returns 各文件的删除结果和无法删除的文件
+ */
+#[tauri::command]
+fn secure_delete_session(session_id: String, save_path: Option<String>) -> Result<secure_delete::SessionSecureDelete, WalletError> {
+    let wallets_dir = resolve_wallets_dir(save_path)?;
+    let files = session_archive::session_files(&wallets_dir, &session_id)?;
+    secure_delete::delete_session(&session_id, &files, &wallets_dir).map_err(WalletError::from)
+}
+
+/**
+ * 将会话结果加密导出给收件人的 OpenPGP 公钥（解密后为 JSON）
+ * 
+ * @param session_id - 会话标识（见 list_result_sessions）
+ * @param recipient_key - 收件人公钥文件路径或 ASCII armor 文本
+ * @param output_path - 输出文件路径（建议使用 .asc 扩展名）
+ * @param signing_key - 本地签名私钥文件路径或 ASCII armor 文本（可选）
+ * @param signing_passphrase - 签名私钥的口令（可选）
+ * @param save_path - 保存路径（可选）
+ * @returns 导出结果
+ */
+#[tauri::command]
+fn export_encrypted_to_recipient(
+    session_id: String,
+    recipient_key: String,
+    output_path: String,
+    signing_key: Option<String>,
+    signing_passphrase: Option<String>,
+    save_path: Option<String>,
+) -> Result<pgp_export::EncryptedExport, WalletError> {
+    let recipient = pgp_export::parse_recipient_key(&recipient_key)?;
+    let signer = signing_key.as_deref().map(pgp_export::parse_signing_key).transpose()?;
+    
+    let file = saved_wallets::session_file(&resolve_wallets_dir(save_path)?, &session_id)?;
+    if !file.exists() {
+        return Err(format!("会话不存在: {}", session_id).into());
+    }
+    let wallets = saved_wallets::read_all(&file)?;
+    if wallets.is_empty() {
+        return Err("该会话没有可导出的结果".into());
+    }
+    let note = notes::load(&profiles::profile_data_dir(&active_profile()?.name)?)?
+        .remove(&session_id)
+        .map(|note| note.text);
+    let results = pgp_export::ConsolidatedResults::new(&session_id, wallets, note);
+    
+    let passphrase = signing_passphrase.unwrap_or_default();
+    pgp_export::export(
+        &results,
+        &recipient,
+        signer.as_ref().map(|key| (key, passphrase.as_str())),
+        &PathBuf::from(output_path),
+    ).map_err(WalletError::from)
+}
+
+/**
+ * 估计正在运行的会话还需要多久才能以目标置信度找到至少一个匹配
+ * 
+ * 根据模式的匹配概率求解 1 - (1 - p)^n = confidence，并按实时测得的速度换算为时间。
+ * 
+ * @param session_id - 会话标识
+ * @param confidence - 目标置信度（例如 0.9 表示 90%）
+ * @returns 所需尝试次数、剩余时间和当前已达到的置信度
+ */
+#[tauri::command]
+fn estimate_time_to_confidence(session_id: String, confidence: f64) -> Result<ConfidenceEstimate, WalletError> {
+    confidence::estimate_for_session(&session_id, confidence).map_err(WalletError::from)
+}
+
+/// 开始前的难度、预计时间和磁盘占用估计
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GenerationEstimate {
+    /// 单次尝试匹配的概率
+    pub probability: f64,
+    /// 找到一个匹配的期望尝试次数
+    pub expected_attempts: f64,
+    /// 最近一次校准测得的速度（每秒尝试次数，没有校准记录时为空；校准只测以太坊，其它链为快速测速的结果）
+    pub rate: Option<f64>,
+    /// 按该速度找到一个匹配的期望时间（毫秒，没有速度时为空）
+    pub expected_ms: Option<f64>,
+    /// 按停止条件和输出选项估计的磁盘占用
+    pub disk: DiskUsageEstimate,
+}
+
+/**
+ * 开始前估计生成的难度、预计时间和磁盘占用
+ * 
+ * 匹配数按停止条件估计（最大匹配数、最大尝试次数和最长运行时间，运行时间按最近一次校准的速度换算，以太坊以外的链按快速测速的速度换算），
+ * 每个匹配的大小由结果文件和回放文件的写入方按当前格式计算；预计占用超过目标卷可用空间的一半时附带警告。
+ * 
+ * @param params - 生成参数
+ */
+#[tauri::command]
+fn estimate_generation(params: GenerationParams) -> Result<GenerationEstimate, WalletError> {
+    params.check_patterns()?;
+    params.validate()?;
+    let parsed_patterns = params.parsed_patterns();
+    let (difficulty_bits, matcher) = session_matcher(&params, &parsed_patterns);
+    let probability = (-difficulty_bits).exp2();
+    let expected_attempts = difficulty_bits.exp2().min(f64::MAX);
+    let rng_mode = params.rng_mode.unwrap_or_default();
+    let mnemonic_words = (params.key_source == Some(KeySourceKind::Mnemonic))
+        .then(|| params.mnemonic_words.unwrap_or(mnemonic::DEFAULT_MNEMONIC_WORDS));
+    // 校准记录的是单个工作线程推导以太坊地址的速度，其它链推导地址的速度不同，按该链快速测速
+    let chain = params.chain()?;
+    let workers = SearchConfig { rng_mode, workers: params.workers, ..SearchConfig::new(params.pattern.as_str()) }.worker_count();
+    let rate = match chain {
+        Chain::Ethereum => calibration::load(&profiles::app_data_dir()?)
+            .ok()
+            .and_then(|store| store.history.last().map(|calibration| calibration.hashrate * workers as f64)),
+        _ => calibration::quick_benchmark(chain, Some(workers)).ok(),
+    }
+    .filter(|&rate| rate > 0.0);
+    
+    let entropy = EntropyProvenance::new(rng_mode, params.test_seed);
+    let details = manifest::SessionDetails {
+        template: None,
+        chain,
+        matcher: Some(&matcher),
+        score_weights: params.score_weights.as_ref(),
+        collection: params.collection.as_deref(),
+        keccak_backend: Some(keccak::backend()),
+        entropy: Some(&entropy),
+        clock_jumps: &[],
+        worker_failures: &[],
+    };
+    // 每个模式写入各自的文件（合并时只有一个文件）：每个文件一个标题行，每行按最长的模式估计（score 模式的模式列按最长的分数估计）
+    let patterns = params.patterns();
+    let row_patterns = match params.score_function {
+        Some(function) => vec![scoring::score_label(function, 100.0)],
+        None => patterns.clone(),
+    };
+    let private_keys = params.csv_private_keys.unwrap_or_default();
+    let format = OutputFormat::parse(params.output_format.as_deref())?;
+    let files = match params.output_layout.unwrap_or_default() {
+        OutputLayout::PerPattern => patterns.len() as u64,
+        OutputLayout::Combined => 1,
+    };
+    let max_row = |row_bytes: &dyn Fn(&str) -> u64| row_patterns.iter().map(|pattern| row_bytes(pattern)).max().unwrap_or_default();
+    let sizes = OutputSizes {
+        csv_header: if format.writes_csv() { (private_keys.header().len() as u64 + 1) * files } else { 0 },
+        csv_row: if format.writes_csv() { max_row(&|pattern| csv_row_bytes(pattern, chain, rng_mode, mnemonic_words, private_keys)) } else { 0 },
+        jsonl_row: if format.writes_jsonl() { max_row(&|pattern| jsonl_row_bytes(pattern, chain, rng_mode, mnemonic_words)) } else { 0 },
+        replay_per_match: replay::bytes_per_match(),
+        session_overhead: replay::session_overhead_bytes(&params.pattern, chain) + manifest::estimated_size(&params.pattern, &details),
+    };
+    let wallets_dir = resolve_wallets_dir(params.save_path.clone())?;
+    let disk = disk_usage::estimate(&params.limits, probability, rate, sizes, disk_usage::free_space(&wallets_dir));
+    
+    Ok(GenerationEstimate {
+        probability,
+        expected_attempts,
+        rate,
+        expected_ms: rate.map(|rate| expected_attempts / rate * 1000.0),
+        disk,
+    })
+}
+
+/**
+ * 按当前语言格式化数量、时长、速度或概率（与后端生成的文字保持一致）
+ * 
+ * @param kind - 数值类型
+ * @param value - 数值（时长以毫秒为单位，概率以期望尝试次数表示）
+ * @param locale - 语言标签（默认 zh-CN）
+ */
+#[tauri::command]
+fn format_quantity(kind: formatting::QuantityKind, value: f64, locale: Option<String>) -> String {
+    formatting::format_quantity(kind, value, locale.as_deref().unwrap_or(formatting::DEFAULT_LOCALE))
+}
+
+/**
+ * 把保存的时间（UTC）转换为本机时区的本地时间显示
+ * 
+ * @param value - 保存的时间（RFC 3339）
+ * @param locale - 语言标签（可选，默认 zh-CN）
+ */
+#[tauri::command]
+fn format_timestamp(value: String, locale: Option<String>) -> String {
+    let locale = formatting::NumberLocale::from_tag(locale.as_deref().unwrap_or(formatting::DEFAULT_LOCALE));
+    formatting::format_timestamp(&value, locale)
+}
+
+/**
+ * 重新订阅正在运行的会话：恢复完整的进度事件，并在下一次进度更新时发送追赶快照
+ * 
+ * @param session_id - 会话标识
+ */
+#[tauri::command]
+fn resubscribe(session_id: String) -> Result<(), WalletError> {
+    events::resubscribe(&session_id).map_err(WalletError::from)
+}
+
+/// 速度的来源
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RateSource {
+    /// 调用方给出的每秒尝试次数
+    Supplied,
+    /// 内置的快速测速
+    Benchmark,
+}
+
+/// 单个模式的难度估计
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PatternDifficulty {
+    /// 引擎实际执行的匹配条件
+    pub matcher: String,
+    /// 解析器对模式的解读
+    pub interpretation: String,
+    /// 难度（期望尝试次数的以 2 为底的对数）
+    pub difficulty_bits: f64,
+    /// 单次尝试匹配的概率
+    pub probability: f64,
+    /// 找到一个匹配的期望尝试次数
+    pub expected_attempts: f64,
+    /// 尝试次数预算（未给出时为空）
+    pub attempt_budget: Option<u64>,
+    /// 在预算内至少找到一个匹配的概率（未给出预算时为空）
+    pub success_probability: Option<f64>,
+    /// 使用的速度（每秒尝试次数）
+    pub rate: f64,
+    /// 速度的来源
+    pub rate_source: RateSource,
+    /// 按该速度找到一个匹配的期望时间（毫秒）
+    pub expected_ms: f64,
+    /// 按该速度用完尝试次数预算的时间（毫秒，未给出预算时为空）
+    pub budget_ms: Option<f64>,
+}
+
+/**
+ * 按给定的速度估计模式的难度（非十六进制字符等无效的模式返回错误，而不是无穷大）
+ * 
+ * @param parsed - 解析后的模式
+ * @param attempt_budget - 尝试次数预算（可选）
+ * @param rate - 速度（每秒尝试次数）及其来源
+ */
+pub(crate) fn pattern_difficulty(
+    parsed: &ParsedPattern,
+    attempt_budget: Option<u64>,
+    (rate, rate_source): (f64, RateSource),
+) -> Result<PatternDifficulty, String> {
+    parsed.validate()?;
+    if !(rate.is_finite() && rate > 0.0) {
+        return Err(format!("每秒尝试次数必须是正数（当前为 {}）", rate));
+    }
+    let probability = parsed.probability();
+    let expected_attempts = parsed.expected_attempts();
+    Ok(PatternDifficulty {
+        matcher: parsed.matcher().to_string(),
+        interpretation: parsed.interpretation(),
+        difficulty_bits: parsed.difficulty_bits(),
+        probability,
+        expected_attempts,
+        attempt_budget,
+        success_probability: attempt_budget.and_then(|budget| confidence::cumulative_success(Some(probability), budget)),
+        rate,
+        rate_source,
+        expected_ms: (expected_attempts / rate * 1000.0).min(f64::MAX),
+        budget_ms: attempt_budget.map(|budget| budget as f64 / rate * 1000.0),
+    })
+}
+
+/**
+ * 开始前估计模式的难度：期望尝试次数（十六进制每个字符 16 倍，区分大小写的字母 32 倍，包含和重复形式按各自的组合计算）、
+ * 在尝试次数预算内成功的概率，以及按给出的速度或内置快速测速换算的时间
+ * 
+ * @param pattern - 靓号模式
+ * @param case_sensitive - 是否区分大小写（可选，默认 false）
+ * @param case_mode - 大小写方式（可选，含义与 generate_fancy_wallet 相同；exact 时每个字母按 32 倍计算）
+ * @param match_mode - 比较方式（可选，默认按模式语法决定）
+ * @param attempt_budget - 尝试次数预算（可选）
+ * @param keys_per_second - 每秒尝试次数（可选，为空时按该链运行约 2 万次尝试的快速测速）
+ * @param chain - 生成的链（可选，默认 ethereum）；决定模式可以使用的字符、地址长度和测速的流程
+ * @param bitcoin_address - 比特币地址的格式（可选，默认 p2pkh）
+ */
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn estimate_pattern_difficulty(
+    pattern: String,
+    case_sensitive: Option<bool>,
+    case_mode: Option<CaseMode>,
+    match_mode: Option<MatchMode>,
+    attempt_budget: Option<u64>,
+    keys_per_second: Option<f64>,
+    chain: Option<Chain>,
+    bitcoin_address: Option<BitcoinAddressType>,
+) -> Result<PatternDifficulty, WalletError> {
+    let chain = chain.unwrap_or_default();
+    let parsed = ParsedPattern::with_case(pattern.trim(), chain.case_sensitive(CaseMode::resolve(case_mode, case_sensitive)?))
+        .with_match_mode(match_mode)
+        .with_alphabet(chain.alphabet(bitcoin_address.unwrap_or_default()));
+    // 模式无效时不运行测速
+    parsed.validate().map_err(|reason| WalletError::invalid_pattern(&parsed, reason))?;
+    let rate = match keys_per_second {
+        Some(rate) => (rate, RateSource::Supplied),
+        None => (calibration::quick_benchmark(chain, None)?, RateSource::Benchmark),
+    };
+    pattern_difficulty(&parsed, attempt_budget, rate).map_err(WalletError::from)
+}
+
+/**
+ * 开始前预检模式：是否有效、规范化的形式、识别出的匹配规则、全部无效字符的位置和难度等级
+ * 
+ * 与 generate_fancy_wallet 开始前的校验使用同一个解析器和 validate，预检通过的模式不会在开始时被拒绝。
+ * 
+ * @param pattern - 靓号模式（与生成时一样去掉首尾空白和 0x 前缀后比较）
+ * @param match_mode - 比较方式（可选，默认按模式语法决定）
+ * @param case_sensitive - 是否区分大小写（可选，默认 false）
+ * @param case_mode - 大小写方式（可选，含义与 generate_fancy_wallet 相同；与 case_sensitive 矛盾时预检无效；
+ *                    区分大小写的模式只有数字时在 warnings 中提示）
+ * @param chain - 生成的链（可选，默认 ethereum；tron、solana 和 bitcoin 的 P2PKH 地址按 Base58 字符校验，solana 总是区分大小写）
+ * @param bitcoin_address - 比特币地址的格式（可选，默认 p2pkh；p2wpkh 按 Bech32 字符校验）
+ */
+#[tauri::command]
+pub(crate) fn validate_pattern(
+    pattern: String,
+    match_mode: Option<MatchMode>,
+    case_sensitive: Option<bool>,
+    case_mode: Option<CaseMode>,
+    chain: Option<Chain>,
+    bitcoin_address: Option<BitcoinAddressType>,
+) -> pattern::PatternValidation {
+    let chain = chain.unwrap_or_default();
+    let resolved = CaseMode::resolve(case_mode, case_sensitive);
+    let mut validation = ParsedPattern::with_options(&pattern, PatternType::Standard, chain.case_sensitive(resolved.clone().unwrap_or_default()))
+        .with_match_mode(match_mode)
+        .with_alphabet(chain.alphabet(bitcoin_address.unwrap_or_default()))
+        .check();
+    if let Err(conflict) = resolved {
+        validation.valid = false;
+        validation.error = Some(conflict);
+    }
+    validation
+}
+
+/**
+ * 预览模式的匹配结果：示例地址是否满足模式，以及满足的字符区间（用于界面高亮）
+ * 
+ * 与生成使用同一个解析和匹配流程，预览匹配的地址在生成时也会匹配。
+ * 
+ * @param address - 示例地址（生成的链的文本格式，以太坊地址可以是任意大小写）
+ * @param pattern - 靓号模式
+ * @param options - 匹配设置（可选：pattern_type、case_sensitive、case_mode、match_mode、chain 和 bitcoin_address，含义与 generate_fancy_wallet 相同）
+ * @returns 是否匹配、规范格式的地址和匹配的字符区间
+ */
+#[tauri::command]
+fn check_pattern_match(address: String, pattern: String, options: Option<match_preview::PatternMatchOptions>) -> Result<match_preview::PatternMatchPreview, WalletError> {
+    match_preview::check(&address, &pattern, &options.unwrap_or_default()).map_err(WalletError::from)
+}
+
+/**
+ * 获取模式语法说明（示例由解析器实时解读并估算难度）
+ */
+#[tauri::command]
+fn describe_pattern_syntax() -> Vec<pattern::PatternSyntaxEntry> {
+    pattern::describe_syntax()
+}
+
+/**
+ * 列出内置的模式预设（难度由解析器实时估算，与 generate_fancy_wallet 使用 preset_id 时的匹配规则一致）
+ */
+#[tauri::command]
+fn list_pattern_presets() -> Vec<presets::PresetInfo> {
+    presets::list()
+}
+
+/// 应用信息
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AppInfo {
+    /// 应用版本
+    pub version: String,
+    /// 当前使用的用户配置
+    pub active_profile: String,
+    /// 应用数据目录
+    pub app_data_dir: String,
+    /// 应用数据是否处于只读模式（启动迁移失败）
+    pub read_only: bool,
+    /// 使用的 Keccak-256 实现和检测到的 CPU 特性
+    pub keccak: KeccakSelection,
+}
+
+/**
+ * 获取应用信息（版本和当前用户配置）
+ */
+#[tauri::command]
+fn get_app_info() -> Result<AppInfo, WalletError> {
+    Ok(AppInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        active_profile: active_profile()?.name,
+        app_data_dir: profiles::app_data_dir()?.to_string_lossy().to_string(),
+        read_only: migration_report().read_only(),
+        keccak: keccak::selection(),
+    })
+}
+
+/**
+ * 获取根据本机校准结果推荐的默认设置（尚未校准时为空）
+ */
+#[tauri::command]
+fn get_recommended_defaults() -> Result<Option<calibration::RecommendedDefaults>, WalletError> {
+    Ok(calibration::load(&profiles::app_data_dir()?)?.recommended().cloned())
+}
+
+/**
+ * 获取最近几次的校准结果（最近的在最后）
+ */
+#[tauri::command]
+fn get_calibration_history() -> Result<calibration::CalibrationStore, WalletError> {
+    calibration::load(&profiles::app_data_dir()?).map_err(WalletError::from)
+}
+
+/**
+ * 重新运行校准（试运行生成流程测速，不写入任何结果文件），可以通过 cancel_generation 提前结束
+ * 
+ * @returns 校准结果
+ */
+#[tauri::command]
+async fn run_calibration() -> Result<calibration::Calibration, WalletError> {
+    if generation_running() {
+        return Err("生成任务正在运行，无法校准".into());
+    }
+    ensure_data_writable()?;
+    let _running = RunningGuard::new();
+    let session = sessions::register(&format!("calibration_{}", chrono::Utc::now().format("%Y%m%d_%H%M%S%3fZ")), SessionKind::Calibration)?;
+    calibrate_and_record(&session.cancel_token()).map_err(WalletError::from)
+}
+
+/**
+ * 测速：在固定时间内按生成时的流程推导地址（不做模式匹配，不写入任何文件），可以通过 cancel_generation 提前结束
+ * 
+ * @param duration_ms - 运行时间（毫秒）
+ * @param thread_count - 工作线程数（为空时每个 CPU 核心一个）
+ * @param chain - 测速的链（可选，默认 ethereum；各链推导地址的流程不同，速度也不同）
+ * @returns 测速报告
+ */
+#[tauri::command]
+async fn benchmark_generation(duration_ms: u64, thread_count: Option<u32>, chain: Option<Chain>) -> Result<benchmark::BenchmarkReport, WalletError> {
+    if generation_running() {
+        return Err("生成任务正在运行，无法测速".into());
+    }
+    let _running = RunningGuard::new();
+    let session = sessions::register(&format!("benchmark_{}", chrono::Utc::now().format("%Y%m%d_%H%M%S%3fZ")), SessionKind::Benchmark)?;
+    benchmark::run(std::time::Duration::from_millis(duration_ms), thread_count, chain.unwrap_or_default(), &session.cancel_token()).map_err(WalletError::from)
+}
+
+/**
+ * 获取本次启动的数据迁移报告（前端错过 data-migration 事件时使用）
+ */
+#[tauri::command]
+fn get_migration_report() -> migrations::MigrationReport {
+    migration_report().clone()
+}
+
+/**
+ * 列出全部用户配置
+ */
+#[tauri::command]
+fn list_profiles() -> Result<ProfileStore, WalletError> {
+    Ok(profile_store()?
+        .lock()
+        .map_err(|_| "用户配置不可用".to_string())?
+        .clone())
+}
+
+/**
+ * 创建新的用户配置
+ * 
+ * @param name - 配置名称
+ * @param default_save_path - 该配置的默认保存路径（可选）
+ */
+#[tauri::command]
+fn create_profile(name: String, default_save_path: Option<String>) -> Result<Profile, WalletError> {
+    ensure_data_writable()?;
+    // 默认保存路径与 save_path 使用同样的规则，在创建时就拒绝无效的路径
+    if let Some(path) = &default_save_path {
+        output_root::validate_root(std::path::Path::new(path), output_root::app_bundle_dir().as_deref())?;
+    }
+    let mut store = profile_store()?.lock().map_err(|_| "用户配置不可用".to_string())?;
+    let mut updated = store.clone();
+    let profile = profiles::create(&mut updated, &name, default_save_path)?;
+    std::fs::create_dir_all(profiles::profile_data_dir(&name)?)
+        .map_err(|e| format!("无法创建配置目录: {}", e))?;
+    profiles::save_store(&profiles::app_data_dir()?, &updated)?;
+    *store = updated;
+    Ok(profile)
+}
+
+/**
+ * 切换当前使用的用户配置（生成任务运行时不允许切换）
+ * 
+ * @param name - 配置名称
+ */
+#[tauri::command]
+fn switch_profile(name: String) -> Result<Profile, WalletError> {
+    if generation_running() {
+        return Err("生成任务正在运行，无法切换用户配置".into());
+    }
+    ensure_data_writable()?;
+    let mut store = profile_store()?.lock().map_err(|_| "用户配置不可用".to_string())?;
+    let mut updated = store.clone();
+    profiles::switch(&mut updated, &name)?;
+    profiles::save_store(&profiles::app_data_dir()?, &updated)?;
+    *store = updated;
+    store.active_profile().cloned().ok_or_else(|| "当前用户配置不存在".to_string()).map_err(WalletError::from)
+}
+
+/**
+ * 启动桌面应用
+ */
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run_app() {
+    // 读取用户配置之前执行启动迁移（首次运行时创建 default 用户配置并迁移已有数据）
+    let report = migration_report();
+    let _ = profile_store();
+    
+    tauri::Builder::default()
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .setup(move |app| {
+            // 迁移结果只在执行了迁移或进入只读模式时通知一次
+            if report.status != migrations::MigrationStatus::UpToDate {
+                let _ = app.handle().emit("data-migration", report.clone());
+            }
+            // 启动时检查应用设置：文件损坏时恢复为默认设置，警告由 get_settings 返回，不影响启动
+            if let Ok(dir) = active_profile().and_then(|profile| profiles::profile_data_dir(&profile.name)) {
+                let _ = settings::load(&dir, !report.read_only());
+            }
+            spawn_idle_watcher(app.handle().clone(), idle::SystemIdleSource);
+            spawn_first_run_calibration(app.handle().clone());
+            // 上次退出时还有等待中的任务：把中断的任务标记为已停止，从下一个任务继续
+            if let Ok(dir) = active_profile().and_then(|profile| profiles::profile_data_dir(&profile.name)) {
+                if !migration_report().read_only() && queue::recover(&dir).is_ok() {
+                    spawn_queue_runner(app.handle().clone());
+                }
+            }
+            // 启动时选择 Keccak-256 实现并用已知向量校验
+            keccak::select(false);
+            // 启动时在后台检测时钟分辨率，计时器很粗时延长速度测量窗口
+            std::thread::spawn(timer::resolution);
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![greet, generate_fancy_wallet, cancel_generation, set_cpu_limit, list_sessions, replay_session, load_split_key_request, export_split_key_result, scan_mnemonic_for_pattern, generate_fancy_contract_wallet, export_recovery_sheet, run_self_test, verify_session_manifest, get_app_info, list_profiles, create_profile, switch_profile, describe_pattern_syntax, list_pattern_presets, resubscribe, format_quantity, list_result_sessions, get_results_page, check_output_directory, validate_save_path, open_output_folder, clean_output_directory_temp_files, burn_in, pause_generation, resume_generation, get_generation_status, get_last_wallet, get_rate_history, get_worker_stats, get_settings, update_settings, get_standing_search, set_standing_search, export_encrypted_to_recipient, export_wallets_xlsx, export_session_archive, secure_delete_wallet_file, secure_delete_session, estimate_time_to_confidence, list_saved_wallets, list_generated_wallets, query_wallets, count_wallets, verify_wallet_file, decrypt_wallet_file, save_template, list_templates, start_from_template, resume_session, get_migration_report, set_memory_soft_limit, get_recommended_defaults, get_calibration_history, run_calibration, benchmark_generation, set_session_note, inspect_keystore, verify_private_key, derive_address_from_mnemonic, checksum_address, validate_checksum, fingerprint_address, set_taskbar_progress, set_keccak_portable, verify_pairs, format_timestamp, score_address, migrate_collection, migrate_wallet_files, enqueue_generation, list_queue, reorder_queue, remove_from_queue, set_queue_options, estimate_generation, estimate_pattern_difficulty, validate_pattern, check_pattern_match, export_plaintext_sheet, check_transcription, generate_wallet_qr, check_balances, copy_secret_to_clipboard, set_start_cooldown, set_output_caps])
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| match event {
+            // 关闭窗口或请求退出时还有会话在运行：先停止它们（停止原因为 app_exit），在后台等待它们写完当前的结果行、
+            // 会话状态和清单（最多 EXIT_GRACE_PERIOD）后再退出，不在循环中途结束进程
+            tauri::RunEvent::ExitRequested { api, .. } if !EXIT_READY.load(Ordering::SeqCst) && !sessions::list().is_empty() => {
+                api.prevent_exit();
+                if EXIT_PENDING.swap(true, Ordering::SeqCst) {
+                    return;
+                }
+                queue::shut_down();
+                let _ = sessions::request_stop(None, StopReason::AppExit, None);
+                let app = app.clone();
+                std::thread::spawn(move || {
+                    if !sessions::wait_until_idle(EXIT_GRACE_PERIOD) {
+                        log_error(&format!("{} 秒内仍有会话没有结束，直接退出", EXIT_GRACE_PERIOD.as_secs()));
+                    }
+                    EXIT_READY.store(true, Ordering::SeqCst);
+                    app.exit(0);
+                });
+            }
+            // 应用退出时清除还没有到期的剪贴板和内存中最后找到的钱包，并停止仍在运行的会话，使其记录停止原因并写入清单
+            tauri::RunEvent::Exit => {
+                let _ = clipboard::clear_pending(&TauriClipboard(app));
+                if let Ok(mut map) = last_wallets().lock() {
+                    map.clear();
+                }
+                queue::shut_down();
+                let _ = sessions::request_stop(None, StopReason::AppExit, None);
+            }
+            _ => {}
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::{DifficultyClass, PatternAlphabet};
+    use crate::engine::FoundKey;
+    use std::time::Duration;
+    use zeroize::{Zeroize, ZeroizeOnDrop};
+    use crate::test_support::{CASE_INSENSITIVE_MATCHES, MNEMONIC_ADDRESS_VECTORS, MNEMONIC_SCAN_INDEX_1, lock_sessions, with_data_dir};
+    use crate::self_test::{KNOWN_KEYS, CHECKSUM_VECTORS};
+
+    /// 粘贴的模式：(用户输入, 是否区分大小写, 规范化的模式)
+    const PASTED_PATTERNS: &[(&str, bool, &str)] = &[
+        ("0xdead", false, "dead"),
+        ("0XDEAD", false, "dead"),
+        (" \t0xdead\r\n", false, "dead"),
+        ("\u{3000}*beef* ", false, "*beef*"),
+        (" 12/34 ", false, "12/34"),
+        ("0x70997970C51812dc3A010C7d01b50e0d17dc79C8", true, "70997970C51812dc3A010C7d01b50e0d17dc79C8"),
+        (" 0x70997970c51812dc3a010c7d01b50e0d17dc79c8\n", false, "70997970c51812dc3a010c7d01b50e0d17dc79c8"),
+    ];
+
+    /// 去掉首尾空白和 0x 前缀后为空的模式
+    const EMPTY_PASTED_PATTERNS: &[&str] = &["0x", "  ", " 0X \t", "\n"];
+
+    /// 同时搜索多个模式时的模式（前缀、前后缀和包含三种比较方式）
+    const MULTIPLE_PATTERNS: &[&str] = &["a/", "8", "*12*"];
+
+    /// 同时搜索多个模式的测试找到的匹配数
+    const MULTIPLE_PATTERN_MATCHES: u64 = 12;
+
+    /// 难度估计用例：(模式, 区分大小写, 比较方式, 期望的难度位数)
+    const DIFFICULTY_CASES: &[(&str, bool, Option<MatchMode>, f64)] = &[
+        ("888", false, None, 24.0),
+        ("dead", false, Some(MatchMode::Prefix), 16.0),
+        ("Dead", true, Some(MatchMode::Prefix), 20.0),
+        ("dead/beef", false, None, 32.0),
+    ];
+
+    /// 难度估计应当拒绝的模式（非十六进制字符、空模式和与模式语法矛盾的比较方式）
+    const INVALID_DIFFICULTY_PATTERNS: &[(&str, Option<MatchMode>)] = &[("xyz", None), ("", None), ("0xg1", None), ("*abc*", Some(MatchMode::Prefix))];
+
+    /// 应当被拒绝的 ? 占位符模式
+    const INVALID_PLACEHOLDER_PATTERNS: &[&str] = &["*d??d*", "*?*", "d?g", "????", "??/??", "0xd?x"];
+
+    /// 比特币的已知向量：(私钥, P2PKH 地址, P2WPKH 地址, WIF 私钥)，都使用压缩公钥
+    const BITCOIN_KEY_VECTORS: &[(&str, &str, &str, &str)] = &[
+        (
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH",
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+            "KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn",
+        ),
+        (
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "1cMh228HTCiwS8ZsaakH8A8wze1JR5ZsP",
+            "bc1qq6hag67dl53wl99vzg42z8eyzfz2xlkvxechjp",
+            "KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU74NMTptX4",
+        ),
+    ];
+
+    /// 无效的比特币地址：校验和错误、大小写混用、见证版本不是 0、版本字节不是主网 P2PKH
+    const INVALID_BITCOIN_ADDRESSES: &[&str] = &[
+        "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5",
+        "bc1Qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+        "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMJ",
+        "T9yD14Nj9j7xAB4dbGeiX9h8unkKHxuWwb",
+    ];
+
+    /// Solana 已知向量：(ed25519 种子, 公钥, Base58 地址)
+    const SOLANA_KEY_VECTORS: &[(&str, &str, &str)] = &[(
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "3b6a27bcceb6a42d62a3a8d02a6f0d73653215771de243a63ac048a18b59da29",
+        "4zvwRjXUKGfvwnParsHAS3HuSVzV5cA4McphgmoCtajS",
+    )];
+
+    /// 应被拒绝的 Solana 地址：包含 0、太短、太长（解码后不是 32 字节）、以太坊地址
+    const INVALID_SOLANA_ADDRESSES: &[&str] = &[
+        "4zvwRjXUKGfvwnParsHAS3HuSVzV5cA4McphgmoCtaj0",
+        "4zvwRjXUKGfvwnParsHAS3Hu",
+        "4zvwRjXUKGfvwnParsHAS3HuSVzV5cA4McphgmoCtajSS",
+        "0x52908400098527886E0F7030069857D2E4169EE7",
+    ];
+
+    /// BIP-32 测试向量 1 的种子
+    const BIP32_VECTOR_SEED: &str = "000102030405060708090a0b0c0d0e0f";
+
+    /// BIP-32 测试向量 1：(派生路径, 私钥)
+    const BIP32_VECTOR_KEYS: &[(&str, &str)] = &[
+        ("m", "e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35"),
+        ("m/0H", "edb2e14f9ee77d26dd93b4ecede8d16ed408ce149b6cd80b0715a2d911a0afea"),
+        ("m/0H/1", "3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368"),
+        ("m/0H/1/2H", "cbce0d719ecf7431d88e6a89fa1483e02e35092af60c042b1df2ff59fa424dca"),
+        ("m/0H/1/2H/2", "0f479245fb19a38a1954c5c7c0ebab2f9bdfd96a17563ef28a6a4b1a2a764ef4"),
+        ("m/0H/1/2H/2/1000000000", "471b76e389e528d6de6d816857e012c5455051cad6660850e58372a6c3e6e7c8"),
+    ];
+
+    /// 找到时间测试中的匹配数
+    const CREATED_AT_MATCHES: u64 = 3;
+
+    /// 模式预检用例：(模式, 比较方式, 是否区分大小写, 规范化的模式, 匹配规则, 无效字符的位置, 难度等级)
+    type PatternValidationCase = (&'static str, Option<MatchMode>, bool, &'static str, MatchRule, &'static [usize], DifficultyClass);
+
+    /// 各种形式的模式预检用例（无效字符的位置为空表示没有无效字符）
+    const PATTERN_VALIDATION_CASES: &[PatternValidationCase] = &[
+        ("0xDEAD", None, false, "dead", MatchRule::PrefixSuffix, &[], DifficultyClass::Hard),
+        ("0xDEAD", None, true, "DEAD", MatchRule::PrefixSuffix, &[], DifficultyClass::Extreme),
+        ("888", None, false, "888", MatchRule::PrefixSuffix, &[], DifficultyClass::Moderate),
+        ("dead", Some(MatchMode::Prefix), false, "dead", MatchRule::Prefix, &[], DifficultyClass::Easy),
+        ("beef", Some(MatchMode::Suffix), false, "beef", MatchRule::Suffix, &[], DifficultyClass::Easy),
+        ("*beef*", None, false, "*beef*", MatchRule::Wildcard, &[], DifficultyClass::Easy),
+        ("*aabb*", None, false, "*aabb*", MatchRule::RepeatAabb, &[], DifficultyClass::Easy),
+        ("12/34", None, false, "12/34", MatchRule::PrefixAndSuffix, &[], DifficultyClass::Easy),
+        ("0000000000000000000000000000000000000000", None, false, "0000000000000000000000000000000000000000", MatchRule::PrefixSuffix, &[], DifficultyClass::Infeasible),
+        ("0xd-g", None, false, "d-g", MatchRule::PrefixSuffix, &[4, 5], DifficultyClass::Moderate),
+        ("*zq*", None, false, "*zq*", MatchRule::Wildcard, &[2, 3], DifficultyClass::Easy),
+        ("*zz*", None, false, "*zz*", MatchRule::Template, &[], DifficultyClass::Easy),
+        ("1x/2 3", None, false, "1x/2 3", MatchRule::PrefixAndSuffix, &[2, 5], DifficultyClass::Easy),
+        (" dead", None, false, "dead", MatchRule::PrefixSuffix, &[], DifficultyClass::Hard),
+        ("", None, false, "", MatchRule::PrefixSuffix, &[], DifficultyClass::Easy),
+        ("*dead*", Some(MatchMode::Prefix), false, "*dead*", MatchRule::Prefix, &[], DifficultyClass::Easy),
+    ];
+
+    /**
+     * 编译期检查：类型在离开作用域时清零
+     */
+    fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+
+    /**
+     * 直接读取内存：Zeroizing<[u8; 32]> 在 Drop 后、SecretHex 在清零后（Drop 时执行同样的清零）原来的缓冲区全部为零
+     * 
+     * @param key - 用于检查的私钥（十六进制）
+     */
+    #[cfg(feature = "zeroize-check")]
+    fn check_zeroized_memory(key: &str) -> Result<(), String> {
+        let mut bytes = std::mem::ManuallyDrop::new(Zeroizing::new([0xa5u8; 32]));
+        // SAFETY: 只调用一次 Drop；[u8; 32] 没有无效的取值，存放它的栈空间在 Drop 后仍然有效
+        let after_drop = unsafe {
+            std::mem::ManuallyDrop::drop(&mut bytes);
+            std::ptr::read_volatile(&**bytes as *const [u8; 32])
+        };
+        if after_drop != [0u8; 32] {
+            return Err("Zeroizing<[u8; 32]> 在 Drop 后没有清零".to_string());
+        }
+        let mut secret = SecretHex::from(key.to_string());
+        let (ptr, len) = (secret.expose().as_ptr(), secret.expose().len());
+        secret.zeroize();
+        // SAFETY: 清零后 String 保留原来的容量，缓冲区在 secret 离开作用域之前不会被释放
+        let buffer = unsafe { std::slice::from_raw_parts(ptr, len) };
+        if buffer.iter().any(|&byte| byte != 0) {
+            return Err("SecretHex 清零后缓冲区中仍有私钥".to_string());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn verify_private_key_cases() -> Result<(), String> {
+        for (key_hex, expected) in KNOWN_KEYS {
+            let expected = format!("0x{}", expected);
+            for input in [key_hex.to_string(), format!("0x{}", key_hex), format!(" 0x{}\n", key_hex.to_uppercase())] {
+                let address = verify_private_key(input.clone())?;
+                if address != expected {
+                    return Err(format!("私钥 {:?} 推导出 {}，期望 {}", input, address, expected));
+                }
+            }
+        }
+        // 无效的十六进制、长度不对和超出曲线阶的私钥各自有不同的错误
+        let invalid = [
+            ("0x000000000000000000000000000000000000000000000000000000000000000g", "私钥包含非十六进制字符 'g'（第 66 个字符）"),
+            ("0x0001", "私钥长度应为 64 个十六进制字符（不含 0x），实际为 4 个"),
+            ("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141", "私钥无效：超出 secp256k1 私钥范围"),
+        ];
+        for (input, expected) in invalid {
+            match verify_private_key(input.to_string()) {
+                Ok(address) => return Err(format!("私钥 {:?} 应当被拒绝，却推导出 {}", input, address)),
+                Err(e) if e.message() != expected => return Err(format!("私钥 {:?} 的错误信息为 “{}”，期望 “{}”", input, e, expected)),
+                Err(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * checksum_address 和 validate_checksum 命令：EIP-55 示例的任意大小写输入（带或不带 0x）转换为示例本身，
+     * 示例本身校验通过，改变一个字母的大小写后校验失败，格式错误的地址返回错误
+     */
+    #[test]
+    fn checksum_commands() -> Result<(), String> {
+        for expected in CHECKSUM_VECTORS {
+            let canonical = format!("0x{}", expected);
+            for input in [expected.to_lowercase(), format!("0x{}", expected.to_uppercase()), format!(" 0X{} ", expected)] {
+                let checksum = checksum_address(input.clone())?;
+                if checksum != canonical {
+                    return Err(format!("checksum_address({:?}) 为 {}，期望 {}", input, checksum, canonical));
+                }
+            }
+            if !validate_checksum(expected.to_string())? || !validate_checksum(canonical.clone())? {
+                return Err(format!("示例 {} 的 checksum 被判为不正确", expected));
+            }
+            // 改变第一个字母的大小写
+            let position = expected.find(|c: char| c.is_ascii_alphabetic()).ok_or("示例中没有字母")?;
+            let mut altered = expected.to_string();
+            let letter = altered.remove(position);
+            let swapped = if letter.is_ascii_uppercase() { letter.to_ascii_lowercase() } else { letter.to_ascii_uppercase() };
+            altered.insert(position, swapped);
+            if validate_checksum(altered.clone())? {
+                return Err(format!("大小写被改动的地址 {} 被判为 checksum 正确", altered));
+            }
+        }
+        for input in ["", "0x52908400098527886E0F7030069857D2E4169EE", "0x52908400098527886E0F7030069857D2E4169EEg"] {
+            if let Ok(value) = checksum_address(input.to_string()) {
+                return Err(format!("checksum_address({:?}) 应当返回错误，却得到 {}", input, value));
+            }
+            if let Ok(value) = validate_checksum(input.to_string()) {
+                return Err(format!("validate_checksum({:?}) 应当返回错误，却得到 {}", input, value));
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * case_mode：exact 时模式按原样与 checksum 地址比较，难度按指定大小写的字母计算，与 case_sensitive 矛盾时拒绝，
+     * 只有数字的模式区分大小写时给出提示
+     */
+    #[test]
+    fn case_mode() -> Result<(), String> {
+        let resolved = [
+            (None, None, Some(false)),
+            (Some(CaseMode::Exact), None, Some(true)),
+            (Some(CaseMode::Insensitive), Some(false), Some(false)),
+            (None, Some(true), Some(true)),
+            (Some(CaseMode::Insensitive), Some(true), None),
+            (Some(CaseMode::Exact), Some(false), None),
+        ];
+        for (case_mode, case_sensitive, expected) in resolved {
+            if CaseMode::resolve(case_mode, case_sensitive).ok() != expected {
+                return Err(format!("case_mode {:?} 与 case_sensitive {:?} 的结果不是 {:?}", case_mode, case_sensitive, expected));
+            }
+        }
+        
+        let checksum = MNEMONIC_SCAN_INDEX_1.trim_start_matches("0x");
+        let params: GenerationParams = serde_json::from_str(r#"{"pattern": "70997970C5/79C8", "case_mode": "exact"}"#).map_err(|e| e.to_string())?;
+        params.validate()?;
+        let lowered = GenerationParams { pattern: "70997970c5/79c8".to_string(), ..params.clone() };
+        if !params.case_sensitive() || !params.parsed_patterns()[0].matches(checksum) || lowered.parsed_patterns()[0].matches(checksum) {
+            return Err("case_mode: exact 没有按原样与 checksum 地址比较".to_string());
+        }
+        let conflict = GenerationParams { case_sensitive: Some(false), ..params.clone() }.validate();
+        if !conflict.as_ref().is_err_and(|e| e.contains("矛盾")) {
+            return Err(format!("case_mode 与 case_sensitive 矛盾时的结果为 {:?}", conflict));
+        }
+        
+        let exact = validate_pattern("De12".to_string(), None, None, Some(CaseMode::Exact), None, None);
+        let insensitive = validate_pattern("De12".to_string(), None, None, Some(CaseMode::Insensitive), None, None);
+        if exact.normalized != "De12" || exact.difficulty_bits != 36.0 || insensitive.difficulty_bits != 32.0 {
+            return Err(format!("De12 的难度为 {} / {} 位，应为 36 / 32 位", exact.difficulty_bits, insensitive.difficulty_bits));
+        }
+        let mismatch = validate_pattern("De12".to_string(), None, Some(false), Some(CaseMode::Exact), None, None);
+        if mismatch.valid || !mismatch.error.as_deref().is_some_and(|e| e.contains("矛盾")) {
+            return Err(format!("预检没有拒绝矛盾的大小写设置: {:?}", mismatch.error));
+        }
+        
+        let digits = validate_pattern("1234".to_string(), None, None, Some(CaseMode::Exact), None, None);
+        let quiet = [
+            validate_pattern("1234".to_string(), None, None, None, None, None),
+            exact,
+            validate_pattern("1234".to_string(), None, None, None, Some(Chain::Solana), None),
+        ];
+        if !digits.valid || digits.warnings.is_empty() || quiet.iter().any(|report| !report.warnings.is_empty()) {
+            return Err(format!("只有数字的模式的提示不正确: {:?} / {:?}", digits.warnings, quiet.iter().map(|report| &report.warnings).collect::<Vec<_>>()));
+        }
+        Ok(())
+    }
+
+    /**
+     * 粘贴的模式：首尾空白和 0x 前缀在匹配前去掉，错误信息中的位置仍按用户输入计算，
+     * 去掉后为空时返回错误，找到的钱包记录用户输入的原始模式
+     */
+    #[test]
+    #[ignore = "运行完整的生成循环，耗时较长"]
+    fn pasted_patterns() -> Result<(), String> {
+        let checksum = MNEMONIC_SCAN_INDEX_1.trim_start_matches("0x");
+        for &(input, case_sensitive, normalized) in PASTED_PATTERNS {
+            let report = validate_pattern(input.to_string(), None, Some(case_sensitive), None, None, None);
+            if !report.valid || report.normalized != normalized {
+                return Err(format!("模式 {:?} 的预检结果为 {:?}", input, report));
+            }
+            GenerationParams { pattern: input.to_string(), case_sensitive: Some(case_sensitive), ..GenerationParams::default() }.validate()?;
+            if normalized.len() == 40 && !ParsedPattern::with_case(input, case_sensitive).matches(checksum) {
+                return Err(format!("粘贴的完整地址 {:?} 与该地址不匹配", input));
+            }
+        }
+        for input in EMPTY_PASTED_PATTERNS {
+            let report = validate_pattern(input.to_string(), None, None, None, None, None);
+            if report.valid || !report.error.as_deref().is_some_and(|e| e.contains("为空")) {
+                return Err(format!("模式 {:?} 去掉空白和 0x 前缀后为空，预检结果为 {:?}", input, report.error));
+            }
+        }
+        
+        let positions: Vec<usize> = validate_pattern(" 0xd-g".to_string(), None, None, None, None, None).invalid_characters.iter().map(|invalid| invalid.position).collect();
+        if positions != [5, 6] {
+            return Err(format!(" 0xd-g 的无效字符位置为 {:?}，应为 [5, 6]", positions));
+        }
+        let tron = validate_pattern(" abc ".to_string(), None, None, None, Some(Chain::Tron), None);
+        let tron_prefixed = validate_pattern("0xabc".to_string(), None, None, None, Some(Chain::Tron), None);
+        if !tron.valid || !tron_prefixed.error.as_deref().is_some_and(|e| e.contains("0x")) {
+            return Err(format!("波场模式的首尾空白和 0x 前缀处理不正确: {:?} / {:?}", tron.error, tron_prefixed.error));
+        }
+        
+        let pasted = " 0xAb\n";
+        let config = SearchConfig {
+            limits: crate::engine::GenerationLimits { max_matches: Some(CASE_INSENSITIVE_MATCHES), ..Default::default() },
+            rng_mode: RngMode::DeterministicTest,
+            test_seed: Some(5),
+            ..SearchConfig::new(pasted)
+        };
+        let mut found = Vec::new();
+        crate::engine::search(&config, &CancellationToken::new(), || false, |event| {
+            if let SearchEvent::Found(key) = event {
+                found.push(key);
+            }
+        });
+        let matched = |key: &FoundKey| {
+            let lower = hex::encode(key.address.as_bytes());
+            key.pattern == pasted && lower.starts_with("ab") && lower.ends_with("ab")
+        };
+        if found.len() as u64 != CASE_INSENSITIVE_MATCHES || !found.iter().all(matched) {
+            return Err(format!("粘贴的模式 {:?} 的搜索结果不正确: {:?}", pasted, found.iter().map(|key| (key.address.to_checksum(), key.pattern.clone())).collect::<Vec<_>>()));
+        }
+        Ok(())
+    }
+
+    /**
+     * 模式预检：规范化的形式、匹配规则、全部无效字符的位置和难度等级符合预期，
+     * 是否有效和错误信息与生成开始前的校验（GenerationParams::validate）完全一致
+     */
+    #[test]
+    fn validate_pattern_cases() -> Result<(), String> {
+        for &(input, match_mode, case_sensitive, normalized, mode, positions, class) in PATTERN_VALIDATION_CASES {
+            let report = validate_pattern(input.to_string(), match_mode, Some(case_sensitive), None, None, None);
+            let actual: Vec<usize> = report.invalid_characters.iter().map(|invalid| invalid.position).collect();
+            if report.normalized != normalized || report.mode != mode || actual != positions || report.difficulty_class != class {
+                return Err(format!("模式 {:?} 的预检结果为 {:?}", input, report));
+            }
+            let params = GenerationParams {
+                pattern: input.to_string(),
+                case_sensitive: Some(case_sensitive),
+                match_mode,
+                ..GenerationParams::default()
+            };
+            let generation = params.validate().err();
+            if report.valid != generation.is_none() || report.error != generation {
+                return Err(format!("模式 {:?} 的预检结果 {:?} 与生成前的校验 {:?} 不一致", input, report.error, generation));
+            }
+            if let (Some(first), Some(error)) = (report.invalid_characters.first(), &report.error) {
+                if !error.contains(&format!("第 {} 个字符 '{}'", first.position, first.character)) {
+                    return Err(format!("模式 {:?} 的错误信息 {} 与第一个无效字符 {:?} 不一致", input, error, first));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * 难度估计：期望尝试次数按字符数和大小写计算，包含和重复形式与解析器的组合计算一致，
+     * 预算等于期望尝试次数时成功的概率约为 1 - 1/e，无效的模式和速度返回错误
+     */
+    #[test]
+    fn pattern_difficulty_cases() -> Result<(), String> {
+        let rate = (1000.0, RateSource::Supplied);
+        for (text, case_sensitive, mode, bits) in DIFFICULTY_CASES {
+            let estimate = pattern_difficulty(&ParsedPattern::with_case(text, *case_sensitive).with_match_mode(*mode), None, rate)?;
+            if estimate.difficulty_bits != *bits || estimate.expected_attempts != bits.exp2() || estimate.success_probability.is_some() {
+                return Err(format!("{} 的难度为 {} 位，应为 {} 位", text, estimate.difficulty_bits, bits));
+            }
+            if (estimate.expected_ms - bits.exp2()).abs() > 1e-6 * bits.exp2() {
+                return Err(format!("{} 按每秒 1000 次的期望时间为 {} 毫秒", text, estimate.expected_ms));
+            }
+        }
+        for (text, mode) in INVALID_DIFFICULTY_PATTERNS {
+            if pattern_difficulty(&ParsedPattern::new(text).with_match_mode(*mode), None, rate).is_ok() {
+                return Err(format!("无效的模式 {:?} 没有被拒绝", text));
+            }
+        }
+        for shape in ["*aaaa*", "*aabb*", "*abab*", "*123*"] {
+            let parsed = ParsedPattern::new(shape);
+            let estimate = pattern_difficulty(&parsed, None, rate)?;
+            if estimate.difficulty_bits != parsed.difficulty_bits() || !estimate.expected_attempts.is_finite() {
+                return Err(format!("{} 的难度估计与解析器不一致", shape));
+            }
+        }
+        let budget = 1u64 << 24;
+        let estimate = pattern_difficulty(&ParsedPattern::new("888"), Some(budget), rate)?;
+        let success = estimate.success_probability.unwrap_or_default();
+        if (success - (1.0 - (-1f64).exp())).abs() > 1e-6 || estimate.budget_ms != Some(budget as f64) {
+            return Err(format!("预算为期望尝试次数时成功的概率为 {}", success));
+        }
+        if [0.0, -5.0, f64::NAN, f64::INFINITY].iter().any(|&bad| pattern_difficulty(&ParsedPattern::new("888"), None, (bad, RateSource::Supplied)).is_ok()) {
+            return Err("无效的速度没有被拒绝".to_string());
+        }
+        let long = pattern_difficulty(&ParsedPattern::with_case(&"A".repeat(20), true), None, rate)?;
+        if !long.expected_attempts.is_finite() || !long.expected_ms.is_finite() {
+            return Err("很长的模式的期望尝试次数不是有限值".to_string());
+        }
+        Ok(())
+    }
+
+    /**
+     * dry_run：与保存或带出私钥的设置同时使用时拒绝；找到的钱包分配序号、私钥换成标记、没有助记词，
+     * wallet-found 事件只有地址，临时保存路径中没有出现任何文件
+     */
+    #[test]
+    #[ignore = "运行完整的生成循环，耗时较长"]
+    fn dry_run() -> Result<(), String> {
+        let dry_run = GenerationParams { pattern: "8".to_string(), dry_run: Some(true), ..GenerationParams::default() };
+        dry_run.validate()?;
+        let conflicts = [
+            GenerationParams { emit_private_key: Some(true), ..dry_run.clone() },
+            GenerationParams { emit_json_files: Some(true), ..dry_run.clone() },
+            GenerationParams { address_qr: Some(QrOptions::default()), ..dry_run.clone() },
+            GenerationParams { collection: Some("dry".to_string()), ..dry_run.clone() },
+        ];
+        if conflicts.iter().any(|params| params.validate().is_ok()) {
+            return Err("dry_run 与保存或带出私钥的设置同时使用时没有被拒绝".to_string());
+        }
+        with_data_dir("dry_run", |dir| {
+            let config = SearchConfig {
+                limits: crate::engine::GenerationLimits { max_matches: Some(3), ..Default::default() },
+                rng_mode: RngMode::DeterministicTest,
+                test_seed: Some(7),
+                key_source: KeySourceKind::Mnemonic,
+                ..SearchConfig::new("a")
+            };
+            let mut wallets = Vec::new();
+            let mut wallet_index = WalletIndex::default();
+            let mut failure = None;
+            crate::engine::search(&config, &CancellationToken::new(), || false, |event| {
+                if let SearchEvent::Found(found) = event {
+                    let mut wallet = found_wallet(found, "a", None, TimestampZone::default());
+                    match dry_run_wallet(&mut wallet, &mut wallet_index) {
+                        Ok(written) if written.bytes == 0 => wallets.push(wallet),
+                        Ok(written) => failure = Some(format!("dry_run 记录写入了 {} 字节", written.bytes)),
+                        Err(e) => failure = Some(e),
+                    }
+                }
+            });
+            if let Some(failure) = failure {
+                return Err(failure);
+            }
+            let indexes: Vec<u64> = wallets.iter().map(|wallet| wallet.index).collect();
+            if indexes != [1, 2, 3] {
+                return Err(format!("dry_run 找到 {} 个钱包，序号为 {:?}", wallets.len(), indexes));
+            }
+            if let Some(wallet) = wallets.iter().find(|wallet| wallet.private_key.expose() != DRY_RUN_PRIVATE_KEY || wallet.mnemonic.is_some()) {
+                return Err(format!("dry_run 返回的钱包 {} 带有私钥或助记词", wallet.address));
+            }
+            let event = serde_json::to_value(WalletFound::new("selftest_dry_run", &wallets[0], "a", None, None, &ScoreWeights::default(), false)).map_err(|e| e.to_string())?;
+            if event.get("private_key").is_some() || event.get("mnemonic").is_some() {
+                return Err(format!("dry_run 的 wallet-found 事件带有私钥: {}", event));
+            }
+            let leftovers: Vec<std::path::PathBuf> = std::fs::read_dir(dir).map_err(|e| e.to_string())?.flatten().map(|entry| entry.path()).collect();
+            if !leftovers.is_empty() {
+                return Err(format!("dry_run 在保存路径中写入了文件: {:?}", leftovers));
+            }
+            Ok(())
+        })
+    }
+
+    /**
+     * 钱包序号按会话分配：两个会话同时运行、多个工作线程密集地找到匹配时，每个会话的序号都从 1 开始连续，
+     * 结果文件中的行顺序与序号顺序一致
+     */
+    #[test]
+    fn concurrent_session_indices() -> Result<(), String> {
+        const SESSION_MATCHES: u64 = 200;
+        with_data_dir("concurrent_session_indices", |dir| {
+            let save_path = Some(dir.to_string_lossy().to_string());
+            let sessions: Vec<_> = (1..=2)
+                .map(|seed| {
+                    let save_path = save_path.clone();
+                    std::thread::spawn(move || -> Result<(PathBuf, Vec<u64>), String> {
+                        let stamp = format!("20260101_00000{}", seed);
+                        let output = resolve_session_output(save_path, Some("a"), Chain::Ethereum, None, RngMode::default(), &stamp, filename_template::DEFAULT_TEMPLATE, None, CsvPrivateKeys::Full)?;
+                        let config = SearchConfig {
+                            limits: crate::engine::GenerationLimits { max_matches: Some(SESSION_MATCHES), ..Default::default() },
+                            rng_mode: RngMode::DeterministicTest,
+                            test_seed: Some(seed),
+                            workers: Some(4),
+                            match_mode: Some(MatchMode::Prefix),
+                            ..SearchConfig::new("a")
+                        };
+                        let mut wallet_index = WalletIndex::default();
+                        let mut indexes = Vec::new();
+                        let mut failure = None;
+                        crate::engine::search(&config, &CancellationToken::new(), || false, |event| {
+                            if let SearchEvent::Found(found) = event {
+                                let mut wallet = found_wallet(found, "a", None, TimestampZone::default());
+                                match save_wallet_to_file(&mut wallet, "a", &output, &mut wallet_index) {
+                                    Ok(_) => indexes.push(wallet.index),
+                                    Err(e) => failure = Some(e),
+                                }
+                            }
+                        });
+                        match failure {
+                            Some(e) => Err(e),
+                            None => Ok((output.csv_path, indexes)),
+                        }
+                    })
+                })
+                .collect();
+            for session in sessions {
+                let (csv_path, indexes) = session.join().map_err(|_| "会话线程崩溃".to_string())??;
+                let expected: Vec<u64> = (1..=SESSION_MATCHES).collect();
+                if indexes != expected {
+                    return Err(format!("会话的钱包序号为 {:?}", indexes));
+                }
+                let csv = std::fs::read_to_string(&csv_path).map_err(|e| e.to_string())?;
+                let rows: Vec<u64> = csv.lines().skip(1).map(|line| line.split(',').nth(3).and_then(|index| index.parse().ok()).unwrap_or(0)).collect();
+                if rows != expected {
+                    return Err(format!("{} 中的序号为 {:?}", csv_path.display(), rows));
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /**
+     * wallet-found 事件：默认不带私钥（序列化结果中没有 private_key 字段），开启 emit_private_key 时带有私钥，其余字段与保存的钱包一致
+     */
+    #[test]
+    fn wallet_found_event() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        let secret_key = SecretKey::from_slice(&[0x42; 32]).map_err(|e| e.to_string())?;
+        let wallet = Wallet {
+            index: 7,
+            address: Address::from_public_key(&PublicKey::from_secret_key(&secp, &secret_key)),
+            private_key: SecretHex::encode(&secret_key.secret_bytes()),
+            attempts: 1234,
+            duration: 56,
+            matched_spans: Vec::new(),
+            rng_mode: RngMode::default(),
+            test_wallet: false,
+            mnemonic: None,
+            chain_address: None,
+            matched_pattern: String::new(),
+            created_at: timestamps::now_rfc3339(),
+        };
+        let weights = ScoreWeights::default();
+        let redacted = serde_json::to_value(WalletFound::new("selftest", &wallet, "8", None, None, &weights, false)).map_err(|e| e.to_string())?;
+        if redacted.get("private_key").is_some() || redacted.to_string().contains(wallet.private_key.expose()) {
+            return Err("默认的 wallet-found 事件带有私钥".to_string());
+        }
+        let full = WalletFound::new("selftest", &wallet, "8", None, None, &weights, true);
+        if full.private_key.as_ref() != Some(&wallet.private_key) || full.index != 7 || full.attempts != 1234 || full.duration != 56 {
+            return Err(format!("wallet-found 事件与保存的钱包不一致: {:?}", full));
+        }
+        Ok(())
+    }
+
+    /**
+     * 同时搜索多个模式：每个匹配记录第一个满足的模式，合并难度按各模式的概率相加，
+     * 重复的模式只保留一个，空列表、与集合同时使用和文件名相同的模式都能正确处理
+     */
+    #[test]
+    fn multiple_patterns() -> Result<(), String> {
+        let config = SearchConfig {
+            limits: crate::engine::GenerationLimits { max_matches: Some(MULTIPLE_PATTERN_MATCHES), ..Default::default() },
+            rng_mode: RngMode::DeterministicTest,
+            test_seed: Some(11),
+            additional_patterns: MULTIPLE_PATTERNS[1..].iter().map(|pattern| pattern.to_string()).collect(),
+            ..SearchConfig::new(MULTIPLE_PATTERNS[0])
+        };
+        config.validate()?;
+        let parsed = config.parsed_patterns();
+        let mut found = Vec::new();
+        crate::engine::search(&config, &CancellationToken::new(), || false, |event| {
+            if let SearchEvent::Found(key) = event {
+                found.push(key);
+            }
+        });
+        if found.len() as u64 != MULTIPLE_PATTERN_MATCHES {
+            return Err(format!("找到 {} 个匹配，应为 {} 个", found.len(), MULTIPLE_PATTERN_MATCHES));
+        }
+        for key in &found {
+            let lower = hex::encode(key.address.as_bytes());
+            let first = parsed.iter().position(|parsed| parsed.matches(&lower)).map(|i| MULTIPLE_PATTERNS[i]);
+            if first != Some(key.pattern.as_str()) {
+                return Err(format!("地址 {} 记录的模式为 {}，第一个满足的模式为 {:?}", lower, key.pattern, first));
+            }
+        }
+        
+        let (single, double) = (ParsedPattern::new("dead"), pattern::combined_difficulty_bits(&[ParsedPattern::new("dead"), ParsedPattern::new("beef")]));
+        if (double - (single.difficulty_bits() - 1.0)).abs() > 1e-9 || pattern::combined_difficulty_bits(std::slice::from_ref(&single)) != single.difficulty_bits() {
+            return Err(format!("两个同样难度的模式合并后为 {} 位，应为 {} 位", double, single.difficulty_bits() - 1.0));
+        }
+        
+        let unique = dedup_patterns(vec!["dead".to_string(), " DEAD ".to_string(), "beef".to_string(), "dead".to_string()], true)?;
+        let exact = dedup_patterns(vec!["dead".to_string(), "DEAD".to_string()], false)?;
+        if unique != ["dead", "beef"] || exact.len() != 2 || dedup_patterns(Vec::new(), true).is_ok() || dedup_patterns(vec![" ".to_string()], true).is_ok() {
+            return Err(format!("去重后的模式为 {:?} / {:?}，或者空列表没有被拒绝", unique, exact));
+        }
+        let labels = session_file_labels(&["*abc*".to_string(), "abc".to_string(), "def".to_string()]);
+        if labels != ["abc", "abc-2", "def"] {
+            return Err(format!("文件名中的模式为 {:?}，应各不相同", labels));
+        }
+        
+        let params = GenerationParams {
+            pattern: "dead".to_string(),
+            additional_patterns: vec!["beef".to_string()],
+            collection: Some("gallery".to_string()),
+            ..GenerationParams::default()
+        };
+        if params.validate().is_ok() {
+            return Err("同时搜索多个模式时追加到集合没有被拒绝".to_string());
+        }
+        let invalid = GenerationParams { additional_patterns: vec!["xyz".to_string()], collection: None, ..params };
+        if invalid.validate().is_ok() {
+            return Err("无效的附加模式没有被拒绝".to_string());
+        }
+        let mut hits: BTreeMap<&str, u64> = BTreeMap::new();
+        for key in &found {
+            *hits.entry(key.pattern.as_str()).or_default() += 1;
+        }
+        Ok(())
+    }
+
+    /**
+     * ? 占位符：不计入难度，不能与 *...* 形式混用，区分大小写时与任意大小写的字符相同，
+     * 开头的 0x 被忽略，结果文件的模式列和文件名保留原始模式
+     */
+    #[test]
+    fn positional_wildcards() -> Result<(), String> {
+        for rejected in INVALID_PLACEHOLDER_PATTERNS {
+            if ParsedPattern::new(rejected).validate().is_ok() {
+                return Err(format!("无效的 ? 占位符模式 {:?} 没有被拒绝", rejected));
+            }
+        }
+        let mixed = ParsedPattern::new("*d??d*").validate().err().unwrap_or_default();
+        let position = ParsedPattern::new("0xd?g").validate().err().unwrap_or_default();
+        if !mixed.contains("*...*") || !position.contains("第 5 个字符 'g'") {
+            return Err(format!("? 占位符模式的错误信息不正确: {} / {}", mixed, position));
+        }
+        let (placeholder, plain) = (ParsedPattern::new("d??d").difficulty_bits(), ParsedPattern::new("dd").difficulty_bits());
+        if placeholder != plain || ParsedPattern::new("0x00??00").difficulty_bits() != 32.0 {
+            return Err(format!("d??d 的难度为 {} 位，应与 dd 的 {} 位相同", placeholder, plain));
+        }
+        if !ParsedPattern::with_case("D??d/", true).matches("DeAda0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e888") || ParsedPattern::with_case("D??d/", true).matches("dEaDa0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e888") {
+            return Err("区分大小写时 ? 占位符的匹配结果不正确".to_string());
+        }
+        let label = pattern::file_label("d??d/b??f");
+        if label != "dxxd-bxxf" || timestamps::parse_session_name(&format!("{}_20240101_120000Z", label)).is_none() {
+            return Err(format!("文件名中的模式为 {}", label));
+        }
+        
+        let dir = std::env::temp_dir().join(format!("fancy-wallet-test-placeholder-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).map_err(|e| format!("无法创建临时目录: {}", e))?;
+        let csv = dir.join("wallet_selftest.csv");
+        let wallet = Wallet {
+            index: 1,
+            address: Address::parse("deada0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0beef")?,
+            private_key: SecretHex::from("1".repeat(64)),
+            attempts: 1,
+            duration: 0,
+            matched_spans: Vec::new(),
+            rng_mode: RngMode::DeterministicTest,
+            test_wallet: true,
+            mnemonic: None,
+            chain_address: None,
+            matched_pattern: String::new(),
+            created_at: timestamps::now_rfc3339(),
+        };
+        let written = write_wallet_row(&csv, &wallet, "0xd??d/b??f", Chain::Ethereum, CsvPrivateKeys::Full);
+        let content = std::fs::read_to_string(&csv).unwrap_or_default();
+        let _ = std::fs::remove_dir_all(&dir);
+        written?;
+        let column = content.lines().nth(1).and_then(|row| row.split(',').nth(2)).unwrap_or_default().to_string();
+        if column != "0xd??d/b??f" {
+            return Err(format!("结果文件的模式列为 {:?}，应保留原始模式", column));
+        }
+        Ok(())
+    }
+
+    /**
+     * 分别指定前缀和后缀：两侧不能同时为空，难度按两侧的字符数计算，模式列记录为 <前缀>/<后缀>，
+     * 文件名中的分隔符换成连字符后仍能解析出会话时间
+     */
+    #[test]
+    fn prefix_suffix_patterns() -> Result<(), String> {
+        if ParsedPattern::new(&pattern::prefix_suffix_pattern(" ", "")).validate() != Err("前缀和后缀不能同时为空".to_string()) {
+            return Err("前缀和后缀同时为空时没有被拒绝".to_string());
+        }
+        for rejected in ["12g/", "/12g", "1/2/3", &format!("{}/{}", "1".repeat(21), "2".repeat(20))] {
+            if ParsedPattern::new(rejected).validate().is_ok() {
+                return Err(format!("无效的前缀/后缀模式 {:?} 没有被拒绝", rejected));
+            }
+        }
+        let combined = pattern::prefix_suffix_pattern("dead", "beef");
+        if combined != "dead/beef" || pattern::prefix_suffix_pattern("dead", "") != "dead/" {
+            return Err(format!("前缀和后缀组成的模式为 {}", combined));
+        }
+        ParsedPattern::new(&combined).validate()?;
+        // 3 位前缀加 3 位后缀与 3 位的前后缀相同模式难度相同
+        let (split, same) = (ParsedPattern::new("123/456").difficulty_bits(), ParsedPattern::new("123").difficulty_bits());
+        if split != same || ParsedPattern::new("123/").difficulty_bits() * 2.0 != same {
+            return Err(format!("前缀/后缀模式的难度为 {} 位，应为 {} 位", split, same));
+        }
+        let label = pattern::file_label(&combined);
+        let parsed = timestamps::parse_session_name(&format!("{}_20240101_120000Z", label));
+        if label != "dead-beef" || parsed.is_none() {
+            return Err(format!("文件名中的模式为 {}，会话时间解析结果为 {:?}", label, parsed.map(|t| t.utc)));
+        }
+        Ok(())
+    }
+
+    /**
+     * 私钥在内存中清零：SecretHex 和随机字节的缓冲区都实现了离开作用域时清零，
+     * 调试输出和日志中不出现私钥，序列化结果与原来的十六进制私钥相同
+     * 
+     * 开启 zeroize-check 时还直接读取内存，确认清零后的缓冲区中没有留下私钥。
+     */
+    #[test]
+    fn secret_zeroize() -> Result<(), String> {
+        assert_zeroize_on_drop::<SecretHex>();
+        assert_zeroize_on_drop::<Zeroizing<[u8; 32]>>();
+        if !std::mem::needs_drop::<SecretHex>() || !std::mem::needs_drop::<Wallet>() || !std::mem::needs_drop::<WalletFound>() {
+            return Err("SecretHex 或包含私钥的结构体没有 Drop 实现".to_string());
+        }
+        let (key, _) = KNOWN_KEYS[0];
+        let secret = SecretHex::from(key.to_string());
+        let debug = format!("{:?}", secret);
+        if debug.contains(key) {
+            return Err(format!("SecretHex 的调试输出包含私钥: {}", debug));
+        }
+        let json = serde_json::to_string(&secret).map_err(|e| e.to_string())?;
+        if json != format!("\"{}\"", key) || serde_json::from_str::<SecretHex>(&json).map_err(|e| e.to_string())? != secret {
+            return Err(format!("SecretHex 的序列化结果不是原来的私钥: {}", json));
+        }
+        let decoded = secret.decode().ok_or("SecretHex 无法解码为 32 字节")?;
+        if hex::encode(*decoded) != key {
+            return Err("SecretHex 解码后的字节与私钥不一致".to_string());
+        }
+        let mut cleared = secret.clone();
+        cleared.zeroize();
+        if !cleared.expose().is_empty() {
+            return Err("清零后的 SecretHex 仍有内容".to_string());
+        }
+        #[cfg(feature = "zeroize-check")]
+        check_zeroized_memory(key)?;
+        Ok(())
+    }
+
+    /**
+     * 钱包的找到时间和满足的模式：每个匹配在找到时各自取时间（晚于会话开始、依次递增），写入 CSV 和 JSON Lines；
+     * local 时带有时区偏移，解析后与 UTC 是同一时刻
+     */
+    #[test]
+    fn wallet_created_at() -> Result<(), String> {
+        with_data_dir("created-at", |dir| {
+            let config = SearchConfig {
+                limits: GenerationLimits { max_matches: Some(CREATED_AT_MATCHES), ..GenerationLimits::default() },
+                workers: Some(1),
+                ..SearchConfig::new("8".to_string())
+            };
+            let started = timestamps::now_rfc3339();
+            let mut wallets = Vec::new();
+            crate::engine::search(&config, &CancellationToken::new(), || false, |event| {
+                if let SearchEvent::Found(found) = event {
+                    std::thread::sleep(Duration::from_millis(5));
+                    wallets.push(found_wallet(found, "8", None, TimestampZone::Utc));
+                }
+            });
+            let times: Vec<_> = wallets.iter().filter_map(|wallet| timestamps::parse_rfc3339(&wallet.created_at)).collect();
+            let started = timestamps::parse_rfc3339(&started).ok_or("无法解析会话开始时间")?;
+            if times.len() != CREATED_AT_MATCHES as usize || times.first().is_none_or(|first| *first <= started) || times.windows(2).any(|pair| pair[0] >= pair[1]) {
+                return Err(format!("找到时间不是各个匹配各自的时间: {:?}", wallets.iter().map(|wallet| &wallet.created_at).collect::<Vec<_>>()));
+            }
+            if wallets.iter().any(|wallet| wallet.matched_pattern != "8") {
+                return Err("钱包没有记录满足的模式".to_string());
+            }
+            
+            let csv = dir.join("wallet_8_selftest.csv");
+            let jsonl = dir.join("wallet_8_selftest.jsonl");
+            for wallet in &wallets {
+                write_wallet_row(&csv, wallet, "8", Chain::Ethereum, CsvPrivateKeys::Full)?;
+                write_jsonl_row(&jsonl, wallet, "8", Chain::Ethereum)?;
+            }
+            let rows = std::fs::read_to_string(&csv).map_err(|e| e.to_string())?;
+            if rows.lines().skip(1).zip(&wallets).any(|(row, wallet)| !row.contains(&wallet.created_at)) {
+                return Err(format!("CSV 的时间列不是找到时间: {}", rows));
+            }
+            let lines = std::fs::read_to_string(&jsonl).map_err(|e| e.to_string())?;
+            for (line, wallet) in lines.lines().zip(&wallets) {
+                let record: serde_json::Value = serde_json::from_str(line).map_err(|e| e.to_string())?;
+                if record["created_at"] != wallet.created_at.as_str() || record["timestamp"] != wallet.created_at.as_str() || record["matched_pattern"] != "8" {
+                    return Err(format!("JSON Lines 的时间或模式不正确: {}", line));
+                }
+            }
+            
+            let now = chrono::Utc::now();
+            let local = TimestampZone::Local.format(now);
+            if local.ends_with('Z') || timestamps::parse_rfc3339(&local) != timestamps::parse_rfc3339(&TimestampZone::Utc.format(now)) {
+                return Err(format!("本机时区的时间 {} 与 UTC 不是同一时刻", local));
+            }
+            Ok(())
+        })
+    }
+
+    /**
+     * 单独的 JSON 文件：wallet_<地址>.json 能解析回原来的钱包并带有模式、链和找到时间；同名文件内容相同时不重复写入，
+     * 内容不同时返回错误且不覆盖；emit_json_files 不能与打码或省略私钥的 CSV 同时使用
+     */
+    #[test]
+    fn wallet_json_files() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        let masked = GenerationParams { pattern: "8".to_string(), emit_json_files: Some(true), csv_private_keys: Some(CsvPrivateKeys::Masked), ..GenerationParams::default() };
+        if masked.validate().is_ok() {
+            return Err("emit_json_files 与 csv_private_keys: masked 同时使用没有被拒绝".to_string());
+        }
+        with_data_dir("wallet_json", |dir| {
+            let (key, address) = KNOWN_KEYS[0];
+            let secret = PrivKeyHex::parse(key)?.secret_key();
+            let wallet = Wallet {
+                index: 1,
+                address: Address::from_public_key(&PublicKey::from_secret_key(&secp, &secret)),
+                private_key: SecretHex::from(key.to_string()),
+                attempts: 1000,
+                duration: 10,
+                matched_spans: Vec::new(),
+                rng_mode: RngMode::default(),
+                test_wallet: false,
+                mnemonic: None,
+                chain_address: None,
+                matched_pattern: "7e5f".to_string(),
+                created_at: timestamps::now_rfc3339(),
+            };
+            let json_dir = dir.join("json");
+            let (path, bytes) = write_wallet_json_file(&json_dir, &wallet, "7e5f", Chain::Ethereum)?;
+            if path != json_dir.join(format!("wallet_0x{}.json", address)) || bytes == 0 {
+                return Err(format!("JSON 文件写入 {}（{} 字节）", path.display(), bytes));
+            }
+            let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            let parsed: Wallet = serde_json::from_str(&content).map_err(|e| format!("无法解析 JSON 文件: {}", e))?;
+            let value: serde_json::Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+            if parsed.address != wallet.address || parsed.private_key != wallet.private_key || parsed.created_at != wallet.created_at {
+                return Err(format!("解析回的钱包与写入的不一致: {:?}", parsed));
+            }
+            if value["pattern"] != "7e5f" || value["chain"] != "ethereum" || value["created_at"] != wallet.created_at.as_str() {
+                return Err(format!("JSON 文件缺少模式、链或找到时间: {}", content));
+            }
+            let (_, again) = write_wallet_json_file(&json_dir, &wallet, "7e5f", Chain::Ethereum)?;
+            if again != 0 {
+                return Err(format!("内容相同的 JSON 文件又写入了 {} 字节", again));
+            }
+            let changed = Wallet { attempts: 2000, ..wallet.clone() };
+            if write_wallet_json_file(&json_dir, &changed, "7e5f", Chain::Ethereum).is_ok() {
+                return Err("内容不同的同名 JSON 文件被覆盖".to_string());
+            }
+            if std::fs::read_to_string(&path).map_err(|e| e.to_string())? != content {
+                return Err("写入失败后原来的 JSON 文件被修改".to_string());
+            }
+            Ok(())
+        })
+    }
+
+    /**
+     * wallets.db 存储后端：与 CSV 和 JSON Lines 同时写入同一批钱包，打码私钥的会话只记录地址；
+     * results_db 不能与 dry_run 同时使用
+     */
+    #[test]
+    fn results_db_backend() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        let dry_run = GenerationParams { pattern: "8".to_string(), dry_run: Some(true), results_db: Some(true), ..GenerationParams::default() };
+        if dry_run.validate().is_ok() {
+            return Err("results_db 与 dry_run 同时使用没有被拒绝".to_string());
+        }
+        with_data_dir("results_db_backend", |dir| {
+            let wallets: Vec<Wallet> = KNOWN_KEYS
+                .iter()
+                .take(2)
+                .enumerate()
+                .map(|(index, (key, _))| {
+                    let secret = PrivKeyHex::parse(key)?.secret_key();
+                    Ok(Wallet {
+                        index: index as u64 + 1,
+                        address: Address::from_public_key(&PublicKey::from_secret_key(&secp, &secret)),
+                        private_key: SecretHex::from(key.to_string()),
+                        attempts: 1000,
+                        duration: 10,
+                        matched_spans: Vec::new(),
+                        rng_mode: RngMode::default(),
+                        test_wallet: false,
+                        mnemonic: None,
+                        chain_address: None,
+                        matched_pattern: "8".to_string(),
+                        created_at: timestamps::now_rfc3339(),
+                    })
+                })
+                .collect::<Result<_, String>>()?;
+            let rows: Vec<(&Wallet, &str)> = wallets.iter().map(|wallet| (wallet, "8")).collect();
+            let save_path = Some(dir.to_string_lossy().to_string());
+            let resolve = |stamp: &str, private_keys: CsvPrivateKeys| {
+                resolve_session_output(save_path.clone(), Some("8"), Chain::Ethereum, None, RngMode::default(), stamp, filename_template::DEFAULT_TEMPLATE, None, private_keys)
+            };
+            let full = resolve("20260101_000000", CsvPrivateKeys::Full)?;
+            let db_path = full.root.wallets_dir.join(results_db::DB_FILE);
+            let database = results_db::SessionDb { path: db_path.clone(), session_id: "full".to_string(), private_keys: true, in_keystore: false };
+            let full = full.with_format(OutputFormat::Both).with_database(Some(database.clone()));
+            if full.backends() != [ResultBackend::Csv, ResultBackend::Jsonl, ResultBackend::Database] {
+                return Err(format!("存储后端为 {:?}", full.backends()));
+            }
+            std::fs::create_dir_all(&full.root.wallets_dir).map_err(|e| e.to_string())?;
+            save_wallets_to_file(&rows, &full)?;
+            let csv = std::fs::read_to_string(&full.csv_path).map_err(|e| e.to_string())?;
+            let jsonl = std::fs::read_to_string(full.jsonl_path()).map_err(|e| e.to_string())?;
+            if csv.lines().count() != 3 || jsonl.lines().count() != 2 || results_db::count_wallets(&db_path, None)? != 2 {
+                return Err("CSV、JSON Lines 和 wallets.db 没有都写入这一批钱包".to_string());
+            }
+
+            let masked = resolve("20260101_000001", CsvPrivateKeys::Masked)?
+                .with_database(Some(results_db::SessionDb { session_id: "masked".to_string(), private_keys: false, ..database }));
+            save_wallets_to_file(&rows[..1], &masked)?;
+            let stored = results_db::query_wallets(&db_path, None, 10, 0, true)?;
+            let keys: Vec<(&str, bool)> = stored.iter().map(|stored| (stored.session_id.as_str(), stored.private_key.is_some())).collect();
+            if keys != [("masked", false), ("full", true), ("full", true)] {
+                return Err(format!("wallets.db 中的会话和私钥为 {:?}", keys));
+            }
+            Ok(())
+        })
+    }
+
+    /**
+     * 波场靓号：模式与 T 之后的 Base58 地址比较，不是 Base58 的字符、0x 前缀、正则表达式和 leading_zeros 被拒绝；
+     * 找到的地址以 T 开头写入结果文件并可以由私钥重新推导，助记词不能用于波场
+     */
+    #[test]
+    fn tron_addresses() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        let encoder = Chain::Tron.encoder().ok_or("波场没有地址编码器")?;
+        if encoder.alphabet() != PatternAlphabet::Base58 {
+            return Err("波场地址的模式字符集不是 Base58".to_string());
+        }
+        for (private_key, _) in KNOWN_KEYS {
+            let key = PrivKeyHex::parse(private_key)?.secret_key();
+            let address = Chain::Tron.format_address(Address::from_secret_key(&secp, &key));
+            let text = encoder.match_text(&PublicKey::from_secret_key(&secp, &key), true);
+            if address.strip_prefix('T') != Some(text.as_str()) {
+                return Err(format!("私钥 {} 的比较文本为 {}，而波场地址为 {}", private_key, text, address));
+            }
+        }
+        let base58 = |text: &str, pattern_type: PatternType| ParsedPattern::with_options(text, pattern_type, false).with_alphabet(PatternAlphabet::Base58);
+        for (text, pattern_type) in [
+            ("0abc", PatternType::Standard),
+            ("abOc", PatternType::Standard),
+            ("Iabc", PatternType::Standard),
+            ("l", PatternType::Standard),
+            ("0xabc", PatternType::Standard),
+            ("^ab", PatternType::Regex),
+            ("zeros>=2", PatternType::LeadingZeros),
+        ] {
+            if base58(text, pattern_type).validate().is_ok() {
+                return Err(format!("无效的波场模式 {} 没有被拒绝", text));
+            }
+        }
+        if let Err(e) = base58("Kz", PatternType::Standard).validate() {
+            return Err(format!("有效的波场模式 Kz 被拒绝: {}", e));
+        }
+        let mnemonic_config = SearchConfig { chain: Chain::Tron, key_source: KeySourceKind::Mnemonic, ..SearchConfig::new("a") };
+        if mnemonic_config.validate().is_ok() {
+            return Err("助记词被用于波场".to_string());
+        }
+        
+        let config = SearchConfig {
+            chain: Chain::Tron,
+            limits: GenerationLimits { max_matches: Some(1), ..GenerationLimits::default() },
+            rng_mode: RngMode::DeterministicTest,
+            test_seed: Some(19),
+            ..SearchConfig::new("a/")
+        };
+        let mut found = None;
+        crate::engine::run(&config, &CancellationToken::new(), |event| {
+            if let SearchEvent::Found(key) = event {
+                found = Some(key);
+            }
+        })?;
+        let found = found.ok_or("没有找到波场地址")?;
+        let address = Chain::Tron.format_address(found.address);
+        // 不区分大小写时 a 与 A 都满足模式
+        if !address.to_lowercase().starts_with("ta") {
+            return Err(format!("找到的波场地址 {} 不满足模式 a/", address));
+        }
+        let dir = std::env::temp_dir().join(format!("fancy-wallet-test-tron-{}", std::process::id()));
+        let result = (|| {
+            let file = Chain::Tron.dir(&dir).join("wallet_tron_selftest.csv");
+            std::fs::create_dir_all(Chain::Tron.dir(&dir)).map_err(|e| format!("无法创建临时目录: {}", e))?;
+            let wallet = Wallet {
+                index: 1,
+                address: found.address,
+                private_key: found.private_key.clone(),
+                attempts: found.attempts,
+                duration: found.duration,
+                matched_spans: found.matched_spans.clone(),
+                rng_mode: found.rng_mode,
+                test_wallet: true,
+                mnemonic: None,
+                chain_address: Some(address.clone()),
+                matched_pattern: String::new(),
+                created_at: timestamps::now_rfc3339(),
+            };
+            write_wallet_row(&file, &wallet, "a/", Chain::Tron, CsvPrivateKeys::Full)?;
+            let content = std::fs::read_to_string(&file).map_err(|e| format!("无法读取结果文件: {}", e))?;
+            if !content.lines().nth(1).is_some_and(|row| row.starts_with(&format!("{},", address)) && row.contains(",tron,")) {
+                return Err(format!("波场结果文件的记录不正确: {}", content));
+            }
+            let report = saved_wallets::verify_file(&file)?;
+            if report.valid_rows != 1 || !report.corrupt_rows.is_empty() || report.chain != Some(Chain::Tron) {
+                return Err(format!("波场结果文件校验到 {} 行有效: {:?}", report.valid_rows, report.corrupt_rows));
+            }
+            Ok(())
+        })();
+        let _ = std::fs::remove_dir_all(&dir);
+        result?;
+        Ok(())
+    }
+
+    /**
+     * 比特币靓号：已知私钥的 P2PKH、P2WPKH 地址和 WIF 私钥，地址解析拒绝校验和错误；Bech32 模式不能区分大小写、
+     * 不能包含 bc1q 和 Bech32 以外的字符；找到的 P2WPKH 地址写入结果文件后 WIF 列可以还原私钥，记录可以由私钥重新校验
+     */
+    #[test]
+    fn bitcoin_addresses() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        let (p2pkh, p2wpkh) = (BitcoinAddressType::P2pkh.encoder(), BitcoinAddressType::P2wpkh.encoder());
+        for (private_key, legacy, segwit, wif) in BITCOIN_KEY_VECTORS {
+            let key = PrivKeyHex::parse(private_key)?.secret_key();
+            let public_key = PublicKey::from_secret_key(&secp, &key);
+            let hash = p2pkh.address(&public_key);
+            if p2pkh.encode(hash) != *legacy || p2wpkh.encode(hash) != *segwit {
+                return Err(format!("私钥 {} 的地址为 {} / {}，期望 {} / {}", private_key, p2pkh.encode(hash), p2wpkh.encode(hash), legacy, segwit));
+            }
+            if legacy.strip_prefix('1') != Some(p2pkh.match_text(&public_key, true).as_str())
+                || segwit.strip_prefix("bc1q") != Some(p2wpkh.match_text(&public_key, false).as_str())
+            {
+                return Err(format!("私钥 {} 的比较文本不是去掉固定开头的地址", private_key));
+            }
+            let secret = key.secret_bytes();
+            if bitcoin::wif(&secret).as_str() != *wif || *bitcoin::parse_wif(wif)? != secret {
+                return Err(format!("私钥 {} 的 WIF 格式不是 {}", private_key, wif));
+            }
+            for address in [legacy, segwit] {
+                if Chain::from_address(address) != Some(Chain::Bitcoin) || Chain::Bitcoin.parse_address(address)? != hash {
+                    return Err(format!("地址 {} 没有解析为私钥 {} 的 HASH160", address, private_key));
+                }
+            }
+        }
+        if let Some(address) = INVALID_BITCOIN_ADDRESSES.iter().find(|address| bitcoin::parse_address(address).is_ok()) {
+            return Err(format!("无效的比特币地址 {} 没有被拒绝", address));
+        }
+        let bech32 = |text: &str, case_sensitive: bool| ParsedPattern::with_options(text, PatternType::Standard, case_sensitive).with_alphabet(PatternAlphabet::Bech32);
+        for (text, case_sensitive) in [("qb", false), ("1q", false), ("io", false), ("bc1qa", false), ("qq", true), ("0xqq", false)] {
+            if bech32(text, case_sensitive).validate().is_ok() {
+                return Err(format!("无效的 Bech32 模式 {} 没有被拒绝", text));
+            }
+        }
+        if let Err(e) = bech32("QQ/7l", false).validate() {
+            return Err(format!("有效的 Bech32 模式 QQ/7l 被拒绝: {}", e));
+        }
+
+        let config = SearchConfig {
+            chain: Chain::Bitcoin,
+            bitcoin_address: BitcoinAddressType::P2wpkh,
+            limits: GenerationLimits { max_matches: Some(1), ..GenerationLimits::default() },
+            rng_mode: RngMode::DeterministicTest,
+            test_seed: Some(23),
+            ..SearchConfig::new("a/")
+        };
+        let mut found = None;
+        crate::engine::run(&config, &CancellationToken::new(), |event| {
+            if let SearchEvent::Found(key) = event {
+                found = Some(key);
+            }
+        })?;
+        let found = found.ok_or("没有找到比特币地址")?;
+        let address = p2wpkh.encode(found.address);
+        if !address.starts_with("bc1qa") {
+            return Err(format!("找到的比特币地址 {} 不满足模式 a/", address));
+        }
+        let dir = std::env::temp_dir().join(format!("fancy-wallet-test-bitcoin-{}", std::process::id()));
+        let result = (|| {
+            let file = Chain::Bitcoin.dir(&dir).join("wallet_bitcoin_selftest.csv");
+            std::fs::create_dir_all(Chain::Bitcoin.dir(&dir)).map_err(|e| format!("无法创建临时目录: {}", e))?;
+            let wallet = Wallet {
+                index: 1,
+                address: found.address,
+                private_key: found.private_key.clone(),
+                attempts: found.attempts,
+                duration: found.duration,
+                matched_spans: found.matched_spans.clone(),
+                rng_mode: found.rng_mode,
+                test_wallet: true,
+                mnemonic: None,
+                chain_address: Some(address.clone()),
+                matched_pattern: String::new(),
+                created_at: timestamps::now_rfc3339(),
+            };
+            write_wallet_row(&file, &wallet, "a/", Chain::Bitcoin, CsvPrivateKeys::Full)?;
+            let content = std::fs::read_to_string(&file).map_err(|e| format!("无法读取结果文件: {}", e))?;
+            let mut lines = content.lines();
+            if lines.next() != Some(saved_wallets::CSV_HEADER) {
+                return Err("比特币结果文件的标题不正确".to_string());
+            }
+            let fields: Vec<&str> = lines.next().unwrap_or_default().split(',').collect();
+            let [row_address, private_key, "a/", "1", "bitcoin", _, "", wif, _, _, _] = fields[..] else {
+                return Err(format!("比特币结果文件的行格式不正确: {:?}", fields));
+            };
+            if row_address != address || hex::encode(*bitcoin::parse_wif(wif)?) != private_key {
+                return Err(format!("比特币结果文件的地址 {} 或 WIF 私钥与找到的钱包不一致", row_address));
+            }
+            let report = saved_wallets::verify_file(&file)?;
+            if report.valid_rows != 1 || !report.corrupt_rows.is_empty() || report.chain != Some(Chain::Bitcoin) {
+                return Err(format!("比特币结果文件校验到 {} 行有效: {:?}", report.valid_rows, report.corrupt_rows));
+            }
+            Ok(())
+        })();
+        let _ = std::fs::remove_dir_all(&dir);
+        result?;
+        Ok(())
+    }
+
+    /**
+     * Solana 靓号：已知种子的 ed25519 公钥和 Base58 地址，密钥对 JSON 可以还原私钥，无效地址被拒绝；模式总是区分大小写、
+     * 最多 44 个字符且不能使用正则表达式；找到的地址写入结果文件和密钥对文件后均可以由私钥重新校验
+     */
+    #[test]
+    fn solana_addresses() -> Result<(), String> {
+        for (seed, public_key, address) in SOLANA_KEY_VECTORS {
+            let seed: [u8; 32] = hex::decode(seed).ok().and_then(|bytes| bytes.try_into().ok()).ok_or("种子不是 32 字节的十六进制")?;
+            let derived = solana::public_key(&seed);
+            if hex::encode(derived) != *public_key || solana::encode(&derived) != *address {
+                return Err(format!("种子 {} 的公钥为 {}（{}），期望 {}", hex::encode(seed), hex::encode(derived), solana::encode(&derived), address));
+            }
+            if Chain::from_address(address) != Some(Chain::Solana) || Chain::Solana.parse_address(address)? != solana::address_id(&derived) {
+                return Err(format!("地址 {} 没有解析为 Solana 公钥的标识", address));
+            }
+            if *solana::parse_keypair(&solana::keypair_json(&seed))? != seed {
+                return Err("密钥对 JSON 没有还原出原来的私钥".to_string());
+            }
+        }
+        if let Some(address) = INVALID_SOLANA_ADDRESSES.iter().find(|address| solana::parse_address(address).is_ok()) {
+            return Err(format!("无效的 Solana 地址 {} 没有被拒绝", address));
+        }
+        // 公钥与私钥不一致的密钥对
+        let mut tampered: Vec<u8> = serde_json::from_str(&solana::keypair_json(&[7u8; 32])).map_err(|e| e.to_string())?;
+        tampered[63] ^= 1;
+        if solana::parse_keypair(&serde_json::to_string(&tampered).map_err(|e| e.to_string())?).is_ok() {
+            return Err("公钥被修改的密钥对没有被拒绝".to_string());
+        }
+        if !Chain::Solana.case_sensitive(false) || Chain::Solana.alphabet(BitcoinAddressType::default()) != PatternAlphabet::Base58PublicKey {
+            return Err("Solana 的模式没有按区分大小写的 Base58 公钥比较".to_string());
+        }
+        let solana_pattern = |text: &str, pattern_type: PatternType| {
+            ParsedPattern::with_options(text, pattern_type, true).with_alphabet(PatternAlphabet::Base58PublicKey)
+        };
+        let too_long = "A".repeat(solana::MAX_ADDRESS_LENGTH + 1);
+        for (text, pattern_type) in [("0xSo", PatternType::Standard), ("So0", PatternType::Standard), (too_long.as_str(), PatternType::Standard), ("^So", PatternType::Regex)] {
+            if solana_pattern(text, pattern_type).validate().is_ok() {
+                return Err(format!("无效的 Solana 模式 {} 没有被拒绝", text));
+            }
+        }
+        if let Err(e) = solana_pattern("So/La", PatternType::Standard).validate() {
+            return Err(format!("有效的 Solana 模式 So/La 被拒绝: {}", e));
+        }
+
+        let config = SearchConfig {
+            chain: Chain::Solana,
+            limits: GenerationLimits { max_matches: Some(1), ..GenerationLimits::default() },
+            rng_mode: RngMode::DeterministicTest,
+            test_seed: Some(29),
+            ..SearchConfig::new("a")
+        };
+        let mut found = None;
+        crate::engine::run(&config, &CancellationToken::new(), |event| {
+            if let SearchEvent::Found(key) = event {
+                found = Some(key);
+            }
+        })?;
+        let found = found.ok_or("没有找到 Solana 地址")?;
+        let address = found.chain_address.clone().ok_or("找到的 Solana 钱包没有地址文本")?;
+        if !address.starts_with('a') || Chain::Solana.parse_address(&address)? != found.address {
+            return Err(format!("找到的 Solana 地址 {} 不满足模式 a 或与标识不一致", address));
+        }
+        let dir = std::env::temp_dir().join(format!("fancy-wallet-test-solana-{}", std::process::id()));
+        let result = (|| {
+            let file = Chain::Solana.dir(&dir).join("wallet_solana_selftest.csv");
+            std::fs::create_dir_all(Chain::Solana.dir(&dir)).map_err(|e| format!("无法创建临时目录: {}", e))?;
+            let wallet = Wallet {
+                index: 1,
+                address: found.address,
+                private_key: found.private_key.clone(),
+                attempts: found.attempts,
+                duration: found.duration,
+                matched_spans: found.matched_spans.clone(),
+                rng_mode: found.rng_mode,
+                test_wallet: true,
+                mnemonic: None,
+                chain_address: Some(address.clone()),
+                matched_pattern: String::new(),
+                created_at: timestamps::now_rfc3339(),
+            };
+            write_wallet_row(&file, &wallet, "a", Chain::Solana, CsvPrivateKeys::Full)?;
+            let content = std::fs::read_to_string(&file).map_err(|e| format!("无法读取结果文件: {}", e))?;
+            if !content.lines().nth(1).is_some_and(|row| row.starts_with(&format!("{},", address)) && row.contains(",solana,")) {
+                return Err(format!("Solana 结果文件的记录不正确: {}", content));
+            }
+            let report = saved_wallets::verify_file(&file)?;
+            if report.valid_rows != 1 || !report.corrupt_rows.is_empty() || report.chain != Some(Chain::Solana) {
+                return Err(format!("Solana 结果文件校验到 {} 行有效: {:?}", report.valid_rows, report.corrupt_rows));
+            }
+            let seed = found.private_key.decode().ok_or("私钥不是 32 字节的十六进制")?;
+            let keypair_dir = dir.join(solana::KEYPAIR_DIR);
+            let (path, _) = solana::write_keypair_file(&keypair_dir, &address, &solana::keypair_json(&seed))?;
+            let keypair = std::fs::read_to_string(&path).map_err(|e| format!("无法读取密钥对文件: {}", e))?;
+            if *solana::parse_keypair(&keypair)? != *seed {
+                return Err("密钥对文件没有还原出找到的私钥".to_string());
+            }
+            if solana::write_keypair_file(&keypair_dir, &address, &solana::keypair_json(&seed)).is_ok() {
+                return Err("已存在的密钥对文件被覆盖".to_string());
+            }
+            Ok(())
+        })();
+        let _ = std::fs::remove_dir_all(&dir);
+        result?;
+        Ok(())
+    }
+
+    /**
+     * 由助记词和派生路径推导地址：BIP-32 测试向量、常见钱包的默认账户，以及指出位置的词表和派生路径错误
+     */
+    #[test]
+    fn mnemonic_derivation() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        let seed = hex::decode(BIP32_VECTOR_SEED).map_err(|e| e.to_string())?;
+        for (path, expected) in BIP32_VECTOR_KEYS {
+            let key = mnemonic::derive_path(&secp, &seed, &mnemonic::parse_derivation_path(path)?).ok_or("派生私钥失败")?;
+            if hex::encode(key.secret_bytes()) != *expected {
+                return Err(format!("{} 的私钥为 {}，应为 {}", path, hex::encode(key.secret_bytes()), expected));
+            }
+        }
+
+        let (abandon, abandon_address) = MNEMONIC_ADDRESS_VECTORS[0];
+        let (test_junk, _) = MNEMONIC_ADDRESS_VECTORS[1];
+        let derived = derive_address_from_mnemonic(abandon.to_string(), mnemonic::ETHEREUM_DERIVATION_PATH.to_string(), None, None)?;
+        if derived.address != abandon_address || derived.private_key.is_some() {
+            return Err(format!("\"{}\" 派生的地址为 {}（应为 {}），或没有要求时返回了私钥", abandon, derived.address, abandon_address));
+        }
+        let second = derive_address_from_mnemonic(format!(" {} ", test_junk.to_uppercase()), "m/44h/60H/0'/0/1".to_string(), Some(String::new()), Some(true))?;
+        let private_key = second.private_key.as_ref().map(|key| key.expose().to_string()).unwrap_or_default();
+        if second.address != MNEMONIC_SCAN_INDEX_1 || second.derivation_path != "m/44'/60'/0'/0/1" || verify_private_key(private_key)? != MNEMONIC_SCAN_INDEX_1 {
+            return Err(format!("m/44h/60H/0'/0/1 派生的地址为 {}（路径 {}），应为 {}", second.address, second.derivation_path, MNEMONIC_SCAN_INDEX_1));
+        }
+        let with_passphrase = derive_address_from_mnemonic(abandon.to_string(), mnemonic::ETHEREUM_DERIVATION_PATH.to_string(), Some("TREZOR".to_string()), None)?;
+        if with_passphrase.address == abandon_address {
+            return Err("口令没有影响派生的地址".to_string());
+        }
+
+        let misspelled = abandon.replacen("abandon abandon abandon abandon abandon abandon abandon", "abandon abandon abandon abandon abandon abandon abandonx", 1);
+        let errors = [
+            (misspelled.as_str(), mnemonic::ETHEREUM_DERIVATION_PATH, "第 7 个词 abandonx"),
+            (abandon, "m/44'/x/0", "第 2 段 'x'"),
+            (abandon, "m/44'/60'/2147483648", "第 3 段 '2147483648'"),
+            (abandon, "m/44'//0", "第 2 段 ''"),
+            (abandon, "44'/60'/0'/0/0", "必须以 m 开头"),
+        ];
+        for (phrase, path, expected) in errors {
+            match derive_address_from_mnemonic(phrase.to_string(), path.to_string(), None, None) {
+                Err(e) if e.message().contains(expected) => {}
+                other => return Err(format!("助记词 \"{}\"、路径 {} 的结果为 {:?}，应为包含 \"{}\" 的错误", phrase, path, other.map(|derived| derived.address), expected)),
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * 随机数来源：确定性测试模式按种子复现且私钥带有 TESTONLY 水印，其它模式不会产生水印；
+     * 测试钱包的水印出现在 CSV、钱包 JSON、恢复单、加密导出的明文和会话清单中，去掉 entropy 列的旧格式文件仍能按私钥识别
+     */
+    #[test]
+    fn entropy_watermark() -> Result<(), String> {
+        if entropy::validate(RngMode::ReseededCsprng, Some(1)).is_ok() {
+            return Err("test_seed 与非测试模式一起使用时没有被拒绝".to_string());
+        }
+        let run = |rng_mode: RngMode, test_seed: Option<u64>| -> Result<crate::engine::SearchReport, String> {
+            let config = crate::engine::SearchConfig {
+                limits: crate::engine::GenerationLimits { max_matches: Some(2), ..Default::default() },
+                rng_mode,
+                test_seed,
+                ..crate::engine::SearchConfig::new("8")
+            };
+            crate::engine::run(&config, &CancellationToken::new(), |_| {})
+        };
+        let keys = |report: &crate::engine::SearchReport| report.last_found.as_ref().map(|found| found.private_key.expose().to_string());
+        let first = run(RngMode::DeterministicTest, Some(42))?;
+        let second = run(RngMode::DeterministicTest, Some(42))?;
+        let test_key = keys(&first).ok_or("确定性测试模式没有找到匹配")?;
+        if keys(&second).as_ref() != Some(&test_key) || first.attempts != second.attempts {
+            return Err("相同种子的确定性测试模式没有得到相同的结果".to_string());
+        }
+        if !entropy::is_test_key(&test_key) || !first.entropy.test_watermark || first.entropy.test_seed != Some(42) {
+            return Err(format!("确定性测试模式的私钥 {} 或搜索报告没有测试水印", test_key));
+        }
+        for mode in [RngMode::OsDirect, RngMode::ReseededCsprng] {
+            let report = run(mode, None)?;
+            let key = keys(&report).ok_or_else(|| format!("{} 模式没有找到匹配", mode.as_str()))?;
+            if entropy::is_test_key(&key) || report.entropy.test_watermark || report.entropy.rng_mode != mode {
+                return Err(format!("{} 模式的结果带有测试水印", mode.as_str()));
+            }
+        }
+        
+        with_data_dir("entropy", |dir| {
+            let secret = SecretKey::from_slice(&hex::decode(&test_key).map_err(|e| e.to_string())?).map_err(|e| e.to_string())?;
+            let address = Address::parse(&public_key_to_checksum_address(&PublicKey::from_secret_key(&Secp256k1::new(), &secret))).map_err(|e| e.to_string())?;
+            let wallet = Wallet {
+                index: 1,
+                address,
+                private_key: SecretHex::from(test_key.clone()),
+                attempts: 1,
+                duration: 0,
+                matched_spans: Vec::new(),
+                rng_mode: RngMode::DeterministicTest,
+                test_wallet: true,
+                mnemonic: None,
+                chain_address: None,
+                matched_pattern: String::new(),
+                created_at: timestamps::now_rfc3339(),
+            };
+            let csv = dir.join("wallet_TEST-ONLY_8_selftest.csv");
+            write_wallet_row(&csv, &wallet, "8", Chain::Ethereum, CsvPrivateKeys::Full)?;
+            let saved = saved_wallets::read_all(&csv)?;
+            let text = std::fs::read_to_string(&csv).map_err(|e| e.to_string())?;
+            let mut marked = vec![
+                ("csv", text.contains(RngMode::DeterministicTest.as_str()) && saved.iter().all(|w| w.test_wallet)),
+                ("wallet_json", serde_json::to_string(&wallet).map_err(|e| e.to_string())?.contains("\"test_wallet\":true")),
+            ];
+            
+            // 旧格式（没有 entropy 列）的文件中只能按私钥的水印识别
+            let legacy = dir.join("wallet_legacy.csv");
+            std::fs::write(&legacy, format!("address,private_key,pattern\n{},{},8\n", address, test_key)).map_err(|e| e.to_string())?;
+            marked.push(("legacy_csv", saved_wallets::read_all(&legacy)?.iter().all(|w| w.test_wallet && w.entropy.is_none())));
+            
+            let sheet = recovery::RecoverySheet {
+                address,
+                pattern: "8".to_string(),
+                secret: None,
+                derivation_path: None,
+                checksum_variant: "EIP-55".to_string(),
+                created_at: String::new(),
+                test_wallet: saved.iter().all(|w| w.test_wallet),
+            };
+            marked.push(("recovery_sheet", recovery::render_html(&sheet)?.contains(entropy::TEST_WATERMARK)));
+            
+            let results = pgp_export::ConsolidatedResults::new("TEST-ONLY_8_selftest", saved, None);
+            let plaintext = serde_json::to_string(&results).map_err(|e| e.to_string())?;
+            marked.push(("pgp_plaintext", results.test_watermark.is_some() && results.wallets.iter().all(|w| w.test_wallet) && plaintext.contains(entropy::TEST_WATERMARK)));
+            
+            let stop = crate::stop::StopRecord { reason: crate::stop::StopReason::MaxMatches, detail: None };
+            let details = manifest::SessionDetails {
+                template: None,
+                chain: Chain::Ethereum,
+                matcher: None,
+                score_weights: None,
+                collection: None,
+                keccak_backend: None,
+                entropy: Some(&first.entropy),
+                clock_jumps: &[],
+                worker_failures: &[],
+            };
+            let manifest_path = dir.join("manifest_TEST-ONLY_8_selftest.json");
+            manifest::write_session_manifest(&manifest_path, "8", &[csv], &stop, &details)?;
+            let manifest_text = std::fs::read_to_string(&manifest_path).map_err(|e| e.to_string())?;
+            marked.push(("manifest", manifest_text.contains("\"test_watermark\": true")));
+            
+            if let Some((format, _)) = marked.iter().find(|(_, ok)| !ok) {
+                return Err(format!("{} 中没有测试水印", format));
+            }
+            Ok(())
+        })
+    }
+
+    /**
+     * 最后找到的钱包：还没有找到时返回说明，可以隐去私钥，新的会话开始时清除已经结束的会话
+     */
+    #[test]
+    fn last_wallet_cases() -> Result<(), String> {
+        let _sessions = lock_sessions();
+        let secp = Secp256k1::new();
+        let session_id = format!("self_test_last_{}", chrono::Utc::now().format("%Y%m%d_%H%M%S%3fZ"));
+        let session = sessions::register(&session_id, SessionKind::Generation)?;
+        reset_last_wallets(&session_id);
+        let empty = last_wallet(&session_id, true)?;
+        if empty.wallet.is_some() || empty.message.is_none() || !empty.running {
+            return Err(format!("还没有找到钱包时的结果不正确: {:?}", empty));
+        }
+        let secret_key = SecretKey::from_slice(&[0x43; 32]).map_err(|e| e.to_string())?;
+        let wallet = Wallet {
+            index: 1,
+            address: Address::from_public_key(&PublicKey::from_secret_key(&secp, &secret_key)),
+            private_key: SecretHex::encode(&secret_key.secret_bytes()),
+            attempts: 10,
+            duration: 5,
+            matched_spans: Vec::new(),
+            rng_mode: RngMode::default(),
+            test_wallet: false,
+            mnemonic: None,
+            chain_address: None,
+            matched_pattern: String::new(),
+            created_at: timestamps::now_rfc3339(),
+        };
+        record_last_wallet(WalletFound::new(&session_id, &wallet, "8", None, None, &ScoreWeights::default(), true));
+        drop(session);
+        let full = last_wallet(&session_id, false)?;
+        if full.wallet.as_ref().and_then(|found| found.private_key.as_ref()) != Some(&wallet.private_key) || full.running {
+            return Err(format!("会话结束后读取的钱包不正确: {:?}", full.wallet.map(|found| found.address)));
+        }
+        let redacted = serde_json::to_string(&last_wallet(&session_id, true)?).map_err(|e| e.to_string())?;
+        if redacted.contains(wallet.private_key.expose()) || !redacted.contains(&wallet.address.to_checksum()) {
+            return Err("隐去私钥的结果仍带有私钥".to_string());
+        }
+        // 新的会话开始时清除已经结束的会话
+        let next_id = format!("{}_next", session_id);
+        let next = sessions::register(&next_id, SessionKind::Generation)?;
+        reset_last_wallets(&next_id);
+        if last_wallet(&session_id, true).is_ok() {
+            return Err("新的会话开始后仍保留已经结束的会话的钱包".to_string());
+        }
+        drop(next);
+        Ok(())
+    }
+
+    /**
+     * 生成状态：进度写入共享状态，读取时反映停止请求，会话结束后注销
+     */
+    #[test]
+    fn generation_status() -> Result<(), String> {
+        let _sessions = lock_sessions();
+        let session_id = format!("self_test_status_{}", chrono::Utc::now().format("%Y%m%d_%H%M%S%3fZ"));
+        let session = sessions::register(&session_id, SessionKind::Generation)?;
+        let published = PublishedGeneration::new(ActiveGeneration {
+            session_id: session_id.clone(),
+            params: GenerationParams { pattern: "8888".to_string(), ..GenerationParams::default() },
+            template: None,
+            started_at: timestamps::now_rfc3339(),
+            attempts: 0,
+            matches: 0,
+            duration: 0,
+            attempts_per_second: None,
+            paused: false,
+            cancelled: false,
+            live: sessions::LiveStatus::default(),
+        });
+        let progress = SearchProgress {
+            attempts: 5_000,
+            matches: 1,
+            duration: 2_000,
+            paused_duration: 0,
+            rejected_keys: 0,
+            rejection_rate: 0.0,
+            matched: false,
+            best_zero_nibbles: None,
+        };
+        published.update(&progress, Some(2_500.0));
+        sessions::request_stop(Some(&session_id), StopReason::UserUi, None)?;
+        let status = active_generation_list().into_iter().find(|status| status.session_id == session_id).ok_or("会话没有出现在生成状态中")?;
+        if status.attempts != 5_000 || status.matches != 1 || status.duration != 2_000 || status.attempts_per_second != Some(2_500.0) || status.params.pattern != "8888" || !status.cancelled {
+            return Err(format!("生成状态不正确: {:?}", status));
+        }
+        drop(published);
+        drop(session);
+        if active_generation_list().iter().any(|status| status.session_id == session_id) {
+            return Err("会话结束后仍出现在生成状态中".to_string());
+        }
+        Ok(())
+    }
+
+    /// describe_pattern_syntax 返回值的快照（语法、示例、解读或难度有意修改时，用 UPDATE_SNAPSHOTS=1 运行测试重新生成）
+    const PATTERN_SYNTAX_SNAPSHOT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/pattern_syntax.json");
+
+    /**
+     * 模式语法说明：describe_pattern_syntax 的完整返回值与快照相同，每个示例都有效且与示例地址匹配
+     */
+    #[test]
+    fn pattern_syntax_snapshot() -> Result<(), String> {
+        let entries = describe_pattern_syntax();
+        if let Some(entry) = entries.iter().find(|entry| entry.error.is_some() || !entry.example_matches) {
+            return Err(format!("语法 {} 的示例 {} 无效或与示例地址不匹配: {:?}", entry.name, entry.example, entry.error));
+        }
+        let actual = serde_json::to_string_pretty(&entries).map_err(|e| e.to_string())? + "\n";
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            return std::fs::write(PATTERN_SYNTAX_SNAPSHOT, actual).map_err(|e| e.to_string());
+        }
+        let expected = std::fs::read_to_string(PATTERN_SYNTAX_SNAPSHOT).map_err(|e| format!("无法读取快照 {}: {}", PATTERN_SYNTAX_SNAPSHOT, e))?;
+        if actual != expected {
+            return Err(format!("describe_pattern_syntax 的返回值与快照 {} 不同:\n{}", PATTERN_SYNTAX_SNAPSHOT, actual));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,431 @@
+use crate::address::{checksum_from_bytes, public_key_to_bytes};
+#[cfg(feature = "gui")]
+use secp256k1::{Secp256k1, SecretKey, Signing};
+use secp256k1::PublicKey;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+/// 十六进制输入的问题
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HexProblem {
+    /// 去掉空白和 0x 前缀后为空
+    Empty,
+    /// 包含非十六进制字符（位置从 1 开始，按去掉首尾空白后的输入计算）
+    InvalidCharacter { position: usize, character: char },
+    /// 十六进制字符数不符合要求
+    WrongLength { expected: &'static [usize], actual: usize },
+    /// 长度正确，但不是有效的值（例如不在曲线上的公钥）
+    InvalidValue(&'static str),
+}
+
+/// 十六进制输入的解析错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexError {
+    /// 值的名称（例如“地址”“私钥”）
+    pub name: &'static str,
+    /// 具体问题
+    pub problem: HexProblem,
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.problem {
+            HexProblem::Empty => write!(f, "{}不能为空", self.name),
+            HexProblem::InvalidCharacter { position, character } => {
+                write!(f, "{}包含非十六进制字符 {:?}（第 {} 个字符）", self.name, character, position)
+            }
+            HexProblem::WrongLength { expected, actual } => {
+                let expected: Vec<String> = expected.iter().map(usize::to_string).collect();
+                write!(
+                    f,
+                    "{}长度应为 {} 个十六进制字符（不含 0x），实际为 {} 个",
+                    self.name,
+                    expected.join(" 或 "),
+                    actual
+                )
+            }
+            HexProblem::InvalidValue(reason) => write!(f, "{}无效：{}", self.name, reason),
+        }
+    }
+}
+
+impl From<HexError> for String {
+    fn from(error: HexError) -> Self {
+        error.to_string()
+    }
+}
+
+/**
+ * 规范化并解码十六进制输入：去掉首尾空白和可选的 0x/0X 前缀，不区分大小写，要求长度完全一致
+ *
+ * @param name - 值的名称（用于错误信息）
+ * @param input - 用户输入
+ * @param expected - 允许的十六进制字符数
+ * @returns 解码后的字节
+ */
+fn decode(name: &'static str, input: &str, expected: &'static [usize]) -> Result<Vec<u8>, HexError> {
+    let error = |problem| HexError { name, problem };
+    let trimmed = input.trim();
+    let (prefix_len, digits) = match trimmed.get(..2) {
+        Some("0x") | Some("0X") => (2, &trimmed[2..]),
+        _ => (0, trimmed),
+    };
+    if digits.is_empty() {
+        return Err(error(HexProblem::Empty));
+    }
+    if let Some((i, character)) = digits.chars().enumerate().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        return Err(error(HexProblem::InvalidCharacter { position: prefix_len + i + 1, character }));
+    }
+    if !expected.contains(&digits.len()) {
+        return Err(error(HexProblem::WrongLength { expected, actual: digits.len() }));
+    }
+    hex::decode(digits).map_err(|_| error(HexProblem::InvalidValue("无法解码")))
+}
+
+/// 以太坊地址（20 字节）
+///
+/// 规范的文本格式是 0x 前缀加 EIP-55 checksum 大小写：`Display` 和序列化都输出规范格式，
+/// 所有写入地址的地方（结果文件、回放、事件、导出）都通过该类型输出，读取时按字节比较。
+/// 反序列化不校验大小写，以便读取旧文件和用户手写的地址；需要确认是规范格式时使用 `parse_canonical`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Address([u8; 20]);
+
+impl Address {
+    /**
+     * 解析地址（不校验 checksum 大小写）
+     */
+    pub fn parse(input: &str) -> Result<Self, HexError> {
+        let bytes = decode("地址", input, &[40])?;
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&bytes);
+        Ok(Address(address))
+    }
+
+    /**
+     * 解析规范格式的地址：必须带 0x 前缀，且大小写与 EIP-55 checksum 完全一致
+     */
+    pub fn parse_canonical(input: &str) -> Result<Self, HexError> {
+        let address = Self::parse(input)?;
+        if input != address.to_checksum() {
+            return Err(HexError { name: "地址", problem: HexProblem::InvalidValue("不是 0x 开头的 EIP-55 checksum 格式") });
+        }
+        Ok(address)
+    }
+
+    /**
+     * 由公钥计算地址
+     */
+    pub fn from_public_key(public_key: &PublicKey) -> Self {
+        Address(public_key_to_bytes(public_key))
+    }
+
+    /**
+     * 由私钥计算地址（与生成循环相同：未压缩公钥的 Keccak-256 取后 20 字节）
+     *
+     * @param secp - secp256k1 上下文
+     * @param secret_key - 私钥
+     */
+    #[cfg(feature = "gui")]
+    pub fn from_secret_key<C: Signing>(secp: &Secp256k1<C>, secret_key: &SecretKey) -> Self {
+        Self::from_public_key(&PublicKey::from_secret_key(secp, secret_key))
+    }
+
+    /**
+     * 地址的原始字节（用于转换为其它链的地址格式）
+     */
+    pub fn as_bytes(&self) -> &[u8; 20] {
+        &self.0
+    }
+
+    /**
+     * checksum 格式（带 0x 前缀）
+     */
+    pub fn to_checksum(self) -> String {
+        format!("0x{}", checksum_from_bytes(&self.0))
+    }
+}
+
+impl From<[u8; 20]> for Address {
+    fn from(bytes: [u8; 20]) -> Self {
+        Address(bytes)
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_checksum())
+    }
+}
+
+impl FromStr for Address {
+    type Err = HexError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Self::parse(input)
+    }
+}
+
+impl Serialize for Address {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_checksum())
+    }
+}
+
+impl<'de> Deserialize<'de> for Address {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let input = String::deserialize(deserializer)?;
+        Self::parse(&input).map_err(serde::de::Error::custom)
+    }
+}
+
+/// 十六进制私钥（32 字节，必须在曲线阶范围内且不为零）
+#[cfg(feature = "gui")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrivKeyHex(SecretKey);
+
+#[cfg(feature = "gui")]
+impl PrivKeyHex {
+    /**
+     * 解析私钥；name 用于错误信息（例如拆分密钥的“偏移量”与私钥取值范围相同）
+     */
+    pub fn parse_named(name: &'static str, input: &str) -> Result<Self, HexError> {
+        let bytes = decode(name, input, &[64])?;
+        SecretKey::from_slice(&bytes)
+            .map(PrivKeyHex)
+            .map_err(|_| HexError { name, problem: HexProblem::InvalidValue("超出 secp256k1 私钥范围") })
+    }
+
+    /**
+     * 解析私钥
+     */
+    pub fn parse(input: &str) -> Result<Self, HexError> {
+        Self::parse_named("私钥", input)
+    }
+
+    /**
+     * 解析后的私钥
+     */
+    pub fn secret_key(self) -> SecretKey {
+        self.0
+    }
+}
+
+/// 十六进制私钥文本：离开作用域时清零，Debug 输出不显示内容
+///
+/// 序列化为普通字符串（写入事件或返回值后的副本不在这里管理，调用方应只在需要时才序列化）。
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct SecretHex(Zeroizing<String>);
+
+impl SecretHex {
+    /**
+     * 把私钥字节编码为十六进制（编码结果直接移入清零的缓冲区，不留下副本）
+     */
+    pub fn encode(bytes: &[u8]) -> Self {
+        SecretHex(Zeroizing::new(hex::encode(bytes)))
+    }
+
+    /**
+     * 十六进制文本（不含 0x 前缀）
+     */
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    /**
+     * 解码为 32 字节私钥（返回的字节同样在离开作用域时清零）
+     */
+    #[cfg(feature = "gui")]
+    pub fn decode(&self) -> Option<Zeroizing<[u8; 32]>> {
+        let mut bytes = Zeroizing::new([0u8; 32]);
+        hex::decode_to_slice(self.expose(), &mut bytes[..]).ok()?;
+        Some(bytes)
+    }
+}
+
+impl From<String> for SecretHex {
+    fn from(text: String) -> Self {
+        SecretHex(Zeroizing::new(text))
+    }
+}
+
+impl Zeroize for SecretHex {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for SecretHex {}
+
+impl fmt::Debug for SecretHex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretHex(..)")
+    }
+}
+
+impl Serialize for SecretHex {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.expose())
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretHex {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(SecretHex::from)
+    }
+}
+
+/// 32 字节哈希（例如 SHA-256）
+#[cfg(feature = "gui")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Hash32([u8; 32]);
+
+#[cfg(feature = "gui")]
+impl Hash32 {
+    /**
+     * 解析哈希
+     */
+    pub fn parse(input: &str) -> Result<Self, HexError> {
+        let bytes = decode("哈希", input, &[64])?;
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&bytes);
+        Ok(Hash32(hash))
+    }
+
+    /**
+     * 小写十六进制（不带 0x 前缀）
+     */
+    pub fn to_hex(self) -> String {
+        hex::encode(self.0)
+    }
+}
+
+#[cfg(feature = "gui")]
+impl From<[u8; 32]> for Hash32 {
+    fn from(bytes: [u8; 32]) -> Self {
+        Hash32(bytes)
+    }
+}
+
+/// secp256k1 公钥，统一为未压缩格式（接受压缩或未压缩的输入）
+#[cfg(feature = "gui")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PubKeyUncompressed(PublicKey);
+
+#[cfg(feature = "gui")]
+impl PubKeyUncompressed {
+    /**
+     * 解析公钥（33 字节压缩格式或 65 字节未压缩格式）
+     */
+    pub fn parse(input: &str) -> Result<Self, HexError> {
+        let bytes = decode("公钥", input, &[66, 130])?;
+        PublicKey::from_slice(&bytes)
+            .map(PubKeyUncompressed)
+            .map_err(|_| HexError { name: "公钥", problem: HexProblem::InvalidValue("不是有效的 secp256k1 公钥") })
+    }
+
+    /**
+     * 解析后的公钥
+     */
+    pub fn public_key(self) -> PublicKey {
+        self.0
+    }
+
+    /**
+     * 未压缩格式的小写十六进制（04 开头，不带 0x 前缀）
+     */
+    #[cfg(test)]
+    pub fn to_hex(self) -> String {
+        hex::encode(self.0.serialize_uncompressed())
+    }
+}
+
+#[cfg(all(test, feature = "gui"))]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+    use rand::Rng;
+
+    /// 十六进制解析的错误输入：(类型, 输入, 期望的错误信息)
+    const MALFORMED_HEX_CASES: &[(&str, &str, &str)] = &[
+        ("address", "", "地址不能为空"),
+        ("address", "  0x ", "地址不能为空"),
+        ("address", "0x52908400098527886E0F7030069857D2E4169EE", "地址长度应为 40 个十六进制字符（不含 0x），实际为 39 个"),
+        ("address", "52908400098527886E0F7030069857D2E4169EE700", "地址长度应为 40 个十六进制字符（不含 0x），实际为 42 个"),
+        ("address", "0x52908400098527886E0F7030069857D2E4169EEg", "地址包含非十六进制字符 'g'（第 42 个字符）"),
+        ("address", "0x5290 8400098527886E0F7030069857D2E4169EE7", "地址包含非十六进制字符 ' '（第 7 个字符）"),
+        ("address", "0x0x52908400098527886E0F7030069857D2E4169EE7", "地址包含非十六进制字符 'x'（第 4 个字符）"),
+        ("private_key", "01", "私钥长度应为 64 个十六进制字符（不含 0x），实际为 2 个"),
+        ("private_key", "0000000000000000000000000000000000000000000000000000000000000000", "私钥无效：超出 secp256k1 私钥范围"),
+        ("private_key", "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141", "私钥无效：超出 secp256k1 私钥范围"),
+        ("private_key", "0xzz", "私钥包含非十六进制字符 'z'（第 3 个字符）"),
+        ("hash", "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b85", "哈希长度应为 64 个十六进制字符（不含 0x），实际为 63 个"),
+        ("hash", "\t", "哈希不能为空"),
+        ("public_key", "02", "公钥长度应为 66 或 130 个十六进制字符（不含 0x），实际为 2 个"),
+        ("public_key", "050000000000000000000000000000000000000000000000000000000000000001", "公钥无效：不是有效的 secp256k1 公钥"),
+        ("public_key", "0x04é", "公钥包含非十六进制字符 'é'（第 5 个字符）"),
+    ];
+
+    /// 十六进制解析模糊测试的输入数量
+    const HEX_FUZZ_INPUTS: usize = 2000;
+
+    /**
+     * 按类型解析十六进制输入，成功时返回规范化后的形式
+     */
+    fn parse_hex_as(kind: &str, input: &str) -> Result<String, HexError> {
+        match kind {
+            "address" => Address::parse(input).map(|address| address.to_checksum()),
+            "private_key" => PrivKeyHex::parse(input).map(|key| hex::encode(key.secret_key().secret_bytes())),
+            "hash" => Hash32::parse(input).map(|hash| hash.to_hex()),
+            _ => PubKeyUncompressed::parse(input).map(|key| key.to_hex()),
+        }
+    }
+
+    /**
+     * 十六进制解析：错误输入的信息与期望一致；随机输入不会崩溃，解析成功的结果规范化后可以原样解析回来
+     */
+    #[test]
+    fn hex_parsers() -> Result<(), String> {
+        for (kind, input, expected) in MALFORMED_HEX_CASES {
+            match parse_hex_as(kind, input) {
+                Ok(value) => return Err(format!("{} 输入 {:?} 应当被拒绝，却解析为 {}", kind, input, value)),
+                Err(e) if e.to_string() != *expected => {
+                    return Err(format!("{} 输入 {:?} 的错误信息为 “{}”，期望 “{}”", kind, input, e, expected));
+                }
+                Err(_) => {}
+            }
+        }
+        
+        let kinds = [("address", "地址", 40), ("private_key", "私钥", 64), ("hash", "哈希", 64), ("public_key", "公钥", 130)];
+        // 前 HEX_DIGITS 个字符是十六进制数字
+        const HEX_DIGITS: usize = 22;
+        let alphabet: Vec<char> = "0123456789abcdefABCDEFxXg \t-é".chars().collect();
+        let mut rng = OsRng;
+        for i in 0..HEX_FUZZ_INPUTS {
+            let (kind, name, length) = kinds[i % kinds.len()];
+            // 一半输入是长度正确的纯十六进制，便于覆盖解码后的取值检查和往返
+            let (length, choices) = if rng.gen_bool(0.5) {
+                (length, HEX_DIGITS)
+            } else {
+                (rng.gen_range(0..=140), alphabet.len())
+            };
+            let mut input: String = (0..length).map(|_| alphabet[rng.gen_range(0..choices)]).collect();
+            if rng.gen_bool(0.3) {
+                input = format!(" 0x{}\n", input);
+            }
+            match parse_hex_as(kind, &input) {
+                Ok(normalized) => {
+                    if parse_hex_as(kind, &normalized).as_ref() != Ok(&normalized) {
+                        return Err(format!("{} 输入 {:?} 规范化为 {}，但无法原样解析回来", kind, input, normalized));
+                    }
+                }
+                Err(e) if e.name != name => {
+                    return Err(format!("{} 输入 {:?} 的错误报告为 {}", kind, input, e.name));
+                }
+                Err(_) => {}
+            }
+        }
+        Ok(())
+    }
+}
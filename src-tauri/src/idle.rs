@@ -0,0 +1,287 @@
+use crate::pause::PauseState;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// 常驻搜索设置的文件名（位于用户配置的数据目录中）
+const STANDING_SEARCH_FILE: &str = "standing_search.json";
+
+/// 默认的空闲阈值（分钟）
+const DEFAULT_IDLE_MINUTES: u64 = 10;
+
+/// 空闲时自动运行的常驻搜索
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StandingSearch {
+    /// 是否启用
+    pub enabled: bool,
+    /// 靓号模式
+    pub pattern: String,
+    /// 保存路径（可选）
+    pub save_path: Option<String>,
+    /// 空闲多少分钟后自动开始
+    pub idle_minutes: u64,
+}
+
+impl Default for StandingSearch {
+    fn default() -> Self {
+        StandingSearch {
+            enabled: false,
+            pattern: String::new(),
+            save_path: None,
+            idle_minutes: DEFAULT_IDLE_MINUTES,
+        }
+    }
+}
+
+/**
+ * 读取常驻搜索设置（文件不存在时返回默认设置）
+ *
+ * @param profile_dir - 用户配置的数据目录
+ */
+pub fn load_standing_search(profile_dir: &Path) -> Result<StandingSearch, String> {
+    let path = profile_dir.join(STANDING_SEARCH_FILE);
+    if !path.exists() {
+        return Ok(StandingSearch::default());
+    }
+    let data = fs::read_to_string(&path).map_err(|e| format!("无法读取常驻搜索设置: {}", e))?;
+    serde_json::from_str(&data).map_err(|e| format!("常驻搜索设置格式错误: {}", e))
+}
+
+/**
+ * 保存常驻搜索设置
+ *
+ * @param profile_dir - 用户配置的数据目录
+ * @param search - 常驻搜索设置
+ */
+pub fn save_standing_search(profile_dir: &Path, search: &StandingSearch) -> Result<(), String> {
+    fs::create_dir_all(profile_dir).map_err(|e| format!("无法创建配置目录: {}", e))?;
+    let json = serde_json::to_string_pretty(search).map_err(|e| format!("无法序列化常驻搜索设置: {}", e))?;
+    let path = profile_dir.join(STANDING_SEARCH_FILE);
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, json).map_err(|e| format!("无法写入常驻搜索设置: {}", e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("无法写入常驻搜索设置: {}", e))
+}
+
+/// 系统空闲时间来源（平台相关的实现隔离在此接口之后）
+pub trait IdleSource: Send {
+    /**
+     * 距离最近一次键盘或鼠标输入的时间；无法获取时返回 None
+     */
+    fn idle_time(&self) -> Option<Duration>;
+}
+
+/// 基于操作系统接口的空闲时间来源
+pub struct SystemIdleSource;
+
+impl IdleSource for SystemIdleSource {
+    fn idle_time(&self) -> Option<Duration> {
+        system_idle_time()
+    }
+}
+
+/**
+ * Windows：GetLastInputInfo 返回最近一次输入时的系统启动毫秒数
+ */
+#[cfg(windows)]
+fn system_idle_time() -> Option<Duration> {
+    use windows_sys::Win32::System::SystemInformation::GetTickCount;
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+    let mut info = LASTINPUTINFO { cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32, dwTime: 0 };
+    // SAFETY: info 是有效且已设置 cbSize 的 LASTINPUTINFO
+    if unsafe { GetLastInputInfo(&mut info) } == 0 {
+        return None;
+    }
+    // SAFETY: GetTickCount 没有参数和前置条件
+    let now = unsafe { GetTickCount() };
+    Some(Duration::from_millis(now.wrapping_sub(info.dwTime) as u64))
+}
+
+/**
+ * macOS：读取 IOHIDSystem 的 HIDIdleTime（纳秒）
+ */
+#[cfg(target_os = "macos")]
+fn system_idle_time() -> Option<Duration> {
+    let output = std::process::Command::new("ioreg")
+        .args(["-c", "IOHIDSystem", "-d", "4"])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let line = text.lines().find(|line| line.contains("\"HIDIdleTime\""))?;
+    let nanos: u64 = line.rsplit('=').next()?.trim().parse().ok()?;
+    Some(Duration::from_nanos(nanos))
+}
+
+/**
+ * Linux：优先使用 X11 的 xprintidle（毫秒），Wayland 下尝试 GNOME Mutter 的 IdleMonitor
+ */
+#[cfg(all(unix, not(target_os = "macos")))]
+fn system_idle_time() -> Option<Duration> {
+    use std::process::Command;
+
+    if let Ok(output) = Command::new("xprintidle").output() {
+        if output.status.success() {
+            if let Ok(millis) = String::from_utf8_lossy(&output.stdout).trim().parse::<u64>() {
+                return Some(Duration::from_millis(millis));
+            }
+        }
+    }
+
+    // 输出形如 "(uint64 12345,)"
+    let output = Command::new("gdbus")
+        .args([
+            "call",
+            "--session",
+            "--dest",
+            "org.gnome.Mutter.IdleMonitor",
+            "--object-path",
+            "/org/gnome/Mutter/IdleMonitor/Core",
+            "--method",
+            "org.gnome.Mutter.IdleMonitor.GetIdletime",
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    let millis: u64 = text
+        .trim()
+        .trim_start_matches('(')
+        .trim_end_matches(')')
+        .trim_end_matches(',')
+        .trim_start_matches("uint64")
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_millis(millis))
+}
+
+#[cfg(not(any(windows, unix)))]
+fn system_idle_time() -> Option<Duration> {
+    None
+}
+
+/// 空闲检测触发的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleAction {
+    /// 开始常驻搜索
+    Start,
+    /// 恢复自动暂停的常驻搜索
+    Resume,
+    /// 检测到用户活动，自动暂停常驻搜索
+    Pause,
+}
+
+/**
+ * 根据空闲时间和当前任务状态决定要执行的操作
+ *
+ * 只会控制由空闲检测自动启动的任务：用户手动开始的任务不会被自动暂停，
+ * 用户手动暂停的任务也不会被自动恢复。
+ *
+ * @param idle - 系统空闲时间
+ * @param threshold - 空闲阈值
+ * @param running - 是否有任务正在运行
+ * @param auto_started - 空闲检测启动的会话是否仍在运行
+ * @param pause - 空闲检测启动的会话的暂停状态
+ */
+pub fn decide(
+    idle: Duration,
+    threshold: Duration,
+    running: bool,
+    auto_started: bool,
+    pause: PauseState,
+) -> Option<IdleAction> {
+    if idle >= threshold {
+        if !running {
+            Some(IdleAction::Start)
+        } else if auto_started && pause == PauseState::AutoPaused {
+            Some(IdleAction::Resume)
+        } else {
+            None
+        }
+    } else if running && auto_started && pause == PauseState::Running {
+        Some(IdleAction::Pause)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pause::PauseRequest;
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+
+    /// 按脚本依次返回空闲时间的模拟来源（None 表示平台接口暂时无法获取）
+    struct ScriptedIdleSource {
+        readings: RefCell<VecDeque<Option<Duration>>>,
+    }
+
+    impl IdleSource for ScriptedIdleSource {
+        fn idle_time(&self) -> Option<Duration> {
+            self.readings.borrow_mut().pop_front().flatten()
+        }
+    }
+
+    /// 空闲检测线程看到的任务状态
+    #[derive(Debug, Default)]
+    struct WatchedSession {
+        running: bool,
+        auto_started: bool,
+        pause: PauseState,
+    }
+
+    impl WatchedSession {
+        /**
+         * 与空闲检测线程相同：读取一次空闲时间，按 decide 的结果开始、暂停或恢复常驻搜索
+         */
+        fn poll(&mut self, source: &impl IdleSource, threshold: Duration) -> Option<IdleAction> {
+            let action = decide(source.idle_time()?, threshold, self.running, self.auto_started, self.pause);
+            match action? {
+                IdleAction::Start => *self = WatchedSession { running: true, auto_started: true, pause: PauseState::Running },
+                IdleAction::Resume => self.pause = self.pause.apply(PauseRequest::ResumeAutomatically)?,
+                IdleAction::Pause => self.pause = self.pause.apply(PauseRequest::PauseAutomatically)?,
+            }
+            action
+        }
+    }
+
+    fn minutes(readings: &[Option<u64>]) -> ScriptedIdleSource {
+        ScriptedIdleSource { readings: RefCell::new(readings.iter().map(|reading| reading.map(|m| Duration::from_secs(m * 60))).collect()) }
+    }
+
+    /**
+     * 空闲检测：空闲达到阈值时开始常驻搜索，用户活动时自动暂停、再次空闲时自动恢复；
+     * 用户手动暂停的常驻搜索不会被自动恢复，用户手动开始的任务不受空闲检测控制，无法获取空闲时间时不做任何操作
+     */
+    #[test]
+    fn idle_transitions() -> Result<(), String> {
+        let threshold = Duration::from_secs(DEFAULT_IDLE_MINUTES * 60);
+        let mut session = WatchedSession::default();
+        let source = minutes(&[Some(1), None, Some(10), Some(11), Some(0), Some(0), Some(12), Some(0)]);
+        let actions: Vec<Option<IdleAction>> = (0..8).map(|_| session.poll(&source, threshold)).collect();
+        let expected = [None, None, Some(IdleAction::Start), None, Some(IdleAction::Pause), None, Some(IdleAction::Resume), Some(IdleAction::Pause)];
+        if actions != expected || session.pause != PauseState::AutoPaused {
+            return Err(format!("空闲检测的操作为 {:?}，应为 {:?}", actions, expected));
+        }
+
+        session.pause = session.pause.apply(PauseRequest::PauseByUser).ok_or("用户暂停没有覆盖自动暂停")?;
+        if session.poll(&minutes(&[Some(30)]), threshold).is_some() || session.pause != PauseState::UserPaused {
+            return Err("用户手动暂停的常驻搜索被自动恢复".to_string());
+        }
+        session.pause = session.pause.apply(PauseRequest::ResumeByUser).ok_or("用户恢复没有生效")?;
+        if session.poll(&minutes(&[Some(0)]), threshold) != Some(IdleAction::Pause) {
+            return Err("用户恢复后的常驻搜索在用户活动时没有自动暂停".to_string());
+        }
+
+        let mut manual = WatchedSession { running: true, ..WatchedSession::default() };
+        let source = minutes(&[Some(0), Some(20)]);
+        if manual.poll(&source, threshold).is_some() || manual.poll(&source, threshold).is_some() || manual.pause != PauseState::Running {
+            return Err("用户手动开始的任务受到了空闲检测的控制".to_string());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,317 @@
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::Mutex;
+
+/// Keccak-256 的吸收速率（字节）
+const RATE: usize = 136;
+
+/// keccak-f[1600] 每轮的轮常数
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000_0000_0000_0001,
+    0x0000_0000_0000_8082,
+    0x8000_0000_0000_808a,
+    0x8000_0000_8000_8000,
+    0x0000_0000_0000_808b,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8009,
+    0x0000_0000_0000_008a,
+    0x0000_0000_0000_0088,
+    0x0000_0000_8000_8009,
+    0x0000_0000_8000_000a,
+    0x0000_0000_8000_808b,
+    0x8000_0000_0000_008b,
+    0x8000_0000_0000_8089,
+    0x8000_0000_0000_8003,
+    0x8000_0000_0000_8002,
+    0x8000_0000_0000_0080,
+    0x0000_0000_0000_800a,
+    0x8000_0000_8000_000a,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8080,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8008,
+];
+
+/// ρ 步骤的循环移位位数（按 π 步骤的遍历顺序）
+const RHO: [u32; 24] = [1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44];
+
+/// π 步骤的遍历顺序（从第 1 个 lane 开始）
+const PI: [usize; 24] = [10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1];
+
+/// 启动自检使用的已知向量（Keccak-256，以太坊使用的填充方式）
+pub const KNOWN_VECTORS: [(&[u8], &str); 2] = [
+    (b"", "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"),
+    (b"abc", "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"),
+];
+
+/// 编译进程序的 Keccak-256 实现
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeccakBackend {
+    /// 本模块中的纯 Rust 实现（只使用基本的整数运算，任何 CPU 上都可以运行）
+    Portable,
+    /// sha3 库的实现（按编译目标优化，默认使用）
+    Library,
+}
+
+impl KeccakBackend {
+    fn to_u8(self) -> u8 {
+        match self {
+            KeccakBackend::Portable => 1,
+            KeccakBackend::Library => 2,
+        }
+    }
+
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(KeccakBackend::Portable),
+            2 => Some(KeccakBackend::Library),
+            _ => None,
+        }
+    }
+
+    /**
+     * 用该实现计算 Keccak-256
+     */
+    pub fn hash(self, data: &[u8]) -> [u8; 32] {
+        match self {
+            KeccakBackend::Portable => portable_keccak256(data),
+            KeccakBackend::Library => Keccak256::digest(data).into(),
+        }
+    }
+}
+
+/// 实现选择的结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KeccakSelection {
+    /// 实际使用的实现
+    pub backend: KeccakBackend,
+    /// 是否强制使用纯 Rust 实现
+    pub force_portable: bool,
+    /// 运行时检测到的 CPU 特性
+    pub cpu_features: Vec<String>,
+    /// 首选实现没有通过已知向量校验而改用纯 Rust 实现时的原因
+    pub fallback_reason: Option<String>,
+}
+
+/// 当前使用的实现（0 表示尚未选择）
+static BACKEND: AtomicU8 = AtomicU8::new(0);
+
+/// 是否强制使用纯 Rust 实现（用于排查优化实现在某些 CPU 上的问题）
+static FORCE_PORTABLE: AtomicBool = AtomicBool::new(false);
+
+/// 最近一次选择的结果
+static SELECTION: Mutex<Option<KeccakSelection>> = Mutex::new(None);
+
+/**
+ * keccak-f[1600] 置换
+ */
+fn keccak_f(state: &mut [u64; 25]) {
+    for round_constant in ROUND_CONSTANTS {
+        // θ
+        let mut columns = [0u64; 5];
+        for (i, lane) in state.iter().enumerate() {
+            columns[i % 5] ^= lane;
+        }
+        for (i, lane) in state.iter_mut().enumerate() {
+            let x = i % 5;
+            *lane ^= columns[(x + 4) % 5] ^ columns[(x + 1) % 5].rotate_left(1);
+        }
+        // ρ 和 π
+        let mut carried = state[1];
+        for (&target, &rotation) in PI.iter().zip(RHO.iter()) {
+            let next = state[target];
+            state[target] = carried.rotate_left(rotation);
+            carried = next;
+        }
+        // χ
+        for row in state.chunks_exact_mut(5) {
+            let copy = [row[0], row[1], row[2], row[3], row[4]];
+            for (x, lane) in row.iter_mut().enumerate() {
+                *lane = copy[x] ^ (!copy[(x + 1) % 5] & copy[(x + 2) % 5]);
+            }
+        }
+        // ι
+        state[0] ^= round_constant;
+    }
+}
+
+fn absorb_block(state: &mut [u64; 25], block: &[u8]) {
+    for (lane, bytes) in state.iter_mut().zip(block.chunks_exact(8)) {
+        let mut word = [0u8; 8];
+        word.copy_from_slice(bytes);
+        *lane ^= u64::from_le_bytes(word);
+    }
+    keccak_f(state);
+}
+
+/**
+ * 纯 Rust 的 Keccak-256（填充为 0x01…0x80，与以太坊一致，不是 SHA3-256）
+ */
+fn portable_keccak256(data: &[u8]) -> [u8; 32] {
+    let mut state = [0u64; 25];
+    let mut blocks = data.chunks_exact(RATE);
+    for block in &mut blocks {
+        absorb_block(&mut state, block);
+    }
+    let remainder = blocks.remainder();
+    let mut last = [0u8; RATE];
+    last[..remainder.len()].copy_from_slice(remainder);
+    last[remainder.len()] ^= 0x01;
+    last[RATE - 1] ^= 0x80;
+    absorb_block(&mut state, &last);
+
+    let mut output = [0u8; 32];
+    for (bytes, lane) in output.chunks_exact_mut(8).zip(state.iter()) {
+        bytes.copy_from_slice(&lane.to_le_bytes());
+    }
+    output
+}
+
+/**
+ * 运行时检测 CPU 特性（只用于记录和排查，不影响正确性）
+ */
+pub fn cpu_features() -> Vec<String> {
+    #[allow(unused_mut)]
+    let mut features: Vec<&str> = Vec::new();
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        let detected = [
+            ("sse2", std::arch::is_x86_feature_detected!("sse2")),
+            ("ssse3", std::arch::is_x86_feature_detected!("ssse3")),
+            ("avx2", std::arch::is_x86_feature_detected!("avx2")),
+            ("bmi2", std::arch::is_x86_feature_detected!("bmi2")),
+        ];
+        features.extend(detected.iter().filter(|(_, present)| *present).map(|(name, _)| *name));
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        let detected = [
+            ("neon", std::arch::is_aarch64_feature_detected!("neon")),
+            ("sha3", std::arch::is_aarch64_feature_detected!("sha3")),
+        ];
+        features.extend(detected.iter().filter(|(_, present)| *present).map(|(name, _)| *name));
+    }
+    features.into_iter().map(str::to_string).collect()
+}
+
+/**
+ * 检查实现对已知向量的结果
+ *
+ * @returns 结果不一致时的说明
+ */
+pub fn check_known_vectors(backend: KeccakBackend) -> Result<(), String> {
+    for (input, expected) in KNOWN_VECTORS {
+        let actual = hex::encode(backend.hash(input));
+        if actual != expected {
+            return Err(format!("{:?} 实现对 {:?} 的结果为 {}，期望 {}", backend, String::from_utf8_lossy(input), actual, expected));
+        }
+    }
+    Ok(())
+}
+
+/**
+ * 选择 Keccak-256 实现：默认使用 sha3 库，强制纯 Rust 实现或库的结果与已知向量不一致时使用纯 Rust 实现
+ *
+ * @param force_portable - 是否强制使用纯 Rust 实现
+ * @returns 选择结果（同时成为当前使用的实现）
+ */
+pub fn select(force_portable: bool) -> KeccakSelection {
+    FORCE_PORTABLE.store(force_portable, Ordering::SeqCst);
+    let mut fallback_reason = None;
+    let backend = if force_portable {
+        KeccakBackend::Portable
+    } else {
+        match check_known_vectors(KeccakBackend::Library) {
+            Ok(()) => KeccakBackend::Library,
+            Err(e) => {
+                eprintln!("Keccak 实现自检失败，改用纯 Rust 实现: {}", e);
+                fallback_reason = Some(e);
+                KeccakBackend::Portable
+            }
+        }
+    };
+    // 纯 Rust 实现不依赖任何 CPU 特性；这里仍做一次校验，编译错误时尽早暴露
+    if let Err(e) = check_known_vectors(KeccakBackend::Portable) {
+        eprintln!("纯 Rust Keccak 实现自检失败: {}", e);
+    }
+    let selection = KeccakSelection { backend, force_portable, cpu_features: cpu_features(), fallback_reason };
+    BACKEND.store(backend.to_u8(), Ordering::SeqCst);
+    *SELECTION.lock().unwrap_or_else(|e| e.into_inner()) = Some(selection.clone());
+    selection
+}
+
+/**
+ * 当前的选择结果（还没有选择时按当前设置选择一次）
+ */
+pub fn selection() -> KeccakSelection {
+    if let Some(selection) = SELECTION.lock().unwrap_or_else(|e| e.into_inner()).clone() {
+        return selection;
+    }
+    select(FORCE_PORTABLE.load(Ordering::SeqCst))
+}
+
+/**
+ * 当前使用的实现
+ */
+pub fn backend() -> KeccakBackend {
+    match KeccakBackend::from_u8(BACKEND.load(Ordering::Relaxed)) {
+        Some(backend) => backend,
+        None => selection().backend,
+    }
+}
+
+/**
+ * 用当前选择的实现计算 Keccak-256
+ */
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    backend().hash(data)
+}
+
+#[cfg(all(test, feature = "gui"))]
+mod tests {
+    use super::*;
+    use crate::address::to_checksum_address;
+
+    /**
+     * Keccak-256 实现选择：两种实现对已知向量和跨越吸收块边界的各种长度结果一致，
+     * 通过设置强制每一种实现后，地址计算使用的哈希与 sha3 库一致；结束后恢复原来的设置
+     */
+    #[test]
+    fn keccak_backends() -> Result<(), String> {
+        let data: Vec<u8> = (0..=300u32).map(|i| (i * 31 + 7) as u8).collect();
+        for backend in [KeccakBackend::Portable, KeccakBackend::Library] {
+            check_known_vectors(backend)?;
+        }
+        for len in 0..=data.len() {
+            let input = &data[..len];
+            if KeccakBackend::Portable.hash(input) != KeccakBackend::Library.hash(input) {
+                return Err(format!("长度为 {} 的输入两种实现的结果不一致", len));
+            }
+        }
+        
+        let previous = selection();
+        let mut outcome = Ok(());
+        for (force, expected) in [(true, KeccakBackend::Portable), (false, KeccakBackend::Library)] {
+            let selection = select(force);
+            if selection.backend != expected || backend() != expected {
+                outcome = Err(format!("force_portable = {} 时选择了 {:?}（{:?}）", force, selection.backend, selection.fallback_reason));
+                break;
+            }
+            if let Some(len) = (0..=data.len()).find(|&len| keccak256(&data[..len]) != KeccakBackend::Library.hash(&data[..len])) {
+                outcome = Err(format!("强制 {:?} 实现时长度为 {} 的输入结果不一致", expected, len));
+                break;
+            }
+            let checksum = to_checksum_address("52908400098527886e0f7030069857d2e4169ee7");
+            if checksum.as_deref() != Ok("52908400098527886E0F7030069857D2E4169EE7") {
+                outcome = Err(format!("强制 {:?} 实现时 checksum 地址为 {:?}", expected, checksum));
+                break;
+            }
+        }
+        select(previous.force_portable);
+        outcome?;
+        Ok(())
+    }
+}
@@ -0,0 +1,628 @@
+use crate::hexutil::Address;
+use crate::keccak::keccak256;
+use aes::cipher::{KeyIvInit, StreamCipher};
+use hmac::{Hmac, Mac};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+/// 支持的 keystore 格式版本（Web3 Secret Storage V3）
+pub const KEYSTORE_VERSION: u64 = 3;
+
+/// 单个 keystore 文件的最大大小（正常的 V3 keystore 不到 1 KiB）
+const MAX_KEYSTORE_BYTES: u64 = 64 * 1024;
+
+/// 已知的密钥派生函数
+const KNOWN_KDFS: &[&str] = &["scrypt", "pbkdf2"];
+
+/// 已知的加密算法
+const KNOWN_CIPHERS: &[&str] = &["aes-128-ctr", "aes-128-cbc"];
+
+/// 解密时允许的 scrypt 最大内存占用（128 · n · r 字节），防止来历不明的文件耗尽内存
+const MAX_SCRYPT_MEMORY: u64 = 1024 * 1024 * 1024;
+
+/// scrypt 参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScryptParams {
+    /// CPU / 内存开销（2 的幂）
+    pub n: u32,
+    /// 块大小
+    pub r: u32,
+    /// 并行度
+    pub p: u32,
+}
+
+impl ScryptParams {
+    /// 与 geth 默认（StandardScryptN）相同的参数：约 256 MiB 内存，每个钱包约 1 秒
+    pub const STANDARD: ScryptParams = ScryptParams { n: 1 << 18, r: 8, p: 1 };
+
+    /**
+     * 检查参数是否有效，且解密时的内存占用不超过上限
+     */
+    fn validate(&self) -> Result<(), String> {
+        if self.n < 2 || !self.n.is_power_of_two() || self.r == 0 || self.p == 0 {
+            return Err(format!("无效的 scrypt 参数: n={} r={} p={}", self.n, self.r, self.p));
+        }
+        if 128 * self.n as u64 * self.r as u64 > MAX_SCRYPT_MEMORY || self.r as u64 * self.p as u64 >= 1 << 30 {
+            return Err(format!("scrypt 参数过大: n={} r={} p={}", self.n, self.r, self.p));
+        }
+        Ok(())
+    }
+}
+
+/// 写入的 keystore（Web3 Secret Storage V3，geth 和 MetaMask 可以直接导入）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct KeystoreV3 {
+    /// 小写地址（不含 0x 前缀）
+    address: String,
+    crypto: KeystoreV3Crypto,
+    id: String,
+    version: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct KeystoreV3Crypto {
+    cipher: String,
+    ciphertext: String,
+    cipherparams: CipherParams,
+    kdf: String,
+    kdfparams: ScryptKdfParams,
+    mac: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct CipherParams {
+    iv: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct ScryptKdfParams {
+    dklen: usize,
+    n: u32,
+    p: u32,
+    r: u32,
+    salt: String,
+}
+
+/// keystore 文件的加密部分（只解析，不解密）
+#[derive(Debug, Deserialize, Clone, Default)]
+struct KeystoreCrypto {
+    #[serde(default)]
+    cipher: Option<String>,
+    #[serde(default)]
+    ciphertext: Option<String>,
+    #[serde(default)]
+    kdf: Option<String>,
+    #[serde(default)]
+    kdfparams: Option<serde_json::Value>,
+    #[serde(default)]
+    mac: Option<String>,
+}
+
+/// keystore 文件（geth、MetaMask 等工具写入的 JSON）
+#[derive(Debug, Deserialize, Clone)]
+struct KeystoreFile {
+    #[serde(default)]
+    address: Option<String>,
+    #[serde(default)]
+    version: Option<serde_json::Value>,
+    /// 部分旧工具（如早期的 MyEtherWallet）写入首字母大写的 Crypto
+    #[serde(default, alias = "Crypto")]
+    crypto: Option<KeystoreCrypto>,
+}
+
+/// keystore 中记录的地址
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(tag = "status", content = "value", rename_all = "snake_case")]
+pub enum KeystoreAddress {
+    /// 文件中记录的地址（checksum 格式；未经解密验证）
+    Embedded(String),
+    /// 文件没有记录地址，只有输入密码解密后才能知道
+    UnknownUntilDecrypted,
+    /// 记录的地址格式无效
+    Invalid(String),
+}
+
+/// 一个 keystore 文件的检查结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KeystoreInspection {
+    /// 文件路径
+    pub path: PathBuf,
+    /// 记录的地址
+    pub address: KeystoreAddress,
+    /// 格式版本
+    pub version: Option<u64>,
+    /// 密钥派生函数（scrypt、pbkdf2）
+    pub kdf: Option<String>,
+    /// 密钥派生参数（原样返回，包括迭代次数、salt 等）
+    pub kdf_params: Option<serde_json::Value>,
+    /// 加密算法
+    pub cipher: Option<String>,
+    /// 是否可疑（缺少 MAC、未知版本等，解密前应先确认文件来源）
+    pub suspicious: bool,
+    /// 可疑之处
+    pub warnings: Vec<String>,
+}
+
+/**
+ * PBKDF2-HMAC-SHA256
+ *
+ * @param password - 密码
+ * @param salt - 盐
+ * @param rounds - 迭代次数
+ * @param output - 输出（长度即派生的字节数）
+ */
+fn pbkdf2_sha256(password: &[u8], salt: &[u8], rounds: u32, output: &mut [u8]) {
+    let mac = Hmac::<Sha256>::new_from_slice(password).expect("HMAC 接受任意长度的密钥");
+    for (block, chunk) in output.chunks_mut(32).enumerate() {
+        let mut first = mac.clone();
+        first.update(salt);
+        first.update(&(block as u32 + 1).to_be_bytes());
+        let mut u: [u8; 32] = first.finalize().into_bytes().into();
+        let mut t = u;
+        for _ in 1..rounds {
+            let mut round = mac.clone();
+            round.update(&u);
+            u = round.finalize().into_bytes().into();
+            t.iter_mut().zip(u).for_each(|(t, u)| *t ^= u);
+        }
+        chunk.copy_from_slice(&t[..chunk.len()]);
+    }
+}
+
+/**
+ * Salsa20/8 核心函数（就地更新 16 个字）
+ */
+fn salsa20_8(block: &mut [u32; 16]) {
+    // 四分之一轮的下标：先按列，再按行
+    const QUARTER_ROUNDS: [[usize; 4]; 8] = [
+        [0, 4, 8, 12],
+        [5, 9, 13, 1],
+        [10, 14, 2, 6],
+        [15, 3, 7, 11],
+        [0, 1, 2, 3],
+        [5, 6, 7, 4],
+        [10, 11, 8, 9],
+        [15, 12, 13, 14],
+    ];
+    let mut x = *block;
+    for _ in 0..4 {
+        for [a, b, c, d] in QUARTER_ROUNDS {
+            x[b] ^= x[a].wrapping_add(x[d]).rotate_left(7);
+            x[c] ^= x[b].wrapping_add(x[a]).rotate_left(9);
+            x[d] ^= x[c].wrapping_add(x[b]).rotate_left(13);
+            x[a] ^= x[d].wrapping_add(x[c]).rotate_left(18);
+        }
+    }
+    block.iter_mut().zip(x).for_each(|(word, x)| *word = word.wrapping_add(x));
+}
+
+/**
+ * scrypt 的 BlockMix：输入和输出都是 2r 个 64 字节的块（按小端的 32 位字存储）
+ */
+fn block_mix(input: &[u32], output: &mut [u32], r: usize) {
+    let mut x: [u32; 16] = input[(2 * r - 1) * 16..].try_into().expect("最后一个块是 16 个字");
+    for (i, chunk) in input.chunks_exact(16).enumerate() {
+        x.iter_mut().zip(chunk).for_each(|(x, word)| *x ^= word);
+        salsa20_8(&mut x);
+        // 偶数块放在前半部分，奇数块放在后半部分
+        let position = (i / 2 + (i % 2) * r) * 16;
+        output[position..position + 16].copy_from_slice(&x);
+    }
+}
+
+/**
+ * scrypt（RFC 7914）
+ *
+ * @param password - 密码
+ * @param salt - 盐
+ * @param params - scrypt 参数
+ * @param dklen - 派生的字节数
+ */
+pub fn scrypt(password: &[u8], salt: &[u8], params: ScryptParams, dklen: usize) -> Result<Vec<u8>, String> {
+    params.validate()?;
+    let (n, r) = (params.n as usize, params.r as usize);
+    let words = 32 * r;
+    let mut bytes = vec![0u8; params.p as usize * 128 * r];
+    pbkdf2_sha256(password, salt, 1, &mut bytes);
+    let mut memory = vec![0u32; n * words];
+    let mut scratch = vec![0u32; words];
+    for chunk in bytes.chunks_exact_mut(128 * r) {
+        let mut x: Vec<u32> = chunk.chunks_exact(4).map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]])).collect();
+        for i in 0..n {
+            memory[i * words..(i + 1) * words].copy_from_slice(&x);
+            block_mix(&x, &mut scratch, r);
+            std::mem::swap(&mut x, &mut scratch);
+        }
+        for _ in 0..n {
+            let j = x[(2 * r - 1) * 16] as usize & (n - 1);
+            x.iter_mut().zip(&memory[j * words..(j + 1) * words]).for_each(|(x, v)| *x ^= v);
+            block_mix(&x, &mut scratch, r);
+            std::mem::swap(&mut x, &mut scratch);
+        }
+        for (word, value) in chunk.chunks_exact_mut(4).zip(x) {
+            word.copy_from_slice(&value.to_le_bytes());
+        }
+    }
+    let mut derived = vec![0u8; dklen];
+    pbkdf2_sha256(password, &bytes, 1, &mut derived);
+    Ok(derived)
+}
+
+/**
+ * 随机的 UUID（版本 4），作为 keystore 的 id
+ */
+fn random_uuid() -> String {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex::encode(bytes);
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+/**
+ * 用密码把私钥加密为 keystore V3 JSON（scrypt 派生密钥，AES-128-CTR 加密，Keccak-256 MAC）
+ *
+ * @param private_key - 32 字节私钥
+ * @param address - 私钥对应的地址
+ * @param password - 密码（不能为空）
+ * @param params - scrypt 参数
+ * @returns keystore JSON
+ */
+pub fn encrypt(private_key: &[u8; 32], address: &Address, password: &str, params: ScryptParams) -> Result<String, String> {
+    if password.is_empty() {
+        return Err("keystore 密码不能为空".to_string());
+    }
+    let mut salt = [0u8; 32];
+    let mut iv = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut iv);
+    let derived = scrypt(password.as_bytes(), &salt, params, 32)?;
+    let mut ciphertext = *private_key;
+    Aes128Ctr::new_from_slices(&derived[..16], &iv).map_err(|e| e.to_string())?.apply_keystream(&mut ciphertext);
+    let mac = keccak256(&[&derived[16..32], &ciphertext[..]].concat());
+    let keystore = KeystoreV3 {
+        address: hex::encode(address.as_bytes()),
+        crypto: KeystoreV3Crypto {
+            cipher: "aes-128-ctr".to_string(),
+            ciphertext: hex::encode(ciphertext),
+            cipherparams: CipherParams { iv: hex::encode(iv) },
+            kdf: "scrypt".to_string(),
+            kdfparams: ScryptKdfParams { dklen: 32, n: params.n, p: params.p, r: params.r, salt: hex::encode(salt) },
+            mac: hex::encode(mac),
+        },
+        id: random_uuid(),
+        version: KEYSTORE_VERSION,
+    };
+    serde_json::to_string_pretty(&keystore).map_err(|e| format!("无法序列化 keystore: {}", e))
+}
+
+/**
+ * 用密码解密 scrypt + AES-128-CTR 的 keystore V3 JSON（先核对 MAC，密码错误或文件被修改时返回错误）
+ *
+ * @param data - keystore JSON
+ * @param password - 密码
+ * @returns 私钥
+ */
+#[cfg(test)]
+pub fn decrypt(data: &str, password: &str) -> Result<[u8; 32], String> {
+    let keystore: KeystoreV3 = serde_json::from_str(data).map_err(|e| format!("不是 scrypt + aes-128-ctr 的 keystore V3 文件: {}", e))?;
+    let crypto = &keystore.crypto;
+    if keystore.version != KEYSTORE_VERSION || crypto.kdf != "scrypt" || crypto.cipher != "aes-128-ctr" || crypto.kdfparams.dklen != 32 {
+        return Err("只支持 scrypt + aes-128-ctr 的 keystore V3 文件".to_string());
+    }
+    let decode = |name: &str, text: &str| hex::decode(text).map_err(|e| format!("keystore 的 {} 无效: {}", name, e));
+    let salt = decode("salt", &crypto.kdfparams.salt)?;
+    let iv = decode("iv", &crypto.cipherparams.iv)?;
+    let ciphertext = decode("ciphertext", &crypto.ciphertext)?;
+    let params = ScryptParams { n: crypto.kdfparams.n, r: crypto.kdfparams.r, p: crypto.kdfparams.p };
+    let derived = scrypt(password.as_bytes(), &salt, params, 32)?;
+    if hex::encode(keccak256(&[&derived[16..32], &ciphertext[..]].concat())) != crypto.mac.to_ascii_lowercase() {
+        return Err("MAC 不一致：密码错误或 keystore 文件被修改".to_string());
+    }
+    let mut private_key: [u8; 32] = ciphertext.try_into().map_err(|_| "keystore 的密文不是 32 字节".to_string())?;
+    Aes128Ctr::new_from_slices(&derived[..16], &iv).map_err(|e| e.to_string())?.apply_keystream(&mut private_key);
+    Ok(private_key)
+}
+
+/**
+ * 写入 keystore 文件（geth 的文件名 UTC--<时间>--<小写地址>.json，仅所有者可读写，不覆盖已有文件）
+ *
+ * @param dir - keystore 目录
+ * @param address - 地址
+ * @param json - keystore JSON
+ * @returns 文件路径和写入的字节数
+ */
+pub fn write_file(dir: &Path, address: &Address, json: &str) -> Result<(PathBuf, u64), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("无法创建 keystore 目录: {}", e))?;
+    let stamp = chrono::Utc::now().format("%Y-%m-%dT%H-%M-%S%.9fZ");
+    let path = dir.join(format!("UTC--{}--{}.json", stamp, hex::encode(address.as_bytes())));
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path).map_err(|e| format!("无法创建 keystore 文件: {}", e))?;
+    file.write_all(json.as_bytes()).map_err(|e| format!("无法写入 keystore 文件: {}", e))?;
+    file.sync_all().map_err(|e| format!("无法写入 keystore 文件: {}", e))?;
+    Ok((path, json.len() as u64))
+}
+
+/**
+ * 解析 keystore 内容（不解密，也不需要密码）
+ *
+ * @param path - 文件路径（只用于结果）
+ * @param data - 文件内容
+ */
+pub fn inspect_json(path: &Path, data: &str) -> Result<KeystoreInspection, String> {
+    let file: KeystoreFile = serde_json::from_str(data).map_err(|e| format!("不是有效的 keystore 文件: {}", e))?;
+    let mut warnings = Vec::new();
+
+    let address = match file.address.as_deref().map(str::trim) {
+        None | Some("") => KeystoreAddress::UnknownUntilDecrypted,
+        Some(text) => match Address::parse(text) {
+            Ok(address) => KeystoreAddress::Embedded(address.to_checksum()),
+            Err(e) => {
+                warnings.push(format!("记录的地址无效: {}", e));
+                KeystoreAddress::Invalid(text.to_string())
+            }
+        },
+    };
+
+    let version = file.version.as_ref().and_then(serde_json::Value::as_u64);
+    match (&file.version, version) {
+        (None, _) => warnings.push("缺少版本号".to_string()),
+        (Some(_), Some(KEYSTORE_VERSION)) => {}
+        (Some(raw), _) => warnings.push(format!("未知的 keystore 版本: {}", raw)),
+    }
+
+    let crypto = file.crypto.unwrap_or_else(|| {
+        warnings.push("缺少 crypto 部分".to_string());
+        KeystoreCrypto::default()
+    });
+    if crypto.mac.as_deref().is_none_or(|mac| mac.trim().is_empty()) {
+        warnings.push("缺少 MAC，无法在解密时发现文件被修改".to_string());
+    }
+    if crypto.ciphertext.as_deref().is_none_or(|text| text.trim().is_empty()) {
+        warnings.push("缺少密文".to_string());
+    }
+    match crypto.kdf.as_deref() {
+        None => warnings.push("缺少密钥派生函数".to_string()),
+        Some(kdf) if !KNOWN_KDFS.contains(&kdf) => warnings.push(format!("未知的密钥派生函数: {}", kdf)),
+        Some(_) => {}
+    }
+    if crypto.kdfparams.as_ref().is_none_or(|params| !params.is_object()) {
+        warnings.push("缺少密钥派生参数".to_string());
+    }
+    match crypto.cipher.as_deref() {
+        None => warnings.push("缺少加密算法".to_string()),
+        Some(cipher) if !KNOWN_CIPHERS.contains(&cipher) => warnings.push(format!("未知的加密算法: {}", cipher)),
+        Some(_) => {}
+    }
+
+    Ok(KeystoreInspection {
+        path: path.to_path_buf(),
+        address,
+        version,
+        kdf: crypto.kdf,
+        kdf_params: crypto.kdfparams,
+        cipher: crypto.cipher,
+        suspicious: !warnings.is_empty(),
+        warnings,
+    })
+}
+
+/**
+ * 检查单个 keystore 文件
+ */
+fn inspect_file(path: &Path) -> Result<KeystoreInspection, String> {
+    let len = fs::metadata(path).map_err(|e| format!("无法读取 {}: {}", path.display(), e))?.len();
+    if len > MAX_KEYSTORE_BYTES {
+        return Err(format!("{} 过大（{} 字节），不是 keystore 文件", path.display(), len));
+    }
+    let data = fs::read_to_string(path).map_err(|e| format!("无法读取 {}: {}", path.display(), e))?;
+    inspect_json(path, &data).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/**
+ * 目录中可能是 keystore 的文件：.json 文件和 geth 的 UTC--… 文件
+ */
+fn is_keystore_candidate(path: &Path) -> bool {
+    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+    name.starts_with("UTC--") || path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+}
+
+/**
+ * 只读检查 keystore 文件或目录（不解密，不需要密码，不修改任何文件）
+ *
+ * 目录只检查第一层的 .json 文件和 geth 的 UTC--… 文件，按文件名排序；
+ * 目录中无法解析的文件计入可疑文件，不中断检查
+ *
+ * @param path - keystore 文件或目录
+ * @returns 每个文件的检查结果
+ */
+pub fn inspect(path: &Path) -> Result<Vec<KeystoreInspection>, String> {
+    if !path.is_dir() {
+        return Ok(vec![inspect_file(path)?]);
+    }
+    let mut files: Vec<PathBuf> = fs::read_dir(path)
+        .map_err(|e| format!("无法读取目录: {}", e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && is_keystore_candidate(path))
+        .collect();
+    files.sort();
+    Ok(files
+        .into_iter()
+        .map(|file| {
+            inspect_file(&file).unwrap_or_else(|e| KeystoreInspection {
+                path: file,
+                address: KeystoreAddress::UnknownUntilDecrypted,
+                version: None,
+                kdf: None,
+                kdf_params: None,
+                cipher: None,
+                suspicious: true,
+                warnings: vec![e],
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{next_secret_key, KeyCandidateStats};
+    use secp256k1::{PublicKey, Secp256k1};
+    use crate::test_support::with_data_dir;
+
+    /// keystore 用例：(来源, 文件名, 内容, 期望的地址, 是否可疑)
+    type KeystoreFixture = (&'static str, &'static str, &'static str, Option<&'static str>, bool);
+
+    /// 各工具写入的 keystore（只检查不解密，内容中的密文无需能被解密）
+    /// RFC 7914 第 12 节的 scrypt 向量：(密码, 盐, n, r, p, 64 字节输出)
+    const SCRYPT_VECTORS: &[(&str, &str, u32, u32, u32, &str)] = &[
+        ("", "", 16, 1, 1, "77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906"),
+        ("password", "NaCl", 1024, 8, 16, "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b3731622eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640"),
+    ];
+
+    const KEYSTORE_FIXTURES: &[KeystoreFixture] = &[
+        (
+            "geth",
+            "UTC--2024-01-02T03-04-05.000000000Z--7e5f4552091a69125d5dfcb7b8c2659029395bdf",
+            r#"{"address":"7e5f4552091a69125d5dfcb7b8c2659029395bdf","crypto":{"cipher":"aes-128-ctr","ciphertext":"d172bf743a674da9cdad04534d56926ef8358534d458fffccd4e6ad2fbde479c","cipherparams":{"iv":"83dbcc02d8ccb40e466191a123791e0e"},"kdf":"scrypt","kdfparams":{"dklen":32,"n":262144,"p":1,"r":8,"salt":"ab0c7876052600dd703518d6fc3fe8984592145b591fc8fb5c6d43190334ba19"},"mac":"2103ac29920d71da29f15d75b4a16dbe95cfd7ff8faea1056c33131d846e3097"},"id":"3198bc9c-6672-5ab3-d995-4942343ae5b6","version":3}"#,
+            Some("0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf"),
+            false,
+        ),
+        (
+            "MetaMask",
+            "metamask-export.json",
+            r#"{"version":3,"id":"e1e1a9d8-5b7c-4a36-9b86-3f0e2e0c2d11","address":"2c7536e3605d9c16a7a3d7b1898e529396a65c23","Crypto":{"ciphertext":"9c9e3ebbf01a512f3bea41ac6fe7676344c0da77236b38847c02718ec9b66126","cipherparams":{"iv":"a8932af2a3c0225ee8452aba5ca4e7e5"},"cipher":"aes-128-ctr","kdf":"pbkdf2","kdfparams":{"dklen":32,"salt":"5788ea266d7e1d12e6e4ab0bf03ae8990e4f5ed1ebbfa50e1d978c8925cc3b73","c":262144,"prf":"hmac-sha256"},"mac":"bd6a0a1d8d8bb0f4bf1e22e0a8d63d3db8e5c4b4e9a8fb3f3274b2b9c59e5fe3"}}"#,
+            Some("0x2c7536E3605D9C16a7a3D7b1898e529396a65c23"),
+            false,
+        ),
+        (
+            "Web3 Secret Storage 规范示例（无地址）",
+            "spec-vector.json",
+            r#"{"crypto":{"cipher":"aes-128-ctr","cipherparams":{"iv":"6087dab2f9fdbbfaddc31a909735c1e6"},"ciphertext":"5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46","kdf":"pbkdf2","kdfparams":{"c":262144,"dklen":32,"prf":"hmac-sha256","salt":"ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd"},"mac":"517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2"},"id":"3198bc9c-6672-5ab3-d995-4942343ae5b6","version":3}"#,
+            None,
+            false,
+        ),
+        (
+            "缺少 MAC 的旧版本文件",
+            "suspicious.json",
+            r#"{"address":"0x6813eb9362372eef6200f3b1dbc3f819671cba69","version":2,"crypto":{"cipher":"aes-128-ctr","ciphertext":"00","kdf":"scrypt","kdfparams":{"n":1024,"r":8,"p":1,"dklen":32,"salt":"00"}}}"#,
+            Some("0x6813Eb9362372EEF6200f3b1dbC3f819671cBA69"),
+            true,
+        ),
+        ("不是 JSON 的文件", "UTC--broken", "not a keystore", None, true),
+    ];
+
+    /**
+     * keystore 只读检查：各工具写入的文件都能读出地址和加密参数，没有地址的文件不猜测，可疑文件被标出
+     */
+    #[test]
+    fn keystore_inspection() -> Result<(), String> {
+        with_data_dir("keystore", |dir| {
+            for (_, name, content, _, _) in KEYSTORE_FIXTURES {
+                std::fs::write(dir.join(name), content).map_err(|e| e.to_string())?;
+            }
+            std::fs::write(dir.join("notes.txt"), "not a keystore").map_err(|e| e.to_string())?;
+            
+            let inspections = inspect(dir)?;
+            if inspections.len() != KEYSTORE_FIXTURES.len() {
+                return Err(format!("目录中检查了 {} 个文件，应为 {} 个", inspections.len(), KEYSTORE_FIXTURES.len()));
+            }
+            for (source, name, _, address, suspicious) in KEYSTORE_FIXTURES {
+                let Some(inspection) = inspections.iter().find(|inspection| inspection.path.file_name().is_some_and(|n| n == *name)) else {
+                    return Err(format!("{} 的文件没有被检查", source));
+                };
+                let expected = address.map_or(KeystoreAddress::UnknownUntilDecrypted, |address| KeystoreAddress::Embedded(address.to_string()));
+                if inspection.address != expected || inspection.suspicious != *suspicious {
+                    return Err(format!("{} 的检查结果不正确: {:?}", source, inspection));
+                }
+                if !suspicious && (inspection.version != Some(KEYSTORE_VERSION) || inspection.kdf.is_none() || inspection.kdf_params.is_none() || inspection.cipher.is_none()) {
+                    return Err(format!("{} 的加密参数没有读出: {:?}", source, inspection));
+                }
+            }
+            let (_, name, _, address, _) = KEYSTORE_FIXTURES[0];
+            let single = inspect(&dir.join(name))?;
+            if single.len() != 1 || single[0].address != KeystoreAddress::Embedded(address.unwrap_or_default().to_string()) {
+                return Err(format!("单独检查 {} 的结果不正确: {:?}", name, single));
+            }
+            Ok(())
+        })
+    }
+
+    /**
+     * keystore 导出：scrypt 与 RFC 7914 的向量一致，写入的文件能用密码解密出原来的私钥，
+     * 错误的密码和被修改的密文被拒绝，只读检查能读出地址和加密参数
+     */
+    #[test]
+    fn keystore_export() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        for (password, salt, n, r, p, expected) in SCRYPT_VECTORS {
+            let derived = hex::encode(scrypt(password.as_bytes(), salt.as_bytes(), ScryptParams { n: *n, r: *r, p: *p }, 64)?);
+            if derived != *expected {
+                return Err(format!("scrypt(\"{}\", \"{}\", n={}) 为 {}，应为 {}", password, salt, n, derived, expected));
+            }
+        }
+        if scrypt(b"", b"", ScryptParams { n: 1000, r: 8, p: 1 }, 32).is_ok() {
+            return Err("n 不是 2 的幂的 scrypt 参数没有被拒绝".to_string());
+        }
+        
+        with_data_dir("keystore_export", |dir| {
+            // 测试使用较小的 n，与默认参数的区别只在耗时
+            let params = ScryptParams { n: 1 << 10, r: 8, p: 1 };
+            let secret_key = next_secret_key(&mut OsRng, &mut KeyCandidateStats::default()).ok_or("无法生成随机私钥")?;
+            let address = Address::from_public_key(&PublicKey::from_secret_key(&secp, &secret_key));
+            let json = encrypt(&secret_key.secret_bytes(), &address, "correct horse", params)?;
+            let (path, bytes) = write_file(dir, &address, &json)?;
+            let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default().to_string();
+            if !name.starts_with("UTC--") || !name.ends_with(&format!("--{}.json", hex::encode(address.as_bytes()))) || bytes != json.len() as u64 {
+                return Err(format!("keystore 文件名 {} 或写入的字节数 {} 不正确", name, bytes));
+            }
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let mode = std::fs::metadata(&path).map_err(|e| e.to_string())?.permissions().mode() & 0o777;
+                if mode != 0o600 {
+                    return Err(format!("keystore 文件的权限为 {:o}，应为 600", mode));
+                }
+            }
+            
+            let written = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            if decrypt(&written, "correct horse")? != secret_key.secret_bytes() {
+                return Err("解密出的私钥与原来的不同".to_string());
+            }
+            if decrypt(&written, "wrong horse").is_ok() {
+                return Err("错误的密码没有被拒绝".to_string());
+            }
+            let mut tampered: serde_json::Value = serde_json::from_str(&written).map_err(|e| e.to_string())?;
+            let ciphertext = tampered["crypto"]["ciphertext"].as_str().unwrap_or_default();
+            let flipped = format!("{}{}", if ciphertext.starts_with('0') { "1" } else { "0" }, &ciphertext[1..]);
+            tampered["crypto"]["ciphertext"] = serde_json::Value::String(flipped);
+            if decrypt(&tampered.to_string(), "correct horse").is_ok() {
+                return Err("被修改的密文没有被拒绝".to_string());
+            }
+            if encrypt(&secret_key.secret_bytes(), &address, "", params).is_ok() {
+                return Err("空密码没有被拒绝".to_string());
+            }
+            
+            let inspection = inspect(&path)?;
+            let expected = KeystoreAddress::Embedded(address.to_checksum());
+            if inspection.len() != 1 || inspection[0].address != expected || inspection[0].suspicious || inspection[0].kdf.as_deref() != Some("scrypt") {
+                return Err(format!("写入的 keystore 的检查结果不正确: {:?}", inspection));
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,411 @@
+use crate::hexutil::SecretHex;
+use crate::scoring::ScoreFunction;
+use crate::session_state::STATE_DIR;
+use crate::timestamps;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
+
+/// score 模式的排行榜保留的钱包数
+pub const LEADERBOARD_SIZE: usize = 10;
+
+/// 排行榜文件的格式版本
+pub const LEADERBOARD_VERSION: u32 = 1;
+
+/// 排行榜只是增加了钱包（没有挤掉已有的钱包）时两次写入的最短间隔
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 排行榜上的一个钱包
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LeaderboardEntry {
+    /// 地址（0x 开头的 checksum 地址）
+    pub address: String,
+    /// 分数
+    pub score: f64,
+    /// 找到时的尝试次数
+    pub attempts: u64,
+    /// 找到时的运行时间（毫秒，不含暂停时间）
+    pub duration: u64,
+    /// 找到时间（RFC 3339）
+    pub found_at: String,
+    /// 私钥（只有会话的结果文件保存明文私钥时才写入排行榜文件）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<SecretHex>,
+}
+
+/// 排行榜文件（FancyWallets/session_state/<会话标识>.leaderboard.json）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Standings {
+    /// 格式版本
+    pub version: u32,
+    /// 会话标识
+    pub session_id: String,
+    /// 评分函数
+    pub score_function: ScoreFunction,
+    /// 是否带有私钥（结果文件打码、省略或加密私钥，或者只写入 keystore 时为 false）
+    pub keys_included: bool,
+    /// 上一次写入的时间（UTC，RFC 3339）
+    pub updated_at: String,
+    /// 按分数从高到低排列的钱包（分数相同时先找到的在前）
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+/// 一个钱包提交到排行榜的结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum Placement {
+    /// 分数不够，没有进入排行榜
+    Rejected,
+    /// 排行榜未满，直接加入
+    Added,
+    /// 加入排行榜并挤掉了分数最低的钱包
+    Displaced(LeaderboardEntry),
+}
+
+/// score 模式的前 LEADERBOARD_SIZE 名
+#[derive(Debug, Clone)]
+pub struct Leaderboard {
+    standings: Standings,
+    size: usize,
+}
+
+impl Leaderboard {
+    /**
+     * 创建空的排行榜
+     *
+     * @param session_id - 会话标识
+     * @param score_function - 评分函数
+     * @param keys_included - 是否保存私钥
+     */
+    pub fn new(session_id: &str, score_function: ScoreFunction, keys_included: bool) -> Self {
+        Leaderboard {
+            standings: Standings {
+                version: LEADERBOARD_VERSION,
+                session_id: session_id.to_string(),
+                score_function,
+                keys_included,
+                updated_at: timestamps::now_rfc3339(),
+                entries: Vec::new(),
+            },
+            size: LEADERBOARD_SIZE,
+        }
+    }
+
+    /**
+     * 恢复会话时从之前的会话的排行榜继续（评分函数不同的排行榜不能继续；本会话不保存私钥时去掉私钥）
+     *
+     * @param session_id - 新会话的标识
+     * @param previous - 之前的会话的排行榜文件
+     * @param keys_included - 本会话是否保存私钥
+     */
+    pub fn resume(session_id: &str, previous: Standings, keys_included: bool) -> Self {
+        let mut board = Leaderboard::new(session_id, previous.score_function, keys_included);
+        for mut entry in previous.entries {
+            if !keys_included {
+                entry.private_key = None;
+            }
+            board.offer(entry);
+        }
+        board
+    }
+
+    /**
+     * 提交一个钱包：分数超过 threshold 时按分数插入，排行榜已满时挤掉分数最低的钱包
+     *
+     * @param entry - 钱包
+     */
+    pub fn offer(&mut self, mut entry: LeaderboardEntry) -> Placement {
+        if entry.score <= self.threshold() {
+            return Placement::Rejected;
+        }
+        if !self.standings.keys_included {
+            entry.private_key = None;
+        }
+        let position = self.standings.entries.iter().position(|existing| existing.score < entry.score).unwrap_or(self.standings.entries.len());
+        self.standings.entries.insert(position, entry);
+        if self.standings.entries.len() > self.size {
+            self.standings.entries.pop().map_or(Placement::Added, Placement::Displaced)
+        } else {
+            Placement::Added
+        }
+    }
+
+    /**
+     * 进入排行榜需要超过的分数（未满时为 0，已满时为最低的分数）
+     */
+    pub fn threshold(&self) -> f64 {
+        match self.standings.entries.last() {
+            Some(last) if self.standings.entries.len() >= self.size => last.score,
+            _ => 0.0,
+        }
+    }
+
+    /**
+     * 最高分的钱包
+     */
+    pub fn best(&self) -> Option<&LeaderboardEntry> {
+        self.standings.entries.first()
+    }
+}
+
+/**
+ * 会话的排行榜文件路径（与会话状态文件放在一起）
+ *
+ * @param wallets_dir - FancyWallets 目录
+ * @param session_id - 会话标识
+ */
+pub fn leaderboard_path(wallets_dir: &Path, session_id: &str) -> PathBuf {
+    wallets_dir.join(STATE_DIR).join(format!("{}.leaderboard.json", session_id))
+}
+
+/**
+ * 写入排行榜文件（先以 0600 权限写入临时文件再替换，中断时不会留下半个文件）
+ *
+ * @param path - 排行榜文件
+ * @param standings - 排行榜
+ */
+pub fn save(path: &Path, standings: &Standings) -> Result<(), String> {
+    let dir = path.parent().ok_or("排行榜文件路径无效")?;
+    fs::create_dir_all(dir).map_err(|e| format!("无法创建会话状态目录: {}", e))?;
+    let json = Zeroizing::new(serde_json::to_string_pretty(standings).map_err(|e| format!("无法序列化排行榜: {}", e))?);
+    let tmp_path = path.with_extension("json.tmp");
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&tmp_path).map_err(|e| format!("无法写入排行榜: {}", e))?;
+    file.write_all(json.as_bytes()).map_err(|e| format!("无法写入排行榜: {}", e))?;
+    fs::rename(&tmp_path, path).map_err(|e| format!("无法写入排行榜: {}", e))
+}
+
+/**
+ * 读取排行榜文件；无法解析或由更新的版本写入时返回错误
+ *
+ * @param path - 排行榜文件
+ */
+pub fn load(path: &Path) -> Result<Standings, String> {
+    let data = Zeroizing::new(fs::read_to_string(path).map_err(|e| format!("无法读取排行榜文件 {}: {}", path.display(), e))?);
+    let standings: Standings = serde_json::from_str(&data).map_err(|e| format!("排行榜文件已损坏（{}）: {}", path.display(), e))?;
+    if standings.version > LEADERBOARD_VERSION {
+        return Err(format!("排行榜文件由更新的版本（格式 {}）写入，不能在此版本中继续", standings.version));
+    }
+    Ok(standings)
+}
+
+/// 运行期间自动保存排行榜：挤掉钱包时立即写入，只是增加钱包时最多每 AUTOSAVE_INTERVAL 写入一次
+pub struct LeaderboardWriter {
+    path: PathBuf,
+    last_saved: Option<Instant>,
+    /// 是否有还没有写入的变化
+    dirty: bool,
+    /// 是否已经报告过写入失败（只报告一次，会话照常进行）
+    failed: bool,
+}
+
+impl LeaderboardWriter {
+    /**
+     * 创建排行榜文件的写入方（第一次变化时才写入）
+     *
+     * @param path - 排行榜文件
+     */
+    pub fn new(path: PathBuf) -> Self {
+        LeaderboardWriter { path, last_saved: None, dirty: false, failed: false }
+    }
+
+    /**
+     * 排行榜文件的路径
+     */
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /**
+     * 记录一次提交的结果：挤掉钱包时立即写入，增加钱包时标记为待写入并按间隔写入
+     *
+     * @param board - 排行榜
+     * @param placement - 提交的结果
+     * @param now - 当前时刻
+     * @returns 第一次写入失败的原因（之后的失败不再返回）
+     */
+    pub fn record(&mut self, board: &mut Leaderboard, placement: &Placement, now: Instant) -> Option<String> {
+        match placement {
+            Placement::Rejected => None,
+            Placement::Added => {
+                self.dirty = true;
+                self.tick(board, now)
+            }
+            Placement::Displaced(_) => self.write(board, now),
+        }
+    }
+
+    /**
+     * 有待写入的变化且距离上一次写入已经超过 AUTOSAVE_INTERVAL 时写入（进度事件时调用）
+     *
+     * @param board - 排行榜
+     * @param now - 当前时刻
+     */
+    pub fn tick(&mut self, board: &mut Leaderboard, now: Instant) -> Option<String> {
+        if !self.dirty || self.last_saved.is_some_and(|last| now.saturating_duration_since(last) < AUTOSAVE_INTERVAL) {
+            return None;
+        }
+        self.write(board, now)
+    }
+
+    /**
+     * 立即写入（恢复的会话开始时先把继续的排行榜写入新会话的文件）
+     *
+     * @param board - 排行榜
+     */
+    pub fn save_now(&mut self, board: &mut Leaderboard) -> Option<String> {
+        self.write(board, Instant::now())
+    }
+
+    /**
+     * 会话结束：写入还没有写入的变化
+     *
+     * @param board - 排行榜
+     */
+    pub fn finish(mut self, board: &mut Leaderboard) -> Option<String> {
+        if !self.dirty {
+            return None;
+        }
+        self.write(board, Instant::now())
+    }
+
+    fn write(&mut self, board: &mut Leaderboard, now: Instant) -> Option<String> {
+        board.standings.updated_at = timestamps::now_rfc3339();
+        self.last_saved = Some(now);
+        match save(&self.path, &board.standings) {
+            Ok(()) => {
+                self.dirty = false;
+                None
+            }
+            Err(_) if self.failed => None,
+            Err(e) => {
+                self.failed = true;
+                Some(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::with_data_dir;
+
+    /**
+     * 分数为 score 的第 index 个钱包（私钥由序号生成）
+     */
+    fn entry(index: u64, score: f64) -> LeaderboardEntry {
+        LeaderboardEntry {
+            address: format!("0x{:040x}", index),
+            score,
+            attempts: index * 100,
+            duration: index * 10,
+            found_at: "2026-01-01T00:00:00Z".to_string(),
+            private_key: Some(SecretHex::from(format!("{:064x}", index))),
+        }
+    }
+
+    /**
+     * 自动保存：第一次增加钱包时写入，之后只是增加钱包时 30 秒内不再写入，挤掉钱包时立即写入；
+     * 没有变化时不写入，分数不超过门槛的钱包被拒绝，排行榜文件只有所有者可以读写
+     */
+    #[test]
+    fn leaderboard_displacement_saves() -> Result<(), String> {
+        with_data_dir("leaderboard-saves", |dir| {
+            let path = leaderboard_path(dir, "score_20260101_000000000Z");
+            let mut board = Leaderboard::new("score_20260101_000000000Z", ScoreFunction::LongestRun, true);
+            let mut writer = LeaderboardWriter::new(path.clone());
+            let start = Instant::now();
+            let saved = |path: &Path| load(path).map(|standings| standings.entries.len());
+
+            let placement = board.offer(entry(1, 3.0));
+            if placement != Placement::Added || writer.record(&mut board, &placement, start).is_some() || saved(&path)? != 1 {
+                return Err("第一次增加钱包时没有写入排行榜文件".to_string());
+            }
+            for index in 2..=LEADERBOARD_SIZE as u64 {
+                let placement = board.offer(entry(index, 3.0 + index as f64));
+                writer.record(&mut board, &placement, start + Duration::from_secs(index));
+            }
+            if saved(&path)? != 1 || board.threshold() != 3.0 {
+                return Err(format!("只是增加钱包时 30 秒内又写入了排行榜文件，门槛为 {}", board.threshold()));
+            }
+
+            let placement = board.offer(entry(11, 4.0));
+            if placement != Placement::Displaced(entry(1, 3.0)) {
+                return Err(format!("排行榜已满时加入的钱包的结果为 {:?}", placement));
+            }
+            writer.record(&mut board, &placement, start + Duration::from_secs(12));
+            let standings = load(&path)?;
+            let scores: Vec<f64> = standings.entries.iter().map(|entry| entry.score).collect();
+            if scores.len() != LEADERBOARD_SIZE || scores.contains(&3.0) || !scores.windows(2).all(|pair| pair[0] >= pair[1]) {
+                return Err(format!("挤掉钱包后保存的排行榜为 {:?}", scores));
+            }
+            if board.offer(entry(12, 4.0)) != Placement::Rejected || board.threshold() != 4.0 {
+                return Err("分数不超过门槛的钱包没有被拒绝".to_string());
+            }
+            let modified = fs::metadata(&path).map_err(|e| e.to_string())?.modified().map_err(|e| e.to_string())?;
+            std::thread::sleep(Duration::from_millis(20));
+            if writer.tick(&mut board, start + Duration::from_secs(60)).is_some() || writer.finish(&mut board).is_some() {
+                return Err("写入排行榜文件失败".to_string());
+            }
+            if fs::metadata(&path).map_err(|e| e.to_string())?.modified().map_err(|e| e.to_string())? != modified {
+                return Err("没有变化时又写入了排行榜文件".to_string());
+            }
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let mode = fs::metadata(&path).map_err(|e| e.to_string())?.permissions().mode() & 0o777;
+                if mode != 0o600 {
+                    return Err(format!("排行榜文件的权限为 {:o}", mode));
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /**
+     * 恢复时排行榜继续：之前的会话保存的名次、门槛和最高分原样继续，新会话不保存私钥时去掉私钥；
+     * 由更新的版本写入的排行榜文件被拒绝
+     */
+    #[test]
+    fn leaderboard_resume() -> Result<(), String> {
+        with_data_dir("leaderboard-resume", |dir| {
+            let previous_path = leaderboard_path(dir, "score_20260101_000000000Z");
+            let mut previous = Leaderboard::new("score_20260101_000000000Z", ScoreFunction::LeadingZeros, true);
+            for index in 1..=LEADERBOARD_SIZE as u64 + 2 {
+                previous.offer(entry(index, index as f64));
+            }
+            let mut writer = LeaderboardWriter::new(previous_path.clone());
+            if let Some(e) = writer.save_now(&mut previous) {
+                return Err(e);
+            }
+
+            let standings = load(&previous_path)?;
+            let resumed = Leaderboard::resume("score_20260102_000000000Z", standings.clone(), true);
+            if resumed.standings.entries != previous.standings.entries || resumed.threshold() != 3.0 || resumed.best() != Some(&entry(12, 12.0)) {
+                return Err(format!("恢复的排行榜为 {:?}", resumed.standings.entries));
+            }
+            if resumed.standings.session_id != "score_20260102_000000000Z" || resumed.standings.score_function != ScoreFunction::LeadingZeros {
+                return Err("恢复的排行榜没有属于新的会话".to_string());
+            }
+            let redacted = Leaderboard::resume("score_20260102_000000000Z", standings.clone(), false);
+            if redacted.standings.entries.iter().any(|entry| entry.private_key.is_some()) || redacted.standings.entries.len() != LEADERBOARD_SIZE {
+                return Err("不保存私钥的会话恢复的排行榜仍带有私钥".to_string());
+            }
+
+            save(&previous_path, &Standings { version: LEADERBOARD_VERSION + 1, ..standings })?;
+            if load(&previous_path).is_ok() {
+                return Err("由更新的版本写入的排行榜文件没有被拒绝".to_string());
+            }
+            Ok(())
+        })
+    }
+}
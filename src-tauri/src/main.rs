@@ -2,5 +2,5 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
-    fancy_wallet_lib::run()
+    fancy_wallet_lib::run_app()
 }
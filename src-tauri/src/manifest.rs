@@ -0,0 +1,437 @@
+use crate::chain::{Chain, KNOWN_CHAINS};
+use crate::entropy::EntropyProvenance;
+use crate::hexutil::Hash32;
+use crate::keccak::KeccakBackend;
+use crate::scoring::ScoreWeights;
+use crate::stop::{StopReason, StopRecord};
+use crate::timer::ClockJump;
+use crate::worker::WorkerFailure;
+use crate::timestamps;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// 清单文件格式版本
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// 清单中的单个产物
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ManifestEntry {
+    /// 相对清单所在目录的路径
+    pub path: String,
+    /// 文件大小（字节），文件缺失时为空
+    pub size: Option<u64>,
+    /// 文件的 SHA-256，文件缺失时为空
+    pub sha256: Option<String>,
+    /// 写入清单时文件是否已经不存在
+    pub missing: bool,
+}
+
+/// 会话产物清单
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SessionManifest {
+    /// 文件格式版本
+    pub version: u32,
+    /// 靓号模式
+    pub pattern: String,
+    /// 会话结束时间（所有产物的修改时间都被设置为该时间）
+    pub session_end: String,
+    /// 会话停止原因（旧版本的清单中没有该字段）
+    #[serde(default)]
+    pub stop: Option<StopRecord>,
+    /// 会话来源的模板名称（直接开始的会话没有该字段）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// 会话生成的链（旧版本的清单中没有该字段）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain: Option<Chain>,
+    /// 会话实际执行的匹配条件（旧版本的清单中没有该字段）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matcher: Option<String>,
+    /// 会话备注（运行结束后通过 set_session_note 写入）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// 会话使用的评分权重（归一化后，用于复现评分；旧版本的清单中没有该字段）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score_weights: Option<ScoreWeights>,
+    /// 会话追加的集合名称（写入会话文件时没有该字段）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collection: Option<String>,
+    /// 会话使用的 Keccak-256 实现（旧版本的清单中没有该字段）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keccak_backend: Option<KeccakBackend>,
+    /// 私钥的随机数来源（旧版本的清单中没有该字段；确定性测试模式的 test_watermark 为 true）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entropy: Option<EntropyProvenance>,
+    /// 会话期间检测到的系统时间跳变（为空时省略；有跳变时记录中的时间可能不是单调递增的）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub clock_jumps: Vec<ClockJump>,
+    /// 会话期间异常退出的工作线程（为空时省略）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub worker_failures: Vec<WorkerFailure>,
+    /// 会话产物列表
+    pub artifacts: Vec<ManifestEntry>,
+}
+
+/// 写入清单时记录的会话信息
+#[derive(Debug, Clone, Copy)]
+pub struct SessionDetails<'a> {
+    /// 会话来源的模板名称
+    pub template: Option<&'a str>,
+    /// 会话生成的链
+    pub chain: Chain,
+    /// 会话实际执行的匹配条件
+    pub matcher: Option<&'a str>,
+    /// 会话使用的评分权重
+    pub score_weights: Option<&'a ScoreWeights>,
+    /// 会话追加的集合名称
+    pub collection: Option<&'a str>,
+    /// 会话使用的 Keccak-256 实现
+    pub keccak_backend: Option<KeccakBackend>,
+    /// 私钥的随机数来源
+    pub entropy: Option<&'a EntropyProvenance>,
+    /// 会话期间检测到的系统时间跳变
+    pub clock_jumps: &'a [ClockJump],
+    /// 会话期间异常退出的工作线程
+    pub worker_failures: &'a [WorkerFailure],
+}
+
+/// 产物校验状态
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactStatus {
+    /// 与清单一致
+    Ok,
+    /// 内容已被修改
+    Modified,
+    /// 文件已丢失
+    Missing,
+    /// 写入清单时就已经丢失
+    MissingAtCreation,
+}
+
+/// 单个产物的校验结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ArtifactCheck {
+    /// 相对路径
+    pub path: String,
+    /// 校验状态
+    pub status: ArtifactStatus,
+}
+
+/// 清单校验结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ManifestVerification {
+    /// 是否全部一致
+    pub ok: bool,
+    /// 各产物的校验结果
+    pub artifacts: Vec<ArtifactCheck>,
+}
+
+/**
+ * 计算文件的 SHA-256 和大小
+ */
+fn hash_file(path: &Path) -> std::io::Result<(Hash32, u64)> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    let mut size = 0u64;
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        size += n as u64;
+    }
+    Ok((Hash32::from(<[u8; 32]>::from(hasher.finalize())), size))
+}
+
+/**
+ * 设置文件的修改时间
+ */
+fn set_mtime(path: &Path, time: SystemTime) -> std::io::Result<()> {
+    // Windows 上修改时间需要以写权限打开，追加模式不会改动内容
+    File::options().append(true).open(path)?.set_modified(time)
+}
+
+/**
+ * 计算产物相对清单目录的路径
+ */
+fn relative_path(dir: &Path, artifact: &Path) -> String {
+    artifact
+        .strip_prefix(dir)
+        .unwrap_or(artifact)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/**
+ * 会话结束时统一产物的修改时间并写入清单
+ * 
+ * 已经消失的产物会被记录为 missing，而不是导致失败。
+ * 
+ * @param manifest_path - 清单文件路径（产物路径相对其所在目录记录）
+ * @param pattern - 靓号模式
+ * @param artifacts - 会话产物路径
+ * @param stop - 会话停止原因
+ * @param details - 会话的来源、链等信息
+ * @returns 写入的清单
+ */
+pub fn write_session_manifest(
+    manifest_path: &Path,
+    pattern: &str,
+    artifacts: &[PathBuf],
+    stop: &StopRecord,
+    details: &SessionDetails,
+) -> Result<SessionManifest, String> {
+    let dir = manifest_path.parent().unwrap_or(Path::new("."));
+    let end = SystemTime::now();
+    
+    let entries = artifacts
+        .iter()
+        .map(|artifact| {
+            let path = relative_path(dir, artifact);
+            match hash_file(artifact) {
+                Ok((sha256, size)) => {
+                    let _ = set_mtime(artifact, end);
+                    ManifestEntry { path, size: Some(size), sha256: Some(sha256.to_hex()), missing: false }
+                }
+                Err(_) => ManifestEntry { path, size: None, sha256: None, missing: true },
+            }
+        })
+        .collect();
+    
+    let manifest = build_manifest(pattern, entries, stop, details, end);
+    let json = serde_json::to_string_pretty(&manifest).map_err(|e| format!("无法序列化清单: {}", e))?;
+    fs::write(manifest_path, json).map_err(|e| format!("无法写入清单文件: {}", e))?;
+    let _ = set_mtime(manifest_path, end);
+    
+    Ok(manifest)
+}
+
+fn build_manifest(
+    pattern: &str,
+    artifacts: Vec<ManifestEntry>,
+    stop: &StopRecord,
+    details: &SessionDetails,
+    end: SystemTime,
+) -> SessionManifest {
+    SessionManifest {
+        version: MANIFEST_VERSION,
+        pattern: pattern.to_string(),
+        session_end: timestamps::to_rfc3339(end.into()),
+        stop: Some(stop.clone()),
+        template: details.template.map(str::to_string),
+        chain: Some(details.chain),
+        matcher: details.matcher.map(str::to_string),
+        note: None,
+        score_weights: details.score_weights.cloned(),
+        collection: details.collection.map(str::to_string),
+        keccak_backend: details.keccak_backend,
+        entropy: details.entropy.cloned(),
+        clock_jumps: details.clock_jumps.to_vec(),
+        worker_failures: details.worker_failures.to_vec(),
+        artifacts,
+    }
+}
+
+/**
+ * 清单文件大小的估计（字节）：按会话信息构造一份包含结果文件和回放文件的清单并序列化，用于开始前估计磁盘占用
+ *
+ * @param pattern - 靓号模式
+ * @param details - 会话的来源、链等信息
+ */
+pub fn estimated_size(pattern: &str, details: &SessionDetails) -> u64 {
+    let entry = |name: &str| ManifestEntry {
+        path: format!("{}_{}_{}.jsonl", name, pattern, timestamps::filename_stamp(chrono::Utc::now())),
+        size: Some(u64::MAX),
+        sha256: Some(Hash32::from([0u8; 32]).to_hex()),
+        missing: false,
+    };
+    let stop = StopRecord { reason: StopReason::SaveErrorPolicy, detail: Some(u64::MAX.to_string()) };
+    let manifest = build_manifest(pattern, vec![entry("wallet"), entry("replay")], &stop, details, SystemTime::now());
+    serde_json::to_string_pretty(&manifest).map_or(0, |json| json.len() as u64)
+}
+
+/**
+ * 查找记录了会话结果文件的清单（各链子目录中的 manifest_*.json）
+ *
+ * 清单以回放文件的会话名命名，与结果文件的会话标识不一定相同；同一次运行中同一模式的多次生成
+ * 会追加到同一个结果文件，因此可能有多份清单。
+ *
+ * @param wallets_dir - FancyWallets 目录
+ * @param session_id - 会话标识
+ */
+pub fn find_session_manifests(wallets_dir: &Path, session_id: &str) -> Vec<PathBuf> {
+    let csv_names = [format!("wallet_{}.csv", session_id), format!("wallets_{}.csv", session_id)];
+    let mut found = Vec::new();
+    for chain in KNOWN_CHAINS {
+        let Ok(entries) = fs::read_dir(chain.dir(wallets_dir)) else {
+            continue;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            let is_manifest = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("manifest_") && name.ends_with(".json"));
+            let lists_session = is_manifest
+                && fs::read_to_string(&path)
+                    .ok()
+                    .and_then(|data| serde_json::from_str::<SessionManifest>(&data).ok())
+                    .is_some_and(|manifest| manifest.artifacts.iter().any(|a| csv_names.contains(&a.path)));
+            if lists_session {
+                found.push(path);
+            }
+        }
+    }
+    found.sort();
+    found
+}
+
+/**
+ * 更新清单中的会话备注：原子地重写清单，并保留原来的修改时间（与产物一致）
+ *
+ * 产物列表不包含清单本身，修改备注不影响产物校验。
+ *
+ * @param manifest_path - 清单文件路径
+ * @param note - 备注（为空时删除）
+ */
+pub fn set_note(manifest_path: &Path, note: Option<&str>) -> Result<(), String> {
+    let data = fs::read_to_string(manifest_path).map_err(|e| format!("无法读取清单文件: {}", e))?;
+    let mut manifest: SessionManifest = serde_json::from_str(&data).map_err(|e| format!("清单格式错误: {}", e))?;
+    if manifest.version > MANIFEST_VERSION {
+        return Err(format!("清单由更新的版本（格式 {}）写入，不能在此版本中修改", manifest.version));
+    }
+    manifest.note = note.map(str::to_string);
+    let modified = fs::metadata(manifest_path).and_then(|meta| meta.modified()).ok();
+    
+    let json = serde_json::to_string_pretty(&manifest).map_err(|e| format!("无法序列化清单: {}", e))?;
+    let tmp_path = manifest_path.with_extension("json.tmp");
+    fs::write(&tmp_path, json).map_err(|e| format!("无法写入清单文件: {}", e))?;
+    fs::rename(&tmp_path, manifest_path).map_err(|e| format!("无法写入清单文件: {}", e))?;
+    if let Some(time) = modified {
+        let _ = set_mtime(manifest_path, time);
+    }
+    Ok(())
+}
+
+/**
+ * 按清单校验会话产物，检测丢失或被修改的文件
+ * 
+ * @param manifest_path - 清单文件路径
+ * @returns 校验结果
+ */
+pub fn verify_session_manifest(manifest_path: &Path) -> Result<ManifestVerification, String> {
+    let data = fs::read_to_string(manifest_path).map_err(|e| format!("无法读取清单文件: {}", e))?;
+    let manifest: SessionManifest =
+        serde_json::from_str(&data).map_err(|e| format!("清单文件格式错误: {}", e))?;
+    let dir = manifest_path.parent().unwrap_or(Path::new("."));
+    
+    let artifacts: Vec<ArtifactCheck> = manifest
+        .artifacts
+        .iter()
+        .map(|entry| {
+            let status = if entry.missing {
+                ArtifactStatus::MissingAtCreation
+            } else {
+                // 记录的哈希按统一规则解析，允许手工编辑后出现的大写或 0x 前缀
+                let recorded = entry.sha256.as_deref().and_then(|sha256| Hash32::parse(sha256).ok());
+                match hash_file(&dir.join(&entry.path)) {
+                    Ok((sha256, size)) if Some(sha256) == recorded && Some(size) == entry.size => {
+                        ArtifactStatus::Ok
+                    }
+                    Ok(_) => ArtifactStatus::Modified,
+                    Err(_) => ArtifactStatus::Missing,
+                }
+            };
+            ArtifactCheck { path: entry.path.clone(), status }
+        })
+        .collect();
+    
+    Ok(ManifestVerification {
+        ok: artifacts.iter().all(|a| a.status == ArtifactStatus::Ok),
+        artifacts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::with_data_dir;
+
+    fn details() -> SessionDetails<'static> {
+        SessionDetails { template: None, chain: Chain::Ethereum, matcher: None, score_weights: None, collection: None, keccak_backend: None, entropy: None, clock_jumps: &[], worker_failures: &[] }
+    }
+
+    fn statuses(verification: &ManifestVerification) -> Vec<(&str, ArtifactStatus)> {
+        verification.artifacts.iter().map(|check| (check.path.as_str(), check.status.clone())).collect()
+    }
+
+    /**
+     * 清单：所有产物的修改时间统一为会话结束时间，写入时已经消失的产物记录为 missing 而不是失败
+     */
+    #[test]
+    fn manifest_write() -> Result<(), String> {
+        with_data_dir("manifest_write", |dir| {
+            let csv = dir.join("wallet_888_test.csv");
+            let replay = dir.join("replay_888_test.jsonl");
+            fs::write(&csv, "address,private_key\n").map_err(|e| e.to_string())?;
+            fs::write(&replay, "{}\n").map_err(|e| e.to_string())?;
+            let stop = StopRecord { reason: StopReason::MaxMatches, detail: Some("1".to_string()) };
+            let manifest_path = dir.join("manifest_888_test.json");
+            let manifest = write_session_manifest(&manifest_path, "888", &[csv.clone(), replay.clone(), dir.join("gone.json")], &stop, &details())?;
+            let missing: Vec<&str> = manifest.artifacts.iter().filter(|entry| entry.missing).map(|entry| entry.path.as_str()).collect();
+            if missing != ["gone.json"] || manifest.artifacts[0].path != "wallet_888_test.csv" {
+                return Err(format!("清单的产物记录不正确: {:?}", manifest.artifacts));
+            }
+            let mtime = |path: &Path| fs::metadata(path).and_then(|meta| meta.modified()).map_err(|e| e.to_string());
+            if mtime(&csv)? != mtime(&replay)? || mtime(&csv)? != mtime(&manifest_path)? {
+                return Err("会话产物的修改时间没有统一".to_string());
+            }
+            let verification = verify_session_manifest(&manifest_path)?;
+            if verification.ok || statuses(&verification)[2] != ("gone.json", ArtifactStatus::MissingAtCreation) {
+                return Err(format!("写入时已经消失的产物的校验结果为 {:?}", verification));
+            }
+            Ok(())
+        })
+    }
+
+    /**
+     * 清单校验：未改动的产物通过，翻转一个字节（大小不变）的产物为 modified，删除的产物为 missing
+     */
+    #[test]
+    fn manifest_tamper() -> Result<(), String> {
+        with_data_dir("manifest_tamper", |dir| {
+            let csv = dir.join("wallet_888_test.csv");
+            let replay = dir.join("replay_888_test.jsonl");
+            fs::write(&csv, "address,private_key\n0x888a,0x01\n").map_err(|e| e.to_string())?;
+            fs::write(&replay, "{}\n").map_err(|e| e.to_string())?;
+            let stop = StopRecord { reason: StopReason::UserUi, detail: None };
+            let manifest_path = dir.join("manifest_888_test.json");
+            write_session_manifest(&manifest_path, "888", &[csv.clone(), replay.clone()], &stop, &details())?;
+            if !verify_session_manifest(&manifest_path)?.ok {
+                return Err("未改动的产物没有通过校验".to_string());
+            }
+
+            let mut data = fs::read(&csv).map_err(|e| e.to_string())?;
+            let last = data.len() - 2;
+            data[last] ^= 0x01;
+            fs::write(&csv, data).map_err(|e| e.to_string())?;
+            let verification = verify_session_manifest(&manifest_path)?;
+            if verification.ok || statuses(&verification) != [("wallet_888_test.csv", ArtifactStatus::Modified), ("replay_888_test.jsonl", ArtifactStatus::Ok)] {
+                return Err(format!("翻转一个字节后的校验结果为 {:?}", verification));
+            }
+
+            fs::remove_file(&replay).map_err(|e| e.to_string())?;
+            let verification = verify_session_manifest(&manifest_path)?;
+            if verification.ok || statuses(&verification)[1] != ("replay_888_test.jsonl", ArtifactStatus::Missing) {
+                return Err(format!("删除产物后的校验结果为 {:?}", verification));
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,185 @@
+use std::time::{Duration, Instant};
+
+/// 只给出 batch_interval_ms 时每批最多的匹配数
+pub const DEFAULT_BATCH_SIZE: u32 = 100;
+/// 只给出 batch_size 时一批最长的等待时间（毫秒）
+pub const DEFAULT_BATCH_INTERVAL_MS: u64 = 250;
+/// batch_size 的上限（一批的钱包都保存在内存中，写入前含有私钥）
+pub const MAX_BATCH_SIZE: u32 = 10_000;
+/// batch_interval_ms 的上限
+pub const MAX_BATCH_INTERVAL_MS: u64 = 60_000;
+
+/**
+ * 检查批量写入的设置
+ *
+ * @param batch_size - 每批最多的匹配数
+ * @param batch_interval_ms - 一批最长的等待时间（毫秒）
+ */
+pub fn validate(batch_size: Option<u32>, batch_interval_ms: Option<u64>) -> Result<(), String> {
+    if batch_size.is_some_and(|size| size == 0 || size > MAX_BATCH_SIZE) {
+        return Err(format!("batch_size 必须在 1 到 {} 之间", MAX_BATCH_SIZE));
+    }
+    if batch_interval_ms.is_some_and(|ms| ms == 0 || ms > MAX_BATCH_INTERVAL_MS) {
+        return Err(format!("batch_interval_ms 必须在 1 到 {} 之间", MAX_BATCH_INTERVAL_MS));
+    }
+    Ok(())
+}
+
+/// 等待一起写入和发送的匹配：攒够 size 个或最早的一个等待超过 interval 时写入
+#[derive(Debug)]
+pub struct MatchBatch<T> {
+    size: usize,
+    interval: Duration,
+    /// 这一批最早的匹配加入的时刻（批为空时为空）
+    oldest: Option<Instant>,
+    pending: Vec<T>,
+}
+
+impl<T> MatchBatch<T> {
+    /**
+     * 按会话参数创建批量写入（两个参数都没有给出时不批量写入，返回空）
+     *
+     * @param batch_size - 每批最多的匹配数（为空时为 DEFAULT_BATCH_SIZE）
+     * @param batch_interval_ms - 一批最长的等待时间（毫秒，为空时为 DEFAULT_BATCH_INTERVAL_MS）
+     */
+    pub fn new(batch_size: Option<u32>, batch_interval_ms: Option<u64>) -> Option<Self> {
+        if batch_size.is_none() && batch_interval_ms.is_none() {
+            return None;
+        }
+        let size = batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1) as usize;
+        Some(MatchBatch {
+            size,
+            interval: Duration::from_millis(batch_interval_ms.unwrap_or(DEFAULT_BATCH_INTERVAL_MS)),
+            oldest: None,
+            pending: Vec::with_capacity(size),
+        })
+    }
+
+    /**
+     * 加入一个匹配
+     *
+     * @param item - 匹配
+     * @param now - 当前时刻
+     */
+    pub fn push(&mut self, item: T, now: Instant) {
+        self.oldest.get_or_insert(now);
+        self.pending.push(item);
+    }
+
+    /**
+     * 这一批是否应该写入：攒够 size 个，或最早的匹配已经等待了 interval
+     *
+     * @param now - 当前时刻
+     */
+    pub fn due(&self, now: Instant) -> bool {
+        self.pending.len() >= self.size || self.oldest.is_some_and(|oldest| now.saturating_duration_since(oldest) >= self.interval)
+    }
+
+    /**
+     * 取出这一批的全部匹配（按加入的顺序），开始新的一批
+     */
+    pub fn take(&mut self) -> Vec<T> {
+        self.oldest = None;
+        std::mem::replace(&mut self.pending, Vec::with_capacity(self.size))
+    }
+
+    /**
+     * 这一批是否为空
+     */
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::Chain;
+    use crate::entropy::RngMode;
+    use crate::gui::{write_wallet_row, write_wallet_rows, Wallet};
+    use crate::hexutil::{Address, PrivKeyHex, SecretHex};
+    use crate::saved_wallets::CsvPrivateKeys;
+    use crate::templates::GenerationParams;
+    use crate::timestamps;
+    use secp256k1::{PublicKey, Secp256k1};
+    use crate::test_support::with_data_dir;
+    use crate::self_test::KNOWN_KEYS;
+
+    /**
+     * 批量写入：攒够 batch_size 个或最早的匹配等待超过 batch_interval_ms 时写入；一次写入多行的 CSV 与逐行写入的内容相同，
+     * 标题只写一次；无效的批量设置和与 verify_unused 同时使用时拒绝
+     */
+    #[test]
+    fn match_batch() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        for (size, interval) in [(Some(0), None), (Some(MAX_BATCH_SIZE + 1), None), (None, Some(0)), (None, Some(MAX_BATCH_INTERVAL_MS + 1))] {
+            if validate(size, interval).is_ok() {
+                return Err(format!("无效的批量设置 {:?} / {:?} 没有被拒绝", size, interval));
+            }
+        }
+        let batched = GenerationParams { pattern: "8".to_string(), batch_size: Some(50), ..GenerationParams::default() };
+        batched.validate()?;
+        if (GenerationParams { verify_unused: Some("https://rpc.example.org".to_string()), ..batched.clone() }).validate().is_ok() {
+            return Err("batch_size 与 verify_unused 同时使用时没有被拒绝".to_string());
+        }
+        if MatchBatch::<u32>::new(None, None).is_some() {
+            return Err("没有批量设置时仍然批量写入".to_string());
+        }
+        let start = std::time::Instant::now();
+        let mut batch = MatchBatch::new(Some(3), Some(1000)).ok_or("批量设置没有生效")?;
+        batch.push(1, start);
+        batch.push(2, start);
+        if batch.due(start) {
+            return Err("不足 batch_size 个匹配时就要写入".to_string());
+        }
+        batch.push(3, start);
+        if !batch.due(start) || batch.take() != vec![1, 2, 3] || !batch.is_empty() {
+            return Err("攒够 batch_size 个匹配时没有按顺序取出".to_string());
+        }
+        batch.push(4, start + std::time::Duration::from_millis(10));
+        if batch.due(start + std::time::Duration::from_millis(1009)) || !batch.due(start + std::time::Duration::from_millis(1010)) {
+            return Err("等待时间没有按最早的匹配计算".to_string());
+        }
+
+        with_data_dir("match_batch", |dir| {
+            let wallets: Vec<Wallet> = KNOWN_KEYS
+                .iter()
+                .take(3)
+                .enumerate()
+                .map(|(index, (key, _))| {
+                    let secret = PrivKeyHex::parse(key)?.secret_key();
+                    Ok(Wallet {
+                        index: index as u64 + 1,
+                        address: Address::from_public_key(&PublicKey::from_secret_key(&secp, &secret)),
+                        private_key: SecretHex::from(key.to_string()),
+                        attempts: 100 * (index as u64 + 1),
+                        duration: index as u64,
+                        matched_spans: Vec::new(),
+                        rng_mode: RngMode::default(),
+                        test_wallet: false,
+                        mnemonic: None,
+                        chain_address: None,
+                        matched_pattern: "8".to_string(),
+                        created_at: timestamps::now_rfc3339(),
+                    })
+                })
+                .collect::<Result<_, String>>()?;
+            let single = dir.join("wallet_8_single.csv");
+            for wallet in &wallets {
+                write_wallet_row(&single, wallet, "8", Chain::Ethereum, CsvPrivateKeys::Full)?;
+            }
+            let batched = dir.join("wallet_8_batched.csv");
+            let rows: Vec<(&Wallet, &str)> = wallets.iter().map(|wallet| (wallet, "8")).collect();
+            let written = write_wallet_rows(&batched, &rows, Chain::Ethereum, CsvPrivateKeys::Full)?;
+            let single = std::fs::read_to_string(&single).map_err(|e| e.to_string())?;
+            let batched = std::fs::read_to_string(&batched).map_err(|e| e.to_string())?;
+            if single != batched || written.bytes != batched.len() as u64 {
+                return Err(format!("一次写入的 CSV 与逐行写入的不同（{} 字节）:\n{}\n{}", written.bytes, single, batched));
+            }
+            if batched.lines().filter(|line| *line == CsvPrivateKeys::Full.header()).count() != 1 {
+                return Err("一次写入多行时标题没有只写一次".to_string());
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,146 @@
+use crate::bitcoin::{self, BitcoinAddressType};
+use crate::chain::Chain;
+use crate::pattern::{CaseMode, MatchMode, MatchedSpan, PatternType};
+use crate::templates::GenerationParams;
+use serde::{Deserialize, Serialize};
+
+/// 预览时的匹配设置（含义与 generate_fancy_wallet 的同名参数相同，未给出的按生成时的默认值）
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PatternMatchOptions {
+    /// 模式类型
+    #[serde(default)]
+    pub pattern_type: Option<PatternType>,
+    /// 是否区分大小写
+    #[serde(default)]
+    pub case_sensitive: Option<bool>,
+    /// 大小写方式
+    #[serde(default)]
+    pub case_mode: Option<CaseMode>,
+    /// 比较方式
+    #[serde(default)]
+    pub match_mode: Option<MatchMode>,
+    /// 生成的链（默认 ethereum）
+    #[serde(default)]
+    pub chain: Option<Chain>,
+    /// 比特币地址的格式（默认 p2pkh；必须与示例地址的格式一致）
+    #[serde(default)]
+    pub bitcoin_address: Option<BitcoinAddressType>,
+}
+
+/// 示例地址的匹配预览（check_pattern_match 的返回值）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PatternMatchPreview {
+    /// 是否匹配
+    pub matched: bool,
+    /// 规范格式的地址（以太坊为 EIP-55 checksum 格式；区分大小写时按这个格式比较）
+    pub address: String,
+    /// 地址中不参与比较的固定开头（以太坊的 0x、波场的 T、比特币的 1 或 bc1q，Solana 为空）
+    pub fixed_prefix: String,
+    /// 满足模式的字符区间（与找到的钱包的 matched_spans 相同，下标不含 fixed_prefix；不匹配时为空）
+    pub spans: Vec<MatchedSpan>,
+    /// 实际执行的匹配条件（与会话记录的 matcher 相同）
+    pub matcher: String,
+}
+
+/**
+ * 按生成时的解析和匹配流程检查示例地址是否满足模式
+ *
+ * 模式由 GenerationParams::parsed_patterns 解析，与生成循环使用同一个 ParsedPattern::matches 和 matched_spans，
+ * 预览的结果与实际生成一致。
+ *
+ * @param address - 示例地址（生成的链的文本格式）
+ * @param pattern - 靓号模式
+ * @param options - 匹配设置
+ */
+pub fn check(address: &str, pattern: &str, options: &PatternMatchOptions) -> Result<PatternMatchPreview, String> {
+    let chain = options.chain.unwrap_or_default();
+    let params = GenerationParams {
+        pattern: pattern.to_string(),
+        chain: Some(chain.as_str().to_string()),
+        bitcoin_address: options.bitcoin_address,
+        case_sensitive: options.case_sensitive,
+        case_mode: options.case_mode,
+        pattern_type: options.pattern_type,
+        match_mode: options.match_mode,
+        ..GenerationParams::default()
+    };
+    params.validate_patterns()?;
+    if chain == Chain::Bitcoin && bitcoin::address_type(address) != options.bitcoin_address.unwrap_or_default() {
+        return Err(format!("示例地址不是 {} 格式的比特币地址: {}", options.bitcoin_address.unwrap_or_default().as_str(), address.trim()));
+    }
+    let (address, fixed_prefix, match_text) = chain.match_text_of(address)?;
+    let parsed = params.parsed_patterns().remove(0);
+    let spans = parsed.matched_spans(&match_text);
+    Ok(PatternMatchPreview {
+        matched: spans.is_some(),
+        address,
+        fixed_prefix: fixed_prefix.to_string(),
+        spans: spans.unwrap_or_default(),
+        matcher: parsed.matcher().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::MatchRule;
+    use crate::hexutil::PrivKeyHex;
+    use secp256k1::{PublicKey, Secp256k1};
+    use crate::self_test::KNOWN_KEYS;
+
+    /**
+     * 匹配预览：示例地址按任意大小写输入时规范为 checksum 格式，区分大小写、比较方式和区间与生成一致；
+     * 各链由地址得到的比较文本与生成循环由公钥得到的相同，地址格式不对时返回错误
+     */
+    #[test]
+    fn pattern_match_preview() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        let (key, address) = KNOWN_KEYS[0];
+        let lowercase = format!("0x{}", address.to_ascii_lowercase());
+        let prefix = PatternMatchOptions { match_mode: Some(MatchMode::Prefix), ..PatternMatchOptions::default() };
+        let preview = check(&lowercase, "7e5f", &prefix)?;
+        let spans: Vec<(usize, usize, MatchRule)> = preview.spans.iter().map(|span| (span.start, span.end, span.rule)).collect();
+        if !preview.matched || preview.address != format!("0x{}", address) || preview.fixed_prefix != "0x" || spans != [(0, 4, MatchRule::Prefix)] {
+            return Err(format!("前缀模式的预览结果不正确: {:?}", preview));
+        }
+        let exact = PatternMatchOptions { case_sensitive: Some(true), ..prefix.clone() };
+        if check(&lowercase, "7e5f", &exact)?.matched {
+            return Err("区分大小写时小写的模式匹配了 checksum 中大写的字母".to_string());
+        }
+        let suffix = PatternMatchOptions { match_mode: Some(MatchMode::Suffix), ..exact.clone() };
+        let preview = check(&lowercase, "5Bdf", &suffix)?;
+        if !preview.matched || preview.spans.iter().map(|span| (span.start, span.end)).collect::<Vec<_>>() != [(36, 40)] {
+            return Err(format!("区分大小写的后缀模式的预览结果不正确: {:?}", preview));
+        }
+        let both = check(&lowercase, "7", &PatternMatchOptions::default())?;
+        if both.matched || !both.spans.is_empty() {
+            return Err(format!("前后缀都要匹配时只有前缀满足的地址被判为匹配: {:?}", both));
+        }
+
+        let public_key = PublicKey::from_secret_key(&secp, &PrivKeyHex::parse(key)?.secret_key());
+        let encoders = [
+            (Chain::Ethereum, BitcoinAddressType::P2pkh),
+            (Chain::Tron, BitcoinAddressType::P2pkh),
+            (Chain::Bitcoin, BitcoinAddressType::P2pkh),
+            (Chain::Bitcoin, BitcoinAddressType::P2wpkh),
+        ];
+        for (chain, bitcoin_address) in encoders {
+            let encoder = chain.address_encoder(bitcoin_address).ok_or("没有地址编码")?;
+            let text = encoder.encode(encoder.address(&public_key));
+            let (canonical, fixed_prefix, match_text) = chain.match_text_of(&text)?;
+            if canonical != text || match_text != encoder.match_text(&public_key, true) || format!("{}{}", fixed_prefix, match_text) != text {
+                return Err(format!("{} 地址 {} 的比较文本为 {}，生成循环为 {}", chain.as_str(), text, match_text, encoder.match_text(&public_key, true)));
+            }
+            let options = PatternMatchOptions { chain: Some(chain), bitcoin_address: Some(bitcoin_address), ..prefix.clone() };
+            if !check(&text, &match_text[..3], &options)?.matched {
+                return Err(format!("{} 地址 {} 不匹配自己开头的 {}", chain.as_str(), text, &match_text[..3]));
+            }
+        }
+        let p2wpkh = PatternMatchOptions { chain: Some(Chain::Bitcoin), bitcoin_address: Some(BitcoinAddressType::P2wpkh), ..prefix.clone() };
+        let p2pkh_text = BitcoinAddressType::P2pkh.encoder().encode(bitcoin::hash160(&public_key));
+        if check(&p2pkh_text, "q", &p2wpkh).is_ok() || check("0x1234", "7e5f", &prefix).is_ok() {
+            return Err("格式不对的示例地址没有被拒绝".to_string());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,336 @@
+use crate::hexutil::Address;
+use crate::replay::ReplayRecorder;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// 默认的内存软上限（字节）
+pub const DEFAULT_SOFT_LIMIT_BYTES: u64 = 64 * 1024 * 1024;
+
+/// 软上限的最小值（字节）
+const MIN_SOFT_LIMIT_BYTES: u64 = 64 * 1024;
+
+/// 丢弃较早的结果时，降到软上限的多少比例以下（避免每次找到匹配都触发一次削减）
+const SHED_TARGET_RATIO: f64 = 0.75;
+
+/// 保留的单个地址占用的字节数
+const ADDRESS_BYTES: u64 = std::mem::size_of::<Address>() as u64;
+
+/// 命令结果中默认最多返回的钱包数
+pub const DEFAULT_RETURNED_WALLETS: usize = 10_000;
+
+/// 命令结果中最多返回的钱包数的上限
+pub const MAX_RETURNED_WALLETS: usize = 1_000_000;
+
+/// 当前的内存软上限
+static SOFT_LIMIT: AtomicU64 = AtomicU64::new(DEFAULT_SOFT_LIMIT_BYTES);
+
+/**
+ * 当前的内存软上限（字节）
+ */
+pub fn soft_limit() -> u64 {
+    SOFT_LIMIT.load(Ordering::SeqCst)
+}
+
+/**
+ * 设置内存软上限，对之后开始的会话生效
+ *
+ * @param bytes - 软上限（字节）
+ */
+pub fn set_soft_limit(bytes: u64) -> Result<(), String> {
+    if bytes < MIN_SOFT_LIMIT_BYTES {
+        return Err(format!("内存软上限不能低于 {} 字节", MIN_SOFT_LIMIT_BYTES));
+    }
+    SOFT_LIMIT.store(bytes, Ordering::SeqCst);
+    Ok(())
+}
+
+/**
+ * 命令结果中最多返回的钱包数
+ *
+ * @param limit - 上限（可选，默认 DEFAULT_RETURNED_WALLETS，值为 1 到 MAX_RETURNED_WALLETS）
+ */
+pub fn returned_wallets_limit(limit: Option<usize>) -> Result<usize, String> {
+    let limit = limit.unwrap_or(DEFAULT_RETURNED_WALLETS);
+    if limit == 0 || limit > MAX_RETURNED_WALLETS {
+        return Err(format!("返回的钱包数必须在 1 到 {} 之间", MAX_RETURNED_WALLETS));
+    }
+    Ok(limit)
+}
+
+/// 会话主要数据结构的内存统计（估算值）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// 保留的已找到地址数（用于重新订阅时的追赶快照）
+    pub results_retained: usize,
+    /// 保留的已找到地址占用的字节数
+    pub results_bytes: u64,
+    /// 已丢弃的较早地址数（这些地址仍完整地保存在结果文件中）
+    pub results_shed: u64,
+    /// 回放历史在内存中保留的事件数
+    pub replay_events: usize,
+    /// 回放历史占用的字节数
+    pub replay_bytes: u64,
+    /// 合计
+    pub total_bytes: u64,
+    /// 软上限
+    pub soft_limit_bytes: u64,
+}
+
+/// 超过软上限时采取的措施
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum SheddingAction {
+    /// 抽稀回放历史中的进度快照（回放文件同步重写）
+    DecimateReplay { dropped_samples: usize },
+    /// 丢弃追赶快照中较早的地址（结果文件不受影响）
+    DropOldestResults { dropped: usize, retained: usize },
+}
+
+/// 超过内存软上限时发送的警告
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MemoryWarning {
+    /// 会话标识
+    pub session_id: String,
+    /// 采取措施后的内存统计
+    pub usage: MemoryUsage,
+    /// 采取的措施
+    pub actions: Vec<SheddingAction>,
+}
+
+/// 单个会话的内存统计和软上限
+///
+/// 超过软上限时依次采取以下措施，并通过返回的警告报告：
+/// 1. 抽稀回放历史中的进度快照；
+/// 2. 仍然超过时，丢弃追赶快照中较早的地址，直到降到软上限的 3/4 以下（至少保留最新的一个）。
+///
+/// 结果文件始终保留全部结果，丢弃的只是内存中的副本。
+pub struct SessionMemory {
+    session_id: String,
+    soft_limit: u64,
+    found: VecDeque<Address>,
+    found_bytes: u64,
+    found_shed: u64,
+}
+
+impl SessionMemory {
+    /**
+     * 创建会话的内存统计
+     *
+     * @param session_id - 会话标识
+     * @param soft_limit - 软上限（字节）
+     */
+    pub fn new(session_id: &str, soft_limit: u64) -> Self {
+        SessionMemory {
+            session_id: session_id.to_string(),
+            soft_limit,
+            found: VecDeque::new(),
+            found_bytes: 0,
+            found_shed: 0,
+        }
+    }
+
+    /**
+     * 记录找到的地址
+     */
+    pub fn push_found(&mut self, address: Address) {
+        self.found_bytes += ADDRESS_BYTES;
+        self.found.push_back(address);
+    }
+
+    /**
+     * 保留的已找到地址（从旧到新）
+     */
+    pub fn found(&self) -> Vec<Address> {
+        self.found.iter().copied().collect()
+    }
+
+    /**
+     * 当前的内存统计
+     *
+     * @param replay - 会话的回放记录器（可选）
+     */
+    pub fn usage(&self, replay: Option<&ReplayRecorder>) -> MemoryUsage {
+        let (replay_events, replay_bytes) = replay.map(ReplayRecorder::retained).unwrap_or((0, 0));
+        MemoryUsage {
+            results_retained: self.found.len(),
+            results_bytes: self.found_bytes,
+            results_shed: self.found_shed,
+            replay_events,
+            replay_bytes,
+            total_bytes: self.found_bytes + replay_bytes,
+            soft_limit_bytes: self.soft_limit,
+        }
+    }
+
+    /**
+     * 更新内存统计；超过软上限时削减内存中的数据
+     *
+     * @param replay - 会话的回放记录器（可选）
+     * @returns 采取了措施时返回警告
+     */
+    pub fn check(&mut self, mut replay: Option<&mut ReplayRecorder>) -> Option<MemoryWarning> {
+        let mut actions = Vec::new();
+        if self.usage(replay.as_deref()).total_bytes > self.soft_limit {
+            if let Some(replay) = replay.as_deref_mut() {
+                let dropped_samples = replay.shed_history();
+                if dropped_samples > 0 {
+                    actions.push(SheddingAction::DecimateReplay { dropped_samples });
+                }
+            }
+        }
+
+        let usage = self.usage(replay.as_deref());
+        if usage.total_bytes > self.soft_limit {
+            let target = (self.soft_limit as f64 * SHED_TARGET_RATIO) as u64;
+            let mut dropped = 0;
+            while self.found.len() > 1 && self.found_bytes + usage.replay_bytes > target {
+                if self.found.pop_front().is_some() {
+                    self.found_bytes -= ADDRESS_BYTES;
+                    dropped += 1;
+                }
+            }
+            if dropped > 0 {
+                self.found_shed += dropped as u64;
+                actions.push(SheddingAction::DropOldestResults { dropped, retained: self.found.len() });
+            }
+        }
+
+        (!actions.is_empty()).then(|| MemoryWarning {
+            session_id: self.session_id.clone(),
+            usage: self.usage(replay.as_deref()),
+            actions,
+        })
+    }
+}
+
+/// 随命令结果返回的匹配：保留最早的 limit 个，之后的只记录有截断（全部匹配都在结果文件中）
+pub struct ReturnedResults<T> {
+    items: Vec<T>,
+    limit: usize,
+    truncated: bool,
+}
+
+impl<T> ReturnedResults<T> {
+    /**
+     * 创建空的结果列表
+     *
+     * @param limit - 最多保留的个数
+     */
+    pub fn new(limit: usize) -> Self {
+        ReturnedResults { items: Vec::new(), limit, truncated: false }
+    }
+
+    /**
+     * 记录一个匹配（已经保留了 limit 个时丢弃它并标记截断）
+     */
+    pub fn push(&mut self, item: T) {
+        if self.items.len() < self.limit {
+            self.items.push(item);
+        } else {
+            self.truncated = true;
+        }
+    }
+
+    /**
+     * 保留的匹配（按记录的顺序），以及是否有匹配因为达到上限而没有保留
+     */
+    pub fn into_parts(self) -> (Vec<T>, bool) {
+        (self.items, self.truncated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::Chain;
+    use crate::templates::GenerationParams;
+    use crate::test_support::with_data_dir;
+
+    /// 内存软上限检查：软上限、写入的进度快照数和找到的地址数（两种削减措施都会被触发）
+    const MEMORY_TEST_LIMIT: u64 = 16 * 1024;
+
+    const MEMORY_TEST_SAMPLES: u64 = 200;
+
+    const MEMORY_TEST_RESULTS: usize = 1000;
+
+    /// 返回的钱包上限测试中记录的匹配数
+    const RETURNED_TEST_MATCHES: usize = 10;
+
+    /**
+     * 内存软上限：超过后依次抽稀回放历史、丢弃较早的地址，降到上限以下并报告采取的措施
+     */
+    #[test]
+    fn memory_shedding() -> Result<(), String> {
+        with_data_dir("memory", |dir| {
+            let mut replay = ReplayRecorder::create(dir, "888", Chain::Ethereum)?.with_progress_interval(0);
+            let mut memory = SessionMemory::new("selftest", MEMORY_TEST_LIMIT);
+            for i in 1..=MEMORY_TEST_SAMPLES {
+                replay.record_progress(i * 1000, 0, i * 100, false);
+            }
+            let addresses: Vec<Address> = (0..MEMORY_TEST_RESULTS)
+                .map(|i| {
+                    let mut bytes = [0u8; 20];
+                    bytes[12..].copy_from_slice(&(i as u64).to_be_bytes());
+                    Address::from(bytes)
+                })
+                .collect();
+            for address in &addresses {
+                memory.push_found(*address);
+            }
+            
+            let _before = memory.usage(Some(&replay));
+            let warning = memory.check(Some(&mut replay)).ok_or("超过软上限后没有采取任何措施")?;
+            let decimated = warning.actions.iter().any(|a| matches!(a, SheddingAction::DecimateReplay { dropped_samples } if *dropped_samples > 0));
+            let dropped = warning.actions.iter().find_map(|a| match a {
+                SheddingAction::DropOldestResults { dropped, .. } => Some(*dropped),
+                _ => None,
+            });
+            if !decimated || dropped.is_none() {
+                return Err(format!("采取的措施不完整: {:?}", warning.actions));
+            }
+            let usage = &warning.usage;
+            if usage.total_bytes > MEMORY_TEST_LIMIT || usage.results_shed as usize + usage.results_retained != MEMORY_TEST_RESULTS {
+                return Err(format!("削减后的内存统计不正确: {:?}", usage));
+            }
+            if memory.found().last() != addresses.last() {
+                return Err("削减时丢弃了最新的地址".to_string());
+            }
+            if memory.check(Some(&mut replay)).is_some() {
+                return Err("没有新增数据时再次触发了削减".to_string());
+            }
+            Ok(())
+        })
+    }
+
+    /**
+     * 命令结果中的钱包：保留最早的 limit 个并标记截断，默认值和越界的上限
+     */
+    #[test]
+    fn returned_wallets() -> Result<(), String> {
+        let mut returned = ReturnedResults::new(3);
+        for i in 0..RETURNED_TEST_MATCHES {
+            returned.push(i);
+        }
+        let (items, truncated) = returned.into_parts();
+        if items != [0, 1, 2] || !truncated {
+            return Err(format!("超过上限后保留的结果不正确: {:?}（截断 {}）", items, truncated));
+        }
+        let mut exact = ReturnedResults::new(RETURNED_TEST_MATCHES);
+        (0..RETURNED_TEST_MATCHES).for_each(|i| exact.push(i));
+        if exact.into_parts() != ((0..RETURNED_TEST_MATCHES).collect(), false) {
+            return Err("恰好达到上限时被标记为截断".to_string());
+        }
+        
+        if returned_wallets_limit(None) != Ok(DEFAULT_RETURNED_WALLETS) {
+            return Err("返回的钱包数的默认值不正确".to_string());
+        }
+        for limit in [0, MAX_RETURNED_WALLETS + 1] {
+            let params = GenerationParams { pattern: "8".to_string(), max_returned_wallets: Some(limit), ..GenerationParams::default() };
+            if params.validate().is_ok() {
+                return Err(format!("无效的 max_returned_wallets {} 没有被拒绝", limit));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,371 @@
+use crate::profiles;
+use crate::timestamps;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 数据格式版本记录文件（位于应用数据目录）
+const VERSION_FILE: &str = "data_version.json";
+
+/// 迁移前备份的目录（位于应用数据目录）
+const BACKUPS_DIR: &str = "backups";
+
+/// 单个迁移步骤
+pub struct Migration {
+    /// 迁移完成后的数据格式版本
+    pub version: u32,
+    /// 迁移名称
+    pub name: &'static str,
+    /// 迁移可能修改、创建或删除的路径（迁移前备份，失败时据此还原）
+    pub paths: fn(&Path) -> Vec<PathBuf>,
+    /// 执行迁移，返回所做修改的说明
+    pub apply: fn(&Path) -> Result<Vec<String>, String>,
+}
+
+/// 按版本排列的全部迁移
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    name: "profiles",
+    paths: profiles::legacy_layout_paths,
+    apply: profiles::migrate_legacy_layout,
+}];
+
+/// 已执行的迁移
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AppliedMigration {
+    /// 迁移完成后的数据格式版本
+    pub version: u32,
+    /// 迁移名称
+    pub name: String,
+    /// 执行时间
+    pub applied_at: String,
+    /// 所做修改的说明
+    #[serde(default)]
+    pub changes: Vec<String>,
+    /// 迁移前文件的备份目录（没有需要备份的文件时为空）
+    #[serde(default)]
+    pub backup: Option<String>,
+}
+
+/// 数据格式版本记录
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+struct DataVersion {
+    version: u32,
+    #[serde(default)]
+    applied: Vec<AppliedMigration>,
+}
+
+/// 启动迁移的结果
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationStatus {
+    /// 数据已是最新格式
+    UpToDate,
+    /// 执行了迁移
+    Migrated,
+    /// 迁移失败，旧数据已还原，应用处于只读模式
+    Failed,
+    /// 数据由更新的版本写入，应用处于只读模式
+    NewerVersion,
+}
+
+/// 启动迁移报告（通过 data-migration 事件发送给前端）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MigrationReport {
+    /// 结果
+    pub status: MigrationStatus,
+    /// 迁移前的数据格式版本
+    pub from_version: u32,
+    /// 迁移后的数据格式版本（失败时与迁移前相同）
+    pub to_version: u32,
+    /// 本次执行的迁移（失败时为已还原的迁移）
+    pub applied: Vec<AppliedMigration>,
+    /// 失败原因
+    pub error: Option<String>,
+}
+
+impl MigrationReport {
+    /**
+     * 是否处于只读模式（迁移失败或数据来自更新的版本）
+     */
+    pub fn read_only(&self) -> bool {
+        matches!(self.status, MigrationStatus::Failed | MigrationStatus::NewerVersion)
+    }
+}
+
+/// 单个路径迁移前的状态
+struct BackedUpPath {
+    path: PathBuf,
+    /// 备份副本（迁移前是文件时）
+    backup: Option<PathBuf>,
+    /// 迁移前是否存在
+    existed: bool,
+}
+
+fn read_version(data_dir: &Path) -> Result<DataVersion, String> {
+    let path = data_dir.join(VERSION_FILE);
+    if !path.exists() {
+        return Ok(DataVersion::default());
+    }
+    let data = fs::read_to_string(&path).map_err(|e| format!("无法读取数据版本记录: {}", e))?;
+    serde_json::from_str(&data).map_err(|e| format!("数据版本记录格式错误: {}", e))
+}
+
+fn write_version(data_dir: &Path, record: &DataVersion) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(record).map_err(|e| format!("无法序列化数据版本记录: {}", e))?;
+    let path = data_dir.join(VERSION_FILE);
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, json).map_err(|e| format!("无法写入数据版本记录: {}", e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("无法写入数据版本记录: {}", e))
+}
+
+/**
+ * 备份迁移涉及的路径（只复制文件；目录只记录是否存在）
+ *
+ * @param data_dir - 应用数据目录
+ * @param backup_dir - 本次迁移的备份目录
+ * @param paths - 迁移涉及的路径
+ */
+fn back_up(data_dir: &Path, backup_dir: &Path, paths: Vec<PathBuf>) -> Result<Vec<BackedUpPath>, String> {
+    let mut backed_up = Vec::new();
+    for path in paths {
+        let backup = if path.is_file() {
+            let relative = path.strip_prefix(data_dir).unwrap_or(&path);
+            let target = backup_dir.join(relative);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("无法创建备份目录: {}", e))?;
+            }
+            fs::copy(&path, &target).map_err(|e| format!("无法备份 {}: {}", path.display(), e))?;
+            Some(target)
+        } else {
+            None
+        };
+        backed_up.push(BackedUpPath { existed: path.exists(), path, backup });
+    }
+    Ok(backed_up)
+}
+
+/**
+ * 把迁移涉及的路径还原到迁移前的状态
+ */
+fn restore(backed_up: &[BackedUpPath]) -> Result<(), String> {
+    for entry in backed_up.iter().rev() {
+        match (&entry.backup, entry.existed) {
+            (Some(backup), _) => {
+                if let Some(parent) = entry.path.parent() {
+                    fs::create_dir_all(parent).map_err(|e| format!("无法还原 {}: {}", entry.path.display(), e))?;
+                }
+                fs::copy(backup, &entry.path).map_err(|e| format!("无法还原 {}: {}", entry.path.display(), e))?;
+            }
+            (None, false) if entry.path.is_dir() => {
+                fs::remove_dir_all(&entry.path).map_err(|e| format!("无法删除 {}: {}", entry.path.display(), e))?;
+            }
+            (None, false) if entry.path.exists() => {
+                fs::remove_file(&entry.path).map_err(|e| format!("无法删除 {}: {}", entry.path.display(), e))?;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/**
+ * 启动时按版本依次执行尚未执行的迁移
+ *
+ * 每个迁移执行前先备份它涉及的文件；任何一个迁移失败时，本次已执行的迁移全部按备份还原，
+ * 版本记录保持不变，报告的状态为 failed，应用应进入只读模式。
+ *
+ * @param data_dir - 应用数据目录
+ * @param migrations - 按版本排列的迁移
+ * @returns 迁移报告
+ */
+pub fn run(data_dir: &Path, migrations: &[Migration]) -> MigrationReport {
+    let latest = migrations.last().map(|m| m.version).unwrap_or(0);
+    let mut record = match read_version(data_dir) {
+        Ok(record) => record,
+        Err(e) => {
+            return MigrationReport {
+                status: MigrationStatus::Failed,
+                from_version: 0,
+                to_version: 0,
+                applied: Vec::new(),
+                error: Some(e),
+            };
+        }
+    };
+    let from_version = record.version;
+    let mut report = MigrationReport {
+        status: MigrationStatus::UpToDate,
+        from_version,
+        to_version: from_version,
+        applied: Vec::new(),
+        error: None,
+    };
+    if from_version > latest {
+        report.status = MigrationStatus::NewerVersion;
+        report.error = Some(format!("数据由更新的版本（格式 {}）写入，此版本最高支持格式 {}", from_version, latest));
+        return report;
+    }
+
+    let pending: Vec<&Migration> = migrations.iter().filter(|m| m.version > from_version).collect();
+    if pending.is_empty() {
+        return report;
+    }
+
+    let run_dir = data_dir
+        .join(BACKUPS_DIR)
+        .join(format!("migration_{}", timestamps::filename_stamp(chrono::Utc::now())));
+    let mut backups: Vec<BackedUpPath> = Vec::new();
+    let mut failure = None;
+    for migration in pending {
+        let backup_dir = run_dir.join(format!("v{}_{}", migration.version, migration.name));
+        let result = back_up(data_dir, &backup_dir, (migration.paths)(data_dir)).and_then(|backed_up| {
+            let has_backup = backed_up.iter().any(|entry| entry.backup.is_some());
+            backups.extend(backed_up);
+            (migration.apply)(data_dir).map(|changes| (changes, has_backup))
+        });
+        match result {
+            Ok((changes, has_backup)) => report.applied.push(AppliedMigration {
+                version: migration.version,
+                name: migration.name.to_string(),
+                applied_at: timestamps::now_rfc3339(),
+                changes,
+                backup: has_backup.then(|| backup_dir.to_string_lossy().to_string()),
+            }),
+            Err(e) => {
+                failure = Some(format!("迁移 v{} {} 失败: {}", migration.version, migration.name, e));
+                break;
+            }
+        }
+    }
+
+    if failure.is_none() {
+        record.version = report.applied.last().map(|m| m.version).unwrap_or(from_version);
+        record.applied.extend(report.applied.iter().cloned());
+        if let Err(e) = write_version(data_dir, &record) {
+            failure = Some(e);
+        }
+    }
+
+    match failure {
+        None => {
+            report.status = MigrationStatus::Migrated;
+            report.to_version = record.version;
+        }
+        Some(e) => {
+            report.status = MigrationStatus::Failed;
+            report.error = Some(match restore(&backups) {
+                Ok(()) => format!("{}（已还原迁移前的数据）", e),
+                Err(restore_error) => format!("{}；还原失败: {}，备份位于 {}", e, restore_error, run_dir.display()),
+            });
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::with_data_dir;
+
+    /// 迁移框架之前的版本在应用数据目录根部留下的数据文件
+    const PRE_PROFILE_FIXTURES: &[(&str, &str)] = &[
+        ("settings.json", "{\"locale\":\"zh-CN\",\"default_pattern\":\"888\"}"),
+        ("history.json", "[{\"pattern\":\"888\",\"attempts\":4096}]"),
+    ];
+
+    fn write_fixtures(dir: &std::path::Path) -> Result<(), String> {
+        for (name, content) in PRE_PROFILE_FIXTURES {
+            std::fs::write(dir.join(name), content).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    fn failing_paths(dir: &std::path::Path) -> Vec<std::path::PathBuf> {
+        vec![dir.join("profiles").join("default").join("settings.json")]
+    }
+
+    fn failing_apply(dir: &std::path::Path) -> Result<Vec<String>, String> {
+        std::fs::write(dir.join("profiles").join("default").join("settings.json"), "half-migrated").map_err(|e| e.to_string())?;
+        Err("模拟的迁移失败".to_string())
+    }
+
+    /**
+     * 迁移 v1：旧版本根部的数据文件移入 default 配置并留有备份；已有配置列表的目录不被修改
+     */
+    #[test]
+    fn migration_v1_profiles() -> Result<(), String> {
+        with_data_dir("migration-v1", |dir| {
+            write_fixtures(dir)?;
+            let report = run(dir, MIGRATIONS);
+            if report.status != MigrationStatus::Migrated || report.to_version != 1 {
+                return Err(format!("迁移结果为 {:?}（版本 {}）: {:?}", report.status, report.to_version, report.error));
+            }
+            let backup = report.applied[0].backup.clone().ok_or("迁移没有留下备份")?;
+            for (name, content) in PRE_PROFILE_FIXTURES {
+                let moved = std::fs::read_to_string(dir.join("profiles").join("default").join(name)).unwrap_or_default();
+                let backed_up = std::fs::read_to_string(std::path::Path::new(&backup).join(name)).unwrap_or_default();
+                if moved != *content || backed_up != *content || dir.join(name).exists() {
+                    return Err(format!("{} 没有完整地移入 default 配置并备份", name));
+                }
+            }
+            if crate::profiles::load(dir)?.active != "default" {
+                return Err("迁移后的配置列表没有选中 default 配置".to_string());
+            }
+            let again = run(dir, MIGRATIONS);
+            if again.status != MigrationStatus::UpToDate || !again.applied.is_empty() {
+                return Err(format!("再次启动时的迁移结果为 {:?}", again.status));
+            }
+            Ok(())
+        })?;
+        
+        // 迁移框架之前的版本已经创建过配置列表：只记录版本，不修改任何文件
+        with_data_dir("migration-v1-existing", |dir| {
+            let existing = "{\"active\":\"work\",\"profiles\":[{\"name\":\"work\",\"default_save_path\":null,\"created_at\":\"\"}]}";
+            std::fs::write(dir.join("profiles.json"), existing).map_err(|e| e.to_string())?;
+            let report = run(dir, MIGRATIONS);
+            if report.status != MigrationStatus::Migrated || !report.applied[0].changes.is_empty() {
+                return Err(format!("已有配置列表时的迁移结果为 {:?}: {:?}", report.status, report.applied));
+            }
+            if std::fs::read_to_string(dir.join("profiles.json")).unwrap_or_default() != existing {
+                return Err("已有的配置列表被修改".to_string());
+            }
+            Ok(())
+        })
+    }
+
+    /**
+     * 迁移失败：本次已执行的迁移全部还原，版本不变并进入只读模式；更新版本写入的数据同样只读
+     */
+    #[test]
+    fn migration_rollback() -> Result<(), String> {
+        let failing = [
+            Migration { version: 1, ..MIGRATIONS[0] },
+            Migration { version: 2, name: "failing", paths: failing_paths, apply: failing_apply },
+        ];
+        with_data_dir("migration-rollback", |dir| {
+            write_fixtures(dir)?;
+            let report = run(dir, &failing);
+            if report.status != MigrationStatus::Failed || !report.read_only() || report.to_version != 0 {
+                return Err(format!("失败的迁移报告为 {:?}（版本 {}）", report.status, report.to_version));
+            }
+            for (name, content) in PRE_PROFILE_FIXTURES {
+                if std::fs::read_to_string(dir.join(name)).unwrap_or_default() != *content {
+                    return Err(format!("迁移失败后 {} 没有还原", name));
+                }
+            }
+            for leftover in ["profiles", "profiles.json", "data_version.json"] {
+                if dir.join(leftover).exists() {
+                    return Err(format!("迁移失败后残留了 {}", leftover));
+                }
+            }
+            
+            std::fs::write(dir.join("data_version.json"), "{\"version\":99}").map_err(|e| e.to_string())?;
+            let newer = run(dir, MIGRATIONS);
+            if newer.status != MigrationStatus::NewerVersion || !newer.read_only() {
+                return Err(format!("更新版本写入的数据的迁移结果为 {:?}", newer.status));
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,407 @@
+#[cfg(feature = "gui")]
+use crate::hexutil::{Address, SecretHex};
+use hmac::{Hmac, Mac};
+use secp256k1::{PublicKey, Scalar, Secp256k1, SecretKey, Signing};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
+use std::sync::OnceLock;
+
+/// BIP-39 英文词表（2048 个词，每行一个，与 bitcoin/bips 仓库中的 english.txt 完全相同）
+const WORDLIST_TEXT: &str = include_str!("bip39_english.txt");
+
+/// 词表文件的 SHA-256（测试中核对，词表被修改后所有助记词和指纹都会改变）
+#[cfg(all(test, feature = "gui"))]
+pub const WORDLIST_SHA256: &str = "2f5eed53a4727b4bf8880d8f3f199efc90e58503646d9ff8eff3a2ed3b24dbda";
+
+/// 生成时可以选择的助记词词数
+pub const MNEMONIC_WORD_COUNTS: [u8; 2] = [12, 24];
+
+/// 默认的助记词词数
+pub const DEFAULT_MNEMONIC_WORDS: u8 = 12;
+
+/// 词表中最长的词的字符数（估计结果文件大小时使用）
+#[cfg(feature = "gui")]
+const MAX_WORD_LENGTH: usize = 8;
+
+/// 由助记词计算种子时 PBKDF2-HMAC-SHA512 的迭代次数
+const PBKDF2_ROUNDS: u32 = 2048;
+
+/// BIP-32 强化派生的序号起点
+const HARDENED: u32 = 1 << 31;
+
+/// 以太坊第一个账户的派生路径 m/44'/60'/0'/0（最后一级为地址序号）
+const ETHEREUM_ACCOUNT_PATH: [u32; 4] = [44 | HARDENED, 60 | HARDENED, HARDENED, 0];
+
+/// 生成的钱包使用的派生路径（写入恢复单）
+#[cfg(feature = "gui")]
+pub const ETHEREUM_DERIVATION_PATH: &str = "m/44'/60'/0'/0/0";
+
+/// 地址序号之前的派生路径（扫描地址序号时使用）
+#[cfg(feature = "gui")]
+const ETHEREUM_ACCOUNT_PREFIX: &str = "m/44'/60'/0'/0";
+
+/// 派生路径的开头（主私钥）
+#[cfg(feature = "gui")]
+const MASTER_PATH: &str = "m";
+
+/// 拆分后的词表
+static WORDLIST: OnceLock<Vec<&'static str>> = OnceLock::new();
+
+type HmacSha512 = Hmac<Sha512>;
+
+/// 候选私钥的来源
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum KeySourceKind {
+    /// 直接使用随机的 32 字节作为私钥（默认）
+    #[default]
+    PrivateKey,
+    /// 每个候选都来自新的 BIP-39 助记词，按 m/44'/60'/0'/0/0 派生私钥（每次尝试多约 4000 次 HMAC-SHA512，慢得多）
+    Mnemonic,
+}
+
+/**
+ * BIP-39 英文词表
+ */
+pub fn wordlist() -> &'static [&'static str] {
+    WORDLIST.get_or_init(|| WORDLIST_TEXT.lines().collect())
+}
+
+/**
+ * 检查私钥来源设置：词数只能与 mnemonic 一起使用，且只能是 12 或 24
+ *
+ * @param key_source - 私钥来源
+ * @param mnemonic_words - 助记词词数
+ */
+pub fn validate(key_source: KeySourceKind, mnemonic_words: Option<u8>) -> Result<(), String> {
+    match mnemonic_words {
+        Some(_) if key_source != KeySourceKind::Mnemonic => Err("mnemonic_words 只能与 key_source: mnemonic 一起使用".to_string()),
+        Some(words) if !MNEMONIC_WORD_COUNTS.contains(&words) => Err(format!("助记词词数只能是 12 或 24（当前为 {}）", words)),
+        _ => Ok(()),
+    }
+}
+
+/**
+ * 词数对应的熵的字节数（12 个词 16 字节，24 个词 32 字节）
+ */
+pub fn entropy_len(words: u8) -> usize {
+    words as usize * 4 / 3
+}
+
+/**
+ * 由熵生成助记词：熵之后接 SHA-256 的前 (熵的位数 / 32) 位作为校验，每 11 位对应一个词
+ *
+ * @param entropy - 熵（16 到 32 字节，4 的倍数）
+ * @returns 以空格分隔的助记词
+ */
+pub fn from_entropy(entropy: &[u8]) -> String {
+    let checksum = Sha256::digest(entropy);
+    let entropy_bits = entropy.len() * 8;
+    let bit = |i: usize| {
+        let byte = if i < entropy_bits { entropy[i / 8] } else { checksum[(i - entropy_bits) / 8] };
+        (byte >> (7 - i % 8)) & 1
+    };
+    let words = wordlist();
+    (0..(entropy_bits + entropy_bits / 32) / 11)
+        .map(|word| words[(0..11).fold(0usize, |index, j| index << 1 | bit(word * 11 + j) as usize)])
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/**
+ * 检查助记词：词数、每个词都在词表中以及校验位（大小写和多余的空白不影响结果）
+ *
+ * @param phrase - 助记词
+ * @returns 规范化的助记词（小写，单个空格分隔）
+ */
+#[cfg(feature = "gui")]
+pub fn parse(phrase: &str) -> Result<String, String> {
+    let words: Vec<String> = phrase.split_whitespace().map(str::to_lowercase).collect();
+    if ![12, 15, 18, 21, 24].contains(&words.len()) {
+        return Err(format!("助记词必须是 12、15、18、21 或 24 个词（当前为 {} 个）", words.len()));
+    }
+    let list = wordlist();
+    let mut bits = Vec::with_capacity(words.len() * 11);
+    for (position, word) in words.iter().enumerate() {
+        let index = list.binary_search(&word.as_str()).map_err(|_| format!("第 {} 个词 {} 不在 BIP-39 英文词表中", position + 1, word))?;
+        bits.extend((0..11).rev().map(|j| (index >> j) & 1 == 1));
+    }
+    let entropy: Vec<u8> = bits[..words.len() * 11 * 32 / 33]
+        .chunks(8)
+        .map(|byte| byte.iter().fold(0u8, |value, &bit| value << 1 | bit as u8))
+        .collect();
+    let phrase = words.join(" ");
+    if from_entropy(&entropy) != phrase {
+        return Err("助记词的校验位不正确，请检查是否有抄错或顺序颠倒的词".to_string());
+    }
+    Ok(phrase)
+}
+
+/**
+ * 一个词数的助记词的最大字符数（估计结果文件大小时使用）
+ */
+#[cfg(feature = "gui")]
+pub fn max_phrase_len(words: u8) -> usize {
+    words as usize * (MAX_WORD_LENGTH + 1) - 1
+}
+
+/**
+ * 地址序号对应的派生路径（例如 m/44'/60'/0'/0/7）
+ */
+#[cfg(feature = "gui")]
+pub fn derivation_path(index: u32) -> String {
+    format!("{}/{}", ETHEREUM_ACCOUNT_PREFIX, index)
+}
+
+/**
+ * 解析 BIP-32 派生路径（例如 m/44'/60'/0'/0/0；强化派生用 '、h 或 H 标记，只写 m 时为主私钥）
+ *
+ * @param path - 派生路径
+ * @returns 各级的子序号（强化派生已加上 2^31）
+ */
+#[cfg(feature = "gui")]
+pub fn parse_derivation_path(path: &str) -> Result<Vec<u32>, String> {
+    let mut segments = path.trim().split('/');
+    if segments.next().map(str::trim) != Some(MASTER_PATH) {
+        return Err(format!("派生路径必须以 {} 开头（例如 {}）: {}", MASTER_PATH, ETHEREUM_DERIVATION_PATH, path.trim()));
+    }
+    segments
+        .enumerate()
+        .map(|(i, segment)| {
+            let invalid = || format!("派生路径的第 {} 段 '{}' 无效（应为小于 2^31 的数字，强化派生在后面加 '）", i + 1, segment);
+            let (digits, hardened) = match segment.strip_suffix(['\'', 'h', 'H']) {
+                Some(digits) => (digits, true),
+                None => (segment, false),
+            };
+            if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(invalid());
+            }
+            let index: u32 = digits.parse().ok().filter(|&index| index < HARDENED).ok_or_else(invalid)?;
+            Ok(if hardened { index | HARDENED } else { index })
+        })
+        .collect()
+}
+
+/**
+ * 派生路径的规范写法（强化派生统一用 ' 标记）
+ *
+ * @param path - 各级的子序号
+ */
+#[cfg(feature = "gui")]
+pub fn format_derivation_path(path: &[u32]) -> String {
+    std::iter::once(MASTER_PATH.to_string())
+        .chain(path.iter().map(|&child| if child >= HARDENED { format!("{}'", child - HARDENED) } else { child.to_string() }))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 由助记词和派生路径推导出的地址
+#[cfg(feature = "gui")]
+#[derive(Debug, Serialize, Clone)]
+pub struct MnemonicDerivation {
+    /// 0x 开头的 EIP-55 checksum 地址
+    pub address: String,
+    /// 规范写法的派生路径
+    pub derivation_path: String,
+    /// 十六进制私钥（只有明确要求时才返回）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<SecretHex>,
+}
+
+/**
+ * 由助记词、口令和派生路径推导地址（用于核对保存的助记词确实对应保存的地址）
+ *
+ * @param secp - secp256k1 上下文
+ * @param phrase - 助记词（先检查词表和校验位）
+ * @param path - 派生路径
+ * @param passphrase - 口令（没有时为空）
+ * @param include_private_key - 是否返回私钥
+ */
+#[cfg(feature = "gui")]
+pub fn derive_address<C: Signing>(secp: &Secp256k1<C>, phrase: &str, path: &str, passphrase: &str, include_private_key: bool) -> Result<MnemonicDerivation, String> {
+    let phrase = zeroize::Zeroizing::new(parse(phrase)?);
+    let children = parse_derivation_path(path)?;
+    let seed = zeroize::Zeroizing::new(to_seed(&phrase, passphrase));
+    let key = derive_path(secp, seed.as_slice(), &children).ok_or("派生结果不是有效的私钥，请换一个地址序号")?;
+    Ok(MnemonicDerivation {
+        address: Address::from_secret_key(secp, &key).to_checksum(),
+        derivation_path: format_derivation_path(&children),
+        private_key: include_private_key.then(|| SecretHex::encode(&key.secret_bytes())),
+    })
+}
+
+/**
+ * 由助记词计算 64 字节的种子（PBKDF2-HMAC-SHA512，盐为 "mnemonic" + 口令）
+ *
+ * 英文词表和口令都只使用 ASCII 时 NFKD 规范化不改变内容，这里不做规范化。
+ *
+ * @param phrase - 助记词
+ * @param passphrase - 口令（BIP-39 的第 25 个词，没有时为空）
+ */
+pub fn to_seed(phrase: &str, passphrase: &str) -> [u8; 64] {
+    let mac = HmacSha512::new_from_slice(phrase.as_bytes()).expect("HMAC 接受任意长度的密钥");
+    let mut first = mac.clone();
+    first.update(b"mnemonic");
+    first.update(passphrase.as_bytes());
+    first.update(&1u32.to_be_bytes());
+    let mut block: [u8; 64] = first.finalize().into_bytes().into();
+    let mut seed = block;
+    for _ in 1..PBKDF2_ROUNDS {
+        let mut round = mac.clone();
+        round.update(&block);
+        block = round.finalize().into_bytes().into();
+        seed.iter_mut().zip(block).for_each(|(seed, byte)| *seed ^= byte);
+    }
+    seed
+}
+
+/**
+ * HMAC-SHA512
+ */
+fn hmac_sha512(key: &[u8], parts: &[&[u8]]) -> [u8; 64] {
+    let mut mac = HmacSha512::new_from_slice(key).expect("HMAC 接受任意长度的密钥");
+    for part in parts {
+        mac.update(part);
+    }
+    mac.finalize().into_bytes().into()
+}
+
+/**
+ * BIP-32 派生一级子私钥
+ *
+ * @returns (子私钥, 子链码)；派生结果无效时（概率约为 2^-127）为空
+ */
+fn derive_child<C: Signing>(secp: &Secp256k1<C>, key: &SecretKey, chain_code: &[u8], child: u32) -> Option<(SecretKey, [u8; 32])> {
+    let index = child.to_be_bytes();
+    let digest = if child >= HARDENED {
+        hmac_sha512(chain_code, &[&[0], &key.secret_bytes(), &index])
+    } else {
+        hmac_sha512(chain_code, &[&PublicKey::from_secret_key(secp, key).serialize(), &index])
+    };
+    let (tweak, chain_code) = digest.split_at(32);
+    let tweak = Scalar::from_be_bytes(tweak.try_into().ok()?).ok()?;
+    Some((key.add_tweak(&tweak).ok()?, chain_code.try_into().ok()?))
+}
+
+/**
+ * 按以太坊的默认路径 m/44'/60'/0'/0/index 由种子派生私钥
+ *
+ * @param secp - secp256k1 上下文
+ * @param seed - BIP-39 种子
+ * @param index - 地址序号（小于 2^31）
+ * @returns 私钥；派生结果无效时（概率约为 2^-127）为空
+ */
+pub fn derive_account<C: Signing>(secp: &Secp256k1<C>, seed: &[u8], index: u32) -> Option<SecretKey> {
+    let [purpose, coin, account, change] = ETHEREUM_ACCOUNT_PATH;
+    derive_path(secp, seed, &[purpose, coin, account, change, index])
+}
+
+/**
+ * 按 BIP-32 派生路径由种子派生私钥
+ *
+ * @param secp - secp256k1 上下文
+ * @param seed - BIP-39 种子
+ * @param path - 各级的子序号（强化派生已加上 2^31；为空时为主私钥）
+ * @returns 私钥；派生结果无效时（概率约为 2^-127）为空
+ */
+pub fn derive_path<C: Signing>(secp: &Secp256k1<C>, seed: &[u8], path: &[u32]) -> Option<SecretKey> {
+    let master = hmac_sha512(b"Bitcoin seed", &[seed]);
+    let mut key = SecretKey::from_slice(&master[..32]).ok()?;
+    let mut chain_code: [u8; 32] = master[32..].try_into().ok()?;
+    for &child in path {
+        (key, chain_code) = derive_child(secp, &key, &chain_code, child)?;
+    }
+    Some(key)
+}
+
+#[cfg(all(test, feature = "gui"))]
+mod tests {
+    use super::*;
+    use crate::entropy::RngMode;
+    use crate::engine::{CancellationToken, GenerationLimits, SearchConfig, SearchEvent};
+    use crate::test_support::MNEMONIC_ADDRESS_VECTORS;
+
+    /// BIP-39 的固定用例：(熵, 助记词)，来自 BIP-39 的测试向量
+    const MNEMONIC_ENTROPY_VECTORS: &[(&str, &str)] = &[
+        ("00000000000000000000000000000000", "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"),
+        ("7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f", "legal winner thank year wave sausage worth useful legal winner thank yellow"),
+        (
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote",
+        ),
+    ];
+
+    /// BIP-39 测试向量中 abandon … about 加口令 TREZOR 的种子
+    const MNEMONIC_TREZOR_SEED: &str =
+        "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04";
+
+    #[test]
+    fn mnemonic() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        for (entropy, expected) in MNEMONIC_ENTROPY_VECTORS {
+            let phrase = from_entropy(&hex::decode(entropy).map_err(|e| e.to_string())?);
+            if phrase != *expected || parse(&phrase).as_deref() != Ok(*expected) {
+                return Err(format!("熵 {} 的助记词为 \"{}\"，应为 \"{}\"", entropy, phrase, expected));
+            }
+        }
+        let seed = hex::encode(to_seed(MNEMONIC_ENTROPY_VECTORS[0].1, "TREZOR"));
+        if seed != MNEMONIC_TREZOR_SEED {
+            return Err(format!("带口令的种子为 {}，应为 {}", seed, MNEMONIC_TREZOR_SEED));
+        }
+        let derive = |phrase: &str| {
+            let key = derive_account(&secp, &to_seed(phrase, ""), 0).ok_or("派生私钥失败")?;
+            Ok::<_, String>((key, Address::from_public_key(&PublicKey::from_secret_key(&secp, &key))))
+        };
+        for (phrase, expected) in MNEMONIC_ADDRESS_VECTORS {
+            let (_, address) = derive(phrase)?;
+            if address.to_checksum() != *expected {
+                return Err(format!("\"{}\" 派生的地址为 {}，应为 {}", phrase, address.to_checksum(), expected));
+            }
+        }
+        let invalid = [
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon",
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon",
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon fancy1",
+        ];
+        if let Some(phrase) = invalid.iter().find(|phrase| parse(phrase).is_ok()) {
+            return Err(format!("无效的助记词 \"{}\" 没有被拒绝", phrase));
+        }
+        if parse("  Test test TEST test test test test test test test test junk ").as_deref() != Ok(MNEMONIC_ADDRESS_VECTORS[1].0) {
+            return Err("大小写和多余空白不同的助记词没有规范化".to_string());
+        }
+        if validate(KeySourceKind::PrivateKey, Some(12)).is_ok() || validate(KeySourceKind::Mnemonic, Some(18)).is_ok() {
+            return Err("无效的助记词词数设置没有被拒绝".to_string());
+        }
+        
+        // 每次尝试都匹配：保存的助记词应当重新派生出同一个私钥和地址
+        for words in MNEMONIC_WORD_COUNTS {
+            let config = SearchConfig {
+                limits: GenerationLimits { max_matches: Some(2), ..GenerationLimits::default() },
+                rng_mode: RngMode::DeterministicTest,
+                workers: Some(1),
+                key_source: KeySourceKind::Mnemonic,
+                mnemonic_words: Some(words),
+                ..SearchConfig::new("8")
+            };
+            config.validate()?;
+            let mut found = Vec::new();
+            crate::engine::search_with(&config, &CancellationToken::new(), || false, |_| true, |event| {
+                if let SearchEvent::Found(key) = event {
+                    found.push(key);
+                }
+            });
+            if found.len() != 2 {
+                return Err(format!("{} 个词的助记词模式找到 {} 个匹配，应为 2 个", words, found.len()));
+            }
+            for key in &found {
+                let phrase = key.mnemonic.as_deref().ok_or("按助记词生成的匹配没有助记词")?;
+                let phrase = parse(phrase)?;
+                let (derived_key, address) = derive(&phrase)?;
+                if phrase.split(' ').count() != words as usize || address != key.address || hex::encode(derived_key.secret_bytes()) != key.private_key.expose() {
+                    return Err(format!("助记词 \"{}\" 没有重新派生出 {}", phrase, key.address));
+                }
+            }
+        }
+        Ok(())
+    }
+}
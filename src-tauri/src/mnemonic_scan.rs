@@ -0,0 +1,164 @@
+use crate::address::checksum_from_bytes;
+use crate::hexutil::{Address, SecretHex};
+use crate::mnemonic;
+use crate::pattern::{MatchedSpan, ParsedPattern};
+use secp256k1::Secp256k1;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+use zeroize::Zeroizing;
+
+/// 最大的地址序号（更大的序号属于强化派生，不在 m/44'/60'/0'/0/i 的范围内）
+pub const MAX_DERIVATION_INDEX: u32 = (1 << 31) - 1;
+
+/// 一个满足模式的地址序号
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IndexMatch {
+    /// 地址序号（派生路径的最后一级）
+    pub index: u32,
+    /// 完整的派生路径
+    pub derivation_path: String,
+    /// 地址
+    pub address: Address,
+    /// 私钥（只在保存时写入文件，不返回给界面；离开作用域时清零）
+    #[serde(skip)]
+    pub private_key: SecretHex,
+    /// 地址中满足模式的字符区间（用于界面高亮）
+    pub matched_spans: Vec<MatchedSpan>,
+}
+
+/// 扫描结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScanReport {
+    /// 已扫描的序号数
+    pub scanned: u64,
+    /// 满足模式的序号（按序号排列）
+    pub matches: Vec<IndexMatch>,
+    /// 运行时间（毫秒）
+    pub duration_ms: u64,
+    /// 是否被取消（取消前的结果仍然有效）
+    pub cancelled: bool,
+}
+
+/**
+ * 检查扫描范围：起点不大于终点，终点不超过最大的非强化序号
+ *
+ * @param start_index - 第一个序号
+ * @param max_index - 最后一个序号（包含）
+ */
+pub fn validate_range(start_index: u32, max_index: u32) -> Result<(), String> {
+    if max_index > MAX_DERIVATION_INDEX {
+        return Err(format!("max_index 不能超过 {}（更大的序号属于强化派生）", MAX_DERIVATION_INDEX));
+    }
+    if start_index > max_index {
+        return Err(format!("start_index（{}）不能大于 max_index（{}）", start_index, max_index));
+    }
+    Ok(())
+}
+
+/**
+ * 依次派生 m/44'/60'/0'/0/i 并与模式比较（只在内存中计算，不写入任何文件）
+ *
+ * @param phrase - 校验过的助记词（mnemonic::parse 的结果）
+ * @param passphrase - BIP-39 口令（没有时为空）
+ * @param parsed - 解析后的模式（与 generate_fancy_wallet 使用同一个匹配器）
+ * @param start_index - 第一个序号
+ * @param max_index - 最后一个序号（包含）
+ * @param cancel - 取消标志（取消时提前结束并返回已有的结果）
+ * @param on_index - 每个序号扫描后调用（已扫描的序号数，满足模式时为该序号）；返回错误时停止扫描并返回该错误
+ * @returns 扫描结果
+ */
+pub fn scan(
+    phrase: &str,
+    passphrase: &str,
+    parsed: &ParsedPattern,
+    start_index: u32,
+    max_index: u32,
+    cancel: &AtomicBool,
+    mut on_index: impl FnMut(u64, Option<&IndexMatch>) -> Result<(), String>,
+) -> Result<ScanReport, String> {
+    validate_range(start_index, max_index)?;
+    let start = Instant::now();
+    let secp = Secp256k1::new();
+    let seed = mnemonic::to_seed(phrase, passphrase);
+    let mut matches = Vec::new();
+    let mut scanned = 0u64;
+    for index in start_index..=max_index {
+        if cancel.load(Ordering::SeqCst) {
+            break;
+        }
+        scanned += 1;
+        // 派生结果无效的序号（概率约为 2^-127）按 BIP-32 跳过
+        let Some(secret_key) = mnemonic::derive_account(&secp, &seed, index) else {
+            on_index(scanned, None)?;
+            continue;
+        };
+        let address = Address::from_secret_key(&secp, &secret_key);
+        let address_checksum = checksum_from_bytes(address.as_bytes());
+        if !parsed.matches(&address_checksum) {
+            on_index(scanned, None)?;
+            continue;
+        }
+        let found = IndexMatch {
+            index,
+            derivation_path: mnemonic::derivation_path(index),
+            address,
+            private_key: SecretHex::encode(&*Zeroizing::new(secret_key.secret_bytes())),
+            matched_spans: parsed.matched_spans(&address_checksum).unwrap_or_default(),
+        };
+        on_index(scanned, Some(&found))?;
+        matches.push(found);
+    }
+    let cancelled = scanned < (max_index - start_index) as u64 + 1;
+    Ok(ScanReport { scanned, matches, duration_ms: start.elapsed().as_millis() as u64, cancelled })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern;
+    use crate::test_support::{MNEMONIC_ADDRESS_VECTORS, MNEMONIC_SCAN_INDEX_1};
+
+    /**
+     * 助记词：BIP-39 测试向量、派生路径的已知地址、无效助记词的拒绝，以及按助记词生成的候选可以由保存的助记词重新派生
+     */
+    #[test]
+    fn mnemonic_scan() -> Result<(), String> {
+        let phrase = mnemonic::parse(MNEMONIC_ADDRESS_VECTORS[1].0)?;
+        let parsed = ParsedPattern::new(&pattern::prefix_suffix_pattern("709979", ""));
+        let mut calls = Vec::new();
+        let report = scan(&phrase, "", &parsed, 0, 3, &AtomicBool::new(false), |scanned, found| {
+            calls.push((scanned, found.map(|found| found.index)));
+            Ok(())
+        })?;
+        let found: Vec<(u32, String, String)> =
+            report.matches.iter().map(|found| (found.index, found.derivation_path.clone(), found.address.to_checksum())).collect();
+        let expected = vec![(1, "m/44'/60'/0'/0/1".to_string(), MNEMONIC_SCAN_INDEX_1.to_string())];
+        if report.scanned != 4 || report.cancelled || found != expected || calls != [(1, None), (2, Some(1)), (3, None), (4, None)] {
+            return Err(format!("扫描序号 0–3 的结果为 {:?}（已扫描 {}，回调 {:?}），应只在序号 1 找到 {}", found, report.scanned, calls, MNEMONIC_SCAN_INDEX_1));
+        }
+        // 私钥只用于保存，不返回给界面
+        let json = serde_json::to_string(&report).map_err(|e| e.to_string())?;
+        if json.contains(report.matches[0].private_key.expose()) {
+            return Err("扫描结果的 JSON 含有私钥".to_string());
+        }
+        
+        let cancelled = scan(&phrase, "", &parsed, 0, 3, &AtomicBool::new(true), |_, _| Ok(()))?;
+        if cancelled.scanned != 0 || !cancelled.cancelled {
+            return Err(format!("取消后仍扫描了 {} 个序号", cancelled.scanned));
+        }
+        let stopped = scan(&phrase, "", &parsed, 0, 3, &AtomicBool::new(false), |_, found| match found {
+            Some(_) => Err("写入失败".to_string()),
+            None => Ok(()),
+        });
+        if stopped.is_ok() {
+            return Err("回调返回错误后扫描没有停止".to_string());
+        }
+        for (start, max) in [(5, 4), (0, MAX_DERIVATION_INDEX + 1)] {
+            if validate_range(start, max).is_ok() {
+                return Err(format!("无效的扫描范围 {}–{} 没有被拒绝", start, max));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,223 @@
+use crate::saved_wallets::validate_session_id;
+use crate::timestamps;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// 会话备注的文件名（位于用户配置的数据目录中，不随结果文件移动）
+const NOTES_FILE: &str = "session_notes.json";
+
+/// 备注文件格式版本
+pub const NOTES_VERSION: u32 = 1;
+
+/// 单条备注的最大长度（UTF-8 字节）
+pub const MAX_NOTE_BYTES: usize = 8 * 1024;
+
+/// 读取-修改-写入备注文件时持有，避免同时编辑时互相覆盖
+static NOTES_LOCK: Mutex<()> = Mutex::new(());
+
+/// 会话备注
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SessionNote {
+    /// 备注内容（原样保存，显示和导出时由各格式自行转义）
+    pub text: String,
+    /// 最后修改时间
+    pub updated_at: String,
+}
+
+/// 备注文件
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct NotesStore {
+    version: u32,
+    /// 以会话标识为键
+    #[serde(default)]
+    notes: BTreeMap<String, SessionNote>,
+}
+
+impl Default for NotesStore {
+    fn default() -> Self {
+        NotesStore { version: NOTES_VERSION, notes: BTreeMap::new() }
+    }
+}
+
+/// 修改备注的结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NoteUpdate {
+    /// 会话标识
+    pub session_id: String,
+    /// 修改后的备注（清空时为空）
+    pub note: Option<SessionNote>,
+    /// 是否同时更新了会话清单（结果文件被移走或会话没有清单时为 false，备注仍然已保存）
+    pub summary_updated: bool,
+}
+
+fn load_store(profile_dir: &Path) -> Result<NotesStore, String> {
+    let path = profile_dir.join(NOTES_FILE);
+    if !path.exists() {
+        return Ok(NotesStore::default());
+    }
+    let data = fs::read_to_string(&path).map_err(|e| format!("无法读取会话备注: {}", e))?;
+    serde_json::from_str(&data).map_err(|e| format!("会话备注格式错误: {}", e))
+}
+
+/**
+ * 读取全部会话备注
+ *
+ * @param profile_dir - 用户配置的数据目录
+ * @returns 以会话标识为键的备注
+ */
+pub fn load(profile_dir: &Path) -> Result<BTreeMap<String, SessionNote>, String> {
+    Ok(load_store(profile_dir)?.notes)
+}
+
+/**
+ * 设置会话备注（内容为空白时删除备注）
+ *
+ * @param profile_dir - 用户配置的数据目录
+ * @param session_id - 会话标识
+ * @param text - 备注内容
+ * @returns 修改后的备注
+ */
+pub fn set(profile_dir: &Path, session_id: &str, text: &str) -> Result<Option<SessionNote>, String> {
+    validate_session_id(session_id)?;
+    if text.len() > MAX_NOTE_BYTES {
+        return Err(format!("备注不能超过 {} 字节（当前 {} 字节）", MAX_NOTE_BYTES, text.len()));
+    }
+    let _guard = NOTES_LOCK.lock().map_err(|_| "会话备注不可用".to_string())?;
+    let mut store = load_store(profile_dir)?;
+    if store.version > NOTES_VERSION {
+        return Err(format!(
+            "会话备注由更新的版本（格式 {}）写入，为避免丢失数据不能在此版本中修改",
+            store.version
+        ));
+    }
+    let note = (!text.trim().is_empty()).then(|| SessionNote {
+        text: text.to_string(),
+        updated_at: timestamps::now_rfc3339(),
+    });
+    match &note {
+        Some(note) => store.notes.insert(session_id.to_string(), note.clone()),
+        None => store.notes.remove(session_id),
+    };
+
+    fs::create_dir_all(profile_dir).map_err(|e| format!("无法创建配置目录: {}", e))?;
+    let json = serde_json::to_string_pretty(&store).map_err(|e| format!("无法序列化会话备注: {}", e))?;
+    let path = profile_dir.join(NOTES_FILE);
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, json).map_err(|e| format!("无法写入会话备注: {}", e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("无法写入会话备注: {}", e))?;
+    Ok(note)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::Chain;
+    use crate::pgp_export;
+    use crate::manifest;
+    use crate::test_support::with_data_dir;
+
+    /// 备注检查使用的多语言文本（含引号、逗号、换行、HTML 和零宽字符）
+    const UNICODE_NOTE: &str = "给朋友的 📝 — «نقش» ✓ \"quoted\", a,b\n</script><b>&amp;</b>\u{200B} 🇨🇳 ";
+
+    /// 并发编辑备注的线程数和每个线程的编辑次数
+    const NOTE_WRITERS: usize = 8;
+
+    const NOTE_EDITS: usize = 20;
+
+    /**
+     * 会话备注：多语言长文本原样保存在配置中、清单和导出中；结果文件移走后仍可编辑；并发编辑不丢失
+     */
+    #[test]
+    fn session_notes() -> Result<(), String> {
+        with_data_dir("notes", |dir| {
+            let profile_dir = dir.join("profile");
+            let chain_dir = Chain::Ethereum.dir(&dir.join("FancyWallets"));
+            std::fs::create_dir_all(&chain_dir).map_err(|e| e.to_string())?;
+            let text = UNICODE_NOTE.repeat(MAX_NOTE_BYTES / UNICODE_NOTE.len());
+            
+            // 配置中的备注
+            set(&profile_dir, "888_selftest", &text)?;
+            if load(&profile_dir)?.get("888_selftest").map(|note| note.text.as_str()) != Some(text.as_str()) {
+                return Err("保存后读回的备注不一致".to_string());
+            }
+            if set(&profile_dir, "888_selftest", &"字".repeat(MAX_NOTE_BYTES)).is_ok() {
+                return Err("超长的备注没有被拒绝".to_string());
+            }
+            
+            // 清单中的备注：重写后产物校验仍然通过
+            let csv = chain_dir.join("wallet_888_selftest.csv");
+            std::fs::write(&csv, "address,private_key,pattern,index,chain\n").map_err(|e| e.to_string())?;
+            let stop = crate::stop::StopRecord { reason: crate::stop::StopReason::UserUi, detail: None };
+            manifest::write_session_manifest(
+                &chain_dir.join("manifest_888_other.json"),
+                "888",
+                &[csv],
+                &stop,
+                &manifest::SessionDetails { template: None, chain: Chain::Ethereum, matcher: None, score_weights: None, collection: None, keccak_backend: None, entropy: None, clock_jumps: &[], worker_failures: &[] },
+            )?;
+            let manifests = manifest::find_session_manifests(&dir.join("FancyWallets"), "888_selftest");
+            let [manifest_path] = manifests.as_slice() else {
+                return Err(format!("找到的清单不正确: {:?}", manifests));
+            };
+            manifest::set_note(manifest_path, Some(&text))?;
+            let written: manifest::SessionManifest =
+                serde_json::from_str(&std::fs::read_to_string(manifest_path).map_err(|e| e.to_string())?).map_err(|e| e.to_string())?;
+            if written.note.as_deref() != Some(text.as_str()) || !manifest::verify_session_manifest(manifest_path)?.ok {
+                return Err("清单中的备注不一致或产物校验失败".to_string());
+            }
+            
+            // 导出中的备注
+            let export = pgp_export::ConsolidatedResults::new("888_selftest", Vec::new(), Some(text.clone()));
+            let json = serde_json::to_string(&export).map_err(|e| e.to_string())?;
+            let parsed: pgp_export::ConsolidatedResults = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+            if parsed.note.as_deref() != Some(text.as_str()) {
+                return Err("导出中的备注不一致".to_string());
+            }
+            
+            // 结果文件被移走后仍然可以编辑，清空时删除
+            std::fs::remove_dir_all(&chain_dir).map_err(|e| e.to_string())?;
+            if !manifest::find_session_manifests(&dir.join("FancyWallets"), "888_selftest").is_empty() {
+                return Err("结果目录移走后仍然找到了清单".to_string());
+            }
+            set(&profile_dir, "888_selftest", "已转入冷钱包")?;
+            if set(&profile_dir, "888_selftest", "  \n")?.is_some() || load(&profile_dir)?.contains_key("888_selftest") {
+                return Err("清空的备注没有被删除".to_string());
+            }
+            
+            // 并发编辑：每个线程编辑自己的会话和一个共享会话
+            let errors: Vec<String> = std::thread::scope(|scope| {
+                let handles: Vec<_> = (0..NOTE_WRITERS)
+                    .map(|writer| {
+                        let profile_dir = &profile_dir;
+                        scope.spawn(move || -> Result<(), String> {
+                            for edit in 0..NOTE_EDITS {
+                                let text = format!("{} #{}-{}", UNICODE_NOTE, writer, edit);
+                                set(profile_dir, &format!("session_{}", writer), &text)?;
+                                set(profile_dir, "shared", &text)?;
+                            }
+                            Ok(())
+                        })
+                    })
+                    .collect();
+                handles.into_iter().filter_map(|handle| handle.join().unwrap_or_else(|_| Err("编辑线程崩溃".to_string())).err()).collect()
+            });
+            if let Some(error) = errors.first() {
+                return Err(format!("并发编辑失败: {}", error));
+            }
+            let stored = load(&profile_dir)?;
+            for writer in 0..NOTE_WRITERS {
+                let expected = format!("{} #{}-{}", UNICODE_NOTE, writer, NOTE_EDITS - 1);
+                if stored.get(&format!("session_{}", writer)).map(|note| &note.text) != Some(&expected) {
+                    return Err(format!("并发编辑后 session_{} 的备注丢失或不是最后一次编辑", writer));
+                }
+            }
+            if !stored.get("shared").is_some_and(|note| note.text.starts_with(UNICODE_NOTE)) {
+                return Err("并发编辑后共享会话的备注损坏".to_string());
+            }
+            Ok(())
+        })
+    }
+}
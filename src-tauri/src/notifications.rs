@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// 两次匹配通知之间的最短间隔；其间的匹配合并到下一次通知中
+pub const MIN_NOTIFICATION_INTERVAL: Duration = Duration::from_secs(15);
+
+/// notify_every 的上限
+pub const MAX_NOTIFY_EVERY: u64 = 1_000_000;
+
+/// 无法发送系统通知时的警告（notification-unavailable 事件；会话继续，本次会话不再发送通知）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct NotificationUnavailable {
+    /// 会话标识
+    pub session_id: String,
+    /// 原因
+    pub reason: String,
+}
+
+/// 一条匹配通知的内容（只有地址和模式，从不包含私钥）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchNotification {
+    /// 标题
+    pub title: String,
+    /// 正文
+    pub body: String,
+}
+
+/**
+ * 校验匹配通知的参数
+ *
+ * @param notify_on_match - 是否在找到匹配时发送系统通知
+ * @param notify_every - 每几个匹配通知一次（默认 1）
+ */
+pub fn validate(notify_on_match: Option<bool>, notify_every: Option<u64>) -> Result<(), String> {
+    match notify_every {
+        Some(_) if notify_on_match != Some(true) => Err("notify_every 只能与 notify_on_match: true 同时使用".to_string()),
+        Some(every) if every == 0 || every > MAX_NOTIFY_EVERY => Err(format!("notify_every 必须在 1 到 {} 之间", MAX_NOTIFY_EVERY)),
+        _ => Ok(()),
+    }
+}
+
+/// 匹配通知的频率限制：每 every 个匹配通知一次，两次通知之间至少间隔 MIN_NOTIFICATION_INTERVAL
+#[derive(Debug, Clone)]
+pub struct MatchNotifier {
+    every: u64,
+    interval: Duration,
+    /// 已找到的匹配数
+    matches: u64,
+    /// 上一次通知之后没有单独通知的匹配数
+    pending: u64,
+    /// 上一次通知的时刻（从会话开始经过的时间）
+    last_shown: Option<Duration>,
+}
+
+impl MatchNotifier {
+    /**
+     * 创建匹配通知的频率限制
+     *
+     * @param notify_every - 每几个匹配通知一次（为空时每个匹配都通知）
+     */
+    pub fn new(notify_every: Option<u64>) -> Result<Self, String> {
+        validate(Some(true), notify_every)?;
+        Ok(MatchNotifier { every: notify_every.unwrap_or(1), interval: MIN_NOTIFICATION_INTERVAL, matches: 0, pending: 0, last_shown: None })
+    }
+
+    /**
+     * 记录一个匹配，判断现在是否发送通知；返回时记为已通知
+     *
+     * @param now - 当前时刻（从会话开始经过的时间，测试中由模拟时钟提供）
+     * @returns 需要通知时为上一次通知之后找到的匹配数（含这一个）
+     */
+    pub fn on_match(&mut self, now: Duration) -> Option<u64> {
+        self.matches += 1;
+        self.pending += 1;
+        let due = self.matches.is_multiple_of(self.every) && self.last_shown.is_none_or(|last| now.saturating_sub(last) >= self.interval);
+        if !due {
+            return None;
+        }
+        self.last_shown = Some(now);
+        Some(std::mem::take(&mut self.pending))
+    }
+
+    /**
+     * 通知的内容
+     *
+     * @param address - 匹配的地址（该链的格式）
+     * @param pattern - 满足的模式
+     * @param matches - 上一次通知之后找到的匹配数（on_match 的返回值）
+     */
+    pub fn message(&self, address: &str, pattern: &str, matches: u64) -> MatchNotification {
+        let (title, body) = match matches {
+            1 => (format!("找到匹配 {} 的钱包", pattern), format!("{}（本次会话第 {} 个）", address, self.matches)),
+            n => (
+                format!("找到 {} 个匹配的钱包", n),
+                format!("最新: {}（{}；另有 {} 个没有单独通知，本次会话共 {} 个）", address, pattern, n - 1, self.matches),
+            ),
+        };
+        MatchNotification { title, body }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::templates::GenerationParams;
+    use crate::self_test::KNOWN_KEYS;
+
+    /**
+     * 匹配通知：每 notify_every 个匹配通知一次，间隔太短的匹配合并到下一次通知；通知内容没有私钥
+     */
+    #[test]
+    fn match_notifications() -> Result<(), String> {
+        let (key, checksum) = KNOWN_KEYS[0];
+        let address = format!("0x{}", checksum);
+        let at = Duration::from_secs;
+        
+        // 容易的模式：一分钟内的 100 个匹配只通知 4 次，之后的通知说明合并了多少个
+        let mut notifier = MatchNotifier::new(None)?;
+        let shown: Vec<(u64, u64)> = (0..100u64).filter_map(|i| notifier.on_match(at(i * 60 / 100)).map(|matches| (i, matches))).collect();
+        let expected_count = (60 / MIN_NOTIFICATION_INTERVAL.as_secs()) as usize;
+        if shown.len() != expected_count || shown[0] != (0, 1) || shown.iter().skip(1).any(|&(_, matches)| matches < 2) {
+            return Err(format!("一分钟 100 个匹配的通知为 {:?}", shown));
+        }
+        let message = notifier.message(&address, "8888*", shown[1].1);
+        if !message.body.contains(&address) || message.body.contains(key) || !message.body.contains(&format!("另有 {} 个", shown[1].1 - 1)) {
+            return Err(format!("合并的通知内容不正确: {:?}", message));
+        }
+        
+        // 每 3 个匹配通知一次，间隔足够长时不合并其它匹配
+        let mut every_third = MatchNotifier::new(Some(3))?;
+        let shown: Vec<Option<u64>> = (1..=6u64).map(|i| every_third.on_match(at(i * 60))).collect();
+        if shown != [None, None, Some(3), None, None, Some(3)] {
+            return Err(format!("notify_every: 3 的通知为 {:?}", shown));
+        }
+        let single = MatchNotifier::new(None)?.message(&address, "8888*", 1);
+        if !single.title.contains("8888*") || single.body.contains(key) {
+            return Err(format!("单个匹配的通知内容不正确: {:?}", single));
+        }
+        
+        for (notify_on_match, notify_every) in [(None, Some(2)), (Some(false), Some(2)), (Some(true), Some(0)), (Some(true), Some(MAX_NOTIFY_EVERY + 1))] {
+            if validate(notify_on_match, notify_every).is_ok() {
+                return Err(format!("notify_on_match {:?} 与 notify_every {:?} 没有被拒绝", notify_on_match, notify_every));
+            }
+        }
+        let params = GenerationParams { pattern: "8".to_string(), notify_every: Some(5), ..GenerationParams::default() };
+        if params.validate().is_ok() {
+            return Err("模板中没有 notify_on_match 的 notify_every 没有被拒绝".to_string());
+        }
+        Ok(())
+    }
+}
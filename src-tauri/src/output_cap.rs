@@ -0,0 +1,312 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+/// 默认的单个会话写入结果文件的字节数上限
+pub const DEFAULT_MAX_BYTES: u64 = 4 * 1024 * 1024 * 1024;
+
+/// 默认的单个会话保存的钱包数上限
+pub const DEFAULT_MAX_WALLETS: u64 = 1_000_000;
+
+/// 当前的输出上限（对正在运行的会话立即生效）
+static CAPS: Mutex<OutputCaps> = Mutex::new(OutputCaps::DEFAULT);
+
+/// 会话输出的安全上限：达到任一上限时暂停会话，防止配置错误时写满磁盘
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct OutputCaps {
+    /// 写入结果文件的字节数上限
+    pub max_bytes: u64,
+    /// 保存的钱包数上限
+    pub max_wallets: u64,
+    /// 显式关闭上限（为 true 时两个上限都不生效）
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+impl OutputCaps {
+    /// 默认上限
+    pub const DEFAULT: OutputCaps = OutputCaps { max_bytes: DEFAULT_MAX_BYTES, max_wallets: DEFAULT_MAX_WALLETS, disabled: false };
+
+    /**
+     * 按统计判断达到了哪个上限（关闭时总是为空）
+     *
+     * @param bytes_written - 已写入的字节数
+     * @param wallets_saved - 已保存的钱包数
+     */
+    fn reached(&self, bytes_written: u64, wallets_saved: u64) -> Option<OutputCapKind> {
+        if self.disabled {
+            None
+        } else if bytes_written >= self.max_bytes {
+            Some(OutputCapKind::Bytes)
+        } else if wallets_saved >= self.max_wallets {
+            Some(OutputCapKind::Wallets)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for OutputCaps {
+    fn default() -> Self {
+        OutputCaps::DEFAULT
+    }
+}
+
+/// 触发的输出上限
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputCapKind {
+    /// 写入的字节数
+    Bytes,
+    /// 保存的钱包数
+    Wallets,
+}
+
+/// 会话的输出统计
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct OutputUsage {
+    /// 已写入结果文件的字节数（新文件包含标题行）
+    pub bytes_written: u64,
+    /// 已保存的钱包数
+    pub wallets_saved: u64,
+    /// 当前的上限
+    pub caps: OutputCaps,
+    /// 最近一次保存后达到的上限（没有达到时为空）
+    pub triggered: Option<OutputCapKind>,
+}
+
+/// 达到输出上限时发送的警告
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OutputCapWarning {
+    /// 会话标识
+    pub session_id: String,
+    /// 触发的上限
+    pub cap: OutputCapKind,
+    /// 触发时的输出统计
+    pub usage: OutputUsage,
+    /// 说明（包含如何提高上限）
+    pub message: String,
+}
+
+/**
+ * 当前的输出上限
+ */
+pub fn caps() -> OutputCaps {
+    CAPS.lock().map(|caps| *caps).unwrap_or_default()
+}
+
+/**
+ * 设置输出上限；对正在运行的会话立即生效，因上限暂停的会话提高上限后可以恢复
+ *
+ * @param caps - 输出上限
+ */
+pub fn set_caps(caps: OutputCaps) -> Result<(), String> {
+    if !caps.disabled && (caps.max_bytes == 0 || caps.max_wallets == 0) {
+        return Err("输出上限必须大于 0（不需要上限时设置 disabled）".to_string());
+    }
+    *CAPS.lock().map_err(|_| "输出上限不可用".to_string())? = caps;
+    Ok(())
+}
+
+/// 单个会话的输出统计，由写入结果文件的一方在每次保存后更新
+#[derive(Debug, Clone)]
+pub struct OutputBudget {
+    /// 会话标识
+    session_id: String,
+    /// 已写入的字节数
+    bytes_written: u64,
+    /// 已保存的钱包数
+    wallets_saved: u64,
+    /// 最近一次检查时使用的上限
+    caps: OutputCaps,
+    /// 最近一次保存后达到的上限
+    triggered: Option<OutputCapKind>,
+}
+
+impl OutputBudget {
+    /**
+     * 创建会话的输出统计
+     *
+     * @param session_id - 会话标识
+     * @param caps - 开始时的上限
+     */
+    pub fn new(session_id: &str, caps: OutputCaps) -> Self {
+        OutputBudget { session_id: session_id.to_string(), bytes_written: 0, wallets_saved: 0, caps, triggered: None }
+    }
+
+    /**
+     * 记录保存的一个钱包，并按当前的上限检查
+     *
+     * @param bytes - 本次写入的字节数
+     * @param caps - 当前的上限（会话运行期间可以修改）
+     * @returns 达到上限时返回警告（调用方据此暂停会话）
+     */
+    pub fn record(&mut self, bytes: u64, caps: OutputCaps) -> Option<OutputCapWarning> {
+        self.bytes_written = self.bytes_written.saturating_add(bytes);
+        self.wallets_saved += 1;
+        self.caps = caps;
+        self.triggered = caps.reached(self.bytes_written, self.wallets_saved);
+        let cap = self.triggered?;
+        let message = match cap {
+            OutputCapKind::Bytes => format!(
+                "会话已写入 {} 字节，达到输出上限 {} 字节，已暂停；请用 set_output_caps 提高 max_bytes（或设置 disabled）后恢复",
+                self.bytes_written, caps.max_bytes
+            ),
+            OutputCapKind::Wallets => format!(
+                "会话已保存 {} 个钱包，达到输出上限 {} 个，已暂停；请用 set_output_caps 提高 max_wallets（或设置 disabled）后恢复",
+                self.wallets_saved, caps.max_wallets
+            ),
+        };
+        Some(OutputCapWarning { session_id: self.session_id.clone(), cap, usage: self.usage(), message })
+    }
+
+    /**
+     * 当前的输出统计
+     */
+    pub fn usage(&self) -> OutputUsage {
+        OutputUsage { bytes_written: self.bytes_written, wallets_saved: self.wallets_saved, caps: self.caps, triggered: self.triggered }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::Chain;
+    use crate::entropy::RngMode;
+    use crate::engine::{CancellationToken, SearchConfig, SearchEvent};
+    use crate::gui::{csv_row_bytes, write_wallet_row, Wallet};
+    use crate::saved_wallets::{self, CsvPrivateKeys};
+    use crate::timestamps;
+    use crate::test_support::with_data_dir;
+
+    /// 输出上限测试中按字节数上限写入的行数（约数）
+    const OUTPUT_CAP_ROWS: u64 = 20;
+
+    /// 输出上限测试中的钱包数上限
+    const OUTPUT_CAP_WALLETS: u64 = 5;
+
+    /// 一次匹配洪流的结果
+    struct FloodOutcome {
+        /// 暂停时发出的警告（每次由运行切换为暂停时一个）
+        warnings: Vec<OutputCapWarning>,
+        /// 结束时的输出统计
+        usage: OutputUsage,
+        /// 结果文件的实际大小（字节）
+        file_bytes: u64,
+        /// 结果文件中的钱包数
+        file_rows: u64,
+    }
+
+    /**
+     * 让每次尝试都匹配，把找到的钱包逐个写入结果文件并按上限检查；达到上限时暂停搜索，暂停一段时间后取消
+     *
+     * @param csv - 结果文件路径
+     * @param caps - 输出上限
+     */
+    fn flood_matches(csv: &std::path::Path, caps: OutputCaps) -> Result<FloodOutcome, String> {
+        let config = SearchConfig { rng_mode: RngMode::DeterministicTest, test_seed: Some(7), workers: Some(1), ..SearchConfig::new("8") };
+        let cancel = CancellationToken::new();
+        let paused = std::cell::Cell::new(false);
+        let mut paused_polls = 0;
+        let mut budget = OutputBudget::new("flood_selftest", caps);
+        let mut warnings = Vec::new();
+        let mut error = None;
+        crate::engine::search_with(
+            &config,
+            &cancel,
+            || {
+                if paused.get() {
+                    paused_polls += 1;
+                    if paused_polls > 3 {
+                        cancel.cancel();
+                    }
+                }
+                paused.get()
+            },
+            |_| true,
+            |event| {
+                let SearchEvent::Found(found) = event else {
+                    return;
+                };
+                let wallet = Wallet {
+                    index: budget.usage().wallets_saved + 1,
+                    address: found.address,
+                    private_key: found.private_key,
+                    attempts: found.attempts,
+                    duration: 0,
+                    matched_spans: Vec::new(),
+                    rng_mode: found.rng_mode,
+                    test_wallet: true,
+                    mnemonic: None,
+                    chain_address: None,
+                    matched_pattern: found.pattern,
+                    created_at: timestamps::now_rfc3339(),
+                };
+                match write_wallet_row(csv, &wallet, "8", Chain::Ethereum, CsvPrivateKeys::Full) {
+                    Ok(written) => {
+                        if let Some(warning) = budget.record(written.bytes, caps).filter(|_| !paused.replace(true)) {
+                            warnings.push(warning);
+                        }
+                    }
+                    Err(e) => {
+                        error = Some(e);
+                        cancel.cancel();
+                    }
+                }
+            },
+        );
+        if let Some(e) = error {
+            return Err(e);
+        }
+        Ok(FloodOutcome {
+            warnings,
+            usage: budget.usage(),
+            file_bytes: std::fs::metadata(csv).map_err(|e| e.to_string())?.len(),
+            file_rows: saved_wallets::read_all(csv)?.len() as u64,
+        })
+    }
+
+    /**
+     * 会话输出上限：匹配洪流在达到字节数或钱包数上限时暂停，统计的字节数与结果文件的实际大小一致；关闭上限后不再暂停
+     */
+    #[test]
+    fn output_caps() -> Result<(), String> {
+        with_data_dir("output-caps", |dir| {
+            let row = csv_row_bytes("8", Chain::Ethereum, RngMode::DeterministicTest, None, CsvPrivateKeys::Full);
+            let max_bytes = saved_wallets::CSV_HEADER.len() as u64 + 1 + OUTPUT_CAP_ROWS * row;
+            let bytes_caps = OutputCaps { max_bytes, max_wallets: u64::MAX, disabled: false };
+            let flood = flood_matches(&dir.join("wallet_bytes.csv"), bytes_caps)?;
+            let [warning] = flood.warnings.as_slice() else {
+                return Err(format!("字节数上限触发了 {} 次暂停，应为 1 次", flood.warnings.len()));
+            };
+            if warning.cap != OutputCapKind::Bytes || warning.usage.bytes_written < max_bytes || flood.usage.triggered != Some(OutputCapKind::Bytes) {
+                return Err(format!("字节数上限的警告不正确: {:?}", warning));
+            }
+            // 在刚达到上限的那一行暂停
+            if warning.usage.bytes_written - row >= max_bytes || !warning.message.contains("set_output_caps") {
+                return Err(format!("写入 {} 字节后才暂停（上限 {}）", warning.usage.bytes_written, max_bytes));
+            }
+            if flood.usage.bytes_written != flood.file_bytes || flood.usage.wallets_saved != flood.file_rows {
+                return Err(format!(
+                    "统计为 {} 字节、{} 个钱包，结果文件为 {} 字节、{} 个钱包",
+                    flood.usage.bytes_written, flood.usage.wallets_saved, flood.file_bytes, flood.file_rows
+                ));
+            }
+            
+            let wallets_caps = OutputCaps { max_bytes: u64::MAX, max_wallets: OUTPUT_CAP_WALLETS, disabled: false };
+            let flood = flood_matches(&dir.join("wallet_count.csv"), wallets_caps)?;
+            match flood.warnings.as_slice() {
+                [warning] if warning.cap == OutputCapKind::Wallets && warning.usage.wallets_saved == OUTPUT_CAP_WALLETS => {}
+                warnings => return Err(format!("钱包数上限的警告不正确: {:?}", warnings)),
+            }
+            
+            let mut disabled = OutputBudget::new("disabled_selftest", OutputCaps { disabled: true, ..wallets_caps });
+            if (0..2 * OUTPUT_CAP_WALLETS).any(|_| disabled.record(row, OutputCaps { disabled: true, ..wallets_caps }).is_some()) {
+                return Err("关闭上限后仍然暂停".to_string());
+            }
+            if OutputCaps::default().disabled || set_caps(OutputCaps { max_wallets: 0, ..OutputCaps::default() }).is_ok() {
+                return Err("默认上限未启用或接受了为 0 的上限".to_string());
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,292 @@
+use crate::chain::{Chain, KNOWN_CHAINS};
+use crate::manifest::MANIFEST_VERSION;
+use crate::replay::REPLAY_VERSION;
+use crate::saved_wallets;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// 每个 CSV 文件抽查的行数
+const SAMPLE_ROWS: usize = 20;
+
+/// 最多检查的文件数（只做简要扫描）
+const MAX_SCANNED_FILES: usize = 2000;
+
+/// 检查发现的问题类型
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingCode {
+    /// 由更新版本写入、当前版本无法完整识别的文件
+    NewerFormatVersion,
+    /// 文件中混有其它链的地址
+    MixedChains,
+    /// 回放文件没有对应的会话清单（会话可能被中断）
+    MissingManifest,
+    /// 原子写入中断后遗留的临时文件
+    OrphanedTempFile,
+    /// 文件过多，只检查了一部分
+    ScanTruncated,
+}
+
+/// 单条检查结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DirectoryFinding {
+    /// 问题类型
+    pub code: FindingCode,
+    /// 相关文件
+    pub path: String,
+    /// 说明
+    pub message: String,
+}
+
+/// 输出目录检查报告
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DirectoryReport {
+    /// 检查的目录
+    pub path: String,
+    /// 发现的问题
+    pub findings: Vec<DirectoryFinding>,
+}
+
+fn finding(code: FindingCode, path: &Path, message: String) -> DirectoryFinding {
+    DirectoryFinding { code, path: path.to_string_lossy().to_string(), message }
+}
+
+fn file_name(path: &Path) -> &str {
+    path.file_name().and_then(|name| name.to_str()).unwrap_or("")
+}
+
+fn check_csv(path: &Path, findings: &mut Vec<DirectoryFinding>) {
+    let Ok(file) = File::open(path) else {
+        return;
+    };
+    let mut lines = BufReader::new(file).lines().map_while(Result::ok);
+    if let Some(header) = lines.next() {
+        if saved_wallets::CsvPrivateKeys::from_header(&header).is_none() {
+            findings.push(finding(
+                FindingCode::NewerFormatVersion,
+                path,
+                format!("无法识别的 CSV 标题: {}", header.trim()),
+            ));
+        }
+    }
+    // 各链子目录中的文件应只包含该链的地址，旧版本直接写在 FancyWallets 下的文件都是以太坊地址
+    let chain = Chain::from_dir(path).unwrap_or(Chain::Ethereum);
+    if let Some(address) = lines
+        .take(SAMPLE_ROWS)
+        .filter_map(|line| line.split(',').next().map(|a| a.trim().to_string()))
+        .find(|address| !address.is_empty() && Chain::from_address(address) != Some(chain))
+    {
+        findings.push(finding(
+            FindingCode::MixedChains,
+            path,
+            format!("文件中包含非 {} 地址: {}", chain.as_str(), address),
+        ));
+    }
+}
+
+fn check_manifest(path: &Path, findings: &mut Vec<DirectoryFinding>) {
+    let version = fs::read_to_string(path)
+        .ok()
+        .and_then(|data| serde_json::from_str::<serde_json::Value>(&data).ok())
+        .and_then(|value| value.get("version").and_then(|v| v.as_u64()));
+    if let Some(version) = version {
+        if version > MANIFEST_VERSION as u64 {
+            findings.push(finding(
+                FindingCode::NewerFormatVersion,
+                path,
+                format!("清单文件版本 {} 高于当前支持的版本 {}", version, MANIFEST_VERSION),
+            ));
+        }
+    }
+}
+
+fn check_replay(path: &Path, findings: &mut Vec<DirectoryFinding>) {
+    let version = File::open(path)
+        .ok()
+        .and_then(|file| BufReader::new(file).lines().next()?.ok())
+        .and_then(|line| serde_json::from_str::<serde_json::Value>(&line).ok())
+        .and_then(|value| value.get("version").and_then(|v| v.as_u64()));
+    if let Some(version) = version {
+        if version > REPLAY_VERSION as u64 {
+            findings.push(finding(
+                FindingCode::NewerFormatVersion,
+                path,
+                format!("回放文件版本 {} 高于当前支持的版本 {}", version, REPLAY_VERSION),
+            ));
+        }
+    }
+    
+    // 清单与回放文件使用同一个会话名，清单位于 replays 的上一级目录
+    let session_dir = path.parent().and_then(Path::parent).unwrap_or(Path::new("."));
+    if let Some(session) = file_name(path).strip_prefix("replay_").and_then(|n| n.strip_suffix(".jsonl")) {
+        if !session_dir.join(format!("manifest_{}.json", session)).exists() {
+            findings.push(finding(
+                FindingCode::MissingManifest,
+                path,
+                "回放文件没有对应的会话清单，该会话可能被中断或仍在运行".to_string(),
+            ));
+        }
+    }
+}
+
+/**
+ * 列出目录、各链子目录及其 replays 子目录中的文件
+ */
+fn scanned_files(wallets_dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut dirs = vec![wallets_dir.to_path_buf()];
+    dirs.extend(KNOWN_CHAINS.iter().map(|chain| chain.dir(wallets_dir)));
+    for dir in dirs.iter().flat_map(|dir| [dir.clone(), dir.join("replays")]) {
+        if let Ok(entries) = fs::read_dir(&dir) {
+            files.extend(entries.flatten().map(|e| e.path()).filter(|p| p.is_file()));
+        }
+    }
+    files.sort();
+    files
+}
+
+/**
+ * 简要扫描输出目录，找出以后可能引起问题的情况（只读，不做任何修改）
+ * 
+ * @param wallets_dir - FancyWallets 目录
+ * @returns 检查报告
+ */
+pub fn check_directory(wallets_dir: &Path) -> DirectoryReport {
+    let mut findings = Vec::new();
+    let files = scanned_files(wallets_dir);
+    if files.len() > MAX_SCANNED_FILES {
+        findings.push(finding(
+            FindingCode::ScanTruncated,
+            wallets_dir,
+            format!("目录中共有 {} 个文件，只检查了前 {} 个", files.len(), MAX_SCANNED_FILES),
+        ));
+    }
+    
+    for path in files.iter().take(MAX_SCANNED_FILES) {
+        let name = file_name(path);
+        if name.ends_with(".tmp") {
+            findings.push(finding(
+                FindingCode::OrphanedTempFile,
+                path,
+                "写入中断后遗留的临时文件，可以在设置中清理".to_string(),
+            ));
+        } else if saved_wallets::is_result_file_name(name, "csv") {
+            check_csv(path, &mut findings);
+        } else if name.starts_with("manifest_") && name.ends_with(".json") {
+            check_manifest(path, &mut findings);
+        } else if name.starts_with("replay_") && name.ends_with(".jsonl") {
+            check_replay(path, &mut findings);
+        }
+    }
+    
+    DirectoryReport { path: wallets_dir.to_string_lossy().to_string(), findings }
+}
+
+/**
+ * 删除检查报告中列出的遗留临时文件（只能由用户显式触发）
+ * 
+ * @param wallets_dir - FancyWallets 目录
+ * @returns 删除的文件列表
+ */
+pub fn remove_orphaned_temp_files(wallets_dir: &Path) -> Result<Vec<String>, String> {
+    let mut removed = Vec::new();
+    for path in scanned_files(wallets_dir) {
+        if file_name(&path).ends_with(".tmp") {
+            fs::remove_file(&path).map_err(|e| format!("无法删除临时文件 {}: {}", path.display(), e))?;
+            removed.push(path.to_string_lossy().to_string());
+        }
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{TRON_ADDRESS, with_data_dir};
+
+    /// 当前版本写入的以太坊结果行
+    const ETHEREUM_ROW: &str = "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf,0000000000000000000000000000000000000000000000000000000000000001,7e,1,ethereum";
+
+    /**
+     * 在 FancyWallets 目录中写入一个文件（自动创建上级目录）
+     */
+    fn write(wallets_dir: &Path, relative: &str, contents: &str) -> Result<(), String> {
+        let path = wallets_dir.join(relative);
+        fs::create_dir_all(path.parent().unwrap_or(wallets_dir)).map_err(|e| e.to_string())?;
+        fs::write(path, contents).map_err(|e| e.to_string())
+    }
+
+    /**
+     * 按场景构造一个 FancyWallets 目录并返回检查到的问题类型和文件名
+     */
+    fn scenario(dir: &Path, name: &str, files: &[(&str, String)]) -> Result<Vec<(FindingCode, String)>, String> {
+        let wallets_dir = dir.join(name);
+        fs::create_dir_all(&wallets_dir).map_err(|e| e.to_string())?;
+        for (relative, contents) in files {
+            write(&wallets_dir, relative, contents)?;
+        }
+        let report = check_directory(&wallets_dir);
+        Ok(report.findings.iter().map(|finding| (finding.code, file_name(Path::new(&finding.path)).to_string())).collect())
+    }
+
+    /**
+     * 输出目录检查：完整的目录没有问题；更新版本的 CSV、清单和回放文件，混有其它链地址的文件，没有清单的回放文件，
+     * 遗留的临时文件和文件过多各自报告对应的问题类型；检查不修改目录，临时文件只在显式清理时删除
+     */
+    #[test]
+    fn output_directory_findings() -> Result<(), String> {
+        with_data_dir("output-check", |dir| {
+            let csv = format!("{}\n{}\n", saved_wallets::CSV_HEADER, ETHEREUM_ROW);
+            let manifest = |version: u32| format!("{{\"version\":{}}}", version);
+            let replay = |version: u32| format!("{{\"version\":{}}}\n", version);
+            let clean = [
+                ("ethereum/wallet_7e_20260101_000000.csv", csv.clone()),
+                ("ethereum/manifest_7e_20260101_000000.json", manifest(MANIFEST_VERSION)),
+                ("ethereum/replays/replay_7e_20260101_000000.jsonl", replay(REPLAY_VERSION)),
+            ];
+            let findings = scenario(dir, "clean", &clean)?;
+            if !findings.is_empty() {
+                return Err(format!("完整的目录报告了问题: {:?}", findings));
+            }
+
+            let cases = [
+                ("newer-csv", vec![("ethereum/wallet_8.csv", format!("address,private_key,pattern,index,chain,future_column\n{}\n", ETHEREUM_ROW))], (FindingCode::NewerFormatVersion, "wallet_8.csv")),
+                ("newer-manifest", vec![("ethereum/manifest_8.json", manifest(MANIFEST_VERSION + 1))], (FindingCode::NewerFormatVersion, "manifest_8.json")),
+                (
+                    "newer-replay",
+                    vec![("ethereum/manifest_8.json", manifest(MANIFEST_VERSION)), ("ethereum/replays/replay_8.jsonl", replay(REPLAY_VERSION + 1))],
+                    (FindingCode::NewerFormatVersion, "replay_8.jsonl"),
+                ),
+                ("mixed-chains", vec![("ethereum/wallet_8.csv", format!("{}{},,8,2,tron\n", csv, TRON_ADDRESS))], (FindingCode::MixedChains, "wallet_8.csv")),
+                ("missing-manifest", vec![("ethereum/replays/replay_8.jsonl", replay(REPLAY_VERSION))], (FindingCode::MissingManifest, "replay_8.jsonl")),
+                ("temp-file", vec![("ethereum/wallet_8.csv.tmp", csv.clone())], (FindingCode::OrphanedTempFile, "wallet_8.csv.tmp")),
+            ];
+            for (name, files, expected) in cases {
+                let findings = scenario(dir, name, &files)?;
+                if findings != [(expected.0, expected.1.to_string())] {
+                    return Err(format!("场景 {} 报告的问题为 {:?}，应为 {:?}", name, findings, expected));
+                }
+            }
+
+            let temp_dir = dir.join("temp-file");
+            if !temp_dir.join("ethereum/wallet_8.csv.tmp").exists() {
+                return Err("检查目录时删除了临时文件".to_string());
+            }
+            write(&temp_dir, "ethereum/wallet_8.csv", &csv)?;
+            let removed = remove_orphaned_temp_files(&temp_dir)?;
+            if removed.len() != 1 || temp_dir.join("ethereum/wallet_8.csv.tmp").exists() || !temp_dir.join("ethereum/wallet_8.csv").exists() {
+                return Err(format!("清理临时文件的结果不正确: {:?}", removed));
+            }
+
+            let many: Vec<(String, String)> = (0..=MAX_SCANNED_FILES).map(|i| (format!("notes_{:04}.txt", i), String::new())).collect();
+            let many: Vec<(&str, String)> = many.iter().map(|(name, contents)| (name.as_str(), contents.clone())).collect();
+            let findings = scenario(dir, "many-files", &many)?;
+            if findings != [(FindingCode::ScanTruncated, "many-files".to_string())] {
+                return Err(format!("文件过多时报告的问题为 {:?}", findings));
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,98 @@
+use crate::sessions;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// 各会话的输出文件夹（保留到下一个会话开始；界面重新加载后 open_output_folder 仍能找到刚结束的会话）
+static FOLDERS: OnceLock<Mutex<Folders>> = OnceLock::new();
+
+#[derive(Default)]
+struct Folders {
+    /// 最近开始的会话
+    latest: Option<String>,
+    sessions: HashMap<String, OutputFolder>,
+}
+
+/// 会话的输出文件夹（open_output_folder 的返回值）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct OutputFolder {
+    /// 会话标识（还没有会话、打开默认目录时为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// 结果文件所在的文件夹（与写入结果文件时创建的目录相同）
+    pub folder: PathBuf,
+    /// 在文件管理器中选中的结果文件（会话没有写入结果文件时为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected: Option<PathBuf>,
+}
+
+fn folders() -> &'static Mutex<Folders> {
+    FOLDERS.get_or_init(|| Mutex::new(Folders::default()))
+}
+
+/**
+ * 新的会话解析完输出位置：清除已经结束的会话的记录（同时运行的其它会话保留），登记这个会话的文件夹
+ *
+ * @param session_id - 会话标识
+ * @param folder - 结果文件所在的文件夹（会话的 SessionOutput::csv_dir）
+ * @param result_file - 会话的结果文件（不写入结果文件时为空）
+ */
+pub fn register(session_id: &str, folder: &Path, result_file: Option<PathBuf>) {
+    let running: Vec<String> = sessions::list().into_iter().map(|session| session.session_id).collect();
+    if let Ok(mut folders) = folders().lock() {
+        folders.sessions.retain(|id, _| running.contains(id));
+        folders.sessions.insert(
+            session_id.to_string(),
+            OutputFolder { session_id: Some(session_id.to_string()), folder: folder.to_path_buf(), selected: result_file },
+        );
+        folders.latest = Some(session_id.to_string());
+    }
+}
+
+/**
+ * 查找会话的输出文件夹
+ *
+ * @param session_id - 会话标识（为空时为最近开始的会话）
+ * @returns 文件夹；没有指定会话且还没有开始过会话时为空
+ */
+pub fn find(session_id: Option<&str>) -> Result<Option<OutputFolder>, String> {
+    let folders = folders().lock().map_err(|_| "会话状态不可用".to_string())?;
+    let Some(session_id) = session_id.map(str::to_string).or_else(|| folders.latest.clone()) else {
+        return Ok(None);
+    };
+    match folders.sessions.get(&session_id) {
+        Some(folder) => Ok(Some(folder.clone())),
+        None => Err(format!("会话 {} 不存在，或它的输出位置已在新的会话开始时清除", session_id)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::lock_sessions;
+
+    /**
+     * 输出文件夹：open_output_folder 默认打开最近开始的会话的文件夹并选中它的结果文件，已经结束的会话在下一个会话开始时清除，
+     * 不存在的会话返回错误
+     */
+    #[test]
+    fn output_folder() -> Result<(), String> {
+        let _sessions = lock_sessions();
+        let dir = std::env::temp_dir().join("fancy-wallet-test-output-folder");
+        register("test-folder-first", &dir, None);
+        let csv = dir.join("wallet_8_selftest.csv");
+        register("test-folder-second", &dir.join("ethereum"), Some(csv.clone()));
+        let latest = find(None)?.ok_or("没有找到最近开始的会话")?;
+        if latest.session_id.as_deref() != Some("test-folder-second") || latest.folder != dir.join("ethereum") || latest.selected != Some(csv) {
+            return Err(format!("最近开始的会话的文件夹为 {:?}", latest));
+        }
+        if find(Some("test-folder-second"))? != Some(latest.clone()) {
+            return Err("按会话标识找到的文件夹与最近的会话不同".to_string());
+        }
+        if find(Some("test-folder-first")).is_ok() || find(Some("test-folder-missing")).is_ok() {
+            return Err("已经结束的会话或不存在的会话没有返回错误".to_string());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,223 @@
+use crate::errors::WalletError;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// 输出根目录下的结果目录名
+pub const WALLETS_DIR_NAME: &str = "FancyWallets";
+
+/// 写入检查的临时文件序号（同时开始的会话各自使用不同的文件）
+static PROBE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// 输出根目录的来源
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputSource {
+    /// 当前用户配置的默认目录（配置的默认保存路径或 Documents）
+    ProfileDefault,
+    /// 本次调用传入的 save_path（包括模板中的保存路径）
+    SavePath,
+}
+
+/// 解析并校验后的输出根目录
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct OutputRoot {
+    /// FancyWallets 目录的绝对路径（不保证已存在）
+    pub wallets_dir: PathBuf,
+    /// 来源
+    pub source: OutputSource,
+}
+
+/**
+ * 应用程序所在的目录：macOS 上为外层的 .app 包，其它系统为可执行文件所在目录
+ */
+pub fn app_bundle_dir() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    let exe_dir = exe.parent()?.to_path_buf();
+    let bundle = exe_dir
+        .ancestors()
+        .find(|dir| dir.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("app")))
+        .map(Path::to_path_buf);
+    Some(bundle.unwrap_or(exe_dir))
+}
+
+/**
+ * 校验输出目录：必须是绝对路径、不能包含 ..、不能位于应用程序目录中（更新应用时会被覆盖或删除）
+ *
+ * @param path - 输出目录
+ * @param bundle_dir - 应用程序目录（无法确定时为空）
+ */
+pub fn validate_root(path: &Path, bundle_dir: Option<&Path>) -> Result<(), String> {
+    if !path.is_absolute() {
+        return Err(format!("保存路径必须是绝对路径: {}", path.display()));
+    }
+    if path.components().any(|c| c == Component::ParentDir) {
+        return Err(format!("保存路径不能包含 \"..\": {}", path.display()));
+    }
+    if bundle_dir.is_some_and(|bundle| path.starts_with(bundle)) {
+        return Err(format!("保存路径不能位于应用程序目录中: {}", path.display()));
+    }
+    Ok(())
+}
+
+/**
+ * 解析输出根目录：优先使用调用时传入的 save_path，否则使用用户配置的默认目录，结果统一校验
+ *
+ * @param save_path - 保存路径（可选；空白视为未传入）
+ * @param profile_default - 用户配置的默认 FancyWallets 目录（只在没有 save_path 时计算）
+ * @param bundle_dir - 应用程序目录（无法确定时为空）
+ */
+pub fn resolve(
+    save_path: Option<&str>,
+    profile_default: impl FnOnce() -> Result<PathBuf, String>,
+    bundle_dir: Option<&Path>,
+) -> Result<OutputRoot, String> {
+    let root = match save_path.map(str::trim).filter(|path| !path.is_empty()) {
+        Some(path) => {
+            let path = PathBuf::from(path);
+            validate_root(&path, bundle_dir)?;
+            OutputRoot { wallets_dir: path.join(WALLETS_DIR_NAME), source: OutputSource::SavePath }
+        }
+        None => OutputRoot { wallets_dir: profile_default()?, source: OutputSource::ProfileDefault },
+    };
+    validate_root(&root.wallets_dir, bundle_dir)?;
+    Ok(root)
+}
+
+/**
+ * 在目录下展开相对路径（链目录、按模式和时间生成的文件名等），展开结果不能超出该目录
+ *
+ * @param dir - 所在目录
+ * @param relative - 相对路径
+ * @returns 展开后的路径
+ */
+pub fn expand(dir: &Path, relative: &str) -> Result<PathBuf, String> {
+    let path = Path::new(relative);
+    if relative.is_empty() || !path.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(format!("输出文件名 \"{}\" 超出了输出目录", relative));
+    }
+    Ok(dir.join(path))
+}
+
+/**
+ * 检查目录可以写入：目录不存在时先创建，再写入并删除一个临时文件
+ *
+ * 生成开始前调用，只读的目录或已满的磁盘在搜索开始前就报告，而不是找到匹配后才发现无法保存。
+ *
+ * @param dir - 结果文件所在的目录
+ */
+pub fn check_writable(dir: &Path) -> Result<(), WalletError> {
+    let unwritable = |e: std::io::Error| WalletError::save_failed(dir, &e);
+    fs::create_dir_all(dir).map_err(unwritable)?;
+    let probe = dir.join(format!(".write-check-{}-{}.tmp", std::process::id(), PROBE_COUNTER.fetch_add(1, Ordering::Relaxed)));
+    let written = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+        .and_then(|mut file| file.write_all(b"fancy-wallet").and_then(|_| file.sync_all()));
+    let removed = fs::remove_file(&probe);
+    written.map_err(unwritable)?;
+    removed.map_err(unwritable)
+}
+
+/**
+ * 开始前检查输出位置：传入的保存路径必须已经存在并且是目录（拼错的路径直接报告，不替用户创建），
+ * 再创建 FancyWallets 目录并写入、删除一个临时文件
+ *
+ * @param root - 解析后的输出根目录
+ */
+pub fn preflight(root: &OutputRoot) -> Result<(), WalletError> {
+    if root.source == OutputSource::SavePath {
+        if let Some(save_path) = root.wallets_dir.parent() {
+            let metadata = fs::metadata(save_path).map_err(|e| WalletError::save_failed(save_path, &e))?;
+            if !metadata.is_dir() {
+                return Err(WalletError::save_failed(save_path, &io::Error::new(ErrorKind::NotADirectory, "保存路径不是目录")));
+            }
+        }
+    }
+    check_writable(&root.wallets_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::with_data_dir;
+
+    /// 输出目录解析用例：(save_path, 默认目录, 期望的结果目录和来源；为空表示应被拒绝)
+    type OutputRootCase<'a> = (Option<String>, &'a std::path::Path, Option<(std::path::PathBuf, OutputSource)>);
+
+    /**
+     * 输出目录解析：save_path 优先于用户配置的默认目录，相对路径、应用程序目录和超出输出目录的文件名被拒绝
+     */
+    #[test]
+    fn output_root() -> Result<(), String> {
+        let base = std::env::temp_dir().join("fancy-wallet-test-output");
+        let bundle = base.join("FancyWallet.app");
+        let default_dir = base.join("Documents").join(WALLETS_DIR_NAME);
+        let override_dir = base.join("Override");
+        let relative_default = std::path::PathBuf::from("relative").join(WALLETS_DIR_NAME);
+        let inside_bundle = bundle.join("Contents");
+        let traversal = format!("{}{}..{}Escaped", override_dir.display(), std::path::MAIN_SEPARATOR, std::path::MAIN_SEPARATOR);
+        
+        let cases: [OutputRootCase; 8] = [
+            (None, &default_dir, Some((default_dir.clone(), OutputSource::ProfileDefault))),
+            (Some("  ".to_string()), &default_dir, Some((default_dir.clone(), OutputSource::ProfileDefault))),
+            (Some(override_dir.display().to_string()), &default_dir, Some((override_dir.join(WALLETS_DIR_NAME), OutputSource::SavePath))),
+            (Some(override_dir.display().to_string()), &relative_default, Some((override_dir.join(WALLETS_DIR_NAME), OutputSource::SavePath))),
+            (None, &relative_default, None),
+            (Some("relative/dir".to_string()), &default_dir, None),
+            (Some(inside_bundle.display().to_string()), &default_dir, None),
+            (Some(traversal), &default_dir, None),
+        ];
+        for (save_path, default, expected) in &cases {
+            let resolved = resolve(save_path.as_deref(), || Ok(default.to_path_buf()), Some(&bundle));
+            let actual = resolved.as_ref().ok().map(|root| (root.wallets_dir.clone(), root.source));
+            if actual != *expected {
+                return Err(format!("save_path {:?}、默认目录 {} 解析为 {:?}，应为 {:?}", save_path, default.display(), resolved, expected));
+            }
+        }
+        
+        let session_dir = default_dir.join("Ethereum");
+        if expand(&session_dir, "wallet_8888_20240101_000000.csv")? != session_dir.join("wallet_8888_20240101_000000.csv") {
+            return Err("正常的文件名展开结果不正确".to_string());
+        }
+        let root_file = format!("{}x.csv", std::path::MAIN_SEPARATOR);
+        for name in ["", "../wallet.csv", "replays/../../wallet.csv", "wallet_../../x.csv", root_file.as_str()] {
+            if expand(&session_dir, name).is_ok() {
+                return Err(format!("超出输出目录的文件名 {:?} 没有被拒绝", name));
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * 开始前检查保存路径：不存在的路径和文件被拒绝并带有路径和错误类别，存在的目录中创建 FancyWallets 且不留下临时文件
+     */
+    #[test]
+    fn save_path_preflight() -> Result<(), String> {
+        with_data_dir("save-path", |dir| {
+            let root = |save_path: &std::path::Path| resolve(Some(&save_path.display().to_string()), || Err("不应使用默认目录".to_string()), None);
+            let missing = dir.join("missing");
+            let file = dir.join("file.txt");
+            std::fs::write(&file, "x").map_err(|e| e.to_string())?;
+            for (save_path, io_kind) in [(&missing, "not_found"), (&file, "not_a_directory")] {
+                match preflight(&root(save_path)?) {
+                    Err(WalletError::SaveFailed { path, io_kind: Some(kind), .. }) if path == save_path.display().to_string() && kind == io_kind => {}
+                    other => return Err(format!("保存路径 {} 的检查结果为 {:?}，应为 {} 的 save_failed", save_path.display(), other, io_kind)),
+                }
+            }
+            if missing.exists() {
+                return Err("检查不存在的保存路径时创建了它".to_string());
+            }
+            
+            let accepted = root(dir)?;
+            preflight(&accepted)?;
+            if !accepted.wallets_dir.is_dir() || std::fs::read_dir(&accepted.wallets_dir).map_err(|e| e.to_string())?.next().is_some() {
+                return Err("FancyWallets 目录没有创建，或写入检查留下了临时文件".to_string());
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,232 @@
+use crate::chain::Chain;
+use crate::csv_field;
+use crate::hexutil::PrivKeyHex;
+use secp256k1::Secp256k1;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// 每校验多少行发送一次进度
+pub const PROGRESS_EVERY_ROWS: u64 = 1000;
+
+/// 校验报告的标题行（报告中不包含私钥）
+const REPORT_HEADER: &str = "row,expected_address,derived_address,result,detail\n";
+
+/// 一行的校验结果
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PairResult {
+    /// 私钥推导出的地址与期望的地址一致
+    Pass,
+    /// 地址不一致
+    Fail,
+    /// 行格式错误（缺少列、私钥或地址无效），不影响其它行
+    Malformed,
+}
+
+impl PairResult {
+    fn as_str(self) -> &'static str {
+        match self {
+            PairResult::Pass => "pass",
+            PairResult::Fail => "fail",
+            PairResult::Malformed => "malformed",
+        }
+    }
+}
+
+/// 校验进度
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PairProgress {
+    /// 已校验的行数（不含标题和空行）
+    pub rows: u64,
+    /// 一致的行数
+    pub passed: u64,
+    /// 不一致的行数
+    pub failed: u64,
+    /// 格式错误的行数
+    pub malformed: u64,
+    /// 已读取的字节数
+    pub bytes_read: u64,
+    /// 文件总字节数
+    pub total_bytes: u64,
+}
+
+/// 校验汇总
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PairSummary {
+    /// 各结果的行数
+    #[serde(flatten)]
+    pub counts: PairProgress,
+    /// 报告文件路径
+    pub report_path: PathBuf,
+}
+
+/**
+ * 去掉字段两端的空白和电子表格导出时加的引号
+ */
+fn clean_field(field: &str) -> &str {
+    let field = field.trim();
+    field.strip_prefix('"').and_then(|f| f.strip_suffix('"')).unwrap_or(field).trim()
+}
+
+/**
+ * 校验一对私钥和地址：按期望地址的格式判断链，用生成时相同的流程推导地址
+ *
+ * @returns (结果, 期望的地址, 推导出的地址, 说明)；都不包含私钥
+ */
+fn check_pair(secp: &Secp256k1<secp256k1::SignOnly>, line: &str) -> (PairResult, String, String, String) {
+    let mut fields = line.split(',');
+    let (Some(key), Some(expected)) = (fields.next().map(clean_field), fields.next().map(clean_field)) else {
+        return (PairResult::Malformed, String::new(), String::new(), "缺少期望的地址列".to_string());
+    };
+    if key.is_empty() || expected.is_empty() {
+        return (PairResult::Malformed, String::new(), String::new(), "私钥或期望的地址为空".to_string());
+    }
+    let chain = Chain::from_address(expected).unwrap_or(Chain::Ethereum);
+    let expected_address = match chain.parse_address(expected) {
+        Ok(address) => address,
+        // 无效的地址列不写入报告：列顺序填反时这一列可能是私钥
+        Err(e) => return (PairResult::Malformed, String::new(), String::new(), e),
+    };
+    let secret = match PrivKeyHex::parse(key) {
+        Ok(key) => key.secret_key(),
+        Err(e) => return (PairResult::Malformed, expected.to_string(), String::new(), e.to_string()),
+    };
+    let (derived, derived_text) = chain.derive_address(secp, &secret, expected);
+    if derived == expected_address {
+        (PairResult::Pass, expected.to_string(), derived_text, chain.as_str().to_string())
+    } else {
+        (PairResult::Fail, expected.to_string(), derived_text, format!("{} 地址不一致", chain.as_str()))
+    }
+}
+
+/**
+ * 报告中的字段：包含逗号或引号时加引号
+ */
+fn report_field(text: &str) -> String {
+    csv_field::escape(text).into_owned()
+}
+
+/**
+ * 校验报告文件路径（与输入文件同目录，文件名加上 .verify-report.csv）
+ */
+pub fn report_path(input: &Path) -> PathBuf {
+    let name = input.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    input.with_file_name(format!("{}.verify-report.csv", name))
+}
+
+/**
+ * 逐行校验 private_key,expected_address 格式的 CSV 文件，并写入报告（只包含行号和地址，不包含私钥）
+ *
+ * 文件按行流式读取；格式错误的行计入 malformed 并继续校验后面的行。第一行以 private_key 开头时视为标题。
+ *
+ * @param input - 输入 CSV 文件
+ * @param on_progress - 进度回调（每 PROGRESS_EVERY_ROWS 行和结束时调用）
+ * @returns 校验汇总
+ */
+pub fn verify_pairs(input: &Path, mut on_progress: impl FnMut(&PairProgress)) -> Result<PairSummary, String> {
+    let file = File::open(input).map_err(|e| format!("无法打开文件: {}", e))?;
+    let total_bytes = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let report = report_path(input);
+    let tmp_path = report.with_extension("csv.tmp");
+    let mut writer = BufWriter::new(File::create(&tmp_path).map_err(|e| format!("无法创建校验报告: {}", e))?);
+    writer.write_all(REPORT_HEADER.as_bytes()).map_err(|e| format!("无法写入校验报告: {}", e))?;
+
+    let secp = Secp256k1::signing_only();
+    let mut progress = PairProgress { total_bytes, ..PairProgress::default() };
+    let mut reader = BufReader::new(file);
+    let mut line = Vec::new();
+    let mut row = 0u64;
+    loop {
+        line.clear();
+        let read = reader.read_until(b'\n', &mut line).map_err(|e| format!("无法读取文件: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        row += 1;
+        progress.bytes_read += read as u64;
+        let checked = match std::str::from_utf8(&line).map(str::trim) {
+            Ok("") => continue,
+            Ok(text) if row == 1 && clean_field(text).to_ascii_lowercase().starts_with("private_key") => continue,
+            Ok(text) => check_pair(&secp, text),
+            Err(_) => (PairResult::Malformed, String::new(), String::new(), "不是 UTF-8 文本".to_string()),
+        };
+
+        let (result, expected, derived, detail) = checked;
+        match result {
+            PairResult::Pass => progress.passed += 1,
+            PairResult::Fail => progress.failed += 1,
+            PairResult::Malformed => progress.malformed += 1,
+        }
+        progress.rows += 1;
+        writeln!(
+            writer,
+            "{},{},{},{},{}",
+            row,
+            report_field(&expected),
+            report_field(&derived),
+            result.as_str(),
+            report_field(&detail)
+        )
+        .map_err(|e| format!("无法写入校验报告: {}", e))?;
+        if progress.rows.is_multiple_of(PROGRESS_EVERY_ROWS) {
+            on_progress(&progress);
+        }
+    }
+
+    writer.flush().map_err(|e| format!("无法写入校验报告: {}", e))?;
+    drop(writer);
+    fs::rename(&tmp_path, &report).map_err(|e| format!("无法写入校验报告: {}", e))?;
+    on_progress(&progress);
+    Ok(PairSummary { counts: progress, report_path: report })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hexutil::Address;
+    use crate::test_support::with_data_dir;
+    use crate::self_test::KNOWN_KEYS;
+
+    /**
+     * 批量校验私钥与地址：各链的正确行通过，不一致的行失败，格式错误的行不中断校验，报告中没有私钥
+     */
+    #[test]
+    fn pair_verification() -> Result<(), String> {
+        with_data_dir("pairs", |dir| {
+            let (key1, address1) = KNOWN_KEYS[0];
+            let (key2, address2) = KNOWN_KEYS[1];
+            let tron2 = Chain::Tron.format_address(Address::parse(address2).map_err(|e| e.to_string())?);
+            let rows = [
+                "private_key,expected_address".to_string(),
+                format!("{},0x{}", key1, address1.to_lowercase()),
+                format!("\"0x{}\", \"{}\"", key2, tron2),
+                format!("{},0x{}", key1, address2),
+                String::new(),
+                key1.to_string(),
+                format!("zz{},0x{}", &key1[2..], address1),
+                format!("0x{},{}", address1, key1),
+            ];
+            let input = dir.join("pairs.csv");
+            let mut content = rows.join("\n").into_bytes();
+            content.extend_from_slice(b"\n\xff\xfe,0x00\n");
+            std::fs::write(&input, content).map_err(|e| e.to_string())?;
+            
+            let mut updates: Vec<PairProgress> = Vec::new();
+            let summary = verify_pairs(&input, |progress| updates.push(progress.clone()))?;
+            let counts = &summary.counts;
+            if (counts.rows, counts.passed, counts.failed, counts.malformed) != (7, 2, 1, 4) {
+                return Err(format!("校验结果不正确: {:?}", counts));
+            }
+            if updates.last().map(|p| p.bytes_read) != Some(counts.total_bytes) {
+                return Err("最后一次进度没有读完整个文件".to_string());
+            }
+            let report = std::fs::read_to_string(&summary.report_path).map_err(|e| e.to_string())?;
+            if report.lines().count() != 8 || report.contains(&key1[2..]) || report.contains(&key2[2..]) {
+                return Err(format!("报告行数不正确或包含私钥:\n{}", report));
+            }
+            Ok(())
+        })
+    }
+}
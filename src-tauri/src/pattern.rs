@@ -0,0 +1,2648 @@
+use crate::bitcoin::BECH32_CHARSET;
+use crate::chain::BASE58_ALPHABET;
+use crate::solana;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "gui")]
+use std::fmt;
+
+/// 地址中可用于匹配的最大字符数
+const ADDRESS_LENGTH: usize = 40;
+
+/// Base58 地址中可用于匹配的最大字符数（波场和比特币 P2PKH 最多 34 个字符，开头固定的 T 或 1 不参与匹配）
+const BASE58_ADDRESS_LENGTH: usize = 33;
+
+/// Bech32 地址中可用于匹配的字符数（P2WPKH 地址 42 个字符，开头固定的 bc1q 不参与匹配）
+const BECH32_ADDRESS_LENGTH: usize = 38;
+
+/// Bech32 地址的人类可读部分和分隔符（模式不能包含）
+const BECH32_HRP_PREFIX: &str = "bc1";
+
+/// 单个字符位置上，随机 checksum 地址与给定数字相同的概率
+const DIGIT_PROBABILITY: f64 = 1.0 / 16.0;
+
+/// 单个字符位置上，随机 checksum 地址与给定字母（含大小写）相同的概率
+const LETTER_PROBABILITY: f64 = 1.0 / 32.0;
+
+/// 不区分大小写时，单个字符位置上与给定字符相同的概率
+const CASE_INSENSITIVE_PROBABILITY: f64 = 1.0 / 16.0;
+
+/// 分别指定前缀和后缀时两者之间的分隔符
+pub const PREFIX_SUFFIX_SEPARATOR: char = '/';
+
+/// 与任意一个十六进制字符相同的占位符
+const ANY_NIBBLE: char = '?';
+
+/// 正则表达式编译后的大小上限（字节），防止重复次数很大的模式占用大量内存
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+/// 估算正则表达式难度时抽样的地址数
+const REGEX_DIFFICULTY_SAMPLES: u32 = 8192;
+
+/// 支持的正则表达式语法（写在错误信息中）
+const REGEX_FLAVOR: &str = "Rust regex 语法：支持 ^ $ 锚点、字符类、分组、| 和有界重复，不支持反向引用和环视";
+
+/// leading_zeros 模式的模式字符串前缀（后接最少的零的个数，例如 zeros>=8）
+const LEADING_ZEROS_PREFIX: &str = "zeros>=";
+
+/// caps_prefix 模式的模式字符串前缀：开头的字母都是大写（后接字符数，例如 caps>=8）
+const CAPS_PREFIX: &str = "caps>=";
+
+/// caps_prefix 模式的模式字符串前缀：开头的字母都是小写（例如 lower>=8）
+const LOWER_PREFIX: &str = "lower>=";
+
+/// char_count 模式的模式字符串前缀（后接字符、)>= 和最少的个数，例如 count(8)>=12；字符为 * 时不限定是哪个字符）
+const CHAR_COUNT_PREFIX: &str = "count(";
+
+/// char_count 模式的模式字符串中字符和最少个数之间的分隔
+const CHAR_COUNT_SEPARATOR: &str = ")>=";
+
+/// char_count 模式中表示任意一个字符的写法
+const ANY_CHARACTER: char = '*';
+
+/// 随机的以太坊地址中一个字符是字母的概率（checksum 形式中字母大写和小写各占一半）
+const LETTER_SHARE: f64 = 6.0 / 16.0;
+
+/// 各难度等级的上限（位，不含）：easy、moderate、hard、extreme，更难的为 infeasible
+#[cfg(feature = "gui")]
+const DIFFICULTY_CLASS_LIMITS: [f64; 4] = [24.0, 32.0, 40.0, 56.0];
+
+/// 文件名中模式部分的最大长度
+#[cfg(feature = "gui")]
+const MAX_FILE_LABEL_LENGTH: usize = 48;
+
+/// 模式与地址的哪一部分比较
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchMode {
+    /// 地址以模式开头
+    Prefix,
+    /// 地址以模式结尾
+    Suffix,
+    /// 地址同时以模式开头和结尾（普通模式的默认值）
+    Both,
+    /// 地址的任意位置包含模式（*...* 形式的默认值）
+    Contains,
+}
+
+/// 模式与地址比较时的大小写方式（与 case_sensitive 等价的另一种写法）
+#[cfg(feature = "gui")]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CaseMode {
+    /// 小写的模式与小写地址比较（默认，相当于 case_sensitive: false）
+    #[default]
+    Insensitive,
+    /// 模式按原样逐字符与 checksum 地址比较，不做任何转换（相当于 case_sensitive: true）
+    Exact,
+}
+
+#[cfg(feature = "gui")]
+impl CaseMode {
+    /**
+     * 由 case_mode 和 case_sensitive 得到是否区分大小写（都没有给出时不区分）
+     *
+     * @param case_mode - 大小写方式
+     * @param case_sensitive - 是否区分大小写
+     * @returns 两者同时给出且矛盾时返回错误
+     */
+    pub fn resolve(case_mode: Option<CaseMode>, case_sensitive: Option<bool>) -> Result<bool, String> {
+        match (case_mode, case_sensitive) {
+            (Some(mode), Some(sensitive)) if (mode == CaseMode::Exact) != sensitive => {
+                Err(format!("case_mode: {} 与 case_sensitive: {} 矛盾，只需给出其中一个", mode.as_str(), sensitive))
+            }
+            (Some(mode), _) => Ok(mode == CaseMode::Exact),
+            (None, sensitive) => Ok(sensitive.unwrap_or_default()),
+        }
+    }
+
+    /**
+     * 参数中的名称
+     */
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CaseMode::Insensitive => "insensitive",
+            CaseMode::Exact => "exact",
+        }
+    }
+}
+
+/// 模式可以使用的字符（由地址所属的链决定）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PatternAlphabet {
+    /// 十六进制字符（以太坊地址，不含 0x 前缀）
+    #[default]
+    Hex,
+    /// Base58 字符，不含 0、O、I、l（波场和比特币 P2PKH 地址，不含开头的 T 或 1，区分大小写时按字符本身比较）
+    Base58,
+    /// Bech32 字符，不含 1、b、i、o（比特币 P2WPKH 地址，不含开头的 bc1q；地址只有小写字母）
+    Bech32,
+    /// Base58 字符（Solana 地址，即整个 ed25519 公钥的 Base58 编码，没有固定的开头；总是区分大小写）
+    Base58PublicKey,
+}
+
+impl PatternAlphabet {
+    /**
+     * 地址中可用于匹配的字符数
+     */
+    fn address_length(self) -> usize {
+        match self {
+            PatternAlphabet::Hex => ADDRESS_LENGTH,
+            PatternAlphabet::Base58 => BASE58_ADDRESS_LENGTH,
+            PatternAlphabet::Bech32 => BECH32_ADDRESS_LENGTH,
+            PatternAlphabet::Base58PublicKey => solana::MAX_ADDRESS_LENGTH,
+        }
+    }
+
+    /**
+     * 错误信息中字母表的名称
+     */
+    fn name(self) -> &'static str {
+        match self {
+            PatternAlphabet::Hex => "十六进制",
+            PatternAlphabet::Base58 => "Base58",
+            PatternAlphabet::Bech32 => "Bech32",
+            PatternAlphabet::Base58PublicKey => "Solana",
+        }
+    }
+
+    /**
+     * 是否为该字母表中的字符
+     */
+    fn contains(self, c: char) -> bool {
+        match self {
+            PatternAlphabet::Hex => c.is_ascii_hexdigit(),
+            PatternAlphabet::Base58 | PatternAlphabet::Base58PublicKey => BASE58_ALPHABET.contains(c),
+            PatternAlphabet::Bech32 => BECH32_CHARSET.contains(c.to_ascii_lowercase()),
+        }
+    }
+
+    /**
+     * 错误信息中对字符的说明
+     */
+    fn description(self) -> &'static str {
+        match self {
+            PatternAlphabet::Hex => "十六进制字符",
+            PatternAlphabet::Base58 | PatternAlphabet::Base58PublicKey => "Base58 字符（不含 0、O、I、l）",
+            PatternAlphabet::Bech32 => "Bech32 字符（不含 1、b、i、o）",
+        }
+    }
+
+    /**
+     * 单个字符位置上的概率：(数字在字母表中的比例, 与给定数字相同的概率, 与给定字母相同的概率)
+     *
+     * Base58 不区分大小写时大多数字母有大小写两种写法，按 2/58 近似（i、o、L 只有一种写法）；Bech32 只有小写字母。
+     *
+     * @param case_sensitive - 是否区分大小写
+     */
+    fn probabilities(self, case_sensitive: bool) -> (f64, f64, f64) {
+        match self {
+            PatternAlphabet::Hex if case_sensitive => (10.0 / 16.0, DIGIT_PROBABILITY, LETTER_PROBABILITY),
+            PatternAlphabet::Hex => (10.0 / 16.0, CASE_INSENSITIVE_PROBABILITY, CASE_INSENSITIVE_PROBABILITY),
+            PatternAlphabet::Base58 | PatternAlphabet::Base58PublicKey if case_sensitive => (9.0 / 58.0, 1.0 / 58.0, 1.0 / 58.0),
+            PatternAlphabet::Base58 | PatternAlphabet::Base58PublicKey => (9.0 / 58.0, 1.0 / 58.0, 2.0 / 58.0),
+            PatternAlphabet::Bech32 => (9.0 / 32.0, 1.0 / 32.0, 1.0 / 32.0),
+        }
+    }
+}
+
+/// 模式的类型
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PatternType {
+    /// 前后缀和通配符模式（默认）
+    #[default]
+    Standard,
+    /// 正则表达式，与 40 个字符的小写地址（不含 0x 前缀）比较
+    Regex,
+    /// 地址开头至少有 min_zero_nibbles 个 0（模式字符串为 zeros>=N）
+    LeadingZeros,
+    /// checksum 地址开头 N 个字符中的字母都是大写或都是小写，数字不限（模式字符串为 caps>=N 或 lower>=N，只用于以太坊地址）
+    CapsPrefix,
+    /// 小写地址中某个字符至少出现 N 次，位置不限（模式字符串为 count(8)>=12，count(*)>=N 时可以是任意一个字符）
+    CharCount,
+}
+
+/// 支持的模式语法（帮助信息直接由此生成，新增语法时需要同步补充）
+struct PatternConstruct {
+    name: &'static str,
+    syntax: &'static str,
+    description: &'static str,
+    example: &'static str,
+    /// 应当与示例匹配的 checksum 地址（用于测试示例是否仍然有效）
+    matching_address: &'static str,
+    /// 示例的模式类型
+    pattern_type: PatternType,
+}
+
+const PATTERN_CONSTRUCTS: &[PatternConstruct] = &[
+    PatternConstruct {
+        name: "prefix_suffix",
+        syntax: "<十六进制字符>",
+        description: "地址的前缀和后缀同时与模式相同（默认不区分大小写；区分大小写时按输入的大小写与 checksum 地址比较）",
+        example: "888",
+        matching_address: "888a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0888",
+        pattern_type: PatternType::Standard,
+    },
+    PatternConstruct {
+        name: "prefix_and_suffix",
+        syntax: "<前缀>/<后缀>",
+        description: "分别指定前缀和后缀，其中一侧可以为空，表示只限制另一侧",
+        example: "123/456",
+        matching_address: "123a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0456",
+        pattern_type: PatternType::Standard,
+    },
+    PatternConstruct {
+        name: "positional_wildcard",
+        syntax: "<十六进制字符和 ?>",
+        description: "? 与该位置上任意一个十六进制字符相同，可以用于前后缀和 <前缀>/<后缀> 形式（不能用于 *...*），开头的 0x 会被忽略",
+        example: "d??d",
+        matching_address: "dead0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0ed00d",
+        pattern_type: PatternType::Standard,
+    },
+    PatternConstruct {
+        name: "wildcard",
+        syntax: "*<十六进制字符>*",
+        description: "以 * 包围的模式：地址的任意位置包含该字符串（普通模式也可以用 match_mode 指定 prefix、suffix、both 或 contains）",
+        example: "*123*",
+        matching_address: "a0b0c0d0e0f0a0b0c0d0123e0f0a0b0c0d0e0f0a",
+        pattern_type: PatternType::Standard,
+    },
+    PatternConstruct {
+        name: "repeat_aaaa",
+        syntax: "*aaaa*",
+        description: "前 4 位是同一个字符，后 4 位也是同一个字符",
+        example: "*aaaa*",
+        matching_address: "1111a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d02222",
+        pattern_type: PatternType::Standard,
+    },
+    PatternConstruct {
+        name: "repeat_aabb",
+        syntax: "*aabb*",
+        description: "前 4 位和后 4 位都是 aabb 形式：两个相同字符后接另外两个相同字符",
+        example: "*aabb*",
+        matching_address: "1122a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d03344",
+        pattern_type: PatternType::Standard,
+    },
+    PatternConstruct {
+        name: "repeat_abab",
+        syntax: "*abab*",
+        description: "前 4 位和后 4 位都是 abab 形式：两个不同字符交替出现",
+        example: "*abab*",
+        matching_address: "1212a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d03434",
+        pattern_type: PatternType::Standard,
+    },
+    PatternConstruct {
+        name: "letter_template",
+        syntax: "*<字母 a–z>*",
+        description: "字母模板：相同的字母为相同的字符，不同的字母为不同的字符，默认前 N 位和后 N 位都满足（match_mode 为 prefix 或 suffix 时只比较一侧）；\
+                      字母按 a、b、c… 的顺序第一次出现（或含有 g–z）且至少有一个字母重复时才是模板，否则按包含比较（例如 *beef*）",
+        example: "*abcabc*",
+        matching_address: "1231230b0c0d0e0f0a0b0c0d0e0f0a0b0c456456",
+        pattern_type: PatternType::Standard,
+    },
+    PatternConstruct {
+        name: "regex",
+        syntax: "pattern_type: regex",
+        description: "正则表达式（Rust regex 语法），与 40 个字符的小写地址比较；不加 ^ 和 $ 时可以出现在地址的任意位置",
+        example: "^dead.*beef$",
+        matching_address: "dead0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0ebeef",
+        pattern_type: PatternType::Regex,
+    },
+    PatternConstruct {
+        name: "leading_zeros",
+        syntax: "pattern_type: leading_zeros, min_zero_nibbles: <1–40>",
+        description: "地址开头至少有指定个数的 0（零字节越多，作为合约地址在 calldata 中越便宜）；结果的模式列记录实际达到的个数",
+        example: "zeros>=8",
+        matching_address: "0000000000e0f0a0b0c0d0e0f0a0b0c0d0e0f0a1",
+        pattern_type: PatternType::LeadingZeros,
+    },
+    PatternConstruct {
+        name: "caps_prefix",
+        syntax: "pattern_type: caps_prefix, caps_prefix: <1–40>",
+        description: "只看 EIP-55 checksum 地址的大小写：开头 N 个字符中的字母都是大写（caps_lowercase 时都是小写），数字不限；难度取决于其中数字和字母的比例",
+        example: "caps>=8",
+        matching_address: "AB12CDEFa0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0",
+        pattern_type: PatternType::CapsPrefix,
+    },
+    PatternConstruct {
+        name: "char_count",
+        syntax: "pattern_type: char_count, character: <十六进制字符>, min_count: <1–40>",
+        description: "小写地址中该字符至少出现 min_count 次，位置不限；不传 character 时任意一个字符出现这么多次即可（count(*)>=N）；\
+                      结果的模式列记录实际的字符和个数",
+        example: "count(8)>=12",
+        matching_address: "8888888888880b0c0d0e0f0a0b0c0d0e0f0a0b0c",
+        pattern_type: PatternType::CharCount,
+    },
+];
+
+/// 模式语法说明条目（示例的解读和难度在调用时由解析器实时计算）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PatternSyntaxEntry {
+    /// 语法名称
+    pub name: String,
+    /// 语法形式
+    pub syntax: String,
+    /// 简要说明
+    pub description: String,
+    /// 示例模式
+    pub example: String,
+    /// 示例的模式类型
+    pub pattern_type: PatternType,
+    /// 解析器对示例的解读
+    pub interpretation: String,
+    /// 示例的期望尝试次数
+    pub expected_attempts: f64,
+    /// 示例的难度（期望尝试次数的以 2 为底的对数）
+    pub difficulty_bits: f64,
+    /// 示例的校验错误（为空表示示例有效）
+    pub error: Option<String>,
+    /// 一个与示例匹配的 checksum 地址
+    pub matching_address: String,
+    /// 解析器是否确认该地址与示例匹配
+    pub example_matches: bool,
+}
+
+/**
+ * 生成模式语法说明：对每个示例实际运行解析器和难度估算
+ * 
+ * @returns 语法说明列表
+ */
+pub fn describe_syntax() -> Vec<PatternSyntaxEntry> {
+    PATTERN_CONSTRUCTS
+        .iter()
+        .map(|construct| {
+            let parsed = ParsedPattern::with_options(construct.example, construct.pattern_type, false);
+            PatternSyntaxEntry {
+                name: construct.name.to_string(),
+                syntax: construct.syntax.to_string(),
+                description: construct.description.to_string(),
+                example: construct.example.to_string(),
+                pattern_type: construct.pattern_type,
+                interpretation: parsed.interpretation(),
+                expected_attempts: parsed.expected_attempts(),
+                difficulty_bits: parsed.difficulty_bits(),
+                error: parsed.validate().err(),
+                matching_address: construct.matching_address.to_string(),
+                example_matches: parsed.matches(construct.matching_address),
+            }
+        })
+        .collect()
+}
+
+/// 匹配到的规则（与模式语法说明中的名称一致）
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchRule {
+    /// 前后缀与模式相同
+    PrefixSuffix,
+    /// 分别指定的前缀和后缀
+    PrefixAndSuffix,
+    /// 以 * 包围的模式（地址的任意位置包含模式）
+    Wildcard,
+    /// 用 match_mode 指定只比较前缀
+    Prefix,
+    /// 用 match_mode 指定只比较后缀
+    Suffix,
+    /// 用 match_mode 指定地址的任意位置包含模式
+    Contains,
+    /// *aaaa*
+    RepeatAaaa,
+    /// *aabb*
+    RepeatAabb,
+    /// *abab*
+    RepeatAbab,
+    /// 其它字母模板（例如 *aabbcc*）
+    Template,
+    /// 正则表达式
+    Regex,
+    /// 开头的 0
+    LeadingZeros,
+    /// 开头的字母都是大写或都是小写
+    CapsPrefix,
+    /// 某个字符出现的次数
+    CharCount,
+}
+
+/// 引擎实际执行的匹配条件（解析之后的结果，记录在会话事件、状态和清单中，便于排查）
+///
+/// Display 输出便于阅读的形式，例如 prefix=8888 AND suffix=8888, case-sensitive。
+#[cfg(feature = "gui")]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Matcher {
+    /// 地址以 prefix 开头并以 suffix 结尾（区分大小写时按给出的大小写与 checksum 地址比较，为空的一侧不限制）
+    Affixes { prefix: String, suffix: String, case_sensitive: bool },
+    /// 前 N 位和/或后 N 位满足字母模板 shape（sides 为 prefix、suffix 或 both）
+    Repeat {
+        shape: String,
+        #[serde(default = "both_sides")]
+        sides: MatchMode,
+        case_sensitive: bool,
+    },
+    /// 地址的任意位置包含 text
+    Contains { text: String, case_sensitive: bool },
+    /// 小写地址满足正则表达式（总是不区分大小写）
+    Regex { pattern: String },
+    /// 地址开头至少有 min_zero_nibbles 个 0（0 没有大小写）
+    LeadingZeros { min_zero_nibbles: u8 },
+    /// checksum 地址开头 length 个字符中的字母都是大写（lowercase 时都是小写）
+    CapsPrefix { length: u8, lowercase: bool },
+    /// 小写地址中 character 至少出现 min_count 次（character 为空时为任意一个字符）
+    CharCount { character: Option<char>, min_count: u8 },
+}
+
+#[cfg(feature = "gui")]
+impl fmt::Display for Matcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let case_sensitive = match self {
+            Matcher::Affixes { prefix, suffix, case_sensitive } => {
+                let sides: Vec<String> = [("prefix", prefix), ("suffix", suffix)]
+                    .into_iter()
+                    .filter(|(_, text)| !text.is_empty())
+                    .map(|(side, text)| format!("{}={}", side, text))
+                    .collect();
+                if sides.is_empty() {
+                    write!(f, "any")?;
+                } else {
+                    write!(f, "{}", sides.join(" AND "))?;
+                }
+                case_sensitive
+            }
+            Matcher::Repeat { shape, sides, case_sensitive } => {
+                match sides {
+                    MatchMode::Prefix => write!(f, "prefix=repeat({})", shape)?,
+                    MatchMode::Suffix => write!(f, "suffix=repeat({})", shape)?,
+                    MatchMode::Both | MatchMode::Contains => write!(f, "prefix=repeat({}) AND suffix=repeat({})", shape, shape)?,
+                }
+                case_sensitive
+            }
+            Matcher::Contains { text, case_sensitive } => {
+                write!(f, "contains={}", text)?;
+                case_sensitive
+            }
+            Matcher::Regex { pattern } => {
+                write!(f, "regex=/{}/", pattern)?;
+                &false
+            }
+            Matcher::LeadingZeros { min_zero_nibbles } => return write!(f, "leading_zeros>={}", min_zero_nibbles),
+            Matcher::CapsPrefix { length, lowercase: false } => return write!(f, "caps_prefix>={}", length),
+            Matcher::CapsPrefix { length, lowercase: true } => return write!(f, "lower_prefix>={}", length),
+            Matcher::CharCount { character: Some(character), min_count } => return write!(f, "count({})>={}", character, min_count),
+            Matcher::CharCount { character: None, min_count } => return write!(f, "count(any)>={}", min_count),
+        };
+        write!(f, ", {}", if *case_sensitive { "case-sensitive" } else { "case-insensitive" })
+    }
+}
+
+/**
+ * 记录中没有 sides 的字母模板匹配条件比较前缀和后缀两侧
+ */
+#[cfg(feature = "gui")]
+fn both_sides() -> MatchMode {
+    MatchMode::Both
+}
+
+/// 地址中满足模式的一段字符
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct MatchedSpan {
+    /// 起始位置（十六进制字符下标，不含 0x 前缀，从 0 开始；其它链为不含固定开头（波场的 T、比特币的 1 或 bc1q）的字符下标）
+    pub start: usize,
+    /// 结束位置（不含）
+    pub end: usize,
+    /// 满足的规则
+    pub rule: MatchRule,
+}
+
+/// 模式中无效的一个字符
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidCharacter {
+    /// 在用户输入中的字符序号（从 1 开始，与错误信息中的序号相同）
+    pub position: usize,
+    /// 无效的字符
+    pub character: char,
+}
+
+/// 难度等级（按难度的位数划分，期望尝试次数约为 2 的位数次方）
+#[cfg(feature = "gui")]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DifficultyClass {
+    /// 不到 2^24 次尝试（通常几秒以内）
+    Easy,
+    /// 2^24 到 2^32 次尝试（通常几分钟到几小时）
+    Moderate,
+    /// 2^32 到 2^40 次尝试（通常几小时到几天）
+    Hard,
+    /// 2^40 到 2^56 次尝试（单机需要数月到数千年）
+    Extreme,
+    /// 2^56 次以上，实际上不可能找到
+    Infeasible,
+}
+
+#[cfg(feature = "gui")]
+impl DifficultyClass {
+    /**
+     * 难度对应的等级
+     *
+     * @param bits - 难度（期望尝试次数的以 2 为底的对数）
+     */
+    pub fn from_bits(bits: f64) -> Self {
+        const CLASSES: [DifficultyClass; 4] = [DifficultyClass::Easy, DifficultyClass::Moderate, DifficultyClass::Hard, DifficultyClass::Extreme];
+        CLASSES.into_iter().zip(DIFFICULTY_CLASS_LIMITS).find(|&(_, limit)| bits < limit).map_or(DifficultyClass::Infeasible, |(class, _)| class)
+    }
+}
+
+/// 开始前对模式的预检结果（与生成前的校验使用同一个 validate）
+#[cfg(feature = "gui")]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PatternValidation {
+    /// 模式是否有效
+    pub valid: bool,
+    /// 无效的原因（与生成时返回的错误相同）
+    pub error: Option<String>,
+    /// 规范化的模式（去掉首尾空白和 0x 前缀，不区分大小写时转换为小写）
+    pub normalized: String,
+    /// 识别出的匹配规则（前缀、后缀、前后缀、通配符或重复形式模板等）
+    pub mode: MatchRule,
+    /// 全部无效的字符（不只是错误信息中的第一个）
+    pub invalid_characters: Vec<InvalidCharacter>,
+    /// 难度（期望尝试次数的以 2 为底的对数）
+    pub difficulty_bits: f64,
+    /// 难度等级
+    pub difficulty_class: DifficultyClass,
+    /// 不影响有效性的提示（例如区分大小写对只有数字的模式没有作用）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// 解析后的靓号模式
+#[derive(Debug, Clone)]
+pub struct ParsedPattern {
+    /// 是否为通配符模式（以 * 开头和结尾，默认表示包含）
+    pub is_wildcard: bool,
+    /// 去掉通配符和 0x 前缀后的小写搜索串（? 表示该位置上任意一个字符）
+    pub search: String,
+    /// 用于前后缀比较的搜索串（区分大小写时保留用户输入的大小写，否则为小写）
+    search_cased: String,
+    /// 分别指定的前缀和后缀（<前缀>/<后缀> 形式，大小写同上；为空的一侧不限制）
+    split: Option<(String, String)>,
+    /// 是否按用户输入的大小写与 checksum 地址比较（为 false 时只比较十六进制字符）
+    case_sensitive: bool,
+    /// 正则表达式模式编译的结果（其它模式为空；编译失败时保存错误，由 validate 返回）
+    regex: Option<Result<CompiledRegex, String>>,
+    /// 用户输入中搜索串之前的字符数（首部的空白、0x 前缀或开头的 *；错误信息中的字符序号按用户输入计算）
+    offset: usize,
+    /// 用户输入是否带有 0x 前缀（Base58、Bech32 和 Solana 地址没有，由 validate 返回错误）
+    hex_prefix: bool,
+    /// 去掉通配符和 0x 前缀后用户输入的搜索串（保留大小写，Base58 模式按它校验）
+    input: String,
+    /// 模式可以使用的字符（默认十六进制）
+    alphabet: PatternAlphabet,
+    /// 显式指定的比较方式（为空时按模式语法决定）
+    requested_mode: Option<MatchMode>,
+    /// leading_zeros 模式要求的最少的零的个数（其它模式为空；无法解析时为 0，由 validate 返回错误）
+    min_zero_nibbles: Option<u8>,
+    /// caps_prefix 模式的要求（其它模式为空；无法解析时字符数为 0，由 validate 返回错误）
+    case_prefix: Option<CasePrefix>,
+    /// char_count 模式的要求（其它模式为空；无法解析时个数为 0、字符为 U+FFFD，由 validate 返回错误）
+    char_threshold: Option<CharThreshold>,
+    /// 是否为 *...* 形式的字母模板（解析时判断一次，生成循环中每次比较直接使用）
+    letter_template: bool,
+}
+
+/// caps_prefix 模式：checksum 地址开头 length 个字符中的字母都是大写（lowercase 时都是小写）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CasePrefix {
+    length: u8,
+    lowercase: bool,
+}
+
+/// char_count 模式：小写地址中 character 至少出现 min_count 次（character 为空时为任意一个字符）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CharThreshold {
+    character: Option<char>,
+    min_count: u8,
+}
+
+/// char_count 模式下地址中实际达到的字符和个数（记录在 wallet-found 事件和结果文件的模式列中）
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct CharCount {
+    /// 计数的字符（小写）
+    pub character: char,
+    /// 出现的次数
+    pub count: usize,
+}
+
+/// 按半字节与地址的 20 个字节比较的前后缀（生成循环在计算十六进制文本之前用它排除绝大多数地址）
+///
+/// 只比较十六进制数字，不比较大小写：区分大小写的模式满足时还要再与 checksum 地址比较。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NibbleAffixes {
+    /// 前缀的半字节（? 占位符为空）
+    prefix: Vec<Option<u8>>,
+    /// 后缀的半字节（? 占位符为空）
+    suffix: Vec<Option<u8>>,
+    /// 前缀和后缀不能重叠（<前缀>/<后缀> 形式；前后缀相同的模式两侧各自比较，可以重叠）
+    disjoint: bool,
+}
+
+impl NibbleAffixes {
+    /**
+     * 地址的字节是否满足前后缀（结果与小写地址的字符串比较相同）
+     *
+     * @param address - 地址的 20 个字节
+     */
+    pub fn matches(&self, address: &[u8; 20]) -> bool {
+        let nibble = |i: usize| if i.is_multiple_of(2) { address[i / 2] >> 4 } else { address[i / 2] & 0xf };
+        let fits = if self.disjoint {
+            self.prefix.len() + self.suffix.len() <= ADDRESS_LENGTH
+        } else {
+            self.prefix.len() <= ADDRESS_LENGTH && self.suffix.len() <= ADDRESS_LENGTH
+        };
+        let suffix_start = ADDRESS_LENGTH.saturating_sub(self.suffix.len());
+        fits && self.prefix.iter().enumerate().all(|(i, expected)| expected.is_none_or(|n| nibble(i) == n))
+            && self.suffix.iter().enumerate().all(|(i, expected)| expected.is_none_or(|n| nibble(suffix_start + i) == n))
+    }
+}
+
+/**
+ * 搜索串的半字节（? 占位符为空）；包含十六进制和 ? 以外的字符时为空
+ */
+fn nibbles(search: &str) -> Option<Vec<Option<u8>>> {
+    search
+        .chars()
+        .map(|c| match c {
+            ANY_NIBBLE => Some(None),
+            _ => c.to_digit(16).map(|n| Some(n as u8)),
+        })
+        .collect()
+}
+
+/// 编译后的正则表达式模式
+#[derive(Debug, Clone)]
+struct CompiledRegex {
+    /// 编译结果（不区分大小写）
+    regex: Regex,
+    /// 抽样估算的难度（位）
+    difficulty_bits: f64,
+}
+
+/**
+ * 同时搜索多个模式时的难度：地址满足其中任意一个的概率按各模式的概率之和近似（不超过 1）
+ *
+ * 在对数域中求和，避免难度很高的模式下溢为 0。
+ *
+ * @param patterns - 解析后的模式（不能为空）
+ */
+pub fn combined_difficulty_bits(patterns: &[ParsedPattern]) -> f64 {
+    let bits: Vec<f64> = patterns.iter().map(ParsedPattern::difficulty_bits).collect();
+    let easiest = bits.iter().copied().fold(f64::INFINITY, f64::min);
+    if !easiest.is_finite() {
+        return easiest;
+    }
+    let sum: f64 = bits.iter().map(|b| (easiest - b).exp2()).sum();
+    (easiest - sum.log2()).max(0.0)
+}
+
+/**
+ * leading_zeros 模式的模式字符串（写入文件名和会话记录，例如 zeros>=8）
+ *
+ * @param min_zero_nibbles - 最少的零的个数
+ */
+#[cfg(feature = "gui")]
+pub fn leading_zeros_pattern(min_zero_nibbles: u8) -> String {
+    format!("{}{}", LEADING_ZEROS_PREFIX, min_zero_nibbles)
+}
+
+/**
+ * caps_prefix 模式的模式字符串（例如 caps>=8 或 lower>=8）
+ *
+ * @param length - 开头的字符数
+ * @param lowercase - 是否要求字母都是小写
+ */
+#[cfg(feature = "gui")]
+pub fn caps_prefix_pattern(length: u8, lowercase: bool) -> String {
+    format!("{}{}", if lowercase { LOWER_PREFIX } else { CAPS_PREFIX }, length)
+}
+
+/**
+ * char_count 模式的模式字符串（例如 count(8)>=12；字符为空时为 count(*)>=N）
+ *
+ * @param character - 计数的字符（为空时为任意一个字符）
+ * @param min_count - 最少的个数
+ */
+#[cfg(feature = "gui")]
+pub fn char_count_pattern(character: Option<char>, min_count: u8) -> String {
+    format!("{}{}{}{}", CHAR_COUNT_PREFIX, character.unwrap_or(ANY_CHARACTER), CHAR_COUNT_SEPARATOR, min_count)
+}
+
+/**
+ * char_count 模式结果的模式列：记录实际的字符和个数（例如 count(8)=13）
+ *
+ * @param achieved - 地址中实际达到的字符和个数
+ */
+#[cfg(feature = "gui")]
+pub fn achieved_count_label(achieved: CharCount) -> String {
+    format!("{}{})={}", CHAR_COUNT_PREFIX, achieved.character, achieved.count)
+}
+
+/**
+ * 地址中出现次数最多的十六进制字符及其个数（不区分大小写；个数相同时取 0–f 中靠前的字符）
+ *
+ * @param address - 地址（任意大小写，不含 0x 前缀）
+ * @param character - 只计数这个字符（为空时取出现次数最多的字符）
+ */
+pub fn count_characters(address: &str, character: Option<char>) -> CharCount {
+    let mut counts = [0usize; 16];
+    for c in address.chars() {
+        if let Some(n) = c.to_digit(16) {
+            counts[n as usize] += 1;
+        }
+    }
+    let nibble = match character.and_then(|c| c.to_digit(16)) {
+        Some(n) => n as usize,
+        None => (0..16).fold(0, |best, n| if counts[n] > counts[best] { n } else { best }),
+    };
+    CharCount { character: char::from_digit(nibble as u32, 16).unwrap_or('0'), count: counts[nibble] }
+}
+
+/**
+ * 随机地址中某一个十六进制字符至少出现 min_count 次的概率（二项分布的尾部）
+ */
+fn char_count_tail(min_count: usize) -> f64 {
+    let p = DIGIT_PROBABILITY;
+    let mut binomial = 1.0;
+    let mut tail = 0.0;
+    for k in 0..=ADDRESS_LENGTH {
+        if k > 0 {
+            binomial = binomial * (ADDRESS_LENGTH - k + 1) as f64 / k as f64;
+        }
+        if k >= min_count {
+            tail += binomial * p.powi(k as i32) * (1.0 - p).powi((ADDRESS_LENGTH - k) as i32);
+        }
+    }
+    tail
+}
+
+/**
+ * 随机地址中有任意一个字符至少出现 min_count 次的概率
+ *
+ * 概率很小时按 16 个字符的事件互斥近似（min_count 超过 20 时就是精确值）；否则由指数型生成函数
+ * 计算全部字符都少于 min_count 次的概率，再取补。
+ */
+fn any_char_count_probability(min_count: usize) -> f64 {
+    let union = 16.0 * char_count_tail(min_count);
+    if union < 1e-6 {
+        return union;
+    }
+    // below[j]：j 个位置的字符串中每个字符都出现少于 min_count 次的指数型生成函数系数
+    let single: Vec<f64> = (0..=ADDRESS_LENGTH).scan(1.0, |factorial, j| {
+        if j > 0 {
+            *factorial /= j as f64;
+        }
+        Some(if j < min_count { *factorial } else { 0.0 })
+    }).collect();
+    let mut below = vec![0.0; ADDRESS_LENGTH + 1];
+    below[0] = 1.0;
+    for _ in 0..16 {
+        let mut next = vec![0.0; ADDRESS_LENGTH + 1];
+        for (i, &a) in below.iter().enumerate() {
+            for (j, &b) in single.iter().enumerate().take(ADDRESS_LENGTH + 1 - i) {
+                next[i + j] += a * b;
+            }
+        }
+        below = next;
+    }
+    let arrangements = (1..=ADDRESS_LENGTH).fold(1.0, |f, k| f * k as f64 / 16.0);
+    (1.0 - below[ADDRESS_LENGTH] * arrangements).clamp(0.0, 1.0)
+}
+
+/**
+ * leading_zeros 模式结果的模式列：记录实际达到的零的个数（例如 zeros=10）
+ *
+ * @param zero_nibbles - 地址开头的零的个数
+ */
+#[cfg(feature = "gui")]
+pub fn achieved_zeros_label(zero_nibbles: usize) -> String {
+    format!("zeros={}", zero_nibbles)
+}
+
+/**
+ * 地址开头的 0 的个数（十六进制字符，不含 0x 前缀）
+ *
+ * @param address - 地址（任意大小写）
+ */
+pub fn leading_zero_nibbles(address: &str) -> usize {
+    address.bytes().take_while(|&b| b == b'0').count()
+}
+
+/**
+ * 由分别指定的前缀和后缀组成模式字符串（写入 CSV 的模式列，例如 dead/beef）
+ *
+ * @param prefix - 前缀（可以为空）
+ * @param suffix - 后缀（可以为空）
+ */
+#[cfg(feature = "gui")]
+pub fn prefix_suffix_pattern(prefix: &str, suffix: &str) -> String {
+    format!("{}{}{}", prefix.trim(), PREFIX_SUFFIX_SEPARATOR, suffix.trim())
+}
+
+/**
+ * 模式在文件名中的形式（去掉通配符，前后缀分隔符换成连字符，? 换成 x；正则表达式中的其它符号也去掉）
+ */
+#[cfg(feature = "gui")]
+pub fn file_label(pattern: &str) -> String {
+    let label: String = pattern
+        .replace(PREFIX_SUFFIX_SEPARATOR, "-")
+        .replace(ANY_NIBBLE, "x")
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .take(MAX_FILE_LABEL_LENGTH)
+        .collect();
+    if label.is_empty() {
+        "pattern".to_string()
+    } else {
+        label
+    }
+}
+
+/**
+ * 编译正则表达式模式（不区分大小写，编译后的大小不超过 REGEX_SIZE_LIMIT）
+ *
+ * 结果文件的模式列以逗号分隔、逐行记录，因此模式中不能有逗号和换行。
+ *
+ * @param pattern - 正则表达式
+ */
+fn compile_regex(pattern: &str) -> Result<CompiledRegex, String> {
+    if pattern.is_empty() {
+        return Err("正则表达式不能为空".to_string());
+    }
+    if let Some(c) = pattern.chars().find(|c| matches!(c, ',' | '\n' | '\r')) {
+        return Err(format!("正则表达式不能包含 {:?}（结果文件的模式列以逗号分隔、逐行记录）", c));
+    }
+    let regex = RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .dfa_size_limit(REGEX_SIZE_LIMIT)
+        .build()
+        .map_err(|e| match e {
+            regex::Error::CompiledTooBig(limit) => {
+                format!("正则表达式过于复杂：编译后超过 {} 字节的上限，请减少重复次数或分支", limit)
+            }
+            e => format!("正则表达式无效（{}）: {}", REGEX_FLAVOR, e),
+        })?;
+    let difficulty_bits = regex_difficulty_bits(&regex, pattern);
+    Ok(CompiledRegex { regex, difficulty_bits })
+}
+
+/**
+ * 估算正则表达式的难度：与固定序列的伪随机小写地址比较，按命中的比例换算
+ *
+ * 一个都没有命中时只能给出下限，按模式中的十六进制字面字符（每个 4 位）估计，且不低于抽样数本身对应的位数。
+ *
+ * @param regex - 编译后的正则表达式
+ * @param pattern - 正则表达式
+ */
+fn regex_difficulty_bits(regex: &Regex, pattern: &str) -> f64 {
+    // splitmix64：固定种子，同一个模式每次得到相同的估计
+    let mut state = 0x9e37_79b9_7f4a_7c15u64;
+    let mut next = || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    };
+    let mut bytes = [0u8; 24];
+    let hits = (0..REGEX_DIFFICULTY_SAMPLES)
+        .filter(|_| {
+            for chunk in bytes.chunks_mut(8) {
+                chunk.copy_from_slice(&next().to_le_bytes());
+            }
+            regex.is_match(&hex::encode(&bytes[..20]))
+        })
+        .count();
+    if hits > 0 {
+        return (REGEX_DIFFICULTY_SAMPLES as f64 / hits as f64).log2();
+    }
+    (4.0 * regex_literal_count(pattern) as f64).max((REGEX_DIFFICULTY_SAMPLES as f64).log2())
+}
+
+/**
+ * 正则表达式中的十六进制字面字符数（跳过转义序列和 {n,m} 中的数字）
+ */
+fn regex_literal_count(pattern: &str) -> usize {
+    let mut count = 0;
+    let (mut escaped, mut in_repeat) = (false, false);
+    for c in pattern.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '{' => in_repeat = true,
+            '}' => in_repeat = false,
+            c if !in_repeat && c.is_ascii_hexdigit() => count += 1,
+            _ => {}
+        }
+    }
+    count
+}
+
+/// 规范化的用户输入（见 normalize_input）
+struct PatternInput<'a> {
+    /// 用于匹配的搜索串
+    text: &'a str,
+    /// 是否为通配符模式（以 * 开头和结尾）
+    is_wildcard: bool,
+    /// 是否去掉了 0x 前缀
+    hex_prefix: bool,
+    /// 搜索串之前的字符数（首部的空白，加上开头的 * 或 0x 前缀）
+    offset: usize,
+}
+
+/**
+ * 规范化用户输入的模式：先去掉首尾空白（粘贴的模式常带有空格和换行），再识别 *...* 通配符，或者去掉地址的 0x/0X 前缀
+ *
+ * 中间的空白保留，由 validate 按无效字符报告位置。
+ *
+ * @param pattern - 用户输入的模式
+ */
+fn normalize_input(pattern: &str) -> PatternInput<'_> {
+    let leading = pattern.chars().take_while(|c| c.is_whitespace()).count();
+    let trimmed = pattern.trim();
+    if trimmed.starts_with('*') && trimmed.ends_with('*') && trimmed.len() > 2 {
+        return PatternInput { text: &trimmed[1..trimmed.len() - 1], is_wildcard: true, hex_prefix: false, offset: leading + 1 };
+    }
+    match trimmed.strip_prefix("0x").or_else(|| trimmed.strip_prefix("0X")) {
+        Some(text) => PatternInput { text, is_wildcard: false, hex_prefix: true, offset: leading + 2 },
+        None => PatternInput { text: trimmed, is_wildcard: false, hex_prefix: false, offset: leading },
+    }
+}
+
+/**
+ * 一段搜索串中不是十六进制字符也不是 ? 占位符的字符
+ *
+ * @param text - 搜索串
+ * @param offset - 在整个模式中的起始位置（字符数）
+ */
+fn invalid_characters(text: &str, offset: usize, alphabet: PatternAlphabet) -> impl Iterator<Item = InvalidCharacter> + '_ {
+    text.chars()
+        .enumerate()
+        .filter(move |&(_, c)| !alphabet.contains(c) && c != ANY_NIBBLE)
+        .map(move |(i, character)| InvalidCharacter { position: offset + i + 1, character })
+}
+
+/**
+ * 校验一段搜索串只包含字母表中的字符（可以包含 ? 占位符）
+ *
+ * @param text - 搜索串（十六进制和 Bech32 为小写，Base58 为用户输入）
+ * @param offset - 在整个模式中的起始位置（用于错误信息中的字符序号）
+ * @param alphabet - 模式可以使用的字符
+ */
+fn validate_characters(text: &str, offset: usize, alphabet: PatternAlphabet) -> Result<(), String> {
+    if let Some(InvalidCharacter { position, character }) = invalid_characters(text, offset, alphabet).next() {
+        return Err(format!("靓号模式第 {} 个字符 '{}' 不是{}或 {}", position, character, alphabet.description(), ANY_NIBBLE));
+    }
+    Ok(())
+}
+
+/**
+ * 包含模式的难度：模式可以出现在 positions 个位置中的任意一个
+ *
+ * 按各位置相互独立近似，概率为 1 - (1 - p)^positions；模式长于地址时不能出现，按单个位置计算以保持结果有限。
+ *
+ * @param one_side - 模式出现在某一个位置的难度（位）
+ * @param positions - 可以出现的位置数
+ */
+fn contains_bits(one_side: f64, positions: usize) -> f64 {
+    let p = (-one_side).exp2();
+    if positions <= 1 || p <= 0.0 {
+        return one_side;
+    }
+    let probability = -(positions as f64 * (-p).ln_1p()).exp_m1();
+    -probability.log2()
+}
+
+/**
+ * 搜索串在地址中第一次出现的位置（? 与任意一个字符相同）
+ *
+ * @param address - 地址（不含0x前缀）
+ * @param search - 搜索串
+ */
+fn find_at(address: &str, search: &str) -> Option<usize> {
+    let last = address.len().checked_sub(search.len())?;
+    (0..=last).find(|&start| matches_at(address, search, start))
+}
+
+/**
+ * 模式不能只由 ? 占位符组成，否则每个地址都匹配
+ */
+fn validate_not_only_placeholders(search: &str, alphabet: PatternAlphabet) -> Result<(), String> {
+    if search.contains(ANY_NIBBLE) && !search.chars().any(|c| alphabet.contains(c)) {
+        return Err(format!("靓号模式不能只由 {} 组成，至少需要一个{}", ANY_NIBBLE, alphabet.description()));
+    }
+    Ok(())
+}
+
+/**
+ * 地址从 start 开始的一段是否与搜索串相同（? 与任意一个字符相同；地址不够长时不相同）
+ *
+ * @param address - 地址（不含0x前缀）
+ * @param search - 搜索串
+ * @param start - 在地址中的起始位置
+ */
+fn matches_at(address: &str, search: &str, start: usize) -> bool {
+    address.as_bytes().get(start..start + search.len()).is_some_and(|window| {
+        window.iter().zip(search.bytes()).all(|(&a, s)| s == ANY_NIBBLE as u8 || a == s)
+    })
+}
+
+/**
+ * 只由字母 a–z 组成的搜索串是否为字母模板：至少有一个字母重复，且字母按 a、b、c… 的顺序第一次出现或含有十六进制以外的字母
+ *
+ * 十六进制的单词（例如 beef、cafe）不满足，仍按包含比较。
+ *
+ * @param search - 去掉 *...* 后的小写搜索串
+ */
+fn is_letter_template(search: &str) -> bool {
+    let bytes = search.as_bytes();
+    if !bytes.iter().all(u8::is_ascii_lowercase) {
+        return false;
+    }
+    let repeated = bytes.iter().enumerate().any(|(i, c)| bytes[..i].contains(c));
+    let mut next = b'a';
+    let ordered = bytes.iter().all(|&c| match c.cmp(&next) {
+        std::cmp::Ordering::Equal => {
+            next += 1;
+            true
+        }
+        order => order == std::cmp::Ordering::Less,
+    });
+    repeated && (ordered || bytes.iter().any(|c| !c.is_ascii_hexdigit()))
+}
+
+/**
+ * 一段字符是否满足字母模板：模板中相同的字母对应相同的字符，不同的字母对应不同的字符
+ *
+ * @param window - 地址中与模板等长的一段（按字节比较，不分配内存）
+ * @param template - 字母模板
+ */
+fn fits_template(window: &[u8], template: &[u8]) -> bool {
+    window.len() == template.len()
+        && (0..window.len()).all(|i| (0..i).all(|j| (template[i] == template[j]) == (window[i] == window[j])))
+}
+
+/**
+ * 一侧满足字母模板的概率：依次为每个不同的字母选一个还没有用过的字符，按数字和字母两类字符分别计数
+ *
+ * @param template - 字母模板
+ * @param (digit_share, digit, letter) - 字母表的概率（见 PatternAlphabet::probabilities）
+ */
+fn template_probability(template: &str, (digit_share, digit, letter): (f64, f64, f64)) -> f64 {
+    let (digits, letters) = (digit_share / digit, (1.0 - digit_share) / letter);
+    let mut counts: Vec<(u8, i32)> = Vec::new();
+    for c in template.bytes() {
+        match counts.iter_mut().find(|(letter, _)| *letter == c) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((c, 1)),
+        }
+    }
+    // ways[j]：已经选了 j 个数字（其余为字母）时的概率之和
+    let mut ways = vec![1.0];
+    for (used, &(_, count)) in counts.iter().enumerate() {
+        let mut next = vec![0.0; ways.len() + 1];
+        for (j, &w) in ways.iter().enumerate() {
+            next[j + 1] += w * (digits - j as f64).max(0.0) * digit.powi(count);
+            next[j] += w * (letters - (used - j) as f64).max(0.0) * letter.powi(count);
+        }
+        ways = next;
+    }
+    ways.iter().sum()
+}
+
+impl ParsedPattern {
+    /**
+     * 解析靓号模式字符串（不区分大小写，只比较十六进制字符）
+     * 
+     * @param pattern - 靓号模式字符串
+     * @returns 解析结果
+     */
+    pub fn new(pattern: &str) -> Self {
+        Self::with_case(pattern, false)
+    }
+    
+    /**
+     * 按模式类型解析模式字符串：正则表达式在这里编译一次，之后每次比较直接使用编译结果
+     * 
+     * leading_zeros 类型的模式字符串为 zeros>=N（也接受单独的 N），N 为地址开头最少的零的个数。
+     * caps_prefix 类型的模式字符串为 caps>=N（也接受单独的 N）或 lower>=N，总是与 checksum 地址比较。
+     * char_count 类型的模式字符串为 count(<字符>)>=N，字符为 * 时任意一个字符出现 N 次即可。
+     * 
+     * @param pattern - 模式字符串
+     * @param pattern_type - 模式类型
+     * @param case_sensitive - 是否按用户输入的大小写与 checksum 地址比较（正则表达式不支持）
+     * @returns 解析结果
+     */
+    pub fn with_options(pattern: &str, pattern_type: PatternType, case_sensitive: bool) -> Self {
+        match pattern_type {
+            PatternType::Standard => Self::with_case(pattern, case_sensitive),
+            PatternType::Regex => ParsedPattern {
+                is_wildcard: false,
+                search: pattern.to_lowercase(),
+                search_cased: pattern.to_string(),
+                split: None,
+                case_sensitive,
+                regex: Some(compile_regex(pattern)),
+                offset: 0,
+                hex_prefix: false,
+                input: pattern.to_string(),
+                alphabet: PatternAlphabet::Hex,
+                requested_mode: None,
+                min_zero_nibbles: None,
+                case_prefix: None,
+                char_threshold: None,
+                letter_template: false,
+            },
+            PatternType::LeadingZeros => {
+                let count = pattern.trim();
+                let count = count.strip_prefix(LEADING_ZEROS_PREFIX).unwrap_or(count);
+                ParsedPattern {
+                    is_wildcard: false,
+                    search: pattern.to_lowercase(),
+                    search_cased: pattern.to_string(),
+                    split: None,
+                    case_sensitive,
+                    regex: None,
+                    offset: 0,
+                    hex_prefix: false,
+                    input: pattern.to_string(),
+                    alphabet: PatternAlphabet::Hex,
+                    requested_mode: None,
+                    min_zero_nibbles: Some(count.parse().unwrap_or(0)),
+                    case_prefix: None,
+                    char_threshold: None,
+                    letter_template: false,
+                }
+            }
+            PatternType::CapsPrefix => {
+                let text = pattern.trim();
+                let (length, lowercase) = match text.strip_prefix(LOWER_PREFIX) {
+                    Some(length) => (length, true),
+                    None => (text.strip_prefix(CAPS_PREFIX).unwrap_or(text), false),
+                };
+                // 只看 checksum 地址的大小写，总是按区分大小写比较
+                ParsedPattern {
+                    is_wildcard: false,
+                    search: pattern.to_lowercase(),
+                    search_cased: pattern.to_string(),
+                    split: None,
+                    case_sensitive: true,
+                    regex: None,
+                    offset: 0,
+                    hex_prefix: false,
+                    input: pattern.to_string(),
+                    alphabet: PatternAlphabet::Hex,
+                    requested_mode: None,
+                    min_zero_nibbles: None,
+                    case_prefix: Some(CasePrefix { length: length.parse().unwrap_or(0), lowercase }),
+                    char_threshold: None,
+                    letter_template: false,
+                }
+            }
+            PatternType::CharCount => {
+                let text = pattern.trim();
+                let (character, min_count) = text.strip_prefix(CHAR_COUNT_PREFIX).and_then(|rest| rest.split_once(CHAR_COUNT_SEPARATOR)).unwrap_or(("", ""));
+                let mut chars = character.chars();
+                let character = match (chars.next(), chars.next()) {
+                    (Some(ANY_CHARACTER), None) => None,
+                    (Some(c), None) => Some(c.to_ascii_lowercase()),
+                    _ => Some(char::REPLACEMENT_CHARACTER),
+                };
+                // 在小写地址中计数，与大小写无关
+                ParsedPattern {
+                    is_wildcard: false,
+                    search: pattern.to_lowercase(),
+                    search_cased: pattern.to_string(),
+                    split: None,
+                    case_sensitive,
+                    regex: None,
+                    offset: 0,
+                    hex_prefix: false,
+                    input: pattern.to_string(),
+                    alphabet: PatternAlphabet::Hex,
+                    requested_mode: None,
+                    min_zero_nibbles: None,
+                    case_prefix: None,
+                    char_threshold: Some(CharThreshold { character, min_count: min_count.parse().unwrap_or(0) }),
+                    letter_template: false,
+                }
+            }
+        }
+    }
+    
+    /**
+     * 解析靓号模式字符串
+     * 
+     * EIP-55 的大小写由完整地址的哈希决定，不能由模式自身算出：不区分大小写时小写的模式直接与小写地址比较；
+     * 区分大小写时按用户输入的大小写逐字符与 checksum 地址比较（小写字母要求该位在 checksum 地址中为小写）。
+     * 
+     * @param pattern - 靓号模式字符串
+     * @param case_sensitive - 是否按用户输入的大小写与 checksum 地址比较
+     * @returns 解析结果
+     */
+    pub fn with_case(pattern: &str, case_sensitive: bool) -> Self {
+        let PatternInput { text, is_wildcard, hex_prefix, offset } = normalize_input(pattern);
+        let search = text.to_lowercase();
+        let search_cased = if case_sensitive { text.to_string() } else { search.clone() };
+        let split = match search_cased.split_once(PREFIX_SUFFIX_SEPARATOR) {
+            Some((prefix, suffix)) if !is_wildcard => Some((prefix.to_string(), suffix.to_string())),
+            _ => None,
+        };
+        
+        let letter_template = is_wildcard && is_letter_template(&search);
+        ParsedPattern {
+            is_wildcard,
+            search,
+            search_cased,
+            split,
+            case_sensitive,
+            regex: None,
+            offset,
+            hex_prefix,
+            input: text.to_string(),
+            alphabet: PatternAlphabet::Hex,
+            requested_mode: None,
+            min_zero_nibbles: None,
+            case_prefix: None,
+            char_threshold: None,
+            letter_template,
+        }
+    }
+    
+    /**
+     * 指定模式可以使用的字符（由地址所属的链决定，默认十六进制）
+     * 
+     * @param alphabet - 字母表
+     */
+    pub fn with_alphabet(mut self, alphabet: PatternAlphabet) -> Self {
+        self.alphabet = alphabet;
+        self
+    }
+    
+    /**
+     * 按字母表校验的各段搜索串及其在用户输入中的起始位置（<前缀>/<后缀> 形式为两段）
+     * 
+     * 十六进制和 Bech32 校验小写的搜索串；Base58 区分大小写（例如 O 无效而 o 有效），校验用户输入。
+     */
+    fn checked_segments(&self) -> Vec<(String, usize)> {
+        let text = match self.alphabet {
+            PatternAlphabet::Hex | PatternAlphabet::Bech32 => &self.search,
+            PatternAlphabet::Base58 | PatternAlphabet::Base58PublicKey => &self.input,
+        };
+        match text.split_once(PREFIX_SUFFIX_SEPARATOR).filter(|_| self.split.is_some()) {
+            Some((prefix, suffix)) => vec![(prefix.to_string(), self.offset), (suffix.to_string(), self.offset + prefix.chars().count() + 1)],
+            None => vec![(text.clone(), self.offset)],
+        }
+    }
+    
+    /**
+     * 显式指定比较方式（为空时按模式语法决定：普通模式为 both，*...* 形式为 contains）
+     * 
+     * 显式指定 contains 时，*aaaa* 等重复形式也按字面的包含比较。
+     * 
+     * @param mode - 比较方式
+     */
+    pub fn with_match_mode(mut self, mode: Option<MatchMode>) -> Self {
+        self.requested_mode = mode;
+        self
+    }
+    
+    /**
+     * *...* 形式的字母模板（例如 *aaaa*、*aabb*、*abcabc*）；显式指定 contains 时按字面的包含比较，返回空
+     */
+    fn template(&self) -> Option<&str> {
+        let literal = self.requested_mode == Some(MatchMode::Contains);
+        (self.letter_template && !literal).then_some(self.search.as_str())
+    }
+    
+    /**
+     * 实际使用的比较方式（<前缀>/<后缀> 形式、正则表达式、leading_zeros、caps_prefix 和 char_count 各自比较，总是返回 both）
+     */
+    pub fn match_mode(&self) -> MatchMode {
+        match self.requested_mode {
+            Some(mode) if self.split.is_none() && self.regex.is_none() && self.min_zero_nibbles.is_none() && self.case_prefix.is_none() && self.char_threshold.is_none() => mode,
+            _ if self.is_wildcard && self.template().is_none() => MatchMode::Contains,
+            _ => MatchMode::Both,
+        }
+    }
+    
+    /**
+     * 严格校验模式：不能为空、只能包含字母表中的字符（默认十六进制）、长度不超过地址长度
+     *
+     * 分别指定前缀和后缀时两者不能同时为空，总长度不超过地址长度。? 占位符不能用于 *...* 形式，
+     * 模式中至少要有一个十六进制字符。字母模板不按字母表校验，不同的字母不能多于地址中的字符种类。显式指定的比较方式不能与模式语法矛盾。正则表达式必须能够编译，且只能与小写地址比较（不能区分大小写）。
+     * leading_zeros 要求的零的个数、caps_prefix 的字符数和 char_count 的个数在 1 到 40 之间，char_count 的字符必须是十六进制字符。Base58、Bech32 和 Solana 地址只支持普通模式，模式不能带 0x 前缀；
+     * Bech32 地址只有小写字母，不能区分大小写，模式从固定的 bc1q 之后开始。
+     */
+    pub fn validate(&self) -> Result<(), String> {
+        if self.alphabet != PatternAlphabet::Hex {
+            if self.regex.is_some() || self.min_zero_nibbles.is_some() || self.case_prefix.is_some() || self.char_threshold.is_some() {
+                return Err(format!("{} 地址只支持普通模式（pattern_type: standard）", self.alphabet.name()));
+            }
+            if self.hex_prefix {
+                return Err(format!("{} 地址没有 0x 前缀，模式不能以 0x 开头", self.alphabet.name()));
+            }
+        }
+        if self.alphabet == PatternAlphabet::Bech32 {
+            if self.case_sensitive {
+                return Err("Bech32 地址只有小写字母，不能使用 case_sensitive".to_string());
+            }
+            if self.search.starts_with(BECH32_HRP_PREFIX) {
+                return Err("Bech32 地址固定以 bc1q 开头，模式从 bc1q 之后的字符开始".to_string());
+            }
+        }
+        match self.requested_mode {
+            Some(_) if self.regex.is_some() => {
+                return Err("正则表达式模式不能指定 match_mode（用 ^ 和 $ 限制前缀和后缀）".to_string());
+            }
+            Some(_) if self.min_zero_nibbles.is_some() => {
+                return Err("leading_zeros 模式总是比较地址的开头，不能指定 match_mode".to_string());
+            }
+            Some(_) if self.case_prefix.is_some() => {
+                return Err("caps_prefix 模式总是比较地址的开头，不能指定 match_mode".to_string());
+            }
+            Some(_) if self.char_threshold.is_some() => {
+                return Err("char_count 模式统计整个地址中的字符，不能指定 match_mode".to_string());
+            }
+            Some(mode) if self.split.is_some() && mode != MatchMode::Both => {
+                return Err("<前缀>/<后缀> 形式已经分别指定了前缀和后缀，match_mode 只能为 both".to_string());
+            }
+            Some(mode) if self.is_wildcard && self.template().is_none() && mode != MatchMode::Contains => {
+                return Err("*...* 形式表示包含，match_mode 只能为 contains（字母模板还可以为 prefix、suffix 或 both）".to_string());
+            }
+            _ => {}
+        }
+        if let Some(min) = self.min_zero_nibbles {
+            if min == 0 || usize::from(min) > ADDRESS_LENGTH {
+                return Err(format!("min_zero_nibbles 必须在 1 到 {} 之间（模式为 {}）", ADDRESS_LENGTH, self.search_cased));
+            }
+            return Ok(());
+        }
+        if let Some(CasePrefix { length, .. }) = self.case_prefix {
+            if length == 0 || usize::from(length) > ADDRESS_LENGTH {
+                return Err(format!("caps_prefix 必须在 1 到 {} 之间（模式为 {}）", ADDRESS_LENGTH, self.search_cased));
+            }
+            return Ok(());
+        }
+        if let Some(CharThreshold { character, min_count }) = self.char_threshold {
+            if character.is_some_and(|c| !c.is_ascii_hexdigit()) {
+                return Err(format!("char_count 的字符必须是一个十六进制字符（模式为 {}）", self.search_cased));
+            }
+            if min_count == 0 || usize::from(min_count) > ADDRESS_LENGTH {
+                return Err(format!("min_count 必须在 1 到 {} 之间（模式为 {}）", ADDRESS_LENGTH, self.search_cased));
+            }
+            // 40 个字符只有 16 种，总有一个字符至少出现 3 次
+            let always = ADDRESS_LENGTH.div_ceil(16);
+            if character.is_none() && usize::from(min_count) <= always {
+                return Err(format!("任意一个字符至少出现 {} 次时每个地址都满足，min_count 必须大于 {}", min_count, always));
+            }
+            return Ok(());
+        }
+        if let Some(compiled) = &self.regex {
+            if self.case_sensitive {
+                return Err("正则表达式模式与小写地址比较，不能与 case_sensitive 同时使用".to_string());
+            }
+            return compiled.as_ref().map(|_| ()).map_err(Clone::clone);
+        }
+        if let Some((prefix, suffix)) = &self.split {
+            if prefix.is_empty() && suffix.is_empty() {
+                return Err("前缀和后缀不能同时为空".to_string());
+            }
+            if prefix.len() + suffix.len() > self.alphabet.address_length() {
+                return Err(format!("前缀和后缀的总长度不能超过 {} 个字符", self.alphabet.address_length()));
+            }
+            for (text, offset) in self.checked_segments() {
+                validate_characters(&text, offset, self.alphabet)?;
+            }
+            return validate_not_only_placeholders(&self.input, self.alphabet);
+        }
+        if self.search.is_empty() && self.offset > 0 {
+            return Err("去掉首尾空白和 0x 前缀后靓号模式为空".to_string());
+        }
+        if self.search.is_empty() {
+            return Err("靓号模式不能为空".to_string());
+        }
+        if self.search.len() > self.alphabet.address_length() {
+            return Err(format!("靓号模式长度不能超过 {} 个字符", self.alphabet.address_length()));
+        }
+        if let Some(template) = self.template() {
+            // 模板中的字母只表示相同和不同，不按字母表校验
+            let distinct = template.bytes().enumerate().filter(|&(i, c)| !template.as_bytes()[..i].contains(&c)).count();
+            let (digit_share, digit, letter) = self.alphabet.probabilities(self.case_sensitive);
+            let symbols = (digit_share / digit + (1.0 - digit_share) / letter).floor() as usize;
+            if distinct > symbols {
+                return Err(format!("字母模板有 {} 个不同的字母，{}地址只有 {} 种字符", distinct, self.alphabet.name(), symbols));
+            }
+            return Ok(());
+        }
+        if self.is_wildcard && self.search.contains(ANY_NIBBLE) {
+            return Err(format!("{} 占位符不能与 *...* 通配符模式同时使用", ANY_NIBBLE));
+        }
+        for (text, offset) in self.checked_segments() {
+            validate_characters(&text, offset, self.alphabet)?;
+        }
+        validate_not_only_placeholders(&self.input, self.alphabet)
+    }
+    
+    /**
+     * 开始前的预检：校验结果（与 validate 相同）、规范化的模式、匹配规则、全部无效的字符和难度等级
+     */
+    #[cfg(feature = "gui")]
+    pub fn check(&self) -> PatternValidation {
+        let error = self.validate().err();
+        let difficulty_bits = self.difficulty_bits();
+        PatternValidation {
+            valid: error.is_none(),
+            error,
+            normalized: self.normalized(),
+            mode: self.rule(),
+            invalid_characters: self.invalid_characters(),
+            difficulty_bits,
+            difficulty_class: DifficultyClass::from_bits(difficulty_bits),
+            warnings: self.warnings(),
+        }
+    }
+    
+    /**
+     * 预检的提示：区分大小写的前后缀或通配符模式中没有字母时，大小写不影响匹配和难度
+     * 
+     * Solana 地址总是区分大小写，不是用户的选择，不提示。
+     */
+    #[cfg(feature = "gui")]
+    fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let standard = self.regex.is_none() && self.min_zero_nibbles.is_none() && self.case_prefix.is_none() && self.char_threshold.is_none();
+        let letters = self.input.chars().any(|c| c.is_ascii_alphabetic());
+        if self.case_sensitive && standard && self.template().is_none() && self.alphabet != PatternAlphabet::Base58PublicKey && !self.search.is_empty() && !letters {
+            warnings.push(format!("模式 {} 中只有数字，数字没有大小写，区分大小写（case_mode: exact）不影响匹配和难度", self.normalized()));
+        }
+        warnings
+    }
+    
+    /**
+     * 规范化的模式：去掉首尾空白和 0x 前缀，不区分大小写时为小写，保留 *...* 和 <前缀>/<后缀> 形式
+     */
+    #[cfg(feature = "gui")]
+    pub fn normalized(&self) -> String {
+        if let Some(min) = self.min_zero_nibbles {
+            return format!("{}{}", LEADING_ZEROS_PREFIX, min);
+        }
+        if let Some(CasePrefix { length, lowercase }) = self.case_prefix {
+            return caps_prefix_pattern(length, lowercase);
+        }
+        if let Some(CharThreshold { character, min_count }) = self.char_threshold {
+            return char_count_pattern(character, min_count);
+        }
+        if self.is_wildcard {
+            return format!("*{}*", self.search_cased);
+        }
+        self.search_cased.clone()
+    }
+    
+    /**
+     * 前后缀和通配符模式中全部无效的字符（正则表达式、leading_zeros、caps_prefix、char_count 模式和字母模板为空）
+     */
+    #[cfg(feature = "gui")]
+    pub fn invalid_characters(&self) -> Vec<InvalidCharacter> {
+        if self.regex.is_some() || self.min_zero_nibbles.is_some() || self.case_prefix.is_some() || self.char_threshold.is_some() || self.template().is_some() {
+            return Vec::new();
+        }
+        self.checked_segments()
+            .iter()
+            .flat_map(|(text, offset)| invalid_characters(text, *offset, self.alphabet).collect::<Vec<_>>())
+            .collect()
+    }
+    
+    /**
+     * 解析器对模式的解读（用于帮助信息和界面提示）
+     */
+    pub fn interpretation(&self) -> String {
+        let mut case = if self.case_sensitive { "区分大小写" } else { "不区分大小写" }.to_string();
+        if self.search.contains(ANY_NIBBLE) {
+            case.push_str(&format!("，{} 表示任意一个{}", ANY_NIBBLE, self.alphabet.description()));
+        }
+        if self.regex.is_some() {
+            return format!("小写地址满足正则表达式 {}", self.search_cased);
+        }
+        if let Some(min) = self.min_zero_nibbles {
+            return format!("地址开头至少有 {} 个 0（{} 个完整的零字节）", min, min / 2);
+        }
+        if let Some(CasePrefix { length, lowercase }) = self.case_prefix {
+            return format!("checksum 地址开头 {} 个字符中的字母都是{}（数字不限）", length, if lowercase { "小写" } else { "大写" });
+        }
+        if let Some(CharThreshold { character, min_count }) = self.char_threshold {
+            return match character {
+                Some(character) => format!("地址中至少有 {} 个 {}（位置不限，不区分大小写）", min_count, character),
+                None => format!("地址中有一个字符至少出现 {} 次（位置不限，不区分大小写）", min_count),
+            };
+        }
+        if let Some((prefix, suffix)) = &self.split {
+            return match (prefix.is_empty(), suffix.is_empty()) {
+                (false, true) => format!("前缀为 {}，后缀不限（{}）", prefix, case),
+                (true, false) => format!("前缀不限，后缀为 {}（{}）", suffix, case),
+                _ => format!("前缀为 {}，后缀为 {}（{}）", prefix, suffix, case),
+            };
+        }
+        if let Some(template) = self.template() {
+            let n = template.len();
+            let sides = match self.match_mode() {
+                MatchMode::Prefix => format!("前 {} 位", n),
+                MatchMode::Suffix => format!("后 {} 位", n),
+                MatchMode::Both | MatchMode::Contains => format!("前 {} 位和后 {} 位均", n, n),
+            };
+            return match template {
+                "aaaa" if self.case_sensitive && n == 4 && self.match_mode() == MatchMode::Both => "前 4 位为同一字符，后 4 位为同一字符".to_string(),
+                _ if self.case_sensitive => format!("{}为 {} 形式", sides, template),
+                _ => format!("{}为 {} 形式（{}）", sides, template, case),
+            };
+        }
+        match self.match_mode() {
+            MatchMode::Prefix => return format!("前缀为 {}，后缀不限（{}）", self.search_cased, case),
+            MatchMode::Suffix => return format!("前缀不限，后缀为 {}（{}）", self.search_cased, case),
+            MatchMode::Contains => return format!("地址的任意位置包含 {}（{}）", self.search_cased, case),
+            MatchMode::Both => {}
+        }
+        format!("前缀和后缀均为 {}（{}）", self.search_cased, case)
+    }
+    
+    /**
+     * 引擎实际执行的匹配条件
+     */
+    #[cfg(feature = "gui")]
+    pub fn matcher(&self) -> Matcher {
+        let case_sensitive = self.case_sensitive;
+        if self.regex.is_some() {
+            return Matcher::Regex { pattern: self.search_cased.clone() };
+        }
+        if let Some(min_zero_nibbles) = self.min_zero_nibbles {
+            return Matcher::LeadingZeros { min_zero_nibbles };
+        }
+        if let Some(CasePrefix { length, lowercase }) = self.case_prefix {
+            return Matcher::CapsPrefix { length, lowercase };
+        }
+        if let Some(CharThreshold { character, min_count }) = self.char_threshold {
+            return Matcher::CharCount { character, min_count };
+        }
+        if let Some((prefix, suffix)) = &self.split {
+            return Matcher::Affixes { prefix: prefix.clone(), suffix: suffix.clone(), case_sensitive };
+        }
+        if let Some(template) = self.template() {
+            return Matcher::Repeat { shape: template.to_string(), sides: self.match_mode(), case_sensitive };
+        }
+        let text = self.search_cased.clone();
+        match self.match_mode() {
+            MatchMode::Prefix => Matcher::Affixes { prefix: text, suffix: String::new(), case_sensitive },
+            MatchMode::Suffix => Matcher::Affixes { prefix: String::new(), suffix: text, case_sensitive },
+            MatchMode::Contains => Matcher::Contains { text, case_sensitive },
+            MatchMode::Both => Matcher::Affixes { prefix: text.clone(), suffix: text, case_sensitive },
+        }
+    }
+    
+    /**
+     * 按字节比较的前后缀：十六进制的前缀、后缀和前后缀模式（包括 <前缀>/<后缀> 形式）才有
+     * 
+     * 地址不满足它时一定不满足 matches；满足时不区分大小写的模式一定满足 matches，区分大小写的模式还要与 checksum 地址比较。
+     * 包含、重复形式、正则表达式、leading_zeros、caps_prefix 和 char_count 模式返回空，只能按字符串比较。
+     */
+    pub fn nibble_affixes(&self) -> Option<NibbleAffixes> {
+        if self.alphabet != PatternAlphabet::Hex || self.regex.is_some() || self.min_zero_nibbles.is_some() || self.case_prefix.is_some() || self.char_threshold.is_some() || self.template().is_some() {
+            return None;
+        }
+        if let Some((prefix, suffix)) = &self.split {
+            return Some(NibbleAffixes { prefix: nibbles(prefix)?, suffix: nibbles(suffix)?, disjoint: true });
+        }
+        let search = nibbles(&self.search_cased)?;
+        let (prefix, suffix) = match self.match_mode() {
+            MatchMode::Prefix => (search, Vec::new()),
+            MatchMode::Suffix => (Vec::new(), search),
+            MatchMode::Both if !self.is_wildcard => (search.clone(), search),
+            MatchMode::Both | MatchMode::Contains => return None,
+        };
+        Some(NibbleAffixes { prefix, suffix, disjoint: false })
+    }
+    
+    /**
+     * 检查 checksum 格式的地址是否符合靓号条件
+     * 
+     * 不区分大小写时地址可以是任意大小写（生成循环直接传入小写地址，不计算 checksum）。
+     * 
+     * @param address_checksum - checksum 格式的地址（不含0x前缀）
+     * @returns 是否匹配
+     */
+    pub fn matches(&self, address_checksum: &str) -> bool {
+        if let Some(min) = self.min_zero_nibbles {
+            return leading_zero_nibbles(address_checksum) >= usize::from(min);
+        }
+        if let Some(CasePrefix { length, lowercase }) = self.case_prefix {
+            let head = address_checksum.as_bytes().get(..usize::from(length));
+            return head.is_some_and(|head| head.iter().all(|c| if lowercase { !c.is_ascii_uppercase() } else { !c.is_ascii_lowercase() }));
+        }
+        if let Some(CharThreshold { character, min_count }) = self.char_threshold {
+            return count_characters(address_checksum, character).count >= usize::from(min_count);
+        }
+        if !self.case_sensitive && address_checksum.bytes().any(|b| b.is_ascii_uppercase()) {
+            return self.matches(&address_checksum.to_ascii_lowercase());
+        }
+        if let Some(compiled) = &self.regex {
+            return compiled.as_ref().is_ok_and(|compiled| compiled.regex.is_match(address_checksum));
+        }
+        if let Some((prefix, suffix)) = &self.split {
+            // 分别指定前缀和后缀：两侧各自比较，为空的一侧总是满足
+            let len = address_checksum.len();
+            return len >= prefix.len() + suffix.len()
+                && matches_at(address_checksum, prefix, 0)
+                && matches_at(address_checksum, suffix, len - suffix.len());
+        }
+        if let Some(template) = self.template() {
+            let (bytes, n) = (address_checksum.as_bytes(), template.len());
+            if bytes.len() < n {
+                return false;
+            }
+            let (prefix, suffix) = (fits_template(&bytes[..n], template.as_bytes()), fits_template(&bytes[bytes.len() - n..], template.as_bytes()));
+            return match self.match_mode() {
+                MatchMode::Prefix => prefix,
+                MatchMode::Suffix => suffix,
+                MatchMode::Both | MatchMode::Contains => prefix && suffix,
+            };
+        }
+        let search = self.search_cased.as_str();
+        match self.match_mode() {
+            MatchMode::Prefix => return matches_at(address_checksum, search, 0),
+            MatchMode::Suffix => {
+                return address_checksum.len() >= search.len() && matches_at(address_checksum, search, address_checksum.len() - search.len());
+            }
+            MatchMode::Contains => return find_at(address_checksum, search).is_some(),
+            MatchMode::Both => {}
+        }
+        if self.is_wildcard {
+            // 其他通配符模式：地址的任意位置包含该模式
+            find_at(address_checksum, search).is_some()
+        } else {
+            // 普通模式：前后缀都需要匹配（同时匹配），? 与该位置上任意一个字符相同
+            address_checksum.len() >= search.len()
+                && matches_at(address_checksum, search, 0)
+                && matches_at(address_checksum, search, address_checksum.len() - search.len())
+        }
+    }
+    
+    /**
+     * 模式使用的匹配规则
+     */
+    pub fn rule(&self) -> MatchRule {
+        if self.regex.is_some() {
+            return MatchRule::Regex;
+        }
+        if self.min_zero_nibbles.is_some() {
+            return MatchRule::LeadingZeros;
+        }
+        if self.case_prefix.is_some() {
+            return MatchRule::CapsPrefix;
+        }
+        if self.char_threshold.is_some() {
+            return MatchRule::CharCount;
+        }
+        if self.split.is_some() {
+            return MatchRule::PrefixAndSuffix;
+        }
+        match self.requested_mode {
+            Some(MatchMode::Prefix) => return MatchRule::Prefix,
+            Some(MatchMode::Suffix) => return MatchRule::Suffix,
+            Some(MatchMode::Contains) if !self.is_wildcard => return MatchRule::Contains,
+            Some(MatchMode::Contains) => return MatchRule::Wildcard,
+            _ => {}
+        }
+        match (self.is_wildcard, self.search.as_str()) {
+            (true, "aaaa") => MatchRule::RepeatAaaa,
+            (true, "aabb") => MatchRule::RepeatAabb,
+            (true, "abab") => MatchRule::RepeatAbab,
+            (true, _) if self.template().is_some() => MatchRule::Template,
+            (true, _) => MatchRule::Wildcard,
+            (false, _) => MatchRule::PrefixSuffix,
+        }
+    }
+    
+    /**
+     * 检查地址是否符合靓号条件，并返回满足条件的字符区间
+     * 
+     * 前缀区间在前、后缀区间在后；模式长于地址的一半时两个区间会重叠，按原样返回。包含模式返回第一次出现的位置。
+     * 正则表达式返回最左边的一处匹配（匹配为空串时不返回区间），leading_zeros 返回开头全部的 0，caps_prefix 返回开头的 N 个字符，char_count 返回计数的字符出现的每个位置。
+     * 
+     * @param address_checksum - checksum 格式的地址（不含0x前缀）
+     * @returns 匹配时返回区间，不匹配时返回 None
+     */
+    pub fn matched_spans(&self, address_checksum: &str) -> Option<Vec<MatchedSpan>> {
+        if !self.matches(address_checksum) {
+            return None;
+        }
+        let rule = self.rule();
+        let len = address_checksum.len();
+        if let Some(Ok(compiled)) = &self.regex {
+            let lowercase = address_checksum.to_ascii_lowercase();
+            let found = compiled.regex.find(&lowercase).filter(|found| !found.is_empty());
+            return Some(found.map(|found| MatchedSpan { start: found.start(), end: found.end(), rule }).into_iter().collect());
+        }
+        if self.min_zero_nibbles.is_some() {
+            return Some(vec![MatchedSpan { start: 0, end: leading_zero_nibbles(address_checksum), rule }]);
+        }
+        if let Some(CasePrefix { length, .. }) = self.case_prefix {
+            return Some(vec![MatchedSpan { start: 0, end: usize::from(length), rule }]);
+        }
+        if let Some(CharThreshold { character, .. }) = self.char_threshold {
+            let counted = count_characters(address_checksum, character).character;
+            let positions = address_checksum.char_indices().filter(|(_, c)| c.eq_ignore_ascii_case(&counted));
+            return Some(positions.map(|(start, _)| MatchedSpan { start, end: start + 1, rule }).collect());
+        }
+        if let Some((prefix, suffix)) = &self.split {
+            // 只返回指定了的一侧
+            let spans = [(0, prefix.len()), (len - suffix.len(), len)];
+            return Some(spans.into_iter().filter(|(start, end)| start < end).map(|(start, end)| MatchedSpan { start, end, rule }).collect());
+        }
+        let width = self.template().map_or(self.search_cased.len(), str::len);
+        let starts = match self.match_mode() {
+            MatchMode::Prefix => vec![0],
+            MatchMode::Suffix => vec![len - width],
+            MatchMode::Contains => {
+                let address = if self.case_sensitive { address_checksum.to_string() } else { address_checksum.to_ascii_lowercase() };
+                find_at(&address, &self.search_cased).into_iter().collect()
+            }
+            MatchMode::Both => vec![0, len - width],
+        };
+        Some(starts.into_iter().map(|start| MatchedSpan { start, end: start + width, rule }).collect())
+    }
+    
+    /**
+     * 难度：找到一个匹配地址的期望尝试次数的以 2 为底的对数
+     * 
+     * 在对数域中按字符累加，任何长度的模式（包括超长的无效输入）都得到有限的结果；
+     * 概率和期望尝试次数都由它换算，避免连乘大量小概率时下溢。正则表达式使用编译时抽样估算的结果。
+     */
+    pub fn difficulty_bits(&self) -> f64 {
+        match &self.regex {
+            Some(Ok(compiled)) => return compiled.difficulty_bits,
+            Some(Err(_)) => return 4.0 * regex_literal_count(&self.search_cased) as f64,
+            None => {}
+        }
+        if let Some(min) = self.min_zero_nibbles {
+            // 每个位置是 0 的概率为 1/16，与大小写无关
+            return -DIGIT_PROBABILITY.log2() * f64::from(min);
+        }
+        if let Some(CasePrefix { length, .. }) = self.case_prefix {
+            // 数字总是满足，字母的大小写由地址的哈希决定，大写和小写各占一半
+            return -(1.0 - LETTER_SHARE / 2.0).log2() * f64::from(length);
+        }
+        if let Some(CharThreshold { character, min_count }) = self.char_threshold {
+            let probability = match character {
+                Some(_) => char_count_tail(usize::from(min_count)),
+                None => any_char_count_probability(usize::from(min_count)),
+            };
+            return -probability.log2();
+        }
+        let (digit_share, digit, letter) = self.alphabet.probabilities(self.case_sensitive);
+        let char_bits = |c: char| {
+            if c == ANY_NIBBLE {
+                0.0
+            } else if c.is_ascii_digit() {
+                -digit.log2()
+            } else {
+                -letter.log2()
+            }
+        };
+        if let Some((prefix, suffix)) = &self.split {
+            // 前缀和后缀各自计算，不重复计入
+            return prefix.chars().chain(suffix.chars()).map(char_bits).sum();
+        }
+        
+        let mode = self.match_mode();
+        let template_bits = self.template().map(|template| -template_probability(template, (digit_share, digit, letter)).log2());
+        // 不同的字母多于字母表中的字符时模板不可能满足（validate 返回错误），按字符计算以保持结果有限
+        let one_side = match template_bits {
+            Some(bits) if bits.is_finite() => bits,
+            _ => self.search_cased.chars().map(char_bits).sum(),
+        };
+        
+        match mode {
+            // 前缀和后缀需要同时满足
+            MatchMode::Both => 2.0 * one_side,
+            MatchMode::Prefix | MatchMode::Suffix => one_side,
+            MatchMode::Contains => contains_bits(one_side, self.alphabet.address_length().saturating_sub(self.search.len()) + 1),
+        }
+    }
+    
+    /**
+     * leading_zeros 模式下地址开头实际的零的个数（其它模式为空）
+     * 
+     * @param address - 地址（不含0x前缀）
+     */
+    pub fn achieved_zero_nibbles(&self, address: &str) -> Option<usize> {
+        self.min_zero_nibbles.map(|_| leading_zero_nibbles(address))
+    }
+    
+    /**
+     * char_count 模式下地址中实际计数的字符和个数（不限定字符时为出现次数最多的字符；其它模式为空）
+     * 
+     * @param address - 地址（不含0x前缀）
+     */
+    pub fn achieved_char_count(&self, address: &str) -> Option<CharCount> {
+        self.char_threshold.map(|threshold| count_characters(address, threshold.character))
+    }
+    
+    /**
+     * 计算单个随机地址满足该模式的概率（难度过高时下溢为 0）
+     */
+    #[cfg(feature = "gui")]
+    pub fn probability(&self) -> f64 {
+        (-self.difficulty_bits()).exp2()
+    }
+    
+    /**
+     * 计算找到一个匹配地址的期望尝试次数（超出 f64 范围时取 f64::MAX，不返回无穷大）
+     */
+    pub fn expected_attempts(&self) -> f64 {
+        self.difficulty_bits().exp2().min(f64::MAX)
+    }
+}
+
+#[cfg(all(test, feature = "gui"))]
+mod tests {
+    use super::*;
+    use crate::address::{checksum_from_bytes, public_key_to_checksum_address};
+    use crate::chain::Chain;
+    use crate::keccak;
+    use crate::entropy::RngMode;
+    use crate::engine::{CancellationToken, GenerationLimits, SearchConfig, SearchEvent};
+    use crate::hexutil::Address;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use secp256k1::{PublicKey, Secp256k1, SecretKey};
+    use crate::test_support::CASE_INSENSITIVE_MATCHES;
+
+    /// 实际执行的匹配条件的快照：(模式, 是否区分大小写, 匹配条件的文本形式)
+    const COMPILED_MATCHER_CASES: &[(&str, bool, &str)] = &[
+        ("888", false, "prefix=888 AND suffix=888, case-insensitive"),
+        ("*123*", false, "contains=123, case-insensitive"),
+        ("ABC", false, "prefix=abc AND suffix=abc, case-insensitive"),
+        ("ABC", true, "prefix=ABC AND suffix=ABC, case-sensitive"),
+        ("123/456", false, "prefix=123 AND suffix=456, case-insensitive"),
+        ("dead/beef", false, "prefix=dead AND suffix=beef, case-insensitive"),
+        ("DeaD/beeF", true, "prefix=DeaD AND suffix=beeF, case-sensitive"),
+        ("123/", false, "prefix=123, case-insensitive"),
+        ("/456", false, "suffix=456, case-insensitive"),
+        ("*aaaa*", false, "prefix=repeat(aaaa) AND suffix=repeat(aaaa), case-insensitive"),
+        ("*AABB*", true, "prefix=repeat(aabb) AND suffix=repeat(aabb), case-sensitive"),
+        ("*abab*", false, "prefix=repeat(abab) AND suffix=repeat(abab), case-insensitive"),
+        ("*abcabc*", false, "prefix=repeat(abcabc) AND suffix=repeat(abcabc), case-insensitive"),
+    ];
+
+    /// 已知私钥的地址（checksum 形式，不含 0x）：(私钥, 地址)
+    const KNOWN_KEY_ADDRESSES: &[(&str, &str)] = &[
+        ("0000000000000000000000000000000000000000000000000000000000000001", "7E5F4552091A69125d5DfCb7b8C2659029395Bdf"),
+        ("0000000000000000000000000000000000000000000000000000000000000002", "2B5AD5c4795c026514f8317c7a215E218DcCD6cF"),
+        ("0000000000000000000000000000000000000000000000000000000000000003", "6813Eb9362372EEF6200f3b1dbC3f819671cBA69"),
+    ];
+
+    /// 正则表达式用例：(正则表达式, 地址, 是否应当匹配)
+    const REGEX_CASES: &[(&str, &str, bool)] = &[
+        ("^dead.*beef$", "dead0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0ebeef", true),
+        ("^dead.*beef$", "0dead0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0beef", false),
+        // 与 checksum 或大写地址比较时先转换为小写
+        ("^DEAD.*beef$", "DEAD0B0C0D0E0F0A0B0C0D0E0F0A0B0C0D0EBEEF", true),
+        ("c0ffee", "0b0c0d0e0f0a0b0c0ffee0d0e0f0a0b0c0d0e0f0", true),
+        ("c0ffee", "0b0c0d0e0f0a0b0c0ff0ee0d0e0f0a0b0c0d0e0f", false),
+        ("(ab){3}$", "0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0fababab", true),
+    ];
+
+    /// 应当在开始前被拒绝的正则表达式：(正则表达式, 错误信息中应当包含的内容)
+    const INVALID_REGEXES: &[(&str, &str)] = &[
+        ("", "不能为空"),
+        ("(dead", "Rust regex 语法"),
+        ("[0-", "Rust regex 语法"),
+        ("(?=dead)", "不支持反向引用和环视"),
+        ("(a)\\1", "不支持反向引用和环视"),
+        ("a{2,3}", "逗号"),
+        ("(a{1000}){1000}", "过于复杂"),
+    ];
+
+    /// 正则表达式模式搜索的匹配数
+    const REGEX_SEARCH_MATCHES: u64 = 3;
+
+    /// 显式比较方式用例：(模式, 比较方式, 地址, 是否应当匹配)
+    const MATCH_MODE_CASES: &[(&str, MatchMode, &str, bool)] = &[
+        ("abc", MatchMode::Prefix, "abca0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0f0a", true),
+        ("abc", MatchMode::Prefix, "a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0f0aabc", false),
+        ("abc", MatchMode::Suffix, "a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0f0aabc", true),
+        ("abc", MatchMode::Suffix, "abca0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0f0a", false),
+        ("abc", MatchMode::Both, "abca0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0abc", true),
+        ("abc", MatchMode::Both, "abca0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0f0a", false),
+        ("abc", MatchMode::Contains, "a0b0c0d0e0f0a0b0c0d0abc0a0b0c0d0e0f0a0b0", true),
+        ("a?c", MatchMode::Contains, "a0b0c0d0e0f0a0b0c0d0a1c0a0b0c0d0e0f0a0b0", true),
+        // 显式指定 contains 时重复形式按字面比较
+        ("*aaaa*", MatchMode::Contains, "a0b0c0d0e0f0a0b0c0d0aaaaa0b0c0d0e0f0a0b0", true),
+        ("*aaaa*", MatchMode::Contains, "1111a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d02222", false),
+        // 字母模板可以只比较一侧
+        ("*aabb*", MatchMode::Prefix, "1122a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0f0", true),
+        ("*aabb*", MatchMode::Suffix, "1122a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0f0", false),
+        ("*aabb*", MatchMode::Suffix, "a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0f03344", true),
+    ];
+
+    /// 与模式语法矛盾的比较方式
+    const CONFLICTING_MATCH_MODES: &[(&str, MatchMode)] = &[
+        ("*abc*", MatchMode::Prefix),
+        ("*abc*", MatchMode::Both),
+        ("abc/def", MatchMode::Contains),
+        ("abc/", MatchMode::Suffix),
+    ];
+
+    /// leading_zeros 模式的测试找到的匹配数
+    const LEADING_ZERO_MATCHES: u64 = 6;
+
+    /// caps_prefix 测试中对照难度估算的随机地址个数
+    const CAPS_PREFIX_SAMPLES: usize = 20_000;
+
+    /// char_count 测试中对照难度估算的随机地址个数
+    const CHAR_COUNT_SAMPLES: usize = 20_000;
+
+    /// 应当被拒绝的 char_count 模式（非十六进制字符、多个字符、个数为 0 或超过 40、每个地址都满足、无法解析）
+    const INVALID_CHAR_COUNTS: &[&str] = &["count(g)>=5", "count(88)>=5", "count(8)>=0", "count(8)>=41", "count(*)>=3", "count()>=5", "count(8)", "8"];
+
+    /// 应当被拒绝的 leading_zeros 模式（零的个数为 0、超过 40 或无法解析）
+    const INVALID_LEADING_ZEROS: &[&str] = &["zeros>=0", "zeros>=41", "zeros>=255", "zeros>=", "zeros>=abc", "00"];
+
+    /// 匹配区间用例：(模式, checksum 地址, 规则, 期望的区间)
+    type SpanCase = (&'static str, &'static str, MatchRule, &'static [(usize, usize)]);
+
+    /// 各匹配规则的区间用例（区间为空表示不应匹配）
+    const SPAN_CASES: &[SpanCase] = &[
+        ("888", "888a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0888", MatchRule::PrefixSuffix, &[(0, 3), (37, 40)]),
+        ("888", "888a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0887", MatchRule::PrefixSuffix, &[]),
+        ("*123*", "123a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0123", MatchRule::Wildcard, &[(0, 3)]),
+        ("*123*", "a0b0c0d0e0f0a0b0c123e0f0a0b0c0d0e0f0a0b0", MatchRule::Wildcard, &[(17, 20)]),
+        ("*123*", "a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0f0a123", MatchRule::Wildcard, &[(37, 40)]),
+        ("*aaaa*", "1111a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d02222", MatchRule::RepeatAaaa, &[(0, 4), (36, 40)]),
+        ("*aabb*", "1122a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d03344", MatchRule::RepeatAabb, &[(0, 4), (36, 40)]),
+        ("*abab*", "1212a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d03434", MatchRule::RepeatAbab, &[(0, 4), (36, 40)]),
+        ("123/456", "123a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0456", MatchRule::PrefixAndSuffix, &[(0, 3), (37, 40)]),
+        // 只指定一侧时只返回这一侧的区间
+        ("123/", "123a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0887", MatchRule::PrefixAndSuffix, &[(0, 3)]),
+        ("/456", "887a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0456", MatchRule::PrefixAndSuffix, &[(37, 40)]),
+        // 模式长于地址的一半：前后缀区间重叠
+        ("111111111111111111111111111111", "1111111111111111111111111111111111111111", MatchRule::PrefixSuffix, &[(0, 30), (10, 40)]),
+    ];
+
+    /// 可以按字节比较的模式：(模式, 是否区分大小写, 比较方式)；随机地址的半字节只取 0、a、b，短模式经常满足
+    const BYTE_MATCHER_PATTERNS: &[(&str, bool, Option<MatchMode>)] = &[
+        ("a", false, None),
+        ("0xBa", false, None),
+        ("a?b", false, None),
+        ("ab", false, Some(MatchMode::Prefix)),
+        ("0", false, Some(MatchMode::Suffix)),
+        ("a/", false, None),
+        ("/0b", false, None),
+        ("b?/?a", false, None),
+        ("A", true, None),
+        ("bA/", true, None),
+        ("B/a", true, Some(MatchMode::Both)),
+    ];
+
+    /// 只能按字符串比较的模式：(模式, 模式类型, 比较方式)
+    const STRING_ONLY_PATTERNS: &[(&str, PatternType, Option<MatchMode>)] = &[
+        ("*ab*", PatternType::Standard, None),
+        ("*aabb*", PatternType::Standard, None),
+        ("*aabb*", PatternType::Standard, Some(MatchMode::Prefix)),
+        ("ab", PatternType::Standard, Some(MatchMode::Contains)),
+        ("^ab", PatternType::Regex, None),
+        ("zeros>=2", PatternType::LeadingZeros, None),
+        ("count(8)>=4", PatternType::CharCount, None),
+    ];
+
+    /**
+     * 原来逐个写出的 aaaa、aabb、abab 重复形式（字母模板的参考实现）
+     *
+     * @param shape - 重复形式
+     * @param c - 地址的前 4 位或后 4 位
+     */
+    fn legacy_repeat_shape(shape: &str, c: &[u8]) -> bool {
+        match shape {
+            "aaaa" => c[1..].iter().all(|&b| b == c[0]),
+            "aabb" => c[0] == c[1] && c[2] == c[3] && c[0] != c[2],
+            _ => c[0] == c[2] && c[1] == c[3] && c[0] != c[1],
+        }
+    }
+
+    /**
+     * 字母模板：aaaa、aabb、abab 在随机地址上与原来逐个写出的判断一致，其它模板按相同和不同的字母比较，
+     * 可以只比较一侧；十六进制的单词仍按包含比较，不可能满足的模板被拒绝
+     */
+    #[test]
+    fn letter_templates() -> Result<(), String> {
+        const SAMPLES: usize = 20_000;
+        let mut rng = StdRng::seed_from_u64(306);
+        // 只用少数几个字符，让重复的字符经常出现
+        let mut random_address = || -> String { (0..40).map(|_| ['1', '2', 'a', 'A'][rng.gen_range(0..4)]).collect() };
+        for case_sensitive in [false, true] {
+            for shape in ["aaaa", "aabb", "abab"] {
+                let parsed = ParsedPattern::with_case(&format!("*{}*", shape), case_sensitive);
+                let mut hits = 0;
+                for _ in 0..SAMPLES {
+                    let address = random_address();
+                    let compared = if case_sensitive { address.clone() } else { address.to_ascii_lowercase() };
+                    let bytes = compared.as_bytes();
+                    let legacy = legacy_repeat_shape(shape, &bytes[..4]) && legacy_repeat_shape(shape, &bytes[36..]);
+                    if parsed.matches(&address) != legacy {
+                        return Err(format!("模板 {} 与地址 {} 的匹配结果与原来的判断不一致（区分大小写: {}）", shape, address, case_sensitive));
+                    }
+                    hits += usize::from(legacy);
+                }
+                if hits == 0 {
+                    return Err(format!("模板 {} 在 {} 个随机地址中没有满足的，没有比较到匹配的情况", shape, SAMPLES));
+                }
+            }
+        }
+        
+        // (模板, 比较方式, 地址, 是否匹配)
+        let cases = [
+            ("*aabbcc*", None, "112233a0b0c0d0e0f0a0b0c0d0e0f0a0b0c445566", true),
+            ("*aabbcc*", None, "112211a0b0c0d0e0f0a0b0c0d0e0f0a0b0c445566", false),
+            ("*abcabc*", None, "1231230b0c0d0e0f0a0b0c0d0e0f0a0b0c456456", true),
+            ("*abcabc*", None, "1211210b0c0d0e0f0a0b0c0d0e0f0a0b0c456456", false),
+            ("*aaaaaa*", Some(MatchMode::Prefix), "ffffff0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0f", true),
+            ("*aaaaaa*", Some(MatchMode::Suffix), "ffffff0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0f", false),
+            ("*xyxy*", None, "1212a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d03434", true),
+        ];
+        for (text, mode, address, expected) in cases {
+            let parsed = ParsedPattern::new(text).with_match_mode(mode);
+            parsed.validate()?;
+            if parsed.matches(address) != expected {
+                return Err(format!("模板 {} 按 {:?} 与 {} 的匹配结果应为 {}", text, mode, address, expected));
+            }
+        }
+        let template = ParsedPattern::new("*abcabc*");
+        let spans = template.matched_spans("1231230b0c0d0e0f0a0b0c0d0e0f0a0b0c456456").unwrap_or_default();
+        if template.rule() != MatchRule::Template || spans.iter().map(|span| (span.start, span.end)).collect::<Vec<_>>() != [(0, 6), (34, 40)] {
+            return Err(format!("模板 *abcabc* 的规则为 {:?}，区间为 {:?}", template.rule(), spans));
+        }
+        let suffix = ParsedPattern::new("*aabb*").with_match_mode(Some(MatchMode::Suffix));
+        if suffix.matcher().to_string() != "suffix=repeat(aabb), case-insensitive" || suffix.difficulty_bits() * 2.0 != ParsedPattern::new("*aabb*").difficulty_bits() {
+            return Err(format!("只比较后缀的模板的匹配条件为 {}，难度为 {} 位", suffix.matcher(), suffix.difficulty_bits()));
+        }
+        
+        // 不区分大小写时 16 种字符等可能：aaaaaa 一侧为 16 / 16^6，abcabc 一侧为 16·15·14 / 16^6，aabb 与原来的估算相同
+        let expected = [("*aaaaaa*", 2.0 * 20.0), ("*abcabc*", 2.0 * (24.0 - (16.0f64 * 15.0 * 14.0).log2())), ("*aabb*", -2.0 * (15.0f64 / 4096.0).log2())];
+        for (text, bits) in expected {
+            let actual = ParsedPattern::new(text).difficulty_bits();
+            if (actual - bits).abs() > 1e-9 {
+                return Err(format!("模板 {} 的难度为 {} 位，应为 {} 位", text, actual, bits));
+            }
+        }
+        
+        // 十六进制的单词和没有重复字母的字母串仍按包含比较
+        for text in ["*beef*", "*cafe*", "*dead*", "*abc*"] {
+            if ParsedPattern::new(text).match_mode() != MatchMode::Contains {
+                return Err(format!("{} 应按包含比较", text));
+            }
+        }
+        for text in [format!("*{}a*", ('a'..='q').collect::<String>()), "*xyz*".to_string()] {
+            if ParsedPattern::new(&text).validate().is_ok() {
+                return Err(format!("无效的模板 {} 没有被拒绝", text));
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * 按字节比较：前后缀模式在随机地址上的字节比较结果与字符串比较一致（区分大小写时是字符串比较的必要条件），
+     * 其它模式和其它链只按字符串比较；先按字节比较的搜索与只按字符串比较的搜索找到相同的私钥
+     */
+    #[test]
+    fn byte_matchers() -> Result<(), String> {
+        const SAMPLES: usize = 4096;
+        let mut rng = StdRng::seed_from_u64(41);
+        let mut random_address = |narrow: bool| {
+            let mut bytes = [0u8; 20];
+            for byte in bytes.iter_mut() {
+                let mut nibble = || if narrow { [0x0, 0xa, 0xb][rng.gen_range(0..3)] } else { rng.gen_range(0..16u8) };
+                *byte = (nibble() << 4) | nibble();
+            }
+            bytes
+        };
+        for (text, case_sensitive, match_mode) in BYTE_MATCHER_PATTERNS {
+            let parsed = ParsedPattern::with_case(text, *case_sensitive).with_match_mode(*match_mode);
+            let ignoring_case = ParsedPattern::with_case(text, false).with_match_mode(*match_mode);
+            let affixes = parsed.nibble_affixes().ok_or_else(|| format!("模式 {} 应可以按字节比较", text))?;
+            let mut pattern_matches = 0;
+            for sample in 0..SAMPLES {
+                let bytes = random_address(sample % 2 == 0);
+                let (lowercase, checksum) = (hex::encode(bytes), checksum_from_bytes(&bytes));
+                let by_bytes = affixes.matches(&bytes);
+                if by_bytes != ignoring_case.matches(&lowercase) || (parsed.matches(&checksum) && !by_bytes) {
+                    return Err(format!("模式 {} 与地址 {} 的字节比较结果为 {}，与字符串比较不一致", text, checksum, by_bytes));
+                }
+                if !case_sensitive && by_bytes != parsed.matches(&lowercase) {
+                    return Err(format!("模式 {} 与小写地址 {} 的字节比较结果为 {}，与字符串比较不一致", text, lowercase, by_bytes));
+                }
+                pattern_matches += usize::from(parsed.matches(&checksum));
+            }
+            if pattern_matches == 0 {
+                return Err(format!("模式 {} 在 {} 个随机地址中没有满足的，没有比较到匹配的情况", text, SAMPLES));
+            }
+        }
+        for (text, pattern_type, match_mode) in STRING_ONLY_PATTERNS {
+            if ParsedPattern::with_options(text, *pattern_type, false).with_match_mode(*match_mode).nibble_affixes().is_some() {
+                return Err(format!("模式 {} 不能按字节比较", text));
+            }
+        }
+
+        let config = SearchConfig {
+            limits: GenerationLimits { max_matches: Some(5), ..GenerationLimits::default() },
+            rng_mode: RngMode::DeterministicTest,
+            test_seed: Some(43),
+            case_sensitive: true,
+            additional_patterns: vec!["/B".to_string()],
+            ..SearchConfig::new("a/")
+        };
+        let excluded = [
+            SearchConfig { chain: Chain::Tron, case_sensitive: false, ..config.clone() },
+            SearchConfig { additional_patterns: vec!["*ab*".to_string()], ..config.clone() },
+        ];
+        if config.nibble_affixes().is_none_or(|affixes| affixes.len() != 2) || excluded.iter().any(|config| config.nibble_affixes().is_some()) {
+            return Err("只有全部模式都是前后缀模式的以太坊搜索应先按字节比较".to_string());
+        }
+        let parsed_patterns = config.parsed_patterns();
+        let mut found = (Vec::new(), Vec::new());
+        let report = crate::engine::search(&config, &CancellationToken::new(), || false, |event| {
+            if let SearchEvent::Found(key) = event {
+                found.0.push((key.private_key.expose().to_string(), key.attempts));
+            }
+        });
+        let patterns = config.patterns();
+        if report.pattern_matches.values().sum::<u64>() != report.matches || report.pattern_matches.keys().any(|pattern| !patterns.contains(&pattern.as_str())) {
+            return Err(format!("按模式统计的匹配数 {:?} 与总匹配数 {} 不一致", report.pattern_matches, report.matches));
+        }
+        let is_match = |address: &str| parsed_patterns.iter().any(|parsed| parsed.matches(address));
+        crate::engine::search_with(&config, &CancellationToken::new(), || false, is_match, |event| {
+            if let SearchEvent::Found(key) = event {
+                found.1.push((key.private_key.expose().to_string(), key.attempts));
+            }
+        });
+        if found.0.len() != 5 || found.0 != found.1 {
+            return Err(format!("先按字节比较的搜索找到 {} 个私钥，与只按字符串比较的 {} 个不一致", found.0.len(), found.1.len()));
+        }
+        Ok(())
+    }
+
+    /**
+     * 实际执行的匹配条件：各种模式解析后的文本形式与快照一致，序列化后可以还原
+     */
+    #[test]
+    fn compiled_matchers() -> Result<(), String> {
+        for (pattern, case_sensitive, expected) in COMPILED_MATCHER_CASES {
+            let matcher = ParsedPattern::with_case(pattern, *case_sensitive).matcher();
+            if matcher.to_string() != *expected {
+                return Err(format!("{} 的匹配条件为 {}，应为 {}", pattern, matcher, expected));
+            }
+            let json = serde_json::to_string(&matcher).map_err(|e| e.to_string())?;
+            if serde_json::from_str::<Matcher>(&json).map_err(|e| e.to_string())? != matcher {
+                return Err(format!("{} 的匹配条件序列化后无法还原: {}", pattern, json));
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * 不区分大小写的匹配：小写模式与任意大小写的地址比较，难度按每个字符 4 位计算，
+     * 生成循环找到的地址仍然以 checksum 地址保存
+     */
+    #[test]
+    fn case_insensitive_matching() -> Result<(), String> {
+        let insensitive = ParsedPattern::with_case("DEAD", false);
+        let sensitive = ParsedPattern::with_case("DEAD", true);
+        for address in ["dead0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0edead", "DeAd0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0edEAD"] {
+            if !insensitive.matches(address) || sensitive.matches(address) {
+                return Err(format!("{} 的不区分大小写匹配结果不正确", address));
+            }
+        }
+        if insensitive.matches("dead0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0edeaf") {
+            return Err("不区分大小写时十六进制字符不同的地址被匹配".to_string());
+        }
+        if insensitive.difficulty_bits() != 32.0 || sensitive.difficulty_bits() != 40.0 {
+            return Err(format!("难度为 {} / {} 位，应为 32 / 40 位", insensitive.difficulty_bits(), sensitive.difficulty_bits()));
+        }
+        let matcher = insensitive.matcher().to_string();
+        if matcher != "prefix=dead AND suffix=dead, case-insensitive" {
+            return Err(format!("不区分大小写的匹配条件为 {}", matcher));
+        }
+        
+        let config = SearchConfig {
+            limits: crate::engine::GenerationLimits { max_matches: Some(CASE_INSENSITIVE_MATCHES), ..Default::default() },
+            rng_mode: RngMode::DeterministicTest,
+            test_seed: Some(3),
+            case_sensitive: false,
+            ..SearchConfig::new("Ab")
+        };
+        let mut found = Vec::new();
+        crate::engine::search(&config, &CancellationToken::new(), || false, |event| {
+            if let SearchEvent::Found(key) = event {
+                found.push(key.address);
+            }
+        });
+        let lower_matches = |address: &Address| {
+            let lower = hex::encode(address.as_bytes());
+            lower.starts_with("ab") && lower.ends_with("ab")
+        };
+        if found.len() as u64 != CASE_INSENSITIVE_MATCHES || !found.iter().all(lower_matches) {
+            return Err(format!("不区分大小写的搜索结果不正确: {:?}", found));
+        }
+        if !found.iter().all(|address| Address::parse_canonical(&address.to_checksum()).as_ref() == Ok(address)) {
+            return Err("不区分大小写时找到的地址不是 checksum 地址".to_string());
+        }
+        Ok(())
+    }
+
+    /**
+     * 已知私钥的地址：模式按地址的前几位和后几位截取，不区分大小写时小写模式匹配，
+     * 区分大小写时只有与 checksum 地址大小写一致的模式匹配（模式的大小写不再由模式自身的哈希决定）
+     */
+    #[test]
+    fn known_key_patterns() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        for (key, expected) in KNOWN_KEY_ADDRESSES {
+            let secret = SecretKey::from_slice(&hex::decode(key).map_err(|e| e.to_string())?).map_err(|e| e.to_string())?;
+            let address = public_key_to_checksum_address(&PublicKey::from_secret_key(&secp, &secret));
+            if address != *expected {
+                return Err(format!("私钥 {} 推导出的地址为 {}，应为 {}", key, address, expected));
+            }
+            for length in 1..=6 {
+                let (prefix, suffix) = (&address[..length], &address[address.len() - length..]);
+                let lower = prefix_suffix_pattern(&prefix.to_lowercase(), &suffix.to_lowercase());
+                let exact = prefix_suffix_pattern(prefix, suffix);
+                if !ParsedPattern::new(&lower).matches(&address) || !ParsedPattern::new(&exact).matches(&address) {
+                    return Err(format!("地址 {} 与不区分大小写的模式 {} 不匹配", address, lower));
+                }
+                if !ParsedPattern::with_case(&exact, true).matches(&address) || !ParsedPattern::new(&format!("{}/", prefix)).matches(&address) {
+                    return Err(format!("地址 {} 与区分大小写的模式 {} 不匹配", address, exact));
+                }
+                let swapped: String = prefix.chars().map(|c| if c.is_ascii_uppercase() { c.to_ascii_lowercase() } else { c.to_ascii_uppercase() }).collect();
+                if swapped != prefix && ParsedPattern::with_case(&format!("{}/", swapped), true).matches(&address) {
+                    return Err(format!("地址 {} 与大小写不一致的模式 {} 匹配", address, swapped));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn matched_spans_cases() -> Result<(), String> {
+        for (pattern, address, rule, expected) in SPAN_CASES {
+            let spans = ParsedPattern::new(pattern).matched_spans(address).unwrap_or_default();
+            let actual: Vec<(usize, usize)> = spans.iter().map(|span| (span.start, span.end)).collect();
+            if actual != *expected {
+                return Err(format!("模式 {} 对地址 {} 的匹配区间为 {:?}，期望 {:?}", pattern, address, actual, expected));
+            }
+            if let Some(span) = spans.iter().find(|span| span.rule != *rule) {
+                return Err(format!("模式 {} 的匹配规则为 {:?}，期望 {:?}", pattern, span.rule, rule));
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * 正则表达式模式：锚定和不锚定的模式与小写地址比较，无效和编译后过大的模式在开始前被拒绝，
+     * 难度按抽样估算，生成循环只返回满足正则表达式的地址
+     */
+    #[test]
+    fn regex_patterns() -> Result<(), String> {
+        for (regex, address, expected) in REGEX_CASES {
+            if ParsedPattern::with_options(regex, PatternType::Regex, false).matches(address) != *expected {
+                return Err(format!("正则表达式 {} 与 {} 的匹配结果应为 {}", regex, address, expected));
+            }
+        }
+        for (regex, expected) in INVALID_REGEXES {
+            match ParsedPattern::with_options(regex, PatternType::Regex, false).validate() {
+                Ok(()) => return Err(format!("无效的正则表达式 {:?} 通过了校验", regex)),
+                Err(e) if !e.contains(expected) => return Err(format!("正则表达式 {:?} 的错误信息不含“{}”: {}", regex, expected, e)),
+                Err(_) => {}
+            }
+        }
+        if ParsedPattern::with_options("^dead", PatternType::Regex, true).validate().is_ok() {
+            return Err("正则表达式模式接受了 case_sensitive".to_string());
+        }
+        
+        let unanchored = ParsedPattern::with_options("c0ffee", PatternType::Regex, false);
+        let spans: Vec<(usize, usize)> = unanchored.matched_spans("0b0c0d0e0f0a0b0c0ffee0d0e0f0a0b0c0d0e0f0").unwrap_or_default().iter().map(|span| (span.start, span.end)).collect();
+        if spans != [(15, 21)] || unanchored.rule() != MatchRule::Regex {
+            return Err(format!("不锚定的正则表达式的匹配区间为 {:?}", spans));
+        }
+        let anchored = ParsedPattern::with_options("^dead.*beef$", PatternType::Regex, false);
+        let one_nibble = ParsedPattern::with_options("^a", PatternType::Regex, false).difficulty_bits();
+        if anchored.difficulty_bits() != 32.0 || (one_nibble - 4.0).abs() > 0.5 {
+            return Err(format!("难度为 {} / {} 位，应约为 32 / 4 位", anchored.difficulty_bits(), one_nibble));
+        }
+        let matcher = anchored.matcher().to_string();
+        if matcher != "regex=/^dead.*beef$/, case-insensitive" || file_label("^dead.*beef$") != "deadbeef" {
+            return Err(format!("正则表达式的匹配条件为 {}，文件名中记为 {}", matcher, file_label("^dead.*beef$")));
+        }
+        
+        let config = SearchConfig {
+            limits: crate::engine::GenerationLimits { max_matches: Some(REGEX_SEARCH_MATCHES), ..Default::default() },
+            rng_mode: RngMode::DeterministicTest,
+            test_seed: Some(5),
+            pattern_type: PatternType::Regex,
+            ..SearchConfig::new("^[a-f][0-9]|[0-9][a-f]$")
+        };
+        config.validate()?;
+        let mut found = Vec::new();
+        crate::engine::search(&config, &CancellationToken::new(), || false, |event| {
+            if let SearchEvent::Found(key) = event {
+                found.push(hex::encode(key.address.as_bytes()));
+            }
+        });
+        let satisfies = |address: &String| {
+            let bytes = address.as_bytes();
+            (bytes[0].is_ascii_alphabetic() && bytes[1].is_ascii_digit()) || (bytes[38].is_ascii_digit() && bytes[39].is_ascii_alphabetic())
+        };
+        if found.len() as u64 != REGEX_SEARCH_MATCHES || !found.iter().all(satisfies) {
+            return Err(format!("正则表达式模式的搜索结果不正确: {:?}", found));
+        }
+        Ok(())
+    }
+
+    /**
+     * 比较方式：显式指定的 prefix、suffix、both、contains 各自生效，与模式语法矛盾时被拒绝，
+     * 包含模式的难度按可以出现的位置数降低
+     */
+    #[test]
+    fn match_modes() -> Result<(), String> {
+        for (text, mode, address, expected) in MATCH_MODE_CASES {
+            let parsed = ParsedPattern::new(text).with_match_mode(Some(*mode));
+            parsed.validate()?;
+            if parsed.matches(address) != *expected {
+                return Err(format!("模式 {} 按 {:?} 与 {} 的匹配结果应为 {}", text, mode, address, expected));
+            }
+        }
+        for (text, mode) in CONFLICTING_MATCH_MODES {
+            if ParsedPattern::new(text).with_match_mode(Some(*mode)).validate().is_ok() {
+                return Err(format!("模式 {} 与比较方式 {:?} 矛盾，没有被拒绝", text, mode));
+            }
+        }
+        if ParsedPattern::with_options("^abc", PatternType::Regex, false).with_match_mode(Some(MatchMode::Prefix)).validate().is_ok() {
+            return Err("正则表达式模式接受了 match_mode".to_string());
+        }
+        
+        let wildcard = ParsedPattern::new("*abc*");
+        let explicit = ParsedPattern::new("abc").with_match_mode(Some(MatchMode::Contains));
+        if wildcard.match_mode() != MatchMode::Contains || ParsedPattern::new("abc").match_mode() != MatchMode::Both {
+            return Err("按模式语法决定的比较方式不正确".to_string());
+        }
+        // 3 位模式可以出现在 38 个位置：约 12 - log2(38) 位
+        let expected = 12.0 - 38f64.log2();
+        if (wildcard.difficulty_bits() - expected).abs() > 0.05 || explicit.difficulty_bits() != wildcard.difficulty_bits() {
+            return Err(format!("包含模式的难度为 {} 位，应约为 {:.2} 位", wildcard.difficulty_bits(), expected));
+        }
+        let prefix = ParsedPattern::new("abc").with_match_mode(Some(MatchMode::Prefix));
+        if prefix.difficulty_bits() != 12.0 || prefix.matcher().to_string() != "prefix=abc, case-insensitive" {
+            return Err(format!("只比较前缀时难度为 {} 位，匹配条件为 {}", prefix.difficulty_bits(), prefix.matcher()));
+        }
+        if !ParsedPattern::new(&"1".repeat(41)).with_match_mode(Some(MatchMode::Contains)).difficulty_bits().is_finite() {
+            return Err("长于地址的包含模式难度不是有限值".to_string());
+        }
+        Ok(())
+    }
+
+    /**
+     * leading_zeros 模式：零的个数在 1 到 40 之间，难度为每个 0 4 位，结果的模式列记录实际的零的个数，
+     * 进度中报告目前见过的最多的零的个数（其它模式不报告）
+     */
+    #[test]
+    fn leading_zeros() -> Result<(), String> {
+        let leading = |text: &str| ParsedPattern::with_options(text, PatternType::LeadingZeros, false);
+        for rejected in INVALID_LEADING_ZEROS {
+            if leading(rejected).validate().is_ok() {
+                return Err(format!("无效的 leading_zeros 模式 {:?} 没有被拒绝", rejected));
+            }
+        }
+        if leading("zeros>=40").validate().is_err() || leading("3").validate().is_err() {
+            return Err("有效的 leading_zeros 模式被拒绝".to_string());
+        }
+        if leading("zeros>=3").with_match_mode(Some(MatchMode::Prefix)).validate().is_ok() {
+            return Err("leading_zeros 模式接受了 match_mode".to_string());
+        }
+        let parsed = leading(&leading_zeros_pattern(3));
+        let (deep, shallow) = ("0000a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0f0", "00a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0f000");
+        if !parsed.matches(deep) || parsed.matches(shallow) || parsed.difficulty_bits() != 12.0 {
+            return Err(format!("zeros>=3 的匹配结果或难度不正确（{} 位）", parsed.difficulty_bits()));
+        }
+        let spans = parsed.matched_spans(deep).unwrap_or_default();
+        if spans.len() != 1 || spans[0].end != 4 || parsed.achieved_zero_nibbles(deep) != Some(4) || ParsedPattern::new("00").achieved_zero_nibbles(deep).is_some() {
+            return Err(format!("zeros>=3 与 {} 的区间为 {:?}", deep, spans));
+        }
+        if parsed.matcher().to_string() != "leading_zeros>=3" || file_label("zeros>=3") != "zeros3" {
+            return Err(format!("leading_zeros 的匹配条件为 {}", parsed.matcher()));
+        }
+        
+        let run = |config: SearchConfig| {
+            let (mut found, mut best) = (Vec::new(), Vec::new());
+            crate::engine::search(&config, &CancellationToken::new(), || false, |event| match event {
+                SearchEvent::Found(key) => found.push(hex::encode(key.address.as_bytes())),
+                SearchEvent::Progress(progress) => best.push(progress.best_zero_nibbles),
+                _ => {}
+            });
+            (found, best)
+        };
+        let limits = crate::engine::GenerationLimits { max_matches: Some(LEADING_ZERO_MATCHES), ..Default::default() };
+        let config = SearchConfig {
+            limits: limits.clone(),
+            rng_mode: RngMode::DeterministicTest,
+            test_seed: Some(13),
+            pattern_type: PatternType::LeadingZeros,
+            ..SearchConfig::new("zeros>=2")
+        };
+        config.validate()?;
+        let (found, best) = run(config);
+        let deepest = found.iter().map(|address| leading_zero_nibbles(address)).max().unwrap_or_default() as u32;
+        if found.len() as u64 != LEADING_ZERO_MATCHES || !found.iter().all(|address| address.starts_with("00")) {
+            return Err(format!("leading_zeros 搜索结果不正确: {:?}", found));
+        }
+        let last = best.last().copied().flatten().unwrap_or_default();
+        if last < deepest || !best.windows(2).all(|pair| pair[0] <= pair[1]) {
+            return Err(format!("进度中最多的零的个数为 {:?}，找到的地址最多有 {} 个", best, deepest));
+        }
+        let (_, plain) = run(SearchConfig { limits, rng_mode: RngMode::DeterministicTest, test_seed: Some(13), ..SearchConfig::new("8") });
+        if plain.iter().any(Option::is_some) {
+            return Err("普通模式的进度中报告了零的个数".to_string());
+        }
+        Ok(())
+    }
+
+    /**
+     * caps_prefix 模式：只比较校验和地址前 N 位的大小写（数字不限），lower>=N 要求全部小写；
+     * 难度按每位 13/16 的概率估算并与随机地址的实际比例对照，引擎搜索时比较的是校验和地址
+     */
+    #[test]
+    fn caps_prefix_cases() -> Result<(), String> {
+        let caps = |text: &str| ParsedPattern::with_options(text, PatternType::CapsPrefix, false);
+        for rejected in ["caps>=0", "caps>=41", "caps>=x", ""] {
+            if caps(rejected).validate().is_ok() {
+                return Err(format!("无效的 caps_prefix 模式 {:?} 没有被拒绝", rejected));
+            }
+        }
+        if caps("caps>=8").with_match_mode(Some(MatchMode::Prefix)).validate().is_ok() || caps("caps>=8").with_alphabet(PatternAlphabet::Base58).validate().is_ok() {
+            return Err("caps_prefix 模式接受了 match_mode 或非以太坊地址".to_string());
+        }
+        let (upper, lower) = (caps(&caps_prefix_pattern(8, false)), caps(&caps_prefix_pattern(8, true)));
+        if upper.validate().is_err() || lower.validate().is_err() || caps("8").matcher().to_string() != "caps_prefix>=8" || lower.matcher().to_string() != "lower_prefix>=8" {
+            return Err(format!("caps_prefix 的匹配条件为 {} / {}", upper.matcher(), lower.matcher()));
+        }
+        let (shouting, mixed) = ("AB12CDEFa0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0", "AB12CdEFa0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0");
+        if !upper.matches(shouting) || upper.matches(mixed) || lower.matches(shouting) || !lower.matches("ab12cdefA0B0C0D0E0F0A0B0C0D0E0F0A0B0C0D0") {
+            return Err("caps_prefix 的匹配结果不正确".to_string());
+        }
+        let spans = upper.matched_spans(shouting).unwrap_or_default();
+        if spans.len() != 1 || spans[0].start != 0 || spans[0].end != 8 || upper.nibble_affixes().is_some() {
+            return Err(format!("caps>=8 与 {} 的区间为 {:?}", shouting, spans));
+        }
+        let expected = -8.0 * (13.0f64 / 16.0).log2();
+        if (upper.difficulty_bits() - expected).abs() > 1e-9 || (lower.difficulty_bits() - expected).abs() > 1e-9 {
+            return Err(format!("caps>=8 的难度为 {} 位，应为 {} 位", upper.difficulty_bits(), expected));
+        }
+        
+        // 随机地址中满足 caps>=4 的比例应接近 (13/16)^4
+        let short = caps("caps>=4");
+        let mut seed = [0u8; 32];
+        let mut hits = 0;
+        for index in 0..CAPS_PREFIX_SAMPLES {
+            seed[..8].copy_from_slice(&(index as u64).to_le_bytes());
+            let mut bytes = [0u8; 20];
+            bytes.copy_from_slice(&keccak::keccak256(&seed)[..20]);
+            if short.matches(&checksum_from_bytes(&bytes)) {
+                hits += 1;
+            }
+        }
+        let (observed, predicted) = (hits as f64 / CAPS_PREFIX_SAMPLES as f64, (-short.difficulty_bits()).exp2());
+        if (observed - predicted).abs() > 0.02 {
+            return Err(format!("caps>=4 的实际比例为 {:.4}，估算为 {:.4}", observed, predicted));
+        }
+        
+        let config = SearchConfig {
+            limits: GenerationLimits { max_matches: Some(3), ..Default::default() },
+            rng_mode: RngMode::DeterministicTest,
+            test_seed: Some(17),
+            pattern_type: PatternType::CapsPrefix,
+            ..SearchConfig::new("caps>=10")
+        };
+        config.validate()?;
+        let mut found = Vec::new();
+        crate::engine::search(&config, &CancellationToken::new(), || false, |event| {
+            if let SearchEvent::Found(key) = event {
+                found.push(checksum_from_bytes(key.address.as_bytes()));
+            }
+        });
+        let prefix_ok = |address: &String| !address[..10].chars().any(|c| c.is_ascii_lowercase());
+        if found.len() != 3 || !found.iter().all(prefix_ok) {
+            return Err(format!("caps_prefix 搜索结果不正确: {:?}", found));
+        }
+        Ok(())
+    }
+
+    /**
+     * char_count 模式：统计小写地址中的字符，count(*)>=N 取出现次数最多的字符；非十六进制字符、个数为 0 或超过 40、
+     * 每个地址都满足的个数被拒绝；难度与随机地址中的实际比例对照，引擎找到的地址满足个数，结果记录实际的字符和个数
+     */
+    #[test]
+    fn char_count() -> Result<(), String> {
+        let count = |text: &str| ParsedPattern::with_options(text, PatternType::CharCount, false);
+        for rejected in INVALID_CHAR_COUNTS {
+            if count(rejected).validate().is_ok() {
+                return Err(format!("无效的 char_count 模式 {:?} 没有被拒绝", rejected));
+            }
+        }
+        if count("count(8)>=4").with_match_mode(Some(MatchMode::Contains)).validate().is_ok() || count("count(8)>=4").with_alphabet(PatternAlphabet::Base58).validate().is_ok() {
+            return Err("char_count 模式接受了 match_mode 或非十六进制地址".to_string());
+        }
+        let upper = count("count(A)>=2");
+        if upper.validate().is_err() || upper.check().normalized != "count(a)>=2" || count(&char_count_pattern(None, 4)).validate().is_err() {
+            return Err(format!("有效的 char_count 模式被拒绝（规范化为 {}）", upper.check().normalized));
+        }
+        
+        let address = "888888888888abcdefabcdefabcdef1234567B9C";
+        let (eights, any) = (count(&char_count_pattern(Some('8'), 12)), count(&char_count_pattern(None, 12)));
+        if !eights.matches(address) || count("count(8)>=13").matches(address) || !any.matches(address) || !count("count(b)>=4").matches(address) {
+            return Err("char_count 的匹配结果不正确".to_string());
+        }
+        let achieved = any.achieved_char_count(address);
+        if achieved != Some(CharCount { character: '8', count: 12 }) || achieved.map(achieved_count_label).as_deref() != Some("count(8)=12") || ParsedPattern::new("8").achieved_char_count(address).is_some() {
+            return Err(format!("count(*)>=12 的实际结果为 {:?}", achieved));
+        }
+        let spans = eights.matched_spans(address).unwrap_or_default();
+        if spans.len() != 12 || spans.iter().any(|span| span.end != span.start + 1 || span.rule != MatchRule::CharCount) || eights.nibble_affixes().is_some() {
+            return Err(format!("count(8)>=12 的区间为 {:?}", spans));
+        }
+        if eights.matcher().to_string() != "count(8)>=12" || count("count(*)>=10").matcher().to_string() != "count(any)>=10" {
+            return Err(format!("char_count 的匹配条件为 {}", eights.matcher()));
+        }
+        // 个数超过地址长度的一半时 16 个字符的事件互斥，任意字符的难度恰好少 4 位
+        let gap = count("count(5)>=25").difficulty_bits() - count("count(*)>=25").difficulty_bits();
+        if (gap - 4.0).abs() > 1e-6 {
+            return Err(format!("count(*)>=25 比 count(5)>=25 容易 {} 位，应为 4 位", gap));
+        }
+        
+        // 随机地址中满足的比例应接近估算的概率
+        let (single, free) = (count("count(a)>=6"), count("count(*)>=6"));
+        let mut seed = [0u8; 32];
+        let (mut single_hits, mut free_hits) = (0, 0);
+        for index in 0..CHAR_COUNT_SAMPLES {
+            seed[..8].copy_from_slice(&(index as u64).to_le_bytes());
+            let sample = hex::encode(&keccak::keccak256(&seed)[..20]);
+            single_hits += usize::from(single.matches(&sample));
+            free_hits += usize::from(free.matches(&sample));
+        }
+        for (parsed, hits) in [(&single, single_hits), (&free, free_hits)] {
+            let (observed, predicted) = (hits as f64 / CHAR_COUNT_SAMPLES as f64, (-parsed.difficulty_bits()).exp2());
+            if (observed - predicted).abs() > 0.02 {
+                return Err(format!("{} 的实际比例为 {:.4}，估算为 {:.4}", parsed.normalized(), observed, predicted));
+            }
+        }
+        
+        let config = SearchConfig {
+            limits: GenerationLimits { max_matches: Some(3), ..Default::default() },
+            rng_mode: RngMode::DeterministicTest,
+            test_seed: Some(19),
+            pattern_type: PatternType::CharCount,
+            ..SearchConfig::new("count(*)>=8")
+        };
+        config.validate()?;
+        let mut found = Vec::new();
+        crate::engine::search(&config, &CancellationToken::new(), || false, |event| {
+            if let SearchEvent::Found(key) = event {
+                found.push(count_characters(&hex::encode(key.address.as_bytes()), None));
+            }
+        });
+        if found.len() != 3 || found.iter().any(|achieved| achieved.count < 8) {
+            return Err(format!("char_count 搜索结果不正确: {:?}", found));
+        }
+        let _labels: Vec<String> = found.into_iter().map(achieved_count_label).collect();
+        Ok(())
+    }
+
+    #[test]
+    fn syntax_examples() -> Result<(), String> {
+        let entries = describe_syntax();
+        for entry in &entries {
+            if let Some(e) = &entry.error {
+                return Err(format!("语法 {} 的示例 {} 无效: {}", entry.name, entry.example, e));
+            }
+            if !entry.example_matches {
+                return Err(format!("语法 {} 的示例 {} 与示例地址不匹配", entry.name, entry.example));
+            }
+        }
+        Ok(())
+    }
+}
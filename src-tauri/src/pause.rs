@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+
+/// 生成任务的暂停状态
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PauseState {
+    /// 正在运行
+    #[default]
+    Running,
+    /// 用户手动暂停
+    UserPaused,
+    /// 检测到用户活动后自动暂停
+    AutoPaused,
+    /// 达到会话输出上限后暂停（不会被自动恢复，提高上限后由用户恢复）
+    OutputCapPaused,
+}
+
+/// 暂停状态的变化跟踪：生成循环定期观察当前状态，据此发送暂停和恢复事件
+#[derive(Debug, Clone)]
+pub struct PauseWatcher {
+    /// 上一次观察到的状态
+    last: PauseState,
+}
+
+impl PauseWatcher {
+    /**
+     * 从运行状态开始跟踪
+     */
+    pub fn new() -> Self {
+        PauseWatcher { last: PauseState::Running }
+    }
+
+    /**
+     * 观察当前状态
+     *
+     * @param state - 当前的暂停状态
+     * @returns 与上一次观察不同时返回新的状态（不同原因的暂停之间切换也算变化）
+     */
+    pub fn observe(&mut self, state: PauseState) -> Option<PauseState> {
+        (state != self.last).then(|| {
+            self.last = state;
+            state
+        })
+    }
+}
+
+/// 暂停和恢复的请求（作用于单个会话的暂停状态，见 sessions::update_pause）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseRequest {
+    /// 用户手动暂停；覆盖自动暂停，之后不会再被自动恢复
+    PauseByUser,
+    /// 自动暂停；用户已经手动暂停时保持不变
+    PauseAutomatically,
+    /// 达到会话输出上限时暂停；覆盖自动暂停，用户已经手动暂停时保持不变
+    PauseForOutputCap,
+    /// 用户手动恢复（无论是手动暂停、自动暂停还是因输出上限暂停）
+    ResumeByUser,
+    /// 自动恢复；只恢复自动暂停，用户手动暂停的任务保持暂停
+    ResumeAutomatically,
+}
+
+impl PauseState {
+    /**
+     * 应用暂停或恢复的请求
+     *
+     * @param request - 请求
+     * @returns 状态发生变化时返回新的状态
+     */
+    pub fn apply(self, request: PauseRequest) -> Option<PauseState> {
+        let next = match (request, self) {
+            (PauseRequest::PauseByUser, _) => PauseState::UserPaused,
+            (PauseRequest::PauseAutomatically, PauseState::Running) => PauseState::AutoPaused,
+            (PauseRequest::PauseForOutputCap, PauseState::Running | PauseState::AutoPaused) => PauseState::OutputCapPaused,
+            (PauseRequest::ResumeByUser, _) => PauseState::Running,
+            (PauseRequest::ResumeAutomatically, PauseState::AutoPaused) => PauseState::Running,
+            _ => return None,
+        };
+        (next != self).then_some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entropy::RngMode;
+    use crate::engine::{CancellationToken, SearchConfig, SearchEvent};
+    use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+    use std::time::{Duration, Instant};
+
+    /**
+     * 暂停和恢复：状态变化（包括不同原因的暂停之间切换）各产生一次事件；暂停期间尝试次数不再增加，
+     * 暂停中取消仍然结束搜索并保留最后一个匹配
+     */
+    #[test]
+    fn pause_resume() -> Result<(), String> {
+        let mut watcher = PauseWatcher::new();
+        let states = [
+            PauseState::Running,
+            PauseState::UserPaused,
+            PauseState::UserPaused,
+            PauseState::Running,
+            PauseState::AutoPaused,
+            PauseState::OutputCapPaused,
+            PauseState::Running,
+            PauseState::Running,
+        ];
+        let transitions: Vec<PauseState> = states.iter().filter_map(|&state| watcher.observe(state)).collect();
+        let expected = [PauseState::UserPaused, PauseState::Running, PauseState::AutoPaused, PauseState::OutputCapPaused, PauseState::Running];
+        if transitions != expected {
+            return Err(format!("暂停状态变化为 {:?}，应为 {:?}", transitions, expected));
+        }
+        
+        // 第 3 个匹配等到汇总线程已经切换为暂停才返回，之后工作线程不再领取新的尝试；暂停一段时间后取消
+        let config = SearchConfig { rng_mode: RngMode::DeterministicTest, test_seed: Some(11), workers: Some(1), ..SearchConfig::new("8") };
+        let cancel = CancellationToken::new();
+        let matched = AtomicU64::new(0);
+        let paused_polls = AtomicU32::new(0);
+        let report = crate::engine::search_with(
+            &config,
+            &cancel,
+            || {
+                if matched.load(Ordering::SeqCst) < 3 {
+                    return false;
+                }
+                if paused_polls.fetch_add(1, Ordering::SeqCst) >= 20 {
+                    cancel.cancel();
+                }
+                true
+            },
+            |_| {
+                if matched.fetch_add(1, Ordering::SeqCst) + 1 == 3 {
+                    // 第二次询问时第一次的暂停已经通知了工作线程
+                    let deadline = Instant::now() + Duration::from_secs(5);
+                    while paused_polls.load(Ordering::SeqCst) < 2 && Instant::now() < deadline {
+                        std::thread::sleep(Duration::from_millis(1));
+                    }
+                }
+                true
+            },
+            |_: SearchEvent| {},
+        );
+        if report.stop.is_some() || report.last_found.is_none() || report.paused_duration == 0 {
+            return Err(format!("暂停中取消的结果不正确: {:?}", report.stop));
+        }
+        if report.attempts != 3 {
+            return Err(format!("暂停期间尝试次数从 3 增加到 {}", report.attempts));
+        }
+        Ok(())
+    }
+}
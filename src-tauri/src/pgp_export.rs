@@ -0,0 +1,557 @@
+use crate::chain::Chain;
+use crate::entropy::{RngMode, TEST_WATERMARK};
+use crate::formatting::{format_timestamp, NumberLocale};
+use crate::saved_wallets::SavedWallet;
+use crate::timestamps;
+use pgp::composed::{
+    ArmorOptions, Deserializable, Message, MessageBuilder, SignedPublicKey, SignedPublicSubKey, SignedSecretKey,
+};
+use pgp::crypto::hash::HashAlgorithm;
+use pgp::crypto::sym::SymmetricKeyAlgorithm;
+use pgp::packet::{Signature, SignatureType};
+use pgp::types::{KeyDetails, Password, Timestamp};
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// 导出文件（解密后的 JSON）格式版本
+pub const EXPORT_VERSION: u32 = 1;
+
+/// 导出的单条结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExportedWallet {
+    /// 会话内的序号
+    pub index: Option<u64>,
+    /// 钱包地址（以太坊地址为 0x + EIP-55 规范格式）
+    pub address: String,
+    /// 私钥
+    pub private_key: String,
+    /// 靓号模式
+    pub pattern: String,
+    /// 所属的链（无法判断时为空）
+    pub chain: Option<Chain>,
+    /// 地址的指纹短语（地址无法解析时为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+    /// 私钥的随机数来源（旧文件中没有记录时为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entropy: Option<RngMode>,
+    /// 确定性测试模式生成的测试钱包（不能用于真实资金）
+    #[serde(default)]
+    pub test_wallet: bool,
+}
+
+/// 会话的全部结果（加密前的明文）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConsolidatedResults {
+    /// 格式版本
+    pub version: u32,
+    /// 会话标识
+    pub session_id: String,
+    /// 导出时间（RFC 3339）
+    pub exported_at: String,
+    /// 会话备注
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// 包含测试钱包时的水印说明（没有测试钱包时没有该字段）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_watermark: Option<String>,
+    /// 结果列表
+    pub wallets: Vec<ExportedWallet>,
+}
+
+impl ConsolidatedResults {
+    /**
+     * 由结果文件中的记录组装导出内容
+     *
+     * @param session_id - 会话标识
+     * @param wallets - 结果记录
+     * @param note - 会话备注（可选）
+     */
+    pub fn new(session_id: &str, wallets: Vec<SavedWallet>, note: Option<String>) -> Self {
+        let tests = wallets.iter().filter(|w| w.test_wallet).count();
+        ConsolidatedResults {
+            version: EXPORT_VERSION,
+            session_id: session_id.to_string(),
+            exported_at: timestamps::now_rfc3339(),
+            note,
+            test_watermark: (tests > 0).then(|| {
+                format!("{}：其中 {} 个钱包由确定性测试模式生成，私钥可复现，切勿用于真实资金", TEST_WATERMARK, tests)
+            }),
+            wallets: wallets
+                .into_iter()
+                .map(|w| ExportedWallet {
+                    index: w.index,
+                    address: w.canonical_address(),
+                    private_key: w.private_key,
+                    pattern: w.pattern,
+                    chain: w.chain,
+                    fingerprint: w.fingerprint,
+                    entropy: w.entropy,
+                    test_wallet: w.test_wallet,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 加密导出结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EncryptedExport {
+    /// 输出文件路径
+    pub output_path: String,
+    /// 收件人密钥指纹
+    pub recipient_fingerprint: String,
+    /// 实际用于加密的子密钥指纹
+    pub encryption_key_fingerprint: String,
+    /// 签名密钥指纹（未签名时为空）
+    pub signed_by: Option<String>,
+    /// 导出的结果条数
+    pub wallet_count: usize,
+    /// 是否已用私钥解密输出文件并与明文比对（只有签名密钥就是收件人密钥时才能做到）
+    pub decrypt_verified: bool,
+}
+
+/// 子密钥的用途
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum KeyUsage {
+    Encrypt,
+    Sign,
+}
+
+/// 密钥组件不可用的原因
+enum Unusable {
+    /// 已被吊销
+    Revoked,
+    /// 已过期（过期时间）
+    Expired(SystemTime),
+    /// 用途标志不包含所需用途
+    WrongUsage,
+}
+
+/**
+ * 读取密钥：以 -----BEGIN 开头时视为 ASCII armor 文本，否则视为文件路径
+ *
+ * @param input - 密钥文件路径或 armor 文本
+ * @param what - 错误信息中的密钥名称
+ */
+fn read_key_input(input: &str, what: &str) -> Result<Vec<u8>, String> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err(format!("未提供{}", what));
+    }
+    if trimmed.starts_with("-----BEGIN") {
+        return Ok(trimmed.as_bytes().to_vec());
+    }
+    fs::read(trimmed).map_err(|e| format!("无法读取{}文件 {}: {}", what, trimmed, e))
+}
+
+fn format_time(time: SystemTime) -> String {
+    format_timestamp(&timestamps::to_rfc3339(time.into()), NumberLocale::SimplifiedChinese)
+}
+
+/**
+ * 签名是否由该密钥本身签发（通过签发者指纹或密钥 ID 判断）
+ */
+fn is_self_issued(sig: &Signature, key: &impl KeyDetails) -> bool {
+    let fingerprint = key.fingerprint();
+    let key_id = key.legacy_key_id();
+    sig.issuer_fingerprint().into_iter().any(|f| *f == fingerprint)
+        || sig.issuer_key_id().into_iter().any(|id| *id == key_id)
+}
+
+/**
+ * 取最新的一个签名（签名时间最晚）
+ */
+fn latest<'a>(sigs: impl Iterator<Item = &'a Signature>) -> Option<&'a Signature> {
+    sigs.max_by_key(|sig| sig.created().map(Timestamp::as_secs).unwrap_or(0))
+}
+
+/**
+ * 根据自签名检查密钥组件是否可用
+ *
+ * @param binding - 最新的自签名（用户 ID 认证或子密钥绑定签名）
+ * @param created - 组件创建时间
+ * @param revoked - 是否存在吊销签名
+ * @param usage - 需要的用途（为空时只检查吊销和过期）
+ */
+fn check_component(
+    binding: Option<&Signature>,
+    created: Timestamp,
+    revoked: bool,
+    usage: Option<KeyUsage>,
+) -> Result<(), Unusable> {
+    let Some(binding) = binding else {
+        return Err(Unusable::WrongUsage);
+    };
+    // 先检查用途，这样吊销和过期只针对用途相符的组件报告
+    let flags = binding.key_flags();
+    let allowed = match usage {
+        None => true,
+        Some(KeyUsage::Encrypt) => flags.encrypt_comms() || flags.encrypt_storage(),
+        Some(KeyUsage::Sign) => flags.sign(),
+    };
+    if !allowed {
+        return Err(Unusable::WrongUsage);
+    }
+    if revoked {
+        return Err(Unusable::Revoked);
+    }
+    if let Some(expiration) = binding.key_expiration_time() {
+        if expiration.as_secs() > 0 {
+            let expires_at = SystemTime::from(Timestamp::from_secs(created.as_secs().saturating_add(expiration.as_secs())));
+            if expires_at <= SystemTime::now() {
+                return Err(Unusable::Expired(expires_at));
+            }
+        }
+    }
+    Ok(())
+}
+
+/**
+ * 获取主密钥最新的自签名（直接签名或用户 ID 自认证）
+ */
+fn primary_self_signature(key: &SignedPublicKey) -> Option<&Signature> {
+    let user_sigs = key.details.users.iter().flat_map(|user| user.signatures.iter());
+    latest(
+        key.details
+            .direct_signatures
+            .iter()
+            .chain(user_sigs)
+            .filter(|sig| sig.typ() != Some(SignatureType::CertRevocation) && is_self_issued(sig, &key.primary_key)),
+    )
+}
+
+/**
+ * 检查主密钥本身没有被吊销或过期
+ *
+ * @param key - 公钥
+ * @param what - 错误信息中的密钥名称
+ */
+fn check_primary(key: &SignedPublicKey, what: &str) -> Result<(), String> {
+    let revoked = !key.details.revocation_signatures.is_empty();
+    match check_component(primary_self_signature(key), key.primary_key.created_at(), revoked, None) {
+        Ok(()) => Ok(()),
+        Err(Unusable::Revoked) => Err(format!("{}已被吊销", what)),
+        Err(Unusable::Expired(at)) => Err(format!("{}已于 {} 过期", what, format_time(at))),
+        Err(Unusable::WrongUsage) => Err(format!("{}缺少有效的自签名", what)),
+    }
+}
+
+/**
+ * 检查子密钥能否用于指定用途
+ */
+fn check_subkey(subkey: &SignedPublicSubKey, usage: KeyUsage) -> Result<(), Unusable> {
+    let capable = match usage {
+        KeyUsage::Encrypt => subkey.key.algorithm().can_encrypt(),
+        KeyUsage::Sign => subkey.key.algorithm().can_sign(),
+    };
+    if !capable {
+        return Err(Unusable::WrongUsage);
+    }
+    let binding = latest(subkey.signatures.iter().filter(|sig| sig.typ() == Some(SignatureType::SubkeyBinding)));
+    let revoked = subkey.signatures.iter().any(|sig| sig.typ() == Some(SignatureType::SubkeyRevocation));
+    check_component(binding, subkey.key.created_at(), revoked, Some(usage))
+}
+
+/**
+ * 说明为什么找不到可用的子密钥（有过期的优先说明过期，其次是吊销，最后是用途不符）
+ */
+fn no_usable_subkey(problems: &[Unusable], what: &str, usage: KeyUsage) -> String {
+    let usage_name = match usage {
+        KeyUsage::Encrypt => "加密",
+        KeyUsage::Sign => "签名",
+    };
+    let expired = problems
+        .iter()
+        .filter_map(|p| match p {
+            Unusable::Expired(at) => Some(*at),
+            _ => None,
+        })
+        .max();
+    if let Some(at) = expired {
+        return format!("{}的{}子密钥已于 {} 过期", what, usage_name, format_time(at));
+    }
+    if problems.iter().any(|p| matches!(p, Unusable::Revoked)) {
+        return format!("{}的{}子密钥已被吊销", what, usage_name);
+    }
+    format!("{}没有可用于{}的密钥（密钥用途标志不包含{}）", what, usage_name, usage_name)
+}
+
+/**
+ * 选择收件人证书中可用的加密子密钥
+ */
+fn select_encryption_subkey(key: &SignedPublicKey) -> Result<&SignedPublicSubKey, String> {
+    let mut problems = Vec::new();
+    for subkey in &key.public_subkeys {
+        match check_subkey(subkey, KeyUsage::Encrypt) {
+            Ok(()) => return Ok(subkey),
+            Err(problem) => problems.push(problem),
+        }
+    }
+    Err(no_usable_subkey(&problems, "收件人密钥", KeyUsage::Encrypt))
+}
+
+/**
+ * 解析收件人公钥并检查自签名、吊销和过期
+ *
+ * @param input - 公钥文件路径或 armor 文本
+ */
+pub fn parse_recipient_key(input: &str) -> Result<SignedPublicKey, String> {
+    let bytes = read_key_input(input, "收件人公钥")?;
+    if String::from_utf8_lossy(&bytes).contains("BEGIN PGP PRIVATE KEY BLOCK") {
+        return Err("收件人密钥应为公钥，请不要提供私钥".to_string());
+    }
+    let (key, _headers) =
+        SignedPublicKey::from_reader_single(&bytes[..]).map_err(|e| format!("无法解析收件人公钥: {}", e))?;
+    key.verify_bindings().map_err(|e| format!("收件人公钥的自签名无效: {}", e))?;
+    check_primary(&key, "收件人密钥")?;
+    Ok(key)
+}
+
+/**
+ * 解析本地签名私钥并检查自签名、吊销和过期
+ *
+ * @param input - 私钥文件路径或 armor 文本
+ */
+pub fn parse_signing_key(input: &str) -> Result<SignedSecretKey, String> {
+    let bytes = read_key_input(input, "签名私钥")?;
+    let (key, _headers) =
+        SignedSecretKey::from_reader_single(&bytes[..]).map_err(|e| format!("无法解析签名私钥: {}", e))?;
+    key.verify_bindings().map_err(|e| format!("签名私钥的自签名无效: {}", e))?;
+    check_primary(&key.to_public_key(), "签名密钥")?;
+    Ok(key)
+}
+
+/**
+ * 将明文加密给收件人，可选用本地私钥签名
+ *
+ * @param plaintext - 明文
+ * @param recipient - 收件人公钥
+ * @param signer - 签名私钥及其口令
+ * @returns (ASCII armor 格式的密文, 加密子密钥指纹, 签名密钥指纹)
+ */
+pub fn encrypt(
+    plaintext: &[u8],
+    recipient: &SignedPublicKey,
+    signer: Option<(&SignedSecretKey, &str)>,
+) -> Result<(String, String, Option<String>), String> {
+    let subkey = select_encryption_subkey(recipient)?;
+    let mut builder = MessageBuilder::from_bytes("", plaintext.to_vec()).seipd_v1(OsRng, SymmetricKeyAlgorithm::AES256);
+    builder.encrypt_to_key(OsRng, &subkey).map_err(|e| format!("加密失败: {}", e))?;
+
+    let mut signed_by = None;
+    if let Some((key, passphrase)) = signer {
+        let public = key.to_public_key();
+        let password = Password::from(passphrase);
+        let primary_ok = check_component(
+            primary_self_signature(&public),
+            key.primary_key.created_at(),
+            false,
+            Some(KeyUsage::Sign),
+        )
+        .is_ok()
+            && key.primary_key.algorithm().can_sign();
+        if primary_ok {
+            builder.sign(&key.primary_key, password, HashAlgorithm::Sha256);
+            signed_by = Some(key.primary_key.fingerprint().to_string());
+        } else {
+            let mut problems = Vec::new();
+            let mut chosen = None;
+            for secret in &key.secret_subkeys {
+                let public_subkey = SignedPublicSubKey::new(secret.key.public_key().clone(), secret.signatures.clone());
+                match check_subkey(&public_subkey, KeyUsage::Sign) {
+                    Ok(()) => {
+                        chosen = Some(secret);
+                        break;
+                    }
+                    Err(problem) => problems.push(problem),
+                }
+            }
+            let secret = chosen.ok_or_else(|| no_usable_subkey(&problems, "签名密钥", KeyUsage::Sign))?;
+            builder.sign(&secret.key, password, HashAlgorithm::Sha256);
+            signed_by = Some(secret.key.fingerprint().to_string());
+        }
+    }
+
+    let armored = builder.to_armored_string(OsRng, ArmorOptions::default()).map_err(|e| {
+        if signed_by.is_some() {
+            format!("加密或签名失败（请检查签名私钥的口令）: {}", e)
+        } else {
+            format!("加密失败: {}", e)
+        }
+    })?;
+    Ok((armored, subkey.key.fingerprint().to_string(), signed_by))
+}
+
+/**
+ * 用私钥解密密文，返回明文并校验签名（如有）
+ *
+ * @param armored - ASCII armor 格式的密文
+ * @param key - 收件人私钥
+ * @param passphrase - 私钥口令
+ */
+pub fn decrypt(armored: &str, key: &SignedSecretKey, passphrase: &str) -> Result<Vec<u8>, String> {
+    let (message, _headers) = Message::from_string(armored).map_err(|e| format!("无法解析加密文件: {}", e))?;
+    let mut message = message
+        .decrypt(&Password::from(passphrase), key)
+        .map_err(|e| format!("无法解密加密文件: {}", e))?;
+    if message.is_compressed() {
+        message = message.decompress().map_err(|e| format!("无法解压加密文件: {}", e))?;
+    }
+    let data = message.as_data_vec().map_err(|e| format!("无法读取解密内容: {}", e))?;
+    if message.is_signed() {
+        let public = key.to_public_key();
+        let verified = message.verify(&public.primary_key).is_ok()
+            || public.public_subkeys.iter().any(|subkey| message.verify(&subkey.key).is_ok());
+        if !verified {
+            return Err("加密文件的签名校验失败".to_string());
+        }
+    }
+    Ok(data)
+}
+
+/**
+ * 将会话结果加密导出给收件人，写入后读回校验
+ *
+ * 签名私钥就是收件人密钥时（加密给自己），会用私钥实际解密输出文件并与明文比对；
+ * 否则只能确认输出文件可以解析，并且是一条加密消息。
+ *
+ * @param results - 会话的全部结果
+ * @param recipient - 收件人公钥
+ * @param signer - 签名私钥及其口令
+ * @param output - 输出文件路径
+ */
+pub fn export(
+    results: &ConsolidatedResults,
+    recipient: &SignedPublicKey,
+    signer: Option<(&SignedSecretKey, &str)>,
+    output: &Path,
+) -> Result<EncryptedExport, String> {
+    let plaintext = serde_json::to_vec_pretty(results).map_err(|e| format!("无法序列化结果: {}", e))?;
+    let (armored, encryption_key_fingerprint, signed_by) = encrypt(&plaintext, recipient, signer)?;
+
+    if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("无法创建输出目录: {}", e))?;
+    }
+    fs::write(output, &armored).map_err(|e| format!("无法写入加密文件: {}", e))?;
+
+    // 读回刚写入的文件，防止写入不完整
+    let written = fs::read_to_string(output).map_err(|e| format!("无法读回加密文件: {}", e))?;
+    let own_key = signer.filter(|(key, _)| key.primary_key.fingerprint() == recipient.primary_key.fingerprint());
+    let decrypt_verified = match own_key {
+        Some((key, passphrase)) => {
+            if decrypt(&written, key, passphrase)? != plaintext {
+                return Err("加密文件校验失败：解密内容与原始结果不一致".to_string());
+            }
+            true
+        }
+        None => {
+            let (message, _headers) = Message::from_string(&written).map_err(|e| format!("加密文件校验失败: {}", e))?;
+            if !message.is_encrypted() {
+                return Err("加密文件校验失败：输出不是加密消息".to_string());
+            }
+            false
+        }
+    };
+
+    Ok(EncryptedExport {
+        output_path: output.to_string_lossy().to_string(),
+        recipient_fingerprint: recipient.primary_key.fingerprint().to_string(),
+        encryption_key_fingerprint,
+        signed_by,
+        wallet_count: results.wallets.len(),
+        decrypt_verified,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::saved_wallets::{self, CsvPrivateKeys};
+    use pgp::composed::{EncryptionCaps, KeyType, SecretKeyParamsBuilder, SubkeyParamsBuilder};
+    use pgp::crypto::ecc_curve::ECCCurve;
+    use crate::self_test::KNOWN_KEYS;
+
+    /**
+     * 生成临时的 OpenPGP 测试密钥（Ed25519 主密钥，可选 Curve25519 加密子密钥）
+     */
+    fn generate_pgp_key(with_encryption: bool) -> Result<SignedSecretKey, String> {
+        let mut subkeys = Vec::new();
+        if with_encryption {
+            let mut encrypt_key = SubkeyParamsBuilder::default();
+            encrypt_key
+                .key_type(KeyType::ECDH(ECCCurve::Curve25519Legacy))
+                .can_sign(false)
+                .can_encrypt(EncryptionCaps::All);
+            subkeys.push(encrypt_key.build().map_err(|e| e.to_string())?);
+        }
+        let mut params = SecretKeyParamsBuilder::default();
+        params
+            .key_type(KeyType::Ed25519Legacy)
+            .can_certify(true)
+            .can_sign(true)
+            .primary_user_id("Fancy Wallet Self Test <selftest@localhost>".into())
+            .subkeys(subkeys);
+        params
+            .build()
+            .map_err(|e| e.to_string())?
+            .generate(OsRng)
+            .map_err(|e| format!("无法生成测试密钥: {}", e))
+    }
+
+    #[test]
+    fn pgp_export() -> Result<(), String> {
+        let dir = std::env::temp_dir().join(format!("fancy-wallet-test-pgp-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).map_err(|e| format!("无法创建临时目录: {}", e))?;
+        
+        let result = (|| {
+            let secret = generate_pgp_key(true)?;
+            let public_armored = secret.to_public_key().to_armored_string(ArmorOptions::default()).map_err(|e| e.to_string())?;
+            let secret_armored = secret.to_armored_string(ArmorOptions::default()).map_err(|e| e.to_string())?;
+            let recipient = parse_recipient_key(&public_armored)?;
+            let signer = parse_signing_key(&secret_armored)?;
+            
+            let (private_key, address) = KNOWN_KEYS[0];
+            let results = ConsolidatedResults::new(
+                "selftest",
+                vec![saved_wallets::SavedWallet {
+                    address: format!("0x{}", address),
+                    private_key: private_key.to_string(),
+                    pattern: "*".to_string(),
+                    index: Some(1),
+                    chain: Some(Chain::Ethereum),
+                    chain_inferred: false,
+                    fingerprint: None,
+                    entropy: None,
+                    test_wallet: false,
+                    mnemonic: None,
+                    created_at: None,
+                    attempts: None,
+                    duration_ms: None,
+                    private_keys: CsvPrivateKeys::Full,
+                    file: dir.join("wallet_selftest.csv"),
+                }],
+                None,
+            );
+            let exported = export(&results, &recipient, Some((&signer, "")), &dir.join("results.asc"))?;
+            if !exported.decrypt_verified {
+                return Err("加密给自己时没有执行解密校验".to_string());
+            }
+            
+            let sign_only = generate_pgp_key(false)?;
+            let sign_only_armored = sign_only.to_public_key().to_armored_string(ArmorOptions::default()).map_err(|e| e.to_string())?;
+            let sign_only = parse_recipient_key(&sign_only_armored)?;
+            match export(&results, &sign_only, None, &dir.join("rejected.asc")) {
+                Ok(_) => Err("没有加密子密钥的收件人未被拒绝".to_string()),
+                Err(e) if e.contains("用途") => Ok(()),
+                Err(e) => Err(format!("仅签名的密钥报告了意外的错误: {}", e)),
+            }
+        })();
+        
+        let _ = std::fs::remove_dir_all(&dir);
+        result
+    }
+}
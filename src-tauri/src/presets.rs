@@ -0,0 +1,216 @@
+use crate::pattern::{self, DifficultyClass, MatchMode, ParsedPattern, PatternType};
+use serde::{Deserialize, Serialize};
+
+/// 内置的模式预设（列表中的难度在调用时由解析器实时计算，与实际的匹配规则一致）
+pub struct PatternPreset {
+    /// 预设标识（generate_fancy_wallet 的 preset_id）
+    pub id: &'static str,
+    /// 显示名称
+    pub name: &'static str,
+    /// 简要说明
+    pub description: &'static str,
+    /// 模式字符串（leading_zeros 预设为空）
+    pub pattern: Option<&'static str>,
+    /// 比较方式（为空时使用模式自己的默认值）
+    pub match_mode: Option<MatchMode>,
+    /// leading_zeros 预设地址开头最少的零的个数
+    pub min_zero_nibbles: Option<u8>,
+}
+
+impl PatternPreset {
+    /**
+     * 预设的模式类型
+     */
+    pub fn pattern_type(&self) -> PatternType {
+        match self.min_zero_nibbles {
+            Some(_) => PatternType::LeadingZeros,
+            None => PatternType::Standard,
+        }
+    }
+
+    /**
+     * 引擎实际使用的模式字符串（leading_zeros 预设为 zeros>=N）
+     */
+    pub fn engine_pattern(&self) -> String {
+        match self.min_zero_nibbles {
+            Some(min) => pattern::leading_zeros_pattern(min),
+            None => self.pattern.unwrap_or_default().to_string(),
+        }
+    }
+
+    /**
+     * 按生成时的默认选项（以太坊地址、不区分大小写）解析预设的模式
+     */
+    pub fn parsed(&self) -> ParsedPattern {
+        ParsedPattern::with_options(&self.engine_pattern(), self.pattern_type(), false).with_match_mode(self.match_mode)
+    }
+}
+
+/// 内置的预设（测试确认每个预设都能通过生成前的校验）
+pub const PATTERN_PRESETS: &[PatternPreset] = &[
+    PatternPreset {
+        id: "dead",
+        name: "dead 开头",
+        description: "地址以 dead 开头",
+        pattern: Some("dead"),
+        match_mode: Some(MatchMode::Prefix),
+        min_zero_nibbles: None,
+    },
+    PatternPreset {
+        id: "beef",
+        name: "beef 结尾",
+        description: "地址以 beef 结尾",
+        pattern: Some("beef"),
+        match_mode: Some(MatchMode::Suffix),
+        min_zero_nibbles: None,
+    },
+    PatternPreset {
+        id: "cafe",
+        name: "cafe 开头",
+        description: "地址以 cafe 开头",
+        pattern: Some("cafe"),
+        match_mode: Some(MatchMode::Prefix),
+        min_zero_nibbles: None,
+    },
+    PatternPreset {
+        id: "8888",
+        name: "8888 首尾",
+        description: "地址同时以 8888 开头和结尾",
+        pattern: Some("8888"),
+        match_mode: Some(MatchMode::Both),
+        min_zero_nibbles: None,
+    },
+    PatternPreset {
+        id: "zeros6",
+        name: "6 个前导零",
+        description: "地址开头至少有 6 个 0（作为合约地址在 calldata 中更便宜）",
+        pattern: None,
+        match_mode: None,
+        min_zero_nibbles: Some(6),
+    },
+    PatternPreset {
+        id: "aaaa",
+        name: "首尾四连号",
+        description: "前 4 位是同一个字符，后 4 位也是同一个字符（例如 1111...2222）",
+        pattern: Some("*aaaa*"),
+        match_mode: None,
+        min_zero_nibbles: None,
+    },
+];
+
+/// 模式预设列表的条目（list_pattern_presets 的返回值）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PresetInfo {
+    /// 预设标识
+    pub id: String,
+    /// 显示名称
+    pub name: String,
+    /// 简要说明
+    pub description: String,
+    /// 引擎实际使用的模式字符串
+    pub pattern: String,
+    /// 模式类型
+    pub pattern_type: PatternType,
+    /// 实际使用的比较方式
+    pub match_mode: MatchMode,
+    /// 引擎实际执行的匹配条件
+    pub matcher: String,
+    /// 解析器对模式的解读
+    pub interpretation: String,
+    /// 难度（期望尝试次数的以 2 为底的对数）
+    pub difficulty_bits: f64,
+    /// 找到一个匹配的期望尝试次数
+    pub expected_attempts: f64,
+    /// 难度等级
+    pub difficulty_class: DifficultyClass,
+}
+
+/**
+ * 按标识查找预设
+ *
+ * @param id - 预设标识
+ */
+pub fn find(id: &str) -> Result<&'static PatternPreset, String> {
+    PATTERN_PRESETS.iter().find(|preset| preset.id == id.trim()).ok_or_else(|| {
+        let ids: Vec<&str> = PATTERN_PRESETS.iter().map(|preset| preset.id).collect();
+        format!("未知的模式预设: {}（可用的预设: {}）", id, ids.join(", "))
+    })
+}
+
+/**
+ * 列出全部预设：对每个预设实际运行解析器和难度估算
+ */
+pub fn list() -> Vec<PresetInfo> {
+    PATTERN_PRESETS
+        .iter()
+        .map(|preset| {
+            let parsed = preset.parsed();
+            let difficulty_bits = parsed.difficulty_bits();
+            PresetInfo {
+                id: preset.id.to_string(),
+                name: preset.name.to_string(),
+                description: preset.description.to_string(),
+                pattern: preset.engine_pattern(),
+                pattern_type: preset.pattern_type(),
+                match_mode: parsed.match_mode(),
+                matcher: parsed.matcher().to_string(),
+                interpretation: parsed.interpretation(),
+                difficulty_bits,
+                expected_attempts: parsed.expected_attempts(),
+                difficulty_class: DifficultyClass::from_bits(difficulty_bits),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::templates::GenerationParams;
+
+    /**
+     * 模式预设：标识不重复，每个预设都能通过生成前的校验，列表中的难度与解析器的估算一致，未知的标识被拒绝
+     */
+    #[test]
+    fn pattern_presets_cases() -> Result<(), String> {
+        let list = list();
+        let mut ids: Vec<&str> = list.iter().map(|info| info.id.as_str()).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        if ids.len() != PATTERN_PRESETS.len() {
+            return Err(format!("预设标识有重复: {:?}", ids));
+        }
+        for (preset, info) in PATTERN_PRESETS.iter().zip(&list) {
+            let params = GenerationParams {
+                pattern: preset.engine_pattern(),
+                pattern_type: Some(preset.pattern_type()),
+                match_mode: preset.match_mode,
+                ..GenerationParams::default()
+            };
+            params.validate()?;
+            params.validate_patterns().map_err(|e| format!("预设 {} 无法通过生成前的校验: {}", preset.id, e))?;
+            let parsed = ParsedPattern::with_options(&params.pattern, preset.pattern_type(), false).with_match_mode(preset.match_mode);
+            if info.difficulty_bits != parsed.difficulty_bits() || info.expected_attempts != parsed.expected_attempts() || info.matcher != parsed.matcher().to_string() {
+                return Err(format!("预设 {} 的难度为 {} 位，解析器估算为 {} 位", preset.id, info.difficulty_bits, parsed.difficulty_bits()));
+            }
+        }
+        // (预设, 难度位数, 应当匹配的地址, 不应匹配的地址)
+        let cases = [
+            ("dead", 16.0, "dead0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0f0a", "a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0f0dead"),
+            ("beef", 16.0, "a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0f0beef", "beef0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0f0a"),
+            ("8888", 32.0, "8888a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d08888", "8888a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0f0"),
+            ("zeros6", 24.0, "000000b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0f0", "00000ab0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0f0"),
+            ("aaaa", 24.0, "1111a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d02222", "1112a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d02222"),
+        ];
+        for (id, bits, matching, other) in cases {
+            let parsed = find(id)?.parsed();
+            if (parsed.difficulty_bits() - bits).abs() > 1e-9 || !parsed.matches(matching) || parsed.matches(other) {
+                return Err(format!("预设 {} 的难度为 {} 位（应为 {}），与 {} 和 {} 的匹配结果不正确", id, parsed.difficulty_bits(), bits, matching, other));
+            }
+        }
+        if find("no-such-preset").is_ok() || find(" dead ").map(|preset| preset.id) != Ok("dead") {
+            return Err("预设标识的查找不正确".to_string());
+        }
+        Ok(())
+    }
+}
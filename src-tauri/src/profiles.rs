@@ -0,0 +1,308 @@
+use crate::timestamps;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 默认用户配置名称
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// 应用标识（与 tauri.conf.json 中的 identifier 一致，用于定位应用数据目录）
+const APP_IDENTIFIER: &str = "com.sega.fancy-wallet";
+
+/// 应用数据目录根部可能存在的、需要按配置隔离的数据文件
+const LEGACY_DATA_FILES: &[&str] = &["settings.json", "history.json", "wallets.db"];
+
+/// 用户配置名称的最大长度
+const MAX_PROFILE_NAME_LENGTH: usize = 32;
+
+/// 单个用户配置
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Profile {
+    /// 配置名称
+    pub name: String,
+    /// 默认保存路径（为空时使用 Documents 目录）
+    pub default_save_path: Option<String>,
+    /// 创建时间
+    pub created_at: String,
+}
+
+/// 用户配置列表（保存在应用数据目录的 profiles.json 中）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProfileStore {
+    /// 当前使用的配置
+    pub active: String,
+    /// 全部配置
+    pub profiles: Vec<Profile>,
+}
+
+impl ProfileStore {
+    /**
+     * 获取当前使用的配置
+     */
+    pub fn active_profile(&self) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.name == self.active)
+    }
+}
+
+/**
+ * 获取应用数据目录
+ */
+pub fn app_data_dir() -> Result<PathBuf, String> {
+    Ok(directories::BaseDirs::new()
+        .ok_or("无法获取用户目录")?
+        .data_dir()
+        .join(APP_IDENTIFIER))
+}
+
+/**
+ * 获取指定配置的数据目录（设置、历史等按配置隔离存放于此）
+ * 
+ * @param name - 配置名称
+ */
+pub fn profile_data_dir(name: &str) -> Result<PathBuf, String> {
+    Ok(app_data_dir()?.join("profiles").join(name))
+}
+
+/**
+ * 校验配置名称：不能为空、不能过长，只允许字母、数字、- 和 _
+ */
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("配置名称不能为空".to_string());
+    }
+    if name.chars().count() > MAX_PROFILE_NAME_LENGTH {
+        return Err(format!("配置名称不能超过 {} 个字符", MAX_PROFILE_NAME_LENGTH));
+    }
+    if let Some(c) = name.chars().find(|c| !(c.is_alphanumeric() || *c == '-' || *c == '_')) {
+        return Err(format!("配置名称不能包含字符 '{}'", c));
+    }
+    Ok(())
+}
+
+fn store_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("profiles.json")
+}
+
+fn new_profile(name: &str, default_save_path: Option<String>) -> Profile {
+    Profile {
+        name: name.to_string(),
+        default_save_path,
+        created_at: timestamps::now_rfc3339(),
+    }
+}
+
+/**
+ * 保存配置列表（先写临时文件再重命名）
+ */
+pub fn save_store(data_dir: &Path, store: &ProfileStore) -> Result<(), String> {
+    fs::create_dir_all(data_dir).map_err(|e| format!("无法创建应用数据目录: {}", e))?;
+    let json = serde_json::to_string_pretty(store).map_err(|e| format!("无法序列化配置列表: {}", e))?;
+    let path = store_path(data_dir);
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, json).map_err(|e| format!("无法写入配置列表: {}", e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("无法写入配置列表: {}", e))
+}
+
+/**
+ * 读取配置列表（配置列表由启动迁移创建；迁移失败处于只读模式时返回内存中的 default 配置）
+ * 
+ * @param data_dir - 应用数据目录
+ * @returns 配置列表
+ */
+pub fn load(data_dir: &Path) -> Result<ProfileStore, String> {
+    let path = store_path(data_dir);
+    if !path.exists() {
+        return Ok(default_store());
+    }
+    let data = fs::read_to_string(&path).map_err(|e| format!("无法读取配置列表: {}", e))?;
+    serde_json::from_str(&data).map_err(|e| format!("配置列表格式错误: {}", e))
+}
+
+fn default_store() -> ProfileStore {
+    ProfileStore {
+        active: DEFAULT_PROFILE.to_string(),
+        profiles: vec![new_profile(DEFAULT_PROFILE, None)],
+    }
+}
+
+/**
+ * 迁移 v1 涉及的路径：配置列表、default 配置目录，以及根部的旧数据文件和它们的新位置
+ * 
+ * @param data_dir - 应用数据目录
+ */
+pub fn legacy_layout_paths(data_dir: &Path) -> Vec<PathBuf> {
+    let default_dir = data_dir.join("profiles").join(DEFAULT_PROFILE);
+    let mut paths = vec![data_dir.to_path_buf(), data_dir.join("profiles"), default_dir.clone(), store_path(data_dir)];
+    for name in LEGACY_DATA_FILES {
+        paths.push(data_dir.join(name));
+        paths.push(default_dir.join(name));
+    }
+    paths
+}
+
+/**
+ * 迁移 v1：创建 default 配置并把应用数据目录根部已有的数据文件移入其中
+ * 
+ * 旧版本没有配置的概念，输出文件直接位于 Documents/FancyWallets，
+ * default 配置原地沿用该目录，因此已有的钱包文件不需要移动。
+ * 已经有配置列表的数据目录（由迁移框架之前的版本创建）不做修改。
+ * 
+ * @param data_dir - 应用数据目录
+ * @returns 所做修改的说明
+ */
+pub fn migrate_legacy_layout(data_dir: &Path) -> Result<Vec<String>, String> {
+    if store_path(data_dir).exists() {
+        return Ok(Vec::new());
+    }
+    
+    let mut changes = Vec::new();
+    let default_dir = data_dir.join("profiles").join(DEFAULT_PROFILE);
+    fs::create_dir_all(&default_dir).map_err(|e| format!("无法创建配置目录: {}", e))?;
+    for name in LEGACY_DATA_FILES {
+        let legacy_path = data_dir.join(name);
+        if legacy_path.is_file() {
+            fs::rename(&legacy_path, default_dir.join(name))
+                .map_err(|e| format!("无法迁移数据文件 {}: {}", name, e))?;
+            changes.push(format!("{} 已移入 default 配置", name));
+        }
+    }
+    
+    save_store(data_dir, &default_store())?;
+    changes.push("已创建 default 配置".to_string());
+    Ok(changes)
+}
+
+/**
+ * 创建新的配置
+ * 
+ * @param store - 配置列表
+ * @param name - 配置名称
+ * @param default_save_path - 默认保存路径（可选）
+ */
+pub fn create(store: &mut ProfileStore, name: &str, default_save_path: Option<String>) -> Result<Profile, String> {
+    validate_name(name)?;
+    if store.profiles.iter().any(|p| p.name == name) {
+        return Err(format!("配置已存在: {}", name));
+    }
+    let profile = new_profile(name, default_save_path);
+    store.profiles.push(profile.clone());
+    Ok(profile)
+}
+
+/**
+ * 切换当前使用的配置
+ * 
+ * @param store - 配置列表
+ * @param name - 配置名称
+ */
+pub fn switch(store: &mut ProfileStore, name: &str) -> Result<(), String> {
+    if !store.profiles.iter().any(|p| p.name == name) {
+        return Err(format!("配置不存在: {}", name));
+    }
+    store.active = name.to_string();
+    Ok(())
+}
+
+/**
+ * 计算配置的默认输出目录
+ * 
+ * default 配置沿用 FancyWallets 根目录，其它配置使用 FancyWallets/profiles/<名称>；
+ * 配置自定义了默认保存路径时，直接使用该路径下的 FancyWallets 目录。
+ * 
+ * @param profile - 用户配置
+ * @param documents_dir - Documents 目录
+ */
+pub fn default_wallets_dir(profile: &Profile, documents_dir: &Path) -> PathBuf {
+    match &profile.default_save_path {
+        Some(path) => PathBuf::from(path).join("FancyWallets"),
+        None if profile.name == DEFAULT_PROFILE => documents_dir.join("FancyWallets"),
+        None => documents_dir.join("FancyWallets").join("profiles").join(&profile.name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::Chain;
+    use crate::saved_wallets;
+    use crate::settings::{self, AppSettings};
+    use crate::test_support::with_data_dir;
+
+    fn profile_dir(data_dir: &Path, name: &str) -> PathBuf {
+        data_dir.join("profiles").join(name)
+    }
+
+    /**
+     * 旧版本的设置：根部的 settings.json 移入 default 配置后按原来的值读取，其它配置仍是默认设置
+     */
+    #[test]
+    fn legacy_settings_migration() -> Result<(), String> {
+        with_data_dir("legacy-settings", |dir| {
+            fs::write(dir.join(settings::SETTINGS_FILE), "{\"workers\":4,\"output_format\":\"jsonl\",\"notify_on_match\":true}").map_err(|e| e.to_string())?;
+            let changes = migrate_legacy_layout(dir)?;
+            if changes.len() != 2 || dir.join(settings::SETTINGS_FILE).exists() {
+                return Err(format!("旧版本的设置文件没有移入 default 配置: {:?}", changes));
+            }
+            let migrated = settings::load(&profile_dir(dir, DEFAULT_PROFILE), true)?;
+            if migrated.settings.workers != Some(4) || !migrated.settings.notify_on_match.unwrap_or_default() || migrated.warning.is_some() {
+                return Err(format!("迁移后的设置为 {:?}", migrated));
+            }
+
+            let mut store = load(dir)?;
+            create(&mut store, "work", None)?;
+            save_store(dir, &store)?;
+            if settings::load(&profile_dir(dir, "work"), true)?.settings != AppSettings::default() {
+                return Err("新建的配置读到了 default 配置的设置".to_string());
+            }
+            if !migrate_legacy_layout(dir)?.is_empty() || load(dir)?.profiles.len() != 2 {
+                return Err("已有配置列表时再次迁移修改了配置".to_string());
+            }
+            Ok(())
+        })
+    }
+
+    /**
+     * 配置隔离：各配置的设置和结果目录互不可见（default 配置的 FancyWallets 根目录不会列出其它配置子目录中的会话），
+     * 切换到不存在的配置被拒绝
+     */
+    #[test]
+    fn profile_isolation() -> Result<(), String> {
+        with_data_dir("profile-isolation", |dir| {
+            let documents = dir.join("Documents");
+            let mut store = default_store();
+            let work = create(&mut store, "work", None)?;
+            if create(&mut store, "work", None).is_ok() || create(&mut store, "../work", None).is_ok() {
+                return Err("重复或无效的配置名称没有被拒绝".to_string());
+            }
+            if switch(&mut store, "missing").is_ok() || store.active != DEFAULT_PROFILE {
+                return Err("切换到不存在的配置没有被拒绝".to_string());
+            }
+            let default = store.active_profile().ok_or("没有当前配置")?.clone();
+
+            let sessions = [(&default, "888_20260101_000000"), (&work, "888_20260102_000000")];
+            for (profile, session_id) in sessions {
+                let chain_dir = Chain::Ethereum.dir(&default_wallets_dir(profile, &documents));
+                fs::create_dir_all(&chain_dir).map_err(|e| e.to_string())?;
+                fs::write(chain_dir.join(format!("wallet_{}.csv", session_id)), "address,private_key,pattern\n").map_err(|e| e.to_string())?;
+            }
+            for (profile, session_id) in sessions {
+                let listed = saved_wallets::list_sessions(&default_wallets_dir(profile, &documents))?;
+                if listed != [session_id] {
+                    return Err(format!("配置 {} 的会话历史为 {:?}", profile.name, listed));
+                }
+            }
+
+            settings::save(&profile_dir(dir, "work"), &AppSettings { workers: Some(2), ..AppSettings::default() })?;
+            switch(&mut store, "work")?;
+            if store.active_profile().map(|profile| profile.name.as_str()) != Some("work") {
+                return Err("切换后的当前配置不是 work".to_string());
+            }
+            if settings::load(&profile_dir(dir, DEFAULT_PROFILE), true)?.settings != AppSettings::default()
+                || settings::load(&profile_dir(dir, "work"), true)?.settings.workers != Some(2)
+            {
+                return Err("配置的设置没有隔离".to_string());
+            }
+            Ok(())
+        })
+    }
+}
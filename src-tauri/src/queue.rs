@@ -0,0 +1,555 @@
+use crate::hexutil::Address;
+use crate::stop::{StopReason, StopRecord};
+use crate::templates::GenerationParams;
+use crate::timestamps;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// 队列文件名（位于用户配置的数据目录中，与模板放在一起）
+const QUEUE_FILE: &str = "queue.json";
+
+/// 队列文件格式版本
+pub const QUEUE_VERSION: u32 = 1;
+
+/// 同时运行的最大任务数（取消标志、暂停状态和钱包序号由所有会话共用，本版本只能依次运行）
+pub const MAX_CONCURRENT_JOBS: u32 = 1;
+
+/// 读写队列文件时持有的锁（命令和队列线程都会修改队列）
+static QUEUE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 队列线程即将开始的任务（会话开始时取走，用于给会话事件标记任务标识）
+static STARTING_JOB: Mutex<Option<String>> = Mutex::new(None);
+
+/// 最近结束的队列会话
+static FINISHED_RUN: Mutex<Option<JobRun>> = Mutex::new(None);
+
+/// 应用正在退出：队列线程不再开始新的任务
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+
+/// 任务状态
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    /// 等待运行
+    Pending,
+    /// 正在运行
+    Running,
+    /// 找到了至少一个钱包
+    Completed,
+    /// 会话结束但没有找到钱包（达到停止条件、被取消或应用退出）
+    Stopped,
+    /// 会话没能开始或运行中出错
+    Failed,
+}
+
+impl JobStatus {
+    fn is_finished(self) -> bool {
+        matches!(self, JobStatus::Completed | JobStatus::Stopped | JobStatus::Failed)
+    }
+}
+
+/// 队列中的一个生成任务
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QueueJob {
+    /// 任务标识
+    pub id: String,
+    /// 生成参数
+    pub params: GenerationParams,
+    /// 状态
+    pub status: JobStatus,
+    /// 加入队列的时间
+    pub enqueued_at: String,
+    /// 开始运行的时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+    /// 结束的时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<String>,
+    /// 任务对应的会话标识
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// 会话的停止原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop: Option<StopRecord>,
+    /// 最后找到的钱包地址
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<Address>,
+    /// 出错或中断的原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 队列选项
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct QueueOptions {
+    /// 任务出错时停止队列（默认跳过出错的任务继续运行后面的任务）
+    pub stop_queue_on_error: bool,
+    /// 同时运行的任务数（本版本最多 MAX_CONCURRENT_JOBS 个）
+    pub concurrency: u32,
+}
+
+impl Default for QueueOptions {
+    fn default() -> Self {
+        QueueOptions { stop_queue_on_error: false, concurrency: 1 }
+    }
+}
+
+/// 队列文件内容
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QueueState {
+    /// 格式版本
+    pub version: u32,
+    /// 队列选项
+    #[serde(default)]
+    pub options: QueueOptions,
+    /// 因任务出错而停止的队列（加入任务或修改选项后继续）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub halted_by: Option<String>,
+    /// 下一个任务的序号
+    #[serde(default)]
+    pub next_job: u64,
+    /// 任务（按运行顺序）
+    #[serde(default)]
+    pub jobs: Vec<QueueJob>,
+}
+
+impl Default for QueueState {
+    fn default() -> Self {
+        QueueState { version: QUEUE_VERSION, options: QueueOptions::default(), halted_by: None, next_job: 1, jobs: Vec::new() }
+    }
+}
+
+impl QueueState {
+    /**
+     * 是否还有可以运行的任务
+     */
+    pub fn has_runnable(&self) -> bool {
+        self.halted_by.is_none() && self.jobs.iter().any(|job| job.status == JobStatus::Pending)
+    }
+}
+
+/// 整个队列运行结束时的汇总
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QueueSummary {
+    /// 已结束的任务（汇总后从队列中移除）
+    pub jobs: Vec<QueueJob>,
+    /// 找到钱包的任务数
+    pub completed: usize,
+    /// 没有找到钱包的任务数
+    pub stopped: usize,
+    /// 出错的任务数
+    pub failed: usize,
+    /// 因出错而停止队列的任务（队列全部运行完时为空）
+    pub halted_by: Option<String>,
+    /// 仍在等待的任务数
+    pub pending: usize,
+}
+
+/// 队列任务对应的一次会话的结果
+#[derive(Debug, Clone)]
+pub struct JobRun {
+    /// 任务标识
+    pub job_id: String,
+    /// 会话标识
+    pub session_id: String,
+    /// 会话的停止原因
+    pub stop: StopRecord,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn load_state(profile_dir: &Path) -> Result<QueueState, String> {
+    let path = profile_dir.join(QUEUE_FILE);
+    if !path.exists() {
+        return Ok(QueueState::default());
+    }
+    let data = fs::read_to_string(&path).map_err(|e| format!("无法读取队列文件: {}", e))?;
+    serde_json::from_str(&data).map_err(|e| format!("队列文件格式错误: {}", e))
+}
+
+fn save_state(profile_dir: &Path, state: &QueueState) -> Result<(), String> {
+    fs::create_dir_all(profile_dir).map_err(|e| format!("无法创建配置目录: {}", e))?;
+    let json = serde_json::to_string_pretty(state).map_err(|e| format!("无法序列化队列: {}", e))?;
+    let path = profile_dir.join(QUEUE_FILE);
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, json).map_err(|e| format!("无法写入队列文件: {}", e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("无法写入队列文件: {}", e))
+}
+
+/**
+ * 加锁读取队列，修改后写回
+ */
+fn update<T>(profile_dir: &Path, change: impl FnOnce(&mut QueueState) -> Result<T, String>) -> Result<T, String> {
+    let _guard = lock(&QUEUE_LOCK);
+    let mut state = load_state(profile_dir)?;
+    if state.version > QUEUE_VERSION {
+        return Err(format!("队列文件由更新的版本（格式 {}）写入，为避免丢失数据不能在此版本中修改", state.version));
+    }
+    let result = change(&mut state)?;
+    save_state(profile_dir, &state)?;
+    Ok(result)
+}
+
+/**
+ * 读取队列
+ *
+ * @param profile_dir - 用户配置的数据目录
+ */
+pub fn list(profile_dir: &Path) -> Result<QueueState, String> {
+    let _guard = lock(&QUEUE_LOCK);
+    load_state(profile_dir)
+}
+
+/**
+ * 把任务加入队列末尾；队列因出错停止时同时恢复队列
+ *
+ * @param profile_dir - 用户配置的数据目录
+ * @param params - 生成参数
+ * @returns 加入的任务
+ */
+pub fn enqueue(profile_dir: &Path, params: GenerationParams) -> Result<QueueJob, String> {
+    params.validate()?;
+    params.check_capabilities()?;
+    update(profile_dir, |state| {
+        let job = QueueJob {
+            id: format!("job-{}", state.next_job),
+            params,
+            status: JobStatus::Pending,
+            enqueued_at: timestamps::now_rfc3339(),
+            started_at: None,
+            finished_at: None,
+            session_id: None,
+            stop: None,
+            address: None,
+            error: None,
+        };
+        state.next_job += 1;
+        state.halted_by = None;
+        state.jobs.push(job.clone());
+        Ok(job)
+    })
+}
+
+/**
+ * 调整等待中任务的顺序：列出的任务按给出的顺序排在最前，其余等待中的任务保持原来的相对顺序排在后面
+ *
+ * @param profile_dir - 用户配置的数据目录
+ * @param job_ids - 任务标识（只能是等待中的任务，不能重复）
+ * @returns 调整后的队列
+ */
+pub fn reorder(profile_dir: &Path, job_ids: &[String]) -> Result<QueueState, String> {
+    update(profile_dir, |state| {
+        let mut seen = HashSet::new();
+        for id in job_ids {
+            if !seen.insert(id.as_str()) {
+                return Err(format!("任务 {} 重复", id));
+            }
+            match state.jobs.iter().find(|job| &job.id == id) {
+                None => return Err(format!("队列中没有任务 {}", id)),
+                Some(job) if job.status != JobStatus::Pending => return Err(format!("任务 {} 不在等待中，不能调整顺序", id)),
+                Some(_) => {}
+            }
+        }
+        let (pending, mut jobs): (Vec<QueueJob>, Vec<QueueJob>) =
+            std::mem::take(&mut state.jobs).into_iter().partition(|job| job.status == JobStatus::Pending);
+        let (mut listed, rest): (Vec<QueueJob>, Vec<QueueJob>) = pending.into_iter().partition(|job| seen.contains(job.id.as_str()));
+        listed.sort_by_key(|job| job_ids.iter().position(|id| *id == job.id));
+        jobs.extend(listed);
+        jobs.extend(rest);
+        state.jobs = jobs;
+        Ok(state.clone())
+    })
+}
+
+/**
+ * 从队列中移除任务（正在运行的任务需要先停止会话）
+ *
+ * @param profile_dir - 用户配置的数据目录
+ * @param job_id - 任务标识
+ * @returns 移除的任务
+ */
+pub fn remove(profile_dir: &Path, job_id: &str) -> Result<QueueJob, String> {
+    update(profile_dir, |state| {
+        let position = state.jobs.iter().position(|job| job.id == job_id).ok_or_else(|| format!("队列中没有任务 {}", job_id))?;
+        if state.jobs[position].status == JobStatus::Running {
+            return Err(format!("任务 {} 正在运行，请先停止当前会话", job_id));
+        }
+        Ok(state.jobs.remove(position))
+    })
+}
+
+/**
+ * 修改队列选项；队列因出错停止时同时恢复队列
+ *
+ * @param profile_dir - 用户配置的数据目录
+ * @param options - 队列选项
+ */
+pub fn set_options(profile_dir: &Path, options: QueueOptions) -> Result<QueueState, String> {
+    if options.concurrency == 0 {
+        return Err("同时运行的任务数必须至少为 1".to_string());
+    }
+    if options.concurrency > MAX_CONCURRENT_JOBS {
+        return Err(format!(
+            "此版本不支持同时运行多个任务（要求 {} 个，最多 {} 个）：各会话共用取消和暂停状态",
+            options.concurrency, MAX_CONCURRENT_JOBS
+        ));
+    }
+    update(profile_dir, |state| {
+        state.options = options;
+        state.halted_by = None;
+        Ok(state.clone())
+    })
+}
+
+/**
+ * 启动时处理上次退出时仍标记为运行中的任务（应用异常退出，会话没有正常结束）：标记为已停止，之后从下一个任务继续
+ *
+ * @param profile_dir - 用户配置的数据目录
+ * @returns 被中断的任务数
+ */
+pub fn recover(profile_dir: &Path) -> Result<usize, String> {
+    if !profile_dir.join(QUEUE_FILE).exists() {
+        return Ok(0);
+    }
+    update(profile_dir, |state| {
+        let mut interrupted = 0;
+        for job in state.jobs.iter_mut().filter(|job| job.status == JobStatus::Running) {
+            job.status = JobStatus::Stopped;
+            job.finished_at = Some(timestamps::now_rfc3339());
+            job.error = Some("应用退出时任务仍在运行，会话已中断".to_string());
+            interrupted += 1;
+        }
+        Ok(interrupted)
+    })
+}
+
+/**
+ * 取出下一个等待中的任务并标记为运行中
+ *
+ * @param profile_dir - 用户配置的数据目录
+ * @returns 任务；没有可运行的任务或队列因出错停止时为空
+ */
+pub fn start_next(profile_dir: &Path) -> Result<Option<QueueJob>, String> {
+    update(profile_dir, |state| {
+        if state.halted_by.is_some() {
+            return Ok(None);
+        }
+        let Some(job) = state.jobs.iter_mut().find(|job| job.status == JobStatus::Pending) else {
+            return Ok(None);
+        };
+        job.status = JobStatus::Running;
+        job.started_at = Some(timestamps::now_rfc3339());
+        Ok(Some(job.clone()))
+    })
+}
+
+/**
+ * 记录任务的结果：找到钱包为 completed；会话运行过但没有找到钱包为 stopped；会话没有开始、出错或工作线程全部异常退出为 failed。
+ * 设置了 stop_queue_on_error 时，failed 会停止队列
+ *
+ * @param profile_dir - 用户配置的数据目录
+ * @param job_id - 任务标识
+ * @param result - 会话的结果（找到的最后一个钱包地址或错误）
+ * @param run - 会话结束时记录的会话标识和停止原因（会话没有正常结束时为空）
+ * @returns 更新后的任务
+ */
+pub fn finish(profile_dir: &Path, job_id: &str, result: Result<Address, String>, run: Option<JobRun>) -> Result<QueueJob, String> {
+    update(profile_dir, |state| {
+        let stop_on_error = state.options.stop_queue_on_error;
+        let job = state.jobs.iter_mut().find(|job| job.id == job_id).ok_or_else(|| format!("队列中没有任务 {}", job_id))?;
+        job.status = match (&result, &run) {
+            (Ok(_), _) => JobStatus::Completed,
+            // 工作线程全部异常退出视为出错
+            (Err(_), Some(run)) if run.stop.reason == StopReason::WorkerFailure => JobStatus::Failed,
+            (Err(_), Some(_)) => JobStatus::Stopped,
+            (Err(_), None) => JobStatus::Failed,
+        };
+        job.finished_at = Some(timestamps::now_rfc3339());
+        if let Some(run) = run {
+            job.session_id = Some(run.session_id);
+            job.stop = Some(run.stop);
+        }
+        match result {
+            Ok(address) => job.address = Some(address),
+            Err(e) => job.error = Some(e),
+        }
+        let job = job.clone();
+        if job.status == JobStatus::Failed && stop_on_error {
+            state.halted_by = Some(job.id.clone());
+        }
+        Ok(job)
+    })
+}
+
+/**
+ * 队列运行结束（没有可运行的任务）时汇总已结束的任务，并把它们从队列中移除
+ *
+ * @param profile_dir - 用户配置的数据目录
+ * @returns 汇总；没有已结束的任务时为空
+ */
+pub fn complete(profile_dir: &Path) -> Result<Option<QueueSummary>, String> {
+    update(profile_dir, |state| {
+        if state.has_runnable() || !state.jobs.iter().any(|job| job.status.is_finished()) {
+            return Ok(None);
+        }
+        let (jobs, remaining): (Vec<QueueJob>, Vec<QueueJob>) =
+            std::mem::take(&mut state.jobs).into_iter().partition(|job| job.status.is_finished());
+        state.jobs = remaining;
+        let count = |status: JobStatus| jobs.iter().filter(|job| job.status == status).count();
+        Ok(Some(QueueSummary {
+            completed: count(JobStatus::Completed),
+            stopped: count(JobStatus::Stopped),
+            failed: count(JobStatus::Failed),
+            halted_by: state.halted_by.clone(),
+            pending: state.jobs.iter().filter(|job| job.status == JobStatus::Pending).count(),
+            jobs,
+        }))
+    })
+}
+
+/**
+ * 队列线程在开始任务的会话前登记任务标识
+ */
+pub fn begin_job(job_id: &str) {
+    *lock(&STARTING_JOB) = Some(job_id.to_string());
+    *lock(&FINISHED_RUN) = None;
+}
+
+/**
+ * 会话开始时取走队列线程登记的任务标识（不是由队列开始的会话为空）
+ */
+pub fn claim_job() -> Option<String> {
+    lock(&STARTING_JOB).take()
+}
+
+/**
+ * 队列会话结束时记录会话标识和停止原因
+ */
+pub fn record_run(job_id: &str, session_id: &str, stop: &StopRecord) {
+    *lock(&FINISHED_RUN) = Some(JobRun { job_id: job_id.to_string(), session_id: session_id.to_string(), stop: stop.clone() });
+}
+
+/**
+ * 取出任务的会话结果（会话没有正常结束时为空）
+ */
+pub fn take_run(job_id: &str) -> Option<JobRun> {
+    let mut run = lock(&FINISHED_RUN);
+    if run.as_ref().is_some_and(|run| run.job_id == job_id) {
+        run.take()
+    } else {
+        None
+    }
+}
+
+/**
+ * 应用退出：队列线程结束当前任务后不再开始新的任务（下次启动时从下一个任务继续）
+ */
+pub fn shut_down() {
+    SHUTTING_DOWN.store(true, Ordering::SeqCst);
+}
+
+/**
+ * 应用是否正在退出
+ */
+pub fn is_shutting_down() -> bool {
+    SHUTTING_DOWN.load(Ordering::SeqCst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::with_data_dir;
+    use crate::self_test::KNOWN_KEYS;
+
+    /**
+     * 在临时目录中准备应用数据目录，执行检查后删除
+     */
+    /**
+     * 生成队列：参数在加入时校验，调整顺序只影响等待中的任务；重启后中断的任务标记为已停止并从下一个任务继续；
+     * 出错的任务在设置 stop_queue_on_error 时停止队列；全部结束后汇总并移除已结束的任务
+     */
+    #[test]
+    fn generation_queue() -> Result<(), String> {
+        with_data_dir("queue", |dir| {
+            let params = |pattern: &str| GenerationParams { pattern: pattern.to_string(), ..GenerationParams::default() };
+            if enqueue(dir, params("")).is_ok() || enqueue(dir, GenerationParams { workers: Some(crate::templates::MAX_WORKERS + 1), ..params("8") }).is_ok() {
+                return Err("无效或本版本不支持的参数被加入队列".to_string());
+            }
+            for pattern in ["8", "88", "888"] {
+                enqueue(dir, params(pattern))?;
+            }
+            let ids = |state: &QueueState| state.jobs.iter().map(|job| job.id.clone()).collect::<Vec<_>>();
+            let state = reorder(dir, &["job-3".to_string()])?;
+            if ids(&state) != ["job-3", "job-1", "job-2"] {
+                return Err(format!("调整顺序后为 {:?}", ids(&state)));
+            }
+            for bad in [vec!["job-9".to_string()], vec!["job-1".to_string(), "job-1".to_string()]] {
+                if reorder(dir, &bad).is_ok() {
+                    return Err(format!("无效的顺序 {:?} 没有被拒绝", bad));
+                }
+            }
+            if set_options(dir, QueueOptions { concurrency: MAX_CONCURRENT_JOBS + 1, ..QueueOptions::default() }).is_ok() {
+                return Err("超过上限的并发数没有被拒绝".to_string());
+            }
+            set_options(dir, QueueOptions { stop_queue_on_error: true, ..QueueOptions::default() })?;
+            
+            // 运行中的任务不能移除；模拟应用退出后重启：中断的任务标记为已停止，从下一个任务继续
+            let first = start_next(dir)?.ok_or("没有取出等待中的任务")?;
+            if first.id != "job-3" {
+                return Err(format!("取出的任务为 {}，应为调整到最前的 job-3", first.id));
+            }
+            if remove(dir, &first.id).is_ok() {
+                return Err("运行中的任务被移除".to_string());
+            }
+            if recover(dir)? != 1 {
+                return Err("重启后没有处理中断的任务".to_string());
+            }
+            let next = start_next(dir)?.ok_or("重启后没有继续下一个任务")?;
+            if next.id != "job-1" {
+                return Err(format!("重启后继续的任务为 {}", next.id));
+            }
+            
+            // 会话事件通过登记的任务标识标记，会话结束后记录停止原因
+            begin_job(&next.id);
+            if claim_job().as_deref() != Some("job-1") || claim_job().is_some() {
+                return Err("任务标识没有被会话取走一次".to_string());
+            }
+            let stop = crate::stop::StopRecord { reason: crate::stop::StopReason::MaxMatches, detail: None };
+            record_run(&next.id, "8_session", &stop);
+            if take_run("job-2").is_some() {
+                return Err("取到了其它任务的会话结果".to_string());
+            }
+            let run: Option<JobRun> = take_run(&next.id);
+            let address = Address::parse(KNOWN_KEYS[0].1).map_err(|e| e.to_string())?;
+            let done = finish(dir, &next.id, Ok(address), run)?;
+            if done.status != JobStatus::Completed || done.session_id.as_deref() != Some("8_session") || done.stop != Some(stop) {
+                return Err(format!("完成的任务记录为 {:?}", done));
+            }
+            
+            let failing = start_next(dir)?.ok_or("没有取出最后一个任务")?;
+            enqueue(dir, params("8888"))?;
+            let failed = finish(dir, &failing.id, Err("无法创建输出目录".to_string()), None)?;
+            if failed.status != JobStatus::Failed || start_next(dir)?.is_some() {
+                return Err("出错的任务没有停止队列".to_string());
+            }
+            let summary = complete(dir)?.ok_or("队列停止时没有汇总")?;
+            if (summary.completed, summary.stopped, summary.failed, summary.pending) != (1, 1, 1, 1) || summary.halted_by.as_deref() != Some("job-2") {
+                return Err(format!("队列汇总不符: {:?}", summary));
+            }
+            
+            // 修改选项后继续运行：汇总过的任务已移除，只剩等待中的任务
+            let state = set_options(dir, QueueOptions::default())?;
+            if ids(&state) != ["job-4"] || !state.has_runnable() {
+                return Err(format!("汇总后的队列为 {:?}，halted_by = {:?}", ids(&state), state.halted_by));
+            }
+            Ok(())
+        })
+    }
+}
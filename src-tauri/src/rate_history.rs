@@ -0,0 +1,141 @@
+use crate::engine::RateSample;
+use crate::sessions;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+
+/// 每个会话保留的吞吐量采样数（每秒一次，约 10 分钟；更早的采样丢弃）
+pub const RATE_HISTORY_CAPACITY: usize = 600;
+
+/// 各会话最近的吞吐量采样（保留到下一个会话开始；界面重新加载后由 get_rate_history 补齐曲线）
+static HISTORIES: OnceLock<Mutex<Histories>> = OnceLock::new();
+
+#[derive(Default)]
+struct Histories {
+    /// 最近开始的会话
+    latest: Option<String>,
+    samples: HashMap<String, VecDeque<RateSample>>,
+}
+
+/// 吞吐量采样事件（rate-sample）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RateSampleEvent {
+    /// 会话标识
+    pub session_id: String,
+    /// 采样
+    #[serde(flatten)]
+    pub sample: RateSample,
+}
+
+/// 会话的吞吐量采样（get_rate_history 的返回值）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RateHistory {
+    /// 会话标识（还没有开始过生成会话时为空）
+    pub session_id: Option<String>,
+    /// 会话是否仍在运行
+    pub running: bool,
+    /// 最多保留的采样数
+    pub capacity: usize,
+    /// 按时间顺序的采样
+    pub samples: Vec<RateSample>,
+}
+
+fn histories() -> &'static Mutex<Histories> {
+    HISTORIES.get_or_init(|| Mutex::new(Histories::default()))
+}
+
+/**
+ * 新的生成会话开始：清除已经结束的会话的采样（同时运行的其它会话保留），登记这个会话
+ *
+ * @param session_id - 新会话的标识
+ */
+pub fn start(session_id: &str) {
+    let running: Vec<String> = sessions::list().into_iter().map(|session| session.session_id).collect();
+    if let Ok(mut histories) = histories().lock() {
+        histories.samples.retain(|id, _| running.contains(id));
+        histories.samples.insert(session_id.to_string(), VecDeque::with_capacity(RATE_HISTORY_CAPACITY));
+        histories.latest = Some(session_id.to_string());
+    }
+}
+
+/**
+ * 记录一次采样（超过 RATE_HISTORY_CAPACITY 时丢弃最早的一次）
+ *
+ * @param session_id - 会话标识
+ * @param sample - 采样
+ */
+pub fn record(session_id: &str, sample: RateSample) {
+    if let Ok(mut histories) = histories().lock() {
+        let samples = histories.samples.entry(session_id.to_string()).or_default();
+        if samples.len() == RATE_HISTORY_CAPACITY {
+            samples.pop_front();
+        }
+        samples.push_back(sample);
+    }
+}
+
+/**
+ * 读取会话的采样
+ *
+ * @param session_id - 会话标识（为空时为最近开始的会话）
+ */
+pub fn history(session_id: Option<&str>) -> Result<RateHistory, String> {
+    let histories = histories().lock().map_err(|_| "会话状态不可用".to_string())?;
+    let Some(session_id) = session_id.map(str::to_string).or_else(|| histories.latest.clone()) else {
+        return Ok(RateHistory { session_id: None, running: false, capacity: RATE_HISTORY_CAPACITY, samples: Vec::new() });
+    };
+    let samples = histories.samples.get(&session_id).ok_or_else(|| format!("会话 {} 不存在，或它的采样已在新的会话开始时清除", session_id))?;
+    Ok(RateHistory {
+        running: sessions::list().iter().any(|session| session.session_id == session_id),
+        capacity: RATE_HISTORY_CAPACITY,
+        samples: samples.iter().cloned().collect(),
+        session_id: Some(session_id),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{CancellationToken, GenerationLimits, SearchConfig, SearchEvent};
+
+    /**
+     * 吞吐量采样：汇总线程每秒发送一次，带累计尝试次数和各工作线程的速度；每个会话在内存中最多保留 600 次，
+     * 超过时丢弃最早的采样，不指定会话时读取最近开始的会话
+     */
+    #[test]
+    fn rate_samples() -> Result<(), String> {
+        let config = SearchConfig {
+            limits: GenerationLimits { max_duration_ms: Some(2_300), ..GenerationLimits::default() },
+            workers: Some(2),
+            ..SearchConfig::new("f".repeat(32))
+        };
+        let mut samples = Vec::new();
+        let report = crate::engine::search(&config, &CancellationToken::new(), || false, |event| {
+            if let SearchEvent::RateSample(sample) = event {
+                samples.push(sample);
+            }
+        });
+        if samples.len() != 2 || samples.iter().any(|sample| sample.threads.len() != 2 || sample.attempts_per_second <= 0.0) {
+            return Err(format!("2.3 秒内的吞吐量采样为 {:?}", samples));
+        }
+        let gap = samples[1].timestamp_ms - samples[0].timestamp_ms;
+        let per_thread: u64 = samples[1].threads.iter().map(|thread| thread.attempts).sum();
+        if !(900..=1_500).contains(&gap) || samples[0].attempts > samples[1].attempts || samples[1].attempts > report.attempts || per_thread > samples[1].attempts {
+            return Err(format!("采样间隔为 {} 毫秒，累计 {} / {} 次，各线程合计 {} 次，报告为 {} 次", gap, samples[0].attempts, samples[1].attempts, per_thread, report.attempts));
+        }
+        
+        let sample = |attempts: u64| RateSample { timestamp_ms: attempts as i64, attempts_per_second: 1.0, attempts, threads: Vec::new() };
+        start("test-rate");
+        for attempts in 0..(RATE_HISTORY_CAPACITY as u64 + 50) {
+            record("test-rate", sample(attempts));
+        }
+        let latest = history(None)?;
+        if latest.session_id.as_deref() != Some("test-rate") || latest.samples.len() != RATE_HISTORY_CAPACITY || latest.samples[0].attempts != 50 {
+            return Err(format!("最近的会话为 {:?}，保留 {} 次采样，最早的是第 {:?} 次", latest.session_id, latest.samples.len(), latest.samples.first().map(|sample| sample.attempts)));
+        }
+        if history(Some("test-missing")).is_ok() {
+            return Err("不存在的会话没有返回错误".to_string());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,482 @@
+use crate::entropy::TEST_WATERMARK;
+use crate::fingerprint;
+use crate::hexutil::{Address, PrivKeyHex};
+use qrcode::render::svg;
+use qrcode::QrCode;
+use sha2::{Digest, Sha256};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+/// 纯文本恢复单中手抄字段每组的字符数
+pub const TRANSCRIPTION_GROUP: usize = 4;
+
+/// 纯文本恢复单中手抄字段每行的组数
+const GROUPS_PER_LINE: usize = 8;
+
+/// 纯文本恢复单中普通字段的折行宽度（字符）
+const TEXT_WRAP_WIDTH: usize = 64;
+
+/// 转录校验码的长度（私钥 SHA-256 的最后几个十六进制字符）
+pub const TRANSCRIPTION_CHECKSUM_LENGTH: usize = 4;
+
+/// 恢复单上可选的敏感信息
+#[derive(Debug, Clone)]
+pub enum SheetSecret {
+    /// 十六进制私钥
+    PrivateKey(String),
+    /// BIP-39 助记词
+    Mnemonic(String),
+}
+
+/// 冷存储恢复单内容
+#[derive(Debug, Clone)]
+pub struct RecoverySheet {
+    /// 钱包地址（显示为 checksum 格式）
+    pub address: Address,
+    /// 靓号模式
+    pub pattern: String,
+    /// 敏感信息（仅在用户确认后提供）
+    pub secret: Option<SheetSecret>,
+    /// 派生路径（原始私钥钱包没有派生路径）
+    pub derivation_path: Option<String>,
+    /// 地址校验格式
+    pub checksum_variant: String,
+    /// 创建日期
+    pub created_at: String,
+    /// 确定性测试模式生成的测试钱包（恢复单上显示醒目的测试水印）
+    pub test_wallet: bool,
+}
+
+/**
+ * 转义 HTML 特殊字符
+ */
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/**
+ * 生成内嵌的 SVG 二维码
+ * 
+ * @param data - 二维码内容
+ * @returns SVG 字符串
+ */
+fn qr_svg(data: &str) -> Result<String, String> {
+    let code = QrCode::new(data.as_bytes()).map_err(|e| format!("无法生成二维码: {}", e))?;
+    Ok(code
+        .render::<svg::Color>()
+        .min_dimensions(180, 180)
+        .quiet_zone(true)
+        .build())
+}
+
+/**
+ * 渲染可打印的 HTML 恢复单（单页）
+ * 
+ * @param sheet - 恢复单内容
+ * @returns HTML 字符串
+ */
+pub fn render_html(sheet: &RecoverySheet) -> Result<String, String> {
+    let address_qr = qr_svg(&sheet.address.to_checksum())?;
+    let secret_section = match &sheet.secret {
+        Some(SheetSecret::PrivateKey(key)) => format!(
+            r#"<section class="secret"><h2>私钥 Private key</h2><div class="qr">{}</div><p class="mono">{}</p></section>"#,
+            qr_svg(key)?,
+            escape_html(key)
+        ),
+        Some(SheetSecret::Mnemonic(phrase)) => format!(
+            r#"<section class="secret"><h2>助记词 Mnemonic</h2><div class="qr">{}</div><p class="mono">{}</p></section>"#,
+            qr_svg(phrase)?,
+            escape_html(phrase)
+        ),
+        None => r#"<section class="secret"><h2>私钥 Private key</h2><p>未包含在本恢复单中 / Not included on this sheet</p></section>"#.to_string(),
+    };
+    let derivation_path = sheet.derivation_path.as_deref().unwrap_or("无（原始私钥） / none (raw private key)");
+    let (title_prefix, watermark) = if sheet.test_wallet {
+        (
+            format!("{} ", TEST_WATERMARK),
+            format!(
+                r#"<p class="test-watermark">{0} — 测试钱包，切勿充值 / TEST WALLET, DO NOT FUND — {0}</p>"#,
+                TEST_WATERMARK
+            ),
+        )
+    } else {
+        (String::new(), String::new())
+    };
+    
+    Ok(format!(
+        r#"<!DOCTYPE html>
+<html lang="zh-CN">
+<head>
+<meta charset="utf-8">
+<title>{title_prefix}Fancy Wallet 恢复单 {address}</title>
+<style>
+@page {{ size: A4; margin: 15mm; }}
+body {{ font-family: sans-serif; color: #000; }}
+h1 {{ font-size: 20px; }}
+h2 {{ font-size: 15px; margin: 12px 0 4px; }}
+.mono {{ font-family: monospace; font-size: 14px; word-break: break-all; }}
+.qr svg {{ width: 45mm; height: 45mm; }}
+table {{ border-collapse: collapse; width: 100%; }}
+td {{ border: 1px solid #000; padding: 6px; vertical-align: top; }}
+.blank {{ height: 14mm; }}
+.test-watermark {{ border: 4px solid #000; padding: 8px; font-size: 22px; font-weight: bold; text-align: center; }}
+</style>
+</head>
+<body>
+{watermark}
+<h1>{title_prefix}Fancy Wallet 冷存储恢复单 / Cold storage recovery sheet</h1>
+<section><h2>地址 Address</h2><div class="qr">{address_qr}</div><p class="mono">{address}</p><p>指纹 Fingerprint: <span class="mono">{fingerprint}</span></p></section>
+{secret_section}
+<table>
+<tr><td>靓号模式 Pattern</td><td class="mono">{pattern}</td></tr>
+<tr><td>派生路径 Derivation path</td><td>{derivation_path}</td></tr>
+<tr><td>地址校验格式 Checksum</td><td>{checksum_variant}</td></tr>
+<tr><td>创建日期 Created</td><td>{created_at}</td></tr>
+<tr><td>用途 Purpose</td><td class="blank"></td></tr>
+<tr><td>存放位置 Storage location</td><td class="blank"></td></tr>
+<tr><td>备注 Notes</td><td class="blank"></td></tr>
+</table>
+{watermark}
+</body>
+</html>
+"#,
+        title_prefix = title_prefix,
+        watermark = watermark,
+        address = escape_html(&sheet.address.to_checksum()),
+        address_qr = address_qr,
+        fingerprint = escape_html(&fingerprint::phrase(&sheet.address)),
+        secret_section = secret_section,
+        pattern = escape_html(&sheet.pattern),
+        derivation_path = escape_html(derivation_path),
+        checksum_variant = escape_html(&sheet.checksum_variant),
+        created_at = escape_html(&sheet.created_at),
+    ))
+}
+
+/**
+ * 按请求的敏感信息类型和确认标志决定恢复单中包含的内容（HTML 和纯文本恢复单共用）
+ * 
+ * @param secret - 要包含的敏感信息（"private_key" 或 "mnemonic"，可选）
+ * @param confirm_sensitive - 是否确认在恢复单中包含敏感信息
+ * @param private_key - 钱包的私钥
+ * @param mnemonic - 钱包的助记词（由原始私钥生成的钱包没有）
+ */
+pub fn resolve_secret(
+    secret: Option<&str>,
+    confirm_sensitive: bool,
+    private_key: &str,
+    mnemonic: Option<&str>,
+) -> Result<Option<SheetSecret>, String> {
+    match secret {
+        None => Ok(None),
+        Some(_) if !confirm_sensitive => Err("在恢复单中包含私钥或助记词需要确认".to_string()),
+        Some("private_key") => Ok(Some(SheetSecret::PrivateKey(private_key.to_string()))),
+        Some("mnemonic") => match mnemonic {
+            Some(phrase) => Ok(Some(SheetSecret::Mnemonic(phrase.to_string()))),
+            None => Err("该钱包由原始私钥生成，没有助记词".to_string()),
+        },
+        Some(other) => Err(format!("不支持的敏感信息类型: {}", other)),
+    }
+}
+
+/**
+ * 去掉手抄时加入的空白和连字符
+ */
+fn strip_transcription(text: &str) -> String {
+    text.chars().filter(|c| !c.is_whitespace() && *c != '-').collect()
+}
+
+/**
+ * 按手抄的格式分组：每 4 个字符一组，每行 8 组
+ * 
+ * @param text - 要分组的内容（私钥等）
+ * @returns 分好组的各行
+ */
+pub fn group_for_transcription(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    chars
+        .chunks(TRANSCRIPTION_GROUP * GROUPS_PER_LINE)
+        .map(|line| line.chunks(TRANSCRIPTION_GROUP).map(|group| group.iter().collect::<String>()).collect::<Vec<_>>().join(" "))
+        .collect()
+}
+
+/**
+ * 私钥的转录校验码：私钥 32 字节的 SHA-256 的最后 4 个十六进制字符，用于发现手抄或重新输入时的错误
+ * 
+ * @param private_key - 十六进制私钥（可带 0x 前缀，可包含分组用的空格）
+ */
+pub fn transcription_checksum(private_key: &str) -> Result<String, String> {
+    let key = PrivKeyHex::parse(&strip_transcription(private_key))?.secret_key();
+    let digest = hex::encode(Sha256::digest(key.secret_bytes()));
+    Ok(digest[digest.len() - TRANSCRIPTION_CHECKSUM_LENGTH..].to_string())
+}
+
+/**
+ * 核对手抄的私钥与转录校验码是否一致（不区分大小写，忽略分组用的空格）
+ * 
+ * @param private_key - 重新输入的私钥
+ * @param checksum - 恢复单上的转录校验码
+ * @returns 一致时为 true；私钥格式错误时返回错误
+ */
+pub fn check_transcription(private_key: &str, checksum: &str) -> Result<bool, String> {
+    let checksum = strip_transcription(checksum);
+    if checksum.len() != TRANSCRIPTION_CHECKSUM_LENGTH || !checksum.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("转录校验码应为 {} 个十六进制字符", TRANSCRIPTION_CHECKSUM_LENGTH));
+    }
+    Ok(transcription_checksum(private_key)?.eq_ignore_ascii_case(&checksum))
+}
+
+/**
+ * 按宽度折行（按字符计）
+ */
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.is_empty() {
+        return vec![String::new()];
+    }
+    chars.chunks(width).map(|line| line.iter().collect()).collect()
+}
+
+/**
+ * 渲染纯文本恢复单：每个字段单独带标签、缩进折行，私钥按 4 个字符一组便于手抄，并附转录校验码
+ * 
+ * @param sheet - 恢复单内容
+ * @returns 文本内容（以换行结尾）
+ */
+pub fn render_text(sheet: &RecoverySheet) -> Result<String, String> {
+    let mut lines = Vec::new();
+    let banner = format!("*** {0} — 测试钱包，切勿充值 / TEST WALLET, DO NOT FUND — {0} ***", TEST_WATERMARK);
+    if sheet.test_wallet {
+        lines.push(banner.clone());
+        lines.push(String::new());
+    }
+    let title_prefix = if sheet.test_wallet { format!("{} ", TEST_WATERMARK) } else { String::new() };
+    lines.push(format!("{}Fancy Wallet 冷存储恢复单（纯文本） / Cold storage recovery sheet (plain text)", title_prefix));
+    lines.push(String::new());
+    
+    let mut field = |label: &str, values: Vec<String>| {
+        lines.push(format!("{}:", label));
+        lines.extend(values.into_iter().map(|value| format!("    {}", value)));
+        lines.push(String::new());
+    };
+    let address = sheet.address.to_checksum();
+    field("地址 Address", vec![address.clone()]);
+    field("地址（分组） Address (grouped)", group_for_transcription(&address));
+    field("指纹 Fingerprint", wrap(&fingerprint::phrase(&sheet.address), TEXT_WRAP_WIDTH));
+    match &sheet.secret {
+        Some(SheetSecret::PrivateKey(key)) => {
+            let key = strip_transcription(key);
+            let key = key.strip_prefix("0x").unwrap_or(&key).to_string();
+            field("私钥（每组 4 个字符） Private key (groups of 4)", group_for_transcription(&key));
+            field(
+                "转录校验码 Transcription checksum (last 4 hex of SHA-256 of the key)",
+                vec![transcription_checksum(&key)?],
+            );
+        }
+        Some(SheetSecret::Mnemonic(phrase)) => field("助记词 Mnemonic", wrap(phrase, TEXT_WRAP_WIDTH)),
+        None => field("私钥 Private key", vec!["未包含在本恢复单中 / Not included on this sheet".to_string()]),
+    }
+    let derivation_path = sheet.derivation_path.as_deref().unwrap_or("无（原始私钥） / none (raw private key)");
+    field("派生路径 Derivation path", wrap(derivation_path, TEXT_WRAP_WIDTH));
+    field("靓号模式 Pattern", wrap(&sheet.pattern, TEXT_WRAP_WIDTH));
+    field("地址校验格式 Checksum", vec![sheet.checksum_variant.clone()]);
+    field("创建日期 Created", vec![sheet.created_at.clone()]);
+    
+    if sheet.test_wallet {
+        lines.push(banner);
+    }
+    let mut text = lines.join("\n");
+    text.push('\n');
+    Ok(text)
+}
+
+/**
+ * 以仅所有者可读写的权限（0600）写入文件
+ * 
+ * @param path - 文件路径
+ * @param contents - 文件内容
+ */
+pub fn write_private_file(path: &Path, contents: &str) -> Result<(), String> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path).map_err(|e| format!("无法创建恢复单文件: {}", e))?;
+    // 文件已存在时 mode 不会生效，需要显式收紧权限
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("无法设置恢复单文件权限: {}", e))?;
+    }
+    file.write_all(contents.as_bytes())
+        .map_err(|e| format!("无法写入恢复单文件: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entropy;
+    use crate::test_support::{MNEMONIC_ADDRESS_VECTORS, with_data_dir};
+    use crate::self_test::KNOWN_KEYS;
+
+    #[test]
+    fn plaintext_sheet() -> Result<(), String> {
+        let (private_key, address) = KNOWN_KEYS[0];
+        
+        // 64 个字符的私钥分为 2 行，每行 8 组 4 个字符
+        let lines = group_for_transcription(private_key);
+        if lines.len() != 2 || lines.iter().any(|line| line.split(' ').count() != 8 || line.split(' ').any(|group| group.len() != 4)) {
+            return Err(format!("私钥分组错误: {:?}", lines));
+        }
+        if lines.concat().replace(' ', "") != private_key {
+            return Err("分组后的私钥与原私钥不一致".to_string());
+        }
+        
+        // 转录校验码：分组、大写的重新输入一致，改动一个字符后不一致（校验码只有 4 个十六进制字符，选一个不碰撞的改动）
+        let checksum = transcription_checksum(private_key)?;
+        if !check_transcription(&lines.join("\n").to_uppercase(), &checksum)? {
+            return Err("分组后重新输入的私钥没有通过转录校验".to_string());
+        }
+        let typo = (0..private_key.len())
+            .flat_map(|i| ['0', '1'].map(|digit| format!("{}{}{}", &private_key[..i], digit, &private_key[i + 1..])))
+            .find(|typo| typo != private_key && transcription_checksum(typo).is_ok_and(|sum| sum != checksum))
+            .ok_or("找不到可用于校验的手抄错误")?;
+        if check_transcription(&typo, &checksum)? {
+            return Err("手抄错误没有被转录校验码发现".to_string());
+        }
+        if check_transcription(private_key, "xyz").is_ok() {
+            return Err("格式错误的转录校验码未被拒绝".to_string());
+        }
+        
+        // 包含私钥需要确认，没有助记词
+        if resolve_secret(Some("private_key"), false, private_key, None).is_ok() {
+            return Err("未确认时恢复单仍包含了私钥".to_string());
+        }
+        if resolve_secret(Some("mnemonic"), true, private_key, None).is_ok() {
+            return Err("原始私钥钱包的助记词请求未被拒绝".to_string());
+        }
+        let secret = resolve_secret(Some("private_key"), true, private_key, None)?;
+        
+        let mut sheet = RecoverySheet {
+            address: Address::parse(&format!("0x{}", address))?,
+            pattern: "*".to_string(),
+            secret,
+            derivation_path: None,
+            checksum_variant: "EIP-55".to_string(),
+            created_at: String::new(),
+            test_wallet: false,
+        };
+        let text = render_text(&sheet)?;
+        if !lines.iter().all(|line| text.contains(line.as_str())) || !text.contains(&checksum) {
+            return Err("纯文本恢复单中没有分组的私钥或转录校验码".to_string());
+        }
+        if text.contains(entropy::TEST_WATERMARK) {
+            return Err("真实钱包的纯文本恢复单带有测试水印".to_string());
+        }
+        let phrase = MNEMONIC_ADDRESS_VECTORS[1].0;
+        sheet.secret = resolve_secret(Some("mnemonic"), true, private_key, Some(phrase))?;
+        let text = render_text(&sheet)?;
+        if !text.contains(phrase) || text.contains(&lines[0]) {
+            return Err("由助记词生成的钱包的恢复单中没有助记词".to_string());
+        }
+        sheet.secret = None;
+        sheet.test_wallet = true;
+        let text = render_text(&sheet)?;
+        if text.contains(&lines[0]) || text.matches(entropy::TEST_WATERMARK).count() < 2 {
+            return Err("未包含私钥的测试钱包恢复单内容错误".to_string());
+        }
+        Ok(())
+    }
+
+    /**
+     * HTML 恢复单：没有确认时不能包含私钥或助记词，未包含敏感信息的恢复单中也不出现它们；确认后只包含选择的一项，
+     * 地址、派生路径、校验格式和创建日期都在页面上，用户输入的字段被转义
+     */
+    #[test]
+    fn recovery_sheet_html() -> Result<(), String> {
+        let (private_key, address) = KNOWN_KEYS[0];
+        let phrase = MNEMONIC_ADDRESS_VECTORS[1].0;
+        for secret in ["private_key", "mnemonic"] {
+            if resolve_secret(Some(secret), false, private_key, Some(phrase)).is_ok() {
+                return Err(format!("未确认时恢复单仍包含了 {}", secret));
+            }
+        }
+        if resolve_secret(Some("seed"), true, private_key, Some(phrase)).is_ok() {
+            return Err("不支持的敏感信息类型没有被拒绝".to_string());
+        }
+
+        let mut sheet = RecoverySheet {
+            address: Address::parse(address)?,
+            pattern: "<script>888</script>".to_string(),
+            secret: resolve_secret(None, true, private_key, Some(phrase))?,
+            derivation_path: Some("m/44'/60'/0'/0/0".to_string()),
+            checksum_variant: "EIP-55".to_string(),
+            created_at: "2026-01-02".to_string(),
+            test_wallet: false,
+        };
+        let html = render_html(&sheet)?;
+        let checksum = sheet.address.to_checksum();
+        if html.contains(private_key) || html.contains(phrase) || html.matches("<svg").count() != 1 {
+            return Err("没有确认的恢复单中出现了私钥、助记词或它们的二维码".to_string());
+        }
+        for field in [checksum.as_str(), "m/44&#39;/60&#39;/0&#39;/0/0", "EIP-55", "2026-01-02", "&lt;script&gt;888&lt;/script&gt;"] {
+            if !html.contains(field) {
+                return Err(format!("恢复单中没有 {}", field));
+            }
+        }
+        if html.contains("<script>") || html.contains(entropy::TEST_WATERMARK) {
+            return Err("恢复单中的模式没有转义，或真实钱包带有测试水印".to_string());
+        }
+
+        sheet.secret = resolve_secret(Some("private_key"), true, private_key, Some(phrase))?;
+        let html = render_html(&sheet)?;
+        if !html.contains(private_key) || html.contains(phrase) || html.matches("<svg").count() != 2 {
+            return Err("确认包含私钥的恢复单内容错误".to_string());
+        }
+        sheet.secret = resolve_secret(Some("mnemonic"), true, private_key, Some(phrase))?;
+        let html = render_html(&sheet)?;
+        if !html.contains(phrase) || html.contains(private_key) || html.matches("<svg").count() != 2 {
+            return Err("确认包含助记词的恢复单内容错误".to_string());
+        }
+        Ok(())
+    }
+
+    /**
+     * 恢复单文件只有所有者可以读写，覆盖权限更宽的已有文件时同样收紧权限
+     */
+    #[test]
+    fn recovery_sheet_permissions() -> Result<(), String> {
+        with_data_dir("recovery-permissions", |dir| {
+            let path = dir.join("recovery_sheet.html");
+            std::fs::write(&path, "old").map_err(|e| e.to_string())?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).map_err(|e| e.to_string())?;
+            }
+            write_private_file(&path, "<html></html>")?;
+            if std::fs::read_to_string(&path).map_err(|e| e.to_string())? != "<html></html>" {
+                return Err("恢复单文件的内容没有被覆盖".to_string());
+            }
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let mode = std::fs::metadata(&path).map_err(|e| e.to_string())?.permissions().mode() & 0o777;
+                if mode != 0o600 {
+                    return Err(format!("恢复单文件的权限为 {:o}", mode));
+                }
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,502 @@
+use crate::chain::Chain;
+use crate::hexutil::Address;
+use crate::pattern;
+use crate::stop::{StopReason, StopRecord};
+use crate::timestamps;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// 回放文件格式版本
+pub const REPLAY_VERSION: u32 = 1;
+
+/// 进度快照的最小记录间隔（毫秒）
+const PROGRESS_SAMPLE_INTERVAL_MS: u64 = 1000;
+
+/// 回放文件中定时记录的进度快照上限，超过后抽稀全部快照并加倍记录间隔
+const MAX_PROGRESS_SAMPLES: usize = 2048;
+
+/// 回放文件头（文件第一行）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReplayHeader {
+    /// 文件格式版本
+    pub version: u32,
+    /// 靓号模式
+    pub pattern: String,
+    /// 会话开始时间
+    pub started_at: String,
+    /// 会话生成的链（旧版本的回放文件中没有该字段）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain: Option<Chain>,
+}
+
+/// 回放事件
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReplayEvent {
+    /// 相对会话开始的时间（毫秒）
+    pub t: u64,
+    /// 事件内容
+    #[serde(flatten)]
+    pub kind: ReplayEventKind,
+}
+
+/// 回放事件类型（不包含任何私钥信息）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReplayEventKind {
+    /// 会话状态变化
+    State { state: String },
+    /// 进度快照
+    Progress { attempts: u64, matches: u64, duration: u64 },
+    /// 警告
+    Warning { message: String },
+    /// 会话停止原因
+    Stopped { reason: StopReason, detail: Option<String> },
+    /// 找到匹配的地址
+    Found {
+        address: Address,
+        attempts: u64,
+        /// 钱包序号（旧版本的回放文件中没有该字段）
+        #[serde(default)]
+        index: u64,
+        /// 写入记录时的系统时间（系统时间跳变时可能不是单调递增的，时间顺序以 t 为准）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        found_at: Option<String>,
+    },
+}
+
+/// 读取到的回放文件
+#[derive(Debug, Clone)]
+pub struct ReplayFile {
+    /// 文件头
+    pub header: ReplayHeader,
+    /// 按顺序排列的事件
+    pub events: Vec<ReplayEvent>,
+    /// 文件末尾是否被截断（例如进程崩溃时写入了半行）
+    pub truncated: bool,
+}
+
+/// 会话事件记录器
+///
+/// 每个事件写入一行 JSON，写入后不保留在内存中；内存中只保留定时记录的进度快照（时间和尝试次数），用于抽稀。
+/// 写入失败时停止记录但不影响生成过程。
+pub struct ReplayRecorder {
+    path: PathBuf,
+    /// 文件中定时记录的进度快照 (t, attempts, matches)，按时间排列（匹配时强制记录的快照随匹配一起保留，不在这里）
+    progress: Vec<(u64, u64, u64)>,
+    /// 定时记录进度快照的间隔（毫秒），每次抽稀后加倍
+    progress_interval_ms: u64,
+    last_progress_at: Option<u64>,
+    file: Option<File>,
+    start: Instant,
+}
+
+/**
+ * 不与已有回放文件重名的路径：同一秒内开始的会话或系统时间后退后，按秒命名的文件名可能重复，
+ * 此时在时间末尾的 Z 之前加上 -2、-3 等序号（会话名中的时间仍可解析），避免覆盖之前的回放
+ *
+ * @param dir - 回放文件目录
+ * @param prefix - 文件名中时间之前的部分
+ * @param stamp - 文件名中的时间（以 Z 结尾）
+ */
+fn unused_path(dir: &Path, prefix: &str, stamp: &str) -> PathBuf {
+    let mut path = dir.join(format!("{}{}.jsonl", prefix, stamp));
+    let base = stamp.strip_suffix('Z').unwrap_or(stamp);
+    let mut suffix = 2;
+    while path.exists() {
+        path = dir.join(format!("{}{}-{}Z.jsonl", prefix, base, suffix));
+        suffix += 1;
+    }
+    path
+}
+
+impl ReplayRecorder {
+    /**
+     * 在指定目录下创建新的回放文件
+     *
+     * @param dir - 回放文件目录
+     * @param pattern - 靓号模式
+     * @param chain - 会话生成的链
+     * @returns 记录器
+     */
+    pub fn create(dir: &Path, pattern: &str, chain: Chain) -> Result<Self, String> {
+        fs::create_dir_all(dir).map_err(|e| format!("无法创建回放目录: {}", e))?;
+
+        let now = chrono::Utc::now();
+        let path = unused_path(dir, &format!("replay_{}_", pattern::file_label(pattern)), &timestamps::filename_stamp(now));
+        let header = ReplayHeader {
+            version: REPLAY_VERSION,
+            pattern: pattern.to_string(),
+            started_at: timestamps::to_rfc3339(now),
+            chain: Some(chain),
+        };
+        let mut file = OpenOptions::new().write(true).create_new(true).open(&path).map_err(|e| format!("无法创建回放文件: {}", e))?;
+        let line = serde_json::to_string(&header).map_err(|e| e.to_string())?;
+        file.write_all(format!("{}\n", line).as_bytes()).map_err(|e| format!("无法创建回放文件: {}", e))?;
+
+        Ok(ReplayRecorder {
+            path,
+            progress: Vec::new(),
+            progress_interval_ms: PROGRESS_SAMPLE_INTERVAL_MS,
+            last_progress_at: None,
+            file: Some(file),
+            start: Instant::now(),
+        })
+    }
+
+    /**
+     * 改变定时记录进度快照的间隔（测试中用 0 在短时间内记录大量快照）
+     *
+     * @param interval_ms - 记录间隔（毫秒）
+     */
+    #[cfg(test)]
+    pub fn with_progress_interval(self, interval_ms: u64) -> Self {
+        ReplayRecorder { progress_interval_ms: interval_ms, ..self }
+    }
+
+    /**
+     * 回放文件路径
+     */
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /**
+     * 内存中保留的事件数及其估算大小（字节）
+     */
+    pub fn retained(&self) -> (usize, u64) {
+        (self.progress.len(), (self.progress.len() * std::mem::size_of::<(u64, u64, u64)>()) as u64)
+    }
+
+    /**
+     * 超过内存软上限时抽稀全部定时记录的进度快照（每两条保留一条），其它事件全部保留
+     *
+     * @returns 丢弃的进度快照数
+     */
+    pub fn shed_history(&mut self) -> usize {
+        self.decimate()
+    }
+
+    /**
+     * 记录会话状态变化
+     */
+    pub fn record_state(&mut self, state: &str) {
+        self.append(ReplayEventKind::State { state: state.to_string() });
+    }
+
+    /**
+     * 记录会话停止原因
+     */
+    pub fn record_stop(&mut self, stop: &StopRecord) {
+        self.append(ReplayEventKind::Stopped { reason: stop.reason, detail: stop.detail.clone() });
+    }
+
+    /**
+     * 记录警告
+     */
+    pub fn record_warning(&mut self, message: &str) {
+        self.append(ReplayEventKind::Warning { message: message.to_string() });
+    }
+
+    /**
+     * 记录找到的地址（只记录地址，不记录私钥）
+     */
+    pub fn record_found(&mut self, address: Address, attempts: u64, index: u64) {
+        let found_at = Some(timestamps::now_rfc3339());
+        self.append(ReplayEventKind::Found { address, attempts, index, found_at });
+    }
+
+    /**
+     * 记录进度快照（降低分辨率，默认每秒最多一条；抽稀后间隔随之加倍）
+     *
+     * @param force - 是否忽略间隔强制记录（例如匹配时；强制记录的快照随匹配一起保留，不会被抽稀）
+     */
+    pub fn record_progress(&mut self, attempts: u64, matches: u64, duration: u64, force: bool) {
+        let t = self.elapsed_ms();
+        if !force && self.last_progress_at.is_some_and(|last| t.saturating_sub(last) < self.progress_interval_ms) {
+            return;
+        }
+        self.last_progress_at = Some(t);
+        self.append_at(t, ReplayEventKind::Progress { attempts, matches, duration });
+        if force {
+            return;
+        }
+        self.progress.push((t, attempts, matches));
+        if self.progress.len() > MAX_PROGRESS_SAMPLES {
+            self.decimate();
+        }
+    }
+
+    fn elapsed_ms(&self) -> u64 {
+        self.start.elapsed().as_millis() as u64
+    }
+
+    fn append(&mut self, kind: ReplayEventKind) {
+        self.append_at(self.elapsed_ms(), kind);
+    }
+
+    fn append_at(&mut self, t: u64, kind: ReplayEventKind) {
+        if let Some(file) = self.file.as_mut() {
+            let written = serde_json::to_string(&ReplayEvent { t, kind })
+                .map_err(std::io::Error::other)
+                .and_then(|line| file.write_all(format!("{}\n", line).as_bytes()));
+            if written.is_err() {
+                // 回放仅用于调试，写入失败时停止记录即可
+                self.file = None;
+            }
+        }
+    }
+
+    /**
+     * 抽稀定时记录的进度快照：每两条保留一条，之后的记录间隔加倍，文件中的进度快照数因此不超过 MAX_PROGRESS_SAMPLES，
+     * 重写文件的次数只随运行时间对数增长
+     *
+     * @returns 丢弃的进度快照数
+     */
+    fn decimate(&mut self) -> usize {
+        let mut index = 0usize;
+        let mut dropped = HashSet::new();
+        self.progress.retain(|sample| {
+            let keep = index.is_multiple_of(2);
+            index += 1;
+            if !keep {
+                dropped.insert(*sample);
+            }
+            keep
+        });
+        self.progress_interval_ms = self.progress_interval_ms.saturating_mul(2);
+
+        if self.file.is_some() && !dropped.is_empty() && self.compact(&dropped).is_err() {
+            self.file = None;
+        }
+        dropped.len()
+    }
+
+    /**
+     * 逐行复制回放文件并去掉抽稀的进度快照，通过临时文件原子地替换，然后以追加模式重新打开（只有进度快照在内存中）
+     *
+     * @param dropped - 抽稀的进度快照 (t, attempts, matches)
+     */
+    fn compact(&mut self, dropped: &HashSet<(u64, u64, u64)>) -> std::io::Result<()> {
+        let tmp_path = self.path.with_extension("jsonl.tmp");
+        {
+            let mut reader = BufReader::new(File::open(&self.path)?);
+            let mut tmp = BufWriter::new(File::create(&tmp_path)?);
+            let mut line = Vec::new();
+            while reader.read_until(b'\n', &mut line)? > 0 {
+                let sample = match serde_json::from_slice::<ReplayEvent>(&line) {
+                    Ok(ReplayEvent { t, kind: ReplayEventKind::Progress { attempts, matches, .. } }) => Some((t, attempts, matches)),
+                    _ => None,
+                };
+                if !sample.is_some_and(|sample| dropped.contains(&sample)) {
+                    tmp.write_all(&line)?;
+                }
+                line.clear();
+            }
+            tmp.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        }
+        fs::rename(&tmp_path, &self.path)?;
+        self.file = Some(OpenOptions::new().append(true).open(&self.path)?);
+        Ok(())
+    }
+}
+
+/**
+ * 一个事件写入回放文件的字节数（含换行）
+ */
+fn line_bytes(kind: ReplayEventKind) -> u64 {
+    serde_json::to_string(&ReplayEvent { t: u64::MAX, kind }).map_or(0, |line| line.len() as u64 + 1)
+}
+
+fn max_progress() -> ReplayEventKind {
+    ReplayEventKind::Progress { attempts: u64::MAX, matches: u64::MAX, duration: u64::MAX }
+}
+
+/**
+ * 每个匹配写入回放文件的字节数上限（匹配事件和随之强制记录的进度快照），用于开始前估计磁盘占用
+ */
+pub fn bytes_per_match() -> u64 {
+    let found = ReplayEventKind::Found {
+        address: Address::from([0xff; 20]),
+        attempts: u64::MAX,
+        index: u64::MAX,
+        found_at: Some(timestamps::now_rfc3339()),
+    };
+    line_bytes(found) + line_bytes(max_progress())
+}
+
+/**
+ * 与匹配数无关的回放部分的字节数上限：文件头、最多 MAX_PROGRESS_SAMPLES 条进度快照以及开始和停止事件
+ *
+ * @param pattern - 靓号模式
+ * @param chain - 会话生成的链
+ */
+pub fn session_overhead_bytes(pattern: &str, chain: Chain) -> u64 {
+    let header = ReplayHeader {
+        version: REPLAY_VERSION,
+        pattern: pattern.to_string(),
+        started_at: timestamps::now_rfc3339(),
+        chain: Some(chain),
+    };
+    let header_bytes = serde_json::to_string(&header).map_or(0, |line| line.len() as u64 + 1);
+    let states = ["started", "cancelled"].map(|state| line_bytes(ReplayEventKind::State { state: state.to_string() }));
+    let stopped = line_bytes(ReplayEventKind::Stopped { reason: StopReason::SaveErrorPolicy, detail: Some(u64::MAX.to_string()) });
+    header_bytes + states.iter().sum::<u64>() + stopped + (MAX_PROGRESS_SAMPLES as u64 + 1) * line_bytes(max_progress())
+}
+
+/**
+ * 读取回放文件，容忍末尾被截断的情况
+ *
+ * @param path - 回放文件路径
+ * @returns 回放内容
+ */
+pub fn read_replay(path: &Path) -> Result<ReplayFile, String> {
+    let data = fs::read(path).map_err(|e| format!("无法读取回放文件: {}", e))?;
+    let mut lines = data.split(|&b| b == b'\n');
+
+    let header: ReplayHeader = lines
+        .next()
+        .and_then(|line| serde_json::from_slice(line).ok())
+        .ok_or("不是有效的回放文件")?;
+    if header.version > REPLAY_VERSION {
+        return Err(format!("不支持的回放文件版本: {}", header.version));
+    }
+
+    let mut events = Vec::new();
+    let mut truncated = false;
+    for line in lines {
+        if line.is_empty() {
+            continue;
+        }
+        match serde_json::from_slice::<ReplayEvent>(line) {
+            Ok(event) => events.push(event),
+            Err(_) => {
+                // 遇到无法解析的行，视为文件在此处被截断
+                truncated = true;
+                break;
+            }
+        }
+    }
+
+    Ok(ReplayFile { header, events, truncated })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::with_data_dir;
+
+    /// 抽稀测试中定时记录的进度快照数（超过 MAX_PROGRESS_SAMPLES，触发多次抽稀）
+    const DECIMATION_TEST_SAMPLES: u64 = 5000;
+
+    /**
+     * 第 i 个测试地址
+     */
+    fn address(i: u64) -> Address {
+        let mut bytes = [0u8; 20];
+        bytes[12..].copy_from_slice(&i.to_be_bytes());
+        Address::from(bytes)
+    }
+
+    /**
+     * 读取被截断的回放文件：最后一行只写入了一半时报告 truncated，之前的事件全部保留；完整的文件不报告截断
+     */
+    #[test]
+    fn replay_truncated() -> Result<(), String> {
+        with_data_dir("replay_truncated", |dir| {
+            let mut recorder = ReplayRecorder::create(dir, "888", Chain::Ethereum)?;
+            recorder.record_state("started");
+            recorder.record_progress(1000, 0, 100, true);
+            recorder.record_found(address(1), 1500, 1);
+            recorder.record_warning("写入钱包信息时遇到短暂错误，重试 1 次后成功");
+            recorder.record_progress(2000, 1, 200, true);
+            let complete = read_replay(recorder.path())?;
+            if complete.truncated || complete.events.len() != 5 || complete.header.pattern != "888" {
+                return Err(format!("完整的回放文件读取为 {} 个事件，截断: {}", complete.events.len(), complete.truncated));
+            }
+
+            let data = fs::read(recorder.path()).map_err(|e| e.to_string())?;
+            let last_line = data[..data.len() - 1].iter().rposition(|&b| b == b'\n').ok_or("回放文件只有一行")? + 1;
+            let cut = last_line + (data.len() - last_line) / 2;
+            fs::write(recorder.path(), &data[..cut]).map_err(|e| e.to_string())?;
+            let truncated = read_replay(recorder.path())?;
+            if !truncated.truncated || truncated.events.len() != 4 {
+                return Err(format!("截断的回放文件读取为 {} 个事件，截断: {}", truncated.events.len(), truncated.truncated));
+            }
+            let kinds: Vec<String> = truncated.events.iter().map(|event| format!("{:?}", event.kind)).collect();
+            let expected: Vec<String> = complete.events[..4].iter().map(|event| format!("{:?}", event.kind)).collect();
+            if kinds != expected {
+                return Err(format!("截断前的事件没有原样保留: {:?}", kinds));
+            }
+
+            fs::write(recorder.path(), b"{\"version\":1,\"pat").map_err(|e| e.to_string())?;
+            if read_replay(recorder.path()).is_ok() {
+                return Err("文件头被截断的回放文件没有被拒绝".to_string());
+            }
+            Ok(())
+        })
+    }
+
+    /**
+     * 抽稀：定时记录的进度快照超过 MAX_PROGRESS_SAMPLES 后被抽稀，文件中的快照数不超过上限，内存中只保留快照；
+     * 匹配、警告和匹配时强制记录的快照全部保留，事件仍按时间排列
+     */
+    #[test]
+    fn replay_decimation() -> Result<(), String> {
+        with_data_dir("replay_decimation", |dir| {
+            let mut recorder = ReplayRecorder::create(dir, "888", Chain::Ethereum)?.with_progress_interval(0);
+            recorder.record_state("started");
+            let mut found = 0;
+            let mut warnings = 0;
+            for i in 1..=DECIMATION_TEST_SAMPLES {
+                recorder.record_progress(i, found, i, false);
+                if i.is_multiple_of(100) {
+                    found += 1;
+                    recorder.record_found(address(found), i, found);
+                    recorder.record_progress(i, found, i, true);
+                }
+                if i.is_multiple_of(250) {
+                    warnings += 1;
+                    recorder.record_warning(&format!("警告 {}", warnings));
+                }
+            }
+            recorder.record_state("cancelled");
+            let (retained, _) = recorder.retained();
+            if retained > MAX_PROGRESS_SAMPLES {
+                return Err(format!("内存中保留了 {} 条进度快照", retained));
+            }
+
+            let replay = read_replay(recorder.path())?;
+            if replay.truncated {
+                return Err("抽稀后的回放文件被读取为截断".to_string());
+            }
+            let mut found_indexes = Vec::new();
+            let mut warning_count = 0;
+            let mut forced = 0;
+            let mut periodic = 0;
+            for event in &replay.events {
+                match &event.kind {
+                    ReplayEventKind::Found { index, .. } => found_indexes.push(*index),
+                    ReplayEventKind::Warning { .. } => warning_count += 1,
+                    // 强制记录的快照的尝试次数是 100 的倍数，且匹配数已经包含这次匹配
+                    ReplayEventKind::Progress { attempts, matches, .. } if *matches == attempts / 100 && attempts.is_multiple_of(100) => forced += 1,
+                    ReplayEventKind::Progress { .. } => periodic += 1,
+                    _ => {}
+                }
+            }
+            if found_indexes != (1..=found).collect::<Vec<u64>>() || warning_count != warnings || forced != found {
+                return Err(format!("抽稀后匹配 {:?}，警告 {} 条，强制记录的快照 {} 条", found_indexes, warning_count, forced));
+            }
+            if periodic == 0 || periodic > MAX_PROGRESS_SAMPLES || periodic as u64 >= DECIMATION_TEST_SAMPLES {
+                return Err(format!("抽稀后文件中有 {} 条定时记录的进度快照", periodic));
+            }
+            if replay.events.windows(2).any(|pair| pair[0].t > pair[1].t) {
+                return Err("抽稀后的事件没有按时间排列".to_string());
+            }
+            Ok(())
+        })
+    }
+}
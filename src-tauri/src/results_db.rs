@@ -0,0 +1,351 @@
+use crate::chain::Chain;
+use crate::gui::{Wallet, WrittenRow};
+use crate::saved_wallets::MAX_PAGE_SIZE;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 结果数据库的文件名（位于输出根目录 FancyWallets 中，各链和各会话共用）
+pub const DB_FILE: &str = "wallets.db";
+
+/// 另一个会话正在写入时等待的最长时间
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 按版本排列的结构迁移：第 n 条执行后 user_version 为 n，已执行的迁移不会再执行
+pub const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE wallets (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_id TEXT NOT NULL,
+        wallet_index INTEGER NOT NULL,
+        address TEXT NOT NULL,
+        private_key TEXT,
+        in_keystore INTEGER NOT NULL DEFAULT 0,
+        pattern TEXT NOT NULL,
+        attempts INTEGER NOT NULL,
+        duration_ms INTEGER NOT NULL,
+        created_at TEXT NOT NULL
+    );",
+    "ALTER TABLE wallets ADD COLUMN chain TEXT NOT NULL DEFAULT 'ethereum';
+    CREATE INDEX wallets_pattern ON wallets (pattern);
+    CREATE INDEX wallets_session ON wallets (session_id, wallet_index);",
+];
+
+/// 会话写入 wallets.db 的设置（会话开始时确定，每批匹配使用同一个）
+#[derive(Debug, Clone)]
+pub struct SessionDb {
+    /// 数据库文件
+    pub path: PathBuf,
+    /// 会话标识
+    pub session_id: String,
+    /// 是否保存明文私钥（结果文件打码、省略或加密私钥，或者只写入 keystore 时不保存）
+    pub private_keys: bool,
+    /// 私钥是否另外保存在 keystore 文件中
+    pub in_keystore: bool,
+}
+
+/// wallets.db 中的一个钱包（query_wallets 的返回值）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StoredWallet {
+    /// 数据库中的行号（按写入顺序递增）
+    pub id: i64,
+    /// 会话标识
+    pub session_id: String,
+    /// 会话内的序号
+    pub index: u64,
+    /// 所属的链
+    pub chain: String,
+    /// 该链格式的地址
+    pub address: String,
+    /// 私钥（只有 include_keys 时才有；没有保存明文私钥时为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<String>,
+    /// 私钥是否保存在 keystore 文件中
+    pub in_keystore: bool,
+    /// 结果文件模式列中的模式
+    pub pattern: String,
+    /// 尝试次数
+    pub attempts: u64,
+    /// 找到时的运行时间（毫秒）
+    pub duration_ms: u64,
+    /// 找到时间（RFC 3339）
+    pub created_at: String,
+}
+
+/**
+ * 执行还没有执行的迁移（每条迁移在各自的事务中执行，失败时回滚这一条）
+ *
+ * @param conn - 数据库连接
+ * @param migrations - 按版本排列的迁移
+ * @returns 迁移后的结构版本
+ */
+pub fn migrate(conn: &mut Connection, migrations: &[&str]) -> Result<u32, String> {
+    let version: u32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0)).map_err(|e| format!("无法读取结果数据库的版本: {}", e))?;
+    if version as usize > migrations.len() {
+        return Err(format!("结果数据库的版本 {} 比本版本支持的 {} 新，请使用新版本打开", version, migrations.len()));
+    }
+    for (index, sql) in migrations.iter().enumerate().skip(version as usize) {
+        let next = index as u32 + 1;
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        tx.execute_batch(sql).map_err(|e| format!("结果数据库迁移到版本 {} 失败: {}", next, e))?;
+        tx.pragma_update(None, "user_version", next).map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| format!("结果数据库迁移到版本 {} 失败: {}", next, e))?;
+    }
+    Ok(migrations.len() as u32)
+}
+
+/**
+ * 打开结果数据库（不存在时创建）并迁移到当前结构
+ *
+ * @param path - 数据库文件
+ */
+pub fn open(path: &Path) -> Result<Connection, String> {
+    let mut conn = Connection::open(path).map_err(|e| format!("无法打开结果数据库 {}: {}", path.display(), e))?;
+    conn.busy_timeout(BUSY_TIMEOUT).map_err(|e| e.to_string())?;
+    migrate(&mut conn, MIGRATIONS)?;
+    Ok(conn)
+}
+
+/**
+ * 在一个事务中写入一批钱包
+ *
+ * @param db - 会话的数据库设置
+ * @param rows - 钱包信息和模式列中的模式（按序号排列）
+ * @param chain - 地址所属的链
+ * @returns 写入的字段的字节数（数据库文件实际增长的大小取决于页的分配）
+ */
+pub fn insert_wallets(db: &SessionDb, rows: &[(&Wallet, &str)], chain: Chain) -> Result<WrittenRow, String> {
+    let mut conn = open(&db.path)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut bytes = 0;
+    {
+        let mut insert = tx
+            .prepare(
+                "INSERT INTO wallets (session_id, wallet_index, chain, address, private_key, in_keystore, pattern, attempts, duration_ms, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            )
+            .map_err(|e| e.to_string())?;
+        for (wallet, pattern) in rows {
+            let address = wallet.chain_address.clone().unwrap_or_else(|| chain.format_address(wallet.address));
+            let private_key = db.private_keys.then(|| wallet.private_key.expose());
+            insert
+                .execute(params![
+                    db.session_id,
+                    wallet.index as i64,
+                    chain.as_str(),
+                    address,
+                    private_key,
+                    db.in_keystore,
+                    pattern,
+                    wallet.attempts as i64,
+                    wallet.duration as i64,
+                    wallet.created_at,
+                ])
+                .map_err(|e| format!("无法写入结果数据库: {}", e))?;
+            bytes += (db.session_id.len() + address.len() + private_key.map_or(0, str::len) + pattern.len() + wallet.created_at.len()) as u64;
+        }
+    }
+    tx.commit().map_err(|e| format!("无法写入结果数据库: {}", e))?;
+    Ok(WrittenRow { bytes, retries: 0 })
+}
+
+/**
+ * 按模式筛选的 WHERE 子句和参数（模式中的 % 和 _ 按原样匹配）
+ */
+fn pattern_clause(pattern_filter: Option<&str>) -> (&'static str, Option<String>) {
+    match pattern_filter.filter(|filter| !filter.is_empty()) {
+        Some(filter) => {
+            let escaped = filter.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+            ("WHERE pattern LIKE ?1 ESCAPE '\\'", Some(format!("%{}%", escaped)))
+        }
+        None => ("WHERE ?1 IS NULL", None),
+    }
+}
+
+/**
+ * 分页查询结果数据库中的钱包（最新写入的在前）
+ *
+ * @param path - 数据库文件
+ * @param pattern_filter - 只返回模式列包含这段文字的钱包（可选，不区分 ASCII 字母的大小写）
+ * @param limit - 每页条数（不超过 MAX_PAGE_SIZE）
+ * @param offset - 跳过的条数
+ * @param include_keys - 是否带有私钥
+ */
+pub fn query_wallets(path: &Path, pattern_filter: Option<&str>, limit: usize, offset: u64, include_keys: bool) -> Result<Vec<StoredWallet>, String> {
+    let conn = open(path)?;
+    let (clause, filter) = pattern_clause(pattern_filter);
+    let sql = format!(
+        "SELECT id, session_id, wallet_index, chain, address, private_key, in_keystore, pattern, attempts, duration_ms, created_at
+         FROM wallets {} ORDER BY id DESC LIMIT ?2 OFFSET ?3",
+        clause
+    );
+    let mut query = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let limit = limit.clamp(1, MAX_PAGE_SIZE) as i64;
+    let rows = query
+        .query_map(params![filter, limit, offset as i64], |row| {
+            Ok(StoredWallet {
+                id: row.get(0)?,
+                session_id: row.get(1)?,
+                index: row.get::<_, i64>(2)? as u64,
+                chain: row.get(3)?,
+                address: row.get(4)?,
+                private_key: if include_keys { row.get(5)? } else { None },
+                in_keystore: row.get(6)?,
+                pattern: row.get(7)?,
+                attempts: row.get::<_, i64>(8)? as u64,
+                duration_ms: row.get::<_, i64>(9)? as u64,
+                created_at: row.get(10)?,
+            })
+        })
+        .map_err(|e| format!("无法查询结果数据库: {}", e))?;
+    rows.collect::<Result<_, _>>().map_err(|e| format!("无法读取结果数据库: {}", e))
+}
+
+/**
+ * 统计结果数据库中的钱包数
+ *
+ * @param path - 数据库文件
+ * @param pattern_filter - 只统计模式列包含这段文字的钱包（可选，与 query_wallets 相同）
+ */
+pub fn count_wallets(path: &Path, pattern_filter: Option<&str>) -> Result<u64, String> {
+    let conn = open(path)?;
+    let (clause, filter) = pattern_clause(pattern_filter);
+    let count: i64 = conn
+        .query_row(&format!("SELECT COUNT(*) FROM wallets {}", clause), params![filter], |row| row.get(0))
+        .map_err(|e| format!("无法统计结果数据库: {}", e))?;
+    Ok(count as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entropy::RngMode;
+    use crate::hexutil::{Address, PrivKeyHex, SecretHex};
+    use crate::self_test::KNOWN_KEYS;
+    use crate::test_support::with_data_dir;
+    use secp256k1::Secp256k1;
+
+    /**
+     * 由已知私钥生成第 index 个钱包
+     */
+    fn known_wallet(index: usize) -> Result<Wallet, String> {
+        let (key, _) = KNOWN_KEYS[index];
+        Ok(Wallet {
+            index: index as u64 + 1,
+            address: Address::from_secret_key(&Secp256k1::new(), &PrivKeyHex::parse(key)?.secret_key()),
+            private_key: SecretHex::from(key.to_string()),
+            attempts: 1000 * (index as u64 + 1),
+            duration: 10 * (index as u64 + 1),
+            matched_spans: Vec::new(),
+            rng_mode: RngMode::default(),
+            test_wallet: false,
+            mnemonic: None,
+            chain_address: None,
+            matched_pattern: "8".to_string(),
+            created_at: format!("2026-01-0{}T00:00:00Z", index + 1),
+        })
+    }
+
+    /**
+     * 结构迁移：新数据库迁移到最新版本；只执行过第一条迁移的旧数据库保留已有的行，新列取默认值，再次打开不重复迁移；
+     * 比本版本新的数据库被拒绝
+     */
+    #[test]
+    fn results_db_migrations() -> Result<(), String> {
+        with_data_dir("results-db-migrations", |dir| {
+            let fresh = dir.join("fresh.db");
+            let mut conn = Connection::open(&fresh).map_err(|e| e.to_string())?;
+            if migrate(&mut conn, MIGRATIONS)? != MIGRATIONS.len() as u32 || migrate(&mut conn, MIGRATIONS)? != MIGRATIONS.len() as u32 {
+                return Err("新数据库没有迁移到最新版本".to_string());
+            }
+
+            let old = dir.join(DB_FILE);
+            let mut conn = Connection::open(&old).map_err(|e| e.to_string())?;
+            migrate(&mut conn, &MIGRATIONS[..1])?;
+            conn.execute(
+                "INSERT INTO wallets (session_id, wallet_index, address, private_key, pattern, attempts, duration_ms, created_at)
+                 VALUES ('old', 1, '0xabc', NULL, '8', 5, 6, '2026-01-01T00:00:00Z')",
+                [],
+            )
+            .map_err(|e| e.to_string())?;
+            drop(conn);
+            let wallets = query_wallets(&old, None, 10, 0, true)?;
+            let version: u32 = open(&old)?.query_row("PRAGMA user_version", [], |row| row.get(0)).map_err(|e| e.to_string())?;
+            if wallets.len() != 1 || wallets[0].chain != "ethereum" || wallets[0].in_keystore || wallets[0].private_key.is_some() || version != MIGRATIONS.len() as u32 {
+                return Err(format!("迁移后的旧数据库为版本 {}: {:?}", version, wallets));
+            }
+
+            open(&old)?.pragma_update(None, "user_version", MIGRATIONS.len() as u32 + 1).map_err(|e| e.to_string())?;
+            if open(&old).is_ok() {
+                return Err("比本版本新的数据库没有被拒绝".to_string());
+            }
+            Ok(())
+        })
+    }
+
+    /**
+     * 写入和查询：一批钱包在一个事务中写入，查询最新的在前并按 limit 和 offset 分页，按模式筛选时 % 和 _ 按原样匹配；
+     * 不保存明文私钥的会话只记录 in_keystore 标记，include_keys 为 false 时不返回私钥
+     */
+    #[test]
+    fn results_db_wallets() -> Result<(), String> {
+        with_data_dir("results-db-wallets", |dir| {
+            let path = dir.join(DB_FILE);
+            let wallets = (0..3).map(known_wallet).collect::<Result<Vec<_>, _>>()?;
+            let plain = SessionDb { path: path.clone(), session_id: "plain".to_string(), private_keys: true, in_keystore: false };
+            results_db_rows(&plain, &[(&wallets[0], "8"), (&wallets[1], "8%_")], Chain::Ethereum)?;
+            let keystore = SessionDb { session_id: "keystore".to_string(), private_keys: false, in_keystore: true, ..plain.clone() };
+            results_db_rows(&keystore, &[(&wallets[2], "score-longest-run=7")], Chain::Ethereum)?;
+
+            let all = query_wallets(&path, None, 10, 0, true)?;
+            let sessions: Vec<(&str, u64)> = all.iter().map(|stored| (stored.session_id.as_str(), stored.index)).collect();
+            if sessions != [("keystore", 3), ("plain", 2), ("plain", 1)] || count_wallets(&path, None)? != 3 {
+                return Err(format!("查询到的钱包为 {:?}", sessions));
+            }
+            let expected = StoredWallet {
+                id: 1,
+                session_id: "plain".to_string(),
+                index: 1,
+                chain: "ethereum".to_string(),
+                address: Chain::Ethereum.format_address(wallets[0].address),
+                private_key: Some(KNOWN_KEYS[0].0.to_string()),
+                in_keystore: false,
+                pattern: "8".to_string(),
+                attempts: 1000,
+                duration_ms: 10,
+                created_at: "2026-01-01T00:00:00Z".to_string(),
+            };
+            if all[2] != expected {
+                return Err(format!("写入的钱包读回为 {:?}", all[2]));
+            }
+            if all[0].private_key.is_some() || !all[0].in_keystore {
+                return Err("只写入 keystore 的钱包保存了明文私钥或没有 in_keystore 标记".to_string());
+            }
+            if query_wallets(&path, None, 10, 0, false)?.iter().any(|stored| stored.private_key.is_some()) {
+                return Err("include_keys 为 false 时返回了私钥".to_string());
+            }
+
+            let page = query_wallets(&path, None, 1, 1, false)?;
+            if page.iter().map(|stored| stored.index).collect::<Vec<_>>() != [2] {
+                return Err(format!("limit 1、offset 1 的一页为 {:?}", page));
+            }
+            for (filter, expected) in [("%_", vec![2]), ("SCORE", vec![3]), ("8", vec![2, 1]), ("", vec![3, 2, 1]), ("9", vec![])] {
+                let found: Vec<u64> = query_wallets(&path, Some(filter), 10, 0, false)?.iter().map(|stored| stored.index).collect();
+                if found != expected || count_wallets(&path, Some(filter))? != expected.len() as u64 {
+                    return Err(format!("按模式 {:?} 筛选到 {:?}", filter, found));
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /**
+     * 写入一批钱包，返回的字节数不为零
+     */
+    fn results_db_rows(db: &SessionDb, rows: &[(&Wallet, &str)], chain: Chain) -> Result<(), String> {
+        if insert_wallets(db, rows, chain)?.bytes == 0 {
+            return Err("写入结果数据库时没有计入字节数".to_string());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,253 @@
+use std::io::{self, ErrorKind};
+use std::time::Duration;
+
+/// Windows 错误码：文件正被其它进程使用（杀毒软件扫描、同步盘上传时常见）
+const ERROR_SHARING_VIOLATION: i32 = 32;
+
+/// Windows 错误码：文件的一部分被其它进程锁定
+const ERROR_LOCK_VIOLATION: i32 = 33;
+
+/// 保存钱包失败后整次写入的默认重试次数
+pub const DEFAULT_SAVE_RETRIES: u32 = 2;
+
+/// 保存钱包失败后整次写入的最大重试次数
+pub const MAX_SAVE_RETRIES: u32 = 10;
+
+/// 保存钱包失败后第一次重试前的等待时间
+const SAVE_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// 写入结果文件时的重试策略
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// 总尝试次数（含第一次）
+    pub attempts: u32,
+    /// 第一次重试前的等待时间，之后每次翻倍
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy { attempts: 3, base_delay: Duration::from_millis(50) }
+    }
+}
+
+impl RetryPolicy {
+    /**
+     * 第 n 次重试（从 1 开始）前的等待时间
+     */
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.base_delay.saturating_mul(1 << (retry - 1).min(16))
+    }
+}
+
+/**
+ * 判断错误是否是短暂的（稍后重试可能成功）
+ *
+ * 权限错误只在 Windows 上视为短暂：杀毒软件或同步盘短暂锁定文件时会报告拒绝访问，
+ * 其它系统上的权限错误不会自行消失
+ *
+ * @param kind - 错误类型
+ * @param raw_os_error - 系统错误码
+ * @param windows - 是否按 Windows 的规则判断
+ */
+pub fn classify(kind: ErrorKind, raw_os_error: Option<i32>, windows: bool) -> bool {
+    match kind {
+        ErrorKind::Interrupted | ErrorKind::WouldBlock | ErrorKind::TimedOut => true,
+        ErrorKind::PermissionDenied => windows,
+        _ => windows && matches!(raw_os_error, Some(ERROR_SHARING_VIOLATION | ERROR_LOCK_VIOLATION)),
+    }
+}
+
+/**
+ * 判断本机上的 IO 错误是否是短暂的
+ */
+pub fn is_transient(error: &io::Error) -> bool {
+    classify(error.kind(), error.raw_os_error(), cfg!(windows))
+}
+
+/**
+ * 执行 IO 操作，遇到短暂错误时按指数退避重试；其它错误或重试用尽时返回最后一次的错误
+ *
+ * 操作本身需要保证失败后可以重新执行（例如写入失败时先截断已写入的半行）
+ *
+ * @param policy - 重试策略
+ * @param op - IO 操作
+ * @returns (操作结果, 重试次数)
+ */
+pub fn retry_io<T>(policy: &RetryPolicy, mut op: impl FnMut() -> io::Result<T>) -> (io::Result<T>, u32) {
+    let mut retries = 0;
+    loop {
+        match op() {
+            Err(e) if retries + 1 < policy.attempts && is_transient(&e) => {
+                retries += 1;
+                std::thread::sleep(policy.delay(retries));
+            }
+            result => return (result, retries),
+        }
+    }
+}
+
+/**
+ * 保存钱包的重试策略：整次写入失败后再重试 retries 次（每次写入内部对短暂错误的重试不计在内）
+ *
+ * @param retries - 重试次数（可选，默认 DEFAULT_SAVE_RETRIES，最多 MAX_SAVE_RETRIES）
+ */
+pub fn save_policy(retries: Option<u32>) -> Result<RetryPolicy, String> {
+    let retries = retries.unwrap_or(DEFAULT_SAVE_RETRIES);
+    if retries > MAX_SAVE_RETRIES {
+        return Err(format!("保存失败后的重试次数不能超过 {}", MAX_SAVE_RETRIES));
+    }
+    Ok(RetryPolicy { attempts: retries + 1, base_delay: SAVE_RETRY_DELAY })
+}
+
+/**
+ * 执行写入操作，失败时不论错误类型都按指数退避重试，重试用尽时返回最后一次的错误
+ *
+ * 用于保存钱包：写入内部已经重试过短暂错误，这里再给磁盘已满、外接硬盘暂时断开等情况留出恢复的时间
+ *
+ * @param policy - 重试策略（save_policy 的结果）
+ * @param op - 写入操作（失败后可以重新执行）
+ * @returns (操作结果, 重试次数)
+ */
+pub fn retry_all<T>(policy: &RetryPolicy, mut op: impl FnMut() -> Result<T, String>) -> (Result<T, String>, u32) {
+    let mut retries = 0;
+    loop {
+        match op() {
+            Err(_) if retries + 1 < policy.attempts => {
+                retries += 1;
+                std::thread::sleep(policy.delay(retries));
+            }
+            result => return (result, retries),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entropy::RngMode;
+    use crate::engine::SearchTotals;
+    use crate::gui::{GenerationResult, Wallet};
+    use crate::output_root;
+    use crate::hexutil::{Address, SecretHex};
+    use crate::templates::GenerationParams;
+    use crate::timestamps;
+    use crate::test_support::with_data_dir;
+
+    /// 写入错误的分类：(错误类型, 系统错误码, 是否按 Windows 规则, 是否应重试)
+    const RETRY_CLASSIFICATION: &[(ErrorKind, Option<i32>, bool, bool)] = &[
+        (ErrorKind::Interrupted, None, false, true),
+        (ErrorKind::WouldBlock, None, false, true),
+        (ErrorKind::TimedOut, None, false, true),
+        (ErrorKind::TimedOut, None, true, true),
+        (ErrorKind::PermissionDenied, Some(13), false, false),
+        (ErrorKind::PermissionDenied, Some(5), true, true),
+        // ERROR_SHARING_VIOLATION / ERROR_LOCK_VIOLATION：文件被其它进程短暂锁定
+        (ErrorKind::Other, Some(32), true, true),
+        (ErrorKind::Other, Some(33), true, true),
+        (ErrorKind::Other, Some(32), false, false),
+        (ErrorKind::NotFound, None, false, false),
+        (ErrorKind::NotFound, Some(2), true, false),
+        (ErrorKind::StorageFull, Some(28), false, false),
+        (ErrorKind::InvalidData, None, true, false),
+    ];
+
+    /**
+     * 写入重试：错误分类表，短暂错误重试到成功为止，其它错误不重试，重试次数不超过策略；
+     * 保存钱包时整次写入不论错误类型都按 save_retries 重试，开始前的写入检查拒绝无法写入的目录
+     */
+    #[test]
+    fn save_retry() -> Result<(), String> {
+        for &(kind, code, windows, expected) in RETRY_CLASSIFICATION {
+            if classify(kind, code, windows) != expected {
+                return Err(format!("{:?}（错误码 {:?}，Windows: {}）应{}重试", kind, code, windows, if expected { "" } else { "不" }));
+            }
+        }
+        
+        let policy = RetryPolicy { attempts: 3, base_delay: Duration::ZERO };
+        let runs: [(&[ErrorKind], bool, u32, u32); 4] = [
+            (&[ErrorKind::Interrupted, ErrorKind::TimedOut], true, 2, 3),
+            (&[ErrorKind::WouldBlock; 5], false, 2, 3),
+            (&[ErrorKind::NotFound], false, 0, 1),
+            (&[], true, 0, 1),
+        ];
+        for (failures, succeeds, expected_retries, expected_calls) in runs {
+            let mut calls = 0;
+            let (result, retries) = retry_io(&policy, || {
+                calls += 1;
+                match failures.get(calls - 1) {
+                    Some(&kind) => Err(io::Error::from(kind)),
+                    None => Ok(calls),
+                }
+            });
+            if result.is_ok() != succeeds || retries != expected_retries || calls as u32 != expected_calls {
+                return Err(format!(
+                    "依次失败 {:?} 时结果为 {:?}，重试 {} 次、调用 {} 次（应为重试 {} 次、调用 {} 次）",
+                    failures, result, retries, calls, expected_retries, expected_calls
+                ));
+            }
+        }
+        
+        let save = RetryPolicy { base_delay: Duration::ZERO, ..save_policy(Some(2))? };
+        for (failures, expected_retries) in [(1, 1), (5, 2), (0, 0)] {
+            let mut calls = 0;
+            let (result, retries) = retry_all(&save, || {
+                calls += 1;
+                if calls <= failures { Err(format!("第 {} 次写入失败", calls)) } else { Ok(calls) }
+            });
+            if result.is_ok() != (failures <= 2) || retries != expected_retries {
+                return Err(format!("整次写入失败 {} 次时结果为 {:?}，重试 {} 次（应为 {} 次）", failures, result, retries, expected_retries));
+            }
+        }
+        if save_policy(Some(MAX_SAVE_RETRIES + 1)).is_ok() || save_policy(None)?.attempts != DEFAULT_SAVE_RETRIES + 1 {
+            return Err("save_retries 的上限或默认值不正确".to_string());
+        }
+        let too_many = GenerationParams { pattern: "8".to_string(), save_retries: Some(MAX_SAVE_RETRIES + 1), ..GenerationParams::default() };
+        if too_many.validate().is_ok() {
+            return Err("超过上限的 save_retries 没有被拒绝".to_string());
+        }
+        
+        with_data_dir("save-retry", |dir| {
+            let nested = dir.join("FancyWallets").join("ethereum");
+            output_root::check_writable(&nested)?;
+            if std::fs::read_dir(&nested).map_err(|e| e.to_string())?.next().is_some() {
+                return Err("写入检查留下了临时文件".to_string());
+            }
+            let file = dir.join("not-a-directory");
+            std::fs::write(&file, "x").map_err(|e| e.to_string())?;
+            if output_root::check_writable(&file.join("ethereum")).is_ok() {
+                return Err("无法创建的输出目录没有被拒绝".to_string());
+            }
+            
+            let wallet = Wallet {
+                index: 1,
+                address: Address::from([0x88; 20]),
+                private_key: SecretHex::from("1".repeat(64)),
+                attempts: 1,
+                duration: 0,
+                matched_spans: Vec::new(),
+                rng_mode: RngMode::ReseededCsprng,
+                test_wallet: false,
+                mnemonic: None,
+                chain_address: None,
+                matched_pattern: String::new(),
+                created_at: timestamps::now_rfc3339(),
+            };
+            let result = |unsaved_wallets: Vec<Wallet>| GenerationResult {
+                wallet: wallet.clone(),
+                wallets: vec![wallet.clone()],
+                wallets_truncated: false,
+                totals: SearchTotals { attempts: 1, matches: 1, duration: 0 },
+                unsaved_wallets,
+                resumed: None,
+                dry_run: false,
+            };
+            let saved = serde_json::to_value(result(Vec::new())).map_err(|e| e.to_string())?;
+            let unsaved = serde_json::to_value(result(vec![wallet.clone()])).map_err(|e| e.to_string())?;
+            if saved.get("address").is_none() || saved.get("unsaved_wallets").is_some() || unsaved["unsaved_wallets"][0]["private_key"] != "1".repeat(64) {
+                return Err(format!("命令结果的格式不正确: {} / {}", saved, unsaved));
+            }
+            Ok(())
+        })
+    }
+}
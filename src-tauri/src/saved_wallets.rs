@@ -0,0 +1,1807 @@
+use crate::bitcoin;
+use crate::chain::{Chain, KNOWN_CHAINS};
+use crate::collections::{self, COLLECTIONS_DIR};
+use crate::csv_field;
+use crate::entropy::{self, RngMode};
+use crate::fingerprint;
+use crate::hexutil::{Address, PrivKeyHex, SecretHex};
+use crate::timestamps;
+use secp256k1::Secp256k1;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// 单页结果的最大条数
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+/// 当前版本写入的 CSV 标题（wif 列只有比特币的记录才有值；created_at 为保存时间，attempts 和 duration_ms 为找到时的尝试次数和运行时间）
+pub const CSV_HEADER: &str = "address,private_key,pattern,index,chain,entropy,mnemonic,wif,created_at,attempts,duration_ms";
+
+/// 各版本写入的 CSV 标题（第 n 项为格式版本 n + 1；字段按 RFC 4180 加引号从第 7 版开始）
+pub const CSV_HEADERS: [&str; 7] = [
+    "address,private_key,pattern",
+    "address,private_key,pattern,index",
+    "address,private_key,pattern,index,chain",
+    "address,private_key,pattern,index,chain,entropy",
+    "address,private_key,pattern,index,chain,entropy,mnemonic",
+    "address,private_key,pattern,index,chain,entropy,mnemonic,wif",
+    CSV_HEADER,
+];
+
+/// 当前版本写入的 CSV 格式版本
+pub const CSV_FORMAT_VERSION: u32 = CSV_HEADERS.len() as u32;
+
+/// 私钥打码时写入的 CSV 标题（列名与完整私钥不同，读取方不会把打码的值当作私钥；也不写入 WIF 私钥）
+pub const CSV_HEADER_MASKED: &str = "address,private_key_masked,pattern,index,chain,entropy,mnemonic,created_at,attempts,duration_ms";
+
+/// 不保存私钥时写入的 CSV 标题
+pub const CSV_HEADER_OMITTED: &str = "address,pattern,index,chain,entropy,mnemonic,created_at,attempts,duration_ms";
+
+/// 旧版本私钥打码时写入的标题（没有 created_at、attempts 和 duration_ms 列）
+const LEGACY_HEADER_MASKED: &str = "address,private_key_masked,pattern,index,chain,entropy,mnemonic";
+
+/// 旧版本不保存私钥时写入的标题
+const LEGACY_HEADER_OMITTED: &str = "address,pattern,index,chain,entropy,mnemonic";
+
+/// 打码的私钥首尾各保留的十六进制字符数
+const MASK_VISIBLE_CHARS: usize = 4;
+
+/// CSV 中私钥列的写法
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvPrivateKeys {
+    /// 完整的私钥（默认）
+    #[default]
+    Full,
+    /// 只保留首尾各 4 个十六进制字符（例如 4c08...ed6e），不能用来恢复钱包
+    Masked,
+    /// 不写入私钥列
+    Omit,
+}
+
+impl CsvPrivateKeys {
+    /**
+     * 写入的 CSV 标题
+     */
+    pub fn header(self) -> &'static str {
+        match self {
+            CsvPrivateKeys::Full => CSV_HEADER,
+            CsvPrivateKeys::Masked => CSV_HEADER_MASKED,
+            CsvPrivateKeys::Omit => CSV_HEADER_OMITTED,
+        }
+    }
+
+    /**
+     * 由 CSV 标题判断私钥列的写法（无法识别的标题为空）
+     */
+    pub fn from_header(header: &str) -> Option<Self> {
+        match header.trim() {
+            CSV_HEADER_MASKED | LEGACY_HEADER_MASKED => Some(CsvPrivateKeys::Masked),
+            CSV_HEADER_OMITTED | LEGACY_HEADER_OMITTED => Some(CsvPrivateKeys::Omit),
+            header => csv_format_version(header).map(|_| CsvPrivateKeys::Full),
+        }
+    }
+
+    /**
+     * 参数和警告中的名称
+     */
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CsvPrivateKeys::Full => "full",
+            CsvPrivateKeys::Masked => "masked",
+            CsvPrivateKeys::Omit => "omit",
+        }
+    }
+}
+
+/// JSON Lines 结果文件的扩展名（与 CSV 文件同名，结果浏览器不读取）
+pub const JSONL_EXTENSION: &str = "jsonl";
+
+/// 结果文件名的前缀：每个模式各自的文件（wallet_<模式>_<时间>.csv）和合并的文件（wallets_<时间>.csv）
+pub const RESULT_FILE_PREFIXES: [&str; 2] = ["wallet_", "wallets_"];
+
+/// 结果文件的格式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    /// 只写入 CSV（默认）
+    #[default]
+    Csv,
+    /// 只写入 JSON Lines（每行一个序列化的钱包）
+    Jsonl,
+    /// 同时写入 CSV 和 JSON Lines
+    Both,
+}
+
+/// 同时搜索多个模式时结果文件的组织方式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputLayout {
+    /// 每个模式写入各自的文件 wallet_<模式>_<时间>.csv（默认）
+    #[default]
+    PerPattern,
+    /// 全部模式写入同一个文件 wallets_<时间>.csv，每行的 pattern 列记录满足的模式
+    Combined,
+}
+
+impl OutputFormat {
+    /**
+     * 解析模板和队列中保存的输出格式（不区分大小写，为空时为 csv）
+     */
+    pub fn parse(value: Option<&str>) -> Result<Self, String> {
+        match value.map(str::to_ascii_lowercase).as_deref() {
+            None | Some("csv") => Ok(OutputFormat::Csv),
+            Some("jsonl") => Ok(OutputFormat::Jsonl),
+            Some("both") => Ok(OutputFormat::Both),
+            Some(other) => Err(format!("此版本不支持输出格式 {}（支持: csv, jsonl, both）", other)),
+        }
+    }
+
+    /**
+     * 模板和队列中保存的名称
+     */
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OutputFormat::Csv => "csv",
+            OutputFormat::Jsonl => "jsonl",
+            OutputFormat::Both => "both",
+        }
+    }
+
+    /**
+     * 是否写入 CSV
+     */
+    pub fn writes_csv(self) -> bool {
+        self != OutputFormat::Jsonl
+    }
+
+    /**
+     * 是否写入 JSON Lines
+     */
+    pub fn writes_jsonl(self) -> bool {
+        self != OutputFormat::Csv
+    }
+}
+
+/**
+ * 打码的私钥：只保留首尾各 4 个字符，中间以 ... 代替
+ * 
+ * @param private_key - 十六进制私钥
+ */
+pub fn mask_private_key(private_key: &str) -> String {
+    match (private_key.get(..MASK_VISIBLE_CHARS), private_key.len().checked_sub(MASK_VISIBLE_CHARS)) {
+        (Some(head), Some(tail)) if tail > MASK_VISIBLE_CHARS => format!("{}...{}", head, &private_key[tail..]),
+        _ => "...".to_string(),
+    }
+}
+
+/**
+ * 结果文件第一行对应的私钥列写法（读取失败或无法识别时按完整私钥处理，与旧版本的行为相同）
+ */
+fn file_layout(file: &Path) -> CsvPrivateKeys {
+    let mut header = String::new();
+    match File::open(file).map(|file| BufReader::new(file).read_line(&mut header)) {
+        Ok(Ok(_)) => CsvPrivateKeys::from_header(&header).unwrap_or_default(),
+        _ => CsvPrivateKeys::default(),
+    }
+}
+
+/**
+ * 内容第一行对应的私钥列写法
+ */
+fn content_layout(content: &str) -> CsvPrivateKeys {
+    content.lines().next().and_then(CsvPrivateKeys::from_header).unwrap_or_default()
+}
+
+/// 正在运行的会话写入的结果文件，以及其中已完整写入的字节数
+static ACTIVE_FILES: OnceLock<Mutex<HashMap<PathBuf, u64>>> = OnceLock::new();
+
+fn active_files() -> &'static Mutex<HashMap<PathBuf, u64>> {
+    ACTIVE_FILES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/**
+ * 结果文件在登记表中的键（目录取规范路径，避免同一文件因路径写法不同而对不上）
+ */
+fn active_key(path: &Path) -> PathBuf {
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => parent.canonicalize().unwrap_or_else(|_| parent.to_path_buf()).join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+/**
+ * CSV 标题对应的格式版本（无法识别时为空）
+ */
+pub fn csv_format_version(header: &str) -> Option<u32> {
+    CSV_HEADERS.iter().position(|known| *known == header.trim()).map(|i| i as u32 + 1)
+}
+
+/**
+ * 按当前格式（CSV_HEADER）写出一条记录（不含换行），用于把旧格式的文件改写为当前格式
+ *
+ * 缺少的链按以太坊写入；旧记录的随机数来源未知时 entropy 列留空，旧记录都由原始私钥生成，mnemonic 列留空；
+ * 比特币记录的 wif 列由私钥重新计算；v7 之前的记录没有生成时间、尝试次数和耗时，这三列留空。
+ *
+ * @param wallet - 读取的记录（需要保存了完整的私钥）
+ */
+pub fn current_format_row(wallet: &SavedWallet) -> String {
+    let chain = wallet.chain.unwrap_or(Chain::Ethereum);
+    let index = wallet.index.map(|index| index.to_string()).unwrap_or_default();
+    let entropy = wallet.entropy.map(|mode| mode.as_str()).unwrap_or_default();
+    let wif = match chain {
+        Chain::Bitcoin => SecretHex::from(wallet.private_key.clone()).decode().map(|secret| bitcoin::wif(&secret)),
+        _ => None,
+    };
+    let attempts = wallet.attempts.map(|attempts| attempts.to_string()).unwrap_or_default();
+    let duration_ms = wallet.duration_ms.map(|duration| duration.to_string()).unwrap_or_default();
+    csv_field::join([
+        wallet.address.as_str(),
+        wallet.private_key.as_str(),
+        wallet.pattern.as_str(),
+        index.as_str(),
+        chain.as_str(),
+        entropy,
+        wallet.mnemonic.as_deref().unwrap_or_default(),
+        wif.as_ref().map(|wif| wif.as_str()).unwrap_or_default(),
+        wallet.created_at.as_deref().unwrap_or_default(),
+        attempts.as_str(),
+        duration_ms.as_str(),
+    ])
+}
+
+/**
+ * 文件是否正被正在运行的会话写入
+ */
+pub fn is_active(path: &Path) -> bool {
+    committed_len(path).is_some()
+}
+
+/**
+ * 正在运行的会话已完整写入的字节数（文件不属于正在运行的会话时为空）
+ */
+fn committed_len(path: &Path) -> Option<u64> {
+    active_files().lock().ok()?.get(&active_key(path)).copied()
+}
+
+/// 正在运行的会话的结果文件登记，离开作用域时注销
+pub struct ActiveFile {
+    key: PathBuf,
+}
+
+impl ActiveFile {
+    /**
+     * 登记会话将要追加的结果文件；文件中已有的内容视为已提交
+     *
+     * @param path - 结果文件路径
+     */
+    #[cfg(test)]
+    pub fn register(path: &Path) -> Self {
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let key = active_key(path);
+        let len = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        if let Ok(mut map) = active_files().lock() {
+            map.insert(key.clone(), len);
+        }
+        ActiveFile { key }
+    }
+
+    /**
+     * 独占登记结果文件：文件已被其它正在运行的会话登记时拒绝（用于多个会话共同追加的集合，避免交错写入）
+     *
+     * @param path - 结果文件路径
+     */
+    pub fn register_exclusive(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let key = active_key(path);
+        let len = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        let mut map = active_files().lock().map_err(|_| "结果文件登记表不可用".to_string())?;
+        if map.contains_key(&key) {
+            return Err(format!("{} 正在被另一个会话写入", path.display()));
+        }
+        map.insert(key.clone(), len);
+        Ok(ActiveFile { key })
+    }
+}
+
+impl Drop for ActiveFile {
+    fn drop(&mut self) {
+        if let Ok(mut map) = active_files().lock() {
+            map.remove(&self.key);
+        }
+    }
+}
+
+/**
+ * 写入方完整写入一行后提交新的文件长度，读取方只读取到该长度为止
+ *
+ * @param path - 结果文件路径
+ * @param len - 已完整写入的字节数
+ */
+pub fn commit(path: &Path, len: u64) {
+    if let Ok(mut map) = active_files().lock() {
+        if let Some(committed) = map.get_mut(&active_key(path)) {
+            *committed = len;
+        }
+    }
+}
+
+/// 结果文件的读取状态
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FileStatus {
+    /// 文件完整
+    Complete,
+    /// 文件属于正在运行的会话，只读取已提交的行
+    ActiveSession,
+    /// 文件末尾有一行尚未写完（已忽略），但不属于本应用正在运行的会话
+    InProgress,
+}
+
+/**
+ * 读取结果文件中可以安全解析的部分
+ *
+ * 正在运行的会话的文件只读取到写入方提交的长度；其它文件末尾没有换行符时忽略最后的半行。
+ *
+ * @param file - 结果文件路径
+ * @returns (完整的行组成的内容, 读取状态)
+ */
+fn read_committed(file: &Path) -> Result<(String, FileStatus), String> {
+    let committed = committed_len(file);
+    let mut bytes = Vec::new();
+    let reader = File::open(file).map_err(|e| format!("无法打开结果文件: {}", e))?;
+    match committed {
+        Some(len) => reader.take(len).read_to_end(&mut bytes),
+        None => BufReader::new(reader).read_to_end(&mut bytes),
+    }
+    .map_err(|e| format!("无法读取结果文件: {}", e))?;
+    
+    let mut status = if committed.is_some() { FileStatus::ActiveSession } else { FileStatus::Complete };
+    if !bytes.is_empty() && !bytes.ends_with(b"\n") {
+        let end = bytes.iter().rposition(|&b| b == b'\n').map(|i| i + 1).unwrap_or(0);
+        bytes.truncate(end);
+        if status == FileStatus::Complete {
+            status = FileStatus::InProgress;
+        }
+    }
+    Ok((String::from_utf8_lossy(&bytes).into_owned(), status))
+}
+
+/// 已保存到 CSV 文件中的钱包记录
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SavedWallet {
+    /// 钱包地址（文件中的原文；地址格式取决于所属的链）
+    pub address: String,
+    /// 私钥
+    pub private_key: String,
+    /// 靓号模式
+    pub pattern: String,
+    /// 会话内的序号（旧文件中没有该列）
+    pub index: Option<u64>,
+    /// 所属的链（无法识别时为空）
+    pub chain: Option<Chain>,
+    /// 链是否由地址格式推断（旧文件中没有 chain 列）
+    pub chain_inferred: bool,
+    /// 地址的指纹短语（地址无法解析时为空）
+    #[serde(default)]
+    pub fingerprint: Option<String>,
+    /// 私钥的随机数来源（旧文件中没有该列）
+    #[serde(default)]
+    pub entropy: Option<RngMode>,
+    /// 确定性测试模式生成的测试钱包（entropy 列为 deterministic-test 或私钥带有 TESTONLY 水印）
+    #[serde(default)]
+    pub test_wallet: bool,
+    /// 派生出私钥的 BIP-39 助记词（由原始私钥生成的钱包和旧文件中没有）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mnemonic: Option<String>,
+    /// 保存时间（RFC 3339，旧文件中没有该列）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    /// 找到时的尝试次数（旧文件中没有该列）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attempts: Option<u64>,
+    /// 找到时的运行时间（毫秒，旧文件中没有该列）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// 文件中私钥列的写法（masked 时 private_key 为打码的值，omit 时为空）
+    #[serde(default)]
+    pub private_keys: CsvPrivateKeys,
+    /// 所在文件
+    pub file: PathBuf,
+}
+
+impl SavedWallet {
+    /**
+     * 规范格式的地址：以太坊地址统一为 0x + EIP-55（兼容旧文件中大小写不规范的地址），其它链保持原文
+     */
+    pub fn canonical_address(&self) -> String {
+        match self.chain {
+            Some(Chain::Ethereum) => Address::parse(&self.address).map(|a| a.to_checksum()).unwrap_or_else(|_| self.address.clone()),
+            _ => self.address.clone(),
+        }
+    }
+}
+
+/**
+ * 由结果文件名（去掉扩展名）得到结果浏览器中的会话标识；不是结果文件时为空
+ * 
+ * @param stem - 文件名中扩展名之前的部分
+ */
+pub fn result_session_id(stem: &str) -> Option<&str> {
+    RESULT_FILE_PREFIXES.iter().find_map(|prefix| stem.strip_prefix(prefix))
+}
+
+/**
+ * 是否是结果文件（wallet_*.csv 或 wallets_*.csv 等，按扩展名区分格式）
+ * 
+ * @param name - 文件名
+ * @param extension - 扩展名（不含点）
+ */
+pub fn is_result_file_name(name: &str, extension: &str) -> bool {
+    name.strip_suffix(extension).and_then(|name| name.strip_suffix('.')).and_then(result_session_id).is_some()
+}
+
+fn is_wallet_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| is_result_file_name(name, "csv"))
+}
+
+fn is_collection_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "csv") && collections::name_of(path).is_some()
+}
+
+/**
+ * FancyWallets 目录和各链子目录中的全部条目
+ */
+fn session_dir_entries(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("无法读取钱包目录: {}", e))?;
+    let mut files: Vec<PathBuf> = entries.filter_map(|entry| entry.ok().map(|e| e.path())).collect();
+    for chain in KNOWN_CHAINS {
+        if let Ok(entries) = fs::read_dir(chain.dir(dir)) {
+            files.extend(entries.filter_map(|entry| entry.ok().map(|e| e.path())));
+        }
+    }
+    Ok(files)
+}
+
+/**
+ * 列出目录中的 JSON Lines 结果文件（wallet_*.jsonl 和 wallets_*.jsonl），包括各链子目录
+ * 
+ * @param dir - FancyWallets 目录
+ * @returns 按路径排序的文件列表
+ */
+pub fn list_jsonl_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut files = session_dir_entries(dir)?;
+    files.retain(|path| {
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| is_result_file_name(name, JSONL_EXTENSION))
+    });
+    files.sort();
+    Ok(files)
+}
+
+/**
+ * 列出目录中的钱包 CSV 文件（wallet_*.csv），包括各链子目录、旧版本直接写在 FancyWallets 下的文件和集合文件
+ * 
+ * @param dir - FancyWallets 目录
+ * @returns 按路径排序的文件列表
+ */
+pub fn list_wallet_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut files = session_dir_entries(dir)?;
+    files.retain(|path| is_wallet_file(path));
+    if let Ok(entries) = fs::read_dir(dir.join(COLLECTIONS_DIR)) {
+        files.extend(entries.filter_map(|entry| entry.ok().map(|e| e.path())).filter(|path| is_collection_file(path)));
+    }
+    files.sort();
+    Ok(files)
+}
+
+/**
+ * 解析一行 CSV 钱包记录（address,private_key,pattern[,index[,chain[,entropy[,mnemonic[,wif[,created_at,attempts,duration_ms]]]]]]；
+ * 不保存私钥的文件没有 private_key 列，只有保存完整私钥的文件有 wif 列）
+ * 
+ * 没有 chain 列的旧记录按地址格式推断所属的链。字段可以带引号（旧版本写入的字段都没有引号，其中不会出现逗号和引号）。
+ * 
+ * @param layout - 文件中私钥列的写法（由标题判断）
+ */
+fn parse_row(line: &str, file: &Path, layout: CsvPrivateKeys) -> Option<SavedWallet> {
+    let fields = csv_field::split(line)?;
+    let mut fields = fields.iter().map(String::as_str);
+    let address = fields.next()?.trim();
+    let private_key = if layout == CsvPrivateKeys::Omit { "" } else { fields.next()?.trim() };
+    let pattern = fields.next().unwrap_or("").trim();
+    let index = fields.next().and_then(|index| index.trim().parse().ok());
+    if address.is_empty() || (private_key.is_empty() && layout != CsvPrivateKeys::Omit) || address == "address" {
+        return None;
+    }
+    let (chain, chain_inferred) = match fields.next().map(str::trim).filter(|chain| !chain.is_empty()) {
+        Some(chain) => (Chain::parse(chain).ok(), false),
+        None => (Chain::from_address(address), true),
+    };
+    let entropy = fields.next().and_then(RngMode::parse);
+    let mnemonic = fields.next().map(str::trim).filter(|mnemonic| !mnemonic.is_empty()).map(str::to_string);
+    if layout == CsvPrivateKeys::Full {
+        // WIF 私钥由私钥推导，不单独读取
+        fields.next();
+    }
+    let created_at = fields.next().map(str::trim).filter(|time| !time.is_empty()).map(str::to_string);
+    let attempts = fields.next().and_then(|attempts| attempts.trim().parse().ok());
+    let duration_ms = fields.next().and_then(|duration| duration.trim().parse().ok());
+    let test_wallet = entropy.is_some_and(RngMode::is_test) || (layout == CsvPrivateKeys::Full && entropy::is_test_key(private_key));
+    let fingerprint = chain
+        .and_then(|chain| chain.parse_address(address).ok())
+        .map(|address| fingerprint::phrase(&address));
+    Some(SavedWallet {
+        address: address.to_string(),
+        private_key: private_key.to_string(),
+        pattern: pattern.to_string(),
+        index,
+        chain,
+        chain_inferred,
+        fingerprint,
+        entropy,
+        test_wallet,
+        mnemonic,
+        created_at,
+        attempts,
+        duration_ms,
+        private_keys: layout,
+        file: file.to_path_buf(),
+    })
+}
+
+/// 解密后的结果文件
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DecryptedWalletFile {
+    /// 文件中的钱包记录
+    pub wallets: Vec<SavedWallet>,
+    /// 最后一行不完整（写入中断），已忽略
+    pub truncated: bool,
+}
+
+/**
+ * 解析多行钱包记录（解密后的结果文件使用；标题和无法解析的行被忽略）
+ * 
+ * @param rows - 各行内容（不含换行）
+ * @param file - 所在文件
+ * @param layout - 文件中私钥列的写法
+ */
+pub fn parse_rows(rows: &[String], file: &Path, layout: CsvPrivateKeys) -> Vec<SavedWallet> {
+    rows.iter().filter_map(|row| parse_row(row, file, layout)).collect()
+}
+
+/**
+ * 判断结果文件所属的链：各链子目录中的文件以目录为准，旧文件按记录推断
+ * 
+ * @param file - 结果文件路径
+ * @param wallets - 文件中的记录
+ * @returns (所属的链, 警告)
+ */
+fn file_chain(file: &Path, wallets: &[SavedWallet]) -> (Option<Chain>, Vec<String>) {
+    let mut warnings = Vec::new();
+    let mut chains: Vec<Chain> = Vec::new();
+    for chain in wallets.iter().filter_map(|wallet| wallet.chain) {
+        if !chains.contains(&chain) {
+            chains.push(chain);
+        }
+    }
+    let chain = match Chain::from_dir(file) {
+        Some(chain) => Some(chain),
+        None => chains.first().copied(),
+    };
+    if let Some(chain) = chain {
+        if chains.iter().any(|other| *other != chain) {
+            let names: Vec<&str> = chains.iter().map(|chain| chain.as_str()).collect();
+            warnings.push(format!("文件中混有多条链的地址（{}），请分开保存", names.join("、")));
+        }
+        if wallets.iter().any(|wallet| wallet.chain_inferred && wallet.chain.is_some()) {
+            warnings.push(format!("旧版本的结果文件没有记录链，已根据地址格式推断为 {}", chain.as_str()));
+        }
+    }
+    if wallets.iter().any(|wallet| wallet.chain.is_none()) {
+        warnings.push("部分记录无法判断所属的链".to_string());
+    }
+    (chain, warnings)
+}
+
+/**
+ * 在钱包目录中查找指定地址的记录（地址比较不区分大小写）
+ * 
+ * @param dir - FancyWallets 目录
+ * @param address - 要查找的地址
+ * @returns 找到的记录
+ */
+pub fn find_saved_wallet(dir: &Path, address: &Address) -> Result<Option<SavedWallet>, String> {
+    for file in list_wallet_files(dir)? {
+        let Ok((content, _)) = read_committed(&file) else {
+            continue;
+        };
+        let layout = content_layout(&content);
+        let found = content
+            .lines()
+            .filter_map(|line| parse_row(line, &file, layout))
+            .find(|wallet| {
+                wallet.chain == Some(Chain::Ethereum) && Address::parse(&wallet.address).is_ok_and(|found| found == *address)
+            });
+        if found.is_some() {
+            return Ok(found);
+        }
+    }
+    Ok(None)
+}
+
+/// 结果排序方式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResultsSort {
+    /// 按序号
+    Index,
+    /// 按找到的时间（同一会话内与序号顺序相同）
+    Date,
+    /// 按评分
+    Score,
+}
+
+/// 一页结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResultsPage {
+    /// 本页的钱包记录
+    pub wallets: Vec<SavedWallet>,
+    /// 下一页的游标（到达末尾后仍可用于读取之后追加的行）
+    pub next_cursor: String,
+    /// 游标之后是否还有完整的行
+    pub has_more: bool,
+    /// 文件读取状态
+    pub status: FileStatus,
+}
+
+/**
+ * 检查会话标识（只能包含字母、数字、下划线和连字符，用于拼接文件名）
+ */
+pub fn validate_session_id(session_id: &str) -> Result<(), String> {
+    if session_id.is_empty() || !session_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return Err(format!("无效的会话标识: {}", session_id));
+    }
+    Ok(())
+}
+
+/**
+ * 获取会话结果文件路径（会话标识即 CSV 文件名去掉 wallet_ 或 wallets_ 前缀和扩展名）
+ * 
+ * 依次查找各链子目录、旧版本直接写在 FancyWallets 下的文件、合并的结果文件和同名的集合；
+ * 都不存在时返回以太坊子目录中的路径。
+ * 
+ * @param dir - FancyWallets 目录
+ * @param session_id - 会话标识
+ */
+pub fn session_file(dir: &Path, session_id: &str) -> Result<PathBuf, String> {
+    validate_session_id(session_id)?;
+    let name = format!("wallet_{}.csv", session_id);
+    let combined = format!("wallets_{}.csv", session_id);
+    let candidates: Vec<PathBuf> = KNOWN_CHAINS
+        .iter()
+        .map(|chain| chain.dir(dir).join(&name))
+        .chain(std::iter::once(dir.join(&name)))
+        .chain(KNOWN_CHAINS.iter().map(|chain| chain.dir(dir).join(&combined)))
+        .chain(std::iter::once(dir.join(COLLECTIONS_DIR).join(format!("{}.csv", session_id))))
+        .collect();
+    Ok(candidates
+        .iter()
+        .find(|path| path.exists())
+        .cloned()
+        .unwrap_or_else(|| Chain::Ethereum.dir(dir).join(&name)))
+}
+
+/**
+ * 列出目录中所有有结果的会话标识（按会话开始时间排序，旧版本的本地时间文件名和新的 UTC 文件名可以混合排序）
+ * 
+ * @param dir - FancyWallets 目录
+ */
+pub fn list_sessions(dir: &Path) -> Result<Vec<String>, String> {
+    let mut sessions: Vec<String> = list_wallet_files(dir)?
+        .iter()
+        .filter_map(|file| result_session_id(file.file_stem()?.to_str()?).map(str::to_string))
+        .collect();
+    sessions.sort_by_cached_key(|session_id| (timestamps::parse_session_name(session_id).map(|t| t.utc), session_id.clone()));
+    Ok(sessions)
+}
+
+/**
+ * 按游标分页读取结果文件；只从游标位置向后读取所需的行，不会加载整个文件
+ * 
+ * 游标是下一行在文件中的字节偏移量，对调用方来说是不透明的字符串。
+ * 文件按写入顺序追加，序号和时间顺序都与行顺序一致，因此可以直接流式读取。
+ * 
+ * @param file - 结果文件路径
+ * @param cursor - 上一页返回的游标（为空时从头开始）
+ * @param limit - 每页条数（不超过 MAX_PAGE_SIZE）
+ * @param sort - 排序方式
+ * @returns 一页结果
+ */
+pub fn read_page(file: &Path, cursor: Option<&str>, limit: usize, sort: ResultsSort) -> Result<ResultsPage, String> {
+    if sort == ResultsSort::Score {
+        return Err("当前结果没有评分，无法按评分排序".to_string());
+    }
+    let limit = limit.clamp(1, MAX_PAGE_SIZE);
+    let offset: u64 = match cursor {
+        Some(cursor) => cursor.parse().map_err(|_| format!("无效的游标: {}", cursor))?,
+        None => 0,
+    };
+    
+    let mut reader = BufReader::new(File::open(file).map_err(|e| format!("无法打开结果文件: {}", e))?);
+    let file_len = reader.get_ref().metadata().map_err(|e| format!("无法读取结果文件: {}", e))?.len();
+    // 正在运行的会话只读取写入方已提交的部分
+    let committed = committed_len(file);
+    let len = committed.map_or(file_len, |c| c.min(file_len));
+    if offset > file_len {
+        return Err(format!("无效的游标: {}", offset));
+    }
+    reader.seek(SeekFrom::Start(offset)).map_err(|e| format!("无法读取结果文件: {}", e))?;
+    let mut reader = reader.take(len.saturating_sub(offset));
+    let layout = file_layout(file);
+    
+    let mut wallets = Vec::with_capacity(limit);
+    let mut position = offset;
+    let mut line = String::new();
+    let mut has_more = false;
+    let mut status = if committed.is_some() { FileStatus::ActiveSession } else { FileStatus::Complete };
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line).map_err(|e| format!("无法读取结果文件: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        // 末尾尚未写完的半行留给下一次读取
+        if !line.ends_with('\n') {
+            if status == FileStatus::Complete {
+                status = FileStatus::InProgress;
+            }
+            break;
+        }
+        if wallets.len() == limit {
+            has_more = true;
+            break;
+        }
+        position += read as u64;
+        if let Some(wallet) = parse_row(line.trim_end(), file, layout) {
+            wallets.push(wallet);
+        }
+    }
+    
+    Ok(ResultsPage { wallets, next_cursor: position.to_string(), has_more, status })
+}
+
+/**
+ * 读取结果文件中的全部记录（按页流式读取，忽略末尾尚未写完的半行）
+ * 
+ * @param file - 结果文件路径
+ */
+pub fn read_all(file: &Path) -> Result<Vec<SavedWallet>, String> {
+    let mut wallets = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let page = read_page(file, cursor.as_deref(), MAX_PAGE_SIZE, ResultsSort::Index)?;
+        wallets.extend(page.wallets);
+        if !page.has_more {
+            return Ok(wallets);
+        }
+        cursor = Some(page.next_cursor);
+    }
+}
+
+/// 结果文件概况
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WalletFileSummary {
+    /// 会话标识
+    pub session_id: String,
+    /// 文件路径
+    pub file: PathBuf,
+    /// 已完整写入的记录数
+    pub rows: usize,
+    /// 文件读取状态
+    pub status: FileStatus,
+    /// 所属的链（无法判断时为空）
+    pub chain: Option<Chain>,
+    /// 警告（例如旧文件的链由地址格式推断）
+    pub warnings: Vec<String>,
+    /// 会话备注（保存在用户配置中，由调用方填入）
+    #[serde(default)]
+    pub note: Option<String>,
+    /// 会话开始时间（UTC，RFC 3339，由文件名解析；无法解析时为空）
+    pub started_at: Option<String>,
+    /// 所属的集合（会话文件为空；集合文件的 session_id 即集合名称）
+    pub collection: Option<String>,
+}
+
+/**
+ * 列出目录中的结果文件及其状态（正在写入的文件不会被当作损坏）
+ * 
+ * 按集合分组：先列出各会话文件（按开始时间），再按名称列出集合文件。
+ * 
+ * @param dir - FancyWallets 目录
+ * @param chain - 只列出该链的文件（可选）
+ */
+pub fn list_files(dir: &Path, chain: Option<Chain>) -> Result<Vec<WalletFileSummary>, String> {
+    let mut summaries = Vec::new();
+    for file in list_wallet_files(dir)? {
+        let collection = collections::name_of(&file).map(str::to_string);
+        let stem = file.file_stem().and_then(|s| s.to_str());
+        let Some(session_id) = collection.clone().or_else(|| result_session_id(stem?).map(str::to_string)) else {
+            continue;
+        };
+        let (content, status) = read_committed(&file)?;
+        let layout = content_layout(&content);
+        let wallets: Vec<SavedWallet> = content.lines().filter_map(|line| parse_row(line, &file, layout)).collect();
+        let (file_chain, mut warnings) = file_chain(&file, &wallets);
+        if chain.is_some_and(|chain| file_chain != Some(chain)) {
+            continue;
+        }
+        let started = collection.is_none().then(|| timestamps::parse_session_name(&session_id)).flatten();
+        if started.is_some_and(|t| t.ambiguous) {
+            warnings.push("文件名中的本地时间在夏令时切换时出现两次，开始时间按较早的一次计算".to_string());
+        }
+        let started_at = started.map(|t| timestamps::to_rfc3339(t.utc));
+        summaries.push(WalletFileSummary { session_id, rows: wallets.len(), file, status, chain: file_chain, warnings, note: None, started_at, collection });
+    }
+    summaries.sort_by(|a, b| (&a.collection, &a.started_at, &a.session_id).cmp(&(&b.collection, &b.started_at, &b.session_id)));
+    Ok(summaries)
+}
+
+/// 校验失败的行
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CorruptRow {
+    /// 行号（从 1 开始，包含标题行）
+    pub line: usize,
+    /// 原因
+    pub reason: String,
+}
+
+/// 结果文件校验报告
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WalletFileReport {
+    /// 文件路径
+    pub file: PathBuf,
+    /// 文件读取状态
+    pub status: FileStatus,
+    /// 校验通过的记录数
+    pub valid_rows: usize,
+    /// 校验失败的行
+    pub corrupt_rows: Vec<CorruptRow>,
+    /// 所属的链（无法判断时为空）
+    pub chain: Option<Chain>,
+    /// 警告（例如旧文件的链由地址格式推断）
+    pub warnings: Vec<String>,
+}
+
+/**
+ * 校验一行记录：按记录所属的链解析地址，由私钥推导出的地址必须与记录的地址一致
+ * 
+ * 没有保存完整私钥的记录只检查地址格式。以太坊和波场使用同一个 20 字节地址，只是文本格式不同；比特币比较压缩公钥的 HASH160；
+ * Solana 比较 ed25519 公钥的 20 字节标识。
+ */
+fn verify_row(secp: &Secp256k1<secp256k1::SignOnly>, wallet: &SavedWallet) -> Result<(), String> {
+    let Some(chain) = wallet.chain else {
+        return Err(format!("无法判断地址 {} 所属的链", wallet.address));
+    };
+    if wallet.private_keys != CsvPrivateKeys::Full {
+        return match chain {
+            Chain::Ethereum => Address::parse_canonical(&wallet.address).map(|_| ()).map_err(|e| e.to_string()),
+            _ => chain.parse_address(&wallet.address).map(|_| ()),
+        };
+    }
+    let key = PrivKeyHex::parse(&wallet.private_key)?.secret_key();
+    let (derived, derived_text) = chain.derive_address(secp, &key, &wallet.address);
+    if chain.parse_address(&wallet.address).ok() != Some(derived) {
+        return Err(format!("私钥推导出的地址为 {}，记录的地址为 {}", derived_text, wallet.address));
+    }
+    if chain == Chain::Ethereum && Address::parse_canonical(&wallet.address).is_err() {
+        return Err(format!("记录的地址 {} 不是规范格式（应为 {}）", wallet.address, derived_text));
+    }
+    Ok(())
+}
+
+/**
+ * 校验结果文件中的每一行；正在写入的最后半行不计为损坏
+ * 
+ * @param file - 结果文件路径
+ */
+pub fn verify_file(file: &Path) -> Result<WalletFileReport, String> {
+    let (content, status) = read_committed(file)?;
+    let secp = Secp256k1::signing_only();
+    let mut valid_rows = 0;
+    let mut corrupt_rows = Vec::new();
+    let mut wallets = Vec::new();
+    let layout = content_layout(&content);
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim_end();
+        if line.is_empty() || (i == 0 && line.starts_with("address,")) {
+            continue;
+        }
+        let Some(wallet) = parse_row(line, file, layout) else {
+            corrupt_rows.push(CorruptRow { line: i + 1, reason: "缺少地址或私钥".to_string() });
+            continue;
+        };
+        match verify_row(&secp, &wallet) {
+            Ok(()) => valid_rows += 1,
+            Err(reason) => corrupt_rows.push(CorruptRow { line: i + 1, reason }),
+        }
+        wallets.push(wallet);
+    }
+    let (chain, mut warnings) = file_chain(file, &wallets);
+    if layout != CsvPrivateKeys::Full {
+        warnings.push(format!("文件没有保存完整的私钥（{}），只检查了地址格式", layout.as_str()));
+    }
+    Ok(WalletFileReport { file: file.to_path_buf(), status, valid_rows, corrupt_rows, chain, warnings })
+}
+
+/// 历史记录中的一个钱包
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GeneratedWallet {
+    /// 钱包地址（文件中的原文）
+    pub address: String,
+    /// 靓号模式
+    pub pattern: String,
+    /// 所属的链（无法识别时为空）
+    pub chain: Option<Chain>,
+    /// 私钥（只有 include_keys 时才有；文件没有保存完整的私钥时为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<String>,
+    /// 所在文件的文件名
+    pub source_file: String,
+    /// 所在文件的完整路径
+    pub file: PathBuf,
+    /// 所在文件的修改时间（UTC，RFC 3339；无法读取时为空）
+    pub modified_at: Option<String>,
+    /// 找到时间（RFC 3339；旧文件中没有时为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+}
+
+/// 历史记录
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct GeneratedWallets {
+    /// 各文件中的钱包（按文件路径和文件中的顺序）
+    pub wallets: Vec<GeneratedWallet>,
+    /// 读取的文件数
+    pub files: usize,
+    /// 无法解析而跳过的行数（例如手动编辑过的旧文件）
+    pub skipped_rows: usize,
+    /// 无法读取而跳过的文件
+    pub skipped_files: Vec<PathBuf>,
+}
+
+/// JSON Lines 文件中的一行（只取历史记录需要的字段，字段名与序列化的钱包相同）
+#[derive(Deserialize)]
+struct JsonlWallet {
+    address: String,
+    private_key: String,
+    pattern: String,
+    chain: Option<Chain>,
+    /// 不使用以太坊格式的链上的地址（以太坊的记录没有这一项）
+    chain_address: Option<String>,
+    /// 找到时间（旧版本的记录没有这一项）
+    #[serde(default)]
+    created_at: Option<String>,
+    /// 旧版本的记录中的找到时间
+    #[serde(default)]
+    timestamp: Option<String>,
+}
+
+/**
+ * 列出目录中全部 CSV 和 JSON Lines 结果文件里的钱包
+ * 
+ * 无法解析的行（缺少列、地址格式不对、不是有效的 JSON）只计入 skipped_rows，不中止读取；
+ * 正在写入的最后半行不计入。
+ * 
+ * @param dir - FancyWallets 目录
+ * @param include_keys - 是否带有私钥
+ */
+pub fn list_generated(dir: &Path, include_keys: bool) -> Result<GeneratedWallets, String> {
+    let mut result = GeneratedWallets::default();
+    for file in list_wallet_files(dir)?.into_iter().chain(list_jsonl_files(dir)?) {
+        let Ok((wallets, skipped_rows)) = read_generated_file(&file, include_keys) else {
+            result.skipped_files.push(file);
+            continue;
+        };
+        result.files += 1;
+        result.wallets.extend(wallets);
+        result.skipped_rows += skipped_rows;
+    }
+    Ok(result)
+}
+
+/**
+ * 读取一个 CSV 或 JSON Lines 结果文件（按扩展名区分）里的钱包
+ * 
+ * 无法解析的行只计入跳过的行数；正在写入的最后半行不计入。
+ * 
+ * @param file - 结果文件路径
+ * @param include_keys - 是否带有私钥（文件没有保存完整的私钥时仍为空）
+ * @returns (文件中的钱包, 跳过的行数)
+ */
+pub fn read_generated_file(file: &Path, include_keys: bool) -> Result<(Vec<GeneratedWallet>, usize), String> {
+    let jsonl = file.extension().is_some_and(|ext| ext == JSONL_EXTENSION);
+    let (content, _) = read_committed(file)?;
+    let source_file = file.file_name().and_then(|name| name.to_str()).unwrap_or_default().to_string();
+    let modified_at = fs::metadata(file)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .map(|time| timestamps::to_rfc3339(time.into()));
+    let layout = if jsonl { CsvPrivateKeys::Full } else { content_layout(&content) };
+    let mut wallets = Vec::new();
+    let mut skipped_rows = 0;
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim_end();
+        if line.is_empty() || (!jsonl && i == 0 && CsvPrivateKeys::from_header(line).is_some()) {
+            continue;
+        }
+        let parsed = if jsonl {
+            serde_json::from_str::<JsonlWallet>(line).ok().map(|wallet| {
+                let created_at = wallet.created_at.filter(|created_at| !created_at.is_empty()).or(wallet.timestamp);
+                (wallet.chain_address.unwrap_or(wallet.address), wallet.private_key, wallet.pattern, wallet.chain, created_at)
+            })
+        } else {
+            parse_row(line, file, layout).map(|wallet| (wallet.address, wallet.private_key, wallet.pattern, wallet.chain, wallet.created_at))
+        };
+        let Some((address, private_key, pattern, chain, created_at)) = parsed.filter(|(address, _, _, chain, _)| {
+            chain.is_some_and(|chain| chain.parse_address(address).is_ok())
+        }) else {
+            skipped_rows += 1;
+            continue;
+        };
+        wallets.push(GeneratedWallet {
+            address,
+            pattern,
+            chain,
+            private_key: (include_keys && layout == CsvPrivateKeys::Full).then_some(private_key),
+            source_file: source_file.clone(),
+            file: file.to_path_buf(),
+            modified_at: modified_at.clone(),
+            created_at,
+        });
+    }
+    Ok((wallets, skipped_rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::public_key_to_checksum_address;
+    use crate::pgp_export;
+    use crate::recovery;
+    use crate::gui::{csv_row_bytes, jsonl_row_bytes, session_csv_path, write_jsonl_row, write_wallet_row, Wallet};
+    use crate::replay::ReplayRecorder;
+    use crate::results_db;
+    use crate::templates::GenerationParams;
+    use secp256k1::{PublicKey, SecretKey};
+    use std::io::Write;
+    use crate::test_support::{TRON_ADDRESS, TRON_ZERO_ADDRESS, with_data_dir};
+    use crate::self_test::KNOWN_KEYS;
+
+    /// 并发读写检查写入的行数
+    const CONCURRENT_ROWS: usize = 200;
+    /// 大结果文件分页检查生成的行数
+    const LARGE_RESULT_ROWS: u64 = 100_000;
+
+    /**
+     * 并发读写：写入方分两次写出每一行时，读取方只能看到完整的行，不会报告损坏
+     */
+    #[test]
+    fn concurrent_read() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        let dir = std::env::temp_dir().join(format!("fancy-wallet-test-concurrent-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).map_err(|e| format!("无法创建临时目录: {}", e))?;
+        let file = dir.join("wallet_selftest.csv");
+        
+        let result = (|| {
+            let rows: Vec<String> = (0..CONCURRENT_ROWS)
+                .map(|i| {
+                    let (private_key, _) = KNOWN_KEYS[i % KNOWN_KEYS.len()];
+                    let key = SecretKey::from_slice(&hex::decode(private_key).unwrap_or_default()).map_err(|e| e.to_string())?;
+                    Ok(format!("0x{},{},*,{}\n", public_key_to_checksum_address(&PublicKey::from_secret_key(&secp, &key)), private_key, i + 1))
+                })
+                .collect::<Result<_, String>>()?;
+            
+            let active = ActiveFile::register(&file);
+            std::fs::write(&file, "address,private_key,pattern,index\n").map_err(|e| e.to_string())?;
+            commit(&file, std::fs::metadata(&file).map_err(|e| e.to_string())?.len());
+            
+            let writer_file = file.clone();
+            let writer = std::thread::spawn(move || -> Result<(), String> {
+                let mut out = std::fs::OpenOptions::new().append(true).open(&writer_file).map_err(|e| e.to_string())?;
+                for row in &rows {
+                    let (head, tail) = row.split_at(row.len() / 2);
+                    out.write_all(head.as_bytes()).map_err(|e| e.to_string())?;
+                    std::thread::yield_now();
+                    out.write_all(tail.as_bytes()).map_err(|e| e.to_string())?;
+                    commit(&writer_file, out.metadata().map_err(|e| e.to_string())?.len());
+                }
+                Ok(())
+            });
+            
+            while !writer.is_finished() {
+                let report = verify_file(&file)?;
+                if let Some(row) = report.corrupt_rows.first() {
+                    return Err(format!("写入过程中第 {} 行被报告为损坏: {}", row.line, row.reason));
+                }
+                if report.status != FileStatus::ActiveSession {
+                    return Err(format!("写入过程中文件状态为 {:?}", report.status));
+                }
+            }
+            writer.join().map_err(|_| "写入线程异常退出".to_string())??;
+            drop(active);
+            
+            // 会话结束后残留的半行：忽略并标记为 in_progress
+            let mut out = std::fs::OpenOptions::new().append(true).open(&file).map_err(|e| e.to_string())?;
+            out.write_all(b"0x7E5F4552091A").map_err(|e| e.to_string())?;
+            let report = verify_file(&file)?;
+            if !report.corrupt_rows.is_empty() || report.valid_rows != CONCURRENT_ROWS {
+                return Err(format!("写入结束后校验到 {} 行有效、{} 行损坏", report.valid_rows, report.corrupt_rows.len()));
+            }
+            if report.status != FileStatus::InProgress {
+                return Err(format!("末尾有半行时文件状态为 {:?}", report.status));
+            }
+            Ok(())
+        })();
+        
+        let _ = std::fs::remove_dir_all(&dir);
+        result
+    }
+
+    /**
+     * 地址的规范格式：各种写法解析为同一地址，写入每种输出格式后读回的地址和原文都与规范格式一致
+     */
+    #[test]
+    fn address_round_trip() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        let (private_key, checksum) = KNOWN_KEYS[3];
+        let canonical = format!("0x{}", checksum);
+        let address = Address::parse_canonical(&canonical)?;
+        let key = PrivKeyHex::parse(private_key)?.secret_key();
+        if Address::from_public_key(&PublicKey::from_secret_key(&secp, &key)) != address || address.to_string() != canonical {
+            return Err(format!("规范格式不一致: {}", address));
+        }
+        for input in [checksum.to_lowercase(), checksum.to_uppercase(), format!("0X{}", checksum.to_lowercase())] {
+            if Address::parse(&input)? != address {
+                return Err(format!("{} 没有解析为 {}", input, canonical));
+            }
+            if Address::parse_canonical(&input).is_ok() {
+                return Err(format!("非规范格式 {} 被当作规范格式接受", input));
+            }
+        }
+        if Chain::Tron.format_address(Address::from([0u8; 20])) != TRON_ZERO_ADDRESS {
+            return Err(format!("全零地址的波场格式应为 {}", TRON_ZERO_ADDRESS));
+        }
+        
+        with_data_dir("address", |dir| {
+            let mut written = Vec::new();
+            
+            // JSON：事件、回放和导出都经过 serde
+            let wallet = Wallet {
+                index: 1,
+                address,
+                private_key: SecretHex::from(private_key.to_string()),
+                attempts: 1,
+                duration: 0,
+                matched_spans: Vec::new(),
+                rng_mode: RngMode::ReseededCsprng,
+                test_wallet: false,
+                mnemonic: None,
+                chain_address: None,
+                matched_pattern: String::new(),
+                created_at: timestamps::now_rfc3339(),
+            };
+            let json = serde_json::to_string(&wallet).map_err(|e| e.to_string())?;
+            let lowercase_json = json.replace(&canonical, &canonical.to_lowercase());
+            let parsed: Wallet = serde_json::from_str(&lowercase_json).map_err(|e| e.to_string())?;
+            written.push(("json", json.contains(&canonical), parsed.address));
+            
+            // CSV 结果文件
+            let file = Chain::Ethereum.dir(dir).join("wallet_address.csv");
+            std::fs::create_dir_all(file.parent().unwrap_or(dir)).map_err(|e| e.to_string())?;
+            write_wallet_row(&file, &wallet, "*", Chain::Ethereum, CsvPrivateKeys::Full)?;
+            let report = verify_file(&file)?;
+            let saved = find_saved_wallet(dir, &address)?.ok_or("结果文件中找不到写入的地址")?;
+            written.push(("csv", report.valid_rows == 1 && saved.address == canonical, Address::parse(&saved.address)?));
+            
+            // 回放文件
+            let mut replay = ReplayRecorder::create(dir, "*", Chain::Ethereum)?;
+            replay.record_found(address, 1, 1);
+            let raw = std::fs::read_to_string(replay.path()).map_err(|e| e.to_string())?;
+            let found = crate::replay::read_replay(replay.path())?.events.into_iter().find_map(|event| match event.kind {
+                crate::replay::ReplayEventKind::Found { address, .. } => Some(address),
+                _ => None,
+            });
+            written.push(("replay", raw.contains(&canonical), found.ok_or("回放文件中没有找到的地址")?));
+            
+            // 拆分密钥结果
+            let split = crate::split_key::SplitKeyResult {
+                request_id: "selftest".to_string(),
+                public_key: String::new(),
+                pattern: "*".to_string(),
+                tweak: private_key.to_string(),
+                address,
+                attempts: 1,
+                duration: 0,
+            };
+            let json = serde_json::to_string(&split).map_err(|e| e.to_string())?;
+            let parsed: crate::split_key::SplitKeyResult = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+            written.push(("split_key", json.contains(&canonical), parsed.address));
+            
+            // 加密导出：旧文件中大小写不规范的以太坊地址统一为规范格式
+            let mut legacy = saved.clone();
+            legacy.address = canonical.to_lowercase();
+            let exported = pgp_export::ConsolidatedResults::new("selftest", vec![legacy], None).wallets.remove(0);
+            written.push(("pgp_export", exported.address == canonical, Address::parse(&exported.address)?));
+            
+            // 恢复单
+            let sheet = recovery::RecoverySheet {
+                address,
+                pattern: "*".to_string(),
+                secret: None,
+                derivation_path: None,
+                checksum_variant: "EIP-55".to_string(),
+                created_at: String::new(),
+                test_wallet: false,
+            };
+            written.push(("recovery_sheet", recovery::render_html(&sheet)?.contains(&canonical), address));
+            
+            for (format, canonical_text, read_back) in &written {
+                if !canonical_text || *read_back != address {
+                    return Err(format!("{} 中的地址没有按规范格式写入或读回为 {}", format, read_back));
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /**
+     * CSV 私钥列的三种写法：标题与列一致，打码和不保存私钥的文件中没有完整的私钥，结果浏览器和校验按标题读取；
+     * 已有的同名文件使用另一种写法时改用带序号的新文件，不在旧文件中混入不同列的行
+     */
+    #[test]
+    fn csv_private_keys() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        with_data_dir("csv_private_keys", |dir| {
+            let (key, checksum) = KNOWN_KEYS[0];
+            let secret = PrivKeyHex::parse(key)?.secret_key();
+            let wallet = Wallet {
+                index: 1,
+                address: Address::from_public_key(&PublicKey::from_secret_key(&secp, &secret)),
+                private_key: SecretHex::from(key.to_string()),
+                attempts: 1,
+                duration: 0,
+                matched_spans: Vec::new(),
+                rng_mode: RngMode::default(),
+                test_wallet: false,
+                mnemonic: None,
+                chain_address: None,
+                matched_pattern: String::new(),
+                created_at: timestamps::now_rfc3339(),
+            };
+            let masked = mask_private_key(key);
+            if masked != format!("{}...{}", &key[..4], &key[key.len() - 4..]) {
+                return Err(format!("打码的私钥不正确: {}", masked));
+            }
+            for (layout, expected_key) in [(CsvPrivateKeys::Full, key), (CsvPrivateKeys::Masked, masked.as_str()), (CsvPrivateKeys::Omit, "")] {
+                let path = dir.join(format!("wallet_{}.csv", layout.as_str()));
+                write_wallet_row(&path, &wallet, "*", Chain::Ethereum, layout)?;
+                let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+                let columns = |line: &str| line.split(',').count();
+                let mut lines = content.lines();
+                let (header, row) = (lines.next().unwrap_or_default(), lines.next().unwrap_or_default());
+                if header != layout.header() || columns(header) != columns(row) {
+                    return Err(format!("{} 的标题与列不一致: {:?}", layout.as_str(), content));
+                }
+                if layout != CsvPrivateKeys::Full && content.contains(key) {
+                    return Err(format!("{} 的文件中有完整的私钥", layout.as_str()));
+                }
+                let saved = read_all(&path)?;
+                let [saved] = &saved[..] else {
+                    return Err(format!("{} 的文件读出 {} 条记录", layout.as_str(), saved.len()));
+                };
+                if saved.address != format!("0x{}", checksum) || saved.private_key != expected_key || saved.private_keys != layout || saved.pattern != "*" {
+                    return Err(format!("{} 的文件读出的记录不正确: {:?}", layout.as_str(), saved));
+                }
+                let report = verify_file(&path)?;
+                if report.valid_rows != 1 || !report.corrupt_rows.is_empty() || (layout != CsvPrivateKeys::Full) == report.warnings.is_empty() {
+                    return Err(format!("{} 的文件校验结果不正确: {:?}", layout.as_str(), report));
+                }
+            }
+            
+            // 已有的同名文件使用另一种写法：改用带序号的新文件，旧文件不变
+            let stem = "wallet_omit";
+            let before = std::fs::read_to_string(dir.join("wallet_omit.csv")).map_err(|e| e.to_string())?;
+            let same = session_csv_path(dir, stem, "", Some(CsvPrivateKeys::Omit.header()))?;
+            let other = session_csv_path(dir, stem, "", Some(CsvPrivateKeys::Full.header()))?;
+            let encrypted = session_csv_path(dir, stem, "", None)?;
+            if same != dir.join("wallet_omit.csv") || other != dir.join("wallet_omit-2.csv") || encrypted != other {
+                return Err(format!("已有文件时选择的文件不正确: {} {} {}", same.display(), other.display(), encrypted.display()));
+            }
+            write_wallet_row(&other, &wallet, "*", Chain::Ethereum, CsvPrivateKeys::Full)?;
+            if std::fs::read_to_string(dir.join("wallet_omit.csv")).map_err(|e| e.to_string())? != before {
+                return Err("写入另一种写法时修改了已有的文件".to_string());
+            }
+            Ok(())
+        })
+    }
+
+    /**
+     * CSV 加引号：含逗号和引号的模式原样读回，保存时间、尝试次数和耗时各占一列；旧标题的文件不追加新格式的行
+     */
+    #[test]
+    fn csv_quoting() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        with_data_dir("csv_quoting", |dir| {
+            for field in ["", "plain", "a,b", "say \"hi\"", "\"", ",\",\"", "x\ry"] {
+                if csv_field::split(&csv_field::join([field, field])) != Some(vec![field.to_string(), field.to_string()]) {
+                    return Err(format!("字段 {:?} 加引号后无法原样拆分", field));
+                }
+            }
+            for line in ["\"unclosed", "\"a\"b,c"] {
+                if csv_field::split(line).is_some() {
+                    return Err(format!("格式错误的行 {:?} 没有被拒绝", line));
+                }
+            }
+            
+            let (key, checksum) = KNOWN_KEYS[0];
+            let secret = PrivKeyHex::parse(key)?.secret_key();
+            let wallet = Wallet {
+                index: 3,
+                address: Address::from_public_key(&PublicKey::from_secret_key(&secp, &secret)),
+                private_key: SecretHex::from(key.to_string()),
+                attempts: 123_456,
+                duration: 789,
+                matched_spans: Vec::new(),
+                rng_mode: RngMode::default(),
+                test_wallet: false,
+                mnemonic: None,
+                chain_address: None,
+                matched_pattern: String::new(),
+                created_at: timestamps::now_rfc3339(),
+            };
+            let pattern = "/^a{1,2}\"x\",b/";
+            let before = timestamps::now_rfc3339();
+            for layout in [CsvPrivateKeys::Full, CsvPrivateKeys::Masked, CsvPrivateKeys::Omit] {
+                let path = dir.join(format!("wallet_{}.csv", layout.as_str()));
+                write_wallet_row(&path, &wallet, pattern, Chain::Ethereum, layout)?;
+                let saved = read_all(&path)?;
+                let [saved] = &saved[..] else {
+                    return Err(format!("{} 的文件读出 {} 条记录", layout.as_str(), saved.len()));
+                };
+                let saved_at = saved.created_at.as_deref().and_then(timestamps::parse_rfc3339);
+                if saved.address != format!("0x{}", checksum) || saved.pattern != pattern || saved.index != Some(3) {
+                    return Err(format!("{} 的文件读出的记录不正确: {:?}", layout.as_str(), saved));
+                }
+                if saved.attempts != Some(123_456) || saved.duration_ms != Some(789) || saved_at < timestamps::parse_rfc3339(&before) {
+                    return Err(format!("{} 的文件中保存时间、尝试次数或耗时不正确: {:?}", layout.as_str(), saved));
+                }
+                let report = verify_file(&path)?;
+                if report.valid_rows != 1 || !report.corrupt_rows.is_empty() {
+                    return Err(format!("{} 的文件校验结果不正确: {:?}", layout.as_str(), report));
+                }
+            }
+            let estimate = csv_row_bytes(pattern, Chain::Ethereum, RngMode::default(), None, CsvPrivateKeys::Full);
+            let written = std::fs::metadata(dir.join("wallet_full.csv")).map_err(|e| e.to_string())?.len() - CSV_HEADER.len() as u64 - 1;
+            if estimate < written {
+                return Err(format!("每行的字节数估计为 {}，小于实际写入的 {}", estimate, written));
+            }
+            
+            // 旧版本（v6）的文件：读出的记录没有新列，同名会话改用新文件
+            let legacy_header = CSV_HEADERS[5];
+            std::fs::write(dir.join("wallet_legacy.csv"), format!("{}\n0x{},{},*,1,ethereum,,,\n", legacy_header, checksum, key)).map_err(|e| e.to_string())?;
+            let legacy = read_all(&dir.join("wallet_legacy.csv"))?;
+            if legacy.len() != 1 || legacy[0].created_at.is_some() || legacy[0].attempts.is_some() {
+                return Err(format!("旧格式的文件读出 {:?}", legacy));
+            }
+            let next = session_csv_path(dir, "wallet_legacy", "", Some(CSV_HEADER))?;
+            if next != dir.join("wallet_legacy-2.csv") {
+                return Err(format!("旧格式的文件被选为追加目标: {}", next.display()));
+            }
+            if CsvPrivateKeys::from_header("address,private_key_masked,pattern,index,chain,entropy,mnemonic") != Some(CsvPrivateKeys::Masked) {
+                return Err("无法识别旧版本打码私钥文件的标题".to_string());
+            }
+            Ok(())
+        })
+    }
+
+    /**
+     * JSON Lines 结果文件：每行一个序列化的钱包，整个文件能解析回原来的 Vec<Wallet>，每行带有模式和保存时间；
+     * 输出格式的名称与模板中保存的一致
+     */
+    #[test]
+    fn jsonl_output() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        for format in [OutputFormat::Csv, OutputFormat::Jsonl, OutputFormat::Both] {
+            if OutputFormat::parse(Some(&format.as_str().to_uppercase())) != Ok(format) {
+                return Err(format!("无法解析输出格式 {}", format.as_str()));
+            }
+        }
+        if OutputFormat::parse(None) != Ok(OutputFormat::Csv) || OutputFormat::parse(Some("parquet")).is_ok() {
+            return Err("默认的输出格式不是 csv，或不支持的格式没有被拒绝".to_string());
+        }
+        with_data_dir("jsonl_output", |dir| {
+            let path = dir.join(format!("wallet_8_selftest.{}", JSONL_EXTENSION));
+            let mut wallets = Vec::new();
+            for (index, (key, _)) in KNOWN_KEYS.iter().enumerate().take(3) {
+                let secret = PrivKeyHex::parse(key)?.secret_key();
+                let wallet = Wallet {
+                    index: index as u64 + 1,
+                    address: Address::from_public_key(&PublicKey::from_secret_key(&secp, &secret)),
+                    private_key: SecretHex::from(key.to_string()),
+                    attempts: 1000 * (index as u64 + 1),
+                    duration: 10 * index as u64,
+                    matched_spans: Vec::new(),
+                    rng_mode: RngMode::default(),
+                    test_wallet: false,
+                    mnemonic: (index == 2).then(|| "abandon ".repeat(11) + "about"),
+                    chain_address: None,
+                    matched_pattern: String::new(),
+                    created_at: timestamps::now_rfc3339(),
+                };
+                write_jsonl_row(&path, &wallet, "8", Chain::Ethereum)?;
+                wallets.push(wallet);
+            }
+            let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            let parsed: Vec<Wallet> = content
+                .lines()
+                .map(|line| serde_json::from_str(line).map_err(|e| format!("无法解析 JSON Lines 的一行: {}", e)))
+                .collect::<Result<_, String>>()?;
+            let summary = |wallet: &Wallet| {
+                (wallet.index, wallet.address, wallet.private_key.clone(), wallet.attempts, wallet.duration, wallet.mnemonic.clone())
+            };
+            if parsed.iter().map(summary).collect::<Vec<_>>() != wallets.iter().map(summary).collect::<Vec<_>>() {
+                return Err(format!("解析回的钱包与写入的不一致: {:?}", parsed));
+            }
+            let row_limit = jsonl_row_bytes("8", Chain::Ethereum, RngMode::default(), Some(12));
+            for line in content.lines() {
+                let value: serde_json::Value = serde_json::from_str(line).map_err(|e| e.to_string())?;
+                let timestamp = value["timestamp"].as_str().unwrap_or_default();
+                if value["pattern"] != "8" || value["chain"] != "ethereum" || timestamps::parse_rfc3339(timestamp).is_none() {
+                    return Err(format!("JSON Lines 的一行缺少模式、链或保存时间: {}", line));
+                }
+                if line.len() as u64 + 1 > row_limit {
+                    return Err(format!("一行 {} 字节超过估计的上限 {} 字节", line.len() + 1, row_limit));
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /**
+     * 历史记录：读取各链子目录中的 CSV 和 JSON Lines 文件，默认不带私钥；手动编辑过的坏行被跳过并计数，不中止读取
+     */
+    #[test]
+    fn generated_wallets() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        with_data_dir("generated_wallets", |dir| {
+            let chain_dir = Chain::Ethereum.dir(dir);
+            std::fs::create_dir_all(&chain_dir).map_err(|e| e.to_string())?;
+            let wallets = KNOWN_KEYS[..3]
+                .iter()
+                .enumerate()
+                .map(|(i, (key, _))| {
+                    let secret = PrivKeyHex::parse(key)?.secret_key();
+                    Ok(Wallet {
+                        index: i as u64 + 1,
+                        address: Address::from_public_key(&PublicKey::from_secret_key(&secp, &secret)),
+                        private_key: SecretHex::from(key.to_string()),
+                        attempts: 1,
+                        duration: 0,
+                        matched_spans: Vec::new(),
+                        rng_mode: RngMode::default(),
+                        test_wallet: false,
+                        mnemonic: None,
+                        chain_address: None,
+                        matched_pattern: String::new(),
+                        created_at: timestamps::now_rfc3339(),
+                    })
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            let csv = chain_dir.join("wallet_8_a.csv");
+            write_wallet_row(&csv, &wallets[0], "8", Chain::Ethereum, CsvPrivateKeys::Full)?;
+            let mut file = std::fs::OpenOptions::new().append(true).open(&csv).map_err(|e| e.to_string())?;
+            file.write_all(b"not-an-address,1234,8\n\n").map_err(|e| e.to_string())?;
+            drop(file);
+            write_wallet_row(&csv, &wallets[1], "8", Chain::Ethereum, CsvPrivateKeys::Full)?;
+            write_wallet_row(&chain_dir.join("wallet_8_b.csv"), &wallets[1], "8", Chain::Ethereum, CsvPrivateKeys::Omit)?;
+            let jsonl = chain_dir.join(format!("wallet_8_c.{}", JSONL_EXTENSION));
+            write_jsonl_row(&jsonl, &wallets[2], "8", Chain::Ethereum)?;
+            let mut file = std::fs::OpenOptions::new().append(true).open(&jsonl).map_err(|e| e.to_string())?;
+            file.write_all(b"{\"address\": \n").map_err(|e| e.to_string())?;
+            drop(file);
+            
+            let redacted = list_generated(dir, false)?;
+            let addresses: Vec<String> = redacted.wallets.iter().map(|wallet| wallet.address.clone()).collect();
+            let expected: Vec<String> = [0, 1, 1, 2].iter().map(|&i| wallets[i].address.to_string()).collect();
+            if addresses != expected || redacted.files != 3 || redacted.skipped_rows != 2 {
+                return Err(format!("历史记录不正确: {:?}", redacted));
+            }
+            if redacted.wallets.iter().any(|wallet| wallet.private_key.is_some() || wallet.modified_at.is_none()) {
+                return Err("默认的历史记录带有私钥，或缺少文件修改时间".to_string());
+            }
+            if redacted.wallets[3].source_file != "wallet_8_c.jsonl" || redacted.wallets[3].pattern != "8" {
+                return Err(format!("JSON Lines 记录的来源文件或模式不正确: {:?}", redacted.wallets[3]));
+            }
+            let with_keys = list_generated(dir, true)?;
+            let keys: Vec<Option<&str>> = with_keys.wallets.iter().map(|wallet| wallet.private_key.as_deref()).collect();
+            if keys != [Some(KNOWN_KEYS[0].0), Some(KNOWN_KEYS[1].0), None, Some(KNOWN_KEYS[2].0)] {
+                return Err(format!("include_keys 时的私钥不正确: {:?}", keys));
+            }
+            Ok(())
+        })
+    }
+
+    /**
+     * 旧结果目录：没有 chain 列的文件按地址格式推断链并给出警告，波场记录不会被当作以太坊记录校验
+     */
+    #[test]
+    fn legacy_chain_inference() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        let dir = std::env::temp_dir().join(format!("fancy-wallet-test-chains-{}", std::process::id()));
+        let chain_dir = Chain::Ethereum.dir(&dir);
+        std::fs::create_dir_all(&chain_dir).map_err(|e| format!("无法创建临时目录: {}", e))?;
+        
+        let result = (|| {
+            let mut rows = Vec::new();
+            for (private_key, _) in &KNOWN_KEYS[..2] {
+                let key = PrivKeyHex::parse(private_key)?.secret_key();
+                rows.push(format!("0x{},{},*", public_key_to_checksum_address(&PublicKey::from_secret_key(&secp, &key)), private_key));
+            }
+            let tron_row = format!("{},{},*", TRON_ADDRESS, KNOWN_KEYS[2].0);
+            let write = |name: &str, lines: &[&String]| {
+                let body: String = lines.iter().map(|line| format!("{}\n", line)).collect();
+                std::fs::write(dir.join(name), format!("address,private_key,pattern\n{}", body)).map_err(|e| e.to_string())
+            };
+            write("wallet_legacy.csv", &[&rows[0], &rows[1]])?;
+            write("wallet_tron.csv", &[&tron_row])?;
+            write("wallet_mixed.csv", &[&rows[0], &tron_row])?;
+            let wallet = Wallet {
+                index: 1,
+                address: Address::parse(rows[0].split(',').next().unwrap_or_default())?,
+                private_key: SecretHex::from(KNOWN_KEYS[0].0.to_string()),
+                attempts: 1,
+                duration: 0,
+                matched_spans: Vec::new(),
+                rng_mode: RngMode::ReseededCsprng,
+                test_wallet: false,
+                mnemonic: None,
+                chain_address: None,
+                matched_pattern: String::new(),
+                created_at: timestamps::now_rfc3339(),
+            };
+            write_wallet_row(&chain_dir.join("wallet_new.csv"), &wallet, "*", Chain::Ethereum, CsvPrivateKeys::Full)?;
+            
+            let files = list_files(&dir, None)?;
+            let summary = |session: &str| {
+                files
+                    .iter()
+                    .find(|file| file.session_id == session)
+                    .ok_or_else(|| format!("没有列出 wallet_{}.csv", session))
+            };
+            let expected = [
+                ("legacy", Chain::Ethereum, Some("推断")),
+                ("tron", Chain::Tron, Some("推断")),
+                ("mixed", Chain::Ethereum, Some("混有")),
+                ("new", Chain::Ethereum, None),
+            ];
+            for (session, chain, warning) in expected {
+                let file = summary(session)?;
+                if file.chain != Some(chain) {
+                    return Err(format!("wallet_{}.csv 的链为 {:?}，期望 {:?}", session, file.chain, chain));
+                }
+                match warning {
+                    Some(warning) if !file.warnings.iter().any(|w| w.contains(warning)) => {
+                        return Err(format!("wallet_{}.csv 缺少“{}”警告: {:?}", session, warning, file.warnings));
+                    }
+                    None if !file.warnings.is_empty() => {
+                        return Err(format!("wallet_{}.csv 有意外的警告: {:?}", session, file.warnings));
+                    }
+                    _ => {}
+                }
+            }
+            let ethereum = list_files(&dir, Some(Chain::Ethereum))?.len();
+            if ethereum != 3 {
+                return Err(format!("按 ethereum 筛选得到 {} 个文件，期望 3 个", ethereum));
+            }
+            
+            let report = verify_file(&dir.join("wallet_legacy.csv"))?;
+            if report.valid_rows != 2 || !report.corrupt_rows.is_empty() {
+                return Err(format!("旧文件校验到 {} 行有效、{} 行失败", report.valid_rows, report.corrupt_rows.len()));
+            }
+            let report = verify_file(&dir.join("wallet_tron.csv"))?;
+            match report.corrupt_rows.first() {
+                // 示例地址不是 KNOWN_KEYS[2] 的地址：按波场格式解析后与推导出的地址比较，而不是因格式被拒绝
+                Some(row) if report.valid_rows == 0 && row.reason.contains("私钥推导出的地址为 T") => {}
+                _ => return Err(format!("波场记录没有按波场地址校验: {:?}", report.corrupt_rows)),
+            }
+            Ok(())
+        })();
+        
+        let _ = std::fs::remove_dir_all(&dir);
+        result
+    }
+
+    /**
+     * 合并的结果文件：wallets_<时间>.csv 中每行记录各自的模式，结果浏览器可以列出和读取；不能与集合或文件名模板同时使用
+     */
+    #[test]
+    fn output_layout() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        let names = [("wallets_20240101_000000Z", Some("20240101_000000Z")), ("wallet_8_20240101_000000Z", Some("8_20240101_000000Z")), ("walletsx_1", None), ("manifest_1", None)];
+        for (stem, expected) in names {
+            if result_session_id(stem) != expected {
+                return Err(format!("{} 的会话标识不是 {:?}", stem, expected));
+            }
+        }
+        let params = GenerationParams { pattern: "8".to_string(), additional_patterns: vec!["abc".to_string()], output_layout: Some(OutputLayout::Combined), ..GenerationParams::default() };
+        params.validate()?;
+        let rejected = [
+            GenerationParams { collection: Some("vanity".to_string()), additional_patterns: Vec::new(), ..params.clone() },
+            GenerationParams { filename_template: Some("{pattern}_{date}_{time}".to_string()), ..params.clone() },
+        ];
+        if rejected.iter().any(|params| params.validate().is_ok()) {
+            return Err("output_layout: combined 与 collection 或 filename_template 同时使用时没有被拒绝".to_string());
+        }
+        
+        with_data_dir("output-layout", |dir| {
+            let path = Chain::Ethereum.dir(dir).join("wallets_20240101_000000Z.csv");
+            std::fs::create_dir_all(Chain::Ethereum.dir(dir)).map_err(|e| e.to_string())?;
+            for (i, pattern) in ["8", "abc"].iter().enumerate() {
+                let secret = PrivKeyHex::parse(KNOWN_KEYS[i].0)?.secret_key();
+                let wallet = Wallet {
+                    index: i as u64 + 1,
+                    address: Address::from_public_key(&PublicKey::from_secret_key(&secp, &secret)),
+                    private_key: SecretHex::from(KNOWN_KEYS[i].0.to_string()),
+                    attempts: 1,
+                    duration: 0,
+                    matched_spans: Vec::new(),
+                    rng_mode: RngMode::default(),
+                    test_wallet: false,
+                    mnemonic: None,
+                    chain_address: None,
+                    matched_pattern: pattern.to_string(),
+                    created_at: timestamps::now_rfc3339(),
+                };
+                write_wallet_row(&path, &wallet, pattern, Chain::Ethereum, CsvPrivateKeys::Full)?;
+            }
+            if list_sessions(dir)? != ["20240101_000000Z"] || session_file(dir, "20240101_000000Z")? != path {
+                return Err("结果浏览器没有找到合并的结果文件".to_string());
+            }
+            let patterns: Vec<String> = list_generated(dir, false)?.wallets.into_iter().map(|wallet| wallet.pattern).collect();
+            if patterns != ["8", "abc"] {
+                return Err(format!("合并的结果文件中的模式为 {:?}", patterns));
+            }
+            Ok(())
+        })
+    }
+
+    /**
+     * 大结果文件分页：10 万行的结果文件按游标逐页读取，每页不超过 MAX_PAGE_SIZE 行，序号连续且不重复；
+     * 按日期排序与按序号相同，没有评分时按评分排序和无效的游标被拒绝，读到末尾的游标之后追加的行可以继续读取；
+     * 同样的 10 万行写入 wallets.db 后按 limit 和 offset 逐页查询，最新的在前，追加的行计入总数并出现在第一页
+     */
+    #[test]
+    #[ignore = "生成并读取 10 万行的结果文件，耗时较长"]
+    fn large_result_pages() -> Result<(), String> {
+        with_data_dir("large-result-pages", |dir| {
+            let file = dir.join("wallet_8_20260101_000000.csv");
+            let row = |index: u64| format!("0x{:040x},{:064x},8,{},ethereum,os,,,2026-01-01T00:00:00Z,{},{}\n", index, index, index, index * 16, index);
+            let mut out = std::io::BufWriter::new(std::fs::File::create(&file).map_err(|e| e.to_string())?);
+            writeln!(out, "{}", CSV_HEADER).map_err(|e| e.to_string())?;
+            for index in 1..=LARGE_RESULT_ROWS {
+                out.write_all(row(index).as_bytes()).map_err(|e| e.to_string())?;
+            }
+            out.flush().map_err(|e| e.to_string())?;
+            drop(out);
+
+            for sort in [ResultsSort::Index, ResultsSort::Date] {
+                let mut cursor: Option<String> = None;
+                let mut expected = 1;
+                loop {
+                    let page = read_page(&file, cursor.as_deref(), MAX_PAGE_SIZE + 1, sort)?;
+                    if page.wallets.len() > MAX_PAGE_SIZE || (page.has_more && page.wallets.len() != MAX_PAGE_SIZE) {
+                        return Err(format!("{:?} 排序的一页有 {} 行", sort, page.wallets.len()));
+                    }
+                    for wallet in &page.wallets {
+                        if wallet.index != Some(expected) || wallet.attempts != Some(expected * 16) {
+                            return Err(format!("{:?} 排序读到的第 {} 行为 {:?}", sort, expected, wallet.index));
+                        }
+                        expected += 1;
+                    }
+                    cursor = Some(page.next_cursor);
+                    if !page.has_more {
+                        break;
+                    }
+                }
+                if expected != LARGE_RESULT_ROWS + 1 {
+                    return Err(format!("{:?} 排序只读到 {} 行", sort, expected - 1));
+                }
+            }
+
+            let end = std::fs::metadata(&file).map_err(|e| e.to_string())?.len();
+            std::fs::OpenOptions::new().append(true).open(&file).and_then(|mut out| out.write_all(row(LARGE_RESULT_ROWS + 1).as_bytes())).map_err(|e| e.to_string())?;
+            let appended = read_page(&file, Some(&end.to_string()), 10, ResultsSort::Index)?;
+            if appended.wallets.iter().map(|wallet| wallet.index).collect::<Vec<_>>() != [Some(LARGE_RESULT_ROWS + 1)] || appended.has_more {
+                return Err(format!("末尾游标之后追加的行读到 {} 条", appended.wallets.len()));
+            }
+            if read_page(&file, None, 10, ResultsSort::Score).is_ok() {
+                return Err("没有评分的结果按评分排序时没有被拒绝".to_string());
+            }
+            let past_end = (end + row(LARGE_RESULT_ROWS + 1).len() as u64 + 1).to_string();
+            if read_page(&file, Some("abc"), 10, ResultsSort::Index).is_ok() || read_page(&file, Some(&past_end), 10, ResultsSort::Index).is_ok() {
+                return Err("无效的游标没有被拒绝".to_string());
+            }
+
+            let database = results_db::SessionDb {
+                path: dir.join(results_db::DB_FILE),
+                session_id: "20260101_000000".to_string(),
+                private_keys: true,
+                in_keystore: false,
+            };
+            let wallet = |index: u64| -> Result<Wallet, String> {
+                Ok(Wallet {
+                    index,
+                    address: Address::parse(&format!("0x{:040x}", index)).map_err(|e| e.to_string())?,
+                    private_key: SecretHex::from(format!("{:064x}", index)),
+                    attempts: index * 16,
+                    duration: index,
+                    matched_spans: Vec::new(),
+                    rng_mode: Default::default(),
+                    test_wallet: false,
+                    mnemonic: None,
+                    chain_address: None,
+                    matched_pattern: "8".to_string(),
+                    created_at: "2026-01-01T00:00:00Z".to_string(),
+                })
+            };
+            for chunk in (1..=LARGE_RESULT_ROWS).collect::<Vec<_>>().chunks(10_000) {
+                let wallets = chunk.iter().map(|&index| wallet(index)).collect::<Result<Vec<_>, _>>()?;
+                let rows: Vec<(&Wallet, &str)> = wallets.iter().map(|wallet| (wallet, "8")).collect();
+                results_db::insert_wallets(&database, &rows, Chain::Ethereum)?;
+            }
+            if results_db::count_wallets(&database.path, None)? != LARGE_RESULT_ROWS {
+                return Err("wallets.db 中的钱包数与写入的不同".to_string());
+            }
+            let mut expected = LARGE_RESULT_ROWS;
+            let mut offset = 0;
+            loop {
+                let page = results_db::query_wallets(&database.path, None, MAX_PAGE_SIZE + 1, offset, false)?;
+                if page.len() > MAX_PAGE_SIZE {
+                    return Err(format!("wallets.db 的一页有 {} 行", page.len()));
+                }
+                for stored in &page {
+                    if stored.index != expected || stored.attempts != expected * 16 || stored.private_key.is_some() {
+                        return Err(format!("wallets.db 读到的第 {} 行为 {:?}", LARGE_RESULT_ROWS - expected + 1, stored));
+                    }
+                    expected -= 1;
+                }
+                offset += page.len() as u64;
+                if page.len() < MAX_PAGE_SIZE {
+                    break;
+                }
+            }
+            if expected != 0 {
+                return Err(format!("wallets.db 只读到 {} 行", LARGE_RESULT_ROWS - expected));
+            }
+            let appended = wallet(LARGE_RESULT_ROWS + 1)?;
+            results_db::insert_wallets(&database, &[(&appended, "8")], Chain::Ethereum)?;
+            let first = results_db::query_wallets(&database.path, None, 1, 0, true)?;
+            if results_db::count_wallets(&database.path, None)? != LARGE_RESULT_ROWS + 1
+                || first.iter().map(|stored| (stored.index, stored.private_key.clone())).collect::<Vec<_>>() != [(LARGE_RESULT_ROWS + 1, Some(format!("{:064x}", LARGE_RESULT_ROWS + 1)))]
+            {
+                return Err(format!("wallets.db 追加的行没有出现在第一页: {:?}", first));
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,446 @@
+use crate::hexutil::Address;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// 地址的十六进制字符数
+const HEX_CHARS: usize = 40;
+
+/// 可配置的评分权重（未给出的项为 0；使用前须经过 normalized 校验并归一化）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScoreWeights {
+    /// 开头连续相同字符
+    pub leading_run: f64,
+    /// 结尾连续相同字符
+    pub trailing_run: f64,
+    /// 值为 0x00 的字节数
+    pub zero_bytes: f64,
+    /// 任意位置最长的连续相同字符
+    pub repeated: f64,
+    /// 只包含数字
+    pub digits_only: f64,
+    /// 只包含字母（a–f）
+    pub letters_only: f64,
+    /// 幸运字符（单个十六进制字符）及其权重，按出现次数计分
+    pub lucky: BTreeMap<String, f64>,
+}
+
+/// score 模式为每个地址评分使用的函数
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreFunction {
+    /// 任意位置最长的连续相同字符的长度
+    #[default]
+    LongestRun,
+    /// 开头的 0 的个数
+    LeadingZeros,
+    /// 按会话评分权重计算的总分（0–100）
+    Weighted,
+}
+
+impl ScoreFunction {
+    /**
+     * 写入文件名和模式列的名称
+     */
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ScoreFunction::LongestRun => "longest-run",
+            ScoreFunction::LeadingZeros => "leading-zeros",
+            ScoreFunction::Weighted => "weighted",
+        }
+    }
+}
+
+/// score 模式进入排行榜的门槛，各工作线程同时读取，汇总线程在排行榜变化后提高
+#[derive(Debug)]
+pub struct RecordTracker {
+    /// 评分函数
+    function: ScoreFunction,
+    /// 归一化后的权重（weighted 使用）
+    weights: ScoreWeights,
+    /// 进入排行榜需要超过的分数（f64 的位模式；分数都不是负数，位模式的大小顺序与数值相同）
+    threshold: AtomicU64,
+}
+
+/// 一项评分的明细
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ScoreComponent {
+    /// 评分项（与权重字段同名，幸运字符为 lucky:<字符>）
+    pub name: String,
+    /// 该项的特征值（0–1）
+    pub feature: f64,
+    /// 归一化后的权重
+    pub weight: f64,
+    /// 得分（特征值 × 权重 × 100）
+    pub points: f64,
+}
+
+/// 地址的评分结果
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AddressScore {
+    /// 总分（0–100）
+    pub score: f64,
+    /// 各项明细
+    pub components: Vec<ScoreComponent>,
+    /// 实际使用的（归一化后的）权重
+    pub weights: ScoreWeights,
+}
+
+impl ScoreWeights {
+    /**
+     * 内置的评分权重（未提供 score_weights 时使用）
+     */
+    pub fn builtin() -> Self {
+        ScoreWeights {
+            leading_run: 0.25,
+            trailing_run: 0.25,
+            zero_bytes: 0.15,
+            repeated: 0.2,
+            digits_only: 0.05,
+            letters_only: 0.05,
+            lucky: BTreeMap::from([("8".to_string(), 0.05)]),
+        }
+    }
+
+    fn scalar_fields(&self) -> [(&'static str, f64); 6] {
+        [
+            ("leading_run", self.leading_run),
+            ("trailing_run", self.trailing_run),
+            ("zero_bytes", self.zero_bytes),
+            ("repeated", self.repeated),
+            ("digits_only", self.digits_only),
+            ("letters_only", self.letters_only),
+        ]
+    }
+
+    /**
+     * 校验并归一化权重：所有权重必须是有限的非负数且不能全为 0，幸运字符必须是单个十六进制字符（不区分大小写，统一为小写）；
+     * 归一化后权重之和为 1，总分范围为 0–100
+     *
+     * @returns 归一化后的权重
+     */
+    pub fn normalized(&self) -> Result<ScoreWeights, String> {
+        let mut lucky = BTreeMap::new();
+        for (key, &weight) in &self.lucky {
+            let mut chars = key.trim().chars();
+            let c = match (chars.next(), chars.next()) {
+                (Some(c), None) if c.is_ascii_hexdigit() => c.to_ascii_lowercase(),
+                _ => return Err(format!("幸运字符必须是单个十六进制字符: \"{}\"", key)),
+            };
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(format!("幸运字符 {} 的权重必须是非负数", key));
+            }
+            if lucky.insert(c.to_string(), weight).is_some() {
+                return Err(format!("幸运字符 {} 重复（不区分大小写）", c));
+            }
+        }
+        if let Some((name, _)) = self.scalar_fields().into_iter().find(|(_, w)| !w.is_finite() || *w < 0.0) {
+            return Err(format!("评分权重 {} 必须是非负数", name));
+        }
+        let total: f64 = self.scalar_fields().iter().map(|(_, w)| w).sum::<f64>() + lucky.values().sum::<f64>();
+        if total <= 0.0 || !total.is_finite() {
+            return Err("评分权重不能全部为 0".to_string());
+        }
+        Ok(ScoreWeights {
+            leading_run: self.leading_run / total,
+            trailing_run: self.trailing_run / total,
+            zero_bytes: self.zero_bytes / total,
+            repeated: self.repeated / total,
+            digits_only: self.digits_only / total,
+            letters_only: self.letters_only / total,
+            lucky: lucky.into_iter().map(|(c, w)| (c, w / total)).collect(),
+        })
+    }
+}
+
+/**
+ * 解析会话使用的权重：未提供时使用内置权重
+ *
+ * @param weights - 用户提供的权重（可选）
+ * @returns 归一化后的权重
+ */
+pub fn resolve(weights: Option<&ScoreWeights>) -> Result<ScoreWeights, String> {
+    match weights {
+        Some(weights) => weights.normalized(),
+        None => ScoreWeights::builtin().normalized(),
+    }
+}
+
+/**
+ * score 模式的模式字符串（写入文件名和会话记录，例如 score-longest-run）
+ *
+ * @param function - 评分函数
+ */
+pub fn score_pattern(function: ScoreFunction) -> String {
+    format!("score-{}", function.as_str())
+}
+
+/**
+ * score 模式结果的模式列：记录这一行的分数（例如 score-longest-run=7）
+ *
+ * @param function - 评分函数
+ * @param score - 分数
+ */
+pub fn score_label(function: ScoreFunction, score: f64) -> String {
+    match function {
+        ScoreFunction::Weighted => format!("{}={:.2}", score_pattern(function), score),
+        _ => format!("{}={}", score_pattern(function), score),
+    }
+}
+
+/**
+ * 按评分函数计算小写十六进制地址（不含 0x 前缀）的分数
+ *
+ * @param function - 评分函数
+ * @param hex - 40 个字符的小写十六进制地址
+ * @param weights - 归一化后的权重（只有 weighted 使用）
+ */
+pub fn evaluate(function: ScoreFunction, hex: &str, weights: &ScoreWeights) -> f64 {
+    let bytes = hex.as_bytes();
+    match function {
+        ScoreFunction::LongestRun => (0..bytes.len()).map(|i| run_from(bytes, i)).max().unwrap_or(0) as f64,
+        ScoreFunction::LeadingZeros => bytes.iter().take_while(|&&c| c == b'0').count() as f64,
+        ScoreFunction::Weighted => score_hex(hex, weights).score,
+    }
+}
+
+impl RecordTracker {
+    /**
+     * 创建门槛为 0 的记录（排行榜未满时分数大于 0 的地址都可以进入）
+     *
+     * @param function - 评分函数
+     * @param weights - 归一化后的权重
+     */
+    pub fn new(function: ScoreFunction, weights: ScoreWeights) -> Self {
+        RecordTracker { function, weights, threshold: AtomicU64::new(0f64.to_bits()) }
+    }
+
+    /**
+     * 地址的分数
+     *
+     * @param hex - 小写十六进制地址（不含 0x 前缀）
+     */
+    pub fn evaluate(&self, hex: &str) -> f64 {
+        evaluate(self.function, hex, &self.weights)
+    }
+
+    /**
+     * 地址的分数是否超过进入排行榜的门槛
+     *
+     * 门槛由汇总线程在排行榜变化后提高，之前各工作线程可能已经提交了不再够格的地址，由排行榜丢弃。
+     *
+     * @param hex - 小写十六进制地址（不含 0x 前缀）
+     */
+    pub fn try_record(&self, hex: &str) -> bool {
+        self.evaluate(hex) > f64::from_bits(self.threshold.load(Ordering::Relaxed))
+    }
+
+    /**
+     * 提高进入排行榜的门槛（不会降低）
+     *
+     * @param score - 新的门槛（排行榜最低的分数）
+     */
+    pub fn raise_threshold(&self, score: f64) {
+        self.threshold.fetch_max(score.to_bits(), Ordering::Relaxed);
+    }
+}
+
+/**
+ * 从 start 开始与 hex[start] 相同的连续字符数
+ */
+fn run_from(hex: &[u8], start: usize) -> usize {
+    hex[start..].iter().take_while(|&&c| c == hex[start]).count()
+}
+
+/**
+ * 计算地址的评分（所有链共用；按 20 字节地址的小写十六进制计算，与地址在各链上的显示格式无关）
+ *
+ * 各项特征值：开头/结尾/任意位置的连续相同字符为 (长度 - 1) / 39，零字节为个数 / 20，
+ * 只含数字/只含字母为 0 或 1，幸运字符为出现次数 / 40。总分为 100 × Σ 特征值 × 权重。
+ *
+ * @param address - 地址
+ * @param weights - 归一化后的权重（ScoreWeights::normalized 的结果）
+ */
+pub fn score(address: &Address, weights: &ScoreWeights) -> AddressScore {
+    score_hex(&hex::encode(address.as_bytes()), weights)
+}
+
+/**
+ * 按 40 个字符的小写十六进制地址计算评分（score 模式的生成循环直接使用，不解析地址）
+ */
+fn score_hex(hex: &str, weights: &ScoreWeights) -> AddressScore {
+    let bytes = hex.as_bytes();
+    let run_feature = |run: usize| (run - 1) as f64 / (HEX_CHARS - 1) as f64;
+    let longest = (0..HEX_CHARS).map(|i| run_from(bytes, i)).max().unwrap_or(1);
+    let trailing = bytes.iter().rev().take_while(|&&c| c == bytes[HEX_CHARS - 1]).count();
+    let features = [
+        run_feature(run_from(bytes, 0)),
+        run_feature(trailing),
+        bytes.chunks(2).filter(|&pair| pair == b"00").count() as f64 / 20.0,
+        run_feature(longest),
+        if bytes.iter().all(u8::is_ascii_digit) { 1.0 } else { 0.0 },
+        if bytes.iter().all(|c| matches!(c, b'a'..=b'f')) { 1.0 } else { 0.0 },
+    ];
+
+    let mut components: Vec<ScoreComponent> = weights
+        .scalar_fields()
+        .into_iter()
+        .zip(features)
+        .map(|((name, weight), feature)| ScoreComponent { name: name.to_string(), feature, weight, points: feature * weight * 100.0 })
+        .collect();
+    for (c, &weight) in &weights.lucky {
+        let feature = hex.matches(c.as_str()).count() as f64 / HEX_CHARS as f64;
+        components.push(ScoreComponent { name: format!("lucky:{}", c), feature, weight, points: feature * weight * 100.0 });
+    }
+    AddressScore { score: components.iter().map(|c| c.points).sum(), components, weights: weights.clone() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::PatternType;
+    use crate::entropy::RngMode;
+    use crate::engine::{CancellationToken, SearchConfig, SearchEvent};
+    use crate::templates::GenerationParams;
+
+    /// score 模式的评分用例：(小写地址, 最长的连续相同字符, 开头的 0 的个数)
+    const SCORE_FUNCTION_CASES: &[(&str, f64, f64)] = &[
+        ("0000a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0f0", 4.0, 4.0),
+        ("a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0f77777", 5.0, 0.0),
+        ("00000000000000000000000000000000000000ff", 38.0, 38.0),
+    ];
+
+    /// score 模式的测试刷新记录的次数
+    const SCORE_MODE_RECORDS: u64 = 4;
+
+    /// 评分用例的地址：全 0、全 8、只含字母、开头 8 个 0 的真实地址
+    const SCORE_ADDRESSES: [&str; 4] = [
+        "0x0000000000000000000000000000000000000000",
+        "0x8888888888888888888888888888888888888888",
+        "0xabcdefabcdefabcdefabcdefabcdefabcdefabcd",
+        "0x00000000219ab540356cBB839Cbe05303d7705Fa",
+    ];
+
+    /// 评分用例：(权重 JSON，为空表示内置权重, 各地址的期望评分)
+    const SCORE_CASES: &[(Option<&str>, [f64; 4])] = &[
+        (None, [90.0, 80.0, 5.0, 11.201923076923077]),
+        (Some(r#"{"lucky": {"8": 3, "A": 1}}"#), [0.0, 75.0, 4.375, 3.125]),
+        (Some(r#"{"trailing_run": 2, "zero_bytes": 2}"#), [100.0, 50.0, 0.0, 10.0]),
+    ];
+
+    /// 应被拒绝的评分权重
+    const INVALID_SCORE_WEIGHTS: &[&str] = &[
+        r#"{"repeated": -1}"#,
+        r#"{"lucky": {"g": 1}}"#,
+        r#"{"lucky": {"88": 1}}"#,
+        r#"{"lucky": {"a": 1, "A": 2}}"#,
+        r#"{}"#,
+        r#"{"leading_runs": 1}"#,
+    ];
+
+    /**
+     * score 模式：评分函数的结果，只有严格超过门槛的地址可以提交，门槛只升不降，搜索提交的地址都有分数；
+     * 与模式有关的设置被拒绝
+     */
+    #[test]
+    fn score_mode() -> Result<(), String> {
+        let weights = resolve(None)?;
+        for (hex, run, zeros) in SCORE_FUNCTION_CASES {
+            let longest = evaluate(ScoreFunction::LongestRun, hex, &weights);
+            let leading = evaluate(ScoreFunction::LeadingZeros, hex, &weights);
+            if longest != *run || leading != *zeros {
+                return Err(format!("{} 的最长连续字符为 {}、开头的 0 为 {}，应为 {} / {}", hex, longest, leading, run, zeros));
+            }
+            let weighted = evaluate(ScoreFunction::Weighted, hex, &weights);
+            let expected = score(&Address::parse(hex).map_err(|e| e.to_string())?, &weights).score;
+            if (weighted - expected).abs() > 1e-9 {
+                return Err(format!("{} 的 weighted 分数为 {}，评分结果为 {}", hex, weighted, expected));
+            }
+        }
+        let tracker = RecordTracker::new(ScoreFunction::LongestRun, weights.clone());
+        let records: Vec<bool> = SCORE_FUNCTION_CASES
+            .iter()
+            .chain(SCORE_FUNCTION_CASES)
+            .map(|(hex, _, _)| {
+                let admitted = tracker.try_record(hex);
+                if admitted {
+                    tracker.raise_threshold(tracker.evaluate(hex));
+                }
+                admitted
+            })
+            .collect();
+        tracker.raise_threshold(0.0);
+        if records != [true, true, true, false, false, false] || tracker.try_record(SCORE_FUNCTION_CASES[2].0) {
+            return Err(format!("门槛的提高顺序不正确: {:?}", records));
+        }
+        if score_label(ScoreFunction::LongestRun, 7.0) != "score-longest-run=7" || score_label(ScoreFunction::Weighted, 12.345) != "score-weighted=12.35" {
+            return Err(format!("score 模式的模式列为 {}", score_label(ScoreFunction::LongestRun, 7.0)));
+        }
+        
+        let config = SearchConfig {
+            limits: crate::engine::GenerationLimits { max_matches: Some(SCORE_MODE_RECORDS), ..Default::default() },
+            rng_mode: RngMode::DeterministicTest,
+            test_seed: Some(17),
+            workers: Some(1),
+            ..SearchConfig::new(score_pattern(ScoreFunction::LongestRun))
+        };
+        let tracker = RecordTracker::new(ScoreFunction::LongestRun, weights);
+        let mut scores = Vec::new();
+        crate::engine::search_with(&config, &CancellationToken::new(), || false, |address| tracker.try_record(address), |event| {
+            if let SearchEvent::Found(key) = event {
+                let score = tracker.evaluate(&hex::encode(key.address.as_bytes()));
+                tracker.raise_threshold(score);
+                scores.push(score);
+            }
+        });
+        // 汇总线程提高门槛之前工作线程可能已经提交了几个不再够格的地址，排行榜会丢弃它们
+        if scores.len() as u64 != SCORE_MODE_RECORDS || scores.iter().any(|&score| score <= 0.0) {
+            return Err(format!("score 模式提交的分数为 {:?}", scores));
+        }
+        
+        let params = GenerationParams {
+            pattern: score_pattern(ScoreFunction::LeadingZeros),
+            score_function: Some(ScoreFunction::LeadingZeros),
+            ..GenerationParams::default()
+        };
+        params.validate()?;
+        let conflicting = [
+            GenerationParams { additional_patterns: vec!["dead".to_string()], ..params.clone() },
+            GenerationParams { case_sensitive: Some(true), ..params.clone() },
+            GenerationParams { pattern_type: Some(PatternType::Regex), ..params.clone() },
+        ];
+        if conflicting.iter().any(|params| params.validate().is_ok()) {
+            return Err("score 模式接受了与模式有关的设置".to_string());
+        }
+        Ok(())
+    }
+
+    /**
+     * 评分：固定地址在几组权重下的评分，以及无效权重的拒绝
+     */
+    #[test]
+    fn score_weights() -> Result<(), String> {
+        for (json, expected) in SCORE_CASES {
+            let weights = match json {
+                Some(json) => Some(serde_json::from_str::<ScoreWeights>(json).map_err(|e| format!("{}: {}", json, e))?),
+                None => None,
+            };
+            let normalized = resolve(weights.as_ref())?;
+            for (address, expected) in SCORE_ADDRESSES.iter().zip(expected) {
+                let score = score(&Address::parse(address).map_err(|e| e.to_string())?, &normalized);
+                let points: f64 = score.components.iter().map(|c| c.points).sum();
+                if (score.score - expected).abs() > 1e-9 || (points - score.score).abs() > 1e-9 {
+                    return Err(format!("权重 {:?} 下 {} 的评分为 {}，应为 {}", json, address, score.score, expected));
+                }
+            }
+        }
+        for json in INVALID_SCORE_WEIGHTS {
+            let accepted = serde_json::from_str::<ScoreWeights>(json).map_err(|e| e.to_string()).and_then(|w| w.normalized());
+            if accepted.is_ok() {
+                return Err(format!("无效的评分权重 {} 没有被拒绝", json));
+            }
+        }
+        Ok(())
+    }
+}
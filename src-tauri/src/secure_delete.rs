@@ -0,0 +1,260 @@
+use crate::saved_wallets;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroize;
+
+/// 写时复制或日志结构的文件系统：覆盖写入新的数据块，旧内容仍留在磁盘上
+const COPY_ON_WRITE_FILESYSTEMS: &[&str] = &["btrfs", "zfs", "bcachefs", "f2fs", "nilfs2", "apfs"];
+
+/// 一个文件的安全删除结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SecureDeleteResult {
+    /// 删除的文件
+    pub path: String,
+    /// 用随机数据覆盖的字节数
+    pub bytes_overwritten: u64,
+    /// 能否确认旧内容已被覆盖（只有机械硬盘上的普通文件系统可以确认）
+    pub overwrite_guaranteed: bool,
+    /// 不能确认时的原因（界面应原样显示给用户）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caveat: Option<String>,
+}
+
+/// 会话输出文件的安全删除结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SessionSecureDelete {
+    /// 会话标识
+    pub session_id: String,
+    /// 已删除的文件（按路径排序）
+    pub files: Vec<SecureDeleteResult>,
+    /// 无法删除的文件及原因（这些文件需要手动处理）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<String>,
+    /// 所有已删除文件的覆盖是否都能确认
+    pub overwrite_guaranteed: bool,
+}
+
+/**
+ * 检查要删除的文件：必须是 FancyWallets 目录中已存在的普通文件（符号链接按指向的位置判断），且没有正被会话写入
+ *
+ * @param path - 文件路径
+ * @param wallets_dir - FancyWallets 目录
+ * @returns 规范化后的文件路径
+ */
+pub fn validate_path(path: &Path, wallets_dir: &Path) -> Result<PathBuf, String> {
+    let wallets_dir = wallets_dir.canonicalize().map_err(|e| format!("钱包目录不可用: {}", e))?;
+    let file = path.canonicalize().map_err(|e| format!("文件不存在: {} ({})", path.display(), e))?;
+    if !file.starts_with(&wallets_dir) {
+        return Err(format!("只能删除钱包目录中的文件: {}", path.display()));
+    }
+    if !file.is_file() {
+        return Err(format!("不是普通文件: {}", path.display()));
+    }
+    if saved_wallets::is_active(&file) {
+        return Err(format!("结果文件 {} 正在被会话写入，结束后才能删除", path.display()));
+    }
+    Ok(file)
+}
+
+/**
+ * 用随机数据覆盖文件内容并写入磁盘，再删除文件
+ *
+ * @param path - 文件路径
+ * @returns 覆盖的字节数
+ */
+pub fn wipe_file(path: &Path) -> std::io::Result<u64> {
+    let len = fs::metadata(path)?.len();
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    let mut noise = vec![0u8; 64 * 1024];
+    let mut remaining = len;
+    while remaining > 0 {
+        let n = remaining.min(noise.len() as u64) as usize;
+        OsRng.fill_bytes(&mut noise[..n]);
+        file.write_all(&noise[..n])?;
+        remaining -= n as u64;
+    }
+    noise.zeroize();
+    file.sync_all()?;
+    drop(file);
+    fs::remove_file(path)?;
+    Ok(len)
+}
+
+/**
+ * 判断能否确认覆盖写到了原来的数据块上
+ *
+ * 固态硬盘和 U 盘的磨损均衡会把写入重定向到新的闪存块，写时复制的文件系统也不会就地覆盖，
+ * 这两种情况下旧内容可能仍然可以恢复。只有 Linux 上能读取文件系统类型和磁盘类型，其它系统一律不能确认。
+ *
+ * @param path - 文件路径（删除之前检查）
+ * @returns 不能确认时的原因
+ */
+fn overwrite_caveat(path: &Path) -> Option<String> {
+    const UNKNOWN: &str = "无法确认文件所在的磁盘类型；固态硬盘、U 盘和写时复制的文件系统可能保留旧内容，请配合全盘加密使用";
+    if !cfg!(target_os = "linux") {
+        return Some(UNKNOWN.to_string());
+    }
+    let Some((device, fs_type)) = mount_of(path) else {
+        return Some(UNKNOWN.to_string());
+    };
+    if COPY_ON_WRITE_FILESYSTEMS.contains(&fs_type.as_str()) {
+        return Some(format!("文件系统 {} 采用写时复制，覆盖不会写到原来的数据块上，旧内容可能仍可恢复", fs_type));
+    }
+    match is_rotational(&device) {
+        Some(true) => None,
+        Some(false) => Some("文件位于固态硬盘或闪存设备上，磨损均衡可能保留旧内容，覆盖无法保证其不可恢复".to_string()),
+        None => Some(UNKNOWN.to_string()),
+    }
+}
+
+/**
+ * 在 /proc/mounts 中查找包含路径的挂载点（最长的前缀）
+ *
+ * @returns 设备和文件系统类型
+ */
+fn mount_of(path: &Path) -> Option<(String, String)> {
+    let mounts = fs::read_to_string("/proc/mounts").ok()?;
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let device = fields.next()?;
+            // 挂载点中的空格等字符以八进制转义
+            let mount_point = fields.next()?.replace("\\040", " ");
+            let fs_type = fields.next()?;
+            path.starts_with(&mount_point).then(|| (mount_point.len(), device.to_string(), fs_type.to_string()))
+        })
+        .max_by_key(|(len, _, _)| *len)
+        .map(|(_, device, fs_type)| (device, fs_type))
+}
+
+/**
+ * 读取块设备是否为机械硬盘（分区读取所在磁盘的 queue/rotational）
+ */
+fn is_rotational(device: &str) -> Option<bool> {
+    let name = Path::new(device.strip_prefix("/dev/")?).file_name()?.to_owned();
+    let sys = Path::new("/sys/class/block").join(name).canonicalize().ok()?;
+    let queue = [sys.join("queue/rotational"), sys.parent()?.join("queue/rotational")].into_iter().find(|path| path.is_file())?;
+    Some(fs::read_to_string(queue).ok()?.trim() == "1")
+}
+
+/**
+ * 安全删除 FancyWallets 目录中的一个文件
+ *
+ * @param path - 文件路径
+ * @param wallets_dir - FancyWallets 目录
+ */
+pub fn delete_file(path: &Path, wallets_dir: &Path) -> Result<SecureDeleteResult, String> {
+    let file = validate_path(path, wallets_dir)?;
+    let caveat = overwrite_caveat(&file);
+    let bytes_overwritten = wipe_file(&file).map_err(|e| format!("无法安全删除 {}: {}", file.display(), e))?;
+    Ok(SecureDeleteResult { path: file.to_string_lossy().to_string(), bytes_overwritten, overwrite_guaranteed: caveat.is_none(), caveat })
+}
+
+/**
+ * 安全删除一组会话文件：逐个删除，失败的文件记录下来并继续
+ *
+ * @param session_id - 会话标识
+ * @param files - 会话的输出文件（见 session_archive::session_files）
+ * @param wallets_dir - FancyWallets 目录
+ */
+pub fn delete_session(session_id: &str, files: &[PathBuf], wallets_dir: &Path) -> Result<SessionSecureDelete, String> {
+    if let Some(active) = files.iter().find(|file| saved_wallets::is_active(file)) {
+        return Err(format!("结果文件 {} 正在被会话写入，结束后才能删除", active.display()));
+    }
+    let mut deleted = Vec::new();
+    let mut failures = Vec::new();
+    for file in files {
+        match delete_file(file, wallets_dir) {
+            Ok(result) => deleted.push(result),
+            Err(e) => failures.push(e),
+        }
+    }
+    Ok(SessionSecureDelete {
+        session_id: session_id.to_string(),
+        overwrite_guaranteed: deleted.iter().all(|result| result.overwrite_guaranteed),
+        files: deleted,
+        failures,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::Chain;
+    use crate::entropy::RngMode;
+    use crate::gui::{write_wallet_row, Wallet};
+    use crate::output_root;
+    use crate::keystore;
+    use crate::hexutil::{Address, PrivKeyHex, SecretHex};
+    use crate::session_archive;
+    use crate::saved_wallets::CsvPrivateKeys;
+    use crate::timestamps;
+    use secp256k1::{PublicKey, Secp256k1};
+    use crate::test_support::with_data_dir;
+    use crate::self_test::KNOWN_KEYS;
+
+    /**
+     * 安全删除：拒绝钱包目录之外的文件（包括用 .. 指向外面的路径），删除目录中的文件并报告覆盖的字节数和能否确认覆盖；
+     * 按会话删除时只删除这个会话的文件
+     */
+    #[test]
+    fn secure_delete() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        with_data_dir("secure_delete", |dir| {
+            let wallets_dir = dir.join(output_root::WALLETS_DIR_NAME);
+            let chain_dir = Chain::Ethereum.dir(&wallets_dir);
+            std::fs::create_dir_all(&chain_dir).map_err(|e| e.to_string())?;
+            let outside = dir.join("outside.txt");
+            std::fs::write(&outside, "keep").map_err(|e| e.to_string())?;
+            for path in [outside.clone(), wallets_dir.join("..").join("outside.txt"), wallets_dir.clone()] {
+                if delete_file(&path, &wallets_dir).is_ok() {
+                    return Err(format!("钱包目录之外的路径 {} 被删除", path.display()));
+                }
+            }
+            if !outside.exists() {
+                return Err("拒绝删除后钱包目录之外的文件仍被删除".to_string());
+            }
+
+            let single = chain_dir.join("notes.txt");
+            std::fs::write(&single, "0123456789").map_err(|e| e.to_string())?;
+            let deleted = delete_file(&single, &wallets_dir)?;
+            if single.exists() || deleted.bytes_overwritten != 10 || deleted.overwrite_guaranteed == deleted.caveat.is_some() {
+                return Err(format!("删除单个文件的结果不正确: {:?}", deleted));
+            }
+
+            let addresses = KNOWN_KEYS[..2]
+                .iter()
+                .map(|(key, _)| Ok(Address::from_public_key(&PublicKey::from_secret_key(&secp, &PrivKeyHex::parse(key)?.secret_key()))))
+                .collect::<Result<Vec<_>, String>>()?;
+            let wallet = Wallet {
+                index: 1,
+                address: addresses[0],
+                private_key: SecretHex::from(KNOWN_KEYS[0].0.to_string()),
+                attempts: 1,
+                duration: 0,
+                matched_spans: Vec::new(),
+                rng_mode: RngMode::default(),
+                test_wallet: false,
+                mnemonic: None,
+                chain_address: None,
+                matched_pattern: String::new(),
+                created_at: timestamps::now_rfc3339(),
+            };
+            let csv = chain_dir.join("wallet_wipe_a.csv");
+            write_wallet_row(&csv, &wallet, "8", Chain::Ethereum, CsvPrivateKeys::Full)?;
+            let (keystore_file, _) = keystore::write_file(&chain_dir.join("keystore"), &addresses[0], "{}")?;
+            let (other_keystore, _) = keystore::write_file(&chain_dir.join("keystore"), &addresses[1], "{}")?;
+            let files = session_archive::session_files(&wallets_dir, "wipe_a")?;
+            let session = delete_session("wipe_a", &files, &wallets_dir)?;
+            if session.files.len() != 2 || !session.failures.is_empty() || csv.exists() || keystore_file.exists() || !other_keystore.exists() {
+                return Err(format!("按会话删除的结果不正确: {:?}", session));
+            }
+            Ok(())
+        })
+    }
+}
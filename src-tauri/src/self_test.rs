@@ -0,0 +1,237 @@
+use crate::address::{public_key_to_checksum_address, to_checksum_address};
+use crate::chain::Chain;
+use crate::pattern::ParsedPattern;
+use crate::entropy::RngMode;
+use crate::engine::{next_secret_key, KeyCandidateStats};
+use crate::gui::{write_wallet_row, Wallet};
+use crate::hexutil::{Address, PrivKeyHex, SecretHex};
+use crate::saved_wallets::{self, CsvPrivateKeys};
+use crate::timestamps;
+use rand::rngs::OsRng;
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// 已知私钥与地址的对照（私钥 1、2、3 以及 web3 文档中的示例）
+pub(crate) const KNOWN_KEYS: &[(&str, &str)] = &[
+    ("0000000000000000000000000000000000000000000000000000000000000001", "7E5F4552091A69125d5DfCb7b8C2659029395Bdf"),
+    ("0000000000000000000000000000000000000000000000000000000000000002", "2B5AD5c4795c026514f8317c7a215E218DcCD6cF"),
+    ("0000000000000000000000000000000000000000000000000000000000000003", "6813Eb9362372EEF6200f3b1dbC3f819671cBA69"),
+    ("4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318", "2c7536E3605D9C16a7a3D7b1898e529396a65c23"),
+];
+
+/// EIP-55 规范中的 checksum 示例
+pub(crate) const CHECKSUM_VECTORS: &[&str] = &[
+    "52908400098527886E0F7030069857D2E4169EE7",
+    "8617E340B3D01FA5F11F306F4090FD50E238070D",
+    "de709f2102306220921060314715629080e2fb77",
+    "27b1fdb04752bbc536007a920d24acb045561c26",
+    "5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+    "fB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+    "dbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+    "D1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+];
+
+/// 匹配器用例：(模式, checksum 地址, 是否应当匹配)
+pub(crate) const MATCHER_CASES: &[(&str, &str, bool)] = &[
+    ("*aaaa*", "1111a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d02222", true),
+    ("*aaaa*", "1112a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d02222", false),
+    // 默认不区分大小写：AAAa 视为同一个字符
+    ("*aaaa*", "AAAaa0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d02222", true),
+    ("*aabb*", "1122a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d03344", true),
+    ("*aabb*", "1111a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d03344", false),
+    ("*abab*", "1212a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d03434", true),
+    ("*abab*", "1221a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d03434", false),
+    ("888", "888a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0888", true),
+    ("888", "888a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0887", false),
+    // *...* 表示地址的任意位置包含该字符串：中间、开头、一直延伸到结尾
+    ("*123*", "a0b0c0d0e0f0a0b0c123e0f0a0b0c0d0e0f0a0b0", true),
+    ("*123*", "123a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0f0a", true),
+    ("*123*", "a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0f0a123", true),
+    ("*123*", "a0b0c0d0e0f0a0b0c120e0f0a0b0c0d0e0f0a0b0", false),
+    ("123/456", "123a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0456", true),
+    ("123/456", "123a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0123", false),
+    ("123/", "123a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0887", true),
+    ("/456", "887a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0456", true),
+    ("/456", "456a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e0887", false),
+    // ? 与该位置上任意一个字符相同
+    ("d??d/", "d00da0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e888", true),
+    ("d??d/", "deada0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e888", true),
+    ("d??d/", "d11da0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e888", true),
+    ("d??d/", "d1e1a0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0e888", false),
+    ("d??d", "deada0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0d00d", true),
+    ("d??d", "deada0b0c0d0e0f0a0b0c0d0e0f0a0b0c0d0d00e", false),
+    ("0x00??00", "00ab00a0b0c0d0e0f0a0b0c0d0e0f0a0b000cd00", true),
+    ("0x00??00", "0ab000a0b0c0d0e0f0a0b0c0d0e0f0a0b000cd00", false),
+];
+
+/// 迷你生成测试的时间上限
+const GENERATION_TIME_LIMIT: Duration = Duration::from_secs(2);
+
+/// 单项检查结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SelfTestCheck {
+    /// 检查名称
+    pub name: String,
+    /// 是否通过
+    pub passed: bool,
+    /// 详细信息
+    pub detail: String,
+}
+
+/// 自检报告
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SelfTestReport {
+    /// 是否全部通过
+    pub passed: bool,
+    /// 各项检查结果
+    pub checks: Vec<SelfTestCheck>,
+    /// 总耗时（毫秒）
+    pub duration: u64,
+}
+
+/**
+ * 根据私钥推导 checksum 格式的地址（不含0x前缀）
+ */
+fn derive_checksum_address(secp: &Secp256k1<secp256k1::All>, key: &SecretKey) -> String {
+    let public_key = PublicKey::from_secret_key(secp, key);
+    public_key_to_checksum_address(&public_key)
+}
+
+fn check(name: &str, result: Result<String, String>) -> SelfTestCheck {
+    let (passed, detail) = match result {
+        Ok(detail) => (true, detail),
+        Err(detail) => (false, detail),
+    };
+    SelfTestCheck { name: name.to_string(), passed, detail }
+}
+
+fn check_known_keys(secp: &Secp256k1<secp256k1::All>) -> Result<String, String> {
+    for (key_hex, expected) in KNOWN_KEYS {
+        let key = PrivKeyHex::parse(key_hex)?.secret_key();
+        let address = derive_checksum_address(secp, &key);
+        if address != *expected {
+            return Err(format!("私钥 {} 推导出 {}，期望 {}", key_hex, address, expected));
+        }
+    }
+    Ok(format!("{} 个已知私钥推导正确", KNOWN_KEYS.len()))
+}
+
+fn check_checksums() -> Result<String, String> {
+    for expected in CHECKSUM_VECTORS {
+        let checksum = to_checksum_address(&expected.to_lowercase())?;
+        if checksum != *expected {
+            return Err(format!("checksum 结果 {}，期望 {}", checksum, expected));
+        }
+    }
+    Ok(format!("{} 个 EIP-55 示例全部一致", CHECKSUM_VECTORS.len()))
+}
+
+fn check_matchers() -> Result<String, String> {
+    for (pattern, address, expected) in MATCHER_CASES {
+        let matched = ParsedPattern::new(pattern).matches(address);
+        if matched != *expected {
+            return Err(format!("模式 {} 对地址 {} 的匹配结果为 {}，期望 {}", pattern, address, matched, expected));
+        }
+    }
+    Ok(format!("{} 个匹配用例全部通过", MATCHER_CASES.len()))
+}
+
+/**
+ * 迷你生成：使用单字符模式在时间上限内找到一个匹配地址，并重新推导验证
+ */
+fn check_generation(secp: &Secp256k1<secp256k1::All>) -> Result<(Wallet, String), String> {
+    let pattern = "8";
+    let parsed = ParsedPattern::new(pattern);
+    let start = Instant::now();
+    let mut rng = OsRng;
+    let mut stats = KeyCandidateStats::default();
+    let mut attempts = 0u64;
+    
+    while start.elapsed() < GENERATION_TIME_LIMIT {
+        attempts += 1;
+        let Some(key) = next_secret_key(&mut rng, &mut stats) else {
+            continue;
+        };
+        let address = derive_checksum_address(secp, &key);
+        if let Some(matched_spans) = parsed.matched_spans(&address) {
+            let wallet = Wallet {
+                index: 1,
+                address: Address::parse(&address)?,
+                private_key: SecretHex::encode(&key.secret_bytes()),
+                attempts,
+                duration: start.elapsed().as_millis() as u64,
+                matched_spans,
+                rng_mode: RngMode::ReseededCsprng,
+                test_wallet: false,
+                mnemonic: None,
+                chain_address: None,
+                matched_pattern: String::new(),
+                created_at: timestamps::now_rfc3339(),
+            };
+            let detail = format!("{} 次尝试后找到 {}", attempts, wallet.address);
+            return Ok((wallet, detail));
+        }
+    }
+    Err(format!("{} 次尝试内未找到匹配模式 {} 的地址", attempts, pattern))
+}
+
+/**
+ * 保存/读取/校验往返：写入临时目录、读回并由私钥重新推导地址
+ */
+fn check_round_trip(secp: &Secp256k1<secp256k1::All>, wallet: &Wallet) -> Result<String, String> {
+    let dir = std::env::temp_dir().join(format!("fancy-wallet-selftest-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).map_err(|e| format!("无法创建临时目录: {}", e))?;
+    
+    let result = (|| {
+        let chain_dir = Chain::Ethereum.dir(&dir);
+        std::fs::create_dir_all(&chain_dir).map_err(|e| format!("无法创建临时目录: {}", e))?;
+        write_wallet_row(&chain_dir.join("wallet_selftest.csv"), wallet, "8", Chain::Ethereum, CsvPrivateKeys::Full)?;
+        let saved = saved_wallets::find_saved_wallet(&dir, &wallet.address)?
+            .ok_or("读回时找不到刚保存的钱包")?;
+        if saved.index != Some(wallet.index) {
+            return Err(format!("读回的序号为 {:?}，期望 {}", saved.index, wallet.index));
+        }
+        let key = PrivKeyHex::parse(&saved.private_key)?.secret_key();
+        let address = Address::from_public_key(&PublicKey::from_secret_key(secp, &key));
+        if address != wallet.address {
+            return Err(format!("读回的私钥推导出 {}，期望 {}", address, wallet.address));
+        }
+        Ok("保存、读回和校验一致".to_string())
+    })();
+    
+    let _ = std::fs::remove_dir_all(&dir);
+    result
+}
+
+/**
+ * 运行离线自检（已知私钥、checksum、匹配器、迷你生成和保存往返）
+ * 
+ * @returns 自检报告
+ */
+pub fn run() -> SelfTestReport {
+    let start = Instant::now();
+    let secp = Secp256k1::new();
+    let mut checks = vec![
+        check("known_keys", check_known_keys(&secp)),
+        check("eip55_checksum", check_checksums()),
+        check("matchers", check_matchers()),
+    ];
+    
+    match check_generation(&secp) {
+        Ok((wallet, detail)) => {
+            checks.push(check("mini_generation", Ok(detail)));
+            checks.push(check("save_round_trip", check_round_trip(&secp, &wallet)));
+        }
+        Err(e) => {
+            checks.push(check("mini_generation", Err(e)));
+            checks.push(check("save_round_trip", Err("迷你生成失败，跳过".to_string())));
+        }
+    }
+    
+    SelfTestReport {
+        passed: checks.iter().all(|c| c.passed),
+        checks,
+        duration: start.elapsed().as_millis() as u64,
+    }
+}
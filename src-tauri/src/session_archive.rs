@@ -0,0 +1,324 @@
+use crate::chain::Chain;
+use crate::hexutil::{Address, Hash32};
+use crate::manifest::{self, SessionManifest};
+use crate::saved_wallets::{self, JSONL_EXTENSION};
+use crate::secure_delete;
+use crate::solana;
+use crate::wallet_qr;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use zeroize::Zeroizing;
+use zip::write::SimpleFileOptions;
+use zip::{AesMode, CompressionMethod, ZipArchive, ZipWriter};
+
+/// 归档文件的扩展名
+pub const ARCHIVE_EXTENSION: &str = "zip";
+
+/// 归档中的一个文件
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ArchivedFile {
+    /// 归档中的路径（相对 FancyWallets 目录，使用 / 分隔）
+    pub path: String,
+    /// 文件大小（字节）
+    pub size: u64,
+    /// 文件的 SHA-256（读回归档时逐个比对）
+    pub sha256: String,
+}
+
+/// 会话归档的结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SessionArchive {
+    /// 归档文件路径
+    pub archive_path: String,
+    /// 归档中的文件（按路径排序）
+    pub files: Vec<ArchivedFile>,
+    /// 是否已删除原文件（只有读回校验通过后才删除）
+    pub originals_deleted: bool,
+    /// 无法删除的原文件（归档已经完整，这些文件需要手动删除）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub delete_failures: Vec<String>,
+}
+
+/**
+ * 收集会话的输出文件：结果文件（CSV、加密的 CSV 和 JSON Lines）、记录了它的清单和清单中的其它产物（回放文件），
+ * 以及结果中各地址的 keystore、Solana 密钥对和地址二维码
+ *
+ * @param wallets_dir - FancyWallets 目录
+ * @param session_id - 会话标识
+ * @returns 按路径排序的文件列表
+ */
+pub fn session_files(wallets_dir: &Path, session_id: &str) -> Result<Vec<PathBuf>, String> {
+    let csv = saved_wallets::session_file(wallets_dir, session_id)?;
+    let mut results: Vec<PathBuf> = [csv.clone(), PathBuf::from(format!("{}.enc", csv.display())), csv.with_extension(JSONL_EXTENSION)]
+        .into_iter()
+        .filter(|path| path.is_file())
+        .collect();
+    if results.is_empty() {
+        return Err(format!("会话不存在: {}", session_id));
+    }
+
+    let mut files = Vec::new();
+    for manifest_path in manifest::find_session_manifests(wallets_dir, session_id) {
+        let dir = manifest_path.parent().unwrap_or(wallets_dir).to_path_buf();
+        let data = fs::read_to_string(&manifest_path).map_err(|e| format!("无法读取清单文件: {}", e))?;
+        let manifest: SessionManifest = serde_json::from_str(&data).map_err(|e| format!("清单格式错误: {}", e))?;
+        files.extend(manifest.artifacts.iter().map(|artifact| dir.join(&artifact.path)).filter(|path| path.is_file()));
+        files.push(manifest_path);
+    }
+
+    let result_dir = csv.parent().unwrap_or(wallets_dir).to_path_buf();
+    for result in results.iter().filter(|path| path.extension().is_some_and(|ext| ext == "csv" || ext == JSONL_EXTENSION)) {
+        let (wallets, _) = saved_wallets::read_generated_file(result, false)?;
+        for wallet in wallets {
+            let Some(chain) = wallet.chain else {
+                continue;
+            };
+            let chain_dir = chain.dir(wallets_dir);
+            let qr_suffix = format!("{}{}", wallet.address, wallet_qr::ADDRESS_FILE_SUFFIX);
+            files.extend(dir_files(&result_dir, |name| name.ends_with(&qr_suffix)));
+            match chain {
+                Chain::Solana => files.extend(Some(chain_dir.join(solana::KEYPAIR_DIR).join(format!("{}.json", wallet.address))).filter(|path| path.is_file())),
+                _ => {
+                    if let Ok(address) = Address::parse(&wallet.address) {
+                        let keystore_suffix = format!("--{}.json", hex::encode(address.as_bytes()));
+                        files.extend(dir_files(&chain_dir.join("keystore"), |name| name.starts_with("UTC--") && name.ends_with(&keystore_suffix)));
+                    }
+                }
+            }
+        }
+    }
+    files.append(&mut results);
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/**
+ * 目录中文件名满足条件的文件（目录不存在时为空）
+ */
+fn dir_files(dir: &Path, matches: impl Fn(&str) -> bool) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.file_name().and_then(|name| name.to_str()).is_some_and(&matches))
+        .collect()
+}
+
+/**
+ * 检查归档路径：必须是 .zip 文件的绝对路径，不能包含 ..，不能位于 FancyWallets 目录中（归档会和原文件一起被收集或删除）
+ *
+ * @param dest - 归档文件路径
+ * @param wallets_dir - FancyWallets 目录
+ */
+pub fn validate_dest(dest: &Path, wallets_dir: &Path) -> Result<(), String> {
+    if !dest.is_absolute() {
+        return Err(format!("归档路径必须是绝对路径: {}", dest.display()));
+    }
+    if dest.components().any(|c| c == Component::ParentDir) {
+        return Err(format!("归档路径不能包含 \"..\": {}", dest.display()));
+    }
+    if !dest.extension().is_some_and(|ext| ext.eq_ignore_ascii_case(ARCHIVE_EXTENSION)) {
+        return Err(format!("归档文件的扩展名必须是 .{}: {}", ARCHIVE_EXTENSION, dest.display()));
+    }
+    if dest.starts_with(wallets_dir) {
+        return Err(format!("归档文件不能位于钱包目录中: {}", dest.display()));
+    }
+    Ok(())
+}
+
+/**
+ * 把文件写入 AES-256 加密的 ZIP 归档，写完后用同一个密码读回并逐个比对内容；可选在校验通过后安全删除原文件
+ *
+ * 归档先写入同目录的临时文件，校验通过后才改名为目标路径；任何一步失败都不会删除原文件。
+ *
+ * @param wallets_dir - FancyWallets 目录（归档中的路径相对它记录）
+ * @param files - 要归档的文件
+ * @param password - 归档密码
+ * @param dest - 归档文件路径（已存在时覆盖）
+ * @param delete_originals - 校验通过后是否覆盖并删除原文件
+ */
+pub fn create(wallets_dir: &Path, files: &[PathBuf], password: &str, dest: &Path, delete_originals: bool) -> Result<SessionArchive, String> {
+    if password.is_empty() {
+        return Err("归档密码不能为空".to_string());
+    }
+    validate_dest(dest, wallets_dir)?;
+    if files.is_empty() {
+        return Err("会话没有可归档的文件".to_string());
+    }
+    if delete_originals {
+        if let Some(active) = files.iter().find(|file| saved_wallets::is_active(file)) {
+            return Err(format!("结果文件 {} 正在被会话写入，结束后才能删除原文件", active.display()));
+        }
+    }
+    if let Some(parent) = dest.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("无法创建归档目录: {}", e))?;
+    }
+
+    let tmp_path = PathBuf::from(format!("{}.tmp", dest.display()));
+    let written = write_archive(wallets_dir, files, password, &tmp_path).and_then(|entries| {
+        verify_archive(&tmp_path, &entries, password)?;
+        fs::rename(&tmp_path, dest).map_err(|e| format!("无法写入归档文件: {}", e))?;
+        Ok(entries)
+    });
+    let entries = match written {
+        Ok(entries) => entries,
+        Err(e) => {
+            let _ = fs::remove_file(&tmp_path);
+            return Err(e);
+        }
+    };
+
+    let mut delete_failures = Vec::new();
+    if delete_originals {
+        for file in files {
+            if let Err(e) = secure_delete::wipe_file(file) {
+                delete_failures.push(format!("{}: {}", file.display(), e));
+            }
+        }
+    }
+    Ok(SessionArchive {
+        archive_path: dest.to_string_lossy().to_string(),
+        files: entries,
+        originals_deleted: delete_originals && delete_failures.is_empty(),
+        delete_failures,
+    })
+}
+
+fn write_archive(wallets_dir: &Path, files: &[PathBuf], password: &str, path: &Path) -> Result<Vec<ArchivedFile>, String> {
+    let file = File::create(path).map_err(|e| format!("无法创建归档文件: {}", e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .with_aes_encryption(AesMode::Aes256, password);
+    let mut entries = Vec::with_capacity(files.len());
+    for source in files {
+        let name = source.strip_prefix(wallets_dir).map_err(|_| format!("文件不在钱包目录中: {}", source.display()))?.to_string_lossy().replace('\\', "/");
+        let data = Zeroizing::new(fs::read(source).map_err(|e| format!("无法读取 {}: {}", source.display(), e))?);
+        zip.start_file(name.as_str(), options).map_err(|e| format!("无法写入归档文件: {}", e))?;
+        zip.write_all(&data).map_err(|e| format!("无法写入归档文件: {}", e))?;
+        entries.push(ArchivedFile { path: name, size: data.len() as u64, sha256: sha256_hex(&data) });
+    }
+    let file = zip.finish().map_err(|e| format!("无法写入归档文件: {}", e))?;
+    file.sync_all().map_err(|e| format!("无法写入归档文件: {}", e))?;
+    Ok(entries)
+}
+
+/**
+ * 用密码读回归档，确认每个文件都在并且内容与原文件一致
+ *
+ * @param path - 归档文件路径
+ * @param entries - 写入时记录的文件
+ * @param password - 归档密码
+ */
+pub fn verify_archive(path: &Path, entries: &[ArchivedFile], password: &str) -> Result<(), String> {
+    let file = File::open(path).map_err(|e| format!("无法读回归档文件: {}", e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("归档校验失败: {}", e))?;
+    if archive.len() != entries.len() {
+        return Err(format!("归档校验失败：归档中有 {} 个文件，应为 {} 个", archive.len(), entries.len()));
+    }
+    for entry in entries {
+        let mut zipped = archive.by_name_decrypt(&entry.path, password.as_bytes()).map_err(|e| format!("归档校验失败（{}）: {}", entry.path, e))?;
+        let mut data = Zeroizing::new(Vec::new());
+        zipped.read_to_end(&mut data).map_err(|e| format!("归档校验失败（{}）: {}", entry.path, e))?;
+        if data.len() as u64 != entry.size || sha256_hex(&data) != entry.sha256 {
+            return Err(format!("归档校验失败：{} 的内容与原文件不一致", entry.path));
+        }
+    }
+    Ok(())
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Hash32::from(<[u8; 32]>::from(Sha256::digest(data))).to_hex()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entropy::RngMode;
+    use crate::gui::{write_wallet_row, Wallet};
+    use crate::output_root;
+    use crate::keystore;
+    use crate::hexutil::{PrivKeyHex, SecretHex};
+    use crate::saved_wallets::CsvPrivateKeys;
+    use crate::timestamps;
+    use crate::wallet_qr::QrOptions;
+    use secp256k1::{PublicKey, Secp256k1};
+    use crate::test_support::with_data_dir;
+    use crate::self_test::KNOWN_KEYS;
+
+    /**
+     * 会话归档：收集结果文件和其中地址的 keystore、二维码（其它地址的不收集），读回校验，
+     * 失败时不删除原文件，校验通过后才删除
+     */
+    #[test]
+    fn session_archive() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        with_data_dir("session_archive", |dir| {
+            let wallets_dir = dir.join(output_root::WALLETS_DIR_NAME);
+            let chain_dir = Chain::Ethereum.dir(&wallets_dir);
+            std::fs::create_dir_all(&chain_dir).map_err(|e| e.to_string())?;
+            let addresses = KNOWN_KEYS[..2]
+                .iter()
+                .map(|(key, _)| Ok(Address::from_public_key(&PublicKey::from_secret_key(&secp, &PrivKeyHex::parse(key)?.secret_key()))))
+                .collect::<Result<Vec<_>, String>>()?;
+            let wallet = Wallet {
+                index: 1,
+                address: addresses[0],
+                private_key: SecretHex::from(KNOWN_KEYS[0].0.to_string()),
+                attempts: 1,
+                duration: 0,
+                matched_spans: Vec::new(),
+                rng_mode: RngMode::default(),
+                test_wallet: false,
+                mnemonic: None,
+                chain_address: None,
+                matched_pattern: String::new(),
+                created_at: timestamps::now_rfc3339(),
+            };
+            let csv = chain_dir.join("wallet_archive_a.csv");
+            write_wallet_row(&csv, &wallet, "8", Chain::Ethereum, CsvPrivateKeys::Full)?;
+            let (keystore_file, _) = keystore::write_file(&chain_dir.join("keystore"), &addresses[0], "{}")?;
+            let (other_keystore, _) = keystore::write_file(&chain_dir.join("keystore"), &addresses[1], "{}")?;
+            let (qr_file, _) = wallet_qr::save_address_png(&chain_dir, "", &Chain::Ethereum.format_address(addresses[0]), &QrOptions::default())?;
+            
+            let mut expected = vec![csv.clone(), keystore_file.clone(), qr_file.clone()];
+            expected.sort();
+            let files = session_files(&wallets_dir, "archive_a")?;
+            if files != expected {
+                return Err(format!("会话的文件为 {:?}，应为 {:?}", files, expected));
+            }
+            
+            let dest = dir.join("cold").join("archive_a.zip");
+            let missing = [files.clone(), vec![chain_dir.join("missing.csv")]].concat();
+            let failures = [
+                create(&wallets_dir, &files, "", &dest, true),
+                create(&wallets_dir, &files, "secret", &chain_dir.join("archive.zip"), true),
+                create(&wallets_dir, &missing, "secret", &dest, true),
+            ];
+            if failures.iter().any(Result::is_ok) || files.iter().any(|file| !file.exists()) || dest.exists() || dest.with_extension("zip.tmp").exists() {
+                return Err("失败的归档删除了原文件，或留下了归档文件".to_string());
+            }
+            
+            let kept = create(&wallets_dir, &files, "secret", &dest, false)?;
+            if kept.originals_deleted || kept.files.len() != 3 || files.iter().any(|file| !file.exists()) {
+                return Err(format!("不删除原文件的归档结果不正确: {:?}", kept));
+            }
+            if verify_archive(&dest, &kept.files, "wrong").is_ok() {
+                return Err("错误的密码通过了归档校验".to_string());
+            }
+            let archived = create(&wallets_dir, &files, "secret", &dest, true)?;
+            verify_archive(&dest, &archived.files, "secret")?;
+            let paths: Vec<&str> = archived.files.iter().map(|file| file.path.as_str()).collect();
+            if !archived.originals_deleted || files.iter().any(|file| file.exists()) || !other_keystore.exists() || !paths.contains(&"ethereum/wallet_archive_a.csv") {
+                return Err(format!("删除原文件的归档结果不正确: {:?}", archived));
+            }
+            Ok(())
+        })
+    }
+}
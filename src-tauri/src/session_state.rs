@@ -0,0 +1,321 @@
+use crate::engine::SearchTotals;
+use crate::entropy::RngMode;
+use crate::sessions;
+use crate::stop::{StopReason, StopRecord};
+use crate::templates::GenerationParams;
+use crate::timestamps;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// 会话状态文件所在的目录（FancyWallets/session_state/<会话标识>.json）
+pub const STATE_DIR: &str = "session_state";
+
+/// 会话状态文件的格式版本
+pub const STATE_VERSION: u32 = 1;
+
+/// 运行期间更新会话状态文件的最短间隔（找到匹配和会话结束时立即更新）
+pub const SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 会话状态文件（不含任何私钥或密码；keystore 和加密输出的密码恢复时需要重新输入）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SessionState {
+    /// 格式版本
+    pub version: u32,
+    /// 会话标识
+    pub session_id: String,
+    /// 生成参数
+    pub params: GenerationParams,
+    /// 来源模板（直接开始时为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// 是否另外保存 keystore 文件（恢复时需要 keystore_password）
+    #[serde(default)]
+    pub keystore: bool,
+    /// 是否只保存 keystore
+    #[serde(default)]
+    pub keystore_only: bool,
+    /// 结果文件是否加密（恢复时需要 encrypt_output）
+    #[serde(default)]
+    pub encrypted_output: bool,
+    /// 到上一次更新为止的累计计数（含恢复之前的会话）
+    pub totals: SearchTotals,
+    /// 每个模式的匹配数
+    #[serde(default)]
+    pub pattern_matches: BTreeMap<String, u64>,
+    /// 会话开始时间（UTC，RFC 3339）
+    pub started_at: String,
+    /// 上一次更新的时间（UTC，RFC 3339）
+    pub updated_at: String,
+    /// 会话的停止原因（仍在运行或中途崩溃时为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop: Option<StopRecord>,
+    /// 这个会话恢复自哪个会话
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resumed_from: Option<String>,
+    /// 这个会话已经恢复为哪个会话（不能再次恢复）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resumed_as: Option<String>,
+}
+
+/// 恢复会话时读取的状态
+#[derive(Debug, Clone)]
+pub struct Resume {
+    /// 会话状态文件
+    pub state_file: PathBuf,
+    /// 读取的状态
+    pub state: SessionState,
+}
+
+/// 恢复的会话（session-resumed 事件和 resume_session 的返回值）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ResumedSession {
+    /// 新会话的标识
+    pub session_id: String,
+    /// 恢复自哪个会话
+    pub resumed_from: String,
+    /// 读取的会话状态文件
+    pub state_file: PathBuf,
+    /// 继续累计的计数（之前的会话最后一次更新状态文件时的值；之后到中断之间的尝试没有记录）
+    pub totals: SearchTotals,
+    /// 是否恢复了随机数状态（总是 false：私钥是随机的，恢复的只是参数和统计，之后的候选与之前的尝试无关）
+    pub rng_state_restored: bool,
+    /// 说明
+    pub note: String,
+}
+
+/**
+ * 会话状态文件的路径
+ *
+ * @param wallets_dir - FancyWallets 目录
+ * @param session_id - 会话标识
+ */
+pub fn state_path(wallets_dir: &Path, session_id: &str) -> PathBuf {
+    wallets_dir.join(STATE_DIR).join(format!("{}.json", session_id))
+}
+
+/**
+ * 写入会话状态文件（先写入临时文件再替换，中断时不会留下半个文件）
+ */
+pub fn save(path: &Path, state: &SessionState) -> Result<(), String> {
+    let dir = path.parent().ok_or("会话状态文件路径无效")?;
+    fs::create_dir_all(dir).map_err(|e| format!("无法创建会话状态目录: {}", e))?;
+    let json = serde_json::to_string_pretty(state).map_err(|e| format!("无法序列化会话状态: {}", e))?;
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, json).map_err(|e| format!("无法写入会话状态: {}", e))?;
+    fs::rename(&tmp_path, path).map_err(|e| format!("无法写入会话状态: {}", e))
+}
+
+/**
+ * 读取会话状态文件；无法解析或由更新的版本写入时返回错误
+ *
+ * @param path - 会话状态文件
+ */
+pub fn load(path: &Path) -> Result<SessionState, String> {
+    let data = fs::read_to_string(path).map_err(|e| format!("无法读取会话状态文件 {}: {}", path.display(), e))?;
+    let state: SessionState = serde_json::from_str(&data).map_err(|e| format!("会话状态文件已损坏（{}）: {}", path.display(), e))?;
+    if state.version > STATE_VERSION {
+        return Err(format!("会话状态文件由更新的版本（格式 {}）写入，不能在此版本中恢复", state.version));
+    }
+    sessions::validate_id(&state.session_id).map_err(|e| format!("会话状态文件已损坏: {}", e))?;
+    if timestamps::parse_rfc3339(&state.started_at).is_none() || timestamps::parse_rfc3339(&state.updated_at).is_none() {
+        return Err("会话状态文件已损坏: 时间格式不正确".to_string());
+    }
+    if state.totals.matches < state.pattern_matches.values().sum::<u64>() {
+        return Err("会话状态文件已损坏: 各模式的匹配数之和超过总匹配数".to_string());
+    }
+    Ok(state)
+}
+
+/**
+ * 检查会话是否可以恢复：不能正在运行、不能已经恢复过、不能已经达到停止条件，参数在此版本中必须有效
+ *
+ * @param state - 读取的状态
+ * @param running - 同一标识的会话是否正在运行
+ */
+pub fn check_resumable(state: &SessionState, running: bool) -> Result<(), String> {
+    if running {
+        return Err(format!("会话 {} 正在运行，不需要恢复", state.session_id));
+    }
+    if state.params.rng_mode.is_some_and(RngMode::is_test) {
+        return Err("确定性测试模式的会话每次都从同一个种子开始，恢复后会重复生成相同的测试钱包".to_string());
+    }
+    if let Some(resumed_as) = &state.resumed_as {
+        return Err(format!("会话 {} 已经恢复为会话 {}，请恢复最新的会话", state.session_id, resumed_as));
+    }
+    if let Some(stop) = state.stop.as_ref().filter(|stop| matches!(stop.reason, StopReason::MaxAttempts | StopReason::MaxMatches | StopReason::MaxDuration)) {
+        return Err(format!("会话 {} 已经达到停止条件（{}），恢复后会立即停止", state.session_id, stop.detail.as_deref().unwrap_or("停止条件")));
+    }
+    state.params.validate().map_err(|e| format!("会话状态文件中的参数在此版本中无效: {}", e))
+}
+
+/// 运行期间按 SAVE_INTERVAL 更新会话状态文件
+pub struct StateWriter {
+    path: PathBuf,
+    state: SessionState,
+    last_saved: Option<Instant>,
+    /// 是否已经报告过写入失败（只报告一次，会话照常进行）
+    failed: bool,
+}
+
+impl StateWriter {
+    /**
+     * 创建会话状态文件
+     *
+     * @param path - 会话状态文件
+     * @param state - 会话开始时的状态
+     */
+    pub fn new(path: PathBuf, state: SessionState) -> Self {
+        StateWriter { path, state, last_saved: None, failed: false }
+    }
+
+    /**
+     * 会话状态文件的路径
+     */
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /**
+     * 记录最新的计数；距离上一次写入不到 SAVE_INTERVAL 且 force 为 false 时只更新内存中的状态
+     *
+     * @param totals - 累计计数
+     * @param pattern_matches - 每个模式的匹配数
+     * @param now - 当前时刻
+     * @param force - 是否立即写入（找到匹配时）
+     * @returns 第一次写入失败的原因（之后的失败不再返回）
+     */
+    pub fn update(&mut self, totals: SearchTotals, pattern_matches: &BTreeMap<String, u64>, now: Instant, force: bool) -> Option<String> {
+        self.state.totals = totals;
+        self.state.pattern_matches.clone_from(pattern_matches);
+        if !force && self.last_saved.is_some_and(|last| now.saturating_duration_since(last) < SAVE_INTERVAL) {
+            return None;
+        }
+        self.last_saved = Some(now);
+        self.write()
+    }
+
+    /**
+     * 会话结束：记录停止原因并写入
+     *
+     * @returns 第一次写入失败的原因
+     */
+    pub fn finish(mut self, totals: SearchTotals, pattern_matches: &BTreeMap<String, u64>, stop: &StopRecord) -> Option<String> {
+        self.state.stop = Some(stop.clone());
+        self.update(totals, pattern_matches, Instant::now(), true)
+    }
+
+    fn write(&mut self) -> Option<String> {
+        self.state.updated_at = timestamps::now_rfc3339();
+        match save(&self.path, &self.state) {
+            Ok(()) => None,
+            Err(_) if self.failed => None,
+            Err(e) => {
+                self.failed = true;
+                Some(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{CancellationToken, GenerationLimits, SearchConfig, SearchEvent, SessionClock};
+    use crate::test_support::with_data_dir;
+
+    /**
+     * 会话恢复：计数从状态文件继续累计，停止条件按累计值判断；损坏、过期或不能恢复的状态文件返回错误
+     */
+    #[test]
+    fn session_resume() -> Result<(), String> {
+        // 尝试次数、匹配数和运行时间从之前的计数继续
+        let totals = SearchTotals { attempts: 1_000, matches: 2, duration: 60_000 };
+        let limits = GenerationLimits { max_attempts: Some(1_500), ..GenerationLimits::default() };
+        let config = SearchConfig { limits, workers: Some(2), resume_from: Some(totals), ..SearchConfig::new("ffffffffff") };
+        let mut first_progress = None;
+        let report = crate::engine::search(&config, &CancellationToken::new(), || false, |event| {
+            if let SearchEvent::Progress(progress) = event {
+                first_progress.get_or_insert(progress.attempts);
+            }
+        });
+        if report.attempts != 1_500 || report.matches < totals.matches || report.duration < totals.duration || first_progress.is_none_or(|attempts| attempts <= totals.attempts) {
+            return Err(format!("恢复后尝试 {} 次、{} 个匹配、运行 {} 毫秒，第一次进度为 {:?}", report.attempts, report.matches, report.duration, first_progress));
+        }
+        let start = Instant::now();
+        let clock = SessionClock::resumed(start, Duration::from_secs(60));
+        if clock.active(start + Duration::from_secs(5)) != Duration::from_secs(65) {
+            return Err("恢复的会话的运行时间没有继续累计".to_string());
+        }
+        
+        with_data_dir("session-resume", |dir| {
+            let params = GenerationParams { pattern: "8888".to_string(), ..GenerationParams::default() };
+            let state = SessionState {
+                version: STATE_VERSION,
+                session_id: "8888_20240101_000000000Z".to_string(),
+                params,
+                template: None,
+                keystore: false,
+                keystore_only: false,
+                encrypted_output: false,
+                totals: SearchTotals::default(),
+                pattern_matches: BTreeMap::from([("8888".to_string(), 0)]),
+                started_at: timestamps::now_rfc3339(),
+                updated_at: timestamps::now_rfc3339(),
+                stop: None,
+                resumed_from: None,
+                resumed_as: None,
+            };
+            let path = state_path(dir, &state.session_id);
+            let mut writer = StateWriter::new(path.clone(), state.clone());
+            let now = Instant::now();
+            let pattern_matches = BTreeMap::from([("8888".to_string(), 1)]);
+            writer.update(totals, &pattern_matches, now, true).map_or(Ok(()), Err)?;
+            // 间隔不到 SAVE_INTERVAL 时不写入，找到匹配时立即写入
+            let later = SearchTotals { attempts: 2_000, ..totals };
+            writer.update(later, &pattern_matches, now + Duration::from_secs(1), false).map_or(Ok(()), Err)?;
+            let saved = load(&path)?;
+            if saved.totals != totals || saved.pattern_matches != pattern_matches {
+                return Err(format!("状态文件中的计数为 {:?}", saved.totals));
+            }
+            writer.update(later, &pattern_matches, now + SAVE_INTERVAL, false).map_or(Ok(()), Err)?;
+            if load(&path)?.totals != later {
+                return Err("超过 SAVE_INTERVAL 后没有更新状态文件".to_string());
+            }
+            check_resumable(&load(&path)?, false)?;
+            
+            // 不能恢复的会话
+            let stopped = StopRecord { reason: StopReason::MaxAttempts, detail: Some("max_attempts=1000".to_string()) };
+            let rejected = [
+                (SessionState { resumed_as: Some("other".to_string()), ..saved.clone() }, false),
+                (SessionState { stop: Some(stopped), ..saved.clone() }, false),
+                (SessionState { params: GenerationParams { rng_mode: Some(RngMode::DeterministicTest), ..saved.params.clone() }, ..saved.clone() }, false),
+                (SessionState { params: GenerationParams { pattern: "xyz".to_string(), ..saved.params.clone() }, ..saved.clone() }, false),
+                (saved.clone(), true),
+            ];
+            for (state, running) in &rejected {
+                if check_resumable(state, *running).is_ok() {
+                    return Err(format!("不能恢复的会话没有被拒绝: {:?}", state));
+                }
+            }
+            let stopped_by_user = StopRecord { reason: StopReason::UserUi, detail: None };
+            check_resumable(&SessionState { stop: Some(stopped_by_user), ..saved.clone() }, false)?;
+            
+            // 损坏或由更新的版本写入的状态文件
+            let newer = serde_json::to_string(&SessionState { version: STATE_VERSION + 1, ..saved.clone() }).map_err(|e| e.to_string())?;
+            let overcounted = serde_json::to_string(&SessionState { pattern_matches: BTreeMap::from([("8888".to_string(), 99)]), ..saved.clone() }).map_err(|e| e.to_string())?;
+            for content in ["", "{\"version\": 1", "[]", newer.as_str(), overcounted.as_str()] {
+                std::fs::write(&path, content).map_err(|e| e.to_string())?;
+                if load(&path).is_ok() {
+                    return Err(format!("损坏的状态文件没有被拒绝: {:?}", content));
+                }
+            }
+            if load(&dir.join("missing.json")).is_ok() {
+                return Err("不存在的状态文件没有返回错误".to_string());
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,526 @@
+use crate::engine::{CancellationToken, CpuLimit};
+use crate::memory::MemoryUsage;
+use crate::output_cap::OutputUsage;
+use crate::pause::{PauseRequest, PauseState};
+use crate::stop::{StopReason, StopRecord};
+use crate::timestamps;
+use crate::worker::WorkerCapacity;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::time::Duration;
+
+/// 调用方指定的会话标识的最大长度
+const MAX_SESSION_ID_LENGTH: usize = 128;
+
+/// 会话标识
+pub type SessionId = String;
+
+/// 会话的种类
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionKind {
+    /// 生成靓号钱包（包括队列和空闲自动开始的任务）
+    Generation,
+    /// 拆分密钥搜索
+    SplitKey,
+    /// 在一个助记词的地址序号中寻找靓号
+    MnemonicScan,
+    /// 寻找靓号合约地址（部署者私钥 + nonce）
+    ContractSearch,
+    /// 硬件稳定性压力测试
+    BurnIn,
+    /// 校准
+    Calibration,
+    /// 测速
+    Benchmark,
+}
+
+impl SessionKind {
+    /**
+     * 是否支持暂停（生成会话和拆分密钥搜索）
+     */
+    pub fn pausable(self) -> bool {
+        matches!(self, SessionKind::Generation | SessionKind::SplitKey)
+    }
+}
+
+/// 正在运行的会话的状态
+struct SessionState {
+    /// 会话的种类
+    kind: SessionKind,
+    /// 会话自己的取消标志（取消一个会话不影响其它会话）
+    cancel: Arc<AtomicBool>,
+    /// 收到的停止请求（只保留第一个）
+    stop: Option<StopRecord>,
+    /// 开始时间
+    started_at: String,
+    /// 会话的 CPU 占用上限（与搜索共享，set_cpu_limit 运行中调整）
+    cpu_limit: CpuLimit,
+    /// 会话运行中更新的暂停状态和统计（只属于这个会话，随会话注销）
+    live: LiveStatus,
+}
+
+/// 生成会话运行中更新的暂停状态和统计（get_generation_status 按会话读取）
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct LiveStatus {
+    /// 暂停状态（区分用户暂停、自动暂停和因输出上限暂停）
+    pub pause: PauseState,
+    /// 内存统计（会话还没有更新时为空）
+    pub memory: Option<MemoryUsage>,
+    /// 工作线程容量（有工作线程异常退出时 degraded；会话还没有开始搜索时为空）
+    pub workers: Option<WorkerCapacity>,
+    /// 实际执行的匹配条件
+    pub matcher: Option<String>,
+    /// 输出统计、上限和触发状态
+    pub output: Option<OutputUsage>,
+}
+
+/// 正在运行的会话的概要
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SessionSummary {
+    /// 会话标识
+    pub session_id: SessionId,
+    /// 会话的种类
+    pub kind: SessionKind,
+    /// 开始时间
+    pub started_at: String,
+    /// 是否已收到停止请求（正在结束）
+    pub stopping: bool,
+    /// CPU 占用上限（百分比，100 为不限制）
+    pub cpu_limit_percent: u8,
+}
+
+/// 正在运行的会话（以会话标识为键）
+static SESSIONS: OnceLock<Mutex<HashMap<SessionId, SessionState>>> = OnceLock::new();
+
+/// 会话注销时通知等待全部会话结束的线程（应用退出时使用）
+static SESSION_ENDED: Condvar = Condvar::new();
+
+fn sessions() -> &'static Mutex<HashMap<SessionId, SessionState>> {
+    SESSIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/**
+ * 检查调用方指定的会话标识：不能为空，只能包含字母、数字、下划线、连字符和点（会话标识会出现在事件和记录中）
+ *
+ * @param session_id - 会话标识
+ */
+pub fn validate_id(session_id: &str) -> Result<(), String> {
+    if session_id.is_empty() || session_id.len() > MAX_SESSION_ID_LENGTH {
+        return Err(format!("会话标识必须是 1 到 {} 个字符", MAX_SESSION_ID_LENGTH));
+    }
+    if let Some(c) = session_id.chars().find(|c| !c.is_ascii_alphanumeric() && !matches!(c, '_' | '-' | '.')) {
+        return Err(format!("会话标识不能包含字符 {:?}（只能使用字母、数字、下划线、连字符和点）", c));
+    }
+    Ok(())
+}
+
+/// 已登记的会话，离开作用域时注销
+pub struct SessionHandle {
+    session_id: SessionId,
+    cancel: Arc<AtomicBool>,
+    cpu_limit: CpuLimit,
+}
+
+impl SessionHandle {
+    /**
+     * 会话的取消令牌
+     */
+    pub fn cancel_token(&self) -> CancellationToken {
+        CancellationToken::from_flag(self.cancel.clone())
+    }
+
+    /**
+     * 会话的取消标志（不使用搜索引擎的循环直接检查）
+     */
+    pub fn cancel_flag(&self) -> &AtomicBool {
+        &self.cancel
+    }
+
+    /**
+     * 会话的 CPU 占用上限（交给搜索配置；与会话登记的上限共享同一个值）
+     */
+    pub fn cpu_limit(&self) -> CpuLimit {
+        self.cpu_limit.clone()
+    }
+
+    /**
+     * 取出停止请求（没有登记原因时视为界面停止）
+     */
+    pub fn take_stop(&self) -> StopRecord {
+        sessions()
+            .lock()
+            .ok()
+            .and_then(|mut map| map.get_mut(&self.session_id)?.stop.take())
+            .unwrap_or(StopRecord { reason: StopReason::UserUi, detail: None })
+    }
+}
+
+impl Drop for SessionHandle {
+    fn drop(&mut self) {
+        if let Ok(mut map) = sessions().lock() {
+            map.remove(&self.session_id);
+        }
+        SESSION_ENDED.notify_all();
+    }
+}
+
+/**
+ * 登记正在运行的会话；同一标识的会话已在运行时拒绝
+ *
+ * @param session_id - 会话标识
+ * @param kind - 会话的种类
+ * @returns 会话句柄（持有会话自己的取消标志）
+ */
+pub fn register(session_id: &str, kind: SessionKind) -> Result<SessionHandle, String> {
+    let mut map = sessions().lock().map_err(|_| "会话状态不可用".to_string())?;
+    if map.contains_key(session_id) {
+        return Err(format!("会话 {} 已在运行", session_id));
+    }
+    let cancel = Arc::new(AtomicBool::new(false));
+    let cpu_limit = CpuLimit::default();
+    map.insert(
+        session_id.to_string(),
+        SessionState {
+            kind,
+            cancel: cancel.clone(),
+            stop: None,
+            started_at: timestamps::now_rfc3339(),
+            cpu_limit: cpu_limit.clone(),
+            live: LiveStatus::default(),
+        },
+    );
+    Ok(SessionHandle { session_id: session_id.to_string(), cancel, cpu_limit })
+}
+
+/**
+ * 登记停止请求并设置取消标志；会话已有停止请求时保留先到的那个
+ *
+ * @param session_id - 目标会话（为空时停止全部正在运行的会话）
+ * @param reason - 停止原因
+ * @param detail - 补充说明
+ * @returns 收到停止请求的会话数；指定的会话不存在时返回错误
+ */
+pub fn request_stop(session_id: Option<&str>, reason: StopReason, detail: Option<String>) -> Result<usize, String> {
+    let mut map = sessions().lock().map_err(|_| "会话状态不可用".to_string())?;
+    let mut stop = |state: &mut SessionState| {
+        state.stop.get_or_insert_with(|| StopRecord { reason, detail: detail.clone() });
+        state.cancel.store(true, Ordering::SeqCst);
+    };
+    match session_id {
+        Some(session_id) => {
+            let state = map.get_mut(session_id).ok_or_else(|| format!("会话不存在或已结束: {}", session_id))?;
+            stop(state);
+            Ok(1)
+        }
+        None => {
+            map.values_mut().for_each(&mut stop);
+            Ok(map.len())
+        }
+    }
+}
+
+/**
+ * 调整正在运行的生成会话的 CPU 占用上限（各工作线程在当前这一段计算结束时生效；其它种类的会话不限制 CPU 占用）
+ *
+ * @param session_id - 目标会话（为空时调整全部正在运行的生成会话）
+ * @param percent - 上限（1–100，100 为不限制）
+ * @returns 调整的会话数；指定的会话不存在或不是生成会话时返回错误
+ */
+pub fn set_cpu_limit(session_id: Option<&str>, percent: u8) -> Result<usize, String> {
+    crate::engine::validate_cpu_limit(percent)?;
+    let map = sessions().lock().map_err(|_| "会话状态不可用".to_string())?;
+    match session_id {
+        Some(session_id) => {
+            let state = map.get(session_id).ok_or_else(|| format!("会话不存在或已结束: {}", session_id))?;
+            if state.kind != SessionKind::Generation {
+                return Err(format!("会话 {} 不是生成会话，不支持限制 CPU 占用", session_id));
+            }
+            state.cpu_limit.set(percent)?;
+            Ok(1)
+        }
+        None => {
+            let generations: Vec<&SessionState> = map.values().filter(|state| state.kind == SessionKind::Generation).collect();
+            for state in &generations {
+                state.cpu_limit.set(percent)?;
+            }
+            Ok(generations.len())
+        }
+    }
+}
+
+/**
+ * 暂停或恢复生成会话和拆分密钥搜索（其它种类的会话不支持暂停）；每个会话的暂停状态相互独立
+ *
+ * @param session_id - 目标会话（为空时作用于全部正在运行的可以暂停的会话）
+ * @param request - 暂停或恢复的请求
+ * @returns 状态发生变化的会话数；指定的会话不存在或不支持暂停时返回错误
+ */
+pub fn update_pause(session_id: Option<&str>, request: PauseRequest) -> Result<usize, String> {
+    let mut map = sessions().lock().map_err(|_| "会话状态不可用".to_string())?;
+    let apply = |state: &mut SessionState| match state.live.pause.apply(request) {
+        Some(next) => {
+            state.live.pause = next;
+            1
+        }
+        None => 0,
+    };
+    match session_id {
+        Some(session_id) => {
+            let state = map.get_mut(session_id).ok_or_else(|| format!("会话不存在或已结束: {}", session_id))?;
+            if !state.kind.pausable() {
+                return Err(format!("会话 {} 不支持暂停", session_id));
+            }
+            Ok(apply(state))
+        }
+        None => Ok(map.values_mut().filter(|state| state.kind.pausable()).map(apply).sum()),
+    }
+}
+
+/**
+ * 会话的暂停状态（会话不存在时视为运行）
+ *
+ * @param session_id - 会话标识
+ */
+pub fn pause_state(session_id: &str) -> PauseState {
+    sessions()
+        .lock()
+        .ok()
+        .and_then(|map| map.get(session_id).map(|state| state.live.pause))
+        .unwrap_or_default()
+}
+
+/**
+ * 更新会话运行中的统计（会话不存在时忽略）
+ *
+ * @param session_id - 会话标识
+ * @param update - 修改会话的统计
+ */
+pub fn update_live(session_id: &str, update: impl FnOnce(&mut LiveStatus)) {
+    if let Ok(mut map) = sessions().lock() {
+        if let Some(state) = map.get_mut(session_id) {
+            update(&mut state.live);
+        }
+    }
+}
+
+/**
+ * 会话的暂停状态和统计
+ *
+ * @param session_id - 会话标识
+ * @returns 会话不存在或已结束时为空
+ */
+pub fn live_status(session_id: &str) -> Option<LiveStatus> {
+    sessions().lock().ok().and_then(|map| map.get(session_id).map(|state| state.live.clone()))
+}
+
+/**
+ * 等待全部会话结束（会话在写完结果文件、会话状态和清单之后才注销），最多等待 timeout
+ *
+ * @param timeout - 最长等待时间
+ * @returns 是否已全部结束；超时时为 false
+ */
+pub fn wait_until_idle(timeout: Duration) -> bool {
+    let Ok(map) = sessions().lock() else {
+        return false;
+    };
+    SESSION_ENDED.wait_timeout_while(map, timeout, |map| !map.is_empty()).is_ok_and(|(map, _)| map.is_empty())
+}
+
+/**
+ * 正在运行的会话（按开始时间排序）
+ */
+pub fn list() -> Vec<SessionSummary> {
+    let mut list: Vec<SessionSummary> = sessions()
+        .lock()
+        .map(|map| {
+            map.iter()
+                .map(|(session_id, state)| SessionSummary {
+                    session_id: session_id.clone(),
+                    kind: state.kind,
+                    started_at: state.started_at.clone(),
+                    stopping: state.cancel.load(Ordering::SeqCst),
+                    cpu_limit_percent: state.cpu_limit.percent(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    list.sort_by(|a, b| (&a.started_at, &a.session_id).cmp(&(&b.started_at, &b.session_id)));
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::lock_sessions;
+    use crate::entropy::RngMode;
+    use crate::engine::{GenerationLimits, SearchConfig};
+    use crate::templates::GenerationParams;
+    use std::time::Instant;
+
+    /// CPU 占用上限测试中每次搜索的运行时间（毫秒）
+    const CPU_LIMIT_TEST_MS: u64 = 400;
+
+    /**
+     * 会话：每个会话有自己的取消标志和停止原因，按标识停止一个会话不影响其它会话；
+     * 同一标识不能同时运行两次，会话结束后注销，无效的标识被拒绝；拆分密钥搜索可以单独暂停，测速等会话不能暂停
+     */
+    #[test]
+    fn sessions_cases() -> Result<(), String> {
+        let _sessions = lock_sessions();
+        let first = register("selftest_session_a", SessionKind::Generation)?;
+        let second = register("selftest_session_b", SessionKind::Generation)?;
+        if register("selftest_session_a", SessionKind::Generation).is_ok() {
+            return Err("同一标识的会话被登记了两次".to_string());
+        }
+        
+        request_stop(Some("selftest_session_a"), StopReason::Tray, Some("托盘".to_string()))?;
+        request_stop(Some("selftest_session_a"), StopReason::UserUi, None)?;
+        if !first.cancel_token().is_cancelled() || second.cancel_token().is_cancelled() {
+            return Err("停止一个会话影响了另一个会话".to_string());
+        }
+        let stopping: Vec<(String, bool)> = list()
+            .into_iter()
+            .filter(|summary| summary.session_id.starts_with("selftest_session_"))
+            .map(|summary| (summary.session_id, summary.stopping))
+            .collect();
+        if stopping != [("selftest_session_a".to_string(), true), ("selftest_session_b".to_string(), false)] {
+            return Err(format!("会话列表不正确: {:?}", stopping));
+        }
+        let stop = first.take_stop();
+        if stop.reason != StopReason::Tray || stop.detail.as_deref() != Some("托盘") || second.take_stop().reason != StopReason::UserUi {
+            return Err(format!("会话的停止原因不正确: {:?}", stop));
+        }
+        
+        drop(first);
+        if request_stop(Some("selftest_session_a"), StopReason::UserUi, None).is_ok() {
+            return Err("已结束的会话仍然可以停止".to_string());
+        }
+        let again = register("selftest_session_a", SessionKind::Benchmark)?;
+        if again.cancel_token().is_cancelled() {
+            return Err("重新登记的会话继承了之前的取消标志".to_string());
+        }
+        if update_pause(Some("selftest_session_a"), PauseRequest::PauseByUser).is_ok() {
+            return Err("测速会话可以暂停".to_string());
+        }
+        let split = register("selftest_session_split", SessionKind::SplitKey)?;
+        update_pause(Some("selftest_session_split"), PauseRequest::PauseByUser)?;
+        if pause_state("selftest_session_split") == PauseState::Running || pause_state("selftest_session_b") != PauseState::Running {
+            return Err("拆分密钥搜索没有被单独暂停".to_string());
+        }
+        drop((again, second, split));
+        
+        let long = "a".repeat(129);
+        for invalid in ["", "../wallets", "a b", "会话", long.as_str()] {
+            if validate_id(invalid).is_ok() {
+                return Err(format!("无效的会话标识 {:?} 没有被拒绝", invalid));
+            }
+        }
+        validate_id("dead_20240101_000000.000Z-2")?;
+        Ok(())
+    }
+
+    /**
+     * 退出时等待会话结束：停止请求到达后会话写完当前的结果才注销，wait_until_idle 在注销时返回；
+     * 会话没有结束时按超时返回 false（其它会话正在运行时只检查超时）
+     */
+    #[test]
+    fn exit_drain() -> Result<(), String> {
+        let _sessions = lock_sessions();
+        let others = !list().is_empty();
+        let session = register("selftest_exit", SessionKind::Generation)?;
+        let started = Instant::now();
+        if wait_until_idle(Duration::from_millis(50)) || started.elapsed() < Duration::from_millis(50) {
+            return Err("会话还在运行时 wait_until_idle 没有等到超时".to_string());
+        }
+        if others {
+            drop(session);
+            return Ok(());
+        }
+        
+        let flushed = AtomicBool::new(false);
+        let (idle, stop) = std::thread::scope(|scope| {
+            let worker = scope.spawn(|| {
+                while !session.cancel_flag().load(Ordering::SeqCst) {
+                    std::thread::sleep(Duration::from_millis(5));
+                }
+                // 模拟写完最后一行和会话状态之后才注销
+                std::thread::sleep(Duration::from_millis(100));
+                flushed.store(true, Ordering::SeqCst);
+                let stop = session.take_stop();
+                drop(session);
+                stop
+            });
+            let requested = request_stop(Some("selftest_exit"), StopReason::AppExit, None);
+            let idle = requested.map(|_| wait_until_idle(Duration::from_secs(5)));
+            (idle, worker.join().map_err(|_| "模拟的会话线程异常退出".to_string()))
+        });
+        let (idle, stop) = (idle?, stop?);
+        if !idle || !flushed.load(Ordering::SeqCst) || stop.reason != StopReason::AppExit {
+            return Err(format!("退出时等待的结果为 {}，会话{}写完，停止原因为 {:?}", idle, if flushed.load(Ordering::SeqCst) { "已" } else { "没有" }, stop.reason));
+        }
+        Ok(())
+    }
+
+    /**
+     * CPU 占用上限：限制为 25% 时的尝试次数约为不限制时的四分之一，运行中调高后速度随之回升；越界的上限和不存在的会话
+     */
+    #[test]
+    fn cpu_limit_cases() -> Result<(), String> {
+        let _sessions = lock_sessions();
+        let limits = GenerationLimits { max_duration_ms: Some(CPU_LIMIT_TEST_MS), ..GenerationLimits::default() };
+        let config = |cpu_limit: CpuLimit| SearchConfig {
+            rng_mode: RngMode::DeterministicTest,
+            test_seed: Some(15),
+            workers: Some(1),
+            limits: limits.clone(),
+            cpu_limit,
+            ..SearchConfig::new("f".repeat(40))
+        };
+        let attempts = |config: &SearchConfig| crate::engine::run(config, &CancellationToken::new(), |_| {}).map(|report| report.attempts);
+        let unlimited = attempts(&config(CpuLimit::default()))?.max(1);
+        let limited = attempts(&config(CpuLimit::new(25)?))?;
+        let ratio = limited as f64 / unlimited as f64;
+        if !(0.1..=0.45).contains(&ratio) {
+            return Err(format!("限制为 25% 时尝试 {} 次，不限制时 {} 次（比例 {:.2}）", limited, unlimited, ratio));
+        }
+        
+        // 运行到一半时从 5% 调到 100%
+        let raised_limit = CpuLimit::new(5)?;
+        let raised = std::thread::scope(|scope| {
+            let limit = raised_limit.clone();
+            scope.spawn(move || {
+                std::thread::sleep(Duration::from_millis(CPU_LIMIT_TEST_MS / 2));
+                limit.set(100)
+            });
+            attempts(&config(raised_limit.clone()))
+        })?;
+        if raised_limit.percent() != 100 || raised <= limited {
+            return Err(format!("运行中调高上限后尝试 {} 次，限制为 25% 时 {} 次", raised, limited));
+        }
+        
+        if CpuLimit::new(0).is_ok() || CpuLimit::new(101).is_ok() || raised_limit.set(0).is_ok() || raised_limit.percent() != 100 {
+            return Err("越界的 CPU 占用上限没有被拒绝".to_string());
+        }
+        if (GenerationParams { pattern: "8".to_string(), cpu_limit_percent: Some(0), ..GenerationParams::default() }).validate().is_ok() {
+            return Err("cpu_limit_percent: 0 的参数通过了检查".to_string());
+        }
+        
+        let session = register("selftest_cpu_limit", SessionKind::Generation)?;
+        let benchmark = register("selftest_cpu_limit_benchmark", SessionKind::Benchmark)?;
+        set_cpu_limit(Some("selftest_cpu_limit"), 30)?;
+        let listed = list().into_iter().find(|summary| summary.session_id == "selftest_cpu_limit").map(|summary| summary.cpu_limit_percent);
+        if session.cpu_limit().percent() != 30 || listed != Some(30) {
+            return Err(format!("会话的 CPU 占用上限为 {}（列表中为 {:?}），应为 30", session.cpu_limit().percent(), listed));
+        }
+        if set_cpu_limit(Some("selftest_cpu_limit_missing"), 50).is_ok() || set_cpu_limit(Some("selftest_cpu_limit_benchmark"), 50).is_ok() {
+            return Err("不存在的会话或测速会话的 CPU 占用上限调整成功".to_string());
+        }
+        if set_cpu_limit(Some("selftest_cpu_limit"), 101).is_ok() || benchmark.cpu_limit().percent() != 100 {
+            return Err("越界的上限或测速会话的上限被修改".to_string());
+        }
+        Ok(())
+    }
+}
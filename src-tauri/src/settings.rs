@@ -0,0 +1,197 @@
+use crate::notifications;
+use crate::saved_wallets::OutputFormat;
+use crate::timer::ProgressThrottle;
+use crate::timestamps;
+use crate::worker;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// 应用设置的文件名（位于用户配置的数据目录中，按配置隔离）
+pub const SETTINGS_FILE: &str = "settings.json";
+
+/// 最近一次因文件损坏恢复为默认设置的说明（成功保存设置后清除）
+static RESET_WARNING: Mutex<Option<String>> = Mutex::new(None);
+
+/// 应用设置：调用 generate_fancy_wallet 时没有传入的参数使用这里的值（都为空时使用各参数自己的默认值）
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct AppSettings {
+    /// 默认保存路径（为空时使用用户配置的默认目录）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_save_path: Option<String>,
+    /// 默认工作线程数（为空时使用全部 CPU 核心）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workers: Option<u32>,
+    /// 默认的进度事件最短间隔（毫秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress_interval_ms: Option<u64>,
+    /// 默认的结果文件格式
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_format: Option<OutputFormat>,
+    /// 找到匹配时是否发送系统通知
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notify_on_match: Option<bool>,
+    /// 每找到多少个匹配通知一次
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notify_every: Option<u64>,
+}
+
+impl AppSettings {
+    /**
+     * 校验设置的取值（保存路径由调用方按输出根目录的规则校验）
+     */
+    pub fn validate(&self) -> Result<(), String> {
+        worker::validate(self.workers)?;
+        ProgressThrottle::new(self.progress_interval_ms)?;
+        notifications::validate(self.notify_on_match, self.notify_every)
+    }
+}
+
+/// 读取的设置（get_settings 的返回值）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SettingsInfo {
+    /// 当前的设置
+    pub settings: AppSettings,
+    /// 设置文件
+    pub path: PathBuf,
+    /// 设置文件损坏、已恢复为默认设置时的说明
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+fn settings_path(profile_dir: &Path) -> PathBuf {
+    profile_dir.join(SETTINGS_FILE)
+}
+
+/**
+ * 读取设置；文件损坏时把它改名为 settings.json.corrupt-<时间> 保留，并用默认设置重新生成
+ *
+ * @param profile_dir - 用户配置的数据目录
+ * @param writable - 是否可以重新生成设置文件（只读模式下只在内存中使用默认设置）
+ */
+pub fn load(profile_dir: &Path, writable: bool) -> Result<SettingsInfo, String> {
+    let path = settings_path(profile_dir);
+    let warning = || RESET_WARNING.lock().ok().and_then(|warning| warning.clone());
+    if !path.exists() {
+        return Ok(SettingsInfo { settings: AppSettings::default(), path, warning: warning() });
+    }
+    let data = fs::read_to_string(&path).map_err(|e| format!("无法读取应用设置: {}", e))?;
+    let error = match serde_json::from_str::<AppSettings>(&data) {
+        Ok(settings) => match settings.validate() {
+            Ok(()) => return Ok(SettingsInfo { settings, path, warning: warning() }),
+            Err(e) => e,
+        },
+        Err(e) => e.to_string(),
+    };
+    let mut message = format!("应用设置文件已损坏（{}），已恢复为默认设置", error);
+    if writable {
+        let backup = path.with_extension(format!("json.corrupt-{}", timestamps::filename_stamp(chrono::Utc::now())));
+        let kept = fs::rename(&path, &backup).map_err(|e| format!("无法保留原来的文件: {}", e));
+        message = match kept.and_then(|_| save(profile_dir, &AppSettings::default())) {
+            Ok(()) => format!("{}；原来的文件保留为 {}", message, backup.display()),
+            Err(e) => format!("{}；{}", message, e),
+        };
+    }
+    if let Ok(mut reset) = RESET_WARNING.lock() {
+        *reset = Some(message.clone());
+    }
+    Ok(SettingsInfo { settings: AppSettings::default(), path, warning: Some(message) })
+}
+
+/**
+ * 保存设置（先写临时文件再重命名）
+ *
+ * @param profile_dir - 用户配置的数据目录
+ * @param settings - 设置
+ */
+pub fn save(profile_dir: &Path, settings: &AppSettings) -> Result<(), String> {
+    fs::create_dir_all(profile_dir).map_err(|e| format!("无法创建配置目录: {}", e))?;
+    let json = serde_json::to_string_pretty(settings).map_err(|e| format!("无法序列化应用设置: {}", e))?;
+    let path = settings_path(profile_dir);
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, json).map_err(|e| format!("无法写入应用设置: {}", e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("无法写入应用设置: {}", e))
+}
+
+/**
+ * 按部分更新修改设置：只修改 partial 中出现的字段，字段为 null 时清除该项（恢复为参数自己的默认值）
+ *
+ * @param current - 当前的设置
+ * @param partial - 部分更新（JSON 对象，字段与 AppSettings 相同）
+ * @returns 修改后的设置（未知字段或无效的取值返回错误）
+ */
+pub fn apply_update(current: &AppSettings, partial: &serde_json::Value) -> Result<AppSettings, String> {
+    let changes = partial.as_object().ok_or("设置的更新必须是一个 JSON 对象")?;
+    let mut merged = match serde_json::to_value(current) {
+        Ok(serde_json::Value::Object(map)) => map,
+        _ => return Err("无法序列化应用设置".to_string()),
+    };
+    for (key, value) in changes {
+        match value {
+            serde_json::Value::Null => merged.remove(key),
+            value => merged.insert(key.clone(), value.clone()),
+        };
+    }
+    let settings: AppSettings = serde_json::from_value(serde_json::Value::Object(merged)).map_err(|e| format!("设置无效: {}", e))?;
+    settings.validate()?;
+    Ok(settings)
+}
+
+/**
+ * 成功保存设置后清除损坏恢复的说明
+ */
+pub fn clear_reset_warning() {
+    if let Ok(mut reset) = RESET_WARNING.lock() {
+        *reset = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::with_data_dir;
+
+    /**
+     * 应用设置：部分更新只修改出现的字段，null 清除该项，无效的更新被拒绝；损坏的设置文件保留备份并恢复为默认设置
+     */
+    #[test]
+    fn app_settings() -> Result<(), String> {
+        with_data_dir("app-settings", |dir| {
+            let loaded = load(dir, true)?;
+            if loaded.settings != AppSettings::default() || loaded.warning.is_some() {
+                return Err(format!("没有设置文件时不是默认设置: {:?}", loaded));
+            }
+            let updated = apply_update(&loaded.settings, &serde_json::json!({ "workers": 4, "output_format": "jsonl", "notify_on_match": true }))?;
+            let updated = apply_update(&updated, &serde_json::json!({ "progress_interval_ms": 1000, "notify_on_match": null }))?;
+            let expected = AppSettings { workers: Some(4), progress_interval_ms: Some(1000), output_format: Some(OutputFormat::Jsonl), ..AppSettings::default() };
+            if updated != expected {
+                return Err(format!("部分更新的结果不正确: {:?}", updated));
+            }
+            save(dir, &updated)?;
+            if load(dir, true)?.settings != expected {
+                return Err("保存的设置与读取的不一致".to_string());
+            }
+            for partial in [serde_json::json!({ "workers": 0 }), serde_json::json!({ "thread_count": 4 }), serde_json::json!({ "notify_every": 3 }), serde_json::json!([])] {
+                if apply_update(&updated, &partial).is_ok() {
+                    return Err(format!("无效的更新没有被拒绝: {}", partial));
+                }
+            }
+            
+            // 损坏的设置文件
+            std::fs::write(dir.join(SETTINGS_FILE), "{\"workers\": ").map_err(|e| e.to_string())?;
+            let reset = load(dir, true);
+            clear_reset_warning();
+            let reset = reset?;
+            let backups = std::fs::read_dir(dir).map_err(|e| e.to_string())?.flatten().filter(|entry| entry.file_name().to_string_lossy().contains(".corrupt-")).count();
+            if reset.settings != AppSettings::default() || reset.warning.is_none() || backups != 1 {
+                return Err(format!("损坏的设置文件没有恢复为默认设置: {:?}，备份 {} 个", reset, backups));
+            }
+            if load(dir, true)?.settings != AppSettings::default() {
+                return Err("没有用默认设置重新生成设置文件".to_string());
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,126 @@
+use crate::chain::base58_encode;
+#[cfg(feature = "gui")]
+use crate::chain::base58_decode;
+use crate::hexutil::Address;
+use crate::keccak::keccak256;
+use ed25519_dalek::SigningKey;
+#[cfg(feature = "gui")]
+use std::fs::{self, OpenOptions};
+#[cfg(feature = "gui")]
+use std::io::Write;
+#[cfg(feature = "gui")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "gui")]
+use zeroize::Zeroizing;
+
+/// Solana 地址的最大字符数（32 字节公钥的 Base58 编码）
+pub(crate) const MAX_ADDRESS_LENGTH: usize = 44;
+
+/// Solana 地址的最小字符数（开头的零字节编码为 1，实际地址几乎都是 43 或 44 个字符）
+#[cfg(feature = "gui")]
+const MIN_ADDRESS_LENGTH: usize = 32;
+
+/// 密钥对文件所在的子目录（位于会话输出目录下）
+#[cfg(feature = "gui")]
+pub const KEYPAIR_DIR: &str = "keypairs";
+
+/**
+ * 私钥（ed25519 种子）对应的 32 字节公钥
+ *
+ * 生成循环中的候选私钥与其它链相同（随机 32 字节），直接作为 ed25519 的种子使用。
+ *
+ * @param seed - 私钥
+ */
+pub fn public_key(seed: &[u8; 32]) -> [u8; 32] {
+    SigningKey::from_bytes(seed).verifying_key().to_bytes()
+}
+
+/**
+ * 公钥的 Base58 编码（即 Solana 地址，没有版本字节和校验和）
+ */
+pub fn encode(public_key: &[u8; 32]) -> String {
+    base58_encode(public_key)
+}
+
+/**
+ * Solana 地址的 20 字节标识：公钥 Keccak-256 的后 20 字节
+ *
+ * 结果中的 address 字段只能保存 20 字节，用于指纹、去重和校验；地址本身无法由它还原，另外保存在 chain_address 中。
+ */
+pub fn address_id(public_key: &[u8; 32]) -> Address {
+    let hash = keccak256(public_key);
+    let mut bytes = [0u8; 20];
+    bytes.copy_from_slice(&hash[12..]);
+    Address::from(bytes)
+}
+
+/**
+ * 解析 Solana 地址，返回 32 字节公钥
+ */
+#[cfg(feature = "gui")]
+pub fn parse_address(text: &str) -> Result<[u8; 32], String> {
+    let text = text.trim();
+    if !(MIN_ADDRESS_LENGTH..=MAX_ADDRESS_LENGTH).contains(&text.len()) {
+        return Err(format!("Solana 地址应为 {} 到 {} 个字符: {}", MIN_ADDRESS_LENGTH, MAX_ADDRESS_LENGTH, text));
+    }
+    let bytes = base58_decode(text).ok_or_else(|| format!("Solana 地址包含无效字符: {}", text))?;
+    bytes.try_into().map_err(|_| format!("不是有效的 Solana 地址（公钥应为 32 字节）: {}", text))
+}
+
+/**
+ * solana-keygen 格式的密钥对：私钥和公钥共 64 字节，写成 JSON 数组（含有私钥，离开作用域时清零）
+ *
+ * @param seed - 私钥
+ */
+#[cfg(feature = "gui")]
+pub fn keypair_json(seed: &[u8; 32]) -> Zeroizing<String> {
+    let keypair = Zeroizing::new(SigningKey::from_bytes(seed).to_keypair_bytes());
+    let bytes: Zeroizing<Vec<String>> = Zeroizing::new(keypair.iter().map(u8::to_string).collect());
+    Zeroizing::new(format!("[{}]", bytes.join(",")))
+}
+
+/**
+ * 解析 solana-keygen 格式的密钥对，检查公钥与私钥一致
+ *
+ * @returns 私钥
+ */
+#[cfg(all(test, feature = "gui"))]
+pub fn parse_keypair(json: &str) -> Result<Zeroizing<[u8; 32]>, String> {
+    let bytes: Zeroizing<Vec<u8>> = Zeroizing::new(serde_json::from_str(json).map_err(|_| "密钥对不是字节数组".to_string())?);
+    if bytes.len() != 64 {
+        return Err(format!("密钥对应为 64 字节，实际为 {} 字节", bytes.len()));
+    }
+    let mut seed = Zeroizing::new([0u8; 32]);
+    seed.copy_from_slice(&bytes[..32]);
+    if public_key(&seed)[..] != bytes[32..] {
+        return Err("密钥对中的公钥与私钥不一致".to_string());
+    }
+    Ok(seed)
+}
+
+/**
+ * 写入密钥对文件（<目录>/<地址>.json，可以直接作为 solana 命令行的 --keypair 使用）
+ *
+ * 文件已存在时不覆盖；Unix 上只有所有者可以读写。
+ *
+ * @param dir - 密钥对目录
+ * @param address - Solana 地址
+ * @param json - keypair_json 的结果
+ * @returns 文件路径和写入的字节数
+ */
+#[cfg(feature = "gui")]
+pub fn write_keypair_file(dir: &Path, address: &str, json: &str) -> Result<(PathBuf, u64), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("无法创建密钥对目录: {}", e))?;
+    let path = dir.join(format!("{}.json", address));
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path).map_err(|e| format!("无法创建密钥对文件: {}", e))?;
+    file.write_all(json.as_bytes()).map_err(|e| format!("无法写入密钥对文件: {}", e))?;
+    file.sync_all().map_err(|e| format!("无法写入密钥对文件: {}", e))?;
+    Ok((path, json.len() as u64))
+}
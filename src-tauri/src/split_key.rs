@@ -0,0 +1,279 @@
+use crate::address::public_key_to_address;
+use crate::formatting::{format_count, NumberLocale};
+use crate::hexutil::{Address, PrivKeyHex, PubKeyUncompressed};
+use crate::pattern::ParsedPattern;
+use secp256k1::{PublicKey, Scalar, Secp256k1, SecretKey, Verification};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// 可接受的最大期望尝试次数，超过后认为请求不现实
+const MAX_EXPECTED_ATTEMPTS: f64 = 1e12;
+
+/// 拆分密钥搜索请求（由请求方生成并发送给搜索方）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SplitKeyRequest {
+    /// 请求编号
+    pub request_id: String,
+    /// 请求方的公钥（十六进制，压缩或未压缩格式）
+    pub public_key: String,
+    /// 靓号模式
+    pub pattern: String,
+    /// 过期时间（RFC 3339）
+    pub expires_at: String,
+}
+
+/// 拆分密钥搜索结果（发回给请求方，不包含搜索方的任何私密信息）
+///
+/// 请求方将自己的私钥与 tweak 相加（模曲线阶）即得到匹配地址的私钥。
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SplitKeyResult {
+    /// 请求编号
+    pub request_id: String,
+    /// 请求方的公钥
+    pub public_key: String,
+    /// 靓号模式
+    pub pattern: String,
+    /// 偏移量 k（十六进制）
+    pub tweak: String,
+    /// 匹配的地址（序列化为带 0x 前缀的 checksum 格式）
+    pub address: Address,
+    /// 尝试次数
+    pub attempts: u64,
+    /// 耗时（毫秒）
+    pub duration: u64,
+}
+
+/// 校验通过的拆分密钥请求
+#[derive(Debug, Clone)]
+pub struct ValidatedRequest {
+    /// 原始请求
+    pub request: SplitKeyRequest,
+    /// 解析后的请求方公钥
+    pub public_key: PublicKey,
+    /// 解析后的靓号模式
+    pub pattern: ParsedPattern,
+    /// 期望尝试次数
+    pub expected_attempts: f64,
+}
+
+/**
+ * 读取并校验拆分密钥请求文件
+ * 
+ * @param path - 请求文件路径
+ * @returns 校验通过的请求
+ */
+pub fn load_request(path: &Path) -> Result<ValidatedRequest, String> {
+    let data = std::fs::read_to_string(path).map_err(|e| format!("无法读取请求文件: {}", e))?;
+    let request: SplitKeyRequest =
+        serde_json::from_str(&data).map_err(|e| format!("请求文件格式错误: {}", e))?;
+    validate_request(request, chrono::Utc::now())
+}
+
+/**
+ * 校验拆分密钥请求：公钥、过期时间、模式和难度
+ * 
+ * @param request - 请求内容
+ * @param now - 当前时间
+ * @returns 校验通过的请求
+ */
+pub fn validate_request(
+    request: SplitKeyRequest,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<ValidatedRequest, String> {
+    if request.request_id.trim().is_empty() {
+        return Err("请求编号不能为空".to_string());
+    }
+    
+    let public_key = PubKeyUncompressed::parse(&request.public_key)
+        .map_err(|e| format!("请求方{}", e))?
+        .public_key();
+    
+    let expires_at = chrono::DateTime::parse_from_rfc3339(&request.expires_at)
+        .map_err(|_| format!("无法解析过期时间: {}", request.expires_at))?;
+    if expires_at < now {
+        return Err(format!("请求已于 {} 过期", request.expires_at));
+    }
+    
+    let pattern = ParsedPattern::new(&request.pattern);
+    pattern.validate()?;
+    let expected_attempts = pattern.expected_attempts();
+    if expected_attempts > MAX_EXPECTED_ATTEMPTS {
+        return Err(format!(
+            "靓号模式难度过高（期望尝试次数约 {}）",
+            format_count(expected_attempts, NumberLocale::SimplifiedChinese)
+        ));
+    }
+    
+    Ok(ValidatedRequest { request, public_key, pattern, expected_attempts })
+}
+
+/**
+ * 计算请求方公钥加上偏移量后的地址：address(A + k·G)
+ * 
+ * @param secp - secp256k1 上下文
+ * @param public_key - 请求方公钥 A
+ * @param tweak - 偏移量 k
+ * @returns 小写的以太坊地址（不含0x前缀）
+ */
+pub fn tweaked_address<C: Verification>(
+    secp: &Secp256k1<C>,
+    public_key: &PublicKey,
+    tweak: &SecretKey,
+) -> Result<String, String> {
+    let combined = public_key
+        .add_exp_tweak(secp, &Scalar::from(*tweak))
+        .map_err(|_| "偏移量与请求方公钥组合失败".to_string())?;
+    Ok(public_key_to_address(&combined))
+}
+
+/**
+ * 校验搜索结果：重新计算 A + k·G 的地址并与记录的地址比较
+ * 
+ * @param result - 搜索结果
+ * @returns 是否一致
+ */
+pub fn verify_result(result: &SplitKeyResult) -> Result<(), String> {
+    let public_key = PubKeyUncompressed::parse(&result.public_key)
+        .map_err(|e| format!("结果中的{}", e))?
+        .public_key();
+    let tweak = PrivKeyHex::parse_named("偏移量", &result.tweak)
+        .map_err(|e| format!("结果中的{}", e))?
+        .secret_key();
+    
+    let secp = Secp256k1::verification_only();
+    let address = Address::parse(&tweaked_address(&secp, &public_key, &tweak)?)?;
+    if address != result.address {
+        return Err(format!("结果校验失败：偏移量对应的地址为 {}，而不是 {}", address, result.address));
+    }
+    Ok(())
+}
+
+/**
+ * 将搜索结果写入文件（写入前先校验）
+ * 
+ * @param result - 搜索结果
+ * @param path - 输出路径
+ */
+pub fn write_result(result: &SplitKeyResult, path: &Path) -> Result<(), String> {
+    verify_result(result)?;
+    let json = serde_json::to_string_pretty(result).map_err(|e| format!("无法序列化结果: {}", e))?;
+    std::fs::write(path, json).map_err(|e| format!("无法写入结果文件: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::to_checksum_address;
+    use crate::self_test::KNOWN_KEYS;
+    use crate::test_support::with_data_dir;
+    use chrono::{Duration, Utc};
+
+    /// 测试请求方的公钥（KNOWN_KEYS 中第一个私钥对应的压缩公钥）
+    fn requester_key() -> Result<(SecretKey, String), String> {
+        let secret = PrivKeyHex::parse(KNOWN_KEYS[0].0)?.secret_key();
+        let public_key = PublicKey::from_secret_key(&Secp256k1::new(), &secret);
+        Ok((secret, hex::encode(public_key.serialize())))
+    }
+
+    fn request(public_key: &str, pattern: &str, expires_in: Duration) -> SplitKeyRequest {
+        SplitKeyRequest {
+            request_id: "req-test".to_string(),
+            public_key: public_key.to_string(),
+            pattern: pattern.to_string(),
+            expires_at: (Utc::now() + expires_in).to_rfc3339(),
+        }
+    }
+
+    /**
+     * 请求校验：空的请求编号、无效的公钥、过期的请求、无效的模式和难度过高的模式被拒绝，格式错误的请求文件不能读取
+     */
+    #[test]
+    fn split_key_request_validation() -> Result<(), String> {
+        let (_, public_key) = requester_key()?;
+        let valid = validate_request(request(&public_key, "88", Duration::hours(1)), Utc::now())?;
+        if valid.expected_attempts != 65536.0 || valid.request.request_id != "req-test" {
+            return Err(format!("有效请求的期望尝试次数为 {}", valid.expected_attempts));
+        }
+        let rejected = [
+            ("空的请求编号", SplitKeyRequest { request_id: " ".to_string(), ..request(&public_key, "88", Duration::hours(1)) }),
+            ("无效的公钥", request(&public_key[..64], "88", Duration::hours(1))),
+            ("不在曲线上的公钥", request(&format!("02{}", "f".repeat(64)), "88", Duration::hours(1))),
+            ("过期的请求", request(&public_key, "88", Duration::hours(-1))),
+            ("无法解析的过期时间", SplitKeyRequest { expires_at: "tomorrow".to_string(), ..request(&public_key, "88", Duration::hours(1)) }),
+            ("无效的模式", request(&public_key, "xyz", Duration::hours(1))),
+            ("难度过高的模式", request(&public_key, "8888888", Duration::hours(1))),
+        ];
+        for (case, rejected) in rejected {
+            if validate_request(rejected, Utc::now()).is_ok() {
+                return Err(format!("{}没有被拒绝", case));
+            }
+        }
+        with_data_dir("split-key-request", |dir| {
+            let path = dir.join("request.json");
+            std::fs::write(&path, "{\"request_id\":\"req-test\"}").map_err(|e| e.to_string())?;
+            if load_request(&path).is_ok() || load_request(&dir.join("missing.json")).is_ok() {
+                return Err("格式错误或不存在的请求文件没有被拒绝".to_string());
+            }
+            let json = serde_json::to_string(&request(&public_key, "88", Duration::hours(1))).map_err(|e| e.to_string())?;
+            std::fs::write(&path, json).map_err(|e| e.to_string())?;
+            load_request(&path).map(|_| ())
+        })
+    }
+
+    /**
+     * 完整流程：搜索偏移量 k 使 A + k·G 的地址符合模式，结果文件不含请求方或搜索方的私钥，
+     * 请求方把私钥与 k 相加后得到的私钥对应结果中的地址；被改动的结果校验失败且不会写入
+     */
+    #[test]
+    fn split_key_round_trip() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        let (secret, public_key) = requester_key()?;
+        let validated = validate_request(request(&public_key, "8", Duration::hours(1)), Utc::now())?;
+        let (tweak, address) = (1u64..100_000)
+            .find_map(|counter| {
+                let mut bytes = [0u8; 32];
+                bytes[24..].copy_from_slice(&counter.to_be_bytes());
+                let tweak = SecretKey::from_slice(&bytes).ok()?;
+                let address = tweaked_address(&secp, &validated.public_key, &tweak).ok()?;
+                if !validated.pattern.matches(&to_checksum_address(&address).ok()?) {
+                    return None;
+                }
+                Some((tweak, Address::parse(&address).ok()?))
+            })
+            .ok_or("没有找到符合模式的偏移量")?;
+        let result = SplitKeyResult {
+            request_id: validated.request.request_id.clone(),
+            public_key: public_key.clone(),
+            pattern: validated.request.pattern.clone(),
+            tweak: hex::encode(tweak.secret_bytes()),
+            address,
+            attempts: 1,
+            duration: 0,
+        };
+
+        with_data_dir("split-key-result", |dir| {
+            let path = dir.join("result.json");
+            write_result(&result, &path)?;
+            let written = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            if written.contains(KNOWN_KEYS[0].0) || written.contains(&hex::encode(secret.secret_bytes())) {
+                return Err("结果文件中出现了请求方的私钥".to_string());
+            }
+            let read: SplitKeyResult = serde_json::from_str(&written).map_err(|e| e.to_string())?;
+            verify_result(&read)?;
+
+            let tweak = PrivKeyHex::parse_named("偏移量", &read.tweak)?.secret_key();
+            let combined = secret.add_tweak(&Scalar::from(tweak)).map_err(|e| e.to_string())?;
+            let combined_address = public_key_to_address(&PublicKey::from_secret_key(&secp, &combined));
+            if Address::parse(&combined_address)? != read.address || !validated.pattern.matches(&to_checksum_address(&combined_address).map_err(|e| e.to_string())?) {
+                return Err(format!("组合私钥的地址为 {}，结果中的地址为 {}", combined_address, read.address));
+            }
+
+            let tampered = SplitKeyResult { tweak: hex::encode(combined.secret_bytes()), ..read };
+            let tampered_path = dir.join("tampered.json");
+            if verify_result(&tampered).is_ok() || write_result(&tampered, &tampered_path).is_ok() || tampered_path.exists() {
+                return Err("被改动的结果通过了校验".to_string());
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,155 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// 默认的开始生成冷却时间（毫秒）
+pub const DEFAULT_START_COOLDOWN_MS: u64 = 500;
+
+/// 冷却时间的最大值（毫秒）
+const MAX_START_COOLDOWN_MS: u64 = 60_000;
+
+/// 当前的冷却时间（毫秒，0 表示不限制）
+static COOLDOWN_MS: AtomicU64 = AtomicU64::new(DEFAULT_START_COOLDOWN_MS);
+
+/// 全应用共用的开始生成频率限制（不区分会话）
+static LIMITER: Mutex<StartLimiter> = Mutex::new(StartLimiter::new());
+
+/// 频率限制使用的单调时钟起点
+static EPOCH: OnceLock<Instant> = OnceLock::new();
+
+/// 开始生成过于频繁时的拒绝信息
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct TooManyRequests {
+    /// 还需要等待的时间（毫秒）
+    pub retry_after_ms: u64,
+    /// 当前的冷却时间（毫秒）
+    pub cooldown_ms: u64,
+}
+
+impl fmt::Display for TooManyRequests {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "开始生成过于频繁（too_many_requests），请在 {} 毫秒后重试（两次开始之间至少间隔 {} 毫秒）",
+            self.retry_after_ms, self.cooldown_ms
+        )
+    }
+}
+
+/// 开始生成的频率限制：两次被接受的开始之间至少间隔冷却时间，被拒绝的请求不会延长等待
+#[derive(Debug, Clone, Default)]
+pub struct StartLimiter {
+    /// 上一次被接受的时刻（从时钟起点开始经过的时间）
+    last_accepted: Option<Duration>,
+}
+
+impl StartLimiter {
+    /**
+     * 创建还没有接受过请求的限制
+     */
+    pub const fn new() -> Self {
+        StartLimiter { last_accepted: None }
+    }
+
+    /**
+     * 尝试接受一次开始生成
+     *
+     * @param now - 当前时刻（从时钟起点开始经过的时间，测试中由模拟时钟提供）
+     * @param cooldown - 冷却时间
+     * @returns 被拒绝时返回还需要等待的时间
+     */
+    pub fn try_accept(&mut self, now: Duration, cooldown: Duration) -> Result<(), TooManyRequests> {
+        if let Some(last) = self.last_accepted {
+            let waited = now.saturating_sub(last);
+            if waited < cooldown {
+                return Err(TooManyRequests {
+                    retry_after_ms: (cooldown - waited).as_millis().max(1) as u64,
+                    cooldown_ms: cooldown.as_millis() as u64,
+                });
+            }
+        }
+        self.last_accepted = Some(now);
+        Ok(())
+    }
+}
+
+/**
+ * 当前的冷却时间（毫秒）
+ */
+pub fn cooldown_ms() -> u64 {
+    COOLDOWN_MS.load(Ordering::SeqCst)
+}
+
+/**
+ * 设置冷却时间，对之后的开始请求生效
+ *
+ * @param cooldown_ms - 冷却时间（毫秒，0 表示不限制）
+ */
+pub fn set_cooldown_ms(cooldown_ms: u64) -> Result<(), String> {
+    if cooldown_ms > MAX_START_COOLDOWN_MS {
+        return Err(format!("开始生成的冷却时间不能超过 {} 毫秒", MAX_START_COOLDOWN_MS));
+    }
+    COOLDOWN_MS.store(cooldown_ms, Ordering::SeqCst);
+    Ok(())
+}
+
+/**
+ * 按全应用的频率限制接受一次开始生成（只用于前端发起的开始；恢复暂停的任务、队列和空闲自动开始不经过这里）
+ *
+ * @returns 被拒绝时返回还需要等待的时间
+ */
+pub fn accept_start() -> Result<(), TooManyRequests> {
+    let now = EPOCH.get_or_init(Instant::now).elapsed();
+    let cooldown = Duration::from_millis(cooldown_ms());
+    match LIMITER.lock() {
+        Ok(mut limiter) => limiter.try_accept(now, cooldown),
+        // 锁中毒时不阻止开始
+        Err(_) => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::Clock;
+    use crate::test_support::SteppedClock;
+
+    /**
+     * 开始生成的冷却时间：用模拟时钟每 100 ms 连续开始一次，只有间隔满 500 ms 的请求被接受，
+     * 被拒绝的请求返回剩余等待时间且不会延长冷却，冷却时间为 0 时不限制
+     */
+    #[test]
+    fn start_cooldown() -> Result<(), String> {
+        let cooldown = Duration::from_millis(DEFAULT_START_COOLDOWN_MS);
+        let mut clock = SteppedClock { now: Duration::ZERO, step: Duration::from_millis(1), advance: Duration::from_millis(100) };
+        let mut limiter = StartLimiter::new();
+        let mut accepted = Vec::new();
+        for _ in 0..12 {
+            let now = clock.elapsed();
+            match limiter.try_accept(now, cooldown) {
+                Ok(()) => accepted.push(now.as_millis() as u64),
+                Err(throttled) => {
+                    let last = *accepted.last().ok_or("第一次开始被拒绝")?;
+                    let expected = DEFAULT_START_COOLDOWN_MS - (now.as_millis() as u64 - last);
+                    if throttled.retry_after_ms != expected || !throttled.to_string().starts_with("开始生成过于频繁") {
+                        return Err(format!("{} ms 时的拒绝信息为 {:?}，剩余等待应为 {} ms", now.as_millis(), throttled, expected));
+                    }
+                }
+            }
+        }
+        if accepted != [100, 600, 1100] {
+            return Err(format!("被接受的开始时刻为 {:?}，应为 [100, 600, 1100]", accepted));
+        }
+        
+        let mut unlimited = StartLimiter::new();
+        if (1..=5).any(|ms| unlimited.try_accept(Duration::from_millis(ms), Duration::ZERO).is_err()) {
+            return Err("冷却时间为 0 时仍然拒绝了开始".to_string());
+        }
+        if set_cooldown_ms(u64::MAX).is_ok() {
+            return Err("过长的冷却时间没有被拒绝".to_string());
+        }
+        Ok(())
+    }
+}
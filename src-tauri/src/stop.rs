@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+
+/// 生成停止的原因
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StopReason {
+    /// 界面上的停止按钮
+    UserUi,
+    /// 托盘菜单
+    Tray,
+    /// 紧急停止文件
+    Killswitch,
+    /// 计划任务
+    Schedule,
+    /// 达到最大匹配数
+    MaxMatches,
+    /// 达到最大尝试次数
+    MaxAttempts,
+    /// 达到最长运行时间
+    MaxDuration,
+    /// 保存失败时按策略停止
+    SaveErrorPolicy,
+    /// 应用退出
+    AppExit,
+    /// 全部工作线程异常退出（panic）
+    WorkerFailure,
+}
+
+/// 停止请求：原因以及可选的补充说明
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct StopRecord {
+    /// 停止原因
+    pub reason: StopReason,
+    /// 补充说明（例如触发的限制值）
+    pub detail: Option<String>,
+}
+
+#[cfg(all(test, feature = "gui"))]
+mod tests {
+    use super::*;
+    use crate::chain::Chain;
+    use crate::engine::GenerationLimits;
+    use crate::manifest::{self, SessionDetails, SessionManifest};
+    use crate::sessions::{self, SessionKind};
+    use crate::test_support::{lock_sessions, with_data_dir};
+    use std::time::Duration;
+
+    /// 每个停止原因在事件、清单和回放中的名称
+    const STOP_REASON_NAMES: &[(StopReason, &str)] = &[
+        (StopReason::UserUi, "user_ui"),
+        (StopReason::Tray, "tray"),
+        (StopReason::Killswitch, "killswitch"),
+        (StopReason::Schedule, "schedule"),
+        (StopReason::MaxMatches, "max_matches"),
+        (StopReason::MaxAttempts, "max_attempts"),
+        (StopReason::MaxDuration, "max_duration"),
+        (StopReason::SaveErrorPolicy, "save_error_policy"),
+        (StopReason::AppExit, "app_exit"),
+        (StopReason::WorkerFailure, "worker_failure"),
+    ];
+
+    /**
+     * 停止原因：各停止途径（界面按钮、托盘、紧急停止文件、计划任务、应用退出和各项限制）记录各自不同的原因和说明，
+     * 序列化名称固定，并原样记录在会话清单中
+     */
+    #[test]
+    fn stop_reasons() -> Result<(), String> {
+        for &(reason, name) in STOP_REASON_NAMES {
+            let json = serde_json::to_string(&reason).map_err(|e| e.to_string())?;
+            if json != format!("\"{}\"", name) || serde_json::from_str::<StopReason>(&json).ok() != Some(reason) {
+                return Err(format!("{:?} 序列化为 {}，应为 {}", reason, json, name));
+            }
+        }
+
+        let _sessions = lock_sessions();
+        let requested = [
+            (StopReason::UserUi, None),
+            (StopReason::Tray, Some("托盘菜单")),
+            (StopReason::Killswitch, Some("STOP 文件")),
+            (StopReason::Schedule, Some("22:00")),
+            (StopReason::AppExit, None),
+        ];
+        for (reason, detail) in requested {
+            let session_id = format!("test_stop_{}", serde_json::to_string(&reason).map_err(|e| e.to_string())?.trim_matches('"'));
+            let session = sessions::register(&session_id, SessionKind::Generation)?;
+            sessions::request_stop(Some(&session_id), reason, detail.map(str::to_string))?;
+            // 之后的停止请求不会覆盖第一个原因
+            sessions::request_stop(Some(&session_id), StopReason::UserUi, Some("之后的请求".to_string()))?;
+            let stop = session.take_stop();
+            if !session.cancel_token().is_cancelled() || stop != (StopRecord { reason, detail: detail.map(str::to_string) }) {
+                return Err(format!("{:?} 的停止请求记录为 {:?}", reason, stop));
+            }
+        }
+
+        let limits = GenerationLimits { max_attempts: Some(1000), max_matches: Some(3), max_duration_ms: Some(50), ..GenerationLimits::default() };
+        let reached = [
+            (limits.reached(10, 3, Duration::ZERO), StopReason::MaxMatches),
+            (limits.reached(1000, 0, Duration::ZERO), StopReason::MaxAttempts),
+            (limits.reached(10, 0, Duration::from_millis(50)), StopReason::MaxDuration),
+        ];
+        for (reached, expected) in reached {
+            if reached.as_ref().map(|(reason, _)| *reason) != Some(expected) {
+                return Err(format!("达到限制时的停止原因为 {:?}，应为 {:?}", reached, expected));
+            }
+        }
+        if limits.reached(999, 2, Duration::from_millis(49)).is_some() {
+            return Err("没有达到限制时就停止了".to_string());
+        }
+
+        with_data_dir("stop-reasons", |dir| {
+            let details = SessionDetails { template: None, chain: Chain::Ethereum, matcher: None, score_weights: None, collection: None, keccak_backend: None, entropy: None, clock_jumps: &[], worker_failures: &[] };
+            for &(reason, name) in STOP_REASON_NAMES {
+                let stop = StopRecord { reason, detail: Some(name.to_string()) };
+                let path = dir.join(format!("manifest_{}.json", name));
+                manifest::write_session_manifest(&path, "888", &[], &stop, &details)?;
+                let written: SessionManifest = serde_json::from_str(&std::fs::read_to_string(&path).map_err(|e| e.to_string())?).map_err(|e| e.to_string())?;
+                if written.stop != Some(stop) {
+                    return Err(format!("清单中记录的停止原因为 {:?}", written.stop));
+                }
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,214 @@
+use crate::confidence::{self, SUMMARY_MILESTONES};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::window::{ProgressBarState, ProgressBarStatus};
+use tauri::{AppHandle, Manager};
+
+/// 是否在任务栏图标上显示会话进度
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// 任务栏进度条的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskbarState {
+    /// 不显示
+    Hidden,
+    /// 只表示正在运行（没有可估计的目标）
+    Pulse,
+    /// 进度百分比（0–100）
+    Progress(u8),
+}
+
+/// 平台的任务栏进度接口
+pub trait TaskbarProgress {
+    /**
+     * 显示任务栏进度状态
+     */
+    fn show(&mut self, state: TaskbarState);
+}
+
+/// 不支持或关闭了任务栏进度时使用，不做任何事
+pub struct NoTaskbar;
+
+impl TaskbarProgress for NoTaskbar {
+    fn show(&mut self, _state: TaskbarState) {}
+}
+
+/// 通过应用窗口设置任务栏进度（Windows 任务栏、支持 libunity 的 Linux 桌面环境）
+pub struct WindowTaskbar {
+    app: AppHandle,
+}
+
+impl TaskbarProgress for WindowTaskbar {
+    fn show(&mut self, state: TaskbarState) {
+        let (status, progress) = match state {
+            TaskbarState::Hidden => (ProgressBarStatus::None, None),
+            TaskbarState::Pulse => (ProgressBarStatus::Indeterminate, None),
+            TaskbarState::Progress(percent) => (ProgressBarStatus::Normal, Some(percent as u64)),
+        };
+        // 不支持的平台上设置失败时忽略，不影响生成
+        for window in self.app.webview_windows().values() {
+            let _ = window.set_progress_bar(ProgressBarState { status: Some(status), progress });
+        }
+    }
+}
+
+/**
+ * 是否显示任务栏进度
+ */
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::SeqCst)
+}
+
+/**
+ * 设置是否显示任务栏进度，对之后开始的会话生效
+ */
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::SeqCst);
+}
+
+/**
+ * 由匹配概率和已尝试次数计算任务栏进度：以达到最高里程碑（99% 置信度）所需的尝试次数为 100%
+ *
+ * @param probability - 单次尝试的匹配概率
+ * @param attempts - 已尝试次数
+ * @returns 0–100 的进度，概率无效（无法估计目标）时为 Pulse
+ */
+pub fn state_for(probability: f64, attempts: f64) -> TaskbarState {
+    let target = SUMMARY_MILESTONES[SUMMARY_MILESTONES.len() - 1];
+    match confidence::attempts_for_confidence(probability, target) {
+        Ok(total) if total.is_finite() && total > 0.0 => {
+            TaskbarState::Progress(((attempts / total).clamp(0.0, 1.0) * 100.0).floor() as u8)
+        }
+        _ => TaskbarState::Pulse,
+    }
+}
+
+/// 一次会话的任务栏进度：只在状态变化时调用平台接口，结束时清除
+pub struct SessionTaskbar<T: TaskbarProgress> {
+    taskbar: T,
+    probability: f64,
+    last: TaskbarState,
+}
+
+impl<T: TaskbarProgress> SessionTaskbar<T> {
+    /**
+     * 创建会话的任务栏进度
+     *
+     * @param taskbar - 平台接口
+     * @param probability - 单次尝试的匹配概率
+     */
+    pub fn new(taskbar: T, probability: f64) -> Self {
+        SessionTaskbar { taskbar, probability, last: TaskbarState::Hidden }
+    }
+
+    fn show(&mut self, state: TaskbarState) {
+        if state != self.last {
+            self.taskbar.show(state);
+            self.last = state;
+        }
+    }
+
+    /**
+     * 按已尝试次数更新进度
+     */
+    pub fn update(&mut self, attempts: u64) {
+        let state = state_for(self.probability, attempts as f64);
+        self.show(state);
+    }
+
+    /**
+     * 会话结束：清除任务栏进度
+     */
+    pub fn finish(&mut self) {
+        self.show(TaskbarState::Hidden);
+    }
+
+    /**
+     * 平台接口（用于检查记录的调用）
+     */
+    #[cfg(test)]
+    pub fn into_inner(self) -> T {
+        self.taskbar
+    }
+}
+
+/**
+ * 创建会话使用的任务栏接口（关闭时为不做任何事的实现）
+ */
+pub fn for_session(app: &AppHandle) -> Box<dyn TaskbarProgress> {
+    if enabled() {
+        Box::new(WindowTaskbar { app: app.clone() })
+    } else {
+        Box::new(NoTaskbar)
+    }
+}
+
+impl<T: TaskbarProgress + ?Sized> TaskbarProgress for Box<T> {
+    fn show(&mut self, state: TaskbarState) {
+        (**self).show(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 记录调用的任务栏接口（代替平台实现）
+    #[derive(Default)]
+    struct RecordingTaskbar(Vec<TaskbarState>);
+
+    impl TaskbarProgress for RecordingTaskbar {
+        fn show(&mut self, state: TaskbarState) {
+            self.0.push(state);
+        }
+    }
+
+    /**
+     * 任务栏进度：进度值在 0–100 之间且随尝试次数不减，无法估计时只显示活动状态，会话结束时清除
+     */
+    #[test]
+    fn taskbar_progress() -> Result<(), String> {
+        let probabilities = [1.0 / 16.0, 1e-12, 1e-300, f64::MIN_POSITIVE, 1.0, 0.0, f64::NAN, -1.0];
+        let attempts = [0.0, 1.0, 1e6, u64::MAX as f64, f64::MAX];
+        for &p in &probabilities {
+            for &n in &attempts {
+                let state = state_for(p, n);
+                let valid = match state {
+                    TaskbarState::Progress(percent) => percent <= 100 && p > 0.0,
+                    TaskbarState::Pulse => !(p > 0.0 && p < 1.0),
+                    TaskbarState::Hidden => false,
+                };
+                if !valid {
+                    return Err(format!("概率 {}、尝试 {} 次时的任务栏状态为 {:?}", p, n, state));
+                }
+            }
+        }
+        
+        let p = 1.0 / 4096.0;
+        let total = confidence::attempts_for_confidence(p, 0.99)?;
+        let mut session = SessionTaskbar::new(RecordingTaskbar::default(), p);
+        for step in 0..=200u64 {
+            session.update((total * step as f64 / 100.0) as u64);
+        }
+        session.finish();
+        let calls = session.into_inner().0;
+        let percents: Vec<u8> = calls.iter().filter_map(|state| match state {
+            TaskbarState::Progress(percent) => Some(*percent),
+            _ => None,
+        }).collect();
+        if percents.first() != Some(&0) || percents.last() != Some(&100) || percents.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(format!("任务栏进度没有从 0 单调增加到 100: {:?}", percents));
+        }
+        if calls.last() != Some(&TaskbarState::Hidden) || calls.len() != percents.len() + 1 {
+            return Err(format!("会话结束时没有清除任务栏进度或有重复调用: {:?}", calls.last()));
+        }
+        
+        let mut open_ended = SessionTaskbar::new(RecordingTaskbar::default(), 0.0);
+        open_ended.update(1);
+        open_ended.update(2);
+        open_ended.finish();
+        if open_ended.into_inner().0 != [TaskbarState::Pulse, TaskbarState::Hidden] {
+            return Err("无法估计目标时应只显示活动状态".to_string());
+        }
+        Ok(())
+    }
+}
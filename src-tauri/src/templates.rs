@@ -0,0 +1,631 @@
+use crate::balances;
+use crate::bitcoin::BitcoinAddressType;
+use crate::chain::{AddressEncoder, Chain};
+use crate::collections;
+use crate::device::Device;
+use crate::engine::{self, GenerationLimits};
+use crate::filename_template;
+use crate::entropy::{self, RngMode};
+use crate::errors::WalletError;
+use crate::match_batch;
+use crate::memory;
+use crate::mnemonic::{self, KeySourceKind};
+use crate::notifications;
+use crate::pattern::{CaseMode, MatchMode, ParsedPattern, PatternType};
+use crate::retry;
+use crate::saved_wallets::{CsvPrivateKeys, OutputFormat, OutputLayout};
+use crate::scoring::{ScoreFunction, ScoreWeights};
+use crate::timestamps::{self, TimestampZone};
+use crate::wallet_qr::QrOptions;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// 模板文件名（位于用户配置的数据目录中，与其它设置放在一起）
+const TEMPLATES_FILE: &str = "templates.json";
+
+/// 模板文件格式版本
+pub const TEMPLATES_VERSION: u32 = 1;
+
+/// 模板名称的最大长度
+const MAX_TEMPLATE_NAME_LENGTH: usize = 64;
+
+/// 本版本支持的链
+const SUPPORTED_CHAINS: &[&str] = &[Chain::Ethereum.as_str(), Chain::Tron.as_str(), Chain::Bitcoin.as_str(), Chain::Solana.as_str()];
+
+/// 本版本支持的计算设备
+const SUPPORTED_DEVICES: &[&str] = &[Device::Cpu.as_str(), Device::Gpu.as_str()];
+
+/// 本版本支持的输出格式
+const SUPPORTED_OUTPUT_FORMATS: &[&str] = &["csv", "jsonl", "both"];
+
+/// 本版本支持的写入时加密方式
+const SUPPORTED_ENCRYPTION: &[&str] = &["none"];
+
+/// 本版本支持的最大工作线程数
+pub(crate) const MAX_WORKERS: u32 = crate::worker::MAX_SEARCH_WORKERS;
+
+/// 一次生成的全部参数
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct GenerationParams {
+    /// 靓号模式
+    #[serde(default)]
+    pub pattern: String,
+    /// 保存路径（可选）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub save_path: Option<String>,
+    /// 链（ethereum、tron、bitcoin 或 solana，默认 ethereum）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain: Option<String>,
+    /// 比特币地址的格式（p2pkh 或 p2wpkh，默认 p2pkh；只能与 chain: bitcoin 一起使用）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bitcoin_address: Option<BitcoinAddressType>,
+    /// 计算设备（cpu 或 gpu，默认 cpu；没有可用的 GPU 时回退到 cpu）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    /// 工作线程数（默认使用全部 CPU 核心）
+    #[serde(default, alias = "thread_count", skip_serializing_if = "Option::is_none")]
+    pub workers: Option<u32>,
+    /// 输出格式（csv、jsonl 或 both，默认 csv）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_format: Option<String>,
+    /// 同时搜索多个模式时结果文件的组织方式（per_pattern 或 combined，默认 per_pattern）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_layout: Option<OutputLayout>,
+    /// 写入时加密方式（默认 none）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<String>,
+    /// 停止条件
+    #[serde(default)]
+    pub limits: GenerationLimits,
+    /// 评分权重（默认使用内置权重）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score_weights: Option<ScoreWeights>,
+    /// 追加的集合名称（默认每次会话写入新的会话文件）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collection: Option<String>,
+    /// 会话结果文件名的模板（默认 {pattern}_{date}_{time}，结果文件为 wallet_<展开的模板>.csv；不能与 collection 同时使用）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filename_template: Option<String>,
+    /// 私钥的随机数来源（默认 reseeded-csprng）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rng_mode: Option<RngMode>,
+    /// 确定性测试模式的种子（只能与 deterministic-test 一起使用，默认 0）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_seed: Option<u64>,
+    /// 是否按模式给出的大小写与 checksum 地址比较（默认 false）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case_sensitive: Option<bool>,
+    /// 大小写方式（insensitive 或 exact，与 case_sensitive 等价；同时给出时必须一致）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case_mode: Option<CaseMode>,
+    /// 模式类型（默认 standard）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern_type: Option<PatternType>,
+    /// 比较方式（默认按模式语法决定）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub match_mode: Option<MatchMode>,
+    /// 与 pattern 同时搜索的其它模式（每个模式的结果写入各自的文件）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub additional_patterns: Vec<String>,
+    /// score 模式的评分函数（为空时按模式匹配；给出时为每个地址评分，保留最高分的钱包，pattern 为 score-<函数名>）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score_function: Option<ScoreFunction>,
+    /// wallet-found 事件是否带有私钥（默认 false：事件可以被任何网页代码观察到，只有结果文件保存私钥）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emit_private_key: Option<bool>,
+    /// 进度事件的最短间隔（毫秒，默认 250；找到匹配时立即发送）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress_interval_ms: Option<u64>,
+    /// 候选私钥的来源（默认 private_key；mnemonic 时由新的 BIP-39 助记词派生，助记词与私钥一起保存）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_source: Option<KeySourceKind>,
+    /// 助记词词数（12 或 24，默认 12）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mnemonic_words: Option<u8>,
+    /// CSV 中私钥列的写法（默认 full；masked 只保留首尾各 4 个字符，omit 不写入私钥列）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub csv_private_keys: Option<CsvPrivateKeys>,
+    /// 保存钱包失败后的重试次数（默认 2，最多 10；仍然失败的钱包保留在内存中，随命令结果返回）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub save_retries: Option<u32>,
+    /// 每个匹配在结果文件旁另外保存地址的二维码（<地址>_addr.png；为空时不保存，私钥的二维码从不保存）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address_qr: Option<QrOptions>,
+    /// 每个匹配另外写入 FancyWallets/json/wallet_<地址>.json（默认 false；保存完整的私钥）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emit_json_files: Option<bool>,
+    /// 找到匹配时是否发送系统通知（默认 false；通知只有地址和模式，按频率限制合并）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notify_on_match: Option<bool>,
+    /// 每几个匹配发送一次系统通知（默认 1，只能与 notify_on_match 一起使用）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notify_every: Option<u64>,
+    /// 检查新地址是否已有链上记录的 JSON-RPC 节点地址（为空时不检查；只发送地址，查询失败时记为未知）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify_unused: Option<String>,
+    /// 命令结果中最多返回的钱包数（默认 10000，最多 1000000；更多的匹配只写入结果文件，结果中标记为截断）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_returned_wallets: Option<usize>,
+    /// 钱包找到时间使用的时区（默认 utc；local 为带时区偏移的本机时间）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_zone: Option<TimestampZone>,
+    /// 工作线程的 CPU 占用上限（1–100，默认 100 即不限制；运行中可以通过 set_cpu_limit 调整）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_limit_percent: Option<u8>,
+    /// 只搜索不保存（默认 false）：不写入任何文件，事件和返回值中没有私钥
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<bool>,
+    /// 每批写入和发送的最多匹配数（与 batch_interval_ms 任一给出时批量写入，见 match_batch）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_size: Option<u32>,
+    /// 一批最长的等待时间（毫秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_interval_ms: Option<u64>,
+    /// 每个匹配另外写入 FancyWallets/wallets.db（默认 false；见 results_db）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub results_db: Option<bool>,
+    /// 本版本不认识的字段（原样保留）
+    #[serde(flatten)]
+    pub unknown: BTreeMap<String, Value>,
+}
+
+impl GenerationParams {
+    /**
+     * 按模式类型、大小写设置和比较方式解析一个模式（可以使用的字符和是否区分大小写由链决定，未知的链由 validate 返回错误）
+     */
+    fn parse(&self, pattern: &str) -> ParsedPattern {
+        let chain = self.chain().unwrap_or_default();
+        ParsedPattern::with_options(pattern, self.pattern_type.unwrap_or_default(), chain.case_sensitive(self.case_sensitive()))
+            .with_match_mode(self.match_mode)
+            .with_alphabet(chain.alphabet(self.bitcoin_address.unwrap_or_default()))
+    }
+
+    /**
+     * 是否区分大小写（由 case_mode 和 case_sensitive 得到；两者矛盾时按 case_sensitive，由 validate_patterns 返回错误）
+     */
+    pub fn case_sensitive(&self) -> bool {
+        CaseMode::resolve(self.case_mode, self.case_sensitive).unwrap_or(self.case_sensitive.unwrap_or_default())
+    }
+
+    /**
+     * 地址编码（由链和比特币地址的格式决定，未知的链按 ethereum；Solana 没有编码）
+     */
+    pub fn encoder(&self) -> Option<&'static dyn AddressEncoder> {
+        self.chain().unwrap_or_default().address_encoder(self.bitcoin_address.unwrap_or_default())
+    }
+
+    /**
+     * 生成的链（默认 ethereum）
+     */
+    pub fn chain(&self) -> Result<Chain, String> {
+        self.chain.as_deref().map(Chain::parse).transpose().map(Option::unwrap_or_default)
+    }
+
+    /**
+     * 请求的计算设备（默认 cpu）
+     */
+    pub fn device(&self) -> Result<Device, String> {
+        self.device.as_deref().map(Device::parse).transpose().map(Option::unwrap_or_default)
+    }
+
+    /**
+     * 搜索的全部模式（pattern 在前，之后是 additional_patterns）
+     */
+    pub fn patterns(&self) -> Vec<String> {
+        std::iter::once(self.pattern.clone()).chain(self.additional_patterns.iter().cloned()).collect()
+    }
+
+    /**
+     * 解析搜索的全部模式（顺序与 patterns 相同）
+     */
+    pub fn parsed_patterns(&self) -> Vec<ParsedPattern> {
+        self.patterns().iter().map(|pattern| self.parse(pattern)).collect()
+    }
+
+    /**
+     * 校验全部模式；同时搜索多个模式时结果分别写入各自的文件，不能追加到集合中，文件名模板必须包含 {pattern}
+     * 
+     * score 模式不使用模式，不能同时给出其它模式或只对模式有意义的设置，评分函数只能用于以太坊地址。
+     */
+    pub fn validate_patterns(&self) -> Result<(), String> {
+        let chain = self.chain()?;
+        CaseMode::resolve(self.case_mode, self.case_sensitive)?;
+        if self.score_function.is_some() {
+            if chain != Chain::Ethereum {
+                return Err(format!("score 模式按十六进制地址评分，不能用于 {}", chain.as_str()));
+            }
+            let pattern_options = !self.additional_patterns.is_empty()
+                || self.case_sensitive()
+                || self.pattern_type.is_some_and(|pattern_type| pattern_type != PatternType::Standard)
+                || self.match_mode.is_some();
+            if pattern_options {
+                return Err("score 模式为每个地址评分，不能与 additional_patterns、case_sensitive（case_mode: exact）、pattern_type 或 match_mode 同时使用".to_string());
+            }
+            return Ok(());
+        }
+        for parsed in self.parsed_patterns() {
+            parsed.validate()?;
+        }
+        if !self.additional_patterns.is_empty() && self.collection.is_some() {
+            return Err("同时搜索多个模式时每个模式的结果写入各自的文件，不能追加到集合中".to_string());
+        }
+        if self.output_layout == Some(OutputLayout::Combined) {
+            if self.collection.is_some() {
+                return Err("集合本身就是一个文件，output_layout: combined 不能与 collection 同时使用".to_string());
+            }
+            if self.filename_template.is_some() {
+                return Err("合并的结果文件命名为 wallets_<时间>.csv，filename_template 不能与 output_layout: combined 同时使用".to_string());
+            }
+        }
+        if let Some(template) = &self.filename_template {
+            if self.collection.is_some() {
+                return Err("集合文件按集合名称命名，filename_template 不能与 collection 同时使用".to_string());
+            }
+            filename_template::validate(template, self.additional_patterns.len() + 1)?;
+        }
+        Ok(())
+    }
+
+    /**
+     * 与 validate_patterns 相同，模式本身无效时返回 InvalidPattern（带有第一个无效字符的位置）
+     */
+    pub fn check_patterns(&self) -> Result<(), WalletError> {
+        if self.score_function.is_none() {
+            for parsed in self.parsed_patterns() {
+                parsed.validate().map_err(|reason| WalletError::invalid_pattern(&parsed, reason))?;
+            }
+        }
+        self.validate_patterns().map_err(WalletError::from)
+    }
+
+    /**
+     * 校验参数本身的取值（不检查本版本是否支持）
+     */
+    pub fn validate(&self) -> Result<(), String> {
+        self.validate_patterns()?;
+        if self.workers == Some(0) {
+            return Err("工作线程数必须至少为 1".to_string());
+        }
+        let limits = &self.limits;
+        if limits.max_attempts == Some(0) || limits.max_matches == Some(0) || limits.max_duration_secs == Some(0) || limits.max_duration_ms == Some(0) {
+            return Err("停止条件必须大于 0".to_string());
+        }
+        if let Some(weights) = &self.score_weights {
+            weights.normalized()?;
+        }
+        if let Some(collection) = &self.collection {
+            collections::validate_name(collection)?;
+        }
+        retry::save_policy(self.save_retries)?;
+        memory::returned_wallets_limit(self.max_returned_wallets)?;
+        if let Some(percent) = self.cpu_limit_percent {
+            engine::validate_cpu_limit(percent)?;
+        }
+        if let Some(address_qr) = &self.address_qr {
+            address_qr.validate()?;
+        }
+        notifications::validate(self.notify_on_match, self.notify_every)?;
+        match_batch::validate(self.batch_size, self.batch_interval_ms)?;
+        if (self.batch_size.is_some() || self.batch_interval_ms.is_some()) && self.verify_unused.is_some() {
+            return Err("verify_unused 的匹配在查询完成后逐个发送，不能与 batch_size 或 batch_interval_ms 同时使用".to_string());
+        }
+        let rng_mode = self.rng_mode.unwrap_or_default();
+        entropy::validate(rng_mode, self.test_seed)?;
+        mnemonic::validate(self.key_source.unwrap_or_default(), self.mnemonic_words)?;
+        let chain = self.chain()?;
+        chain.validate_key_source(self.key_source.unwrap_or_default())?;
+        if let Some(rpc_url) = &self.verify_unused {
+            balances::validate_rpc_url(rpc_url)?;
+            if chain != Chain::Ethereum {
+                return Err(format!("verify_unused 只能与 chain: ethereum 一起使用（当前为 {}）", chain.as_str()));
+            }
+        }
+        if self.bitcoin_address.is_some() && chain != Chain::Bitcoin {
+            return Err(format!("bitcoin_address 只能与 chain: bitcoin 一起使用（当前为 {}）", chain.as_str()));
+        }
+        if rng_mode.is_test() && self.collection.is_some() {
+            return Err("确定性测试模式的钱包不能追加到集合中，以免与真实钱包混在一起".to_string());
+        }
+        if self.csv_private_keys.is_some_and(|keys| keys != CsvPrivateKeys::Full) && self.collection.is_some() {
+            return Err("集合必须保存完整的私钥，csv_private_keys 不能与 collection 同时使用".to_string());
+        }
+        if self.emit_json_files == Some(true) && self.csv_private_keys.is_some_and(|keys| keys != CsvPrivateKeys::Full) {
+            return Err("JSON 文件保存完整的私钥，emit_json_files 不能与不为 full 的 csv_private_keys 同时使用".to_string());
+        }
+        if OutputFormat::parse(self.output_format.as_deref()).is_ok_and(OutputFormat::writes_jsonl) && self.collection.is_some() {
+            return Err("集合只保存 CSV，output_format 为 jsonl 或 both 时不能与 collection 同时使用".to_string());
+        }
+        self.check_dry_run()
+    }
+
+    /**
+     * dry_run 不写入任何文件、不交出私钥，不能与只用于保存或带出私钥的设置同时使用
+     */
+    pub fn check_dry_run(&self) -> Result<(), String> {
+        if self.dry_run != Some(true) {
+            return Ok(());
+        }
+        let conflicts = [
+            ("collection", self.collection.is_some()),
+            ("emit_json_files", self.emit_json_files == Some(true)),
+            ("results_db", self.results_db == Some(true)),
+            ("address_qr", self.address_qr.is_some()),
+            ("verify_unused", self.verify_unused.is_some()),
+            ("emit_private_key", self.emit_private_key == Some(true)),
+        ];
+        match conflicts.iter().find(|(_, set)| *set) {
+            Some((name, _)) => Err(format!("dry_run 不写入任何文件、不交出私钥，不能与 {} 同时使用", name)),
+            None => Ok(()),
+        }
+    }
+
+    /**
+     * 检查本版本是否具备参数所需的能力（例如 GPU、其它链）
+     *
+     * @returns 缺少的能力（以“此版本不支持”开头，便于与其它错误区分）
+     */
+    pub fn check_capabilities(&self) -> Result<(), String> {
+        let checks = [
+            ("链", self.chain.as_deref(), SUPPORTED_CHAINS),
+            ("计算设备", self.device.as_deref(), SUPPORTED_DEVICES),
+            ("输出格式", self.output_format.as_deref(), SUPPORTED_OUTPUT_FORMATS),
+            ("写入时加密", self.encryption.as_deref(), SUPPORTED_ENCRYPTION),
+        ];
+        for (capability, value, supported) in checks {
+            if let Some(value) = value {
+                if !supported.iter().any(|s| s.eq_ignore_ascii_case(value)) {
+                    return Err(format!(
+                        "此版本不支持{} {}（支持: {}）",
+                        capability,
+                        value,
+                        supported.join(", ")
+                    ));
+                }
+            }
+        }
+        if let Some(workers) = self.workers.filter(|&w| w > MAX_WORKERS) {
+            return Err(format!("此版本不支持 {} 个工作线程（最多 {} 个）", workers, MAX_WORKERS));
+        }
+        Ok(())
+    }
+
+    /**
+     * 本版本不认识的字段（用于提示，字段本身会原样保存）
+     */
+    pub fn unknown_fields(&self) -> Vec<String> {
+        self.unknown
+            .keys()
+            .cloned()
+            .chain(self.limits.unknown.keys().map(|key| format!("limits.{}", key)))
+            .collect()
+    }
+}
+
+/// 命名的生成模板
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Template {
+    /// 模板名称
+    pub name: String,
+    /// 生成参数
+    pub params: GenerationParams,
+    /// 最后保存时间
+    pub updated_at: String,
+}
+
+/// 模板文件内容
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct TemplateStore {
+    version: u32,
+    templates: Vec<Template>,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, Value>,
+}
+
+impl Default for TemplateStore {
+    fn default() -> Self {
+        TemplateStore { version: TEMPLATES_VERSION, templates: Vec::new(), unknown: BTreeMap::new() }
+    }
+}
+
+/// 列表中的模板及加载时的提示
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TemplateInfo {
+    /// 模板
+    #[serde(flatten)]
+    pub template: Template,
+    /// 提示（例如本版本不认识的字段）
+    pub warnings: Vec<String>,
+    /// 本版本不具备的能力（为空表示可以直接开始）
+    pub capability_error: Option<String>,
+}
+
+/**
+ * 校验模板名称
+ */
+fn validate_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("模板名称不能为空".to_string());
+    }
+    if name.chars().count() > MAX_TEMPLATE_NAME_LENGTH {
+        return Err(format!("模板名称不能超过 {} 个字符", MAX_TEMPLATE_NAME_LENGTH));
+    }
+    if let Some(c) = name.chars().find(|c| c.is_control()) {
+        return Err(format!("模板名称不能包含控制字符 {:?}", c));
+    }
+    Ok(())
+}
+
+fn load_store(profile_dir: &Path) -> Result<TemplateStore, String> {
+    let path = profile_dir.join(TEMPLATES_FILE);
+    if !path.exists() {
+        return Ok(TemplateStore::default());
+    }
+    let data = fs::read_to_string(&path).map_err(|e| format!("无法读取模板文件: {}", e))?;
+    serde_json::from_str(&data).map_err(|e| format!("模板文件格式错误: {}", e))
+}
+
+fn save_store(profile_dir: &Path, store: &TemplateStore) -> Result<(), String> {
+    fs::create_dir_all(profile_dir).map_err(|e| format!("无法创建配置目录: {}", e))?;
+    let json = serde_json::to_string_pretty(store).map_err(|e| format!("无法序列化模板: {}", e))?;
+    let path = profile_dir.join(TEMPLATES_FILE);
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, json).map_err(|e| format!("无法写入模板文件: {}", e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("无法写入模板文件: {}", e))
+}
+
+/**
+ * 保存（或覆盖）命名模板
+ *
+ * @param profile_dir - 用户配置的数据目录
+ * @param name - 模板名称
+ * @param params - 生成参数
+ * @returns 保存的模板及提示
+ */
+pub fn save_template(profile_dir: &Path, name: &str, params: GenerationParams) -> Result<TemplateInfo, String> {
+    validate_name(name)?;
+    params.validate()?;
+    let mut store = load_store(profile_dir)?;
+    if store.version > TEMPLATES_VERSION {
+        return Err(format!(
+            "模板文件由更新的版本（格式 {}）写入，为避免丢失数据不能在此版本中修改",
+            store.version
+        ));
+    }
+    let template = Template { name: name.to_string(), params, updated_at: timestamps::now_rfc3339() };
+    match store.templates.iter_mut().find(|t| t.name == name) {
+        Some(existing) => *existing = template.clone(),
+        None => store.templates.push(template.clone()),
+    }
+    save_store(profile_dir, &store)?;
+    Ok(describe(template, &store))
+}
+
+/**
+ * 附上加载提示和能力检查结果
+ */
+fn describe(template: Template, store: &TemplateStore) -> TemplateInfo {
+    let mut warnings: Vec<String> = template
+        .params
+        .unknown_fields()
+        .into_iter()
+        .map(|field| format!("字段 {} 不被此版本识别，已原样保留", field))
+        .collect();
+    if store.version > TEMPLATES_VERSION {
+        warnings.push(format!("模板文件由更新的版本（格式 {}）写入", store.version));
+    }
+    let capability_error = template.params.check_capabilities().err();
+    TemplateInfo { template, warnings, capability_error }
+}
+
+/**
+ * 列出全部模板
+ *
+ * @param profile_dir - 用户配置的数据目录
+ */
+pub fn list_templates(profile_dir: &Path) -> Result<Vec<TemplateInfo>, String> {
+    let store = load_store(profile_dir)?;
+    Ok(store.templates.iter().cloned().map(|t| describe(t, &store)).collect())
+}
+
+/**
+ * 把覆盖项合并到 JSON 对象上（嵌套对象逐层合并，其它值直接替换）
+ */
+fn merge(target: &mut Map<String, Value>, overrides: Map<String, Value>) {
+    for (key, value) in overrides {
+        match (target.get_mut(&key), value) {
+            (Some(Value::Object(existing)), Value::Object(nested)) => merge(existing, nested),
+            (_, value) => {
+                target.insert(key, value);
+            }
+        }
+    }
+}
+
+/**
+ * 读取模板并应用覆盖项，得到可以直接开始的参数
+ *
+ * @param profile_dir - 用户配置的数据目录
+ * @param name - 模板名称
+ * @param overrides - 覆盖项（与参数相同的 JSON 结构，只需包含要修改的字段）
+ * @returns 合并后的参数（已校验取值和本版本的能力）
+ */
+pub fn resolve_template(profile_dir: &Path, name: &str, overrides: Option<Value>) -> Result<GenerationParams, String> {
+    let store = load_store(profile_dir)?;
+    let template = store
+        .templates
+        .into_iter()
+        .find(|t| t.name == name)
+        .ok_or_else(|| format!("模板不存在: {}", name))?;
+    let params = match overrides {
+        None | Some(Value::Null) => template.params,
+        Some(Value::Object(overrides)) => {
+            let Value::Object(mut merged) =
+                serde_json::to_value(&template.params).map_err(|e| format!("无法读取模板参数: {}", e))?
+            else {
+                return Err("模板参数格式错误".to_string());
+            };
+            merge(&mut merged, overrides);
+            serde_json::from_value(Value::Object(merged)).map_err(|e| format!("覆盖项格式错误: {}", e))?
+        }
+        Some(_) => return Err("覆盖项必须是 JSON 对象".to_string()),
+    };
+    params.validate()?;
+    params.check_capabilities()?;
+    Ok(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{CancellationToken, SearchConfig, SearchEvent};
+    use crate::stop::StopReason;
+    use std::time::{Duration, Instant};
+
+    /**
+     * 时间限制：到时后汇总线程停止搜索，最后一次进度事件带上实际的运行时间；秒和毫秒两种限制同时设置时以较短的为准
+     */
+    #[test]
+    fn time_budget() -> Result<(), String> {
+        let limits = GenerationLimits { max_duration_secs: Some(60), max_duration_ms: Some(1_500), ..GenerationLimits::default() };
+        if limits.max_duration() != Some(Duration::from_millis(1_500)) || limits.reached(1, 0, Duration::from_millis(1_499)).is_some() {
+            return Err(format!("{:?} 的最长运行时间为 {:?}", limits, limits.max_duration()));
+        }
+        let reached = |limits: GenerationLimits, elapsed: u64| limits.reached(1, 0, Duration::from_millis(elapsed)).map(|(_, detail)| detail);
+        if reached(limits, 1_500).as_deref() != Some("1500ms") || reached(GenerationLimits { max_duration_ms: Some(2_000), ..GenerationLimits::default() }, 2_000).as_deref() != Some("2s") {
+            return Err("时间限制的停止记录不正确".to_string());
+        }
+        if (GenerationParams { pattern: "8".to_string(), limits: GenerationLimits { max_duration_ms: Some(0), ..GenerationLimits::default() }, ..GenerationParams::default() }).validate().is_ok() {
+            return Err("max_duration_ms 为 0 没有被拒绝".to_string());
+        }
+        
+        // 32 个 f 的前缀在 50 毫秒内不可能匹配
+        let config = SearchConfig {
+            limits: GenerationLimits { max_duration_ms: Some(50), ..GenerationLimits::default() },
+            workers: Some(1),
+            ..SearchConfig::new("f".repeat(32))
+        };
+        let mut last_progress = None;
+        let started = Instant::now();
+        let report = crate::engine::search(&config, &CancellationToken::new(), || false, |event| {
+            if let SearchEvent::Progress(progress) = event {
+                last_progress = Some(progress);
+            }
+        });
+        let wall = started.elapsed();
+        let stop = report.stop.as_ref().ok_or("到时后没有记录停止原因")?;
+        if stop.reason != StopReason::MaxDuration || stop.detail.as_deref() != Some("50ms") || report.matches != 0 {
+            return Err(format!("到时后的停止记录为 {:?}，匹配数为 {}", stop, report.matches));
+        }
+        let last = last_progress.ok_or("到时后没有发送最后一次进度事件")?;
+        if last.attempts != report.attempts || last.duration < 50 || report.duration < 50 {
+            return Err(format!("最后一次进度为 {} 次、{} 毫秒，报告为 {} 次、{} 毫秒", last.attempts, last.duration, report.attempts, report.duration));
+        }
+        if wall > Duration::from_secs(5) {
+            return Err(format!("50 毫秒的时间限制用了 {:?} 才停止", wall));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use crate::timer::Clock;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+/// 不区分大小写时找到的匹配数
+pub(crate) const CASE_INSENSITIVE_MATCHES: u64 = 3;
+
+/// 旧结果文件中波场格式的地址（用于检查按地址格式推断链）
+pub(crate) const TRON_ADDRESS: &str = "TLa2f6VPqDgRE67v1736s7bJ8Ray5wYjU7";
+
+/// 全零地址的波场格式（用于检查地址格式转换）
+pub(crate) const TRON_ZERO_ADDRESS: &str = "T9yD14Nj9j7xAB4dbGeiX9h8unkKHxuWwb";
+
+/// 助记词按 m/44'/60'/0'/0/0 派生的地址（与常见钱包和开发工具的默认账户一致）
+pub(crate) const MNEMONIC_ADDRESS_VECTORS: &[(&str, &str)] = &[
+    (
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        "0x9858EfFD232B4033E47d90003D41EC34EcaEda94",
+    ),
+    ("test test test test test test test test test test test junk", "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"),
+];
+
+/// "test … junk" 在 m/44'/60'/0'/0/1 的地址（Hardhat 的第二个默认账户）
+pub(crate) const MNEMONIC_SCAN_INDEX_1: &str = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8";
+
+/// 按固定刻度前进的模拟时钟：每次读取真实时间前进 advance，读数向下取整到 step 的整数倍
+pub(crate) struct SteppedClock {
+    pub(crate) now: Duration,
+    pub(crate) step: Duration,
+    pub(crate) advance: Duration,
+}
+
+impl SteppedClock {
+    pub(crate) fn reading(&self, at: Duration) -> Duration {
+        self.step * (at.as_nanos() / self.step.as_nanos()) as u32
+    }
+}
+
+impl Clock for SteppedClock {
+    fn elapsed(&mut self) -> Duration {
+        self.now += self.advance;
+        self.reading(self.now)
+    }
+}
+
+/**
+ * 在临时数据目录中运行测试，结束后删除目录
+ *
+ * @param name - 目录名称（每个测试不同，测试并行运行）
+ * @param test - 测试内容
+ */
+pub(crate) fn with_data_dir<T>(name: &str, test: impl FnOnce(&Path) -> Result<T, String>) -> Result<T, String> {
+    let dir = std::env::temp_dir().join(format!("fancy-wallet-test-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("无法创建临时目录: {}", e))?;
+    let result = test(&dir);
+    let _ = std::fs::remove_dir_all(&dir);
+    result
+}
+
+/// 读写会话登记表的测试之间的锁（测试并行运行，登记表是进程内共享的）
+static SESSION_REGISTRY: Mutex<()> = Mutex::new(());
+
+/**
+ * 独占会话登记表，直到返回值被丢弃
+ */
+pub(crate) fn lock_sessions() -> MutexGuard<'static, ()> {
+    SESSION_REGISTRY.lock().unwrap_or_else(|e| e.into_inner())
+}
@@ -0,0 +1,572 @@
+use crate::timestamps;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// 分辨率不超过该值时认为计时精度足够
+const FINE_RESOLUTION: Duration = Duration::from_millis(1);
+
+/// 计时精度足够时的速度测量窗口
+const BASE_WINDOW: Duration = Duration::from_millis(200);
+
+/// 测量窗口至少包含的计时器刻度数（刻度越粗窗口越长，量化误差不超过约 2 / 50）
+const WINDOW_TICKS: u32 = 50;
+
+/// 计时精度足够时的平滑系数（新样本的权重）
+const BASE_ALPHA: f64 = 0.3;
+
+/// 计时精度很低时平滑系数的下限
+const MIN_ALPHA: f64 = 0.1;
+
+/// 滑动窗口速度使用的样本时长（粗计时器的测量窗口更长时取测量窗口）
+pub const SLIDING_RATE_WINDOW: Duration = Duration::from_secs(5);
+
+/// 默认的进度事件最短间隔（毫秒）
+pub const DEFAULT_PROGRESS_INTERVAL_MS: u64 = 250;
+
+/// 进度事件最短间隔的最大值（毫秒）
+const MAX_PROGRESS_INTERVAL_MS: u64 = 60_000;
+
+/// 检测分辨率时最多等待的时间（按被检测的时钟计）
+const MAX_PROBE_TIME: Duration = Duration::from_millis(100);
+
+/// 检测分辨率时观察的刻度变化次数
+const PROBE_TICKS: usize = 4;
+
+/// 检测分辨率时最多读取时钟的次数（时钟停止前进时以此结束）
+const MAX_PROBE_READS: usize = 2_000_000;
+
+/// 时钟在检测期间没有前进时假定的分辨率（与 Windows 默认的 15.625 ms 定时器一致）
+const STALLED_RESOLUTION: Duration = Duration::from_micros(15_625);
+
+/// 相邻两次观察之间系统时间与单调时钟的差超过该值时认为系统时间发生了跳变（NTP 校时或手动修改）
+pub const CLOCK_JUMP_THRESHOLD: Duration = Duration::from_secs(2);
+
+/// 一个会话中最多记录的系统时间跳变次数
+const MAX_CLOCK_JUMPS: usize = 32;
+
+/// 启动时检测到的单调时钟分辨率
+static RESOLUTION: OnceLock<Duration> = OnceLock::new();
+
+/// 可读取的单调时钟（测试中用构造的阶梯时钟代替）
+pub trait Clock {
+    /**
+     * 从某个固定起点开始经过的时间
+     */
+    fn elapsed(&mut self) -> Duration;
+}
+
+/// 系统单调时钟
+struct MonotonicClock(Instant);
+
+impl Clock for MonotonicClock {
+    fn elapsed(&mut self) -> Duration {
+        self.0.elapsed()
+    }
+}
+
+/// 根据时钟分辨率确定的速度测量参数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateTuning {
+    /// 时钟分辨率
+    pub resolution: Duration,
+    /// 测量窗口（相邻两次速度样本之间的最短运行时间）
+    pub window: Duration,
+    /// 平滑系数（新样本的权重）
+    pub alpha: f64,
+    /// 计时精度不足，速度和剩余时间只是粗略估计
+    pub low_precision: bool,
+}
+
+impl RateTuning {
+    /**
+     * 按时钟分辨率确定测量窗口和平滑系数
+     *
+     * @param resolution - 时钟分辨率
+     */
+    pub fn for_resolution(resolution: Duration) -> Self {
+        let low_precision = resolution > FINE_RESOLUTION;
+        let window = BASE_WINDOW.max(resolution * WINDOW_TICKS);
+        let alpha = if low_precision {
+            (BASE_ALPHA * FINE_RESOLUTION.as_secs_f64() / resolution.as_secs_f64()).max(MIN_ALPHA)
+        } else {
+            BASE_ALPHA
+        };
+        RateTuning { resolution, window, alpha, low_precision }
+    }
+}
+
+/**
+ * 检测时钟分辨率：连续读取时钟，取相邻两次不同读数之差的最小值
+ *
+ * @param clock - 时钟
+ * @returns 分辨率；检测期间时钟没有前进时为 STALLED_RESOLUTION
+ */
+pub fn measure_resolution(clock: &mut impl Clock) -> Duration {
+    let start = clock.elapsed();
+    let mut last = start;
+    let mut smallest: Option<Duration> = None;
+    let mut ticks = 0;
+    for _ in 0..MAX_PROBE_READS {
+        let now = clock.elapsed();
+        if now > last {
+            let step = now - last;
+            smallest = Some(smallest.map_or(step, |s| s.min(step)));
+            ticks += 1;
+            last = now;
+        } else if now < last {
+            // 时钟后退：不是可靠的单调时钟，从当前读数重新开始
+            last = now;
+        }
+        if ticks >= PROBE_TICKS || now.saturating_sub(start) >= MAX_PROBE_TIME {
+            break;
+        }
+    }
+    smallest.unwrap_or(STALLED_RESOLUTION)
+}
+
+/**
+ * 启动时检测到的系统单调时钟分辨率（第一次调用时检测）
+ */
+pub fn resolution() -> Duration {
+    *RESOLUTION.get_or_init(|| measure_resolution(&mut MonotonicClock(Instant::now())))
+}
+
+/**
+ * 系统时钟对应的速度测量参数
+ */
+pub fn tuning() -> RateTuning {
+    RateTuning::for_resolution(resolution())
+}
+
+/// 平滑后的速度：只在测量窗口足够长时取样，时钟后退或停止时不产生样本
+#[derive(Debug, Clone)]
+pub struct RateEstimator {
+    tuning: RateTuning,
+    window_start: Option<(u64, Duration)>,
+    rate: Option<f64>,
+}
+
+impl RateEstimator {
+    /**
+     * 创建速度估计
+     *
+     * @param tuning - 测量参数
+     */
+    pub fn new(tuning: RateTuning) -> Self {
+        RateEstimator { tuning, window_start: None, rate: None }
+    }
+
+    /**
+     * 记录当前尝试次数和运行时间；距离窗口开始超过测量窗口时取一个样本并更新平滑后的速度
+     *
+     * @param attempts - 已尝试次数
+     * @param active - 运行时间（不含暂停时间）
+     */
+    pub fn update(&mut self, attempts: u64, active: Duration) {
+        let Some((start_attempts, start_active)) = self.window_start else {
+            self.window_start = Some((attempts, active));
+            return;
+        };
+        if active < start_active || attempts < start_attempts {
+            self.window_start = Some((attempts, active));
+            return;
+        }
+        let span = active - start_active;
+        // 时钟没有前进（读数落在同一刻度内）时继续等待，不会得到无穷大的速度
+        if span.is_zero() || span < self.tuning.window || attempts == start_attempts {
+            return;
+        }
+        let sample = (attempts - start_attempts) as f64 / span.as_secs_f64();
+        self.rate = Some(match self.rate {
+            Some(rate) => rate + self.tuning.alpha * (sample - rate),
+            None => sample,
+        });
+        self.window_start = Some((attempts, active));
+    }
+
+    /**
+     * 当前速度（每秒尝试次数，还没有完整的测量窗口时为空）
+     */
+    pub fn rate(&self) -> Option<f64> {
+        self.rate
+    }
+}
+
+/// 滑动窗口内的速度：只使用最近 SLIDING_RATE_WINDOW 内的样本，反映当前吞吐量而不是整个会话的平均值
+#[derive(Debug, Clone)]
+pub struct SlidingRate {
+    tuning: RateTuning,
+    samples: VecDeque<(u64, Duration)>,
+}
+
+impl SlidingRate {
+    /**
+     * 创建滑动窗口速度
+     *
+     * @param tuning - 测量参数
+     */
+    pub fn new(tuning: RateTuning) -> Self {
+        SlidingRate { tuning, samples: VecDeque::new() }
+    }
+
+    /**
+     * 记录当前尝试次数和运行时间，丢弃窗口之外的样本；时钟或次数后退时重新开始
+     *
+     * @param attempts - 已尝试次数
+     * @param active - 运行时间（不含暂停时间）
+     */
+    pub fn update(&mut self, attempts: u64, active: Duration) {
+        if self.samples.back().is_some_and(|&(last_attempts, last_active)| attempts < last_attempts || active < last_active) {
+            self.samples.clear();
+        }
+        self.samples.push_back((attempts, active));
+        // 保留窗口开始之前的最后一个样本，测量跨度不小于窗口
+        let window = SLIDING_RATE_WINDOW.max(self.tuning.window);
+        while self.samples.get(1).is_some_and(|&(_, next_active)| active - next_active >= window) {
+            self.samples.pop_front();
+        }
+    }
+
+    /**
+     * 窗口内的速度（每秒尝试次数，样本跨度还不到测量窗口时为空）
+     */
+    pub fn rate(&self) -> Option<f64> {
+        let (&(first_attempts, first_active), &(last_attempts, last_active)) = (self.samples.front()?, self.samples.back()?);
+        let span = last_active - first_active;
+        if span.is_zero() || span < self.tuning.window {
+            return None;
+        }
+        Some((last_attempts - first_attempts) as f64 / span.as_secs_f64())
+    }
+}
+
+/// 按时间限制进度事件的发送频率：两次发送之间至少间隔 interval，找到匹配时立即发送
+#[derive(Debug, Clone)]
+pub struct ProgressThrottle {
+    interval: Duration,
+    /// 上一次发送的时刻（从会话开始经过的时间）
+    last_sent: Option<Duration>,
+}
+
+impl ProgressThrottle {
+    /**
+     * 创建进度事件的频率限制
+     *
+     * @param interval_ms - 最短间隔（毫秒，为空时使用默认值，0 表示不限制）
+     */
+    pub fn new(interval_ms: Option<u64>) -> Result<Self, String> {
+        let interval_ms = interval_ms.unwrap_or(DEFAULT_PROGRESS_INTERVAL_MS);
+        if interval_ms > MAX_PROGRESS_INTERVAL_MS {
+            return Err(format!("进度事件的间隔不能超过 {} 毫秒", MAX_PROGRESS_INTERVAL_MS));
+        }
+        Ok(ProgressThrottle { interval: Duration::from_millis(interval_ms), last_sent: None })
+    }
+
+    /**
+     * 判断现在是否发送进度事件；返回 true 时记为已发送
+     *
+     * @param now - 当前时刻（从会话开始经过的时间，测试中由模拟时钟提供）
+     * @param force - 不受间隔限制（找到匹配时）
+     */
+    pub fn ready(&mut self, now: Duration, force: bool) -> bool {
+        let due = force || self.last_sent.is_none_or(|last| now.saturating_sub(last) >= self.interval);
+        if due {
+            self.last_sent = Some(now);
+        }
+        due
+    }
+}
+
+/// 会话期间检测到的一次系统时间跳变
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ClockJump {
+    /// 检测到跳变时相对会话开始的单调时间（毫秒）
+    pub at_ms: u64,
+    /// 系统时间相对单调时钟的跳变量（毫秒，负数表示系统时间后退）
+    pub offset_ms: i64,
+    /// 跳变后的系统时间
+    pub wall_time: String,
+}
+
+/// 系统时间跳变检测：比较相邻两次观察之间系统时间和单调时钟各自前进的时间
+///
+/// 会话的时长和速度只使用单调时钟，系统时间只用于写入记录中的时间；
+/// 检测结果用于说明记录中的时间为什么不是单调递增的。
+#[derive(Debug, Clone, Default)]
+pub struct ClockJumpDetector {
+    last: Option<(Duration, DateTime<Utc>)>,
+    jumps: Vec<ClockJump>,
+}
+
+impl ClockJumpDetector {
+    /**
+     * 创建检测器
+     */
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * 记录一次观察
+     *
+     * @param monotonic - 单调时钟读数（相对会话开始）
+     * @param wall - 同一时刻的系统时间
+     * @returns 与上一次观察相比系统时间发生跳变时的跳变信息
+     */
+    pub fn observe(&mut self, monotonic: Duration, wall: DateTime<Utc>) -> Option<ClockJump> {
+        let previous = self.last.replace((monotonic, wall));
+        let (last_monotonic, last_wall) = previous?;
+        let monotonic_ms = monotonic.saturating_sub(last_monotonic).as_millis() as i64;
+        let offset_ms = (wall - last_wall).num_milliseconds() - monotonic_ms;
+        if offset_ms.unsigned_abs() < CLOCK_JUMP_THRESHOLD.as_millis() as u64 {
+            return None;
+        }
+        let jump = ClockJump { at_ms: monotonic.as_millis() as u64, offset_ms, wall_time: timestamps::to_rfc3339(wall) };
+        if self.jumps.len() < MAX_CLOCK_JUMPS {
+            self.jumps.push(jump.clone());
+        }
+        Some(jump)
+    }
+
+    /**
+     * 已记录的跳变（超过上限后不再记录，但仍会返回给调用方用于提示）
+     */
+    pub fn jumps(&self) -> &[ClockJump] {
+        &self.jumps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::Chain;
+    use crate::confidence;
+    use crate::engine::GenerationLimits;
+    use crate::hexutil::Address;
+    use crate::replay::ReplayRecorder;
+    use chrono::TimeZone;
+    use crate::test_support::SteppedClock;
+
+    /**
+     * 按模拟的阶梯时钟运行 60 秒，返回速度稳定后（前 5 秒之后）相对真实速度的最大误差
+     *
+     * @param tuning - 测量参数
+     * @param step - 时钟刻度
+     */
+    fn simulated_rate_error(tuning: RateTuning, step: Duration) -> Result<f64, String> {
+        // 每秒 100000 次尝试，每 1000 次上报一次进度（真实间隔 10 ms，小于一个刻度）
+        const TRUE_RATE: f64 = 100_000.0;
+        let clock = SteppedClock { now: Duration::ZERO, step, advance: Duration::ZERO };
+        let mut estimator = RateEstimator::new(tuning);
+        let mut worst: f64 = 0.0;
+        for report in 1..=6000u64 {
+            let attempts = report * 1000;
+            let active = clock.reading(Duration::from_secs_f64(attempts as f64 / TRUE_RATE));
+            estimator.update(attempts, active);
+            if active >= Duration::from_secs(5) {
+                let rate = estimator.rate().ok_or("没有测得速度")?;
+                worst = worst.max((rate - TRUE_RATE).abs() / TRUE_RATE);
+            }
+        }
+        Ok(worst)
+    }
+
+    /**
+     * 进度中的速度和剩余时间：滑动窗口内的速度跟随速度变化，而不是整个会话的平均值；
+     * 剩余时间按停止条件中最先达到的一个估计，匹配概率或速度未知时为空
+     */
+    #[test]
+    fn progress_eta() -> Result<(), String> {
+        // 前 20 秒每秒 1000 次，之后每秒 4000 次，每 100 毫秒上报一次
+        let mut recent = SlidingRate::new(RateTuning::for_resolution(Duration::from_micros(1)));
+        let mut attempts = 0;
+        for tick in 1..=300u64 {
+            attempts += if tick <= 200 { 100 } else { 400 };
+            recent.update(attempts, Duration::from_millis(tick * 100));
+        }
+        let rate = recent.rate().ok_or("没有测得滑动窗口内的速度")?;
+        if (rate - 4000.0).abs() > 1.0 {
+            return Err(format!("滑动窗口内的速度为 {:.1}，应为 4000（整个会话平均为 {:.1}）", rate, attempts as f64 / 30.0));
+        }
+        recent.update(0, Duration::from_secs(31));
+        if recent.rate().is_some() {
+            return Err("次数后退后滑动窗口没有重新开始".to_string());
+        }
+        
+        let limits = |max_attempts: Option<u64>, max_matches: Option<u64>, max_duration_secs: Option<u64>| GenerationLimits {
+            max_attempts,
+            max_matches,
+            max_duration_secs,
+            ..GenerationLimits::default()
+        };
+        let p = 1.0 / 1000.0;
+        let active = Duration::from_secs(10);
+        // (停止条件, 匹配概率, 速度, 期望的剩余时间)
+        let cases = [
+            (limits(None, None, None), Some(p), Some(100.0), Some(10_000.0)),
+            (limits(None, Some(3), None), Some(p), Some(100.0), Some(20_000.0)),
+            (limits(Some(2_500), Some(3), None), Some(p), Some(100.0), Some(5_000.0)),
+            (limits(None, Some(3), Some(12)), Some(p), Some(100.0), Some(2_000.0)),
+            (limits(None, None, Some(5)), Some(p), Some(100.0), Some(0.0)),
+            (limits(None, Some(3), None), None, Some(100.0), None),
+            (limits(None, Some(3), None), Some(p), None, None),
+            (limits(None, Some(3), None), Some(p), Some(f64::NAN), None),
+        ];
+        for (limits, probability, rate, expected) in &cases {
+            let remaining = confidence::remaining_ms(limits, *probability, 2_000, 1, active, *rate);
+            let close = match (remaining, expected) {
+                (Some(remaining), Some(expected)) => (remaining - expected).abs() < 1e-6,
+                (None, None) => true,
+                _ => false,
+            };
+            if !close {
+                return Err(format!("{:?} 的剩余时间为 {:?}，应为 {:?}", limits, remaining, expected));
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * 进度事件的频率限制：默认间隔内最多发送一次，找到匹配时立即发送，过大的间隔被拒绝
+     */
+    #[test]
+    fn progress_throttle() -> Result<(), String> {
+        // 每 10 毫秒一个进度事件，共 1 秒，其中第 333 毫秒找到匹配
+        let mut throttle = ProgressThrottle::new(None)?;
+        let sent: Vec<u64> = (0..100u64)
+            .map(|tick| tick * 10)
+            .filter(|&ms| throttle.ready(Duration::from_millis(ms), ms == 330))
+            .collect();
+        if sent != [0, 250, 330, 580, 830] {
+            return Err(format!("默认间隔下 1 秒内发送进度事件的时刻为 {:?}，应为 [0, 250, 330, 580, 830]", sent));
+        }
+        let mut unlimited = ProgressThrottle::new(Some(0))?;
+        if !(0..10).all(|ms| unlimited.ready(Duration::from_millis(ms), false)) {
+            return Err("间隔为 0 时仍然限制了进度事件".to_string());
+        }
+        if ProgressThrottle::new(Some(DEFAULT_PROGRESS_INTERVAL_MS * 1000)).is_ok() {
+            return Err("过大的进度事件间隔没有被拒绝".to_string());
+        }
+        Ok(())
+    }
+
+    /**
+     * 计时器分辨率：阶梯时钟的分辨率检测、测量参数的调整，以及粗计时器下速度的稳定性
+     */
+    #[test]
+    fn rate_tuning() -> Result<(), String> {
+        let coarse_step = Duration::from_micros(15_625);
+        let mut coarse = SteppedClock { now: Duration::ZERO, step: coarse_step, advance: Duration::from_micros(10) };
+        let mut fine = SteppedClock { now: Duration::ZERO, step: Duration::from_micros(1), advance: Duration::from_nanos(100) };
+        let mut stalled = SteppedClock { now: Duration::ZERO, step: coarse_step, advance: Duration::ZERO };
+        let measured = [measure_resolution(&mut coarse), measure_resolution(&mut fine)];
+        if measured != [coarse_step, Duration::from_micros(1)] {
+            return Err(format!("检测到的分辨率为 {:?}，应为 15.625 ms 和 1 µs", measured));
+        }
+        if !RateTuning::for_resolution(measure_resolution(&mut stalled)).low_precision {
+            return Err("停止前进的时钟没有被标记为低精度".to_string());
+        }
+        
+        let coarse_tuning = RateTuning::for_resolution(coarse_step);
+        let fine_tuning = RateTuning::for_resolution(Duration::from_micros(1));
+        if !coarse_tuning.low_precision || fine_tuning.low_precision {
+            return Err("低精度标记与分辨率不符".to_string());
+        }
+        if coarse_tuning.window <= fine_tuning.window || coarse_tuning.alpha >= fine_tuning.alpha {
+            return Err(format!("粗计时器的测量参数没有调整: {:?} / {:?}", coarse_tuning, fine_tuning));
+        }
+        
+        // 粗计时器下按调整后的参数测量，速度误差应明显小于每次上报都取样（原来的做法）
+        let adapted = simulated_rate_error(coarse_tuning, coarse_step)?;
+        let unadapted = simulated_rate_error(RateTuning { window: Duration::ZERO, ..fine_tuning }, coarse_step)?;
+        if adapted > 0.01 || adapted * 10.0 >= unadapted {
+            return Err(format!("粗计时器下的速度误差为 {:.2}%（每次上报都取样时 {:.2}%）", adapted * 100.0, unadapted * 100.0));
+        }
+        
+        // 时钟后退时重新开始测量窗口，不产生负的速度
+        let mut estimator = RateEstimator::new(coarse_tuning);
+        estimator.update(1000, Duration::from_secs(10));
+        estimator.update(2000, Duration::from_secs(9));
+        estimator.update(3000, Duration::from_secs(9));
+        if estimator.rate().is_some() {
+            return Err("时钟后退后仍然产生了速度样本".to_string());
+        }
+        Ok(())
+    }
+
+    /**
+     * 系统时间跳变：单调时钟正常前进时让模拟的系统时间后退和前进，跳变必须被检测到，
+     * 按单调时钟计算的时长和速度不受影响；同一秒内创建的回放文件不能重名
+     */
+    #[test]
+    fn clock_jumps() -> Result<(), String> {
+        // 每次上报单调时钟前进 1 秒；系统时间在第 10 次上报时后退 1 小时、第 20 次前进 5 秒，
+        // 第 30 次只慢 0.5 秒（NTP 微调，不算跳变）
+        let start_wall = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).single().ok_or("无效的日期")?;
+        let mut monotonic = SteppedClock { now: Duration::ZERO, step: Duration::from_millis(1), advance: Duration::from_secs(1) };
+        let mut wall = start_wall;
+        let mut detector = ClockJumpDetector::new();
+        let mut estimator = RateEstimator::new(RateTuning::for_resolution(Duration::from_millis(1)));
+        let mut last = Duration::ZERO;
+        for report in 1..=40u64 {
+            let elapsed = monotonic.elapsed();
+            let shift = match report {
+                10 => chrono::Duration::hours(-1),
+                20 => chrono::Duration::seconds(5),
+                30 => chrono::Duration::milliseconds(-500),
+                _ => chrono::Duration::zero(),
+            };
+            wall += chrono::Duration::seconds(1) + shift;
+            detector.observe(elapsed, wall);
+            if elapsed < last {
+                return Err(format!("第 {} 次上报的时长 {:?} 小于上一次的 {:?}", report, elapsed, last));
+            }
+            last = elapsed;
+            estimator.update(report * 100_000, elapsed);
+        }
+        
+        let jumps: Vec<(u64, i64)> = detector.jumps().iter().map(|jump| (jump.at_ms, jump.offset_ms)).collect();
+        if jumps != [(10_000, -3_600_000), (20_000, 5_000)] {
+            return Err(format!("检测到的跳变为 {:?}，应为 10 秒时后退 1 小时和 20 秒时前进 5 秒", jumps));
+        }
+        // 直接用系统时间相减得到的时长是负的，单调时钟的时长仍然正确
+        if (wall - start_wall).num_milliseconds() >= 0 || last != Duration::from_secs(40) {
+            return Err(format!("模拟的系统时间没有后退，或单调时长 {:?} 不是 40 秒", last));
+        }
+        let rate = estimator.rate().ok_or("没有测得速度")?;
+        if (rate - 100_000.0).abs() > 1.0 {
+            return Err(format!("系统时间跳变后速度为 {:.1}，应为 100000", rate));
+        }
+        
+        // 同一秒内（或系统时间后退后）创建的回放文件不覆盖之前的文件，会话名中的时间仍可解析
+        let dir = std::env::temp_dir().join(format!("fancy-wallet-test-clock-{}", std::process::id()));
+        let result = (|| -> Result<(), String> {
+            let mut first = ReplayRecorder::create(&dir, "8", Chain::Ethereum)?;
+            first.record_found(Address::parse("0x8888888888888888888888888888888888888888")?, 1, 1);
+            let second = ReplayRecorder::create(&dir, "8", Chain::Ethereum)?;
+            if first.path() == second.path() {
+                return Err(format!("两个回放文件重名: {}", first.path().display()));
+            }
+            for path in [first.path(), second.path()] {
+                let name = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+                if timestamps::parse_session_name(name).is_none() {
+                    return Err(format!("回放文件名 {} 中的时间无法解析", name));
+                }
+            }
+            let found_at = crate::replay::read_replay(first.path())?.events.into_iter().find_map(|event| match event.kind {
+                crate::replay::ReplayEventKind::Found { found_at, .. } => found_at,
+                _ => None,
+            });
+            if found_at.as_deref().and_then(timestamps::parse_rfc3339).is_none() {
+                return Err("回放文件中的匹配事件没有写入时的系统时间".to_string());
+            }
+            Ok(())
+        })();
+        let _ = std::fs::remove_dir_all(&dir);
+        result?;
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+use chrono::{DateTime, FixedOffset, LocalResult, NaiveDateTime, Offset, SecondsFormat, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 文件名中的时间格式（UTC，以 Z 结尾）
+const FILENAME_FORMAT: &str = "%Y%m%d_%H%M%SZ";
+
+/// 旧版本文件名中的时间格式（本地时间，没有时区）
+const LEGACY_FILENAME_FORMAT: &str = "%Y%m%d_%H%M%S";
+
+/// 从文件名解析出的时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FilenameTime {
+    /// 对应的 UTC 时间
+    pub utc: DateTime<Utc>,
+    /// 旧文件名中的本地时间落在夏令时回拨的重复区间内，无法确定是哪一次（取较早的一次）
+    pub ambiguous: bool,
+}
+
+/// 钱包找到时间使用的时区（写入结果文件和命令结果的 created_at）
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TimestampZone {
+    /// UTC，以 Z 结尾（默认）
+    #[default]
+    Utc,
+    /// 本机时区，带时区偏移，例如 +08:00
+    Local,
+}
+
+impl TimestampZone {
+    /**
+     * 当前时间在该时区的 RFC 3339 文本（精确到毫秒）
+     */
+    pub fn now(self) -> String {
+        self.format(Utc::now())
+    }
+
+    /**
+     * 时间在该时区的 RFC 3339 文本（精确到毫秒；parse_rfc3339 都能解析回同一时刻）
+     */
+    pub fn format(self, time: DateTime<Utc>) -> String {
+        match self {
+            TimestampZone::Utc => to_rfc3339(time),
+            TimestampZone::Local => time.with_timezone(&chrono::Local).to_rfc3339_opts(SecondsFormat::Millis, false),
+        }
+    }
+}
+
+/**
+ * 当前时间（UTC，RFC 3339，保存到文件中的时间除钱包的找到时间外都使用这个格式）
+ */
+pub fn now_rfc3339() -> String {
+    to_rfc3339(Utc::now())
+}
+
+/**
+ * UTC 时间的 RFC 3339 文本（精确到毫秒，以 Z 结尾）
+ */
+pub fn to_rfc3339(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/**
+ * 文件名中使用的 UTC 时间，例如 20240101_120000Z（不同时区的机器生成的文件可以直接按名称排序）
+ */
+pub fn filename_stamp(time: DateTime<Utc>) -> String {
+    time.format(FILENAME_FORMAT).to_string()
+}
+
+/**
+ * 解析保存的时间：新的 UTC 值和旧版本写入的带时区偏移的本地时间都是 RFC 3339，统一转换为 UTC
+ */
+pub fn parse_rfc3339(text: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text.trim()).ok().map(|time| time.with_timezone(&Utc))
+}
+
+/**
+ * 由本地时间和该时刻可能的时区偏移换算 UTC；夏令时回拨时同一本地时间出现两次，取较早的一次
+ *
+ * @param local - 本地时间
+ * @param offsets - 该本地时间在本地时区中的偏移（chrono 的 offset_from_local_datetime 的结果）
+ * @returns 换算结果；本地时间落在夏令时拨快跳过的区间内时为空
+ */
+pub fn resolve_local(local: NaiveDateTime, offsets: LocalResult<FixedOffset>) -> Option<FilenameTime> {
+    let utc = |offset: FixedOffset| Utc.from_utc_datetime(&(local - offset));
+    match offsets {
+        LocalResult::Single(offset) => Some(FilenameTime { utc: utc(offset), ambiguous: false }),
+        LocalResult::Ambiguous(a, b) => Some(FilenameTime { utc: utc(a).min(utc(b)), ambiguous: true }),
+        LocalResult::None => None,
+    }
+}
+
+/**
+ * 解析文件名中的时间：新文件名为 UTC（以 Z 结尾），旧文件名按本机时区的本地时间解释
+ *
+ * @param stamp - 文件名中的时间部分，例如 20240101_120000Z 或 20240101_120000
+ */
+pub fn parse_filename_stamp(stamp: &str) -> Option<FilenameTime> {
+    if let Some(utc) = stamp.strip_suffix('Z') {
+        // 会话标识中的时间带毫秒（%3f），重名的回放文件带 -2 等序号，只取到秒
+        let local = NaiveDateTime::parse_from_str(utc.get(..15)?, LEGACY_FILENAME_FORMAT).ok()?;
+        return Some(FilenameTime { utc: Utc.from_utc_datetime(&local), ambiguous: false });
+    }
+    let local = NaiveDateTime::parse_from_str(stamp.get(..15)?, LEGACY_FILENAME_FORMAT).ok()?;
+    let offsets = chrono::Local
+        .offset_from_local_datetime(&local)
+        .map(|offset| offset.fix());
+    resolve_local(local, offsets)
+}
+
+/**
+ * 从会话名（<模式>_<日期>_<时间>）中取出时间部分并解析
+ */
+pub fn parse_session_name(name: &str) -> Option<FilenameTime> {
+    let mut parts = name.rsplitn(3, '_');
+    let time = parts.next()?;
+    let date = parts.next()?;
+    parse_filename_stamp(&format!("{}_{}", date, time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formatting;
+    use chrono::NaiveDate;
+
+    /// 文件名时间的解析用例：(会话名, 期望的 UTC 时间；为空表示无法解析)
+    const FILENAME_STAMP_CASES: &[(&str, Option<&str>)] = &[
+        ("8888_20240101_120000Z", Some("2024-01-01T12:00:00.000Z")),
+        ("dead_beef_20240630_235959123Z", Some("2024-06-30T23:59:59.000Z")),
+        ("8888_20240101_120000-2Z", Some("2024-01-01T12:00:00.000Z")),
+        ("burn_in_20241103_013000Z", Some("2024-11-03T01:30:00.000Z")),
+        ("8888_20241301_120000Z", None),
+        ("8888", None),
+    ];
+
+    /**
+     * 时间戳：新文件名按 UTC 解析，旧文件名按本地时间解析，夏令时回拨的重复时间取较早的一次
+     */
+    #[test]
+    fn timestamps() -> Result<(), String> {
+        for (name, expected) in FILENAME_STAMP_CASES {
+            let parsed = parse_session_name(name).map(|t| to_rfc3339(t.utc));
+            if parsed.as_deref() != *expected {
+                return Err(format!("{} 解析为 {:?}，应为 {:?}", name, parsed, expected));
+            }
+        }
+        
+        // 旧文件名没有时区，按本机时区解释
+        let legacy = parse_session_name("8888_20240101_120000").ok_or("旧文件名无法解析")?;
+        let naive = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(12, 0, 0)).ok_or("无效的日期")?;
+        let local = chrono::Local.from_local_datetime(&naive).earliest().ok_or("本地时间不存在")?;
+        if legacy.utc != local.with_timezone(&Utc) {
+            return Err(format!("旧文件名解析为 {}，应为本地时间 {}", legacy.utc, local));
+        }
+        
+        // 夏令时回拨（例如美国东部 11 月 3 日 01:30 出现两次，分别为 -04:00 和 -05:00）
+        let hour = |h: i32| FixedOffset::east_opt(h * 3600).ok_or("无效的时区偏移");
+        let repeated = NaiveDate::from_ymd_opt(2024, 11, 3).and_then(|d| d.and_hms_opt(1, 30, 0)).ok_or("无效的日期")?;
+        let resolved = resolve_local(repeated, LocalResult::Ambiguous(hour(-5)?, hour(-4)?))
+            .ok_or("重复的本地时间没有解析结果")?;
+        if to_rfc3339(resolved.utc) != "2024-11-03T05:30:00.000Z" || !resolved.ambiguous {
+            return Err(format!("重复的本地时间解析为 {:?}，应为较早的 05:30Z 并标记为不确定", resolved));
+        }
+        let single = resolve_local(repeated, LocalResult::Single(hour(-5)?)).ok_or("本地时间没有解析结果")?;
+        if to_rfc3339(single.utc) != "2024-11-03T06:30:00.000Z" || single.ambiguous {
+            return Err(format!("确定的本地时间解析为 {:?}", single));
+        }
+        // 夏令时拨快跳过的本地时间不存在
+        if resolve_local(repeated, LocalResult::None).is_some() {
+            return Err("不存在的本地时间有解析结果".to_string());
+        }
+        
+        // 新文件名往返一致，显示时按时区转换；旧版本带偏移的本地时间也能读取
+        let now = Utc::now();
+        let stamp = filename_stamp(now);
+        let parsed = parse_filename_stamp(&stamp).ok_or("新文件名无法解析")?;
+        if parsed.utc.timestamp() != now.timestamp() {
+            return Err(format!("文件名 {} 解析为 {}，应为 {}", stamp, parsed.utc, now));
+        }
+        let east8 = hour(8)?;
+        for value in ["2024-01-01T12:00:00.000Z", "2024-01-01T20:00:00+08:00", "2024-01-01T07:00:00-05:00"] {
+            let shown = formatting::format_timestamp_in(value, formatting::NumberLocale::Western, &east8);
+            if shown != "2024-01-01 20:00" {
+                return Err(format!("{} 显示为 {}，应为 2024-01-01 20:00", value, shown));
+            }
+        }
+        let chinese = formatting::format_timestamp_in("2024-01-01T12:00:00Z", formatting::NumberLocale::SimplifiedChinese, &east8);
+        if chinese != "2024年1月1日 20:00" {
+            return Err(format!("中文显示为 {}", chinese));
+        }
+        if formatting::format_timestamp("不是时间", formatting::NumberLocale::Western) != "不是时间" {
+            return Err("无法解析的时间没有原样显示".to_string());
+        }
+        Ok(())
+    }
+}
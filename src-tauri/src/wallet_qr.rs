@@ -0,0 +1,254 @@
+use crate::chain::Chain;
+use crate::hexutil::PrivKeyHex;
+use base64::Engine;
+use qrcode::{Color, EcLevel, QrCode};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+/// 二维码图片的默认最小边长（像素）
+pub const DEFAULT_QR_SIZE: u32 = 256;
+
+/// 二维码图片的最小边长下限（像素）
+pub const MIN_QR_SIZE: u32 = 64;
+
+/// 二维码图片的最小边长上限（像素）
+pub const MAX_QR_SIZE: u32 = 2048;
+
+/// 二维码四周的空白（模块数，按二维码规范至少 4 个）
+const QUIET_ZONE_MODULES: u32 = 4;
+
+/// 自动保存的地址二维码文件名后缀（<地址>_addr.png）
+pub const ADDRESS_FILE_SUFFIX: &str = "_addr.png";
+
+/// 二维码的内容
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QrKind {
+    /// 钱包地址（任何链的地址格式）
+    Address,
+    /// 十六进制私钥（需要确认，从不自动保存到磁盘）
+    PrivateKey,
+}
+
+/// 二维码的纠错等级（等级越高，图片越密，被遮挡或弄脏后越容易识别）
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QrErrorCorrection {
+    /// 约 7% 的数据可以恢复
+    Low,
+    /// 约 15%（默认）
+    #[default]
+    Medium,
+    /// 约 25%
+    Quartile,
+    /// 约 30%
+    High,
+}
+
+impl QrErrorCorrection {
+    fn level(self) -> EcLevel {
+        match self {
+            QrErrorCorrection::Low => EcLevel::L,
+            QrErrorCorrection::Medium => EcLevel::M,
+            QrErrorCorrection::Quartile => EcLevel::Q,
+            QrErrorCorrection::High => EcLevel::H,
+        }
+    }
+}
+
+/// 二维码图片的设置
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QrOptions {
+    /// 图片的最小边长（像素，默认 256，64–2048；按整数倍放大模块，实际边长可能略大）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u32>,
+    /// 纠错等级（默认 medium）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_correction: Option<QrErrorCorrection>,
+}
+
+impl QrOptions {
+    /**
+     * 校验设置
+     *
+     * @returns 图片的最小边长
+     */
+    pub fn validate(&self) -> Result<u32, String> {
+        let size = self.size.unwrap_or(DEFAULT_QR_SIZE);
+        if !(MIN_QR_SIZE..=MAX_QR_SIZE).contains(&size) {
+            return Err(format!("二维码的边长必须在 {} 到 {} 像素之间", MIN_QR_SIZE, MAX_QR_SIZE));
+        }
+        Ok(size)
+    }
+}
+
+/**
+ * 二维码的内容：地址必须是某条链的有效地址，私钥必须是有效的十六进制私钥且已确认
+ *
+ * @param kind - 二维码的内容类型
+ * @param text - 地址或私钥
+ * @param confirm_sensitive - 是否确认生成私钥的二维码
+ * @returns 编码进二维码的文本（私钥为不含 0x 的小写十六进制，离开作用域时清零）
+ */
+pub fn content(kind: QrKind, text: &str, confirm_sensitive: bool) -> Result<Zeroizing<String>, String> {
+    let text = text.trim();
+    match kind {
+        QrKind::Address => match Chain::from_address(text) {
+            Some(_) => Ok(Zeroizing::new(text.to_string())),
+            None => Err(format!("不是任何支持的链的有效地址: {}", text)),
+        },
+        QrKind::PrivateKey if !confirm_sensitive => Err("生成私钥的二维码需要确认（任何看到二维码的人都能取走资金）".to_string()),
+        QrKind::PrivateKey => {
+            let secret = PrivKeyHex::parse(text)?.secret_key();
+            Ok(Zeroizing::new(hex::encode(secret.secret_bytes())))
+        }
+    }
+}
+
+/**
+ * 渲染 PNG 格式的二维码（8 位灰度，四周留 4 个模块的空白）
+ *
+ * @param data - 二维码内容
+ * @param options - 图片设置
+ * @returns PNG 文件的字节
+ */
+pub fn render_png(data: &str, options: &QrOptions) -> Result<Vec<u8>, String> {
+    let size = options.validate()?;
+    let level = options.error_correction.unwrap_or_default().level();
+    let code = QrCode::with_error_correction_level(data.as_bytes(), level).map_err(|e| format!("无法生成二维码: {}", e))?;
+    let modules = code.width() as u32;
+    let colors = code.to_colors();
+    let total = modules + 2 * QUIET_ZONE_MODULES;
+    let scale = size.div_ceil(total);
+    let edge = total * scale;
+    let mut pixels = Zeroizing::new(vec![u8::MAX; (edge * edge) as usize]);
+    for (i, color) in colors.iter().enumerate() {
+        if *color != Color::Dark {
+            continue;
+        }
+        let (x, y) = ((i as u32 % modules + QUIET_ZONE_MODULES) * scale, (i as u32 / modules + QUIET_ZONE_MODULES) * scale);
+        for row in y..y + scale {
+            let start = (row * edge + x) as usize;
+            pixels[start..start + scale as usize].fill(0);
+        }
+    }
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, edge, edge);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| format!("无法生成二维码图片: {}", e))?;
+    writer.write_image_data(&pixels).map_err(|e| format!("无法生成二维码图片: {}", e))?;
+    writer.finish().map_err(|e| format!("无法生成二维码图片: {}", e))?;
+    Ok(png)
+}
+
+/**
+ * 渲染二维码并编码为 base64（界面用 data:image/png;base64, 显示）
+ *
+ * @param data - 二维码内容
+ * @param options - 图片设置
+ */
+pub fn png_base64(data: &str, options: &QrOptions) -> Result<String, String> {
+    Ok(base64::engine::general_purpose::STANDARD.encode(render_png(data, options)?))
+}
+
+/**
+ * 在结果文件旁保存地址的二维码（<地址>_addr.png；只保存地址，私钥的二维码从不写入磁盘）
+ *
+ * @param dir - 结果文件所在的目录
+ * @param prefix - 文件名前缀（确定性测试模式为测试水印，其余为空）
+ * @param address - 该链格式的地址
+ * @param options - 图片设置
+ * @returns 图片路径和写入的字节数
+ */
+pub fn save_address_png(dir: &Path, prefix: &str, address: &str, options: &QrOptions) -> Result<(PathBuf, u64), String> {
+    let png = render_png(address, options)?;
+    let path = dir.join(format!("{}{}{}", prefix, address, ADDRESS_FILE_SUFFIX));
+    std::fs::write(&path, &png).map_err(|e| format!("无法保存地址二维码: {}", e))?;
+    Ok((path, png.len() as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::templates::GenerationParams;
+    use crate::test_support::{TRON_ADDRESS, with_data_dir};
+    use crate::self_test::KNOWN_KEYS;
+
+    /**
+     * 二维码：各链的地址可以生成，私钥需要确认；PNG 四周留白、边长不小于请求的大小，自动保存的文件只有地址
+     */
+    #[test]
+    fn wallet_qr() -> Result<(), String> {
+        let (key, checksum) = KNOWN_KEYS[0];
+        let address = format!("0x{}", checksum);
+        for text in [address.as_str(), TRON_ADDRESS] {
+            if content(QrKind::Address, text, false)?.as_str() != text {
+                return Err(format!("地址 {} 的二维码内容不正确", text));
+            }
+        }
+        for text in ["0x1234", "hello"] {
+            if content(QrKind::Address, text, true).is_ok() {
+                return Err(format!("无效的地址 {} 没有被拒绝", text));
+            }
+        }
+        if content(QrKind::PrivateKey, key, false).is_ok() {
+            return Err("没有确认时生成了私钥的二维码".to_string());
+        }
+        if content(QrKind::PrivateKey, &format!("0x{}", key.to_uppercase()), true)?.as_str() != key {
+            return Err("私钥二维码的内容不是小写十六进制私钥".to_string());
+        }
+        
+        let decode = |png: &[u8]| -> Result<(u32, Vec<u8>), String> {
+            let mut reader = png::Decoder::new(std::io::Cursor::new(png)).read_info().map_err(|e| e.to_string())?;
+            let mut pixels = vec![0; reader.output_buffer_size()];
+            let info = reader.next_frame(&mut pixels).map_err(|e| e.to_string())?;
+            if info.width != info.height || info.color_type != png::ColorType::Grayscale {
+                return Err(format!("二维码图片为 {}x{} {:?}", info.width, info.height, info.color_type));
+            }
+            pixels.truncate(info.buffer_size());
+            Ok((info.width, pixels))
+        };
+        let mut edges = Vec::new();
+        for level in [QrErrorCorrection::Low, QrErrorCorrection::Medium, QrErrorCorrection::Quartile, QrErrorCorrection::High] {
+            let options = QrOptions { size: Some(MIN_QR_SIZE), error_correction: Some(level) };
+            let (edge, _) = decode(&render_png(&address, &options)?)?;
+            edges.push(edge);
+        }
+        if edges.windows(2).any(|pair| pair[0] > pair[1]) || edges[0] < MIN_QR_SIZE {
+            return Err(format!("各纠错等级的二维码边长为 {:?}", edges));
+        }
+        let encoded = png_base64(&address, &QrOptions::default())?;
+        let png = base64::engine::general_purpose::STANDARD.decode(&encoded).map_err(|e| e.to_string())?;
+        let (edge, pixels) = decode(&png)?;
+        let code = qrcode::QrCode::with_error_correction_level(address.as_bytes(), qrcode::EcLevel::M).map_err(|e| e.to_string())?;
+        let scale = edge / (code.width() as u32 + 8);
+        let pixel = |x: u32, y: u32| pixels[(y * edge + x) as usize];
+        if edge < DEFAULT_QR_SIZE || edge % (code.width() as u32 + 8) != 0 || pixel(0, 0) != u8::MAX || pixel(4 * scale, 4 * scale) != 0 {
+            return Err(format!("默认设置的二维码为 {} 像素，四周留白或定位图案不正确", edge));
+        }
+        for size in [MIN_QR_SIZE - 1, MAX_QR_SIZE + 1] {
+            if render_png(&address, &QrOptions { size: Some(size), error_correction: None }).is_ok() {
+                return Err(format!("边长 {} 没有被拒绝", size));
+            }
+        }
+        let params = GenerationParams {
+            pattern: "8".to_string(),
+            address_qr: Some(QrOptions { size: Some(1), error_correction: None }),
+            ..GenerationParams::default()
+        };
+        if params.validate().is_ok() {
+            return Err("模板中过小的 address_qr 没有被拒绝".to_string());
+        }
+        
+        with_data_dir("wallet-qr", |dir| {
+            let (path, bytes) = save_address_png(dir, "", &address, &QrOptions::default())?;
+            let saved = std::fs::read(&path).map_err(|e| e.to_string())?;
+            if path != dir.join(format!("{}_addr.png", address)) || saved.len() as u64 != bytes || saved != png {
+                return Err(format!("保存的地址二维码 {} 不正确", path.display()));
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,252 @@
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::cell::{Cell, RefCell};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+/// 一次搜索最多使用的工作线程数
+pub const MAX_SEARCH_WORKERS: u32 = 256;
+
+/// 异常退出时调用栈摘要中保留的帧数
+const BACKTRACE_SUMMARY_FRAMES: usize = 8;
+
+/// 调用栈摘要中省略的标准库和 panic 处理帧
+const SKIPPED_FRAME_PREFIXES: &[&str] = &["std::", "core::", "alloc::", "<std::", "<core::", "<alloc::", "rust_", "__rust"];
+
+/// 工作线程异常退出（panic）的记录
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct WorkerFailure {
+    /// 工作线程编号（从 0 开始）
+    pub worker: u32,
+    /// panic 信息
+    pub message: String,
+    /// panic 发生的位置（文件:行:列）
+    pub location: Option<String>,
+    /// 调用栈摘要（只保留本程序的帧，平台不支持时为空）
+    pub backtrace: Vec<String>,
+    /// 异常退出时会话的尝试次数
+    pub attempts: u64,
+}
+
+/// 会话的工作线程容量
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct WorkerCapacity {
+    /// 会话开始时的工作线程数
+    pub total: u32,
+    /// 仍在运行的工作线程数
+    pub alive: u32,
+    /// 异常退出的工作线程数
+    pub failed: u32,
+}
+
+impl WorkerCapacity {
+    /**
+     * 全部工作线程正常运行的容量
+     *
+     * @param total - 工作线程数
+     */
+    pub fn full(total: u32) -> Self {
+        WorkerCapacity { total, alive: total, failed: 0 }
+    }
+
+    /**
+     * 记录一个工作线程异常退出
+     */
+    pub fn record_failure(&mut self) {
+        self.alive = self.alive.saturating_sub(1);
+        self.failed += 1;
+    }
+
+    /**
+     * 是否有工作线程异常退出（搜索速度按比例下降）
+     */
+    pub fn degraded(&self) -> bool {
+        self.failed > 0
+    }
+}
+
+thread_local! {
+    /// 当前线程是否在隔离边界内运行（只为这些线程记录 panic 的位置和调用栈）
+    static ISOLATED: Cell<bool> = const { Cell::new(false) };
+    /// 当前线程最近一次 panic 的位置和调用栈
+    static LAST_PANIC: RefCell<Option<(Option<String>, Backtrace)>> = const { RefCell::new(None) };
+}
+
+/// 只安装一次 panic 钩子
+static HOOK: Once = Once::new();
+
+/**
+ * 安装 panic 钩子：隔离边界内的 panic 额外记录位置和调用栈，之后仍交给原来的钩子处理（输出到标准错误）
+ */
+fn install_hook() {
+    HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if ISOLATED.with(Cell::get) {
+                let location = info.location().map(|location| location.to_string());
+                LAST_PANIC.with(|last| *last.borrow_mut() = Some((location, Backtrace::force_capture())));
+            }
+            previous(info);
+        }));
+    });
+}
+
+/**
+ * panic 携带的信息（panic! 的参数为字符串时）
+ */
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "未知的 panic".to_string()
+    }
+}
+
+/**
+ * 调用栈摘要：取出帧的函数名，省略标准库和 panic 处理的帧
+ */
+fn summarize_backtrace(backtrace: &Backtrace) -> Vec<String> {
+    if backtrace.status() != BacktraceStatus::Captured {
+        return Vec::new();
+    }
+    backtrace
+        .to_string()
+        .lines()
+        .filter_map(|line| {
+            let (index, name) = line.trim().split_once(": ")?;
+            index.parse::<u32>().ok()?;
+            Some(name.to_string())
+        })
+        .filter(|name| !SKIPPED_FRAME_PREFIXES.iter().any(|prefix| name.starts_with(prefix)))
+        .take(BACKTRACE_SUMMARY_FRAMES)
+        .collect()
+}
+
+/**
+ * 默认的工作线程数：每个 CPU 核心一个（无法读取核心数时为 1）
+ */
+pub fn default_workers() -> u32 {
+    let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    (cores as u32).clamp(1, MAX_SEARCH_WORKERS)
+}
+
+/**
+ * 检查配置的工作线程数
+ *
+ * @param workers - 工作线程数（为空时使用默认值）
+ */
+pub fn validate(workers: Option<u32>) -> Result<(), String> {
+    match workers {
+        Some(0) => Err("工作线程数必须至少为 1".to_string()),
+        Some(workers) if workers > MAX_SEARCH_WORKERS => {
+            Err(format!("工作线程数不能超过 {}（当前为 {}）", MAX_SEARCH_WORKERS, workers))
+        }
+        _ => Ok(()),
+    }
+}
+
+/**
+ * 在隔离边界内运行工作线程：panic 不会传播到调用方，而是转换为异常退出记录
+ *
+ * @param worker - 工作线程编号
+ * @param work - 工作线程的主体
+ * @returns 主体的结果；panic 时为异常退出记录（尝试次数由调用方填写）
+ */
+pub fn run_isolated<T>(worker: u32, work: impl FnOnce() -> T) -> Result<T, WorkerFailure> {
+    install_hook();
+    let was_isolated = ISOLATED.with(|isolated| isolated.replace(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(work));
+    ISOLATED.with(|isolated| isolated.set(was_isolated));
+    result.map_err(|payload| {
+        let (location, backtrace) = LAST_PANIC
+            .with(|last| last.borrow_mut().take())
+            .map(|(location, backtrace)| (location, summarize_backtrace(&backtrace)))
+            .unwrap_or_default();
+        WorkerFailure { worker, message: panic_message(payload.as_ref()), location, backtrace, attempts: 0 }
+    })
+}
+
+#[cfg(all(test, feature = "gui"))]
+mod tests {
+    use super::*;
+    use crate::engine::{CancellationToken, SearchConfig, SearchEvent};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    /// 注入的匹配函数在第几次调用时 panic
+    const INJECTED_PANIC_AT: u64 = 500;
+
+    /**
+     * 工作线程隔离：单个工作线程时注入在第 500 次调用时 panic 的匹配函数（之前每 100 次报告一次匹配），
+     * panic 必须被隔离并通过事件报告，之前的结果保留，全部工作线程退出后以 worker_failure 停止而不是空转；
+     * 多个工作线程时只有一个异常退出，其余工作线程继续到停止条件
+     */
+    #[test]
+    fn worker_isolation() -> Result<(), String> {
+        let config = SearchConfig {
+            limits: crate::engine::GenerationLimits { max_attempts: Some(10 * INJECTED_PANIC_AT), ..Default::default() },
+            workers: Some(1),
+            ..SearchConfig::new("8")
+        };
+        let calls = AtomicU64::new(0);
+        let injected = |_: &str| {
+            let calls = calls.fetch_add(1, Ordering::Relaxed) + 1;
+            if calls == INJECTED_PANIC_AT {
+                panic!("测试注入的匹配函数异常");
+            }
+            calls.is_multiple_of(100)
+        };
+        let mut found = 0u64;
+        let mut failures = Vec::new();
+        let report = crate::engine::search_with(
+            &config,
+            &CancellationToken::new(),
+            || false,
+            injected,
+            |event| match event {
+                SearchEvent::Found(_) => found += 1,
+                SearchEvent::WorkerFailed(failure) => failures.push(failure),
+                _ => {}
+            },
+        );
+        
+        let [failure] = failures.as_slice() else {
+            return Err(format!("收到 {} 个工作线程异常退出事件，应为 1 个", failures.len()));
+        };
+        if failure.message != "测试注入的匹配函数异常" || !failure.location.as_deref().is_some_and(|location| location.contains("worker.rs")) {
+            return Err(format!("异常退出记录不正确: {:?}", failure));
+        }
+        if failure.attempts != INJECTED_PANIC_AT || report.attempts != INJECTED_PANIC_AT {
+            return Err(format!("异常退出时的尝试次数为 {} / {}，应为 {}", failure.attempts, report.attempts, INJECTED_PANIC_AT));
+        }
+        if failure.backtrace.iter().any(|frame| frame.starts_with("std::") || frame.starts_with("core::")) {
+            return Err(format!("调用栈摘要中包含标准库的帧: {:?}", failure.backtrace));
+        }
+        // 异常退出前找到的匹配保留在报告中
+        if found != 4 || report.matches != 4 || report.last_found.is_none() {
+            return Err(format!("异常退出前找到 {} 个匹配（报告中 {} 个），应为 4 个", found, report.matches));
+        }
+        let stopped = report.stop.as_ref().map(|stop| stop.reason);
+        if stopped != Some(crate::stop::StopReason::WorkerFailure) || report.workers.alive != 0 || !report.workers.degraded() || report.worker_failures.len() != 1 {
+            return Err(format!("全部工作线程退出后停止原因为 {:?}、容量为 {:?}", stopped, report.workers));
+        }
+        
+        // 不注入异常时容量完整
+        let normal = crate::engine::search_with(&config, &CancellationToken::new(), || false, |_| false, |_| {});
+        if normal.workers.degraded() || !normal.worker_failures.is_empty() || normal.attempts != 10 * INJECTED_PANIC_AT {
+            return Err(format!("正常搜索的容量为 {:?}，尝试 {} 次", normal.workers, normal.attempts));
+        }
+        
+        // 多个工作线程：只有一个线程调用到第 500 次，其余线程继续到最大尝试次数
+        calls.store(0, Ordering::Relaxed);
+        let parallel = SearchConfig { workers: Some(4), ..config.clone() };
+        let partial = crate::engine::search_with(&parallel, &CancellationToken::new(), || false, injected, |_| {});
+        let stopped = partial.stop.as_ref().map(|stop| stop.reason);
+        if partial.workers != (WorkerCapacity { total: 4, alive: 3, failed: 1 }) || stopped != Some(crate::stop::StopReason::MaxAttempts) {
+            return Err(format!("一个工作线程异常退出后容量为 {:?}、停止原因为 {:?}", partial.workers, stopped));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+use crate::engine::{RateSample, ThreadRate};
+use crate::sessions;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// 发送 worker-stats 事件的间隔（按吞吐量采样的时间计算，各工作线程的速度是这段时间内的平均值）
+pub const WORKER_STATS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 各会话的工作线程统计（保留到下一个会话开始；界面重新加载后由 get_worker_stats 读取）
+static TRACKERS: OnceLock<Mutex<Trackers>> = OnceLock::new();
+
+#[derive(Default)]
+struct Trackers {
+    /// 最近开始的会话
+    latest: Option<String>,
+    sessions: HashMap<String, SessionTracker>,
+}
+
+/// 一个会话的统计窗口：窗口开始时各工作线程的累计尝试次数，以及最近一次算出的统计
+struct SessionTracker {
+    /// 窗口开始时间（Unix 毫秒）
+    window_start_ms: i64,
+    /// 窗口开始时各工作线程的累计尝试次数（按线程序号；会话开始时为空，即都是 0）
+    window_attempts: Vec<u64>,
+    /// 最近一次算出的统计（还没有收到采样时为空）
+    latest: Option<WorkerStats>,
+}
+
+/// 各工作线程的统计（worker-stats 事件和 get_worker_stats 的返回值）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct WorkerStats {
+    /// 会话标识
+    pub session_id: String,
+    /// 统计时间（Unix 毫秒）
+    pub timestamp_ms: i64,
+    /// 速度的统计窗口（毫秒）
+    pub window_ms: u64,
+    /// 各工作线程的累计尝试次数、窗口内每秒的尝试次数和匹配数（按线程序号）
+    pub workers: Vec<ThreadRate>,
+}
+
+fn trackers() -> &'static Mutex<Trackers> {
+    TRACKERS.get_or_init(|| Mutex::new(Trackers::default()))
+}
+
+/**
+ * 按窗口开始时的计数计算各工作线程在窗口内的平均速度
+ */
+fn window_stats(session_id: &str, tracker: &SessionTracker, sample: &RateSample) -> WorkerStats {
+    let window_ms = sample.timestamp_ms.saturating_sub(tracker.window_start_ms).max(1) as u64;
+    let workers = sample
+        .threads
+        .iter()
+        .map(|thread| {
+            let start = tracker.window_attempts.get(thread.worker as usize).copied().unwrap_or(0);
+            ThreadRate { attempts_per_second: thread.attempts.saturating_sub(start) as f64 * 1000.0 / window_ms as f64, ..thread.clone() }
+        })
+        .collect();
+    WorkerStats { session_id: session_id.to_string(), timestamp_ms: sample.timestamp_ms, window_ms, workers }
+}
+
+/**
+ * 新的生成会话开始：清除已经结束的会话的统计（同时运行的其它会话保留），登记这个会话，第一个窗口从现在开始
+ *
+ * @param session_id - 新会话的标识
+ */
+pub fn start(session_id: &str) {
+    start_at(session_id, chrono::Utc::now().timestamp_millis());
+}
+
+/**
+ * 登记会话，第一个窗口从指定时间开始
+ *
+ * @param session_id - 会话标识
+ * @param timestamp_ms - 窗口开始时间（Unix 毫秒）
+ */
+pub(crate) fn start_at(session_id: &str, timestamp_ms: i64) {
+    let running: Vec<String> = sessions::list().into_iter().map(|session| session.session_id).collect();
+    if let Ok(mut trackers) = trackers().lock() {
+        trackers.sessions.retain(|id, _| running.contains(id));
+        trackers
+            .sessions
+            .insert(session_id.to_string(), SessionTracker { window_start_ms: timestamp_ms, window_attempts: Vec::new(), latest: None });
+        trackers.latest = Some(session_id.to_string());
+    }
+}
+
+/**
+ * 记录一次吞吐量采样
+ *
+ * 窗口达到 WORKER_STATS_INTERVAL 时按窗口内的平均速度算出统计、开始下一个窗口，并返回统计用于发送 worker-stats 事件；
+ * 第一个窗口结束之前 get_worker_stats 读到的是到这次采样为止的统计。
+ *
+ * @param session_id - 会话标识
+ * @param sample - 采样（只读取各工作线程的累计计数）
+ * @returns 需要发送的统计
+ */
+pub fn record(session_id: &str, sample: &RateSample) -> Option<WorkerStats> {
+    let mut trackers = trackers().lock().ok()?;
+    let tracker = trackers.sessions.get_mut(session_id)?;
+    let stats = window_stats(session_id, tracker, sample);
+    if stats.window_ms < WORKER_STATS_INTERVAL.as_millis() as u64 {
+        if tracker.latest.is_none() {
+            tracker.latest = Some(stats);
+        }
+        return None;
+    }
+    tracker.window_start_ms = sample.timestamp_ms;
+    tracker.window_attempts = sample.threads.iter().map(|thread| thread.attempts).collect();
+    tracker.latest = Some(stats.clone());
+    Some(stats)
+}
+
+/**
+ * 读取会话最近一次的工作线程统计
+ *
+ * @param session_id - 会话标识（为空时为最近开始的会话）
+ * @returns 统计；会话还没有收到第一次采样时各工作线程为空
+ */
+pub fn current(session_id: Option<&str>) -> Result<WorkerStats, String> {
+    let trackers = trackers().lock().map_err(|_| "会话状态不可用".to_string())?;
+    let Some(session_id) = session_id.map(str::to_string).or_else(|| trackers.latest.clone()) else {
+        return Err("还没有开始过生成会话".to_string());
+    };
+    let tracker = trackers.sessions.get(&session_id).ok_or_else(|| format!("会话 {} 不存在，或它的统计已在新的会话开始时清除", session_id))?;
+    Ok(tracker.latest.clone().unwrap_or_else(|| WorkerStats {
+        session_id: session_id.clone(),
+        timestamp_ms: tracker.window_start_ms,
+        window_ms: 0,
+        workers: Vec::new(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{CancellationToken, GenerationLimits, SearchConfig, SearchEvent};
+
+    /**
+     * 工作线程统计：各线程的匹配数由自己的计数记录，合计不超过报告的匹配数；worker-stats 每 5 秒（按采样时间）发送一次，
+     * 速度是整个窗口内的平均值，第一个窗口结束之前 get_worker_stats 读到的是到最近一次采样为止的统计
+     */
+    #[test]
+    fn worker_stats() -> Result<(), String> {
+        let config = SearchConfig {
+            limits: GenerationLimits { max_duration_ms: Some(1_300), ..GenerationLimits::default() },
+            workers: Some(2),
+            ..SearchConfig::new("ab/")
+        };
+        let mut samples = Vec::new();
+        let report = crate::engine::search(&config, &CancellationToken::new(), || false, |event| {
+            if let SearchEvent::RateSample(sample) = event {
+                samples.push(sample);
+            }
+        });
+        let Some(sample) = samples.first() else {
+            return Err("1.3 秒内没有吞吐量采样".to_string());
+        };
+        let thread_matches: Vec<u64> = sample.threads.iter().map(|thread| thread.matches).collect();
+        if thread_matches.iter().sum::<u64>() == 0 || thread_matches.iter().sum::<u64>() > report.matches {
+            return Err(format!("各线程的匹配数为 {:?}，报告为 {} 个", thread_matches, report.matches));
+        }
+
+        // 线程 0 每秒 1000 次，线程 1 每秒 250 次
+        let sample = |seconds: u64| RateSample {
+            timestamp_ms: seconds as i64 * 1000,
+            attempts_per_second: 1_250.0,
+            attempts: seconds * 1_250,
+            threads: vec![
+                ThreadRate { worker: 0, attempts: seconds * 1_000, attempts_per_second: 1_000.0, matches: seconds },
+                ThreadRate { worker: 1, attempts: seconds * 250, attempts_per_second: 250.0, matches: 0 },
+            ],
+        };
+        start_at("test-workers", 0);
+        let early: Vec<bool> = (1..5).map(|seconds| record("test-workers", &sample(seconds)).is_some()).collect();
+        let partial = current(None)?;
+        if early.contains(&true) || partial.window_ms != 1_000 || partial.workers.len() != 2 {
+            return Err(format!("第一个窗口结束之前发送了 {:?}，读到的窗口为 {} 毫秒", early, partial.window_ms));
+        }
+        let first = record("test-workers", &sample(5)).ok_or("5 秒的窗口结束时没有发送统计")?;
+        let skipped = record("test-workers", &sample(9));
+        let second = record("test-workers", &sample(10)).ok_or("第二个窗口结束时没有发送统计")?;
+        let rates = |stats: &WorkerStats| stats.workers.iter().map(|worker| worker.attempts_per_second).collect::<Vec<_>>();
+        if first.window_ms != 5_000 || rates(&first) != [1_000.0, 250.0] || first.workers[0].matches != 5 || skipped.is_some() || second.window_ms != 5_000 || second.workers[0].attempts != 10_000 {
+            return Err(format!("窗口统计为 {:?} / {:?}", first, second));
+        }
+        if current(Some("test-workers"))? != second || current(Some("test-missing")).is_ok() {
+            return Err("get_worker_stats 没有返回最近一次的统计，或不存在的会话没有返回错误".to_string());
+        }
+        Ok(())
+    }
+}
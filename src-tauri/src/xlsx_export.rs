@@ -0,0 +1,307 @@
+use crate::saved_wallets::{self, GeneratedWallet, JSONL_EXTENSION};
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// 导出文件的扩展名
+pub const XLSX_EXTENSION: &str = "xlsx";
+
+/// 每个工作表最多的行数（Excel 的上限，包括标题行）
+const MAX_SHEET_ROWS: usize = 1_048_576;
+
+/// 工作表名称的最大长度（Excel 的限制）
+const MAX_SHEET_NAME_CHARS: usize = 31;
+
+/// 工作表名称中不能出现的字符
+const INVALID_SHEET_NAME_CHARS: [char; 7] = ['[', ']', ':', '*', '?', '/', '\\'];
+
+/// 各列的标题和宽度（与 CSV 的列名相同）
+const COLUMNS: [(&str, f64); 6] = [("address", 46.0), ("private_key", 68.0), ("pattern", 16.0), ("chain", 10.0), ("created_at", 26.0), ("source_file", 40.0)];
+
+/// 导出的工作表
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ExportedSheet {
+    /// 工作表名称（由模式得到，去掉 Excel 不允许的字符）
+    pub name: String,
+    /// 靓号模式
+    pub pattern: String,
+    /// 导出的行数（不含标题行）
+    pub rows: usize,
+}
+
+/// Excel 导出结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct XlsxExport {
+    /// 输出文件路径
+    pub output_path: String,
+    /// 读取的结果文件数
+    pub files: usize,
+    /// 导出的行数
+    pub exported_rows: usize,
+    /// 跳过的行数（无法解析的行，以及超过 Excel 每个工作表行数上限的行）
+    pub skipped_rows: usize,
+    /// 各模式的工作表（按模式第一次出现的顺序）
+    pub worksheets: Vec<ExportedSheet>,
+}
+
+/**
+ * 会话的结果文件：有 CSV 文件时导出 CSV（同时写入两种格式时 JSON Lines 是重复的），否则导出同名的 JSON Lines 文件
+ *
+ * @param dir - FancyWallets 目录
+ * @param session_id - 会话标识
+ */
+pub fn session_sources(dir: &Path, session_id: &str) -> Result<Vec<PathBuf>, String> {
+    let csv = saved_wallets::session_file(dir, session_id)?;
+    if csv.is_file() {
+        return Ok(vec![csv]);
+    }
+    let jsonl: Vec<PathBuf> = saved_wallets::list_jsonl_files(dir)?
+        .into_iter()
+        .filter(|file| file.file_stem().and_then(|stem| stem.to_str()).and_then(saved_wallets::result_session_id) == Some(session_id))
+        .collect();
+    if jsonl.is_empty() {
+        return Err(format!("会话不存在: {}", session_id));
+    }
+    Ok(jsonl)
+}
+
+/**
+ * 检查要导出的结果文件：必须存在，并且是 CSV 或 JSON Lines 文件（加密的结果文件需要先解密）
+ *
+ * @param file - 结果文件路径
+ */
+pub fn validate_source(file: &Path) -> Result<(), String> {
+    let extension = file.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+    if extension != "csv" && extension != JSONL_EXTENSION {
+        return Err(format!("只能导出 CSV 或 JSON Lines 结果文件（加密的结果文件需要先解密）: {}", file.display()));
+    }
+    if !file.is_file() {
+        return Err(format!("结果文件不存在: {}", file.display()));
+    }
+    Ok(())
+}
+
+/**
+ * 检查输出路径：必须是 .xlsx 文件的绝对路径，不能包含 ..，不能覆盖要读取的结果文件
+ *
+ * @param output - 输出文件路径
+ * @param sources - 要读取的结果文件
+ */
+pub fn validate_output(output: &Path, sources: &[PathBuf]) -> Result<(), String> {
+    if !output.is_absolute() {
+        return Err(format!("输出路径必须是绝对路径: {}", output.display()));
+    }
+    if output.components().any(|c| c == Component::ParentDir) {
+        return Err(format!("输出路径不能包含 \"..\": {}", output.display()));
+    }
+    if !output.extension().is_some_and(|ext| ext.eq_ignore_ascii_case(XLSX_EXTENSION)) {
+        return Err(format!("输出文件的扩展名必须是 .{}: {}", XLSX_EXTENSION, output.display()));
+    }
+    if sources.iter().any(|source| source == output) {
+        return Err(format!("输出文件不能覆盖要导出的结果文件: {}", output.display()));
+    }
+    Ok(())
+}
+
+/**
+ * 由模式得到工作表名称：去掉 Excel 不允许的字符，截断到 31 个字符，与已有的名称重复时（不区分大小写）加上序号
+ *
+ * @param pattern - 靓号模式
+ * @param taken - 已使用的名称
+ */
+pub fn sheet_name(pattern: &str, taken: &[String]) -> String {
+    let cleaned: String = pattern.chars().map(|c| if INVALID_SHEET_NAME_CHARS.contains(&c) { '_' } else { c }).collect();
+    let cleaned = cleaned.trim_matches('\'').trim();
+    // History 是 Excel 保留的名称
+    let base = if cleaned.is_empty() || cleaned.eq_ignore_ascii_case("history") { "wallets" } else { cleaned };
+    let is_taken = |name: &str| taken.iter().any(|other| other.eq_ignore_ascii_case(name));
+    let truncate = |name: &str, max: usize| name.chars().take(max).collect::<String>();
+    let name = truncate(base, MAX_SHEET_NAME_CHARS);
+    if !is_taken(&name) {
+        return name;
+    }
+    (2..)
+        .map(|n| {
+            let suffix = format!(" ({})", n);
+            format!("{}{}", truncate(base, MAX_SHEET_NAME_CHARS - suffix.len()), suffix)
+        })
+        .find(|name| !is_taken(name))
+        .unwrap_or_default()
+}
+
+/**
+ * 把结果文件中的钱包导出为 Excel 文件：每个模式一个工作表，地址和私钥写成文本单元格（不会被当作数字），冻结标题行
+ *
+ * @param sources - 要读取的 CSV 或 JSON Lines 结果文件
+ * @param output - 输出文件路径（已存在时覆盖）
+ */
+pub fn export(sources: &[PathBuf], output: &Path) -> Result<XlsxExport, String> {
+    validate_output(output, sources)?;
+    let mut skipped_rows = 0;
+    let mut groups: Vec<(String, Vec<GeneratedWallet>)> = Vec::new();
+    for source in sources {
+        validate_source(source)?;
+        let (wallets, skipped) = saved_wallets::read_generated_file(source, true)?;
+        skipped_rows += skipped;
+        for wallet in wallets {
+            match groups.iter_mut().find(|(pattern, _)| *pattern == wallet.pattern) {
+                Some((_, rows)) => rows.push(wallet),
+                None => groups.push((wallet.pattern.clone(), vec![wallet])),
+            }
+        }
+    }
+    if groups.is_empty() {
+        return Err("结果文件中没有可导出的钱包".to_string());
+    }
+
+    let mut workbook = Workbook::new();
+    let header = Format::new().set_bold().set_num_format("@");
+    let text = Format::new().set_num_format("@");
+    let mut worksheets = Vec::with_capacity(groups.len());
+    let mut taken = Vec::with_capacity(groups.len());
+    for (pattern, rows) in &groups {
+        let exported = rows.len().min(MAX_SHEET_ROWS - 1);
+        skipped_rows += rows.len() - exported;
+        let name = sheet_name(pattern, &taken);
+        let sheet = workbook.add_worksheet();
+        sheet.set_name(name.as_str()).map_err(xlsx_error)?;
+        for (col, (title, width)) in COLUMNS.iter().enumerate() {
+            let col = col as u16;
+            sheet.set_column_width(col, *width).map_err(xlsx_error)?;
+            sheet.set_column_format(col, &text).map_err(xlsx_error)?;
+            sheet.write_string_with_format(0, col, *title, &header).map_err(xlsx_error)?;
+        }
+        sheet.set_freeze_panes(1, 0).map_err(xlsx_error)?;
+        for (i, wallet) in rows.iter().take(exported).enumerate() {
+            let row = i as u32 + 1;
+            let chain = wallet.chain.map(|chain| chain.as_str()).unwrap_or_default();
+            let cells = [
+                wallet.address.as_str(),
+                wallet.private_key.as_deref().unwrap_or_default(),
+                wallet.pattern.as_str(),
+                chain,
+                wallet.created_at.as_deref().unwrap_or_default(),
+                wallet.source_file.as_str(),
+            ];
+            for (col, cell) in cells.iter().enumerate() {
+                sheet.write_string_with_format(row, col as u16, *cell, &text).map_err(xlsx_error)?;
+            }
+        }
+        worksheets.push(ExportedSheet { name: name.clone(), pattern: pattern.clone(), rows: exported });
+        taken.push(name);
+    }
+
+    if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("无法创建输出目录: {}", e))?;
+    }
+    workbook.save(output).map_err(xlsx_error)?;
+    Ok(XlsxExport {
+        output_path: output.to_string_lossy().to_string(),
+        files: sources.len(),
+        exported_rows: worksheets.iter().map(|sheet| sheet.rows).sum(),
+        skipped_rows,
+        worksheets,
+    })
+}
+
+fn xlsx_error(error: XlsxError) -> String {
+    format!("无法写入 Excel 文件: {}", error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::Chain;
+    use crate::entropy::RngMode;
+    use crate::gui::{write_jsonl_row, write_wallet_row, Wallet};
+    use crate::hexutil::{Address, PrivKeyHex, SecretHex};
+    use crate::saved_wallets::CsvPrivateKeys;
+    use crate::timestamps;
+    use secp256k1::{PublicKey, Secp256k1};
+    use std::io::Write;
+    use crate::test_support::with_data_dir;
+    use crate::self_test::KNOWN_KEYS;
+
+    /**
+     * Excel 导出：每个模式一个工作表，无法解析的行计入跳过的行数，工作表名称去掉 Excel 不允许的字符并去重，
+     * 只有 JSON Lines 文件的会话也能导出，相对路径和非 .xlsx 的输出路径被拒绝
+     */
+    #[test]
+    fn xlsx_export() -> Result<(), String> {
+        let secp = Secp256k1::new();
+        let taken = vec!["Ab".to_string()];
+        let long = "f".repeat(40);
+        let names = [
+            sheet_name("a*b?", &[]),
+            sheet_name("ab", &taken),
+            sheet_name("", &[]),
+            sheet_name(&long, &[]),
+            sheet_name(&long, &[long[..31].to_string()]),
+        ];
+        let expected = ["a_b_".to_string(), "ab (2)".to_string(), "wallets".to_string(), long[..31].to_string(), format!("{} (2)", &long[..27])];
+        if names != expected {
+            return Err(format!("工作表名称为 {:?}，应为 {:?}", names, expected));
+        }
+        
+        with_data_dir("xlsx_export", |dir| {
+            let chain_dir = Chain::Ethereum.dir(dir);
+            std::fs::create_dir_all(&chain_dir).map_err(|e| e.to_string())?;
+            let wallets = KNOWN_KEYS[..3]
+                .iter()
+                .enumerate()
+                .map(|(i, (key, _))| {
+                    let secret = PrivKeyHex::parse(key)?.secret_key();
+                    Ok(Wallet {
+                        index: i as u64 + 1,
+                        address: Address::from_public_key(&PublicKey::from_secret_key(&secp, &secret)),
+                        private_key: SecretHex::from(key.to_string()),
+                        attempts: 1,
+                        duration: 0,
+                        matched_spans: Vec::new(),
+                        rng_mode: RngMode::default(),
+                        test_wallet: false,
+                        mnemonic: None,
+                        chain_address: None,
+                        matched_pattern: String::new(),
+                        created_at: timestamps::now_rfc3339(),
+                    })
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            let csv = chain_dir.join("wallets_xlsx_a.csv");
+            write_wallet_row(&csv, &wallets[0], "0", Chain::Ethereum, CsvPrivateKeys::Full)?;
+            write_wallet_row(&csv, &wallets[1], "a*b", Chain::Ethereum, CsvPrivateKeys::Full)?;
+            let mut file = std::fs::OpenOptions::new().append(true).open(&csv).map_err(|e| e.to_string())?;
+            file.write_all(b"not-an-address,1234,0\n").map_err(|e| e.to_string())?;
+            drop(file);
+            write_wallet_row(&csv, &wallets[2], "0", Chain::Ethereum, CsvPrivateKeys::Full)?;
+            let jsonl = chain_dir.join(format!("wallet_xlsx_b.{}", saved_wallets::JSONL_EXTENSION));
+            write_jsonl_row(&jsonl, &wallets[2], "0", Chain::Ethereum)?;
+            
+            let output = dir.join("export").join("wallets.xlsx");
+            let report = export(&[csv.clone(), jsonl.clone()], &output)?;
+            let sheets: Vec<(&str, usize)> = report.worksheets.iter().map(|sheet| (sheet.name.as_str(), sheet.rows)).collect();
+            if sheets != [("0", 3), ("a_b", 1)] || report.exported_rows != 4 || report.skipped_rows != 1 || report.files != 2 {
+                return Err(format!("导出结果不正确: {:?}", report));
+            }
+            let written = std::fs::read(&output).map_err(|e| e.to_string())?;
+            if !written.starts_with(b"PK\x03\x04") {
+                return Err("导出的文件不是 xlsx（zip）格式".to_string());
+            }
+            let (read, _) = saved_wallets::read_generated_file(&jsonl, false)?;
+            if read.first().and_then(|wallet| wallet.created_at.as_deref()) != Some(wallets[2].created_at.as_str()) {
+                return Err(format!("JSON Lines 记录的找到时间没有读出: {:?}", read));
+            }
+            
+            let sources = session_sources(dir, "xlsx_b")?;
+            if sources != [jsonl.clone()] || session_sources(dir, "xlsx_missing").is_ok() {
+                return Err(format!("只有 JSON Lines 文件的会话找到的文件为 {:?}", sources));
+            }
+            let invalid = [std::path::PathBuf::from("relative.xlsx"), dir.join("wallets.csv"), dir.join("export").join("..").join("x.xlsx")];
+            if invalid.iter().any(|path| export(std::slice::from_ref(&csv), path).is_ok()) || export(&[csv.with_extension("csv.enc")], &output).is_ok() {
+                return Err("无效的输出路径或加密的结果文件没有被拒绝".to_string());
+            }
+            Ok(())
+        })
+    }
+}
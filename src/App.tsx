@@ -155,10 +155,12 @@ function App() {
     setProgress({ attempts: 0, matches: 0, duration: 0 });
     
     try {
+      // 生成参数作为一个 params 对象传递，字段名与后端相同（snake_case）
       const wallet = await invoke("generate_fancy_wallet", {
-        pattern,
-        maxAttempts: 100000,  // 保留参数以保持兼容性，但后端不再使用此限制
-        savePath: savePath || null,
+        params: {
+          pattern,
+          save_path: savePath || null,
+        },
       });
       
       if (!cancelRef.current) {
@@ -167,7 +169,8 @@ function App() {
     } catch (error) {
       if (!cancelRef.current) {
         console.error("生成钱包失败:", error);
-        setResult({ error: String(error) });
+        // 后端的错误为带 kind 和 message 的对象
+        setResult({ error: (error as { message?: string })?.message ?? String(error) });
       }
     } finally {
       setIsGenerating(false);